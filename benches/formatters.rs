@@ -0,0 +1,133 @@
+//! Formatter benchmarks on multi-thousand-line artifacts.
+//!
+//! Run with `cargo bench --bench formatters`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use hotwired_mcp::ipc::messages::{
+    CommentDetail, DocArtifactListCommentsResponse, DocArtifactListSuggestionsResponse,
+    DocArtifactReadResponse, InlineComment, SuggestionDetail,
+};
+use hotwired_mcp::tools::{artifacts, compact};
+
+const LINES: usize = 5_000;
+const COMMENTS: usize = 200;
+
+fn document() -> DocArtifactReadResponse {
+    let content = (1..=LINES)
+        .map(|i| {
+            format!(
+                "Line {} of the spec, with some prose about the design 🚀.",
+                i
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let comments = (0..COMMENTS)
+        .map(|i| InlineComment {
+            id: format!("comment-{}", i),
+            comment_type: "question".to_string(),
+            status: "open".to_string(),
+            line_number: (i * LINES / COMMENTS + 1) as i64,
+            author: "reviewer".to_string(),
+            preview: "Why   is this\nhere?".to_string(),
+        })
+        .collect();
+    DocArtifactReadResponse {
+        artifact_id: "art-1".to_string(),
+        filename: "spec.md".to_string(),
+        content,
+        content_hash: "abc123".to_string(),
+        total_lines: LINES as i64,
+        returned_lines: LINES as i64,
+        offset: 0,
+        has_more: false,
+        comments: Some(comments),
+    }
+}
+
+fn comments() -> DocArtifactListCommentsResponse {
+    DocArtifactListCommentsResponse {
+        artifact_id: "art-1".to_string(),
+        offset: 0,
+        has_more: false,
+        comments: (0..COMMENTS)
+            .map(|i| CommentDetail {
+                id: format!("comment-{}", i),
+                comment_type: "issue".to_string(),
+                status: "open".to_string(),
+                selection_start: i as i64 * 40,
+                selection_end: i as i64 * 40 + 20,
+                selection_text: Some("A selection that runs past the preview limit".repeat(2)),
+                content: "This paragraph contradicts the overview.".repeat(4),
+                suggested_text: None,
+                author: "reviewer".to_string(),
+                resolved_by: None,
+                resolved_at: None,
+                resolution_note: None,
+                created_at: "2024-01-15T10:00:00Z".to_string(),
+                mentions: vec![],
+                parent_comment_id: None,
+            })
+            .collect(),
+    }
+}
+
+fn suggestions() -> DocArtifactListSuggestionsResponse {
+    let original = (0..20)
+        .map(|i| format!("Original line {}", i))
+        .collect::<Vec<_>>()
+        .join("\n");
+    DocArtifactListSuggestionsResponse {
+        artifact_id: "art-1".to_string(),
+        offset: 0,
+        has_more: false,
+        suggestions: (0..50)
+            .map(|i| SuggestionDetail {
+                id: format!("sugg-{}", i),
+                comment_id: format!("comment-{}", i),
+                artifact_id: "art-1".to_string(),
+                suggested_by: "builder".to_string(),
+                edit_type: "replace_range".to_string(),
+                start_offset: Some(0),
+                end_offset: Some(original.len() as i64),
+                original_text: Some(original.clone()),
+                suggested_text: original.replace("line 1", "row 1"),
+                rationale: Some("Consistent naming".to_string()),
+                status: "pending".to_string(),
+                accepted_by: None,
+                accepted_at: None,
+                rejection_reason: None,
+                created_at: "2024-01-15T10:00:00Z".to_string(),
+                updated_at: "2024-01-15T10:00:00Z".to_string(),
+                base_content_hash: None,
+            })
+            .collect(),
+    }
+}
+
+fn bench_read(c: &mut Criterion) {
+    let document = document();
+    c.bench_function("read/markdown", |b| {
+        b.iter(|| artifacts::format_read_response(black_box(&document)))
+    });
+    c.bench_function("read/markdown_numbered", |b| {
+        b.iter(|| artifacts::format_read_response_numbered(black_box(&document)))
+    });
+    c.bench_function("read/compact_numbered", |b| {
+        b.iter(|| compact::format_read_response_numbered(black_box(&document)))
+    });
+}
+
+fn bench_lists(c: &mut Criterion) {
+    let comments = comments();
+    let suggestions = suggestions();
+    c.bench_function("comments/markdown", |b| {
+        b.iter(|| artifacts::format_comments_response(black_box(&comments)))
+    });
+    c.bench_function("suggestions/markdown", |b| {
+        b.iter(|| artifacts::format_suggestions_response(black_box(&suggestions)))
+    });
+}
+
+criterion_group!(benches, bench_read, bench_lists);
+criterion_main!(benches);
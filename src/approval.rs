@@ -0,0 +1,205 @@
+//! Human approval of high-impact tool calls.
+//!
+//! Tools named in the config's `approvals.tools` don't run when the agent
+//! calls them. Instead the human is asked through the run's input requests
+//! (and the `approval_request` webhook), and the call goes ahead only once
+//! they approve it. `full_replace` stands for `doc_artifact_edit` calls that
+//! replace the whole document and for `restore_backup`, which does the same.
+//!
+//! An unanswered request stays open: calling the tool again with the same
+//! arguments waits on it instead of asking again.
+
+use crate::tools::text;
+use rmcp::model::JsonObject;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Gate name covering every whole-document replacement.
+pub const FULL_REPLACE: &str = "full_replace";
+
+/// Options offered to the human.
+pub const OPTIONS: [&str; 2] = ["Approve", "Deny"];
+
+/// Characters of one argument shown to the human.
+const ARGUMENT_PREVIEW_CHARS: usize = 300;
+
+/// Which tool calls wait for the human.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ApprovalConfig {
+    /// Tool names, or "full_replace"
+    pub tools: Vec<String>,
+    /// Seconds one call waits for an answer before returning (default 300)
+    pub timeout_secs: Option<u64>,
+}
+
+impl ApprovalConfig {
+    /// How long a gated call waits for the human.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(300))
+    }
+
+    /// The gate `tool` called with `arguments` falls under, if any. Dry runs
+    /// and listing backups change nothing and are never gated.
+    pub fn gate(&self, tool: &str, arguments: Option<&JsonObject>) -> Option<&str> {
+        let arg = |name: &str| arguments.and_then(|args| args.get(name));
+        if arg("dryRun") == Some(&Value::Bool(true)) {
+            return None;
+        }
+        if tool == "restore_backup" && arg("backupId").is_none_or(Value::is_null) {
+            return None;
+        }
+        if let Some(gate) = self.tools.iter().find(|gated| *gated == tool) {
+            return Some(gate);
+        }
+        let replaces = match tool {
+            "doc_artifact_edit" => arg("editType") == Some(&Value::from(FULL_REPLACE)),
+            "restore_backup" => true,
+            _ => false,
+        };
+        self.tools
+            .iter()
+            .find(|gated| replaces && *gated == FULL_REPLACE)
+            .map(String::as_str)
+    }
+}
+
+/// Whether the human's answer approves the call.
+pub fn is_approved(answer: Option<&str>) -> bool {
+    answer.is_some_and(|answer| {
+        matches!(
+            answer.trim().to_lowercase().as_str(),
+            "approve" | "approved" | "yes" | "y" | "ok"
+        )
+    })
+}
+
+/// The question asked for a gated call.
+pub fn question(tool: &str, gate: &str, role: &str) -> String {
+    if gate == tool {
+        format!("Approve {} by {}?", tool, role)
+    } else {
+        format!("Approve {} ({}) by {}?", tool, gate, role)
+    }
+}
+
+/// The call's arguments, one per line, long values shortened.
+pub fn describe(arguments: Option<&JsonObject>) -> String {
+    let Some(arguments) = arguments else {
+        return "(no arguments)".to_string();
+    };
+    arguments
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            let shown = text::prefix(&value, ARGUMENT_PREVIEW_CHARS);
+            let cut = value[shown.len()..].chars().count();
+            if cut > 0 {
+                format!(
+                    "{}: {}{} ({} more characters)",
+                    name,
+                    shown,
+                    text::ELLIPSIS,
+                    cut
+                )
+            } else {
+                format!("{}: {}", name, shown)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Approval requests still waiting for the human, by call.
+#[derive(Default)]
+pub struct PendingApprovals {
+    /// (tool, arguments as sent) -> input request ID
+    requests: Mutex<HashMap<(String, String), String>>,
+}
+
+impl PendingApprovals {
+    pub fn get(&self, call: &(String, String)) -> Option<String> {
+        self.requests.lock().unwrap().get(call).cloned()
+    }
+
+    pub fn insert(&self, call: (String, String), request_id: String) {
+        self.requests.lock().unwrap().insert(call, request_id);
+    }
+
+    pub fn remove(&self, call: &(String, String)) {
+        self.requests.lock().unwrap().remove(call);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn arguments(value: Value) -> JsonObject {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_gates() {
+        let config = ApprovalConfig {
+            tools: vec!["request_end_run".to_string(), FULL_REPLACE.to_string()],
+            timeout_secs: None,
+        };
+        let edit = |edit_type: &str, dry_run: bool| {
+            arguments(json!({"runId": "r", "editType": edit_type, "dryRun": dry_run}))
+        };
+        assert_eq!(
+            config.gate("request_end_run", None),
+            Some("request_end_run")
+        );
+        assert_eq!(
+            config.gate("doc_artifact_edit", Some(&edit("full_replace", false))),
+            Some(FULL_REPLACE)
+        );
+        assert_eq!(
+            config.gate("doc_artifact_edit", Some(&edit("full_replace", true))),
+            None
+        );
+        assert_eq!(
+            config.gate("doc_artifact_edit", Some(&edit("append", false))),
+            None
+        );
+        assert_eq!(
+            config.gate(
+                "restore_backup",
+                Some(&arguments(json!({"backupId": "b1"})))
+            ),
+            Some(FULL_REPLACE)
+        );
+        assert_eq!(
+            config.gate("restore_backup", Some(&arguments(json!({"runId": "r"})))),
+            None
+        );
+        assert_eq!(config.gate("send_message", None), None);
+    }
+
+    #[test]
+    fn test_answers() {
+        assert!(is_approved(Some(" Approve")));
+        assert!(is_approved(Some("yes")));
+        assert!(!is_approved(Some("Deny")));
+        assert!(!is_approved(Some("approve, but change the title first")));
+        assert!(!is_approved(None));
+    }
+
+    #[test]
+    fn test_describe_shortens_long_values() {
+        let described = describe(Some(&arguments(
+            json!({"newContent": "x".repeat(350), "runId": "r"}),
+        )));
+        assert!(described.contains("... (50 more characters)"));
+        assert!(described.ends_with("runId: r"));
+    }
+}
@@ -0,0 +1,274 @@
+//! Local copies of documents taken before destructive edits.
+//!
+//! A `full_replace` (or a `replace_range` over much of a document) built from
+//! a stale or truncated read can wipe out work, and not every backend keeps
+//! history to get it back from. Before such an edit the current content is
+//! saved under `backups/<run>/<artifact>/<id>.json` in the state dir (see
+//! [`crate::paths`]); `restore_backup` lists the copies and puts one back. The
+//! newest `backup_keep` copies per artifact are kept.
+
+use crate::ipc::messages::{ArtifactBackup, ArtifactBackupList, DocArtifactReadResponse, EditType};
+use crate::tools::timestamps;
+use chrono::{SecondsFormat, Utc};
+use std::fmt::Write;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Why an edit must be backed up first, if it replaces enough of the
+/// document to be. Offsets are in characters.
+pub fn backup_reason(
+    edit_type: EditType,
+    start_offset: Option<i64>,
+    end_offset: Option<i64>,
+    min_chars: usize,
+) -> Option<&'static str> {
+    match (edit_type, start_offset, end_offset) {
+        (EditType::FullReplace, _, _) => Some("Before full_replace"),
+        (EditType::ReplaceRange, Some(start), Some(end))
+            if (end - start).max(0) as usize >= min_chars =>
+        {
+            Some("Before replace_range")
+        }
+        _ => None,
+    }
+}
+
+/// A backup of `document` as it is now.
+pub fn snapshot(
+    run_id: &str,
+    document: &DocArtifactReadResponse,
+    reason: &str,
+    role: Option<&str>,
+) -> ArtifactBackup {
+    let now = Utc::now();
+    ArtifactBackup {
+        id: now.format("%Y%m%d-%H%M%S%3f").to_string(),
+        run_id: run_id.to_string(),
+        artifact_id: document.artifact_id.clone(),
+        filename: document.filename.clone(),
+        content_hash: document.content_hash.clone(),
+        chars: document.content.chars().count(),
+        reason: reason.to_string(),
+        role: role.map(String::from),
+        created_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        content: document.content.clone(),
+    }
+}
+
+/// Backups on disk, or in memory when no directory is configured.
+#[derive(Default)]
+pub struct BackupStore {
+    dir: Option<PathBuf>,
+    memory: Mutex<Vec<ArtifactBackup>>,
+}
+
+impl BackupStore {
+    /// Keep backups below `dir`.
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir: Some(dir),
+            memory: Mutex::default(),
+        }
+    }
+
+    /// Save `backup`, then drop all but the newest `keep` of its artifact.
+    /// Skipped when the newest backup already has the same content.
+    pub fn save(&self, mut backup: ArtifactBackup, keep: usize) -> io::Result<()> {
+        let existing = self.list(&backup.run_id, &backup.artifact_id)?;
+        if existing
+            .first()
+            .is_some_and(|newest| newest.content_hash == backup.content_hash)
+        {
+            return Ok(());
+        }
+        // Two backups in the same millisecond
+        if existing.iter().any(|b| b.id == backup.id) {
+            backup.id.push_str("-1");
+        }
+        let stale: Vec<String> = existing
+            .iter()
+            .skip(keep.saturating_sub(1))
+            .map(|b| b.id.clone())
+            .collect();
+        match &self.dir {
+            Some(dir) => {
+                let dir = artifact_dir(dir, &backup.run_id, &backup.artifact_id);
+                std::fs::create_dir_all(&dir)?;
+                std::fs::write(
+                    dir.join(format!("{}.json", backup.id)),
+                    serde_json::to_vec_pretty(&backup)?,
+                )?;
+                for id in stale {
+                    std::fs::remove_file(dir.join(format!("{}.json", id)))?;
+                }
+            }
+            None => {
+                let mut memory = self.memory.lock().unwrap();
+                memory.retain(|b| {
+                    !(b.run_id == backup.run_id
+                        && b.artifact_id == backup.artifact_id
+                        && stale.contains(&b.id))
+                });
+                memory.push(backup);
+            }
+        }
+        Ok(())
+    }
+
+    /// Backups of one artifact, newest first.
+    pub fn list(&self, run_id: &str, artifact_id: &str) -> io::Result<Vec<ArtifactBackup>> {
+        let mut backups = match &self.dir {
+            Some(dir) => {
+                let dir = artifact_dir(dir, run_id, artifact_id);
+                let entries = match std::fs::read_dir(&dir) {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+                    Err(e) => return Err(e),
+                };
+                let mut backups = Vec::new();
+                for entry in entries {
+                    let path = entry?.path();
+                    if path.extension().is_some_and(|ext| ext == "json") {
+                        match serde_json::from_slice(&std::fs::read(&path)?) {
+                            Ok(backup) => backups.push(backup),
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Ignoring unreadable backup {}: {}",
+                                    path.display(),
+                                    e
+                                )
+                            }
+                        }
+                    }
+                }
+                backups
+            }
+            None => self
+                .memory
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|b| b.run_id == run_id && b.artifact_id == artifact_id)
+                .cloned()
+                .collect(),
+        };
+        backups.sort_by(|a: &ArtifactBackup, b| b.id.cmp(&a.id));
+        Ok(backups)
+    }
+
+    /// One backup by id.
+    pub fn get(
+        &self,
+        run_id: &str,
+        artifact_id: &str,
+        id: &str,
+    ) -> io::Result<Option<ArtifactBackup>> {
+        Ok(self
+            .list(run_id, artifact_id)?
+            .into_iter()
+            .find(|b| b.id == id))
+    }
+}
+
+/// Lists an artifact's backups and how to restore one.
+pub fn format_backups(list: &ArtifactBackupList) -> String {
+    if list.backups.is_empty() {
+        return format!(
+            "No backups of artifact {} in run {}. Backups are taken before full_replace and large replace_range edits.",
+            list.artifact_id, list.run_id
+        );
+    }
+    let mut output = format!(
+        "## Backups of {} ({})\n\n",
+        list.backups[0].filename, list.artifact_id
+    );
+    for backup in &list.backups {
+        let _ = writeln!(
+            output,
+            "- `{}` {}, {} chars, hash `{}`: {}{}",
+            backup.id,
+            timestamps::age(&backup.created_at),
+            backup.chars,
+            backup.content_hash,
+            backup.reason,
+            backup
+                .role
+                .as_deref()
+                .map(|role| format!(" by {}", role))
+                .unwrap_or_default()
+        );
+    }
+    output.push_str("\nCall restore_backup with a `backup_id` to put that version back.\n");
+    output
+}
+
+/// Directory of one artifact's backups. Ids are made safe as file names.
+fn artifact_dir(dir: &Path, run_id: &str, artifact_id: &str) -> PathBuf {
+    let safe = |id: &str| -> String {
+        id.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+    dir.join(safe(run_id)).join(safe(artifact_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(content: &str, hash: &str) -> DocArtifactReadResponse {
+        DocArtifactReadResponse {
+            artifact_id: "spec".to_string(),
+            filename: "spec.md".to_string(),
+            content: content.to_string(),
+            content_hash: hash.to_string(),
+            total_lines: 1,
+            returned_lines: 1,
+            offset: 0,
+            has_more: false,
+            comments: None,
+        }
+    }
+
+    #[test]
+    fn test_backup_reason() {
+        let reason = |edit_type, start, end| backup_reason(edit_type, start, end, 1000);
+        assert_eq!(
+            reason(EditType::FullReplace, None, None),
+            Some("Before full_replace")
+        );
+        assert!(reason(EditType::ReplaceRange, Some(10), Some(1010)).is_some());
+        assert!(reason(EditType::ReplaceRange, Some(10), Some(1009)).is_none());
+        assert!(backup_reason(EditType::Append, None, None, 0).is_none());
+    }
+
+    #[test]
+    fn test_store_keeps_newest_and_skips_unchanged() {
+        let dir = std::env::temp_dir().join(format!("hotwired-backups-{}", std::process::id()));
+        for store in [BackupStore::default(), BackupStore::new(dir.clone())] {
+            for (i, hash) in ["h1", "h2", "h2", "h3"].into_iter().enumerate() {
+                let mut backup =
+                    snapshot("run-1", &document(hash, hash), "Before full_replace", None);
+                backup.id = format!("2024-{}", i);
+                store.save(backup, 2).unwrap();
+            }
+            let kept: Vec<String> = store
+                .list("run-1", "spec")
+                .unwrap()
+                .into_iter()
+                .map(|b| b.content)
+                .collect();
+            assert_eq!(kept, vec!["h3", "h2"]);
+            assert!(store.get("run-1", "spec", "2024-3").unwrap().is_some());
+            assert!(store.list("run-1", "other").unwrap().is_empty());
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,324 @@
+//! Bridging of MCP servers declared by a run's playbook.
+//!
+//! A protocol can list extra MCP servers the playbook relies on, e.g. a
+//! docs-search server. When an agent fetches the protocol, this server starts
+//! them over stdio, acts as their MCP client, and re-exports their tools as
+//! `<server>__<tool>`, so agents get playbook-specific tooling through the one
+//! Hotwired connection. Only the servers of the run attached last are kept;
+//! attaching another run stops them.
+
+use crate::ipc::messages::DownstreamServer;
+use rmcp::model::{CallToolRequestParam, CallToolResult, JsonObject, Tool};
+use rmcp::service::{RunningService, ServiceError};
+use rmcp::transport::IntoTransport;
+use rmcp::{RoleClient, ServiceExt};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Between a server's name and its tool's name in exported tool names.
+pub const SEPARATOR: &str = "__";
+
+/// Longest tool name MCP clients accept.
+const MAX_TOOL_NAME: usize = 64;
+
+/// Time a downstream server gets to start and list its tools.
+const START_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// The downstream servers of the attached run.
+#[derive(Default)]
+pub struct Bridge {
+    attached: tokio::sync::Mutex<Option<Attached>>,
+}
+
+struct Attached {
+    run_id: String,
+    declared: Vec<DownstreamServer>,
+    servers: Vec<Downstream>,
+}
+
+/// A connected downstream server. Dropping it closes the session and kills
+/// the process.
+struct Downstream {
+    name: String,
+    tools: Vec<Tool>,
+    service: RunningService<RoleClient, ()>,
+    _process: Option<tokio::process::Child>,
+}
+
+impl Bridge {
+    /// Bridge the servers `run_id` declares, started in `project`. Servers of
+    /// a previously attached run are stopped. Servers that fail to start are
+    /// logged and left out. Returns whether the exported tools changed.
+    pub async fn attach(&self, run_id: &str, servers: &[DownstreamServer], project: &Path) -> bool {
+        let mut attached = self.attached.lock().await;
+        if let Some(current) = &*attached {
+            if current.run_id == run_id && current.declared == servers {
+                return false;
+            }
+        }
+        let had_tools = attached.as_ref().is_some_and(Attached::has_tools);
+        // Stop the old servers before starting their replacements
+        *attached = None;
+
+        let started =
+            futures::future::join_all(servers.iter().map(|server| start(server, project))).await;
+        let mut connected = Vec::new();
+        for (server, result) in servers.iter().zip(started) {
+            match result {
+                Ok(downstream) => {
+                    tracing::info!(
+                        "Bridged MCP server '{}' for run {} ({} tools)",
+                        server.name,
+                        run_id,
+                        downstream.tools.len()
+                    );
+                    connected.push(downstream);
+                }
+                Err(e) => tracing::warn!("Failed to start MCP server '{}': {}", server.name, e),
+            }
+        }
+        let next = Attached {
+            run_id: run_id.to_string(),
+            declared: servers.to_vec(),
+            servers: connected,
+        };
+        let changed = had_tools || next.has_tools();
+        *attached = Some(next);
+        changed
+    }
+
+    /// The bridged tools, under their exported names.
+    pub async fn tools(&self) -> Vec<Tool> {
+        let attached = self.attached.lock().await;
+        let Some(attached) = &*attached else {
+            return Vec::new();
+        };
+        attached
+            .servers
+            .iter()
+            .flat_map(|server| server.tools.iter().map(|tool| exported(&server.name, tool)))
+            .filter(|tool| tool.name.len() <= MAX_TOOL_NAME)
+            .collect()
+    }
+
+    /// Forward a call of the exported tool `name`; `None` if no bridged
+    /// server provides it.
+    pub async fn call(
+        &self,
+        name: &str,
+        arguments: Option<JsonObject>,
+    ) -> Option<Result<CallToolResult, ServiceError>> {
+        let (server, tool) = name.split_once(SEPARATOR)?;
+        let peer = {
+            let attached = self.attached.lock().await;
+            let downstream = attached
+                .as_ref()?
+                .servers
+                .iter()
+                .find(|s| s.name == server && s.tools.iter().any(|t| t.name == tool))?;
+            downstream.service.peer().clone()
+        };
+        Some(
+            peer.call_tool(CallToolRequestParam {
+                name: tool.to_string().into(),
+                arguments,
+            })
+            .await,
+        )
+    }
+}
+
+impl Attached {
+    fn has_tools(&self) -> bool {
+        self.servers.iter().any(|server| !server.tools.is_empty())
+    }
+}
+
+/// `tool` of `server` as re-exported: namespaced name, description saying
+/// where it comes from.
+fn exported(server: &str, tool: &Tool) -> Tool {
+    let mut tool = tool.clone();
+    tool.name = format!("{}{}{}", server, SEPARATOR, tool.name).into();
+    tool.description = Some(
+        format!(
+            "[Playbook MCP server '{}'] {}",
+            server,
+            tool.description.as_deref().unwrap_or_default()
+        )
+        .trim_end()
+        .to_string()
+        .into(),
+    );
+    tool
+}
+
+/// Server names become part of tool names, so they are limited to the
+/// characters tool names allow and may not contain the separator.
+fn check_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && !name.contains(SEPARATOR)
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "server name '{}' must be letters, digits, '-' or '_', without '{}'",
+            name, SEPARATOR
+        ))
+    }
+}
+
+/// Spawn `server` in `project` and connect to it over its stdio.
+async fn start(server: &DownstreamServer, project: &Path) -> Result<Downstream, String> {
+    check_name(&server.name)?;
+    let mut process = tokio::process::Command::new(&server.command)
+        .args(&server.args)
+        .envs(&server.env)
+        .current_dir(project)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        // Our stdout carries the agent's MCP session; diagnostics go to stderr
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to run {}: {}", server.command, e))?;
+    let stdout = process.stdout.take().ok_or("no stdout")?;
+    let stdin = process.stdin.take().ok_or("no stdin")?;
+    let mut downstream = connect(&server.name, (stdout, stdin)).await?;
+    downstream._process = Some(process);
+    Ok(downstream)
+}
+
+/// Run the MCP handshake over `transport` and list the server's tools.
+async fn connect<T, E, A>(name: &str, transport: T) -> Result<Downstream, String>
+where
+    T: IntoTransport<RoleClient, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let handshake = async {
+        let service = ().serve(transport).await.map_err(|e| e.to_string())?;
+        let tools = service.list_all_tools().await.map_err(|e| e.to_string())?;
+        Ok::<_, String>((service, tools))
+    };
+    let (service, tools) = tokio::time::timeout(START_TIMEOUT, handshake)
+        .await
+        .map_err(|_| format!("no response within {}s", START_TIMEOUT.as_secs()))??;
+    Ok(Downstream {
+        name: name.to_string(),
+        tools,
+        service,
+        _process: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::handler::server::ServerHandler;
+    use rmcp::model::{Content, ListToolsResult, PaginatedRequestParam, ServerInfo};
+    use rmcp::service::RequestContext;
+    use rmcp::{ErrorData, RoleServer};
+    use std::sync::Arc;
+
+    /// A downstream server with one `search` tool that echoes its query.
+    struct DocsSearch;
+
+    impl ServerHandler for DocsSearch {
+        fn get_info(&self) -> ServerInfo {
+            ServerInfo {
+                capabilities: rmcp::model::ServerCapabilities::builder()
+                    .enable_tools()
+                    .build(),
+                ..Default::default()
+            }
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<PaginatedRequestParam>,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<ListToolsResult, ErrorData> {
+            Ok(ListToolsResult::with_all_items(vec![Tool::new(
+                "search",
+                "Search the docs",
+                Arc::new(JsonObject::new()),
+            )]))
+        }
+
+        async fn call_tool(
+            &self,
+            request: CallToolRequestParam,
+            _context: RequestContext<RoleServer>,
+        ) -> Result<CallToolResult, ErrorData> {
+            let query = request.arguments.unwrap_or_default()["query"].clone();
+            Ok(CallToolResult::success(vec![Content::text(format!(
+                "results for {}",
+                query
+            ))]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bridged_tools_are_namespaced_and_forwarded() {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            let server = DocsSearch.serve(tokio::io::split(server_io)).await.unwrap();
+            server.waiting().await.unwrap();
+        });
+        let downstream = connect("docs", tokio::io::split(client_io)).await.unwrap();
+        let bridge = Bridge::default();
+        *bridge.attached.lock().await = Some(Attached {
+            run_id: "run-1".to_string(),
+            declared: vec![],
+            servers: vec![downstream],
+        });
+
+        let tools = bridge.tools().await;
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "docs__search");
+        assert_eq!(
+            tools[0].description.as_deref(),
+            Some("[Playbook MCP server 'docs'] Search the docs")
+        );
+
+        let arguments = serde_json::json!({ "query": "cache" });
+        let result = bridge
+            .call("docs__search", arguments.as_object().cloned())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "results for \"cache\""
+        );
+        assert!(bridge.call("docs__missing", None).await.is_none());
+        assert!(bridge.call("get_protocol", None).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_attach_skips_servers_that_fail_to_start() {
+        let bridge = Bridge::default();
+        let servers = vec![
+            DownstreamServer {
+                name: "bad__name".to_string(),
+                command: "true".to_string(),
+                args: vec![],
+                env: Default::default(),
+            },
+            DownstreamServer {
+                name: "missing".to_string(),
+                command: "/nonexistent/mcp-server".to_string(),
+                args: vec![],
+                env: Default::default(),
+            },
+        ];
+        let project = std::env::temp_dir();
+        assert!(!bridge.attach("run-1", &servers, &project).await);
+        assert!(bridge.tools().await.is_empty());
+        // Same run and servers: nothing restarted
+        assert!(!bridge.attach("run-1", &servers, &project).await);
+    }
+}
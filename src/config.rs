@@ -0,0 +1,752 @@
+//! User configuration for the MCP server.
+//!
+//! Loaded from `~/.hotwired/config.json` (`$HOTWIRED_HOME/config.json` when set,
+//! or the path passed via `--config`).
+//! Every field is optional so a missing or partial file falls back to defaults.
+
+use crate::approval::ApprovalConfig;
+use crate::ipc::messages::{AgentCapabilities, Multiplexer, OutputFormat};
+use crate::paths::HotwiredDirs;
+use crate::policy::CommentPolicy;
+use crate::types::errors::ConfigError;
+use crate::webhook::WebhookConfig;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Server configuration read from the JSON config file.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    /// Log filter used when neither `--log-level` nor `RUST_LOG` is set
+    /// (e.g., "info", "debug", "hotwired_mcp=trace").
+    pub log_level: Option<String>,
+    /// Masking applied to request/response bodies before they are logged.
+    pub redaction: RedactionConfig,
+    /// Default rendering of tool results ("markdown", "compact", "json", or "table");
+    /// tools that return backend data also accept a per-call `format`.
+    pub output_format: OutputFormat,
+    /// Record failed run-scoped tool calls as `tool_error` events on the run.
+    pub forward_tool_errors: bool,
+    /// Seconds during which a `report_status` identical to the agent's previous
+    /// one is not re-sent (default 60; 0 sends every report).
+    pub status_dedup_secs: Option<u64>,
+    /// Seconds during which a mutating tool call byte-identical to an earlier
+    /// successful one returns that call's result instead of running again
+    /// (default 10; 0 disables).
+    pub duplicate_call_secs: Option<u64>,
+    /// When set, mutating tools first check that the run has not ended, reusing
+    /// a fetched run status for this many seconds. Unset disables the check.
+    pub run_guard_ttl_secs: Option<u64>,
+    /// Seconds a fetched protocol is served from the local cache before it is
+    /// revalidated against the run's protocol version (default 300; 0 disables).
+    pub protocol_cache_secs: Option<u64>,
+    /// Calls in flight at once when a tool runs one operation per artifact, such
+    /// as `doc_artifact_search_all` (default 4).
+    pub batch_concurrency: Option<usize>,
+    /// What identifies the agent's terminal session: "zellij", "tmux", or "tty"
+    /// (the agent's TTY and process id). Unset tries Zellij, then tmux.
+    pub multiplexer: Option<Multiplexer>,
+    /// Register the terminal session at startup and deregister it at shutdown, so the
+    /// SessionStart/SessionEnd hooks are not required (default true).
+    pub auto_register: Option<bool>,
+    /// Connect to the backend and prefetch the playbook list (and the protocol of
+    /// a restored run) in the background at startup (default true).
+    pub warm_up: Option<bool>,
+    /// Start the MCP servers a run's playbook declares and re-export their
+    /// tools (default true).
+    pub bridge_mcp_servers: Option<bool>,
+    /// Seconds between session heartbeats sent while running inside a terminal
+    /// session (default 30; 0 disables).
+    pub heartbeat_secs: Option<u64>,
+    /// Mirror the attached run's artifacts to `.hotwired/artifacts/<run>/` in the
+    /// project and sync it both ways every this many seconds (default off).
+    pub mirror_artifacts_secs: Option<u64>,
+    /// Seconds between checks for documents edited by someone else since the
+    /// agent read them; the agent is notified of each (default 30; 0 disables).
+    pub watch_artifacts_secs: Option<u64>,
+    /// Seconds `report_usage` deltas are summed locally before being sent to
+    /// the backend (default 60; 0 sends every report).
+    pub usage_flush_secs: Option<u64>,
+    /// Local backups kept per artifact of the content replaced by `full_replace`
+    /// and large `replace_range` edits, restorable with `restore_backup`
+    /// (default 20; 0 disables).
+    pub backup_keep: Option<usize>,
+    /// Characters a `replace_range` edit must replace to be backed up first
+    /// (default 2000).
+    pub backup_min_chars: Option<usize>,
+    /// Capabilities declared when this agent registers its session, so the
+    /// backend and playbooks can route handoffs.
+    pub capabilities: Option<AgentCapabilities>,
+    /// Agent CLI this server runs under ("claude", "gemini", "codex", or a custom
+    /// name; also `--agent-type`). Sent when registering and used to word tool
+    /// descriptions (default "claude").
+    pub agent_type: Option<String>,
+    /// How tool descriptions refer to each agent type's own file tools, keyed by
+    /// agent type. Overrides the built-in wording.
+    pub agent_tools: HashMap<String, AgentToolNames>,
+    /// Agent role used when a tool call omits `source`.
+    pub default_source: Option<String>,
+    /// Agent role used when a tool call omits `author`, `resolved_by`, or `created_by`.
+    /// Falls back to `default_source`.
+    pub default_author: Option<String>,
+    /// Connect even if the backend socket is not private to the current user
+    /// (also `--allow-shared-socket`).
+    pub allow_shared_socket: bool,
+    /// File holding a shared secret; when set, every IPC request is signed
+    /// with HMAC-SHA256 and a timestamp so the backend can reject forged events.
+    pub signing_key_path: Option<String>,
+    /// Backend socket path used when `--socket-path` is not given. Usually set
+    /// per project, e.g. to point a worktree at its own backend. May also be
+    /// `tcp://host:port`.
+    pub socket_path: Option<String>,
+    /// Per-project overrides, keyed by absolute project path.
+    pub projects: HashMap<String, ProjectConfig>,
+    /// Expose only read/list tools, for observer agents and demos (also `--read-only`).
+    pub read_only: bool,
+    /// Which tools are exposed to the agent.
+    pub tools: ToolFilterConfig,
+    /// URLs that selected run events (impediments, end-run requests, handoffs, ...)
+    /// are POSTed to when an agent reports them through this server.
+    pub webhooks: Vec<WebhookConfig>,
+    /// Defaults for doc_artifact_read/doc_artifact_search when the agent omits them,
+    /// and the cap on formatted tool output.
+    pub pagination: PaginationConfig,
+    /// Token buckets limiting how fast the agent can call each class of tools.
+    pub rate_limits: RateLimitConfig,
+    /// Comment actions refused to a role, checked before the backend is asked.
+    pub comment_policies: Vec<CommentPolicy>,
+    /// Tools that wait for the human to approve each call before running.
+    pub approvals: ApprovalConfig,
+}
+
+/// Read/search sizes. Each can also be set via `HOTWIRED_*` env vars
+/// (see [`Config::with_env`]).
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PaginationConfig {
+    /// Lines returned by doc_artifact_read when `limit` is omitted.
+    pub read_limit: i64,
+    /// Upper bound on doc_artifact_read `limit`.
+    pub read_max_limit: i64,
+    /// When doc_artifact_read is called without `limit`, further pages are read
+    /// and joined until the content reaches this many bytes. 0 returns one page.
+    pub read_budget_bytes: i64,
+    /// Results returned by doc_artifact_search when `max_results` is omitted.
+    pub search_max_results: i64,
+    /// Context lines around search matches when `context_lines` is omitted.
+    pub search_context_lines: i64,
+    /// Comments or suggestions listed per page when `limit` is omitted. 0 lists
+    /// them all.
+    pub list_limit: i64,
+    /// Characters of formatted tool output before it is cut and a
+    /// continuation token returned. 0 disables the cap.
+    pub max_output_chars: i64,
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            read_limit: 500,
+            read_max_limit: 2000,
+            read_budget_bytes: 100_000,
+            search_max_results: 20,
+            search_context_lines: 2,
+            list_limit: 100,
+            max_output_chars: 20_000,
+        }
+    }
+}
+
+impl PaginationConfig {
+    /// Effective read limit: the requested value (or default), capped at the max.
+    pub fn read_limit(&self, requested: Option<i64>) -> i64 {
+        requested
+            .unwrap_or(self.read_limit)
+            .clamp(1, self.read_max_limit.max(1))
+    }
+
+    /// Byte budget for assembling reads without an explicit limit; 0 means one page.
+    pub fn read_budget_bytes(&self) -> usize {
+        self.read_budget_bytes.max(0) as usize
+    }
+
+    /// Effective search result count.
+    pub fn search_max_results(&self, requested: Option<i64>) -> i64 {
+        requested.unwrap_or(self.search_max_results).max(1)
+    }
+
+    /// Effective search context lines.
+    pub fn search_context_lines(&self, requested: Option<i64>) -> i64 {
+        requested.unwrap_or(self.search_context_lines).max(0)
+    }
+
+    /// Effective page size of comment and suggestion listings; None lists them all.
+    pub fn list_limit(&self, requested: Option<i64>) -> Option<usize> {
+        match requested.unwrap_or(self.list_limit) {
+            limit if limit > 0 => Some(limit as usize),
+            _ => None,
+        }
+    }
+
+    /// Output cap in characters; 0 means unlimited.
+    pub fn max_output_chars(&self) -> usize {
+        self.max_output_chars.max(0) as usize
+    }
+}
+
+/// Rate limits per tool class. Mutating tools are those hidden in read-only
+/// mode; every other tool of this server is a read tool. Bridged playbook
+/// tools are not limited.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub read: RateLimit,
+    pub mutating: RateLimit,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            read: RateLimit {
+                per_minute: 240,
+                burst: Some(60),
+            },
+            mutating: RateLimit {
+                per_minute: 60,
+                burst: Some(20),
+            },
+        }
+    }
+}
+
+/// A token bucket: holds up to `burst` calls and refills at `per_minute`.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct RateLimit {
+    /// Calls per minute sustained; 0 disables the limit.
+    pub per_minute: u32,
+    /// Calls allowed at once after a quiet period (default `per_minute`).
+    pub burst: Option<u32>,
+}
+
+impl RateLimit {
+    /// Bucket size, at least 1.
+    pub fn burst(&self) -> u32 {
+        self.burst.unwrap_or(self.per_minute).max(1)
+    }
+}
+
+/// Tool allow/deny lists. Entries are tool names, optionally ending in `*`
+/// to match a prefix (e.g., "doc_artifact_*").
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ToolFilterConfig {
+    /// If non-empty, only matching tools are exposed.
+    pub allow: Vec<String>,
+    /// Matching tools are never exposed, even if allowed.
+    pub deny: Vec<String>,
+}
+
+impl ToolFilterConfig {
+    /// Whether the tool `name` should be exposed.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        let matches = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == pattern,
+        };
+        (self.allow.is_empty() || self.allow.iter().any(matches)) && !self.deny.iter().any(matches)
+    }
+}
+
+/// The agent's own tools for source files, as named in tool descriptions
+/// (e.g. "Use the Read tool for source files").
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct AgentToolNames {
+    /// How the agent reads a source file, e.g. "the Read tool".
+    pub read: Option<String>,
+    /// How the agent changes a source file, e.g. "the Edit/Write tools".
+    pub edit: Option<String>,
+}
+
+impl AgentToolNames {
+    /// Built-in wording for the agent CLIs Hotwired ships hooks for.
+    pub fn builtin(agent_type: &str) -> Self {
+        let (read, edit) = match agent_type {
+            "claude" => ("the Read tool", "the Edit/Write tools"),
+            "gemini" => ("the read_file tool", "the replace/write_file tools"),
+            "codex" => ("shell commands (e.g. `cat`)", "apply_patch"),
+            _ => ("your own file tools", "your own file tools"),
+        };
+        Self {
+            read: Some(read.to_string()),
+            edit: Some(edit.to_string()),
+        }
+    }
+
+    /// Reading tool wording, e.g. "the Read tool".
+    pub fn read(&self) -> &str {
+        self.read.as_deref().unwrap_or("your own file tools")
+    }
+
+    /// Editing tool wording, e.g. "the Edit/Write tools".
+    pub fn edit(&self) -> &str {
+        self.edit.as_deref().unwrap_or("your own file tools")
+    }
+}
+
+/// Settings that override the top-level config inside a specific project.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ProjectConfig {
+    pub default_source: Option<String>,
+    pub default_author: Option<String>,
+    pub socket_path: Option<String>,
+}
+
+/// Patterns masked out of logged content.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct RedactionConfig {
+    /// Apply the built-in patterns (API keys, tokens, bearer headers, emails).
+    pub builtin: bool,
+    /// Additional regular expressions to mask.
+    pub patterns: Vec<String>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            builtin: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Default config file location: ~/.hotwired/config.json (see [`HotwiredDirs`])
+    pub fn default_path() -> PathBuf {
+        HotwiredDirs::resolve().config_file()
+    }
+
+    /// Load configuration from `path`, or from the default location if `None`.
+    /// A missing file is not an error and yields the default configuration.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let path = path.map(PathBuf::from).unwrap_or_else(Self::default_path);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::from_json(&contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(ConfigError::Read(format!("{}: {}", path.display(), e))),
+        }
+    }
+
+    /// Apply environment variable overrides. `lookup` is normally `std::env::var`;
+    /// unparsable values are reported on stderr and ignored.
+    pub fn with_env(mut self, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let pagination = &mut self.pagination;
+        let overrides: [(&str, &mut i64); 7] = [
+            ("HOTWIRED_READ_LIMIT", &mut pagination.read_limit),
+            ("HOTWIRED_READ_MAX_LIMIT", &mut pagination.read_max_limit),
+            (
+                "HOTWIRED_READ_BUDGET_BYTES",
+                &mut pagination.read_budget_bytes,
+            ),
+            (
+                "HOTWIRED_SEARCH_MAX_RESULTS",
+                &mut pagination.search_max_results,
+            ),
+            (
+                "HOTWIRED_SEARCH_CONTEXT_LINES",
+                &mut pagination.search_context_lines,
+            ),
+            ("HOTWIRED_LIST_LIMIT", &mut pagination.list_limit),
+            (
+                "HOTWIRED_MAX_OUTPUT_CHARS",
+                &mut pagination.max_output_chars,
+            ),
+        ];
+        for (key, field) in overrides {
+            if let Some(value) = lookup(key) {
+                match value.trim().parse() {
+                    Ok(parsed) => *field = parsed,
+                    Err(_) => eprintln!("Ignoring invalid {}='{}'", key, value),
+                }
+            }
+        }
+        self
+    }
+
+    /// Apply the overrides of the project containing `project_dir`.
+    /// When several project paths match, the most specific (longest) one wins.
+    pub fn for_project(mut self, project_dir: &Path) -> Self {
+        let project = self
+            .projects
+            .iter()
+            .filter(|(path, _)| project_dir.starts_with(path))
+            .max_by_key(|(path, _)| path.len())
+            .map(|(_, project)| project.clone());
+
+        if let Some(project) = project {
+            self.default_source = project.default_source.or(self.default_source);
+            self.default_author = project.default_author.or(self.default_author);
+            self.socket_path = project.socket_path.or(self.socket_path);
+        }
+        self
+    }
+
+    /// Window for skipping repeated identical status reports.
+    pub fn status_dedup_window(&self) -> Duration {
+        Duration::from_secs(self.status_dedup_secs.unwrap_or(60))
+    }
+
+    /// How long a repeated mutating call is answered with the earlier result, if enabled.
+    pub fn duplicate_call_window(&self) -> Option<Duration> {
+        match self.duplicate_call_secs.unwrap_or(10) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// How long a fetched run status is trusted by the run-state guard, if enabled.
+    pub fn run_guard_ttl(&self) -> Option<Duration> {
+        self.run_guard_ttl_secs.map(Duration::from_secs)
+    }
+
+    /// How long a cached protocol is served without revalidation, or `None` if
+    /// protocol caching is disabled.
+    pub fn protocol_cache_ttl(&self) -> Option<Duration> {
+        match self.protocol_cache_secs.unwrap_or(300) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Bound on concurrent calls for run-wide artifact operations.
+    pub fn batch_concurrency(&self) -> usize {
+        self.batch_concurrency
+            .unwrap_or(crate::tools::batch::DEFAULT_CONCURRENCY)
+            .max(1)
+    }
+
+    /// Agent CLI this server runs under.
+    pub fn agent_type(&self) -> &str {
+        self.agent_type.as_deref().unwrap_or("claude")
+    }
+
+    /// File tool wording for [`Config::agent_type`]: the configured names, falling
+    /// back to the built-in ones.
+    pub fn agent_tools(&self) -> AgentToolNames {
+        let builtin = AgentToolNames::builtin(self.agent_type());
+        match self.agent_tools.get(self.agent_type()) {
+            Some(configured) => AgentToolNames {
+                read: configured.read.clone().or(builtin.read),
+                edit: configured.edit.clone().or(builtin.edit),
+            },
+            None => builtin,
+        }
+    }
+
+    /// Whether the server registers its own session at startup.
+    pub fn auto_register(&self) -> bool {
+        self.auto_register.unwrap_or(true)
+    }
+
+    /// Whether the server warms up its backend connection at startup.
+    pub fn warm_up(&self) -> bool {
+        self.warm_up.unwrap_or(true)
+    }
+
+    /// Whether playbook-declared MCP servers are bridged.
+    pub fn bridge_mcp_servers(&self) -> bool {
+        self.bridge_mcp_servers.unwrap_or(true)
+    }
+
+    /// Interval between session heartbeats, or `None` if disabled.
+    pub fn heartbeat_interval(&self) -> Option<Duration> {
+        match self.heartbeat_secs.unwrap_or(30) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Interval between artifact mirror syncs, or `None` if the mirror is off.
+    pub fn mirror_interval(&self) -> Option<Duration> {
+        match self.mirror_artifacts_secs.unwrap_or(0) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Interval between artifact change checks, or `None` if disabled.
+    pub fn watch_interval(&self) -> Option<Duration> {
+        match self.watch_artifacts_secs.unwrap_or(30) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// How long reported usage may wait before it is sent.
+    pub fn usage_flush_interval(&self) -> Duration {
+        Duration::from_secs(self.usage_flush_secs.unwrap_or(60))
+    }
+
+    /// Backups kept per artifact; 0 when backups are disabled.
+    pub fn backup_keep(&self) -> usize {
+        self.backup_keep.unwrap_or(20)
+    }
+
+    /// Smallest `replace_range` edit that is backed up first.
+    pub fn backup_min_chars(&self) -> usize {
+        self.backup_min_chars.unwrap_or(2000)
+    }
+
+    /// The request signing secret, if `signing_key_path` is set.
+    pub fn signing_key(&self) -> Result<Option<Vec<u8>>, ConfigError> {
+        let Some(path) = &self.signing_key_path else {
+            return Ok(None);
+        };
+        let key = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Read(format!("{}: {}", path, e)))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ConfigError::Parse(format!(
+                "signing key file {} is empty",
+                path
+            )));
+        }
+        Ok(Some(key.as_bytes().to_vec()))
+    }
+
+    /// Parse configuration from a JSON string.
+    pub fn from_json(json: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(json).map_err(|e| ConfigError::Parse(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = Config::from_json("{}").unwrap();
+        assert!(config.log_level.is_none());
+        assert!(config.redaction.builtin);
+        assert!(config.redaction.patterns.is_empty());
+        assert!(!config.forward_tool_errors);
+        assert_eq!(config.status_dedup_window(), Duration::from_secs(60));
+        assert_eq!(
+            config.duplicate_call_window(),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(config.heartbeat_interval(), Some(Duration::from_secs(30)));
+        assert_eq!(config.mirror_interval(), None);
+        assert_eq!(config.watch_interval(), Some(Duration::from_secs(30)));
+        assert_eq!(config.usage_flush_interval(), Duration::from_secs(60));
+        assert!(config.auto_register());
+        assert!(config.warm_up());
+        assert_eq!(config.batch_concurrency(), 4);
+        assert_eq!(config.protocol_cache_ttl(), Some(Duration::from_secs(300)));
+        assert_eq!(config.agent_type(), "claude");
+        assert_eq!(config.agent_tools().read(), "the Read tool");
+        assert_eq!(config.rate_limits.mutating.per_minute, 60);
+        let disabled = Config::from_json(r#"{"protocol_cache_secs": 0}"#).unwrap();
+        assert_eq!(disabled.protocol_cache_ttl(), None);
+    }
+
+    #[test]
+    fn test_parses_redaction_patterns() {
+        let config = Config::from_json(r#"{"redaction": {"patterns": ["ACME-\\d+"]}}"#).unwrap();
+        assert!(config.redaction.builtin);
+        assert_eq!(config.redaction.patterns, vec!["ACME-\\d+"]);
+    }
+
+    #[test]
+    fn test_parses_multiplexer() {
+        assert_eq!(Config::default().multiplexer, None);
+        let config = Config::from_json(r#"{"multiplexer": "tmux"}"#).unwrap();
+        assert_eq!(config.multiplexer, Some(Multiplexer::Tmux));
+        assert!(Config::from_json(r#"{"multiplexer": "screen"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parses_log_level() {
+        let config = Config::from_json(r#"{"log_level": "debug"}"#).unwrap();
+        assert_eq!(config.log_level.as_deref(), Some("debug"));
+    }
+
+    #[test]
+    fn test_parses_agent_capabilities() {
+        let config = Config::from_json(
+            r#"{"capabilities": {"languages": ["rust"], "can_run_tests": true, "max_context_tokens": 200000}}"#,
+        )
+        .unwrap();
+        let caps = config.capabilities.unwrap();
+        assert_eq!(caps.languages, vec!["rust"]);
+        assert!(caps.can_run_tests);
+        assert!(!caps.filesystem_access);
+        assert_eq!(caps.max_context_tokens, Some(200_000));
+    }
+
+    #[test]
+    fn test_agent_tools_merge_configured_over_builtin() {
+        let config = Config::from_json(
+            r#"{
+                "agent_type": "gemini",
+                "agent_tools": {"gemini": {"edit": "the edit tool"}, "aider": {"read": "/read"}}
+            }"#,
+        )
+        .unwrap();
+        let tools = config.agent_tools();
+        assert_eq!(tools.read(), "the read_file tool");
+        assert_eq!(tools.edit(), "the edit tool");
+
+        let custom = Config {
+            agent_type: Some("aider".into()),
+            ..config
+        };
+        assert_eq!(custom.agent_tools().read(), "/read");
+        assert_eq!(custom.agent_tools().edit(), "your own file tools");
+    }
+
+    #[test]
+    fn test_signing_key_is_read_and_trimmed() {
+        assert_eq!(Config::default().signing_key().unwrap(), None);
+
+        let path = std::env::temp_dir().join(format!("hotwired-key-{}", std::process::id()));
+        std::fs::write(&path, "s3cret\n").unwrap();
+        let config = Config {
+            signing_key_path: Some(path.to_string_lossy().into_owned()),
+            ..Config::default()
+        };
+        assert_eq!(config.signing_key().unwrap(), Some(b"s3cret".to_vec()));
+
+        std::fs::write(&path, "  \n").unwrap();
+        assert!(config.signing_key().is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(config.signing_key().is_err());
+    }
+
+    #[test]
+    fn test_project_overrides_apply_to_matching_project() {
+        let config = Config::from_json(
+            r#"{
+                "default_source": "builder",
+                "projects": {
+                    "/work/app": {"default_source": "strategist"},
+                    "/work/app/docs": {"default_author": "reviewer"}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let app = config.clone().for_project(Path::new("/work/app/src"));
+        assert_eq!(app.default_source.as_deref(), Some("strategist"));
+        assert!(app.default_author.is_none());
+
+        let docs = config.clone().for_project(Path::new("/work/app/docs"));
+        assert_eq!(docs.default_source.as_deref(), Some("builder"));
+        assert_eq!(docs.default_author.as_deref(), Some("reviewer"));
+
+        let other = config.for_project(Path::new("/work/other"));
+        assert_eq!(other.default_source.as_deref(), Some("builder"));
+    }
+
+    #[test]
+    fn test_project_socket_path_mapping() {
+        let config = Config::from_json(
+            r#"{"projects": {"/work/app-worktree": {"socket_path": "/work/app-worktree/.hotwired/hotwired.sock"}}}"#,
+        )
+        .unwrap();
+
+        let worktree = config.clone().for_project(Path::new("/work/app-worktree"));
+        assert_eq!(
+            worktree.socket_path.as_deref(),
+            Some("/work/app-worktree/.hotwired/hotwired.sock")
+        );
+
+        let main = config.for_project(Path::new("/work/app"));
+        assert!(main.socket_path.is_none());
+    }
+
+    #[test]
+    fn test_tool_filter_defaults_to_all_enabled() {
+        let config = Config::from_json("{}").unwrap();
+        assert!(config.tools.is_enabled("doc_artifact_edit"));
+        assert!(config.tools.is_enabled("request_end_run"));
+    }
+
+    #[test]
+    fn test_tool_filter_deny_with_prefix_pattern() {
+        let config =
+            Config::from_json(r#"{"tools": {"deny": ["doc_artifact_*", "request_end_run"]}}"#)
+                .unwrap();
+        assert!(!config.tools.is_enabled("doc_artifact_edit"));
+        assert!(!config.tools.is_enabled("request_end_run"));
+        assert!(config.tools.is_enabled("report_status"));
+    }
+
+    #[test]
+    fn test_tool_filter_allow_list_then_deny() {
+        let config = Config::from_json(
+            r#"{"tools": {"allow": ["ping", "doc_artifact_*"], "deny": ["doc_artifact_edit"]}}"#,
+        )
+        .unwrap();
+        assert!(config.tools.is_enabled("ping"));
+        assert!(config.tools.is_enabled("doc_artifact_read"));
+        assert!(!config.tools.is_enabled("doc_artifact_edit"));
+        assert!(!config.tools.is_enabled("handoff"));
+    }
+
+    #[test]
+    fn test_pagination_defaults_and_clamping() {
+        let pagination = PaginationConfig::default();
+        assert_eq!(pagination.read_limit(None), 500);
+        assert_eq!(pagination.read_limit(Some(100)), 100);
+        assert_eq!(pagination.read_limit(Some(10_000)), 2000);
+        assert_eq!(pagination.read_budget_bytes(), 100_000);
+        assert_eq!(pagination.search_max_results(None), 20);
+        assert_eq!(pagination.search_context_lines(None), 2);
+        assert_eq!(pagination.list_limit(None), Some(100));
+        assert_eq!(pagination.list_limit(Some(10)), Some(10));
+        assert_eq!(pagination.max_output_chars(), 20_000);
+
+        let unlimited = PaginationConfig {
+            max_output_chars: -1,
+            list_limit: 0,
+            ..PaginationConfig::default()
+        };
+        assert_eq!(unlimited.max_output_chars(), 0);
+        assert_eq!(unlimited.list_limit(None), None);
+    }
+
+    #[test]
+    fn test_pagination_from_config_and_env() {
+        let config =
+            Config::from_json(r#"{"pagination": {"read_limit": 5000, "read_max_limit": 20000}}"#)
+                .unwrap()
+                .with_env(|key| match key {
+                    "HOTWIRED_SEARCH_MAX_RESULTS" => Some("100".to_string()),
+                    "HOTWIRED_READ_MAX_LIMIT" => Some("not-a-number".to_string()),
+                    _ => None,
+                });
+        assert_eq!(config.pagination.read_limit(None), 5000);
+        assert_eq!(config.pagination.read_limit(Some(15_000)), 15_000);
+        assert_eq!(config.pagination.search_max_results(None), 100);
+        assert_eq!(config.pagination.search_context_lines(None), 2);
+    }
+
+    #[test]
+    fn test_invalid_json_returns_parse_error() {
+        let result = Config::from_json("{not json");
+        assert!(matches!(result, Err(ConfigError::Parse(_))));
+    }
+
+    #[test]
+    fn test_missing_file_returns_default() {
+        let config = Config::load(Some(Path::new("/nonexistent/hotwired/config.json"))).unwrap();
+        assert!(config.log_level.is_none());
+    }
+}
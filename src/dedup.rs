@@ -0,0 +1,126 @@
+//! Suppression of repeated state-changing tool calls.
+//!
+//! An agent whose context was truncated often makes the call it just made
+//! again: a second comment, a second handoff, the same edit applied twice.
+//! A mutating call with exactly the same arguments as a recent successful one
+//! gets that call's result back, marked as deduplicated, and is not run.
+//! Failed and refused calls, which tools flag with `is_error`, are not
+//! remembered, so a retry after a transient error or a role switch runs.
+
+use rmcp::model::{CallToolResult, Content, JsonObject};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Recent successful mutating calls and their results.
+#[derive(Default)]
+pub struct RecentCalls {
+    /// (tool, arguments as sent) -> (result, when the call was made)
+    calls: Mutex<HashMap<(String, String), (CallToolResult, Instant)>>,
+}
+
+impl RecentCalls {
+    /// Identifies a call by its tool and exact arguments.
+    pub fn key(tool: &str, arguments: Option<&JsonObject>) -> (String, String) {
+        let arguments = arguments
+            .map(|args| serde_json::to_string(args).unwrap_or_default())
+            .unwrap_or_default();
+        (tool.to_string(), arguments)
+    }
+
+    /// The earlier result of the call `key`, if it was made less than
+    /// `window` before `now`, with a note saying it was not run again.
+    pub fn replay(
+        &self,
+        key: &(String, String),
+        window: Duration,
+        now: Instant,
+    ) -> Option<CallToolResult> {
+        let calls = self.calls.lock().unwrap();
+        let (result, at) = calls.get(key)?;
+        let age = now.saturating_duration_since(*at);
+        if age >= window {
+            return None;
+        }
+        let mut replayed = result.clone();
+        replayed.content.insert(
+            0,
+            Content::text(format!(
+                "Deduplicated: an identical {} call was made {}s ago, so it was not run again. \
+                Its result follows. Change an argument if you meant to repeat it.",
+                key.0,
+                age.as_secs()
+            )),
+        );
+        Some(replayed)
+    }
+
+    /// Remember the result of a call if it succeeded, forgetting calls older
+    /// than `window`.
+    pub fn record(
+        &self,
+        key: (String, String),
+        result: &CallToolResult,
+        window: Duration,
+        now: Instant,
+    ) {
+        if result.is_error == Some(true) {
+            return;
+        }
+        let mut calls = self.calls.lock().unwrap();
+        calls.retain(|_, (_, at)| now.saturating_duration_since(*at) < window);
+        calls.insert(key, (result.clone(), now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(json: serde_json::Value) -> JsonObject {
+        json.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_identical_calls_replay_within_the_window() {
+        let calls = RecentCalls::default();
+        let window = Duration::from_secs(10);
+        let start = Instant::now();
+        let comment = args(serde_json::json!({"runId": "r1", "content": "Define TTL"}));
+        let key = RecentCalls::key("doc_artifact_add_comment", Some(&comment));
+        let result = CallToolResult::success(vec![Content::text("✓ Comment added (ID: c-1)")]);
+        calls.record(key.clone(), &result, window, start);
+
+        let replayed = calls
+            .replay(&key, window, start + Duration::from_secs(3))
+            .unwrap();
+        let note = replayed.content[0].as_text().unwrap().text.clone();
+        assert!(note.starts_with(
+            "Deduplicated: an identical doc_artifact_add_comment call was made 3s ago"
+        ));
+        assert_eq!(replayed.content[1], result.content[0]);
+
+        assert!(calls
+            .replay(&key, window, start + Duration::from_secs(10))
+            .is_none());
+        let other = args(serde_json::json!({"runId": "r1", "content": "Define TTL."}));
+        let other_key = RecentCalls::key("doc_artifact_add_comment", Some(&other));
+        assert!(calls.replay(&other_key, window, start).is_none());
+    }
+
+    #[test]
+    fn test_failed_calls_are_not_remembered() {
+        let calls = RecentCalls::default();
+        let window = Duration::from_secs(10);
+        let now = Instant::now();
+        let key = RecentCalls::key("handoff", None);
+        let failed = CallToolResult::error(vec![Content::text(
+            "Failed to record handoff: Not connected",
+        )]);
+        calls.record(key.clone(), &failed, window, now);
+        assert!(calls.replay(&key, window, now).is_none());
+        let error = CallToolResult::error(vec![Content::text("Rate limited")]);
+        calls.record(key.clone(), &error, window, now);
+        assert!(calls.replay(&key, window, now).is_none());
+    }
+}
@@ -0,0 +1,179 @@
+//! Optional backend features and the tools that need them.
+//!
+//! At startup the server asks the backend which optional features it
+//! supports (`/api/capabilities`) and drops the tools of those it lacks, so
+//! an older Hotwired app shows a clean tool list instead of tools that always
+//! fail. A backend that predates the endpoint is assumed to have the features
+//! that shipped before it; one that can't be reached keeps every tool.
+
+use crate::ipc::messages::{GetBackendCapabilitiesRequest, GetBackendCapabilitiesResponse};
+use crate::ipc::traits::IpcClient;
+use crate::types::errors::IpcError;
+
+/// An optional backend feature.
+pub struct Feature {
+    /// Name in the backend's `features` list
+    pub name: &'static str,
+    /// Tools that fail without it
+    pub tools: &'static [&'static str],
+    /// Whether backends without `/api/capabilities` have it
+    pub predates_discovery: bool,
+}
+
+pub const FEATURES: &[Feature] = &[
+    Feature {
+        name: "suggestions",
+        tools: &[
+            "doc_artifact_suggest_edit",
+            "doc_artifact_accept_suggestion",
+            "doc_artifact_reject_suggestion",
+            "doc_artifact_list_suggestions",
+            "sweep_stale_suggestions",
+        ],
+        predates_discovery: true,
+    },
+    Feature {
+        name: "notifications",
+        tools: &["get_notifications", "ack_notification"],
+        predates_discovery: true,
+    },
+    Feature {
+        name: "protocol_steps",
+        tools: &["protocol_steps_list", "protocol_step_complete"],
+        predates_discovery: true,
+    },
+    Feature {
+        name: "usage",
+        tools: &["report_usage", "get_run_usage"],
+        predates_discovery: true,
+    },
+    Feature {
+        name: "edit_history",
+        tools: &["doc_artifact_changelog"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "impediment_resolution",
+        tools: &["get_impediment_resolution"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "artifact_tags",
+        tools: &["doc_artifact_tag"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "summaries",
+        tools: &["post_summary", "get_latest_summary"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "run_context",
+        tools: &["set_run_context", "get_run_context"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "completed_tasks",
+        tools: &["list_completed_tasks"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "impediment_templates",
+        tools: &["list_impediment_templates"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "message_deliveries",
+        tools: &["get_message_deliveries"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "message_acks",
+        tools: &["ack_message"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "run_config",
+        tools: &["get_run_config"],
+        predates_discovery: false,
+    },
+    Feature {
+        name: "read_receipts",
+        tools: &["get_artifact_readers"],
+        predates_discovery: false,
+    },
+];
+
+/// The backend's feature list, or `None` if it predates discovery (it
+/// answered, but not with a feature list).
+pub async fn discover<C: IpcClient>(client: &C) -> Result<Option<Vec<String>>, IpcError> {
+    match client
+        .request::<_, GetBackendCapabilitiesResponse>(
+            "/api/capabilities",
+            &GetBackendCapabilitiesRequest {},
+        )
+        .await
+    {
+        Ok(response) => {
+            tracing::info!(
+                "Backend {} supports: {}",
+                response.app_version,
+                response.features.join(", ")
+            );
+            Ok(Some(response.features))
+        }
+        Err(IpcError::RequestFailed(e)) => {
+            tracing::info!("Backend predates capability discovery ({})", e);
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Tools the backend can't serve, given its feature list (`None` for a
+/// backend that predates discovery).
+pub fn unsupported_tools(features: Option<&[String]>) -> Vec<&'static str> {
+    FEATURES
+        .iter()
+        .filter(|feature| match features {
+            Some(features) => !features.iter().any(|f| f == feature.name),
+            None => !feature.predates_discovery,
+        })
+        .flat_map(|feature| feature.tools.iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsupported_tools() {
+        let features = vec!["suggestions".to_string(), "usage".to_string()];
+        let hidden = unsupported_tools(Some(&features));
+        assert!(hidden.contains(&"get_notifications"));
+        assert!(hidden.contains(&"get_impediment_resolution"));
+        assert!(!hidden.contains(&"doc_artifact_suggest_edit"));
+        assert!(!hidden.contains(&"report_usage"));
+
+        let legacy = unsupported_tools(None);
+        assert_eq!(
+            legacy,
+            vec![
+                "doc_artifact_changelog",
+                "get_impediment_resolution",
+                "doc_artifact_tag",
+                "post_summary",
+                "get_latest_summary",
+                "set_run_context",
+                "get_run_context",
+                "list_completed_tasks",
+                "list_impediment_templates",
+                "get_message_deliveries",
+                "ack_message",
+                "get_run_config",
+                "get_artifact_readers"
+            ]
+        );
+    }
+}
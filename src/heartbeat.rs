@@ -0,0 +1,89 @@
+//! Session heartbeat: keeps the backend's view of this session fresh so that
+//! sessions whose agent crashed (and never ran the SessionEnd hook) go stale
+//! and can be purged.
+
+use crate::ipc::messages::{SessionHeartbeatRequest, SessionHeartbeatResponse};
+use crate::ipc::traits::IpcClient;
+use crate::periodic;
+use crate::types::errors::IpcError;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Tell the backend that `session_name` is still alive.
+pub async fn send<C: IpcClient>(
+    client: &C,
+    session_name: &str,
+    project_dir: &str,
+) -> Result<bool, IpcError> {
+    let request = SessionHeartbeatRequest {
+        session_name: session_name.to_string(),
+        project_dir: project_dir.to_string(),
+        pid: std::process::id(),
+        sent_at: chrono::Utc::now().timestamp(),
+    };
+    let response: SessionHeartbeatResponse =
+        client.request("/api/sessions/heartbeat", &request).await?;
+    Ok(response.success)
+}
+
+/// Send a heartbeat now and then every `interval` until the task is aborted.
+/// Failures are logged once per outage; the backend may simply not be running yet.
+pub fn spawn<C: IpcClient + 'static>(
+    client: Arc<C>,
+    session_name: String,
+    project_dir: String,
+    interval: Duration,
+) -> JoinHandle<()> {
+    let name = format!("Session heartbeat for {}", session_name);
+    periodic::spawn(name, interval, move || {
+        let client = client.clone();
+        let session_name = session_name.clone();
+        let project_dir = project_dir.clone();
+        async move { send(client.as_ref(), &session_name, &project_dir).await }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::mock::MockIpcClient;
+
+    #[tokio::test]
+    async fn test_send_includes_session_and_project() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/sessions/heartbeat",
+            SessionHeartbeatResponse { success: true },
+        );
+
+        assert!(send(&mock, "hw-1", "/path/to/project").await.unwrap());
+
+        let requests = mock.requests_to("/api/sessions/heartbeat");
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("\"sessionName\":\"hw-1\""));
+        assert!(requests[0].contains("\"projectDir\":\"/path/to/project\""));
+        assert!(requests[0].contains("\"sentAt\":"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_spawn_repeats_every_interval() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/sessions/heartbeat",
+            SessionHeartbeatResponse { success: true },
+        );
+
+        let handle = spawn(
+            Arc::new(mock.clone()),
+            "hw-1".to_string(),
+            "/path".to_string(),
+            Duration::from_secs(30),
+        );
+        tokio::time::sleep(Duration::from_secs(65)).await;
+        handle.abort();
+
+        // Immediately, then at 30s and 60s
+        assert_eq!(mock.requests_to("/api/sessions/heartbeat").len(), 3);
+    }
+}
@@ -1,30 +1,75 @@
 use crate::ipc::traits::IpcClient;
+use crate::ipc::wire::{self, WIRE_VERSION};
+use crate::paths::HotwiredDirs;
+use crate::redact::Redactor;
 use crate::types::errors::IpcError;
 use async_trait::async_trait;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::UnixStream;
+use chrono::{DateTime, Utc};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpStream, UnixStream};
 
 /// Unix socket client for communicating with Hotwired backend.
 /// This is the primary IPC mechanism, communicating with hotwired-core's SocketServer.
+/// A `tcp://host:port` socket path speaks the same line protocol over TCP, for
+/// a backend running in a container or on another machine.
 pub struct UnixSocketClient {
+    /// Socket path, or `tcp://host:port`
     socket_path: String,
-    /// Auth token for request validation (read from ~/.hotwired/auth_token)
+    /// Auth token for request validation (read from ~/.hotwired/auth_token, see [`HotwiredDirs`])
     auth_token: Option<String>,
+    /// Per-session token issued at registration; lets the backend verify that a
+    /// request's `source` belongs to this session
+    session_token: Option<String>,
+    /// Skip the socket ownership and permission checks (shared-machine setups)
+    allow_shared_socket: bool,
+    /// Shared secret for HMAC request signing, if enabled
+    signing_key: Option<Vec<u8>>,
+    /// Masks secrets in request/response bodies before they are logged
+    redactor: Redactor,
+    /// Connection opened by `warm_up`, used by the next request
+    spare: std::sync::Mutex<Option<Box<dyn Connection>>>,
+    /// When the backend last answered, for `ping`
+    last_contact: std::sync::Mutex<Option<DateTime<Utc>>>,
 }
 
-/// Request format for socket protocol (matches hotwired-core/src/socket/mod.rs)
+/// A stream to the backend: a Unix socket, or TCP for `tcp://` backends.
+trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+/// The `host:port` of a `tcp://host:port` backend, or `None` for a socket path.
+pub fn tcp_address(backend: &str) -> Option<&str> {
+    backend
+        .strip_prefix("tcp://")
+        .map(|address| address.trim_end_matches('/'))
+}
+
+/// Request format for socket protocol (matches hotwired-core/src/socket/mod.rs).
+/// Borrows everything, so large params are serialized straight into the request
+/// line without intermediate copies.
 #[derive(Debug, serde::Serialize)]
-struct SocketRequest {
+struct SocketRequest<'a, P> {
+    /// Wire version of this request (see [`wire`])
+    v: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    id: Option<String>,
-    method: String,
-    params: serde_json::Value,
+    id: Option<&'a str>,
+    method: &'a str,
+    params: &'a P,
     /// Auth token for request validation
     #[serde(skip_serializing_if = "Option::is_none")]
-    token: Option<String>,
+    token: Option<&'a str>,
+    /// Session token from registration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_token: Option<&'a str>,
+    /// Unix time (seconds) the request was signed, when signing is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<i64>,
+    /// Hex HMAC-SHA256 over `timestamp`, `method`, and `params` (see [`sign`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
 }
 
-/// Response format from socket server
+/// Response format from socket server, after [`wire::upgrade`]
 #[derive(Debug, serde::Deserialize)]
 struct SocketResponse {
     /// Request ID for correlation (protocol field, may not be used in simple request/response)
@@ -35,45 +80,80 @@ struct SocketResponse {
     #[serde(default)]
     data: Option<serde_json::Value>,
     #[serde(default)]
-    error: Option<String>,
+    error: Option<WireError>,
+}
+
+/// A failed request, as reported since wire version 2.
+#[derive(Debug, serde::Deserialize)]
+struct WireError {
+    /// Machine-readable reason, "unknown" for older backends
+    code: String,
+    message: String,
 }
 
 impl UnixSocketClient {
     /// Create a new Unix socket client.
-    /// Default socket path: ~/.hotwired/hotwired.sock
+    /// Default socket path: ~/.hotwired/hotwired.sock (see [`HotwiredDirs`])
     /// NOTE: We intentionally do NOT read HOTWIRED_SOCKET_PATH env var here.
-    /// Socket path override should only come from CLI argument to prevent
-    /// worktree environments from accidentally connecting to wrong backend.
+    /// Socket path override should only come from the CLI argument or an explicit
+    /// config entry to prevent worktree environments from accidentally connecting
+    /// to the wrong backend.
     pub fn new(socket_path: Option<String>) -> Self {
-        let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".into());
-        let hotwired_dir = format!("{}/.hotwired", home);
-
-        let path = socket_path.unwrap_or_else(|| format!("{}/hotwired.sock", hotwired_dir));
+        let dirs = HotwiredDirs::resolve();
+        let path = socket_path.unwrap_or_else(|| dirs.socket().to_string_lossy().into_owned());
 
         // Read auth token from file (generated by Tauri app on startup)
-        let token_path = format!("{}/auth_token", hotwired_dir);
-        let auth_token = std::fs::read_to_string(&token_path).ok();
+        let auth_token = std::fs::read_to_string(dirs.auth_token()).ok();
 
         Self {
             socket_path: path,
             auth_token,
+            session_token: None,
+            allow_shared_socket: false,
+            signing_key: None,
+            redactor: Redactor::default(),
+            spare: std::sync::Mutex::new(None),
+            last_contact: std::sync::Mutex::new(None),
         }
     }
 
+    /// Path of the backend socket this client connects to (or its `tcp://` address).
+    pub fn socket_path(&self) -> &str {
+        &self.socket_path
+    }
+
+    /// Attach the session token issued by `register_session` to every request.
+    pub fn with_session_token(mut self, token: Option<String>) -> Self {
+        self.session_token = token;
+        self
+    }
+
+    /// Connect even if the socket is not private to the current user.
+    pub fn with_allow_shared_socket(mut self, allow: bool) -> Self {
+        self.allow_shared_socket = allow;
+        self
+    }
+
+    /// Sign every request with HMAC-SHA256 using this shared secret, so the
+    /// backend can reject payloads from processes that do not know it.
+    pub fn with_signing_key(mut self, key: Option<Vec<u8>>) -> Self {
+        self.signing_key = key;
+        self
+    }
+
+    /// Use a custom redactor (e.g., with patterns from the config file) for logged bodies.
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
     /// Register a Claude session with the Hotwired backend.
     /// Called by the SessionStart hook from the Claude Code plugin.
     pub async fn register_session(
         &self,
-        session_name: &str,
-        project_dir: &str,
-    ) -> Result<bool, IpcError> {
-        let request = serde_json::json!({
-            "sessionName": session_name,
-            "projectDir": project_dir,
-        });
-        let response: crate::ipc::messages::RegisterSessionResponse =
-            self.send_request("register_session", &request).await?;
-        Ok(response.success)
+        request: &crate::ipc::messages::RegisterSessionRequest,
+    ) -> Result<crate::ipc::messages::RegisterSessionResponse, IpcError> {
+        self.send_request("register_session", request).await
     }
 
     /// Deregister a Claude session from the Hotwired backend.
@@ -87,22 +167,110 @@ impl UnixSocketClient {
         Ok(response.success)
     }
 
+    /// Open a connection now, so the first request skips the connect and
+    /// socket checks.
+    pub async fn open_spare(&self) -> Result<(), IpcError> {
+        let stream = self.get_connection().await?;
+        *self.spare.lock().unwrap() = Some(stream);
+        Ok(())
+    }
+
     /// Get or create a connection to the socket
-    async fn get_connection(&self) -> Result<UnixStream, IpcError> {
+    async fn get_connection(&self) -> Result<Box<dyn Connection>, IpcError> {
+        if let Some(address) = tcp_address(&self.socket_path) {
+            // No file or peer checks apply over TCP; the auth token and request
+            // signing are what the backend has to go on
+            let stream = TcpStream::connect(address)
+                .await
+                .map_err(|e| self.connect_error(e))?;
+            // Requests are single lines; don't hold them back for coalescing
+            let _ = stream.set_nodelay(true);
+            return Ok(Box::new(stream));
+        }
+        if !self.allow_shared_socket {
+            self.check_socket_file()?;
+        }
+
         // Try to connect fresh each time for reliability
         // (socket connections can go stale)
-        UnixStream::connect(&self.socket_path).await.map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound
-                || e.kind() == std::io::ErrorKind::ConnectionRefused
-            {
-                IpcError::NotConnected
-            } else {
-                IpcError::ConnectionFailed(format!(
-                    "Failed to connect to socket at {}: {}",
-                    self.socket_path, e
-                ))
+        let stream = UnixStream::connect(&self.socket_path)
+            .await
+            .map_err(|e| self.connect_error(e))?;
+
+        if !self.allow_shared_socket {
+            // The file checks can race with a replacement; also make sure the
+            // process on the other end runs as us
+            if let Ok(cred) = stream.peer_cred() {
+                check_owner("backend process", cred.uid(), current_uid())
+                    .map_err(IpcError::InsecureSocket)?;
             }
-        })
+        }
+        Ok(Box::new(stream))
+    }
+
+    /// A missing socket or refused connection means the backend is not running.
+    fn connect_error(&self, e: std::io::Error) -> IpcError {
+        if e.kind() == std::io::ErrorKind::NotFound
+            || e.kind() == std::io::ErrorKind::ConnectionRefused
+        {
+            IpcError::NotConnected
+        } else {
+            IpcError::ConnectionFailed(format!(
+                "Failed to connect to socket at {}: {}",
+                self.socket_path, e
+            ))
+        }
+    }
+
+    /// Refuse a socket file that another user owns or could have replaced.
+    fn check_socket_file(&self) -> Result<(), IpcError> {
+        use std::os::unix::fs::MetadataExt;
+
+        // A missing socket is reported by connect() as "backend not running"
+        let Ok(metadata) = std::fs::metadata(&self.socket_path) else {
+            return Ok(());
+        };
+        check_owner("socket", metadata.uid(), current_uid())
+            .and_then(|()| check_mode(metadata.mode()))
+            .map_err(|e| IpcError::InsecureSocket(format!("{} ({})", e, self.socket_path)))
+    }
+
+    /// Serialize one request line. Params are only converted to a JSON value
+    /// when they have to be signed; otherwise they are written directly.
+    fn encode_request<Req: serde::Serialize>(
+        &self,
+        method: &str,
+        params: &Req,
+    ) -> Result<String, IpcError> {
+        let Some(key) = &self.signing_key else {
+            return self.request_line(method, params, None, None);
+        };
+        let params = serde_json::to_value(params)
+            .map_err(|e| IpcError::InvalidResponse(format!("Failed to serialize params: {}", e)))?;
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = sign(key, timestamp, method, &params);
+        self.request_line(method, &params, Some(timestamp), Some(signature))
+    }
+
+    fn request_line<P: serde::Serialize>(
+        &self,
+        method: &str,
+        params: &P,
+        timestamp: Option<i64>,
+        signature: Option<String>,
+    ) -> Result<String, IpcError> {
+        let request = SocketRequest {
+            v: WIRE_VERSION,
+            id: None, // We don't need request IDs for simple request/response
+            method,
+            params,
+            token: self.auth_token.as_deref(),
+            session_token: self.session_token.as_deref(),
+            timestamp,
+            signature,
+        };
+        serde_json::to_string(&request)
+            .map_err(|e| IpcError::InvalidResponse(format!("Failed to serialize request: {}", e)))
     }
 
     /// Send a request to the socket server and receive a response
@@ -111,65 +279,100 @@ impl UnixSocketClient {
         Req: serde::Serialize + Send + Sync,
         Res: serde::de::DeserializeOwned,
     {
-        let mut stream = self.get_connection().await?;
-
-        // Build the request
-        let request = SocketRequest {
-            id: None, // We don't need request IDs for simple request/response
-            method: method.to_string(),
-            params: serde_json::to_value(params).map_err(|e| {
-                IpcError::InvalidResponse(format!("Failed to serialize params: {}", e))
-            })?,
-            token: self.auth_token.clone(),
+        let request_json = self.encode_request(method, params)?;
+
+        tracing::debug!(
+            "IPC request {}: {}",
+            method,
+            self.redactor.redact(&request_json)
+        );
+
+        // A spare connection may have been closed by the backend since it was
+        // opened; the request can't have been handled then, so retry fresh
+        let spare = self.spare.lock().unwrap().take();
+        let response_line = match spare {
+            Some(stream) => match exchange(stream, &request_json).await {
+                Ok(line) if !line.is_empty() => line,
+                _ => exchange(self.get_connection().await?, &request_json).await?,
+            },
+            None => exchange(self.get_connection().await?, &request_json).await?,
         };
 
-        let request_json = serde_json::to_string(&request).map_err(|e| {
-            IpcError::InvalidResponse(format!("Failed to serialize request: {}", e))
-        })?;
-
-        // Send request (line-delimited JSON)
-        stream
-            .write_all(request_json.as_bytes())
-            .await
-            .map_err(|e| IpcError::ConnectionFailed(format!("Failed to write request: {}", e)))?;
-        stream
-            .write_all(b"\n")
-            .await
-            .map_err(|e| IpcError::ConnectionFailed(format!("Failed to write newline: {}", e)))?;
-        stream
-            .flush()
-            .await
-            .map_err(|e| IpcError::ConnectionFailed(format!("Failed to flush: {}", e)))?;
+        if !response_line.is_empty() {
+            *self.last_contact.lock().unwrap() = Some(Utc::now());
+        }
+        tracing::debug!(
+            "IPC response {}: {}",
+            method,
+            self.redactor.redact(response_line.trim_end())
+        );
 
-        // Read response
-        let mut reader = BufReader::new(&mut stream);
-        let mut response_line = String::new();
-        reader
-            .read_line(&mut response_line)
-            .await
-            .map_err(|e| IpcError::ConnectionFailed(format!("Failed to read response: {}", e)))?;
+        decode_response(method, &response_line)
+    }
+}
 
-        // Parse response
-        let response: SocketResponse = serde_json::from_str(&response_line)
-            .map_err(|e| IpcError::InvalidResponse(format!("Failed to parse response: {}", e)))?;
+/// Parse one response line to `method`, upgrade it to the current wire
+/// version, and deserialize its `data`. Malformed or unexpected output from the
+/// backend is an `InvalidResponse`, never a panic.
+pub(crate) fn decode_response<Res: serde::de::DeserializeOwned>(
+    method: &str,
+    response_line: &str,
+) -> Result<Res, IpcError> {
+    let invalid = |e| IpcError::InvalidResponse(format!("Failed to parse response: {}", e));
+    let mut response: serde_json::Value = serde_json::from_str(response_line).map_err(invalid)?;
+    let version = wire::upgrade(method, &mut response);
+    let response: SocketResponse = serde_json::from_value(response).map_err(invalid)?;
+
+    if !response.success {
+        return Err(IpcError::RequestFailed(match response.error {
+            Some(error) => {
+                tracing::debug!("{} failed with code {}", method, error.code);
+                error.message
+            }
+            None => "Unknown error".to_string(),
+        }));
+    }
 
-        if !response.success {
-            return Err(IpcError::RequestFailed(
-                response
-                    .error
-                    .unwrap_or_else(|| "Unknown error".to_string()),
+    // Extract and deserialize data
+    let data = response
+        .data
+        .ok_or_else(|| IpcError::InvalidResponse("Response missing data field".to_string()))?;
+
+    serde_json::from_value(data).map_err(|e| {
+        let mut message = format!("Failed to deserialize response data: {}", e);
+        if version > WIRE_VERSION {
+            message.push_str(&format!(
+                " (the backend speaks wire version {}, this binary {}; upgrade hotwired-mcp)",
+                version, WIRE_VERSION
             ));
         }
+        IpcError::InvalidResponse(message)
+    })
+}
 
-        // Extract and deserialize data
-        let data = response
-            .data
-            .ok_or_else(|| IpcError::InvalidResponse("Response missing data field".to_string()))?;
-
-        serde_json::from_value(data).map_err(|e| {
-            IpcError::InvalidResponse(format!("Failed to deserialize response data: {}", e))
-        })
-    }
+/// Write one line-delimited JSON request to `stream` and read the response line.
+/// An empty string means the backend closed the connection without answering.
+async fn exchange(mut stream: Box<dyn Connection>, request_json: &str) -> Result<String, IpcError> {
+    stream
+        .write_all(request_json.as_bytes())
+        .await
+        .map_err(|e| IpcError::ConnectionFailed(format!("Failed to write request: {}", e)))?;
+    stream
+        .write_all(b"\n")
+        .await
+        .map_err(|e| IpcError::ConnectionFailed(format!("Failed to write newline: {}", e)))?;
+    stream
+        .flush()
+        .await
+        .map_err(|e| IpcError::ConnectionFailed(format!("Failed to flush: {}", e)))?;
+
+    let mut reader = BufReader::new(&mut stream);
+    let mut response_line = String::new();
+    reader
+        .read_line(&mut response_line)
+        .await
+        .map_err(|e| IpcError::ConnectionFailed(format!("Failed to read response: {}", e)))?;
+    Ok(response_line)
 }
 
 #[async_trait]
@@ -189,12 +392,66 @@ impl IpcClient for UnixSocketClient {
         let _: serde_json::Value = self.send_request("ping", &serde_json::json!({})).await?;
         Ok(())
     }
+
+    async fn warm_up(&self) -> Result<(), IpcError> {
+        self.open_spare().await
+    }
+
+    fn last_contact(&self) -> Option<DateTime<Utc>> {
+        *self.last_contact.lock().unwrap()
+    }
+}
+
+/// HMAC-SHA256 of `"{timestamp}.{method}.{params}"`, hex encoded, where
+/// `params` is the compact JSON exactly as it appears in the request.
+pub fn sign(key: &[u8], timestamp: i64, method: &str, params: &serde_json::Value) -> String {
+    use hmac::{Hmac, Mac};
+
+    let mut mac =
+        Hmac::<sha2::Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}.{}", timestamp, method, params).as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() }
+}
+
+/// `what` must belong to the current user.
+fn check_owner(what: &str, uid: u32, current: u32) -> Result<(), String> {
+    if uid == current {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is owned by uid {}, not the current user (uid {}). \
+            Use --allow-shared-socket if this is intended",
+            what, uid, current
+        ))
+    }
+}
+
+/// The socket must not be accessible to group or others (i.e. at most 0600).
+fn check_mode(mode: u32) -> Result<(), String> {
+    if mode & 0o077 == 0 {
+        Ok(())
+    } else {
+        Err(format!(
+            "socket permissions are {:o}; expected 600 (no group or other access). \
+            Use --allow-shared-socket if this is intended",
+            mode & 0o777
+        ))
+    }
 }
 
 /// Map HTTP-style endpoints to socket method names.
 /// The tools use endpoints like "/api/protocol", "/api/status", etc.
 /// The socket server expects method names like "get_protocol", "report_status", etc.
-fn endpoint_to_method(endpoint: &str) -> String {
+pub fn endpoint_to_method(endpoint: &str) -> String {
     // Remove leading /api/ prefix if present
     let path = endpoint.trim_start_matches("/api/");
 
@@ -250,6 +507,26 @@ fn endpoint_to_method(endpoint: &str) -> String {
         return "doc_artifact_list_suggestions".to_string();
     }
 
+    // Handle artifact edit history: runs/{run_id}/artifacts/{artifact_id}/edits/list
+    if segments.len() == 6
+        && segments[0] == "runs"
+        && segments[2] == "artifacts"
+        && segments[4] == "edits"
+        && segments[5] == "list"
+    {
+        return "doc_artifact_list_edits".to_string();
+    }
+
+    // Handle read receipts: runs/{run_id}/artifacts/{artifact_id}/readers/report
+    if segments.len() == 6
+        && segments[0] == "runs"
+        && segments[2] == "artifacts"
+        && segments[4] == "readers"
+        && segments[5] == "report"
+    {
+        return "report_artifact_read".to_string();
+    }
+
     // Handle artifact sub-actions: runs/{run_id}/artifacts/{artifact_id}/{action}
     // e.g., runs/{run_id}/artifacts/{artifact_id}/edit
     // e.g., runs/{run_id}/artifacts/{artifact_id}/search
@@ -259,6 +536,8 @@ fn endpoint_to_method(endpoint: &str) -> String {
             "edit" => "doc_artifact_edit".to_string(),
             "search" => "doc_artifact_search".to_string(),
             "suggestions" => "doc_artifact_suggest_edit".to_string(),
+            "tags" => "doc_artifact_set_tags".to_string(),
+            "readers" => "get_artifact_readers".to_string(),
             _ => format!("doc_artifact_{}", segments[4]),
         };
     }
@@ -278,11 +557,27 @@ fn endpoint_to_method(endpoint: &str) -> String {
         return "doc_artifact_create".to_string();
     }
 
+    // Handle playbook detail: playbooks/{playbook_id}
+    if segments.len() == 2 && segments[0] == "playbooks" {
+        return "get_playbook".to_string();
+    }
+
     // Handle 4-segment patterns first: runs/{run_id}/{action}/{sub_action}
     if segments.len() == 4 && segments[0] == "runs" {
         return match (segments[2], segments[3]) {
             ("impediment", "resolve") => "resolve_impediment".to_string(),
             ("end", "respond") => "respond_input".to_string(),
+            ("input", _) => "get_input_status".to_string(),
+            ("impediments", _) => "get_impediment_resolution".to_string(),
+            ("messages", "ack") => "ack_message".to_string(),
+            ("messages", _) => "get_message_deliveries".to_string(),
+            ("agents", "capabilities") => "get_agent_capabilities".to_string(),
+            ("notifications", "ack") => "ack_notification".to_string(),
+            ("steps", "complete") => "protocol_step_complete".to_string(),
+            ("usage", "report") => "report_usage".to_string(),
+            ("summary", "latest") => "get_latest_summary".to_string(),
+            ("context", "set") => "set_run_context".to_string(),
+            ("tasks", "completed") => "list_completed_tasks".to_string(),
             _ => format!("runs_{}_{}", segments[2], segments[3]),
         };
     }
@@ -296,9 +591,20 @@ fn endpoint_to_method(endpoint: &str) -> String {
             "report-status" => "report_status".to_string(),
             "task-complete" => "task_complete".to_string(),
             "impediment" => "report_impediment".to_string(),
+            "impediments" => "get_impediments".to_string(),
+            "impediment-templates" => "list_impediment_templates".to_string(),
             "handoff" => "handoff".to_string(),
+            "handoffs" => "get_handoffs".to_string(),
+            "switch-role" => "switch_role".to_string(),
             "input" => "request_input".to_string(),
             "end" => "request_end_run".to_string(),
+            "events" => "create_event".to_string(),
+            "notifications" => "get_notifications".to_string(),
+            "steps" => "protocol_steps_list".to_string(),
+            "usage" => "get_run_usage".to_string(),
+            "summary" => "post_summary".to_string(),
+            "context" => "get_run_context".to_string(),
+            "config" => "get_run_config".to_string(),
             other => format!("runs_{}", other),
         };
     }
@@ -319,11 +625,210 @@ fn endpoint_to_method(endpoint: &str) -> String {
         "runs" => "list_runs".to_string(),
         "events" => "create_event".to_string(),
         "events/conversation" => "get_conversation_events".to_string(),
+        "runs/search" => "search_runs".to_string(),
         // Terminal workflow tools
         "hotwire" => "hotwire".to_string(),
         "pair" => "pair".to_string(),
+        "resume" => "resume_run".to_string(),
         "active-runs" => "list_active_runs".to_string(),
         "playbooks" => "list_playbooks".to_string(),
+        // Session lifecycle
+        "sessions/heartbeat" => "session_heartbeat".to_string(),
+        "capabilities" => "get_backend_capabilities".to_string(),
         _ => path.replace(['-', '/'], "_"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_matches_reference_hmac() {
+        use hmac::Mac;
+
+        // Same as an HMAC computed directly over "{timestamp}.{method}.{params}"
+        let mut reference = hmac::Hmac::<sha2::Sha256>::new_from_slice(b"key").unwrap();
+        reference.update(b"1700000000.ping.{}");
+        let expected: String = reference
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        let params = serde_json::json!({});
+        assert_eq!(sign(b"key", 1700000000, "ping", &params), expected);
+        assert_eq!(expected.len(), 64);
+        // Any change to the signed fields changes the signature
+        assert_ne!(sign(b"key", 1700000001, "ping", &params), expected);
+        assert_ne!(sign(b"other", 1700000000, "ping", &params), expected);
+        assert_ne!(
+            sign(b"key", 1700000000, "ping", &serde_json::json!({"a": 1})),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_signed_and_unsigned_requests_carry_the_same_params() {
+        let params = serde_json::json!({"runId": "run-1", "newContent": "line\n\"quoted\""});
+        let client = UnixSocketClient::new(Some("/tmp/unused.sock".to_string()))
+            .with_session_token(Some("session".to_string()));
+        let unsigned: serde_json::Value =
+            serde_json::from_str(&client.encode_request("doc_artifact_edit", &params).unwrap())
+                .unwrap();
+        assert_eq!(unsigned["method"], "doc_artifact_edit");
+        assert_eq!(unsigned["params"], params);
+        assert_eq!(unsigned["session_token"], "session");
+        assert!(unsigned.get("signature").is_none());
+
+        let client = client.with_signing_key(Some(b"key".to_vec()));
+        let signed: serde_json::Value =
+            serde_json::from_str(&client.encode_request("doc_artifact_edit", &params).unwrap())
+                .unwrap();
+        assert_eq!(signed["params"], params);
+        let timestamp = signed["timestamp"].as_i64().unwrap();
+        assert_eq!(
+            signed["signature"],
+            sign(b"key", timestamp, "doc_artifact_edit", &params)
+        );
+    }
+
+    #[test]
+    fn test_responses_of_any_wire_version_decode() {
+        let params = serde_json::json!({});
+        let client = UnixSocketClient::new(Some("/tmp/unused.sock".to_string()));
+        let request: serde_json::Value =
+            serde_json::from_str(&client.encode_request("ping", &params).unwrap()).unwrap();
+        assert_eq!(request["v"], WIRE_VERSION);
+
+        let unversioned = r#"{"success":false,"error":"Run not found"}"#;
+        let current =
+            r#"{"v":2,"success":false,"error":{"code":"not_found","message":"Run not found"}}"#;
+        for line in [unversioned, current] {
+            match decode_response::<serde_json::Value>("get_run_status", line) {
+                Err(IpcError::RequestFailed(message)) => assert_eq!(message, "Run not found"),
+                other => panic!("{:?}", other),
+            }
+        }
+
+        let pong: serde_json::Value =
+            decode_response("ping", r#"{"v":3,"success":true,"data":{"pong":true}}"#).unwrap();
+        assert_eq!(pong["pong"], true);
+        let err = decode_response::<crate::ipc::messages::AckNotificationResponse>(
+            "ack_notifications",
+            r#"{"v":3,"success":true,"data":{"ok":true}}"#,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("backend speaks wire version 3, this binary 2"));
+    }
+
+    #[test]
+    fn test_socket_must_belong_to_current_user() {
+        assert!(check_owner("socket", 501, 501).is_ok());
+        let err = check_owner("socket", 0, 501).unwrap_err();
+        assert!(err.contains("owned by uid 0"));
+        assert!(err.contains("--allow-shared-socket"));
+    }
+
+    #[test]
+    fn test_socket_must_not_be_group_or_world_accessible() {
+        assert!(check_mode(0o140600).is_ok());
+        assert!(check_mode(0o140700).is_ok());
+        assert!(check_mode(0o140660).unwrap_err().contains("660"));
+        assert!(check_mode(0o140755).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connects_to_private_socket_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("hotwired-sock-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.sock");
+        let _listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let client = UnixSocketClient::new(Some(path.to_string_lossy().into_owned()));
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(client.get_connection().await.is_ok());
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o666)).unwrap();
+        assert!(matches!(
+            client.get_connection().await,
+            Err(IpcError::InsecureSocket(_))
+        ));
+        let shared = client.with_allow_shared_socket(true);
+        assert!(shared.get_connection().await.is_ok());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_retries_when_spare_connection_was_closed() {
+        let dir = std::env::temp_dir().join(format!("hotwired-spare-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.sock");
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let client = UnixSocketClient::new(Some(path.to_string_lossy().into_owned()))
+            .with_allow_shared_socket(true);
+
+        // The backend drops the spare, then answers the fresh connection
+        let backend = tokio::spawn(async move {
+            let (spare, _) = listener.accept().await.unwrap();
+            drop(spare);
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut line = String::new();
+            BufReader::new(&mut stream)
+                .read_line(&mut line)
+                .await
+                .unwrap();
+            stream
+                .write_all(b"{\"success\":true,\"data\":{\"pong\":true}}\n")
+                .await
+                .unwrap();
+            line
+        });
+
+        client.warm_up().await.unwrap();
+        let response: serde_json::Value = client
+            .send_request("ping", &serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(response["pong"], true);
+        assert!(backend.await.unwrap().contains("\"method\":\"ping\""));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tcp_backend_uses_same_framing() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let backend = format!("tcp://{}", listener.local_addr().unwrap());
+        assert_eq!(tcp_address(&backend), Some(&backend[6..]));
+        assert_eq!(tcp_address("/tmp/hotwired.sock"), None);
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut line = String::new();
+            BufReader::new(&mut stream)
+                .read_line(&mut line)
+                .await
+                .unwrap();
+            stream
+                .write_all(b"{\"success\":true,\"data\":{\"pong\":true}}\n")
+                .await
+                .unwrap();
+            line
+        });
+
+        let client = UnixSocketClient::new(Some(backend));
+        let response: serde_json::Value = client
+            .send_request("ping", &serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(response["pong"], true);
+        assert!(server.await.unwrap().contains("\"method\":\"ping\""));
+    }
+}
@@ -0,0 +1,294 @@
+//! Wire compatibility with hotwired-core.
+//!
+//! `tests/fixtures/backend/<method>.json` holds the `params` the backend
+//! receives for a socket method and the `responses` it sends back, in the
+//! backend's own format. Every message type must round-trip those payloads
+//! unchanged: a field renamed on either side is dropped or rejected by serde,
+//! which shows up here as a diff instead of as a silently missing value. Tool
+//! arguments are also checked against the JSON Schema advertised to agents.
+
+use super::messages::*;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+struct Fixture {
+    params: Value,
+    responses: Vec<Value>,
+}
+
+fn parse_fixture(method: &str, json: &str) -> Fixture {
+    let mut fixture: Value = serde_json::from_str(json)
+        .unwrap_or_else(|e| panic!("{}: fixture is not valid JSON: {}", method, e));
+    let responses = match fixture["responses"].take() {
+        Value::Array(responses) if !responses.is_empty() => responses,
+        _ => panic!("{}: fixture needs a non-empty `responses` array", method),
+    };
+    Fixture {
+        params: fixture["params"].take(),
+        responses,
+    }
+}
+
+/// Deserialize `payload` as `T` and serialize it back; the result must be identical.
+fn assert_round_trip<T: Serialize + DeserializeOwned>(what: &str, payload: &Value) -> T {
+    let parsed: T = serde_json::from_value(payload.clone()).unwrap_or_else(|e| {
+        panic!(
+            "{}: failed to deserialize {}: {}",
+            what,
+            std::any::type_name::<T>(),
+            e
+        )
+    });
+    let written = serde_json::to_value(&parsed).unwrap();
+    assert_eq!(
+        &written,
+        payload,
+        "{}: {} does not round-trip",
+        what,
+        std::any::type_name::<T>()
+    );
+    parsed
+}
+
+/// `arguments` must be accepted by the schema generated for tool `T`.
+fn assert_matches_schema<T: JsonSchema>(what: &str, arguments: &Value) {
+    let schema = serde_json::to_value(schemars::schema_for!(T)).unwrap();
+    let validator = jsonschema::validator_for(&schema)
+        .unwrap_or_else(|e| panic!("{}: invalid schema: {}", what, e));
+    let errors: Vec<String> = validator
+        .iter_errors(arguments)
+        .map(|e| format!("{} at {}", e, e.instance_path))
+        .collect();
+    assert!(
+        errors.is_empty(),
+        "{}: arguments rejected by the {} schema: {:?}",
+        what,
+        std::any::type_name::<T>(),
+        errors
+    );
+}
+
+fn assert_responses<R: Serialize + DeserializeOwned>(method: &str, fixture: &Fixture) {
+    for (i, response) in fixture.responses.iter().enumerate() {
+        assert_round_trip::<R>(&format!("{} response {}", method, i), response);
+    }
+}
+
+macro_rules! fixture {
+    ($method:ident) => {
+        parse_fixture(
+            stringify!($method),
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/backend/",
+                stringify!($method),
+                ".json"
+            )),
+        )
+    };
+}
+
+/// Methods whose params are a tool's arguments as-is.
+macro_rules! tool_methods {
+    ($($method:ident => $request:ty, $response:ty;)*) => {
+        mod tools {
+            use super::*;
+            $(
+                #[test]
+                fn $method() {
+                    let method = stringify!($method);
+                    let fixture = fixture!($method);
+                    assert_round_trip::<$request>(method, &fixture.params);
+                    assert_matches_schema::<$request>(method, &fixture.params);
+                    assert_responses::<$response>(method, &fixture);
+                }
+            )*
+        }
+    };
+}
+
+/// Methods only the server itself calls, with no tool schema.
+macro_rules! socket_methods {
+    ($($method:ident => $request:ty, $response:ty;)*) => {
+        mod socket {
+            use super::*;
+            $(
+                #[test]
+                fn $method() {
+                    let method = stringify!($method);
+                    let fixture = fixture!($method);
+                    assert_round_trip::<$request>(method, &fixture.params);
+                    assert_responses::<$response>(method, &fixture);
+                }
+            )*
+        }
+    };
+}
+
+tool_methods! {
+    get_run_status => GetRunStatusRequest, GetRunStatusResponse;
+    get_agent_capabilities => GetAgentCapabilitiesRequest, GetAgentCapabilitiesResponse;
+    get_notifications => GetNotificationsRequest, GetNotificationsResponse;
+    ack_notification => AckNotificationRequest, AckNotificationResponse;
+    protocol_steps_list => ProtocolStepsListRequest, ProtocolStepsListResponse;
+    protocol_step_complete => ProtocolStepCompleteRequest, ProtocolStepCompleteResponse;
+    report_usage => ReportUsageRequest, ReportUsageResponse;
+    get_run_usage => GetRunUsageRequest, GetRunUsageResponse;
+    post_summary => PostSummaryRequest, PostSummaryResponse;
+    get_latest_summary => GetLatestSummaryRequest, GetLatestSummaryResponse;
+    set_run_context => SetRunContextRequest, SetRunContextResponse;
+    get_run_context => GetRunContextRequest, GetRunContextResponse;
+    get_run_config => GetRunConfigRequest, GetRunConfigResponse;
+    get_artifact_readers => GetArtifactReadersRequest, GetArtifactReadersResponse;
+    report_status => ReportStatusRequest, ReportStatusResponse;
+    send_message => SendMessageRequest, SendMessageResponse;
+    get_message_deliveries => GetMessageDeliveriesRequest, GetMessageDeliveriesResponse;
+    ack_message => AckMessageRequest, AckMessageResponse;
+    task_complete => TaskCompleteRequest, TaskCompleteResponse;
+    list_completed_tasks => ListCompletedTasksRequest, ListCompletedTasksResponse;
+    report_impediment => ReportImpedimentRequest, ReportImpedimentResponse;
+    list_impediment_templates => ListImpedimentTemplatesRequest, ListImpedimentTemplatesResponse;
+    get_impediments => GetImpedimentsRequest, GetImpedimentsResponse;
+    get_impediment_resolution => GetImpedimentResolutionRequest, GetImpedimentResolutionResponse;
+    resolve_impediment => ResolveImpedimentRequest, ResolveImpedimentResponse;
+    request_input => RequestInputRequest, RequestInputResponse;
+    get_input_status => InputStatusRequest, InputStatusResponse;
+    handoff => HandoffRequest, HandoffResponse;
+    get_handoffs => GetHandoffsRequest, GetHandoffsResponse;
+    switch_role => SwitchRoleRequest, SwitchRoleResponse;
+    request_end_run => RequestEndRunRequest, RequestEndRunResponse;
+    respond_input => RespondToEndRequestRequest, RespondToEndRequestResponse;
+    doc_artifact_list => DocArtifactListRequest, DocArtifactListResponse;
+    doc_artifact_read => DocArtifactReadRequest, DocArtifactReadResponse;
+    doc_artifact_create => DocArtifactCreateRequest, DocArtifactCreateResponse;
+    doc_artifact_edit => DocArtifactEditRequest, DocArtifactEditResponse;
+    doc_artifact_search => DocArtifactSearchRequest, DocArtifactSearchResponse;
+    doc_artifact_add_comment => DocArtifactAddCommentRequest, DocArtifactAddCommentResponse;
+    doc_artifact_resolve_comment => DocArtifactResolveCommentRequest, DocArtifactResolveCommentResponse;
+    doc_artifact_list_comments => DocArtifactListCommentsRequest, DocArtifactListCommentsResponse;
+    doc_artifact_suggest_edit => DocArtifactSuggestEditRequest, DocArtifactSuggestEditResponse;
+    doc_artifact_accept_suggestion => DocArtifactAcceptSuggestionRequest, DocArtifactAcceptSuggestionResponse;
+    doc_artifact_reject_suggestion => DocArtifactRejectSuggestionRequest, DocArtifactRejectSuggestionResponse;
+    doc_artifact_list_suggestions => DocArtifactListSuggestionsRequest, DocArtifactListSuggestionsResponse;
+    doc_artifact_set_tags => DocArtifactSetTagsRequest, DocArtifactSetTagsResponse;
+    hotwire => HotwireRequest, HotwireResponse;
+    pair => PairRequest, PairResponse;
+    list_active_runs => ListActiveRunsRequest, ListActiveRunsResponse;
+    list_playbooks => ListPlaybooksRequest, ListPlaybooksResponse;
+    get_playbook => GetPlaybookRequest, GetPlaybookResponse;
+    search_runs => SearchRunsRequest, SearchRunsResponse;
+}
+
+socket_methods! {
+    register_session => RegisterSessionRequest, RegisterSessionResponse;
+    deregister_session => DeregisterSessionRequest, DeregisterSessionResponse;
+    session_heartbeat => SessionHeartbeatRequest, SessionHeartbeatResponse;
+    list_active_sessions => ListActiveSessionsRequest, ListActiveSessionsResponse;
+    get_backend_capabilities => GetBackendCapabilitiesRequest, GetBackendCapabilitiesResponse;
+    create_event => CreateEventRequest, CreateEventResponse;
+    get_conversation_events => GetConversationEventsRequest, GetConversationEventsResponse;
+    doc_artifact_list_edits => DocArtifactListEditsRequest, DocArtifactListEditsResponse;
+    report_artifact_read => ReportArtifactReadRequest, ReportArtifactReadResponse;
+}
+
+#[test]
+fn test_get_protocol_sends_agent_role_as_role() {
+    let fixture = fixture!(get_protocol);
+    let arguments = serde_json::json!({
+        "runId": fixture.params["runId"],
+        "agentRole": fixture.params["role"],
+    });
+    assert_matches_schema::<GetProtocolRequest>("get_protocol", &arguments);
+    let request: GetProtocolRequest = serde_json::from_value(arguments).unwrap();
+    let sent = serde_json::to_value(GetProtocolSocketRequest::from(&request)).unwrap();
+    assert_eq!(sent, fixture.params);
+    assert_responses::<GetProtocolResponse>("get_protocol", &fixture);
+}
+
+#[test]
+fn test_resume_run_adds_last_run_outside_the_tool_arguments() {
+    let fixture = fixture!(resume_run);
+    let mut arguments = fixture.params.clone();
+    let object = arguments.as_object_mut().unwrap();
+    let last_run_id = object.remove("lastRunId").unwrap();
+    let last_role = object.remove("lastRole").unwrap();
+    assert_matches_schema::<ResumeRunRequest>("resume_run", &arguments);
+
+    let mut request: ResumeRunRequest = serde_json::from_value(arguments).unwrap();
+    request.last_run_id = last_run_id.as_str().map(String::from);
+    request.last_role = last_role.as_str().map(String::from);
+    assert_eq!(serde_json::to_value(&request).unwrap(), fixture.params);
+    assert_responses::<ResumeRunResponse>("resume_run", &fixture);
+}
+
+#[test]
+fn test_offsets_accept_numbers_numeric_strings_and_blanks() {
+    let edit = |offsets: Value| {
+        let mut arguments = serde_json::json!({
+            "runId": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+            "artifactId": "art-1",
+            "editType": "replace_range",
+            "contentHash": "hash",
+            "newContent": "x",
+        });
+        arguments
+            .as_object_mut()
+            .unwrap()
+            .extend(offsets.as_object().unwrap().clone());
+        serde_json::from_value::<DocArtifactEditRequest>(arguments)
+    };
+
+    let request = edit(serde_json::json!({
+        "startOffset": "120",
+        "endOffset": 145,
+        "insertOffset": "",
+    }))
+    .unwrap();
+    assert_eq!(request.start_offset, Some(120));
+    assert_eq!(request.end_offset, Some(145));
+    assert_eq!(request.insert_offset, None);
+    // Always sent to the backend as numbers, blanks omitted
+    let sent = serde_json::to_value(&request).unwrap();
+    assert_eq!(sent["startOffset"], 120);
+    assert!(sent.get("insertOffset").is_none());
+
+    let request = edit(serde_json::json!({"startOffset": null})).unwrap();
+    assert_eq!(request.start_offset, None);
+    assert!(edit(serde_json::json!({"startOffset": "12a"})).is_err());
+    assert!(edit(serde_json::json!({"startOffset": 1.5})).is_err());
+}
+
+#[test]
+fn test_required_ids_accept_numeric_strings() {
+    let request: ResolveImpedimentRequest = serde_json::from_value(serde_json::json!({
+        "runId": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+        "impedimentId": "42",
+        "response": "ok",
+    }))
+    .unwrap();
+    assert_eq!(request.impediment_id, 42);
+    assert_eq!(serde_json::to_value(&request).unwrap()["impedimentId"], 42);
+
+    let comment: DocArtifactAddCommentRequest = serde_json::from_value(serde_json::json!({
+        "runId": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+        "artifactId": "art-1",
+        "commentType": "question",
+        "selectionStart": "8",
+        "selectionEnd": 49,
+        "content": "Why?",
+    }))
+    .unwrap();
+    assert_eq!((comment.selection_start, comment.selection_end), (8, 49));
+}
+
+#[test]
+fn test_snake_case_field_names_are_not_wire_names() {
+    // Rust field names must never leak onto the wire
+    let result = serde_json::from_value::<DocArtifactReadRequest>(serde_json::json!({
+        "run_id": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+        "artifact_id": "art-1",
+    }));
+    assert!(result.is_err());
+}
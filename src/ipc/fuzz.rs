@@ -0,0 +1,236 @@
+//! Property tests that feed malformed backend output to the response decoder.
+//!
+//! Whatever the backend sends, decoding must end in a value or an
+//! `IpcError::InvalidResponse` (or `RequestFailed` for an explicit failure),
+//! never a panic. The permissive deserializers (`string_or_i64` and the
+//! `status`-tagged terminal responses) get the most attention.
+
+use super::client::decode_response;
+use super::messages::*;
+use crate::types::errors::IpcError;
+use proptest::prelude::*;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+/// Field names and `status` tags the backend actually uses, so generated
+/// payloads get past the first missing-field check often enough to matter.
+const KNOWN_KEYS: &[&str] = &[
+    "status",
+    "runId",
+    "role",
+    "roleName",
+    "playbook",
+    "protocol",
+    "error",
+    "message",
+    "pendingRunId",
+    "suggestedPlaybook",
+    "pendingRuns",
+    "context",
+    "digest",
+    "awaySince",
+    "impedimentId",
+    "startOffset",
+];
+
+const TAGS: &[&str] = &[
+    "started",
+    "needs_confirmation",
+    "joined",
+    "needs_selection",
+    "none",
+    "project_mismatch",
+    "resumed",
+    "error",
+    "Started",
+    "",
+];
+
+fn key() -> impl Strategy<Value = String> {
+    prop_oneof![
+        3 => prop::sample::select(KNOWN_KEYS).prop_map(String::from),
+        1 => "[a-zA-Z_]{0,12}",
+    ]
+}
+
+fn json_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        ".{0,16}".prop_map(Value::from),
+        prop::sample::select(TAGS).prop_map(Value::from),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(Value::from),
+            prop::collection::btree_map(key(), inner, 0..8)
+                .prop_map(|fields| Value::Object(fields.into_iter().collect())),
+        ]
+    })
+}
+
+/// A `status`-tagged object, as the terminal tools return.
+fn tagged_value() -> impl Strategy<Value = Value> {
+    (
+        prop::sample::select(TAGS),
+        prop::collection::btree_map(key(), json_value(), 0..8),
+    )
+        .prop_map(|(tag, fields)| {
+            let mut object: serde_json::Map<String, Value> = fields.into_iter().collect();
+            object.insert("status".to_string(), Value::from(tag));
+            Value::Object(object)
+        })
+}
+
+/// Decode `line`, panicking only on an error kind the decoder must not produce.
+fn decode<T: DeserializeOwned>(line: &str) -> Result<T, IpcError> {
+    let result = decode_response::<T>("hotwire", line);
+    if let Err(e) = &result {
+        assert!(
+            matches!(e, IpcError::InvalidResponse(_) | IpcError::RequestFailed(_)),
+            "unexpected error kind decoding {:?}: {:?}",
+            line,
+            e
+        );
+    }
+    result
+}
+
+fn decode_data<T: DeserializeOwned>(data: &Value) -> Result<T, IpcError> {
+    decode(&json!({"success": true, "data": data}).to_string())
+}
+
+fn decode_all(data: &Value) {
+    let _ = decode_data::<HotwireResponse>(data);
+    let _ = decode_data::<PairResponse>(data);
+    let _ = decode_data::<ResumeRunResponse>(data);
+    let _ = decode_data::<GetProtocolResponse>(data);
+    let _ = decode_data::<DocArtifactReadResponse>(data);
+    let _ = decode_data::<DocArtifactEditResponse>(data);
+}
+
+/// The responses recorded in a backend fixture.
+fn fixture_responses(json: &str) -> Vec<Value> {
+    let fixture: Value = serde_json::from_str(json).unwrap();
+    fixture["responses"].as_array().unwrap().clone()
+}
+
+proptest! {
+    #[test]
+    fn test_arbitrary_lines_do_not_panic(line in ".{0,256}") {
+        let _ = decode::<HotwireResponse>(&line);
+        let _ = decode::<PairResponse>(&line);
+        let _ = decode::<ResumeRunResponse>(&line);
+    }
+
+    #[test]
+    fn test_truncated_responses_are_invalid(cut in 0usize..200) {
+        let line = json!({
+            "success": true,
+            "data": {"status": "started", "runId": "r", "role": "b", "playbook": "p", "protocol": "x"}
+        })
+        .to_string();
+        let cut = cut.min(line.len() - 1);
+        let result = decode::<HotwireResponse>(&line[..cut]);
+        prop_assert!(matches!(result, Err(IpcError::InvalidResponse(_))));
+    }
+
+    #[test]
+    fn test_arbitrary_envelopes_do_not_panic(envelope in json_value()) {
+        let _ = decode::<HotwireResponse>(&envelope.to_string());
+    }
+
+    #[test]
+    fn test_arbitrary_data_is_decoded_or_invalid(data in json_value()) {
+        decode_all(&data);
+    }
+
+    #[test]
+    fn test_tagged_data_is_decoded_or_invalid(data in tagged_value()) {
+        decode_all(&data);
+    }
+
+    #[test]
+    fn test_mutated_terminal_responses_are_decoded_or_invalid(
+        which in 0usize..11,
+        field in key(),
+        replacement in prop::option::of(json_value()),
+    ) {
+        let mut responses = fixture_responses(include_str!("../../tests/fixtures/backend/hotwire.json"));
+        responses.extend(fixture_responses(include_str!("../../tests/fixtures/backend/pair.json")));
+        responses.extend(fixture_responses(include_str!("../../tests/fixtures/backend/resume_run.json")));
+        let mut data = responses[which % responses.len()].clone();
+        let object = data.as_object_mut().unwrap();
+        match replacement {
+            Some(value) => object.insert(field, value),
+            None => object.remove(&field),
+        };
+        decode_all(&data);
+    }
+
+    #[test]
+    fn test_string_or_i64_parses_exactly_the_integers(id in prop_oneof![
+        ".{0,24}",
+        any::<i64>().prop_map(|i| i.to_string()),
+        "[+-]?[0-9]{1,22}",
+    ]) {
+        let request = json!({
+            "runId": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+            "impedimentId": id,
+            "response": "ok",
+        });
+        let parsed = serde_json::from_value::<ResolveImpedimentRequest>(request);
+        match id.parse::<i64>() {
+            Ok(expected) => prop_assert_eq!(parsed.unwrap().impediment_id, expected),
+            Err(_) => prop_assert!(parsed.is_err()),
+        }
+    }
+
+    #[test]
+    fn test_optional_offsets_parse_exactly_the_integers(offset in json_value()) {
+        let request = json!({
+            "runId": "7c9e6679-7425-40de-944b-e07fc1f90ae7",
+            "artifactId": "art-1",
+            "editType": "insert",
+            "contentHash": "hash",
+            "newContent": "x",
+            "insertOffset": offset,
+        });
+        let parsed = serde_json::from_value::<DocArtifactEditRequest>(request)
+            .map(|r| r.insert_offset);
+        let expected = match &offset {
+            Value::Null => Some(None),
+            Value::String(s) if s.is_empty() => Some(None),
+            Value::String(s) => s.parse::<i64>().ok().map(Some),
+            Value::Number(n) => n.as_i64().map(Some),
+            _ => None,
+        };
+        match expected {
+            Some(expected) => prop_assert_eq!(parsed.unwrap(), expected),
+            None => prop_assert!(parsed.is_err()),
+        }
+    }
+}
+
+#[test]
+fn test_deeply_nested_data_is_invalid_not_a_stack_overflow() {
+    let line = format!(
+        "{{\"success\":true,\"data\":{}{}}}",
+        "[".repeat(100_000),
+        "]".repeat(100_000)
+    );
+    assert!(matches!(
+        decode::<PairResponse>(&line),
+        Err(IpcError::InvalidResponse(_))
+    ));
+}
+
+#[test]
+fn test_unknown_status_tag_is_invalid() {
+    let data = json!({"status": "paused", "message": "later"});
+    let err = decode_data::<ResumeRunResponse>(&data).unwrap_err();
+    assert!(err.to_string().contains("unknown variant"));
+}
@@ -1,16 +1,76 @@
+use crate::types::run_state::RunStatus;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+/// JSON Schema pattern for run IDs (UUIDs).
+pub const RUN_ID_PATTERN: &str =
+    "^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$";
+
+/// Example run ID shown in tool schemas.
+const RUN_ID_EXAMPLE: &str = "123e4567-e89b-12d3-a456-426614174000";
+
 // =============================================================================
-// SESSION REGISTRATION (Claude Code Plugin Hooks)
+// SESSION REGISTRATION (Agent Plugin Hooks)
 // =============================================================================
 
-/// Request to register an active Claude session
+/// What names the terminal session an agent runs in
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Multiplexer {
+    /// A Zellij session
+    Zellij,
+    /// A tmux session
+    Tmux,
+    /// No multiplexer; the session is named after the agent's TTY and process id
+    Tty,
+}
+
+/// Request to register an active agent session
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct RegisterSessionRequest {
     pub session_name: String,
     pub project_dir: String,
+    /// What `session_name` names; the backend assumes Zellij when omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplexer: Option<Multiplexer>,
+    /// Agent CLI running in the session (e.g. "claude", "gemini", "codex")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_type: Option<String>,
+    /// Claude Code session id, for deep-linking to the conversation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claude_session_id: Option<String>,
+    /// Path to the Claude Code transcript (JSONL) for this session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript_path: Option<String>,
+    /// Model the session is running
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Workspace root, when it differs from the project directory (e.g. a worktree)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_root: Option<String>,
+    /// What this agent can do, for routing handoffs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<AgentCapabilities>,
+}
+
+/// What an agent declares it can do when it registers. Also read from the
+/// config file, hence the snake_case aliases.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AgentCapabilities {
+    /// Programming languages the agent is set up to work in
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    /// Whether the agent can read and write the project's files
+    #[serde(alias = "filesystem_access")]
+    pub filesystem_access: bool,
+    /// Whether the agent can run the project's tests
+    #[serde(alias = "can_run_tests")]
+    pub can_run_tests: bool,
+    /// Largest context window the agent works with, in tokens
+    #[serde(alias = "max_context_tokens", skip_serializing_if = "Option::is_none")]
+    pub max_context_tokens: Option<u64>,
 }
 
 /// Response to session registration
@@ -18,6 +78,9 @@ pub struct RegisterSessionRequest {
 #[serde(rename_all = "camelCase")]
 pub struct RegisterSessionResponse {
     pub success: bool,
+    /// Token to send with every later request from this session, if the backend issues one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
 }
 
 /// Request to deregister a Claude session
@@ -34,6 +97,25 @@ pub struct DeregisterSessionResponse {
     pub success: bool,
 }
 
+/// Periodic re-assertion that a registered session is still alive
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHeartbeatRequest {
+    pub session_name: String,
+    pub project_dir: String,
+    /// Process ID of the MCP server, so the backend can tell restarts apart
+    pub pid: u32,
+    /// Unix timestamp (seconds) when the heartbeat was sent
+    pub sent_at: i64,
+}
+
+/// Response to a session heartbeat
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHeartbeatResponse {
+    pub success: bool,
+}
+
 /// Request to list active Claude sessions
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -46,6 +128,9 @@ pub struct ActiveSessionInfo {
     pub session_name: String,
     pub project_dir: String,
     pub registered_at: i64,
+    /// Unix timestamp of the last heartbeat; sessions that stop sending them are stale
+    #[serde(default)]
+    pub last_seen_at: Option<i64>,
 }
 
 /// Response with list of active sessions
@@ -55,6 +140,21 @@ pub struct ListActiveSessionsResponse {
     pub sessions: Vec<ActiveSessionInfo>,
 }
 
+/// Request for the optional features the backend supports
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBackendCapabilitiesRequest {}
+
+/// Optional features of the connected backend
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetBackendCapabilitiesResponse {
+    /// Version of the Hotwired app
+    pub app_version: String,
+    /// Feature names, e.g. "suggestions" or "impediment_resolution"
+    pub features: Vec<String>,
+}
+
 // =============================================================================
 
 /// Helper module to deserialize i64 that may come as string or integer.
@@ -108,6 +208,34 @@ mod option_string_or_i64 {
     }
 }
 
+// ===== OUTPUT FORMAT =====
+
+/// How a tool renders its result.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Markdown prose for LLM agents
+    #[default]
+    Markdown,
+    /// Token-efficient text: no emoji or banners, tabular lists, short previews
+    Compact,
+    /// Compact JSON of the backend response, for programmatic clients
+    Json,
+    /// Markdown tables for list tools (artifacts, comments, suggestions);
+    /// other tools render as `Markdown`
+    Table,
+}
+
+/// File format of a comment or suggestion report.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    /// Comma-separated values with a header row, for spreadsheets
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
 // ===== GET PROTOCOL =====
 
 /// MCP-facing request (accepts agentRole from Claude/mock agents).
@@ -115,9 +243,22 @@ mod option_string_or_i64 {
 #[serde(rename_all = "camelCase")]
 pub struct GetProtocolRequest {
     /// The run ID (UUID) for the active Hotwired workflow
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
     /// Your role in the workflow (e.g., "strategist", "builder")
     pub agent_role: String,
+    /// Bypass the local protocol cache and fetch from the backend (default false).
+    /// Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    pub refresh: Option<bool>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
 /// Socket-facing request (sends role to hotwired-core).
@@ -138,12 +279,44 @@ impl From<&GetProtocolRequest> for GetProtocolSocketRequest {
 }
 
 /// Role capabilities for an agent
-#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct RoleCapabilities {
     /// Whether this role can resolve impediments raised by other agents
     #[serde(default)]
     pub can_resolve_impediments: bool,
+    /// Whether this role can create and edit documents and accept or reject
+    /// suggestions. Allowed unless the backend says otherwise.
+    #[serde(default = "allowed")]
+    pub can_edit_artifacts: bool,
+    /// Whether this role can request that the run end. Allowed unless the
+    /// backend says otherwise.
+    #[serde(default = "allowed")]
+    pub can_end_run: bool,
+    /// Whether this role can reject comments made by other roles. Allowed
+    /// unless the backend says otherwise.
+    #[serde(default = "allowed")]
+    pub can_reject_comments: bool,
+    /// Whether this role can reject or address comment threads it started.
+    /// Allowed unless the backend says otherwise.
+    #[serde(default = "allowed")]
+    pub can_close_own_comments: bool,
+}
+
+fn allowed() -> bool {
+    true
+}
+
+impl Default for RoleCapabilities {
+    fn default() -> Self {
+        Self {
+            can_resolve_impediments: false,
+            can_edit_artifacts: true,
+            can_end_run: true,
+            can_reject_comments: true,
+            can_close_own_comments: true,
+        }
+    }
 }
 
 /// Response with protocol content.
@@ -164,279 +337,1475 @@ pub struct GetProtocolResponse {
     /// Capabilities for this role (e.g., can resolve impediments)
     #[serde(default)]
     pub capabilities: Option<RoleCapabilities>,
+    /// Changes whenever the run's protocol is updated; used to revalidate cached protocols
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+    /// Extra MCP servers the playbook relies on; bridged by this server
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mcp_servers: Vec<DownstreamServer>,
 }
 
-// ===== GET RUN STATUS =====
+/// An MCP server declared by a playbook, started over stdio.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DownstreamServer {
+    /// Namespace for the server's tools, which are exposed as `<name>__<tool>`
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: std::collections::BTreeMap<String, String>,
+}
+
+// ===== PROTOCOL STEPS =====
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct GetRunStatusRequest {
-    /// The run ID (UUID) to check status for
+pub struct ProtocolStepsListRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
-/// A connected agent in a run (matches hotwired-core's ConnectedAgent)
+/// One step of a role's playbook.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct ConnectedAgent {
-    /// The playbook role ID (e.g., "strategist", "builder")
-    pub role_id: String,
-    /// The Zellij session name
-    pub session_name: String,
-    /// The agent type (e.g., "claude", "gemini")
-    pub agent_type: String,
+pub struct ProtocolStep {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub completed: bool,
+    #[serde(default)]
+    pub completed_at: Option<String>,
+    /// Note left when the step was completed
+    #[serde(default)]
+    pub note: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct GetRunStatusResponse {
+pub struct ProtocolStepsListResponse {
     pub run_id: String,
-    pub status: String,
-    pub phase: String,
-    pub template_name: String,
-    pub has_protocol: bool,
-    /// Connected agents with their roles
-    #[serde(default)]
-    pub connected_agents: Vec<ConnectedAgent>,
+    pub role: String,
+    /// In playbook order
+    pub steps: Vec<ProtocolStep>,
 }
 
-// ===== REPORT STATUS =====
-
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct ReportStatusRequest {
+pub struct ProtocolStepCompleteRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
-    /// Your current working status: working, thinking, waiting, idle, or complete
-    pub status: String,
-    pub message: String,
-    /// Your agent role (e.g., "strategist", "builder")
-    pub source: String,
-    /// Optional agent status for UI indicators (active, awaiting_response, blocked, idle)
-    /// Note: hotwired-core may ignore this field
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub agent_status: Option<String>,
+    /// Step ID from protocol_steps_list
+    pub step_id: String,
+    /// What was done, shown next to the step on the dashboard
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct ReportStatusResponse {
+pub struct ProtocolStepCompleteResponse {
     pub success: bool,
+    /// Steps of the role completed so far
+    pub completed: i64,
+    pub total: i64,
 }
 
-// ===== SEND MESSAGE =====
+// ===== WORK SUMMARIES =====
 
-/// Send a message event.
-/// Note: This maps to create_event on the socket server side.
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct SendMessageRequest {
+pub struct PostSummaryRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
-    pub content: String,
-    /// Your agent role (e.g., "strategist", "builder")
-    pub source: String,
-    /// Optional target agent or "human" for the message
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub target: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub agent_status: Option<String>,
+    /// What you have done so far, what you are doing now, and what is left.
+    /// Replaces your previous summary, so include anything still relevant.
+    #[schemars(length(max = 4000))]
+    pub summary: String,
+    /// Artifact ID of the document you are working on, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_artifact: Option<String>,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct SendMessageResponse {
-    pub success: bool,
-    pub event_id: String,
+pub struct PostSummaryResponse {
+    pub summary_id: String,
+    pub created_at: String,
 }
 
-// ===== TASK COMPLETE =====
-
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct TaskCompleteRequest {
+pub struct GetLatestSummaryRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
-    /// Description of the task that was completed
-    pub task_description: String,
-    /// Your agent role (e.g., "strategist", "builder")
-    pub source: String,
-    /// Optional outcome description or result summary
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub outcome: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub agent_status: Option<String>,
+    /// Whose summary to get; the latest from any role if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// An agent's own account of its work, as of `created_at`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkSummary {
+    pub id: String,
+    pub role: String,
+    pub summary: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_artifact: Option<String>,
+    pub created_at: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct TaskCompleteResponse {
-    pub success: bool,
+pub struct GetLatestSummaryResponse {
+    pub run_id: String,
+    /// None if no summary was posted yet
+    pub summary: Option<WorkSummary>,
 }
 
-// ===== REPORT IMPEDIMENT =====
+// ===== RUN CONTEXT =====
 
-/// A single option for radio/checkbox/select fields.
-#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+/// Facts about the project a run works in, shared between its agents.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-pub struct ResponseFormatOption {
-    pub value: String,
-    pub label: String,
+pub struct RunContext {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<String>,
+    /// Anything else worth knowing, by name
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub env: std::collections::BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
 }
 
-/// A field in the response format form.
 #[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct ResponseFormatField {
-    pub id: String,
-    /// Field type: radio, checkbox, select, text, textarea
-    #[serde(rename = "type")]
-    pub field_type: String,
-    pub label: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub required: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub options: Option<Vec<ResponseFormatOption>>,
+pub struct SetRunContextRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Absolute path of the worktree the run works in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_path: Option<String>,
+    /// Git branch the run's work goes on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Command that runs the project's tests (e.g., "cargo test --workspace")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_command: Option<String>,
+    /// Package manager the project uses (e.g., "pnpm", "cargo", "uv")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_manager: Option<String>,
+    /// Other facts by name (e.g., "build_command", "node_version"). Merged
+    /// into those already set; an empty value removes the entry.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub env: std::collections::BTreeMap<String, String>,
+    /// Fill worktree_path, branch, and package_manager from this server's
+    /// project directory where not given. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    pub detect: Option<bool>,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
-/// Schema defining how the human should respond to an impediment.
-#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct ResponseFormat {
-    pub fields: Vec<ResponseFormatField>,
+pub struct SetRunContextResponse {
+    pub run_id: String,
+    /// The context after the update
+    pub context: RunContext,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct ReportImpedimentRequest {
+pub struct GetRunContextRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
-    /// Type of impediment: missing_information, permission_needed, technical_error, unclear_requirements, dependency_blocked, or other
-    pub impediment_type: String,
-    /// Clear description of what is blocking progress
-    pub description: String,
-    /// Your agent role (e.g., "strategist", "builder")
-    pub source: String,
-    /// Additional context about the impediment
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub context: Option<String>,
-    /// Your suggested resolution if you have one
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub suggestion: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub agent_status: Option<String>,
-    /// Optional schema defining how the human should respond.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub response_format: Option<ResponseFormat>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct ReportImpedimentResponse {
-    pub success: bool,
+pub struct GetRunContextResponse {
+    pub run_id: String,
+    pub context: RunContext,
 }
 
-// ===== RESOLVE IMPEDIMENT (Agent) =====
+// ===== RUN CONFIG =====
 
-/// Resolve an impediment raised by another agent.
-/// The response must match the impediment's responseFormat options if defined.
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct ResolveImpedimentRequest {
+pub struct GetRunConfigRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
-    /// The event ID of the impediment to resolve
-    #[serde(deserialize_with = "string_or_i64::deserialize")]
-    pub impediment_id: i64,
-    /// The resolution response - must match responseFormat options if defined
-    pub response: serde_json::Value,
-    /// Who is resolving (agent role)
-    pub source: String,
-    /// Optional rationale for why this agent is resolving the impediment
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub rationale: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// How a run was started: the brief as the human gave it and what they
+/// chose in the setup wizard. Fixed for the life of the run.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRunConfigResponse {
+    pub run_id: String,
+    pub playbook_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playbook_name: Option<String>,
+    /// What the human asked for
+    pub intent: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    /// Artifacts chosen to create or work from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub selected_artifacts: Vec<HotwireArtifact>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initialization_condition: Option<String>,
+    /// Answers given in the setup wizard, in the order asked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wizard_answers: Vec<WizardAnswer>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct ResolveImpedimentResponse {
-    pub success: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<String>,
+pub struct WizardAnswer {
+    pub question: String,
+    pub answer: String,
 }
 
-// ===== REQUEST INPUT =====
+// ===== USAGE =====
 
-#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+/// Token and tool-call usage. As a tool argument it is one turn's delta; as
+/// sent to the backend it is the sum of the deltas accumulated since the last
+/// flush for one run, role, and model.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct RequestInputRequest {
+pub struct ReportUsageRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
-    /// The question to ask the human user
-    pub question: String,
-    /// Your agent role (e.g., "strategist", "builder")
-    pub source: String,
-    /// Additional context to help the human understand the question
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub context: Option<String>,
-    /// Optional list of choices for multiple-choice questions
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub options: Option<Vec<String>>,
+    /// Model that consumed the tokens (e.g., "claude-sonnet-4")
+    pub model: String,
+    /// Input tokens used since your last report
+    #[serde(default)]
+    #[schemars(range(min = 0))]
+    pub input_tokens: i64,
+    /// Output tokens used since your last report
+    #[serde(default)]
+    #[schemars(range(min = 0))]
+    pub output_tokens: i64,
+    /// Tool calls made since your last report
+    #[serde(default)]
+    #[schemars(range(min = 0))]
+    pub tool_calls: i64,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct RequestInputResponse {
+pub struct ReportUsageResponse {
     pub success: bool,
 }
 
-// ===== HANDOFF =====
-
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct HandoffRequest {
+pub struct GetRunUsageRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
-    /// The target agent role to hand off to (e.g., "builder", "strategist")
-    pub to: String,
-    /// Brief summary of what is being handed off
-    pub summary: String,
-    /// Your agent role (e.g., "strategist", "builder")
-    pub source: String,
-    /// Detailed information about the handoff
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub details: Option<String>,
-    /// List of relevant file paths or artifact references
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub artifacts: Option<Vec<String>>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Usage of one role with one model over the whole run.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct HandoffResponse {
-    pub success: bool,
+pub struct UsageTotals {
+    pub role: String,
+    pub model: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub tool_calls: i64,
+    /// Estimated cost, if the backend knows the model's pricing
+    #[serde(default)]
+    pub cost_usd: Option<f64>,
 }
 
-// ===== REQUEST END RUN =====
-
-#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct SuggestedFollowUp {
-    pub title: String,
-    pub description: String,
+pub struct GetRunUsageResponse {
+    pub run_id: String,
+    pub usage: Vec<UsageTotals>,
 }
 
+// ===== GET RUN STATUS =====
+
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct RequestEndRunRequest {
+pub struct GetRunStatusRequest {
+    /// The run ID (UUID) to check status for
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
-    /// Reason for ending the run: completed, scope_changed, pause_requested, or error
-    pub reason: String,
-    /// Detailed description of why the run should end
-    pub description: String,
-    /// Your agent role (e.g., "strategist", "builder")
-    pub source: String,
-    /// Optional suggested follow-up task after this run ends
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub suggested_follow_up: Option<SuggestedFollowUp>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// A connected agent in a run (matches hotwired-core's ConnectedAgent)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectedAgent {
+    /// The playbook role ID (e.g., "strategist", "builder")
+    pub role_id: String,
+    /// The Zellij session name
+    pub session_name: String,
+    /// The agent type (e.g., "claude", "gemini")
+    pub agent_type: String,
+    /// Where the agent last said it was, from report_status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<StatusProgress>,
+}
+
+// ===== AGENT CAPABILITIES =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAgentCapabilitiesRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// A connected agent and the capabilities it declared at registration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCapabilityInfo {
+    pub role_id: String,
+    pub session_name: String,
+    pub agent_type: String,
+    /// `None` if the agent registered without declaring capabilities
+    #[serde(default)]
+    pub capabilities: Option<AgentCapabilities>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAgentCapabilitiesResponse {
+    pub agents: Vec<AgentCapabilityInfo>,
+}
+
+// ===== NOTIFICATIONS =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetNotificationsRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Also return notifications you already acknowledged (default false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_acknowledged: Option<bool>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// Something in an agent's inbox that needs its attention.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub id: String,
+    /// What happened: "comment", "mention", "handoff", "answer", "message", ...
+    pub kind: String,
+    /// Role (or "human") that caused it
+    #[serde(default)]
+    pub from: Option<String>,
+    /// One-line summary
+    pub summary: String,
+    /// Artifact the notification is about, if any
+    #[serde(default)]
+    pub artifact_id: Option<String>,
+    /// Comment the notification is about, if any
+    #[serde(default)]
+    pub comment_id: Option<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub acknowledged: bool,
+    /// Message event the notification is about, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    /// Priority of the message, for message notifications
+    #[serde(default, skip_serializing_if = "MessagePriority::is_normal")]
+    pub priority: MessagePriority,
+    /// Whether the sender asked for an ack_message
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub requires_ack: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetNotificationsResponse {
+    pub run_id: String,
+    pub role: String,
+    pub notifications: Vec<Notification>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AckNotificationRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// IDs from get_notifications to mark as handled
+    #[schemars(length(min = 1))]
+    pub notification_ids: Vec<String>,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AckNotificationResponse {
+    pub success: bool,
+    /// How many of the IDs were still unacknowledged
+    pub acknowledged: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRunStatusResponse {
+    pub run_id: String,
+    pub status: RunStatus,
+    /// Playbook-defined phase, e.g. "building"
+    pub phase: String,
+    pub template_name: String,
+    pub has_protocol: bool,
+    /// Connected agents with their roles
+    #[serde(default)]
+    pub connected_agents: Vec<ConnectedAgent>,
+    /// Current protocol version (see [`GetProtocolResponse::protocol_version`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+}
+
+// ===== REPORT STATUS =====
+
+/// Working status reported by an agent.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkStatus {
+    Working,
+    Thinking,
+    Waiting,
+    Idle,
+    Complete,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportStatusRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Your current working status
+    pub status: WorkStatus,
+    /// What you are doing right now
+    pub message: String,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Optional agent status for UI indicators (active, awaiting_response, blocked, idle)
+    /// Note: hotwired-core may ignore this field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_status: Option<String>,
+    /// Where you are, machine-readable: the artifact and section you are on,
+    /// how far along you are, and what you are waiting for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<StatusProgress>,
+}
+
+/// Structured part of a status report, so the dashboard and other agents
+/// don't have to parse it out of the message.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusProgress {
+    /// ID of the artifact you are working on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_id: Option<String>,
+    /// Section (heading) of that artifact you are in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+    /// How much of the current task is done, 0-100
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(max = 100))]
+    pub percent_complete: Option<u8>,
+    /// What you are waiting for before you can go on (an agent, an answer, a build)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocking_on: Option<String>,
+}
+
+impl StatusProgress {
+    pub fn is_empty(&self) -> bool {
+        *self == StatusProgress::default()
+    }
+
+    /// One line, e.g. "40% · `spec` § Caching · blocked on: reviewer".
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(percent) = self.percent_complete {
+            parts.push(format!("{}%", percent));
+        }
+        match (&self.artifact_id, &self.section) {
+            (Some(artifact), Some(section)) => parts.push(format!("`{}` § {}", artifact, section)),
+            (Some(artifact), None) => parts.push(format!("`{}`", artifact)),
+            (None, Some(section)) => parts.push(format!("§ {}", section)),
+            (None, None) => {}
+        }
+        if let Some(blocking_on) = &self.blocking_on {
+            parts.push(format!("blocked on: {}", blocking_on));
+        }
+        parts.join(" · ")
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportStatusResponse {
+    pub success: bool,
+}
+
+// ===== SEND MESSAGE =====
+
+/// Send a message event.
+/// Note: This maps to create_event on the socket server side.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SendMessageRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    pub content: String,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Optional target agent or "human" for the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Several target agents at once, instead of one send per agent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// Deliver to every other agent in the run. Leave target and targets out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broadcast: Option<bool>,
+    /// Event ID of the message this replies to, as shown by get_messages.
+    /// The reply is threaded under it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to_event_id: Option<String>,
+    /// How urgent the message is (default "normal"). High and urgent
+    /// messages are listed first in the recipients' notifications
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<MessagePriority>,
+    /// Ask each recipient to confirm with ack_message that they have acted
+    /// on it. Needs target, targets, or broadcast
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requires_ack: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_status: Option<String>,
+}
+
+/// How urgent a message is.
+#[derive(
+    Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum MessagePriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl MessagePriority {
+    /// The name agents pass as priority.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MessagePriority::Low => "low",
+            MessagePriority::Normal => "normal",
+            MessagePriority::High => "high",
+            MessagePriority::Urgent => "urgent",
+        }
+    }
+
+    pub fn is_normal(&self) -> bool {
+        *self == MessagePriority::Normal
+    }
+
+    /// Marker put in front of high and urgent messages.
+    pub fn marker(self) -> &'static str {
+        match self {
+            MessagePriority::Urgent => "🚨 ",
+            MessagePriority::High => "❗ ",
+            MessagePriority::Normal | MessagePriority::Low => "",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SendMessageResponse {
+    pub success: bool,
+    pub event_id: String,
+    /// Roles the message was delivered to, if the backend reports them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipients: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMessageDeliveriesRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Event ID returned by send_message
+    pub event_id: String,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// Where one recipient's copy of a message is: in its inbox until it
+/// acknowledges the notification.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageDelivery {
+    pub recipient: String,
+    pub delivered_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledged_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMessageDeliveriesResponse {
+    pub run_id: String,
+    pub event_id: String,
+    pub from: String,
+    #[serde(default)]
+    pub broadcast: bool,
+    #[serde(default, skip_serializing_if = "MessagePriority::is_normal")]
+    pub priority: MessagePriority,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub requires_ack: bool,
+    pub deliveries: Vec<MessageDelivery>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AckMessageRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Event IDs of the messages you have acted on, from get_notifications or get_messages
+    #[schemars(length(min = 1))]
+    pub event_ids: Vec<String>,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AckMessageResponse {
+    pub success: bool,
+    /// How many of the messages were still unacknowledged by you
+    pub acknowledged: i64,
+}
+
+// ===== TASK COMPLETE =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskCompleteRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Description of the task that was completed
+    pub task_description: String,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Optional outcome description or result summary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_status: Option<String>,
+    /// What the task produced: artifact IDs, test runs, and links (PRs, commits, CI runs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<TaskResults>,
+}
+
+/// Structured results of a completed task, so a run's retrospective can be
+/// built from them instead of from prose.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskResults {
+    /// IDs of the artifacts the task created or changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifact_ids: Vec<String>,
+    /// Test runs and how they went
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<TestRun>,
+    /// Links to what the task produced elsewhere
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<TaskLink>,
+}
+
+impl TaskResults {
+    pub fn is_empty(&self) -> bool {
+        self.artifact_ids.is_empty() && self.tests.is_empty() && self.links.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TestRun {
+    /// The command that was run (e.g., "cargo test --workspace")
+    pub command: String,
+    /// Whether it passed
+    pub passed: bool,
+    /// Counts or failures worth keeping (e.g., "412 passed, 3 skipped")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskLink {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskCompleteResponse {
+    pub success: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCompletedTasksRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Only tasks completed by this role; every role's if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// A task reported with `task_complete`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletedTask {
+    pub id: String,
+    pub role: String,
+    pub task_description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<TaskResults>,
+    pub completed_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCompletedTasksResponse {
+    pub run_id: String,
+    /// Oldest first
+    pub tasks: Vec<CompletedTask>,
+}
+
+// ===== REPORT IMPEDIMENT =====
+
+/// A single option for radio/checkbox/select fields.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseFormatOption {
+    pub value: String,
+    pub label: String,
+}
+
+/// A field in the response format form.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseFormatField {
+    pub id: String,
+    /// Field type: radio, checkbox, select, text, textarea
+    #[serde(rename = "type")]
+    pub field_type: String,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<ResponseFormatOption>>,
+}
+
+/// Field types the UI can render.
+pub const RESPONSE_FIELD_TYPES: [&str; 5] = ["radio", "checkbox", "select", "text", "textarea"];
+
+/// Schema defining how the human should respond to an impediment.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseFormat {
+    pub fields: Vec<ResponseFormatField>,
+}
+
+impl ResponseFormat {
+    pub fn builder() -> ResponseFormatBuilder {
+        ResponseFormatBuilder::default()
+    }
+}
+
+/// Builds a `ResponseFormat` one field at a time. `required` and
+/// `description` apply to the field added last.
+#[derive(Debug, Default)]
+pub struct ResponseFormatBuilder {
+    fields: Vec<ResponseFormatField>,
+}
+
+impl ResponseFormatBuilder {
+    fn field(mut self, field_type: &str, id: &str, label: &str, options: &[(&str, &str)]) -> Self {
+        self.fields.push(ResponseFormatField {
+            id: id.to_string(),
+            field_type: field_type.to_string(),
+            label: label.to_string(),
+            description: None,
+            required: None,
+            options: (!options.is_empty()).then(|| {
+                options
+                    .iter()
+                    .map(|(value, label)| ResponseFormatOption {
+                        value: value.to_string(),
+                        label: label.to_string(),
+                    })
+                    .collect()
+            }),
+        });
+        self
+    }
+
+    /// One choice out of `options`, given as (value, label) pairs.
+    pub fn radio(self, id: &str, label: &str, options: &[(&str, &str)]) -> Self {
+        self.field("radio", id, label, options)
+    }
+
+    /// Any number of `options`.
+    pub fn checkbox(self, id: &str, label: &str, options: &[(&str, &str)]) -> Self {
+        self.field("checkbox", id, label, options)
+    }
+
+    /// One choice out of `options`, as a dropdown.
+    pub fn select(self, id: &str, label: &str, options: &[(&str, &str)]) -> Self {
+        self.field("select", id, label, options)
+    }
+
+    pub fn text(self, id: &str, label: &str) -> Self {
+        self.field("text", id, label, &[])
+    }
+
+    pub fn textarea(self, id: &str, label: &str) -> Self {
+        self.field("textarea", id, label, &[])
+    }
+
+    pub fn required(mut self) -> Self {
+        if let Some(field) = self.fields.last_mut() {
+            field.required = Some(true);
+        }
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        if let Some(field) = self.fields.last_mut() {
+            field.description = Some(description.to_string());
+        }
+        self
+    }
+
+    pub fn build(self) -> ResponseFormat {
+        ResponseFormat {
+            fields: self.fields,
+        }
+    }
+}
+
+/// Kind of blocker reported via report_impediment.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImpedimentType {
+    MissingInformation,
+    PermissionNeeded,
+    TechnicalError,
+    UnclearRequirements,
+    DependencyBlocked,
+    Other,
+}
+
+impl ImpedimentType {
+    /// The name agents pass as impediment_type.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImpedimentType::MissingInformation => "missing_information",
+            ImpedimentType::PermissionNeeded => "permission_needed",
+            ImpedimentType::TechnicalError => "technical_error",
+            ImpedimentType::UnclearRequirements => "unclear_requirements",
+            ImpedimentType::DependencyBlocked => "dependency_blocked",
+            ImpedimentType::Other => "other",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportImpedimentRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Type of impediment. Required unless template_id is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub impediment_type: Option<ImpedimentType>,
+    /// Clear description of what is blocking progress. With template_id, the
+    /// specifics that follow the template's description
+    pub description: String,
+    /// ID of an impediment template from list_impediment_templates. Fills in
+    /// the type, a description lead-in, and the response form you leave out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Additional context about the impediment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Your suggested resolution if you have one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_status: Option<String>,
+    /// Optional schema defining how the human should respond. Needs at least
+    /// one field; field ids must be unique, and radio and select fields need options
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+    /// If still unresolved after this many seconds, the impediment's urgency is
+    /// raised and the human is notified again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 60, max = 604800), example = 1800)]
+    pub escalate_after_seconds: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportImpedimentResponse {
+    pub success: bool,
+}
+
+// ===== IMPEDIMENT TEMPLATES =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListImpedimentTemplatesRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// A common blocker, worded the way the run's playbook wants it raised.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpedimentTemplate {
+    pub id: String,
+    pub title: String,
+    pub impediment_type: ImpedimentType,
+    /// Lead-in for the description; the agent adds the specifics
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<ResponseFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ListImpedimentTemplatesResponse {
+    pub run_id: String,
+    /// The run's playbook, whose templates these are
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playbook_id: Option<String>,
+    pub templates: Vec<ImpedimentTemplate>,
+}
+
+// ===== GET IMPEDIMENTS =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetImpedimentsRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Role whose impediments to list. Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Also list resolved impediments (default false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_resolved: Option<bool>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// A reported impediment and how long it has been in its current state.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpedimentState {
+    /// Event ID, as taken by resolve_impediment
+    pub id: i64,
+    /// One of the report_impediment types, e.g. "missing_information"
+    pub impediment_type: String,
+    pub description: String,
+    pub raised_by: String,
+    /// "open", "escalated", or "resolved"
+    pub status: String,
+    /// Times the impediment was escalated
+    #[serde(default)]
+    pub escalation_level: i64,
+    pub created_at: String,
+    /// When the impediment entered its current status
+    pub status_since: String,
+    /// When it escalates next, if an escalation is scheduled
+    #[serde(default)]
+    pub escalate_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetImpedimentsResponse {
+    pub run_id: String,
+    pub impediments: Vec<ImpedimentState>,
+}
+
+// ===== GET IMPEDIMENT RESOLUTION =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetImpedimentResolutionRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Event ID of the impediment, as listed by get_impediments
+    #[serde(deserialize_with = "string_or_i64::deserialize")]
+    #[schemars(range(min = 0))]
+    pub impediment_id: i64,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// How an impediment was resolved, once it has been.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetImpedimentResolutionResponse {
+    pub impediment_id: i64,
+    /// "open", "escalated", or "resolved"
+    pub status: String,
+    /// The form the impediment asked the human to fill in, if any
+    #[serde(default)]
+    pub response_format: Option<ResponseFormat>,
+    /// The resolution: an object keyed by response_format field IDs, or free text
+    #[serde(default)]
+    pub response: Option<serde_json::Value>,
+    #[serde(default)]
+    pub resolved_by: Option<String>,
+    #[serde(default)]
+    pub resolved_at: Option<String>,
+    #[serde(default)]
+    pub rationale: Option<String>,
+}
+
+// ===== RESOLVE IMPEDIMENT (Agent) =====
+
+/// Resolve an impediment raised by another agent.
+/// The response must match the impediment's responseFormat options if defined.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveImpedimentRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// The event ID of the impediment to resolve
+    #[serde(deserialize_with = "string_or_i64::deserialize")]
+    #[schemars(range(min = 0))]
+    pub impediment_id: i64,
+    /// The resolution response - must match responseFormat options if defined
+    pub response: serde_json::Value,
+    /// Who is resolving (agent role). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Optional rationale for why this agent is resolving the impediment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveImpedimentResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// ===== REQUEST INPUT =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestInputRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// The question to ask the human user
+    pub question: String,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Additional context to help the human understand the question
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Optional list of choices for multiple-choice questions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestInputResponse {
+    pub success: bool,
+    /// ID to wait on with await_input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+// ===== AWAIT INPUT =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AwaitInputRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Request ID returned by request_input
+    pub request_id: String,
+    /// How long to wait for the answer, in seconds (default 300)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1, max = 900), example = 300)]
+    pub timeout_seconds: Option<i64>,
+}
+
+/// State of one `request_input` question.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InputStatusRequest {
+    pub run_id: String,
+    pub request_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InputStatusResponse {
+    pub request_id: String,
+    pub answered: bool,
+    /// The human's answer, once answered
+    #[serde(default)]
+    pub answer: Option<String>,
+    #[serde(default)]
+    pub answered_by: Option<String>,
+    #[serde(default)]
+    pub answered_at: Option<String>,
+}
+
+// ===== HANDOFF =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// The target agent role to hand off to (e.g., "builder", "strategist")
+    pub to: String,
+    /// Brief summary of what is being handed off
+    pub summary: String,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Detailed information about the handoff
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// List of relevant file paths or artifact references
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifacts: Option<Vec<String>>,
+    /// Tasks being handed off, which the receiver sees as a checklist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tasks: Option<Vec<HandoffTask>>,
+}
+
+/// One task in a handoff.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffTask {
+    /// Short ID, unique within the handoff (e.g. "T1", "cache-layer")
+    pub id: String,
+    /// What to do
+    pub title: String,
+    /// Conditions that must hold for the task to be done
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub acceptance_criteria: Vec<String>,
+    /// Artifact IDs or file paths the task is about
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffResponse {
+    pub success: bool,
+}
+
+// ===== GET HANDOFFS =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetHandoffsRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Role whose received handoffs to list. Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// A handoff received by a role.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HandoffRecord {
+    pub id: String,
+    /// Role that handed off
+    pub from: String,
+    pub summary: String,
+    #[serde(default)]
+    pub details: Option<String>,
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+    #[serde(default)]
+    pub tasks: Vec<HandoffTask>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetHandoffsResponse {
+    pub run_id: String,
+    pub role: String,
+    /// Oldest first
+    pub handoffs: Vec<HandoffRecord>,
+}
+
+// ===== SWITCH ROLE =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchRoleRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// The role to take over (e.g., "builder")
+    pub new_role: String,
+    /// Your current role. Defaults to the role you are attached with, then `default_source` from config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Why you are switching (e.g., "builder disconnected")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Response format for the new protocol: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchRoleResponse {
+    pub success: bool,
+    /// Set when the backend refused the switch (e.g., the role is held by a connected agent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// ===== REQUEST END RUN =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedFollowUp {
+    pub title: String,
+    pub description: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestEndRunRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Reason for ending the run: completed, scope_changed, pause_requested, or error
+    pub reason: String,
+    /// Detailed description of why the run should end
+    pub description: String,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Optional suggested follow-up task after this run ends
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_follow_up: Option<SuggestedFollowUp>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct RequestEndRunResponse {
     pub success: bool,
@@ -448,13 +1817,16 @@ pub struct RequestEndRunResponse {
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct RespondToEndRequestRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
     /// The ID of the end request to respond to
     pub request_id: String,
     /// Your response: agree, disagree, or verify
     pub response: String,
-    /// Your agent role (e.g., "strategist", "builder")
-    pub source: String,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
     /// Optional explanation for your response
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
@@ -466,6 +1838,213 @@ pub struct RespondToEndRequestResponse {
     pub success: bool,
 }
 
+// ===== CREATE EVENT =====
+
+/// Event type for tool failures forwarded to the dashboard.
+pub const TOOL_ERROR_EVENT: &str = "tool_error";
+
+/// Generic run event (maps to create_event on the socket server).
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateEventRequest {
+    pub run_id: String,
+    /// Event type (e.g., "tool_error")
+    pub event_type: String,
+    /// Agent role that produced the event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub content: String,
+    /// Structured event details
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateEventResponse {
+    pub success: bool,
+    #[serde(default)]
+    pub event_id: Option<String>,
+}
+
+// ===== CONVERSATION EVENTS =====
+
+/// A run's event timeline (maps to get_conversation_events on the socket server).
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetConversationEventsRequest {
+    pub run_id: String,
+}
+
+/// One entry in a run's timeline: a message, status report, handoff, etc.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunEvent {
+    pub id: String,
+    pub event_type: String,
+    /// Agent role (or "human") that produced the event
+    pub source: Option<String>,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    /// For messages: the message this one replies to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to_event_id: Option<String>,
+    /// For messages: how urgent the sender marked it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<MessagePriority>,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetConversationEventsResponse {
+    pub run_id: String,
+    /// Oldest first
+    pub events: Vec<RunEvent>,
+}
+
+// ===== GET MESSAGES =====
+
+/// MCP-only request: a run's messages as threads.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMessagesRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Event ID of any message in a thread, to get only that thread
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// A message in thread order: after the message it replies to and that
+/// message's earlier replies.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadedMessage {
+    /// Event ID of the thread's first message
+    pub thread_id: String,
+    /// Replies between this message and the first one
+    pub depth: usize,
+    #[serde(flatten)]
+    pub event: RunEvent,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageThreads {
+    pub run_id: String,
+    pub threads: usize,
+    pub messages: Vec<ThreadedMessage>,
+}
+
+// ===== EXPORT RUN =====
+
+/// MCP-only request: write a run's timeline, artifacts, comments, and
+/// suggestions to a directory.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRunRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Directory to write the bundle to, relative to the project directory.
+    /// Default: hotwired-export-<run_id>
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+    /// Also pack the directory into <output_dir>.zip (needs the `zip` command). Default: false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("default" = false))]
+    pub zip: Option<bool>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+/// One artifact as written to an export.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedArtifact {
+    /// Path of the document, relative to the export directory
+    pub path: String,
+    pub total_lines: i64,
+    pub comments: usize,
+    pub suggestions: usize,
+}
+
+/// What export_run wrote.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RunExport {
+    pub run_id: String,
+    pub directory: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archive: Option<String>,
+    pub events: usize,
+    pub artifacts: Vec<ArtifactOutcome<ExportedArtifact>>,
+}
+
+// ===== DUMP EVENTS =====
+
+/// MCP-only request: write a run's event stream to a JSONL file.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpEventsRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// File to write, relative to the project directory.
+    /// Default: hotwired-events-<run_id>.jsonl
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_file: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+/// One line of a dumped event stream. Every field is always written, null
+/// when unknown; any change to the fields bumps `schema`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventRecord {
+    /// Schema version of this record
+    pub schema: u32,
+    pub run_id: String,
+    /// Position in the run's timeline, from 0
+    pub seq: usize,
+    pub id: String,
+    pub event_type: String,
+    pub source: Option<String>,
+    pub content: String,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: String,
+}
+
+/// What dump_events wrote.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDump {
+    pub run_id: String,
+    pub path: String,
+    pub schema: u32,
+    pub events: usize,
+    /// Events per type, most frequent first
+    pub event_types: Vec<(String, usize)>,
+    pub first_at: Option<String>,
+    pub last_at: Option<String>,
+}
+
 // =============================================================================
 // DOC ARTIFACT MESSAGES
 // =============================================================================
@@ -475,7 +2054,24 @@ pub struct RespondToEndRequestResponse {
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DocArtifactListRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Only artifacts with this tag (case-insensitive). Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"adr")]
+    pub tag: Option<String>,
+    /// Only artifacts of this document type, e.g. "spec". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    pub document_type: Option<String>,
+    /// Response format: "markdown" (default), "compact", "json", or "table". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
 /// Artifact summary for listing
@@ -489,6 +2085,9 @@ pub struct ArtifactSummary {
     pub content_hash: String,
     pub created_at: String,
     pub updated_at: String,
+    /// Labels for finding the artifact, lowercase
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -498,22 +2097,89 @@ pub struct DocArtifactListResponse {
     pub artifacts: Vec<ArtifactSummary>,
 }
 
+// ===== DOC ARTIFACT SET TAGS =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactSetTagsRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
+    pub artifact_id: String,
+    /// Tags to add, e.g. ["adr", "meeting-notes"]. Lowercased; no spaces.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub add: Vec<String>,
+    /// Tags to remove
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub remove: Vec<String>,
+    /// Who changed the tags (agent role). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactSetTagsResponse {
+    pub artifact_id: String,
+    /// The artifact's tags after the change
+    pub tags: Vec<String>,
+}
+
 // ===== DOC ARTIFACT READ =====
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DocArtifactReadRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Artifact ID from doc_artifact_list
     pub artifact_id: String,
     /// Line offset (0-based). Default: 0
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0), extend("default" = 0))]
     pub offset: Option<i64>,
-    /// Max lines to return. Default: 500, Max: 2000
+    /// Max lines to return. Default: 500, Max: 2000 (configurable)
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1), extend("default" = 500))]
     pub limit: Option<i64>,
     /// Include inline comment markers. Default: true
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(extend("default" = true))]
     pub include_comments: Option<bool>,
+    /// Content rendering: "plain" (default) or "numbered" (line numbers and inline
+    /// [comment-id] anchors). Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "plain"))]
+    pub render_mode: Option<ReadRenderMode>,
+    /// Your agent role, recorded as having read this version (see
+    /// get_artifact_readers). Defaults to `default_source` from config. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    pub source: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// How doc_artifact_read renders the content block.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadRenderMode {
+    /// Content as-is
+    #[default]
+    Plain,
+    /// Each line prefixed with its line number, comment anchors at the end of their line
+    Numbered,
 }
 
 /// Inline comment for read response
@@ -548,6 +2214,8 @@ pub struct DocArtifactReadResponse {
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DocArtifactCreateRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
     pub filename: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -558,6 +2226,25 @@ pub struct DocArtifactCreateRequest {
     /// Who created this artifact (agent role)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_by: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+/// What `doc_artifact_create` sends to the backend. Borrows from the tool
+/// arguments so the initial content is not copied before serialization.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactCreateBody<'a> {
+    pub run_id: &'a str,
+    pub filename: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_content: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<&'a str>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -570,14 +2257,131 @@ pub struct DocArtifactCreateResponse {
 
 // ===== DOC ARTIFACT EDIT =====
 
+/// Unit of character positions in edit, comment, and suggestion offsets.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OffsetUnit {
+    /// Unicode characters, as used by the doc-editor
+    #[default]
+    Char,
+    /// UTF-8 bytes
+    Byte,
+}
+
+/// How an edit or suggestion changes the document.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EditType {
+    /// Replace the characters between start_offset and end_offset
+    ReplaceRange,
+    /// Insert at insert_offset (edits) or start_offset (suggestions)
+    Insert,
+    /// Add to the end of the document
+    Append,
+    /// Replace the whole document
+    FullReplace,
+}
+
+/// What kind of change an edit makes, for grouping the changelog.
+#[derive(
+    Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EditCategory {
+    /// Addresses review comments (list them in related_comment_ids)
+    AddressFeedback,
+    /// Fixes something wrong
+    Correction,
+    /// Makes existing content clearer without changing its meaning
+    Clarification,
+    /// Moves or reorganizes content
+    Restructure,
+    /// Adds new content
+    Addition,
+    /// Removes content
+    Removal,
+    /// Wording, formatting, or typos
+    Style,
+    Other,
+}
+
+impl EditCategory {
+    /// Heading of the category in the changelog.
+    pub fn title(self) -> &'static str {
+        match self {
+            EditCategory::AddressFeedback => "Review feedback",
+            EditCategory::Correction => "Corrections",
+            EditCategory::Clarification => "Clarifications",
+            EditCategory::Restructure => "Restructuring",
+            EditCategory::Addition => "Additions",
+            EditCategory::Removal => "Removals",
+            EditCategory::Style => "Style",
+            EditCategory::Other => "Other changes",
+        }
+    }
+}
+
+/// Why an edit was made. A plain string is also accepted, as the summary of
+/// an edit in the "other" category.
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EditReason {
+    pub category: EditCategory,
+    /// One line describing the change (shown in the UI)
+    pub summary: String,
+    /// Comments this edit addresses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub related_comment_ids: Vec<String>,
+}
+
+impl<'de> Deserialize<'de> for EditReason {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Structured {
+            category: EditCategory,
+            summary: String,
+            #[serde(default)]
+            related_comment_ids: Vec<String>,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum TextOrStructured {
+            Text(String),
+            Structured(Structured),
+        }
+
+        Ok(match TextOrStructured::deserialize(deserializer)? {
+            TextOrStructured::Text(summary) => EditReason {
+                category: EditCategory::Other,
+                summary,
+                related_comment_ids: vec![],
+            },
+            TextOrStructured::Structured(reason) => EditReason {
+                category: reason.category,
+                summary: reason.summary,
+                related_comment_ids: reason.related_comment_ids,
+            },
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DocArtifactEditRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Artifact ID from doc_artifact_list
     pub artifact_id: String,
-    /// Edit type: replace_range, insert, append, full_replace
-    pub edit_type: String,
+    /// Edit type
+    pub edit_type: EditType,
     /// Hash from last read - required for conflict detection
+    #[schemars(example = &"a1b2c3d4e5f6")]
     pub content_hash: String,
     /// New content to write
     pub new_content: String,
@@ -587,6 +2391,7 @@ pub struct DocArtifactEditRequest {
         default,
         deserialize_with = "option_string_or_i64::deserialize"
     )]
+    #[schemars(range(min = 0), example = 120)]
     pub start_offset: Option<i64>,
     /// For replace_range: end character offset
     #[serde(
@@ -594,6 +2399,7 @@ pub struct DocArtifactEditRequest {
         default,
         deserialize_with = "option_string_or_i64::deserialize"
     )]
+    #[schemars(range(min = 0), example = 145)]
     pub end_offset: Option<i64>,
     /// For insert: insert position
     #[serde(
@@ -601,35 +2407,266 @@ pub struct DocArtifactEditRequest {
         default,
         deserialize_with = "option_string_or_i64::deserialize"
     )]
+    #[schemars(range(min = 0), example = 0)]
     pub insert_offset: Option<i64>,
-    /// Why this edit is being made (shown in UI)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub edit_reason: Option<String>,
+    /// Unit of the offsets: "char" (default) or "byte". Offsets are checked against
+    /// the current document and sent as characters. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "char"))]
+    pub offset_unit: Option<OffsetUnit>,
+    /// Why this edit is being made: category, one-line summary (shown in UI),
+    /// and the comments it addresses. Feeds doc_artifact_changelog
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit_reason: Option<EditReason>,
     /// Who made this edit (agent role)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
+    /// Check the hash and offsets and return the resulting diff without
+    /// changing the document. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = false))]
+    pub dry_run: Option<bool>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+/// What an edit or suggestion would do, computed locally by `dry_run`.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EditPreview {
+    pub artifact_id: String,
+    /// Hash of the document the preview was computed against
+    pub content_hash: String,
+    /// Whether the given content_hash matches; None for suggestions, which take no hash
+    pub hash_matches: Option<bool>,
+    pub original_chars: usize,
+    pub resulting_chars: usize,
+    /// Unified or word diff of the change
+    pub diff: String,
+}
+
+/// What `doc_artifact_edit` sends to the backend. Borrows from the tool
+/// arguments so the new content is not copied before serialization.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactEditBody<'a> {
+    pub run_id: &'a str,
+    pub artifact_id: &'a str,
+    pub edit_type: EditType,
+    pub content_hash: &'a str,
+    pub new_content: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_offset: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edit_reason: Option<&'a EditReason>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<&'a str>,
 }
 
 /// Conflict info when edit fails due to hash mismatch
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct EditConflict {
-    pub expected_hash: String,
-    pub actual_hash: String,
-    pub message: String,
+pub struct EditConflict {
+    pub expected_hash: String,
+    pub actual_hash: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactEditResponse {
+    pub success: bool,
+    pub artifact_id: String,
+    pub new_content_hash: String,
+    pub edit_id: String,
+    #[serde(default)]
+    pub affected_comments: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<EditConflict>,
+}
+
+// ===== DOC ARTIFACT LIST EDITS =====
+
+/// Edit history of an artifact (maps to doc_artifact_list_edits on the socket server).
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactListEditsRequest {
+    pub run_id: String,
+    pub artifact_id: String,
+}
+
+/// One applied edit
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EditRecord {
+    pub id: String,
+    /// Agent role (or "human") that made the edit
+    pub author: String,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit_reason: Option<EditReason>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactListEditsResponse {
+    pub artifact_id: String,
+    /// Oldest first
+    pub edits: Vec<EditRecord>,
+}
+
+/// MCP-only request: an artifact's edit history as a readable changelog.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactChangelogRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
+    pub artifact_id: String,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+// ===== DOC ARTIFACT COMMIT =====
+
+/// MCP-only request: write an artifact into the project and commit it with git.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactCommitRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
+    pub artifact_id: String,
+    /// Where to write the document, relative to the project directory. Default: the artifact's filename
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(example = &"docs/spec.md")]
+    pub path: Option<String>,
+    /// Commit subject line. Default: "Update <path> from Hotwired run"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+/// What doc_artifact_commit committed.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactCommit {
+    pub artifact_id: String,
+    /// Path relative to the project directory
+    pub path: String,
+    /// Commit hash; `None` when the file in the repository was already up to date
+    pub commit: Option<String>,
+    pub edit_ids: Vec<String>,
+    /// Roles that edited the document, in order of their first edit
+    pub agents: Vec<String>,
+}
+
+// ===== RESTORE BACKUP =====
+
+/// MCP-only request: list an artifact's local backups, or restore one.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreBackupRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
+    pub artifact_id: String,
+    /// Backup to restore. Omit to list the artifact's backups instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_id: Option<String>,
+    /// Your agent role (e.g., "strategist", "builder"). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+/// A document's content saved locally before a destructive edit.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactBackup {
+    /// Sorts by creation time
+    pub id: String,
+    pub run_id: String,
+    pub artifact_id: String,
+    pub filename: String,
+    pub content_hash: String,
+    pub chars: usize,
+    /// What the backup was taken before (e.g., "Before full_replace")
+    pub reason: String,
+    /// Role whose edit triggered the backup
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    pub created_at: String,
+    /// Left out of listings
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub content: String,
+}
+
+/// An artifact's backups, newest first, without their content.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactBackupList {
+    pub run_id: String,
+    pub artifact_id: String,
+    pub backups: Vec<ArtifactBackup>,
+}
+
+// ===== DOC ARTIFACT RENDER =====
+
+/// MCP-only request: render an artifact to a standalone HTML file.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactRenderRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
+    pub artifact_id: String,
+    /// Where to write the HTML, relative to the project directory. Default: the
+    /// artifact's filename with an .html extension
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(example = &"docs/spec.html")]
+    pub output_path: Option<String>,
+    /// Show resolved comments as margin notes next to the text they refer to. Default: false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_comments: Option<bool>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
 }
 
+/// What doc_artifact_render wrote.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactEditResponse {
-    pub success: bool,
+pub struct RenderedArtifact {
     pub artifact_id: String,
-    pub new_content_hash: String,
-    pub edit_id: String,
-    #[serde(default)]
-    pub affected_comments: Vec<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub conflict: Option<EditConflict>,
+    /// The HTML file that was written
+    pub path: String,
+    pub bytes: usize,
+    /// Resolved comments shown as margin notes
+    pub notes: usize,
 }
 
 // ===== DOC ARTIFACT SEARCH =====
@@ -637,7 +2674,10 @@ pub struct DocArtifactEditResponse {
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DocArtifactSearchRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Artifact ID from doc_artifact_list
     pub artifact_id: String,
     /// Search query (regex supported)
     pub query: String,
@@ -647,12 +2687,22 @@ pub struct DocArtifactSearchRequest {
     /// Limit search to specific markdown elements: all, headings, body, code_blocks
     #[serde(skip_serializing_if = "Option::is_none")]
     pub scope: Option<String>,
-    /// Lines of context around match. Default: 2
+    /// Lines of context around match. Default: 2 (configurable)
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0), extend("default" = 2))]
     pub context_lines: Option<i64>,
-    /// Maximum results. Default: 20
+    /// Maximum results. Default: 20 (configurable)
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1), extend("default" = 20))]
     pub max_results: Option<i64>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
 /// A search match result
@@ -679,29 +2729,58 @@ pub struct DocArtifactSearchResponse {
 
 // ===== DOC ARTIFACT ADD COMMENT =====
 
+/// Kind of inline comment.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentType {
+    Comment,
+    Question,
+    Suggestion,
+    Issue,
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DocArtifactAddCommentRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Artifact ID from doc_artifact_list
     pub artifact_id: String,
-    /// Comment type: comment, question, suggestion, issue
-    pub comment_type: String,
+    /// Comment type
+    pub comment_type: CommentType,
     /// Character offset where selection starts
     #[serde(deserialize_with = "string_or_i64::deserialize")]
+    #[schemars(range(min = 0), example = 120)]
     pub selection_start: i64,
     /// Character offset where selection ends
     #[serde(deserialize_with = "string_or_i64::deserialize")]
+    #[schemars(range(min = 0), example = 145)]
     pub selection_end: i64,
+    /// Unit of the offsets: "char" (default) or "byte". Offsets are checked against
+    /// the current document and sent as characters. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "char"))]
+    pub offset_unit: Option<OffsetUnit>,
     /// Comment text
     pub content: String,
     /// For suggestions: the proposed replacement text
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suggested_text: Option<String>,
-    /// Who created this comment (agent role)
-    pub author: String,
+    /// Who created this comment (agent role). Defaults to `default_author` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
     /// Parent comment ID for replies (creates a thread)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_comment_id: Option<String>,
+    /// Roles to notify; each gets a "mention" notification in its inbox
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(example = &["builder"])]
+    pub mentions: Option<Vec<String>>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -714,19 +2793,43 @@ pub struct DocArtifactAddCommentResponse {
 
 // ===== DOC ARTIFACT RESOLVE COMMENT =====
 
+/// What to do with a comment thread.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResolveAction {
+    /// Approve the feedback and signal the agent to address it (thread stays open)
+    Accept,
+    /// Disagree with the feedback and close the thread
+    Reject,
+    /// Continue the discussion (thread stays open)
+    Reply,
+    /// The feedback has been addressed; closes the thread
+    Address,
+    /// Legacy action, same as address
+    Resolve,
+}
+
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DocArtifactResolveCommentRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Artifact ID from doc_artifact_list
     pub artifact_id: String,
     pub comment_id: String,
-    /// Action: accept, reject, reply, address, resolve
-    pub action: String,
+    /// Action to take on the comment
+    pub action: ResolveAction,
     /// Reply text or resolution note
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response: Option<String>,
-    /// Who resolved this (agent role)
-    pub resolved_by: String,
+    /// Who resolved this (agent role). Defaults to `default_author` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -742,7 +2845,10 @@ pub struct DocArtifactResolveCommentResponse {
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct DocArtifactListCommentsRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Artifact ID from doc_artifact_list
     pub artifact_id: String,
     /// Filter by status: open, resolved, rejected, all. Default: open
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -756,171 +2862,681 @@ pub struct DocArtifactListCommentsRequest {
         default,
         deserialize_with = "option_string_or_i64::deserialize"
     )]
+    #[schemars(range(min = 1))]
     pub line_start: Option<i64>,
+    /// End of the line range filter (inclusive)
     #[serde(
         skip_serializing_if = "Option::is_none",
         default,
         deserialize_with = "option_string_or_i64::deserialize"
     )]
+    #[schemars(range(min = 1))]
     pub line_end: Option<i64>,
+    /// Only comments mentioning this role; "me" for your own role
+    #[serde(default, alias = "mentions", skip_serializing_if = "Option::is_none")]
+    #[schemars(example = &"me")]
+    pub mentioning: Option<String>,
+    /// Only comments by this role; "me" for your own role. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"reviewer")]
+    pub author: Option<String>,
+    /// Only comments created after this date, `YYYY-MM-DD` or RFC 3339. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"2024-01-15T10:30:00Z")]
+    pub created_after: Option<String>,
+    /// true: only comments someone has replied to; false: only unanswered ones.
+    /// Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    pub has_reply: Option<bool>,
+    /// Response format: "markdown" (default), "compact", "json", or "table". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+    /// Export the listed records as a "csv" or "jsonl" report instead of
+    /// rendering them. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    pub report: Option<ReportFormat>,
+    /// File for the report, relative to the project directory; returned inline
+    /// if omitted. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"reports/comments.csv")]
+    pub report_path: Option<String>,
+    /// Listed comments to skip, for the next page. Default: 0. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(range(min = 0), extend("default" = 0))]
+    pub offset: Option<i64>,
+    /// Max comments per page. Default: 100 (configurable); reports include all
+    /// unless set. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(range(min = 1), extend("default" = 100))]
+    pub limit: Option<i64>,
+}
+
+/// Full comment details for listing
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentDetail {
+    pub id: String,
+    pub comment_type: String,
+    pub status: String,
+    pub selection_start: i64,
+    pub selection_end: i64,
+    pub selection_text: Option<String>,
+    pub content: String,
+    pub suggested_text: Option<String>,
+    pub author: String,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<String>,
+    pub resolution_note: Option<String>,
+    pub created_at: String,
+    /// Roles the comment mentions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mentions: Vec<String>,
+    /// The comment this one replies to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_comment_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactListCommentsResponse {
+    pub artifact_id: String,
+    pub comments: Vec<CommentDetail>,
+    /// Comments skipped before this page
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset: i64,
+    /// More comments follow this page
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub has_more: bool,
+}
+
+fn is_zero(n: &i64) -> bool {
+    *n == 0
+}
+
+// ===== DOC ARTIFACT IMPORT REVIEW =====
+
+/// MCP-only request: turn the comments of a GitHub pull-request review into
+/// doc-artifact comments.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactImportReviewRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Path to the review export, relative to the project directory: the JSON array returned by
+    /// `gh api repos/{owner}/{repo}/pulls/{number}/comments`, or an object with a `comments` array
+    #[schemars(example = &"review.json")]
+    pub review_path: String,
+    /// Put every comment on this artifact instead of matching the comment's file path to artifact filenames
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_id: Option<String>,
+    /// Who the comments are attributed to (agent role). Defaults to `default_author` from config;
+    /// the GitHub reviewer is named in each comment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+/// A review comment that became a doc-artifact comment.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedComment {
+    pub github_id: u64,
+    pub artifact_id: String,
+    pub comment_id: String,
+    /// 1-based line the selection starts on
+    pub line: i64,
+}
+
+/// A review comment that could not be imported.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedComment {
+    pub github_id: u64,
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewImport {
+    pub run_id: String,
+    pub imported: Vec<ImportedComment>,
+    pub skipped: Vec<SkippedComment>,
+}
+
+// =============================================================================
+// EDIT SUGGESTIONS (Mode 2: Suggest with diff preview)
+// =============================================================================
+
+// ===== DOC ARTIFACT SUGGEST EDIT =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactSuggestEditRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
+    pub artifact_id: String,
+    /// The comment ID this suggestion addresses (links suggestion to comment thread)
+    pub comment_id: String,
+    /// Edit type
+    pub edit_type: EditType,
+    /// For replace_range: start character offset
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "option_string_or_i64::deserialize"
+    )]
+    #[schemars(range(min = 0), example = 120)]
+    pub start_offset: Option<i64>,
+    /// For replace_range: end character offset
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        default,
+        deserialize_with = "option_string_or_i64::deserialize"
+    )]
+    #[schemars(range(min = 0), example = 145)]
+    pub end_offset: Option<i64>,
+    /// Unit of the offsets: "char" (default) or "byte". Offsets are checked against
+    /// the current document and sent as characters. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "char"))]
+    pub offset_unit: Option<OffsetUnit>,
+    /// The suggested replacement/new text
+    pub suggested_text: String,
+    /// Rationale for the suggestion (shown to user)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<String>,
+    /// Who created this suggestion (agent role). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Content hash of the document the offsets were checked against. Filled
+    /// in by this server, so stale suggestions can be found later.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub base_content_hash: Option<String>,
+    /// Check the offsets and return the resulting diff without creating the
+    /// suggestion. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = false))]
+    pub dry_run: Option<bool>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactSuggestEditResponse {
+    pub suggestion_id: String,
+    pub comment_id: String,
+    pub artifact_id: String,
+}
+
+// ===== DOC ARTIFACT ACCEPT SUGGESTION =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactAcceptSuggestionRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
+    pub artifact_id: String,
+    pub suggestion_id: String,
+    /// Who accepted this suggestion. Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactAcceptSuggestionResponse {
+    pub success: bool,
+    pub suggestion_id: String,
+    pub new_content_hash: String,
+    /// Comments that were resolved as a result of this acceptance
+    #[serde(default)]
+    pub resolved_comments: Vec<String>,
+}
+
+// ===== DOC ARTIFACT REJECT SUGGESTION =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactRejectSuggestionRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
+    pub artifact_id: String,
+    pub suggestion_id: String,
+    /// Reason for rejecting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Who rejected this suggestion. Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactRejectSuggestionResponse {
+    pub success: bool,
+    pub suggestion_id: String,
+}
+
+// ===== DOC ARTIFACT LIST SUGGESTIONS =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactListSuggestionsRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
+    pub artifact_id: String,
+    /// Filter by status: pending, accepted, rejected, all. Default: pending
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// Response format: "markdown" (default), "compact", "json", or "table". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+    /// Export the listed records as a "csv" or "jsonl" report instead of
+    /// rendering them. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    pub report: Option<ReportFormat>,
+    /// File for the report, relative to the project directory; returned inline
+    /// if omitted. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"reports/suggestions.csv")]
+    pub report_path: Option<String>,
+    /// Listed suggestions to skip, for the next page. Default: 0. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(range(min = 0), extend("default" = 0))]
+    pub offset: Option<i64>,
+    /// Max suggestions per page. Default: 100 (configurable); reports include all
+    /// unless set. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(range(min = 1), extend("default" = 100))]
+    pub limit: Option<i64>,
+}
+
+/// Full suggestion details for listing
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionDetail {
+    pub id: String,
+    pub comment_id: String,
+    pub artifact_id: String,
+    pub suggested_by: String,
+    pub edit_type: String,
+    pub start_offset: Option<i64>,
+    pub end_offset: Option<i64>,
+    pub original_text: Option<String>,
+    pub suggested_text: String,
+    pub rationale: Option<String>,
+    pub status: String,
+    pub accepted_by: Option<String>,
+    pub accepted_at: Option<String>,
+    pub rejection_reason: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Content hash of the document when the suggestion was made; absent for
+    /// suggestions made before it was tracked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_content_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactListSuggestionsResponse {
+    pub artifact_id: String,
+    pub suggestions: Vec<SuggestionDetail>,
+    /// Suggestions skipped before this page
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset: i64,
+    /// More suggestions follow this page
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub has_more: bool,
+}
+
+// ===== SWEEP STALE SUGGESTIONS =====
+
+/// MCP-only request: find pending suggestions that no longer fit their document.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepStaleSuggestionsRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Only this artifact; every artifact in the run if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_id: Option<String>,
+    /// Reject the suggestions whose anchor text is gone instead of only listing them
+    #[serde(default)]
+    #[schemars(extend("default" = false))]
+    pub withdraw: Option<bool>,
+    /// Who withdraws the suggestions (agent role). Defaults to `default_source` from config if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+/// Why a suggestion can't be applied as it stands.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Staleness {
+    /// The text it replaces is still in the document, at other offsets
+    Moved,
+    /// The text it replaces, or the offset it inserts at, is gone
+    Missing,
+}
+
+/// A pending suggestion that no longer fits its document.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleSuggestion {
+    pub artifact_id: String,
+    pub suggestion_id: String,
+    pub comment_id: String,
+    pub suggested_by: String,
+    pub staleness: Staleness,
+    pub detail: String,
+    /// Whether the sweep rejected it
+    pub withdrawn: bool,
+    /// Why withdrawing it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of a stale-suggestion sweep.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionSweep {
+    pub run_id: String,
+    /// Pending suggestions checked
+    pub checked: usize,
+    pub stale: Vec<StaleSuggestion>,
+    /// Artifacts that could not be checked, with the reason
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<(String, String)>,
+}
+
+// ===== RUN-WIDE ARTIFACT OPERATIONS =====
+
+/// MCP-only request: search every artifact in the run.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactSearchAllRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Search query (regex supported)
+    pub query: String,
+    /// Match type: exact, regex, fuzzy. Default: exact
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_type: Option<String>,
+    /// Limit search to specific markdown elements: all, headings, body, code_blocks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Lines of context around match. Default: 2 (configurable)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 0), extend("default" = 2))]
+    pub context_lines: Option<i64>,
+    /// Maximum results per artifact. Default: 20 (configurable)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1), extend("default" = 20))]
+    pub max_results: Option<i64>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// MCP-only request: read the first page of every artifact in the run.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DocArtifactReadAllRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Max lines per artifact. Default: 500, Max: 2000 (configurable)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1), extend("default" = 500))]
+    pub limit: Option<i64>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// One artifact's outcome in a run-wide operation; a failure on one artifact
+/// does not fail the others.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactOutcome<T> {
+    pub artifact_id: String,
+    pub filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
-/// Full comment details for listing
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct CommentDetail {
-    pub id: String,
-    pub comment_type: String,
-    pub status: String,
-    pub selection_start: i64,
-    pub selection_end: i64,
-    pub selection_text: Option<String>,
-    pub content: String,
-    pub suggested_text: Option<String>,
-    pub author: String,
-    pub resolved_by: Option<String>,
-    pub resolved_at: Option<String>,
-    pub resolution_note: Option<String>,
-    pub created_at: String,
+pub struct DocArtifactSearchAllResponse {
+    pub run_id: String,
+    pub query: String,
+    pub artifacts: Vec<ArtifactOutcome<DocArtifactSearchResponse>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactListCommentsResponse {
-    pub artifact_id: String,
-    pub comments: Vec<CommentDetail>,
+pub struct DocArtifactReadAllResponse {
+    pub run_id: String,
+    pub artifacts: Vec<ArtifactOutcome<DocArtifactReadResponse>>,
 }
 
-// =============================================================================
-// EDIT SUGGESTIONS (Mode 2: Suggest with diff preview)
-// =============================================================================
-
-// ===== DOC ARTIFACT SUGGEST EDIT =====
+// ===== DOC ARTIFACT REVIEW CONTEXT =====
 
+/// MCP-only request: combines a read, the open comments, and the pending
+/// suggestions of one artifact.
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactSuggestEditRequest {
+pub struct DocArtifactReviewContextRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Artifact ID from doc_artifact_list
     pub artifact_id: String,
-    /// The comment ID this suggestion addresses (links suggestion to comment thread)
-    pub comment_id: String,
-    /// Edit type: replace_range, insert, append, full_replace
-    pub edit_type: String,
-    /// For replace_range: start character offset
-    #[serde(
-        skip_serializing_if = "Option::is_none",
-        default,
-        deserialize_with = "option_string_or_i64::deserialize"
-    )]
-    pub start_offset: Option<i64>,
-    /// For replace_range: end character offset
-    #[serde(
-        skip_serializing_if = "Option::is_none",
-        default,
-        deserialize_with = "option_string_or_i64::deserialize"
-    )]
-    pub end_offset: Option<i64>,
-    /// The suggested replacement/new text
-    pub suggested_text: String,
-    /// Rationale for the suggestion (shown to user)
+    /// Line offset (0-based). Default: 0
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub rationale: Option<String>,
-    /// Who created this suggestion (agent role)
-    pub source: String,
+    #[schemars(range(min = 0), extend("default" = 0))]
+    pub offset: Option<i64>,
+    /// Max lines to return. Default: 500, Max: 2000 (configurable)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1), extend("default" = 500))]
+    pub limit: Option<i64>,
+    /// Your agent role, recorded as having read this version (see
+    /// get_artifact_readers). Defaults to `default_source` from config.
+    #[serde(default, skip_serializing)]
+    pub source: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
+/// Everything a reviewer needs for one artifact, fetched concurrently.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactSuggestEditResponse {
-    pub suggestion_id: String,
-    pub comment_id: String,
-    pub artifact_id: String,
+pub struct DocArtifactReviewContext {
+    pub document: DocArtifactReadResponse,
+    /// Open comments only
+    pub comments: DocArtifactListCommentsResponse,
+    /// Pending suggestions only
+    pub suggestions: DocArtifactListSuggestionsResponse,
 }
 
-// ===== DOC ARTIFACT ACCEPT SUGGESTION =====
+// ===== ARTIFACT READERS =====
 
-#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+/// Sent after every doc_artifact_read and doc_artifact_review_context: which
+/// version of the document `source` has seen.
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactAcceptSuggestionRequest {
+pub struct ReportArtifactReadRequest {
     pub run_id: String,
     pub artifact_id: String,
-    pub suggestion_id: String,
-    /// Who accepted this suggestion
+    pub content_hash: String,
     pub source: String,
+    /// Whether the read reached the end of the document
+    pub read_to_end: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactAcceptSuggestionResponse {
+pub struct ReportArtifactReadResponse {
     pub success: bool,
-    pub suggestion_id: String,
-    pub new_content_hash: String,
-    /// Comments that were resolved as a result of this acceptance
-    #[serde(default)]
-    pub resolved_comments: Vec<String>,
 }
 
-// ===== DOC ARTIFACT REJECT SUGGESTION =====
-
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactRejectSuggestionRequest {
+pub struct GetArtifactReadersRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
     pub run_id: String,
+    /// Artifact ID from doc_artifact_list
     pub artifact_id: String,
-    pub suggestion_id: String,
-    /// Reason for rejecting
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub reason: Option<String>,
-    /// Who rejected this suggestion
-    pub source: String,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// The version of a document one role read last.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactRejectSuggestionResponse {
-    pub success: bool,
-    pub suggestion_id: String,
+pub struct ArtifactReader {
+    pub role: String,
+    pub content_hash: String,
+    pub read_at: String,
+    #[serde(default)]
+    pub read_to_end: bool,
 }
 
-// ===== DOC ARTIFACT LIST SUGGESTIONS =====
+impl ArtifactReader {
+    /// Whether this reader has seen the document as it is now, to the end.
+    pub fn is_current(&self, content_hash: &str) -> bool {
+        self.content_hash == content_hash && self.read_to_end
+    }
+}
 
-#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactListSuggestionsRequest {
+pub struct GetArtifactReadersResponse {
     pub run_id: String,
     pub artifact_id: String,
-    /// Filter by status: pending, accepted, rejected, all. Default: pending
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub status: Option<String>,
+    pub filename: String,
+    /// Hash of the document as it is now
+    pub content_hash: String,
+    pub readers: Vec<ArtifactReader>,
 }
 
-/// Full suggestion details for listing
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// MCP-only request: where the feedback on one artifact clusters.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct SuggestionDetail {
-    pub id: String,
-    pub comment_id: String,
+pub struct DocArtifactFeedbackSummaryRequest {
+    /// The run ID (UUID) from your protocol
+    #[schemars(regex(pattern = RUN_ID_PATTERN), example = RUN_ID_EXAMPLE)]
+    pub run_id: String,
+    /// Artifact ID from doc_artifact_list
     pub artifact_id: String,
-    pub suggested_by: String,
-    pub edit_type: String,
-    pub start_offset: Option<i64>,
-    pub end_offset: Option<i64>,
-    pub original_text: Option<String>,
-    pub suggested_text: String,
-    pub rationale: Option<String>,
-    pub status: String,
-    pub accepted_by: Option<String>,
-    pub accepted_at: Option<String>,
-    pub rejection_reason: Option<String>,
-    pub created_at: String,
-    pub updated_at: String,
-}
-
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// The comments made on one section of a document. A section runs from a
+/// markdown heading to the next; text before the first heading is its own.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionFeedback {
+    /// The heading, or None for the text before the first heading
+    pub heading: Option<String>,
+    /// 1-based line of the heading
+    pub line: usize,
+    pub comments: usize,
+    pub open: usize,
+    /// Open comments of type "question"
+    pub open_questions: usize,
+    /// Creation time of the section's oldest open comment
+    pub oldest_open: Option<String>,
+}
+
+/// Comment density per section of an artifact, with the issues to address first.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct DocArtifactListSuggestionsResponse {
+pub struct DocArtifactFeedbackSummary {
     pub artifact_id: String,
-    pub suggestions: Vec<SuggestionDetail>,
+    pub filename: String,
+    pub total_comments: usize,
+    pub open_comments: usize,
+    /// Sections with at least one comment, in document order
+    pub sections: Vec<SectionFeedback>,
+    /// The open comment that has waited longest
+    pub oldest_open: Option<CommentDetail>,
+    /// Open questions, oldest first
+    pub unanswered_questions: Vec<CommentDetail>,
 }
 
 // =============================================================================
@@ -943,8 +3559,12 @@ pub struct HotwireArtifact {
 pub struct HotwireRequest {
     /// The project directory path
     pub project_path: String,
-    /// The Zellij session name
+    /// The terminal session name (Zellij or tmux session, or the server's TTY session name)
     pub zellij_session: String,
+    /// What `zellij_session` names: "zellij" (default), "tmux", or "tty".
+    /// Defaults to the session this server detected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiplexer: Option<Multiplexer>,
     /// User's intent/description of what they want to do
     #[serde(skip_serializing_if = "Option::is_none")]
     pub intent: Option<String>,
@@ -954,6 +3574,10 @@ pub struct HotwireRequest {
     /// Suggested artifacts to create or use
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suggested_artifacts: Option<Vec<HotwireArtifact>>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
 }
 
 /// Response when run starts immediately
@@ -990,10 +3614,18 @@ pub enum HotwireResponse {
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PairRequest {
-    /// The Zellij session name
+    /// The terminal session name (Zellij or tmux session, or the server's TTY session name)
     pub zellij_session: String,
     /// The project directory path
     pub project_path: String,
+    /// What `zellij_session` names: "zellij" (default), "tmux", or "tty".
+    /// Defaults to the session this server detected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiplexer: Option<Multiplexer>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
 }
 
 /// Context about the primary agent's state when joining
@@ -1004,6 +3636,9 @@ pub struct PairingContext {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub current_artifact: Option<String>,
     pub conversation_summary: String,
+    /// What the run's agents published with set_run_context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<Box<RunContext>>,
 }
 
 /// Response when successfully joined a run
@@ -1061,6 +3696,103 @@ pub enum PairResponse {
     },
 }
 
+// ===== RESUME RUN - Re-attach after agent restart =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeRunRequest {
+    /// The Zellij session name
+    pub zellij_session: String,
+    /// The project directory path
+    pub project_path: String,
+    /// Run this session was last attached to, from its saved run context
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub last_run_id: Option<String>,
+    /// Role this session last held, from its saved run context
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    #[schemars(skip)]
+    pub last_role: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+}
+
+/// A message posted to the run while the agent was away
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestMessage {
+    pub source: String,
+    pub content: String,
+    pub created_at: String,
+}
+
+/// A document changed while the agent was away
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestEdit {
+    pub artifact_id: String,
+    pub filename: String,
+    /// Roles that edited the document
+    pub edited_by: Vec<String>,
+    pub edit_count: u32,
+    pub updated_at: String,
+}
+
+/// A comment or suggestion that is still open
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DigestComment {
+    pub artifact_id: String,
+    pub comment_id: String,
+    pub comment_type: String,
+    pub author: String,
+    pub content: String,
+    pub created_at: String,
+}
+
+/// What happened in a run since the agent was last seen
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumeDigest {
+    #[serde(default)]
+    pub messages: Vec<DigestMessage>,
+    #[serde(default)]
+    pub edits: Vec<DigestEdit>,
+    #[serde(default)]
+    pub open_comments: Vec<DigestComment>,
+}
+
+/// Response when the session was re-attached to its run
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunResumed {
+    pub run_id: String,
+    pub role: String,
+    pub role_name: String,
+    pub playbook: String,
+    pub protocol: String,
+    /// When this session was last seen before the restart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub away_since: Option<String>,
+    pub digest: ResumeDigest,
+}
+
+/// Resume response - tagged enum for easy parsing
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ResumeRunResponse {
+    Resumed(RunResumed),
+    #[serde(rename = "none")]
+    NoneAvailable {
+        message: String,
+    },
+    Error {
+        error: String,
+    },
+}
+
 // ===== LIST ACTIVE RUNS =====
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
@@ -1072,6 +3804,14 @@ pub struct ListActiveRunsRequest {
     /// Filter by Zellij session
     #[serde(skip_serializing_if = "Option::is_none")]
     pub zellij_session: Option<String>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
 }
 
 /// An active or resumable run
@@ -1094,11 +3834,132 @@ pub struct ListActiveRunsResponse {
     pub runs: Vec<ActiveRun>,
 }
 
+// ===== SEARCH RUNS - Find past runs and their artifacts =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchRunsRequest {
+    /// Keywords matched against run intents, summaries, and artifact content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(example = &"cache invalidation")]
+    pub query: Option<String>,
+    /// Only runs in this project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_path: Option<String>,
+    /// Only runs of this playbook ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub playbook: Option<String>,
+    /// Only runs created on or after this date (YYYY-MM-DD or RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(example = &"2024-01-01")]
+    pub since: Option<String>,
+    /// Only runs created on or before this date (YYYY-MM-DD or RFC 3339)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// Maximum number of runs to return (default 20)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1, max = 100))]
+    pub limit: Option<i64>,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// An artifact of a past run
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunArtifactRef {
+    pub id: String,
+    pub filename: String,
+    pub document_type: String,
+}
+
+/// A run matching a search
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunSearchHit {
+    pub run_id: String,
+    pub playbook: String,
+    pub intent: String,
+    pub status: String,
+    #[serde(default)]
+    pub project_path: Option<String>,
+    pub created_at: String,
+    #[serde(default)]
+    pub ended_at: Option<String>,
+    /// Text around the best keyword match, if a query was given
+    #[serde(default)]
+    pub snippet: Option<String>,
+    #[serde(default)]
+    pub artifacts: Vec<RunArtifactRef>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchRunsResponse {
+    /// Most relevant first
+    pub runs: Vec<RunSearchHit>,
+    /// Matching runs beyond `limit`
+    #[serde(default)]
+    pub more: i64,
+}
+
+// ===== GET MY CONTEXT - Who am I and what run am I in =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetMyContextRequest {
+    /// Response format: "markdown" (default), "compact", or "json".
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// Everything the MCP server knows about the calling agent
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MyContext {
+    /// Zellij session this server runs in
+    pub session_name: Option<String>,
+    pub project_dir: Option<String>,
+    /// Backend socket this server talks to
+    pub socket_path: Option<String>,
+    /// Agent CLI this server runs under
+    pub agent_type: Option<String>,
+    /// Run this agent is attached to
+    pub run_id: Option<String>,
+    pub role: Option<String>,
+    /// Current status of that run, if the backend could be reached
+    pub run_status: Option<String>,
+    /// Capabilities of the role, if the backend could be reached
+    pub capabilities: Option<RoleCapabilities>,
+    /// Documents whose content hash the server has seen in this run
+    pub tracked_documents: usize,
+}
+
 // ===== LIST PLAYBOOKS =====
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct ListPlaybooksRequest {}
+pub struct ListPlaybooksRequest {
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
 
 /// Role information within a playbook
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -1140,8 +4001,48 @@ pub struct PlaybookInfo {
     pub initialization: PlaybookInitHints,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ListPlaybooksResponse {
     pub playbooks: Vec<PlaybookInfo>,
 }
+
+// ===== GET PLAYBOOK =====
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPlaybookRequest {
+    /// Playbook ID, as returned by list_playbooks
+    #[schemars(example = &"spec-and-build")]
+    pub playbook_id: String,
+    /// Response format: "markdown" (default), "compact", or "json". Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(extend("default" = "markdown"))]
+    pub format: Option<OutputFormat>,
+    /// Continuation token from a truncated previous response. Not sent to the backend.
+    #[serde(default, skip_serializing)]
+    #[schemars(example = &"line:120")]
+    pub continuation: Option<String>,
+}
+
+/// One section of a playbook's protocol
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolOutlineSection {
+    pub title: String,
+    /// Role the section applies to; `None` for the shared playbook protocol
+    #[serde(default)]
+    pub role: Option<String>,
+    /// One-line summary of the section
+    #[serde(default)]
+    pub summary: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPlaybookResponse {
+    pub playbook: PlaybookInfo,
+    /// Section headings of the protocol agents receive in this playbook
+    #[serde(default)]
+    pub protocol_outline: Vec<ProtocolOutlineSection>,
+}
@@ -1,6 +1,7 @@
 use crate::ipc::traits::IpcClient;
 use crate::types::errors::IpcError;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
@@ -14,6 +15,8 @@ pub struct MockIpcClient {
     requests: Arc<Mutex<Vec<(String, String)>>>,
     /// If true, all requests fail with NotConnected
     disconnected: Arc<Mutex<bool>>,
+    /// When a request last got a response
+    last_contact: Arc<Mutex<Option<DateTime<Utc>>>>,
 }
 
 impl MockIpcClient {
@@ -87,6 +90,8 @@ impl IpcClient for MockIpcClient {
             .unwrap()
             .push((endpoint.to_string(), request_json));
 
+        *self.last_contact.lock().unwrap() = Some(Utc::now());
+
         // Return configured response
         let responses = self.responses.lock().unwrap();
         let response_json = responses
@@ -100,9 +105,14 @@ impl IpcClient for MockIpcClient {
         if *self.disconnected.lock().unwrap() {
             Err(IpcError::NotConnected)
         } else {
+            *self.last_contact.lock().unwrap() = Some(Utc::now());
             Ok(())
         }
     }
+
+    fn last_contact(&self) -> Option<DateTime<Utc>> {
+        *self.last_contact.lock().unwrap()
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,12 @@
 pub mod client;
+#[cfg(test)]
+mod compat;
+#[cfg(test)]
+mod fuzz;
 pub mod messages;
 pub mod mock;
 pub mod traits;
+pub mod wire;
 
 pub use client::UnixSocketClient;
 pub use mock::MockIpcClient;
@@ -1,5 +1,6 @@
 use crate::types::errors::IpcError;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 
 /// Trait for communicating with the Hotwired backend.
 /// Implementations: UnixSocketClient (production), MockIpcClient (testing)
@@ -13,4 +14,14 @@ pub trait IpcClient: Send + Sync {
 
     /// Check if the backend is available.
     async fn health_check(&self) -> Result<(), IpcError>;
+
+    /// Prepare for the first request (e.g., open a connection ahead of time).
+    async fn warm_up(&self) -> Result<(), IpcError> {
+        self.health_check().await
+    }
+
+    /// When the backend last answered a request, if ever.
+    fn last_contact(&self) -> Option<DateTime<Utc>> {
+        None
+    }
 }
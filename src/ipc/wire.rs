@@ -0,0 +1,125 @@
+//! Versioned envelope for IPC payloads.
+//!
+//! Every request carries the wire version this binary speaks (`v`) and every
+//! response the backend's. A backend that predates the envelope sends none and
+//! is treated as version 1. Responses from an older backend are upgraded one
+//! version at a time by the shims below before they are deserialized, so the
+//! Hotwired app and this binary can be upgraded independently. Responses from
+//! a newer backend are read as they are: fields added since are ignored, and
+//! a payload that no longer fits names both versions so the user knows which
+//! side to upgrade.
+//!
+//! Version history:
+//! - 1: unversioned; `error` is a plain string.
+//! - 2: `v` on requests and responses; `error` is `{code, message}`.
+
+use serde_json::{json, Value};
+
+/// Wire version this binary speaks.
+pub const WIRE_VERSION: u32 = 2;
+
+/// Version of a response without `v`.
+pub const UNVERSIONED: u32 = 1;
+
+/// Upgrades a response line from version `from` to `from + 1`.
+struct Shim {
+    from: u32,
+    /// Socket method the shim applies to, or None for the envelope itself
+    method: Option<&'static str>,
+    upgrade: fn(&mut Value),
+}
+
+const SHIMS: &[Shim] = &[Shim {
+    from: 1,
+    method: None,
+    upgrade: structured_error,
+}];
+
+/// The wire version a response was written in.
+pub fn version(response: &Value) -> u32 {
+    response
+        .get("v")
+        .and_then(Value::as_u64)
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(UNVERSIONED)
+}
+
+/// Bring a response to `method` up to [`WIRE_VERSION`]. Returns the version it
+/// was written in. Newer responses are left alone.
+pub fn upgrade(method: &str, response: &mut Value) -> u32 {
+    let written = version(response);
+    apply(SHIMS, method, written, response);
+    written
+}
+
+fn apply(shims: &[Shim], method: &str, written: u32, response: &mut Value) {
+    for from in written..WIRE_VERSION {
+        for shim in shims
+            .iter()
+            .filter(|s| s.from == from && s.method.is_none_or(|m| m == method))
+        {
+            (shim.upgrade)(response);
+        }
+    }
+}
+
+/// v1 -> v2: a string `error` becomes `{code: "unknown", message}`.
+fn structured_error(response: &mut Value) {
+    if let Some(error) = response.get_mut("error") {
+        if let Value::String(message) = error {
+            *error = json!({ "code": "unknown", "message": message });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unversioned_errors_become_structured() {
+        let mut response = json!({"success": false, "error": "Run not found"});
+        assert_eq!(upgrade("get_run_status", &mut response), UNVERSIONED);
+        assert_eq!(
+            response["error"],
+            json!({"code": "unknown", "message": "Run not found"})
+        );
+
+        let current = json!({"v": 2, "success": false, "error": {"code": "not_found", "message": "Run not found"}});
+        let mut response = current.clone();
+        assert_eq!(upgrade("get_run_status", &mut response), 2);
+        assert_eq!(response, current);
+    }
+
+    #[test]
+    fn test_newer_responses_are_left_alone() {
+        let newer = json!({"v": 9, "success": false, "error": "a string again"});
+        let mut response = newer.clone();
+        assert_eq!(upgrade("ping", &mut response), 9);
+        assert_eq!(response, newer);
+    }
+
+    #[test]
+    fn test_method_shims_only_touch_their_method() {
+        fn rename(response: &mut Value) {
+            if let Some(data) = response.get_mut("data").and_then(Value::as_object_mut) {
+                if let Some(status) = data.remove("state") {
+                    data.insert("status".to_string(), status);
+                }
+            }
+        }
+        let shims = [Shim {
+            from: 1,
+            method: Some("get_run_status"),
+            upgrade: rename,
+        }];
+
+        let mut response = json!({"success": true, "data": {"state": "active"}});
+        apply(&shims, "get_run_status", 1, &mut response);
+        assert_eq!(response["data"], json!({"status": "active"}));
+
+        let mut other = json!({"success": true, "data": {"state": "active"}});
+        apply(&shims, "list_runs", 1, &mut other);
+        assert_eq!(other["data"], json!({"state": "active"}));
+    }
+}
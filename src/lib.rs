@@ -1,4 +1,22 @@
+pub mod approval;
+pub mod backup;
+pub mod bridge;
+pub mod config;
+pub mod dedup;
+pub mod features;
+pub mod heartbeat;
 pub mod ipc;
+pub mod logging;
+pub mod mirror;
+pub mod paths;
+pub mod periodic;
+pub mod policy;
+pub mod ratelimit;
+pub mod redact;
 pub mod server;
+pub mod session;
+pub mod state;
 pub mod tools;
 pub mod types;
+pub mod watcher;
+pub mod webhook;
@@ -0,0 +1,169 @@
+//! Log subscriber setup with a runtime-adjustable level filter.
+//!
+//! Logs MUST NOT go to stdout: stdout carries JSON-RPC traffic with the MCP client.
+//! Logs are written to `<log_dir>/mcp-rs.log`, falling back to stderr.
+
+use rmcp::model::LoggingLevel;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter};
+
+/// Filter used when no level is configured anywhere.
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Pick the log filter by precedence: CLI flag, then `RUST_LOG`, then config file.
+/// A blank level at any of them counts as unset.
+pub fn resolve_filter(
+    cli_level: Option<&str>,
+    env_level: Option<&str>,
+    config_level: Option<&str>,
+) -> String {
+    [cli_level, env_level, config_level]
+        .into_iter()
+        .flatten()
+        .find(|level| !level.trim().is_empty())
+        .unwrap_or(DEFAULT_LOG_LEVEL)
+        .to_string()
+}
+
+/// Map an MCP `logging/setLevel` level onto a tracing filter directive.
+/// MCP has syslog-style levels; everything above warning collapses to "error".
+pub fn filter_for_level(level: LoggingLevel) -> &'static str {
+    match level {
+        LoggingLevel::Debug => "debug",
+        LoggingLevel::Info | LoggingLevel::Notice => "info",
+        LoggingLevel::Warning => "warn",
+        LoggingLevel::Error
+        | LoggingLevel::Critical
+        | LoggingLevel::Alert
+        | LoggingLevel::Emergency => "error",
+    }
+}
+
+/// Handle for changing the active log filter after initialization.
+#[derive(Clone)]
+pub struct LogLevelHandle {
+    handle: reload::Handle<EnvFilter, tracing_subscriber::Registry>,
+}
+
+impl LogLevelHandle {
+    /// Replace the active filter (e.g., "debug" or "hotwired_mcp=trace").
+    pub fn set_filter(&self, filter: &str) -> Result<(), String> {
+        let filter = EnvFilter::try_new(filter)
+            .map_err(|e| format!("Invalid log filter '{}': {}", filter, e))?;
+        self.handle
+            .reload(filter)
+            .map_err(|e| format!("Failed to update log level: {}", e))
+    }
+
+    /// Apply an MCP logging level.
+    pub fn set_level(&self, level: LoggingLevel) -> Result<(), String> {
+        self.set_filter(filter_for_level(level))
+    }
+}
+
+/// Build a reloadable filter layer from a filter string, falling back to the default
+/// filter if the string is not a valid directive.
+fn reloadable_filter(
+    filter: &str,
+) -> (
+    reload::Layer<EnvFilter, tracing_subscriber::Registry>,
+    LogLevelHandle,
+) {
+    let env_filter = EnvFilter::try_new(filter).unwrap_or_else(|e| {
+        eprintln!(
+            "Invalid log filter '{}' ({}), using '{}'",
+            filter, e, DEFAULT_LOG_LEVEL
+        );
+        EnvFilter::new(DEFAULT_LOG_LEVEL)
+    });
+    let (layer, handle) = reload::Layer::new(env_filter);
+    (layer, LogLevelHandle { handle })
+}
+
+/// Install the global subscriber writing to `<log_dir>/mcp-rs.log`.
+pub fn init(filter: &str, log_dir: &Path) -> LogLevelHandle {
+    // Create logs directory if it doesn't exist
+    std::fs::create_dir_all(log_dir).ok();
+
+    // Open log file with line buffering for immediate writes
+    let log_path = log_dir.join("mcp-rs.log");
+    let log_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .ok();
+
+    let (filter_layer, handle) = reloadable_filter(filter);
+    let registry = tracing_subscriber::registry().with(filter_layer);
+
+    if let Some(file) = log_file {
+        registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(Mutex::new(file))
+                    .with_ansi(false),
+            )
+            .init();
+    } else {
+        // Fallback to stderr (never stdout)
+        registry
+            .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+            .init();
+    }
+
+    handle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_filter_prefers_cli_flag() {
+        let filter = resolve_filter(Some("debug"), Some("warn"), Some("error"));
+        assert_eq!(filter, "debug");
+    }
+
+    #[test]
+    fn test_resolve_filter_falls_back_to_env_then_config() {
+        assert_eq!(resolve_filter(None, Some("warn"), Some("error")), "warn");
+        assert_eq!(resolve_filter(None, None, Some("error")), "error");
+    }
+
+    #[test]
+    fn test_resolve_filter_defaults_to_info() {
+        assert_eq!(resolve_filter(None, None, None), "info");
+        assert_eq!(resolve_filter(Some("  "), None, None), "info");
+    }
+
+    #[test]
+    fn test_resolve_filter_skips_blank_levels() {
+        assert_eq!(resolve_filter(Some(""), None, Some("error")), "error");
+        assert_eq!(resolve_filter(None, Some(" "), Some("error")), "error");
+        assert_eq!(resolve_filter(Some(""), Some("warn"), None), "warn");
+    }
+
+    #[test]
+    fn test_filter_for_level_maps_mcp_levels() {
+        assert_eq!(filter_for_level(LoggingLevel::Debug), "debug");
+        assert_eq!(filter_for_level(LoggingLevel::Notice), "info");
+        assert_eq!(filter_for_level(LoggingLevel::Warning), "warn");
+        assert_eq!(filter_for_level(LoggingLevel::Emergency), "error");
+    }
+
+    #[test]
+    fn test_handle_reloads_filter() {
+        let (_layer, handle) = reloadable_filter("info");
+        assert!(handle.set_level(LoggingLevel::Debug).is_ok());
+        assert!(handle.set_filter("hotwired_mcp=trace").is_ok());
+    }
+
+    #[test]
+    fn test_handle_rejects_invalid_filter() {
+        let (_layer, handle) = reloadable_filter("info");
+        let result = handle.set_filter("hotwired_mcp=notalevel");
+        assert!(result.is_err());
+    }
+}
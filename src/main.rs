@@ -1,103 +1,188 @@
 use clap::{Parser, Subcommand};
-use hotwired_mcp::{ipc, server};
+use hotwired_mcp::backup::BackupStore;
+use hotwired_mcp::ipc::messages::{Multiplexer, RegisterSessionRequest};
+use hotwired_mcp::paths::HotwiredDirs;
+use hotwired_mcp::state::StateStore;
+use hotwired_mcp::tools::{export, review_import};
+use hotwired_mcp::{config::Config, ipc, logging, redact::Redactor, server, session};
 use rmcp::{transport::stdio, ServiceExt};
-use std::fs::OpenOptions;
 use std::path::PathBuf;
-use std::sync::Mutex;
 
-/// Hotwired MCP server for Claude Code integration
+/// Hotwired MCP server for coding agents (Claude Code, Gemini CLI, Codex, ...)
 #[derive(Parser, Debug)]
 #[command(name = "hotwired-mcp")]
 #[command(about = "MCP server for Hotwired multi-agent workflows")]
 struct Args {
-    /// Path to the Unix socket for communicating with the Hotwired backend.
-    /// Defaults to ~/.hotwired/hotwired.sock. Only use this for worktree development.
-    #[arg(long, short = 's', global = true)]
+    /// Path to the Unix socket for communicating with the Hotwired backend,
+    /// or `tcp://host:port` for a backend in a container or on another machine.
+    /// Defaults to the config's socket_path, then ~/.hotwired/hotwired.sock
+    /// ($HOTWIRED_HOME or $XDG_RUNTIME_DIR/hotwired when set up there).
+    /// Only use this for worktree development or remote backends.
+    #[arg(long, short = 's', global = true, visible_alias = "backend")]
     socket_path: Option<String>,
 
+    /// Path to the config file. Defaults to ~/.hotwired/config.json
+    /// ($HOTWIRED_HOME/config.json when set).
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Log level or filter directive (e.g., "debug", "hotwired_mcp=trace").
+    /// Overrides RUST_LOG and the config file's log_level.
+    #[arg(long, global = true)]
+    log_level: Option<String>,
+
+    /// Expose only read/list tools; everything that changes run or document
+    /// state is hidden. Same as `read_only` in the config file.
+    #[arg(long)]
+    read_only: bool,
+
+    /// Connect even if the backend socket is not owned by the current user with
+    /// 0600 permissions (shared-machine setups). Same as `allow_shared_socket`
+    /// in the config file.
+    #[arg(long, global = true)]
+    allow_shared_socket: bool,
+
+    /// Agent CLI this server runs under ("claude", "gemini", "codex", or a
+    /// custom name). Same as `agent_type` in the config file.
+    #[arg(long, global = true)]
+    agent_type: Option<String>,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
-    /// Register a Claude session with the Hotwired backend (called by SessionStart hook)
+    /// Register an agent session with the Hotwired backend (called by SessionStart hook)
     Register {
-        /// Zellij session name
+        /// Terminal session name (Zellij or tmux session)
         #[arg(long)]
         session: String,
+        /// What `--session` names: zellij, tmux, or tty. Defaults to the
+        /// config's multiplexer, else zellij.
+        #[arg(long, value_parser = parse_multiplexer)]
+        multiplexer: Option<Multiplexer>,
         /// Project directory path
         #[arg(long)]
         project: String,
+        /// Claude Code session id (`session_id` from the SessionStart hook input)
+        #[arg(long)]
+        claude_session_id: Option<String>,
+        /// Path to the session transcript (`transcript_path` from the hook input)
+        #[arg(long)]
+        transcript_path: Option<String>,
+        /// Model the session is running
+        #[arg(long)]
+        model: Option<String>,
+        /// Workspace root, if different from the project directory
+        #[arg(long)]
+        workspace_root: Option<String>,
     },
-    /// Deregister a Claude session (called by SessionEnd hook)
+    /// Deregister an agent session (called by SessionEnd hook)
     Deregister {
-        /// Zellij session name
+        /// Terminal session name
         #[arg(long)]
         session: String,
     },
+    /// Export a run's timeline, artifacts, comments, and suggestions to a directory
+    Export {
+        /// Run ID (UUID)
+        run_id: String,
+        /// Directory to write to. Defaults to ./hotwired-export-<run_id>
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+        /// Also pack the directory into <output>.zip (needs the `zip` command)
+        #[arg(long)]
+        zip: bool,
+    },
+    /// Write a run's full event stream to a JSONL file, one event per line
+    DumpEvents {
+        /// Run ID (UUID)
+        run_id: String,
+        /// File to write, or "-" for stdout. Defaults to ./hotwired-events-<run_id>.jsonl
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+    /// Turn a GitHub pull-request review export into doc-artifact comments
+    ImportReview {
+        /// Run ID (UUID)
+        run_id: String,
+        /// Review export: output of `gh api repos/OWNER/REPO/pulls/NUMBER/comments`
+        review: PathBuf,
+        /// Put every comment on this artifact instead of matching file paths to artifact filenames
+        #[arg(long)]
+        artifact_id: Option<String>,
+        /// Who the comments are attributed to. Defaults to the config's
+        /// default_author, then default_source, then "human"
+        #[arg(long)]
+        author: Option<String>,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
-    // Socket path: CLI arg only, otherwise use default (~/.hotwired/hotwired.sock)
+    let config = Config::load(args.config.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{} - using default configuration", e);
+        Config::default()
+    });
+    let mut config = config.with_env(|key| std::env::var(key).ok());
+    config.read_only |= args.read_only;
+    config.allow_shared_socket |= args.allow_shared_socket;
+    if let Some(agent_type) = args.agent_type.clone() {
+        config.agent_type = Some(agent_type);
+    }
+
+    // Resolve per-project overrides. Register names its project explicitly; otherwise
+    // the MCP client launches us from the project directory.
+    let project_dir = match &args.command {
+        Some(Command::Register { project, .. }) => Some(PathBuf::from(project)),
+        _ => std::env::current_dir().ok(),
+    };
+    let config = match project_dir {
+        Some(dir) => config.for_project(&dir),
+        None => config,
+    };
+
+    // Socket path: CLI arg, then the config's (per-project) socket_path,
+    // otherwise the default (~/.hotwired/hotwired.sock)
     // NOTE: We intentionally do NOT read HOTWIRED_SOCKET_PATH env var here.
     // This prevents worktree environments from accidentally overriding the socket path
     // when Claude sessions should connect to the main Hotwired backend.
-    let socket_path = args.socket_path.clone();
+    let socket_path = args
+        .socket_path
+        .clone()
+        .or_else(|| config.socket_path.clone());
 
-    // Handle subcommands (register/deregister are quick CLI operations, not MCP servers)
+    // Handle subcommands (register, export, etc. are quick CLI operations, not MCP servers)
     if let Some(cmd) = args.command {
-        return handle_command(cmd, socket_path).await;
+        return handle_command(cmd, socket_path, config).await;
     }
 
     // No subcommand: Start the MCP server
     // Initialize logging - MUST NOT write to stdout
     // stdout is used for JSON-RPC communication with the MCP client
-    // Write logs to a file in ~/.hotwired/logs/ directory
-
-    // Determine log directory from socket path or use default
-    let log_dir = if let Some(ref path) = socket_path {
-        // Use parent directory of socket path (e.g., ~/.hotwired/)
-        PathBuf::from(path)
-            .parent()
-            .map(|p| p.join("logs"))
-            .unwrap_or_else(|| {
-                dirs::home_dir()
-                    .unwrap_or_else(|| PathBuf::from("/tmp"))
-                    .join(".hotwired/logs")
-            })
-    } else {
-        // Default to ~/.hotwired/logs/
-        dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("/tmp"))
-            .join(".hotwired/logs")
-    };
+    // Write logs to a file in the logs/ directory below
 
-    // Create logs directory if it doesn't exist
-    std::fs::create_dir_all(&log_dir).ok();
-
-    // Open log file with line buffering for immediate writes
-    let log_path = log_dir.join("mcp-rs.log");
-    let log_file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_path)
-        .ok();
-
-    if let Some(file) = log_file {
-        tracing_subscriber::fmt()
-            .with_writer(Mutex::new(file))
-            .with_ansi(false)
-            .init();
-    } else {
-        // Fallback to stderr (never stdout)
-        tracing_subscriber::fmt()
-            .with_writer(std::io::stderr)
-            .init();
-    }
+    // Logs and saved state live next to an explicitly configured socket (e.g. a
+    // worktree's), otherwise in the state dir: $HOTWIRED_HOME, $XDG_STATE_HOME/hotwired,
+    // or ~/.hotwired/. A TCP backend has no local directory to share.
+    let hotwired_dir = socket_path
+        .as_deref()
+        .filter(|path| ipc::client::tcp_address(path).is_none())
+        .and_then(|path| PathBuf::from(path).parent().map(PathBuf::from))
+        .unwrap_or_else(|| HotwiredDirs::resolve().state);
+    let log_dir = hotwired_dir.join("logs");
+
+    // Log level precedence: --log-level, then RUST_LOG, then config file
+    let env_level = std::env::var("RUST_LOG").ok();
+    let log_filter = logging::resolve_filter(
+        args.log_level.as_deref(),
+        env_level.as_deref(),
+        config.log_level.as_deref(),
+    );
+    let log_handle = logging::init(&log_filter, &log_dir);
 
     tracing::info!("Hotwired MCP server starting");
     if let Some(ref path) = socket_path {
@@ -105,34 +190,176 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Create IPC client to communicate with hotwired-core via Unix socket
-    // Reads HOTWIRED_SOCKET_PATH env var for worktree support (default: ~/.hotwired/hotwired.sock)
-    let client = ipc::UnixSocketClient::new(socket_path);
+    // Default socket: ~/.hotwired/hotwired.sock (see HotwiredDirs)
+    let redactor = Redactor::from_config(&config.redaction).unwrap_or_else(|e| {
+        tracing::warn!("{} - using built-in redaction patterns only", e);
+        Redactor::default()
+    });
+    // Register/deregister go over their own connection; the server owns `client`
+    // A configured but unreadable key is fatal: unsigned requests would be rejected anyway
+    let signing_key = config.signing_key()?;
+    let mut lifecycle = ipc::UnixSocketClient::new(socket_path.clone())
+        .with_allow_shared_socket(config.allow_shared_socket)
+        .with_signing_key(signing_key.clone());
+    let mut client = ipc::UnixSocketClient::new(socket_path)
+        .with_allow_shared_socket(config.allow_shared_socket)
+        .with_signing_key(signing_key)
+        .with_redactor(redactor);
+
+    // Inside a terminal session, register ourselves rather than relying on the session hooks
+    let terminal = session::detect(
+        config.multiplexer,
+        |key| std::env::var(key).ok(),
+        std::env::current_dir().ok().as_deref(),
+    );
+    let multiplexer = terminal.as_ref().map(|t| t.multiplexer);
+    let session = terminal.map(|t| (t.name, t.project_dir));
+    let registered = match &session {
+        Some((name, project)) if config.auto_register() => {
+            let request = RegisterSessionRequest {
+                session_name: name.clone(),
+                project_dir: project.clone(),
+                multiplexer,
+                agent_type: Some(config.agent_type().to_string()),
+                claude_session_id: None,
+                transcript_path: None,
+                model: None,
+                workspace_root: None,
+                capabilities: config.capabilities.clone(),
+            };
+            match lifecycle.register_session(&request).await {
+                Ok(response) => {
+                    tracing::info!("Registered session {} for {}", name, project);
+                    // Authenticate every later request (including deregister) as this session
+                    lifecycle = lifecycle.with_session_token(response.session_token.clone());
+                    client = client.with_session_token(response.session_token);
+                    true
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to register session {}: {}", name, e);
+                    false
+                }
+            }
+        }
+        _ => false,
+    };
 
-    // Create and run the server with STDIO transport
-    let service = server::HotwiredMcp::new(client)
-        .serve(stdio())
-        .await
-        .inspect_err(|e| {
-            eprintln!("Error starting server: {}", e);
-        })?;
+    let socket = client.socket_path().to_string();
+    let mut server = server::HotwiredMcp::new(client)
+        .with_config(config)
+        .with_log_handle(log_handle)
+        .with_session(session.clone(), Some(socket))
+        .with_multiplexer(multiplexer)
+        .with_backups(BackupStore::new(hotwired_dir.join("backups")))
+        .with_backend_features()
+        .await;
+
+    // Restore the run context this session had before a restart
+    if let Some((name, _)) = &session {
+        let state = StateStore::load(StateStore::path_for(&hotwired_dir.join("state"), name));
+        let context = state.context();
+        if let (Some(run_id), Some(role)) = (&context.run_id, &context.role) {
+            tracing::info!("Restored run context: run {} as {}", run_id, role);
+        }
+        server = server.with_state(state);
+    }
+
+    // Don't make the agent's first tool call pay for connecting
+    server.spawn_warm_up();
+
+    // Keep the session registration fresh so a crashed agent goes stale
+    let heartbeat = session
+        .clone()
+        .and_then(|(name, project)| server.spawn_heartbeat(name, project));
+
+    // Usage reports still batched at shutdown are sent after the service stops
+    let flush_usage = server.flush_usage();
+
+    // Run the server with STDIO transport
+    let service = server.serve(stdio()).await.inspect_err(|e| {
+        eprintln!("Error starting server: {}", e);
+    })?;
+
+    // Stop when the client disconnects or we are asked to terminate
+    let result = tokio::select! {
+        result = service.waiting() => result.map(|_| ()),
+        _ = shutdown_signal() => {
+            tracing::info!("Shutdown signal received");
+            Ok(())
+        }
+    };
 
-    service.waiting().await?;
+    if let Some(heartbeat) = heartbeat {
+        heartbeat.abort();
+    }
+    flush_usage.await;
+    if let (true, Some((name, _))) = (registered, &session) {
+        if let Err(e) = lifecycle.deregister_session(name).await {
+            tracing::warn!("Failed to deregister session {}: {}", name, e);
+        }
+    }
+    result?;
 
     Ok(())
 }
 
-/// Handle register/deregister subcommands
+/// Parses `--multiplexer` with the same names as the config file.
+fn parse_multiplexer(value: &str) -> Result<Multiplexer, String> {
+    serde_json::from_value(serde_json::Value::from(value)).map_err(|_| {
+        format!(
+            "unknown multiplexer '{}' (expected zellij, tmux, or tty)",
+            value
+        )
+    })
+}
+
+/// Resolves on SIGINT or SIGTERM.
+async fn shutdown_signal() {
+    let mut terminate =
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(signal) => signal,
+            Err(_) => return std::future::pending().await,
+        };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+/// Handle CLI subcommands
 async fn handle_command(
     cmd: Command,
     socket_path: Option<String>,
+    config: Config,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Create IPC client
-    let client = ipc::UnixSocketClient::new(socket_path);
+    let client = ipc::UnixSocketClient::new(socket_path)
+        .with_allow_shared_socket(config.allow_shared_socket)
+        .with_signing_key(config.signing_key()?);
 
     match cmd {
-        Command::Register { session, project } => {
+        Command::Register {
+            session,
+            multiplexer,
+            project,
+            claude_session_id,
+            transcript_path,
+            model,
+            workspace_root,
+        } => {
+            let request = RegisterSessionRequest {
+                session_name: session,
+                project_dir: project,
+                multiplexer: multiplexer.or(config.multiplexer),
+                agent_type: Some(config.agent_type().to_string()),
+                claude_session_id,
+                transcript_path,
+                model,
+                workspace_root,
+                capabilities: config.capabilities,
+            };
             // Send register request to backend
-            match client.register_session(&session, &project).await {
+            match client.register_session(&request).await {
                 Ok(_) => {
                     // Success - exit silently (this runs as a hook)
                     Ok(())
@@ -158,5 +385,57 @@ async fn handle_command(
                 }
             }
         }
+        Command::Export {
+            run_id,
+            output,
+            zip,
+        } => {
+            // Unlike the hooks above, a failed export is reported through the exit status
+            let bundle = export::collect(
+                &client,
+                &run_id,
+                config.pagination.read_max_limit.max(1),
+                config.batch_concurrency(),
+            )
+            .await?;
+            let dir = output.unwrap_or_else(|| export::default_dir(&run_id));
+            let exported = export::write(&bundle, &dir, zip)?;
+            print!("{}", export::format_run_export(&exported));
+            Ok(())
+        }
+        Command::DumpEvents { run_id, output } => {
+            let records = export::event_records(&client, &run_id).await?;
+            if output.as_deref() == Some(std::path::Path::new("-")) {
+                export::write_jsonl_to(std::io::stdout().lock(), &records)?;
+                return Ok(());
+            }
+            let path = output.unwrap_or_else(|| export::default_events_file(&run_id));
+            let dumped = export::dump_events(&run_id, &records, &path)?;
+            print!("{}", export::format_event_dump(&dumped));
+            Ok(())
+        }
+        Command::ImportReview {
+            run_id,
+            review,
+            artifact_id,
+            author,
+        } => {
+            let comments = review_import::parse_review(&std::fs::read_to_string(&review)?)?;
+            let author = author
+                .or(config.default_author)
+                .or(config.default_source)
+                .unwrap_or_else(|| "human".to_string());
+            let imported = review_import::import_review(
+                &client,
+                &run_id,
+                comments,
+                artifact_id.as_deref(),
+                &author,
+                config.pagination.read_max_limit.max(1),
+            )
+            .await?;
+            print!("{}", review_import::format_review_import(&imported));
+            Ok(())
+        }
     }
 }
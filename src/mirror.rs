@@ -0,0 +1,586 @@
+//! Local mirror of a run's artifacts.
+//!
+//! When `mirror_artifacts_secs` is set, the artifacts of the run an agent
+//! attaches to are written to `.hotwired/artifacts/<run>/` in the project, so
+//! agents and humans can work on them with normal editors. The directory is
+//! polled at that interval: documents changed in the doc-editor are written to
+//! disk, and files changed on disk are pushed back as full-replace edits.
+//!
+//! A manifest next to the files records, per artifact, the backend's content
+//! hash and the SHA-256 of the file as last synced, which tells each side's
+//! changes apart. When both changed, the backend wins: the file is overwritten
+//! and the local version kept as `<file>.conflict` to merge and save again.
+//!
+//! Pushes are held to what a `doc_artifact_edit` call would be: nothing is
+//! pushed to an ended run or as a role that may not edit artifacts, and the
+//! backend's version is backed up before each push.
+
+use crate::backup::{self, BackupStore};
+use crate::ipc::messages::{ArtifactSummary, EditCategory, EditReason, EditType};
+use crate::ipc::traits::IpcClient;
+use crate::periodic;
+use crate::tools::protocol::{RoleCapabilityCache, RunStatusCache};
+use crate::tools::{artifacts, export};
+use crate::types::errors::MirrorError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tokio::task::JoinHandle;
+
+/// Sync state, kept in the mirror directory.
+const MANIFEST: &str = ".mirror.json";
+
+/// Suffix of the file a conflicting local version is saved to.
+const CONFLICT_SUFFIX: &str = ".conflict";
+
+/// Summary in the `edit_reason` of edits pushed from the mirror.
+const EDIT_SUMMARY: &str = "Edited in the local artifact mirror";
+
+/// Reason recorded on the backup taken before a push.
+const BACKUP_REASON: &str = "Before a push from the local artifact mirror";
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    /// Keyed by artifact id
+    artifacts: BTreeMap<String, Entry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    /// File name inside the mirror directory
+    file: String,
+    /// Backend content hash as last synced
+    content_hash: String,
+    /// SHA-256 of the file as last synced
+    digest: String,
+}
+
+/// What one pass changed, by file name.
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Written from the backend
+    pub pulled: Vec<String>,
+    /// Pushed to the backend
+    pub pushed: Vec<String>,
+    /// Changed on both sides; the local version was saved aside
+    pub conflicts: Vec<String>,
+    /// Changed locally but not pushed, as pushes were refused
+    pub held: Vec<String>,
+}
+
+impl SyncReport {
+    pub fn is_empty(&self) -> bool {
+        self.pulled.is_empty()
+            && self.pushed.is_empty()
+            && self.conflicts.is_empty()
+            && self.held.is_empty()
+    }
+}
+
+/// What pushes are checked against, as `doc_artifact_edit` calls are.
+#[derive(Clone, Default)]
+pub struct PushGuard {
+    /// Run states, for not pushing to ended runs
+    pub run_statuses: Arc<RunStatusCache>,
+    /// How long a run state is trusted; `None` skips the check
+    pub run_guard_ttl: Option<Duration>,
+    /// For not pushing as a role that may not edit artifacts
+    pub role_capabilities: Arc<RoleCapabilityCache>,
+    /// Where the backend's version is saved before each push
+    pub backups: Arc<BackupStore>,
+    /// Backups kept per artifact; 0 takes none
+    pub backup_keep: usize,
+}
+
+impl PushGuard {
+    /// Why `role` may not push to `run_id` now, if it may not. A state or
+    /// capabilities that can't be fetched allow the push, and the backend decides.
+    async fn refusal<C: IpcClient>(&self, client: &C, run_id: &str, role: &str) -> Option<String> {
+        if let Some(ttl) = self.run_guard_ttl {
+            if let Ok(state) = self.run_statuses.status(client, run_id, ttl).await {
+                if state.status.is_ended() {
+                    return Some(format!("the run has ended (status: {})", state.status));
+                }
+            }
+        }
+        let capabilities = self
+            .role_capabilities
+            .capabilities(client, run_id, role)
+            .await?;
+        (!capabilities.can_edit_artifacts)
+            .then(|| format!("role '{}' is not permitted to edit artifacts", role))
+    }
+}
+
+/// Mirror directory of `run_id` in `project`.
+pub fn dir(project: &Path, run_id: &str) -> PathBuf {
+    project.join(".hotwired").join("artifacts").join(run_id)
+}
+
+/// Bring `dir` and the backend's artifacts of `run_id` in line once. Local
+/// changes are pushed as edits by `role` when `guard` allows; documents are
+/// read `page_lines` lines at a time. Failing to list the artifacts or to use
+/// the directory fails the pass; a failure on one artifact is logged and
+/// retried next pass. Local changes `guard` refuses stay on disk, unpushed,
+/// and fail the pass once everything else is synced.
+pub async fn sync<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: &str,
+    dir: &Path,
+    page_lines: i64,
+    guard: &PushGuard,
+) -> Result<SyncReport, MirrorError> {
+    std::fs::create_dir_all(dir)?;
+    let manifest_path = dir.join(MANIFEST);
+    let mut manifest: Manifest = match std::fs::read(&manifest_path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Manifest::default(),
+        Err(e) => return Err(e.into()),
+    };
+    let listed = artifacts::list_artifacts(client, run_id).await?.artifacts;
+
+    let mut used: HashSet<String> = manifest
+        .artifacts
+        .values()
+        .map(|entry| entry.file.clone())
+        .collect();
+    used.insert(MANIFEST.to_string());
+
+    let pass = Pass {
+        client,
+        run_id,
+        role,
+        dir,
+        page_lines,
+        guard,
+        refusal: OnceCell::new(),
+    };
+    let mut report = SyncReport::default();
+    let mut synced = BTreeMap::new();
+    for artifact in &listed {
+        let entry = manifest.artifacts.remove(&artifact.id);
+        let file = match &entry {
+            Some(entry) => entry.file.clone(),
+            None => export::file_name(&artifact.filename, &artifact.id, &mut used),
+        };
+        match pass
+            .artifact(artifact, &file, entry.as_ref(), &mut report)
+            .await
+        {
+            Ok(next) => {
+                synced.insert(artifact.id.clone(), next);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to mirror artifact {}: {}", artifact.id, e);
+                if let Some(entry) = entry {
+                    synced.insert(artifact.id.clone(), entry);
+                }
+            }
+        }
+    }
+
+    // Artifacts gone from the run drop out of the manifest; their files stay
+    manifest.artifacts = synced;
+    std::fs::write(&manifest_path, serde_json::to_vec_pretty(&manifest)?)?;
+    if let (Some(Some(reason)), false) = (pass.refusal.get(), report.held.is_empty()) {
+        return Err(MirrorError::NotPushed {
+            files: report.held.join(", "),
+            reason: reason.clone(),
+        });
+    }
+    Ok(report)
+}
+
+/// Sync `dir` every `interval` until the task is aborted. Failures are logged
+/// once per outage.
+pub fn spawn<C: IpcClient + 'static>(
+    client: Arc<C>,
+    run_id: String,
+    role: String,
+    dir: PathBuf,
+    page_lines: i64,
+    interval: Duration,
+    guard: PushGuard,
+) -> JoinHandle<()> {
+    let name = format!("Artifact mirror of run {}", run_id);
+    periodic::spawn(name, interval, move || {
+        let client = client.clone();
+        let run_id = run_id.clone();
+        let role = role.clone();
+        let dir = dir.clone();
+        let guard = guard.clone();
+        async move {
+            let report = sync(client.as_ref(), &run_id, &role, &dir, page_lines, &guard).await?;
+            if !report.is_empty() {
+                tracing::info!(
+                    "Artifact mirror of run {}: pulled {:?}, pushed {:?}, conflicts {:?}",
+                    run_id,
+                    report.pulled,
+                    report.pushed,
+                    report.conflicts
+                );
+            }
+            Ok::<_, MirrorError>(())
+        }
+    })
+}
+
+/// Arguments shared by every artifact of one sync pass.
+struct Pass<'a, C> {
+    client: &'a C,
+    run_id: &'a str,
+    role: &'a str,
+    dir: &'a Path,
+    page_lines: i64,
+    guard: &'a PushGuard,
+    /// Why pushes are refused this pass, checked before the first one
+    refusal: OnceCell<Option<String>>,
+}
+
+impl<C: IpcClient> Pass<'_, C> {
+    /// Sync one artifact mirrored to `file`; returns its new manifest entry.
+    async fn artifact(
+        &self,
+        artifact: &ArtifactSummary,
+        file: &str,
+        entry: Option<&Entry>,
+        report: &mut SyncReport,
+    ) -> Result<Entry, MirrorError> {
+        let local = match std::fs::read_to_string(self.dir.join(file)) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let (Some(entry), Some(local)) = (entry, local.as_deref()) else {
+            // New to the mirror, or deleted locally: take the backend's version
+            return self.pull(artifact, file, local.as_deref(), report).await;
+        };
+
+        let local_changed = digest(local) != entry.digest;
+        let remote_changed = artifact.content_hash != entry.content_hash;
+        match (local_changed, remote_changed) {
+            (false, false) => Ok(entry.clone()),
+            (false, true) => self.pull(artifact, file, None, report).await,
+            (true, false) => {
+                let refusal = self
+                    .refusal
+                    .get_or_init(|| self.guard.refusal(self.client, self.run_id, self.role))
+                    .await;
+                if refusal.is_some() {
+                    report.held.push(file.to_string());
+                    return Ok(entry.clone());
+                }
+                self.back_up(&artifact.id).await;
+                let response = artifacts::edit_artifact(
+                    self.client,
+                    self.run_id,
+                    &artifact.id,
+                    EditType::FullReplace,
+                    &entry.content_hash,
+                    local,
+                    None,
+                    None,
+                    None,
+                    Some(&EditReason {
+                        category: EditCategory::Other,
+                        summary: EDIT_SUMMARY.to_string(),
+                        related_comment_ids: vec![],
+                    }),
+                    Some(self.role),
+                )
+                .await?;
+                if !response.success {
+                    // Edited in the doc-editor since the last listing
+                    return self.pull(artifact, file, Some(local), report).await;
+                }
+                report.pushed.push(file.to_string());
+                Ok(Entry {
+                    file: file.to_string(),
+                    content_hash: response.new_content_hash,
+                    digest: digest(local),
+                })
+            }
+            (true, true) => self.pull(artifact, file, Some(local), report).await,
+        }
+    }
+
+    /// Save the backend's version of `artifact_id` before it is replaced. A
+    /// failure is only logged, as for `doc_artifact_edit`.
+    async fn back_up(&self, artifact_id: &str) {
+        if self.guard.backup_keep == 0 {
+            return;
+        }
+        let document = match artifacts::read_full_content(
+            self.client,
+            self.run_id,
+            artifact_id,
+            self.page_lines,
+        )
+        .await
+        {
+            Ok(document) => document,
+            Err(e) => {
+                tracing::warn!("Failed to read {} for a backup: {}", artifact_id, e);
+                return;
+            }
+        };
+        let snapshot = backup::snapshot(self.run_id, &document, BACKUP_REASON, Some(self.role));
+        if let Err(e) = self.guard.backups.save(snapshot, self.guard.backup_keep) {
+            tracing::warn!("Failed to back up {}: {}", artifact_id, e);
+        }
+    }
+
+    /// Write the backend's version of `artifact` to `file`. A differing
+    /// `local` version is saved aside as a conflict first.
+    async fn pull(
+        &self,
+        artifact: &ArtifactSummary,
+        file: &str,
+        local: Option<&str>,
+        report: &mut SyncReport,
+    ) -> Result<Entry, MirrorError> {
+        let document =
+            artifacts::read_full_content(self.client, self.run_id, &artifact.id, self.page_lines)
+                .await?;
+        let path = self.dir.join(file);
+        match local {
+            Some(local) if local != document.content => {
+                std::fs::write(self.dir.join(format!("{}{}", file, CONFLICT_SUFFIX)), local)?;
+                report.conflicts.push(file.to_string());
+            }
+            Some(_) => {}
+            None => report.pulled.push(file.to_string()),
+        }
+        std::fs::write(&path, &document.content)?;
+        Ok(Entry {
+            file: file.to_string(),
+            content_hash: document.content_hash,
+            digest: digest(&document.content),
+        })
+    }
+}
+
+/// Hex SHA-256 of `content`.
+fn digest(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::{
+        DocArtifactEditResponse, DocArtifactListResponse, DocArtifactReadResponse, RoleCapabilities,
+    };
+    use crate::ipc::mock::MockIpcClient;
+    use crate::types::run_state::{RunState, RunStatus};
+
+    const RUN: &str = "123e4567-e89b-12d3-a456-426614174000";
+
+    fn backend(mock: &MockIpcClient, content: &str, hash: &str) {
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts", RUN),
+            DocArtifactListResponse {
+                run_id: RUN.to_string(),
+                artifacts: vec![ArtifactSummary {
+                    id: "spec".to_string(),
+                    filename: "docs/spec.md".to_string(),
+                    document_type: "spec".to_string(),
+                    total_lines: 1,
+                    content_hash: hash.to_string(),
+                    created_at: "2024-01-15T10:00:00Z".to_string(),
+                    updated_at: "2024-01-15T10:00:00Z".to_string(),
+                    tags: vec![],
+                }],
+            },
+        );
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/spec", RUN),
+            DocArtifactReadResponse {
+                artifact_id: "spec".to_string(),
+                filename: "docs/spec.md".to_string(),
+                content: content.to_string(),
+                content_hash: hash.to_string(),
+                total_lines: 1,
+                returned_lines: 1,
+                offset: 0,
+                has_more: false,
+                comments: None,
+            },
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_pulls_pushes_and_keeps_conflicts() {
+        let dir = std::env::temp_dir().join(format!("hotwired-mirror-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let file = dir.join("spec.md");
+        let mock = MockIpcClient::new();
+
+        // First pass writes the document
+        backend(&mock, "one\n", "h1");
+        let report = sync(&mock, RUN, "builder", &dir, 500, &PushGuard::default())
+            .await
+            .unwrap();
+        assert_eq!(report.pulled, vec!["spec.md"]);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "one\n");
+        assert!(
+            sync(&mock, RUN, "builder", &dir, 500, &PushGuard::default())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // A local change is pushed against the last synced hash
+        std::fs::write(&file, "two\n").unwrap();
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/spec/edit", RUN),
+            DocArtifactEditResponse {
+                success: true,
+                artifact_id: "spec".to_string(),
+                new_content_hash: "h2".to_string(),
+                edit_id: "e1".to_string(),
+                affected_comments: vec![],
+                conflict: None,
+            },
+        );
+        let report = sync(&mock, RUN, "builder", &dir, 500, &PushGuard::default())
+            .await
+            .unwrap();
+        assert_eq!(report.pushed, vec!["spec.md"]);
+        let edit = &mock.requests_to(&format!("/api/runs/{}/artifacts/spec/edit", RUN))[0];
+        assert!(edit.contains(r#""contentHash":"h1""#));
+        assert!(edit.contains(r#""newContent":"two\n""#));
+        assert!(edit.contains(r#""source":"builder""#));
+        backend(&mock, "two\n", "h2");
+        assert!(
+            sync(&mock, RUN, "builder", &dir, 500, &PushGuard::default())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Changed on both sides: the backend wins, the local version is kept
+        std::fs::write(&file, "three\n").unwrap();
+        backend(&mock, "remote\n", "h3");
+        let report = sync(&mock, RUN, "builder", &dir, 500, &PushGuard::default())
+            .await
+            .unwrap();
+        assert_eq!(report.conflicts, vec!["spec.md"]);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "remote\n");
+        assert_eq!(
+            std::fs::read_to_string(dir.join("spec.md.conflict")).unwrap(),
+            "three\n"
+        );
+        assert_eq!(
+            mock.requests_to(&format!("/api/runs/{}/artifacts/spec/edit", RUN))
+                .len(),
+            1
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pushes_are_guarded_and_backed_up() {
+        let dir =
+            std::env::temp_dir().join(format!("hotwired-mirror-guard-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let file = dir.join("spec.md");
+        let edits = format!("/api/runs/{}/artifacts/spec/edit", RUN);
+        let mock = MockIpcClient::new();
+        backend(&mock, "one\n", "h1");
+        mock.when_called(
+            &edits,
+            DocArtifactEditResponse {
+                success: true,
+                artifact_id: "spec".to_string(),
+                new_content_hash: "h2".to_string(),
+                edit_id: "e1".to_string(),
+                affected_comments: vec![],
+                conflict: None,
+            },
+        );
+        let guard = PushGuard {
+            run_guard_ttl: Some(Duration::from_secs(60)),
+            backup_keep: 5,
+            ..PushGuard::default()
+        };
+        let reviewer = RoleCapabilities {
+            can_edit_artifacts: false,
+            ..RoleCapabilities::default()
+        };
+        guard
+            .role_capabilities
+            .record(RUN, "reviewer", Some(reviewer));
+        let active = RunState {
+            status: RunStatus::from("active"),
+            phase: "build".to_string(),
+        };
+        guard
+            .run_statuses
+            .record(RUN, active, std::time::Instant::now());
+        sync(&mock, RUN, "builder", &dir, 500, &guard)
+            .await
+            .unwrap();
+        std::fs::write(&file, "two\n").unwrap();
+
+        // A role that may not edit keeps its change on disk, unpushed
+        let err = sync(&mock, RUN, "reviewer", &dir, 500, &guard)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("spec.md not pushed"));
+        assert!(err.to_string().contains("'reviewer'"));
+        assert!(mock.requests_to(&edits).is_empty());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "two\n");
+
+        // An allowed role pushes it, backing up the backend's version first
+        let report = sync(&mock, RUN, "builder", &dir, 500, &guard)
+            .await
+            .unwrap();
+        assert_eq!(report.pushed, vec!["spec.md"]);
+        let backups = guard.backups.list(RUN, "spec").unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].content, "one\n");
+        assert_eq!(backups[0].role.as_deref(), Some("builder"));
+
+        // Nothing is pushed to an ended run
+        backend(&mock, "two\n", "h2");
+        sync(&mock, RUN, "builder", &dir, 500, &guard)
+            .await
+            .unwrap();
+        let completed = RunState {
+            status: RunStatus::from("completed"),
+            phase: "done".to_string(),
+        };
+        guard
+            .run_statuses
+            .record(RUN, completed, std::time::Instant::now());
+        std::fs::write(&file, "three\n").unwrap();
+        let err = sync(&mock, RUN, "builder", &dir, 500, &guard)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("the run has ended"));
+        assert_eq!(mock.requests_to(&edits).len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dir_is_per_run_inside_project() {
+        assert_eq!(
+            dir(Path::new("/work/app"), RUN),
+            PathBuf::from(format!("/work/app/.hotwired/artifacts/{}", RUN))
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Where Hotwired keeps its files.
+//!
+//! Everything lives in `~/.hotwired/` unless overridden:
+//! - `HOTWIRED_HOME` moves all of it (config, socket, auth token, logs, state).
+//! - `XDG_STATE_HOME` moves logs and saved state to `$XDG_STATE_HOME/hotwired/`.
+//! - `XDG_RUNTIME_DIR` is used for the socket and auth token when the backend
+//!   has created `$XDG_RUNTIME_DIR/hotwired/hotwired.sock`.
+//!
+//! XDG variables that are unset, empty, or relative are ignored, as the XDG
+//! Base Directory spec requires.
+
+use std::path::{Path, PathBuf};
+
+/// Overrides every Hotwired directory.
+pub const HOME_ENV: &str = "HOTWIRED_HOME";
+
+const SOCKET_FILE: &str = "hotwired.sock";
+
+/// Resolved Hotwired directories.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HotwiredDirs {
+    /// Config file location
+    pub home: PathBuf,
+    /// Backend socket and auth token
+    pub runtime: PathBuf,
+    /// Logs and saved run context
+    pub state: PathBuf,
+}
+
+impl HotwiredDirs {
+    /// Resolve from the process environment.
+    pub fn resolve() -> Self {
+        Self::from_env(
+            |key| std::env::var(key).ok(),
+            dirs::home_dir().as_deref(),
+            Path::exists,
+        )
+    }
+
+    /// Resolve from `lookup` (normally `std::env::var`) and the user's home
+    /// directory. `exists` tells whether a path exists on disk.
+    pub fn from_env(
+        lookup: impl Fn(&str) -> Option<String>,
+        home_dir: Option<&Path>,
+        exists: impl Fn(&Path) -> bool,
+    ) -> Self {
+        if let Some(home) = lookup(HOME_ENV).filter(|h| !h.is_empty()) {
+            let home = PathBuf::from(home);
+            return Self {
+                runtime: home.clone(),
+                state: home.clone(),
+                home,
+            };
+        }
+
+        let legacy = home_dir.unwrap_or(Path::new("/tmp")).join(".hotwired");
+        let xdg = |key: &str| {
+            lookup(key)
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .map(|dir| dir.join("hotwired"))
+        };
+        let runtime = xdg("XDG_RUNTIME_DIR")
+            .filter(|dir| exists(&dir.join(SOCKET_FILE)))
+            .unwrap_or_else(|| legacy.clone());
+        let state = xdg("XDG_STATE_HOME").unwrap_or_else(|| legacy.clone());
+        Self {
+            home: legacy,
+            runtime,
+            state,
+        }
+    }
+
+    /// Config file: `<home>/config.json`.
+    pub fn config_file(&self) -> PathBuf {
+        self.home.join("config.json")
+    }
+
+    /// Default backend socket: `<runtime>/hotwired.sock`.
+    pub fn socket(&self) -> PathBuf {
+        self.runtime.join(SOCKET_FILE)
+    }
+
+    /// Auth token written by the desktop app: `<runtime>/auth_token`.
+    pub fn auth_token(&self) -> PathBuf {
+        self.runtime.join("auth_token")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(vars: &[(&str, &str)], existing: &[&str]) -> HotwiredDirs {
+        HotwiredDirs::from_env(
+            |key| {
+                vars.iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            },
+            Some(Path::new("/home/dev")),
+            |path| existing.iter().any(|e| Path::new(e) == path),
+        )
+    }
+
+    #[test]
+    fn test_defaults_to_dot_hotwired() {
+        let dirs = resolve(&[], &[]);
+        assert_eq!(
+            dirs.config_file(),
+            PathBuf::from("/home/dev/.hotwired/config.json")
+        );
+        assert_eq!(
+            dirs.socket(),
+            PathBuf::from("/home/dev/.hotwired/hotwired.sock")
+        );
+        assert_eq!(dirs.state, PathBuf::from("/home/dev/.hotwired"));
+    }
+
+    #[test]
+    fn test_hotwired_home_overrides_everything() {
+        let dirs = resolve(
+            &[
+                ("HOTWIRED_HOME", "/opt/hw"),
+                ("XDG_STATE_HOME", "/home/dev/.local/state"),
+            ],
+            &[],
+        );
+        assert_eq!(dirs.config_file(), PathBuf::from("/opt/hw/config.json"));
+        assert_eq!(dirs.auth_token(), PathBuf::from("/opt/hw/auth_token"));
+        assert_eq!(dirs.state, PathBuf::from("/opt/hw"));
+    }
+
+    #[test]
+    fn test_xdg_state_home_moves_logs_and_state() {
+        let dirs = resolve(&[("XDG_STATE_HOME", "/home/dev/.local/state")], &[]);
+        assert_eq!(dirs.state, PathBuf::from("/home/dev/.local/state/hotwired"));
+        assert_eq!(dirs.home, PathBuf::from("/home/dev/.hotwired"));
+
+        let relative = resolve(&[("XDG_STATE_HOME", "state")], &[]);
+        assert_eq!(relative.state, PathBuf::from("/home/dev/.hotwired"));
+    }
+
+    #[test]
+    fn test_runtime_dir_used_only_when_backend_socket_is_there() {
+        let vars = [("XDG_RUNTIME_DIR", "/run/user/1000")];
+        assert_eq!(
+            resolve(&vars, &[]).socket(),
+            PathBuf::from("/home/dev/.hotwired/hotwired.sock")
+        );
+
+        let dirs = resolve(&vars, &["/run/user/1000/hotwired/hotwired.sock"]);
+        assert_eq!(
+            dirs.socket(),
+            PathBuf::from("/run/user/1000/hotwired/hotwired.sock")
+        );
+        assert_eq!(
+            dirs.auth_token(),
+            PathBuf::from("/run/user/1000/hotwired/auth_token")
+        );
+    }
+}
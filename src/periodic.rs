@@ -0,0 +1,72 @@
+//! Background tasks that repeat on a fixed interval (session heartbeat,
+//! artifact mirror).
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Run `task` now and then every `interval` until the returned handle is aborted.
+/// A failure doesn't stop the task: it is logged once per outage as "`name`
+/// failed", and the next success as "`name` restored".
+pub fn spawn<F, Fut, T, E>(name: String, interval: Duration, mut task: F) -> JoinHandle<()>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, E>> + Send,
+    E: Display,
+{
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut failing = false;
+        loop {
+            ticker.tick().await;
+            match task().await {
+                Ok(_) => {
+                    if failing {
+                        tracing::info!("{} restored", name);
+                    }
+                    failing = false;
+                }
+                Err(e) => {
+                    if !failing {
+                        tracing::warn!("{} failed: {}", name, e);
+                    }
+                    failing = true;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failures_do_not_stop_the_task() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        let handle = spawn(
+            "Flaky task".to_string(),
+            Duration::from_secs(10),
+            move || {
+                let run = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if run.is_multiple_of(2) {
+                        Err("backend unavailable")
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+        );
+        tokio::time::sleep(Duration::from_secs(35)).await;
+        handle.abort();
+
+        // Immediately, then at 10s, 20s and 30s
+        assert_eq!(runs.load(Ordering::SeqCst), 4);
+    }
+}
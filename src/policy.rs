@@ -0,0 +1,199 @@
+//! Who may close which comment threads.
+//!
+//! The backend lets any agent take any action on any comment, so a writer can
+//! reject the reviewer's feedback or mark its own question addressed. Rules
+//! from the config (`comment_policies`) and the role's capabilities are
+//! checked here first; a refused action gets a policy error and never reaches
+//! the backend.
+
+use crate::ipc::messages::{CommentDetail, ResolveAction, RoleCapabilities};
+use serde::Deserialize;
+
+/// Whose threads a rule covers.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Threads {
+    /// Threads the role started
+    Own,
+    /// Threads started by another role
+    Others,
+    #[default]
+    Any,
+}
+
+/// Comment actions a role may not take.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommentPolicy {
+    /// Role the rule restricts
+    pub role: String,
+    /// Refused actions
+    pub deny: Vec<ResolveAction>,
+    /// Threads the rule covers (default any)
+    #[serde(default)]
+    pub threads: Threads,
+    /// Only threads started by these roles; all when empty
+    #[serde(default)]
+    pub authors: Vec<String>,
+}
+
+impl CommentPolicy {
+    fn forbids(&self, role: &str, action: ResolveAction, comment: &CommentDetail) -> bool {
+        let own = comment.author == role;
+        self.role == role
+            && self.deny.contains(&action)
+            && match self.threads {
+                Threads::Own => own,
+                Threads::Others => !own,
+                Threads::Any => true,
+            }
+            && (self.authors.is_empty() || self.authors.contains(&comment.author))
+    }
+}
+
+/// Whether `action` closes the thread.
+pub fn is_terminal(action: ResolveAction) -> bool {
+    matches!(
+        action,
+        ResolveAction::Reject | ResolveAction::Address | ResolveAction::Resolve
+    )
+}
+
+/// Why `role` may not take `action` on `comment`, if it may not. Config rules
+/// are checked before the role's capabilities (`None` when unknown).
+pub fn refusal(
+    rules: &[CommentPolicy],
+    capabilities: Option<&RoleCapabilities>,
+    role: &str,
+    action: ResolveAction,
+    comment: &CommentDetail,
+) -> Option<String> {
+    let verb = action_name(action);
+    if rules.iter().any(|rule| rule.forbids(role, action, comment)) {
+        return Some(format!(
+            "Policy: role '{}' may not {} comment {} by '{}' (comment_policies in the config).",
+            role, verb, comment.id, comment.author
+        ));
+    }
+    let capabilities = capabilities?;
+    let own = comment.author == role;
+    if action == ResolveAction::Reject && !own && !capabilities.can_reject_comments {
+        return Some(format!(
+            "Policy: role '{}' may not reject comments by other roles in this run; \
+            reply instead, or ask '{}' to withdraw it.",
+            role, comment.author
+        ));
+    }
+    if is_terminal(action) && own && !capabilities.can_close_own_comments {
+        return Some(format!(
+            "Policy: role '{}' may not {} its own comment {} in this run; \
+            another role has to close it.",
+            role, verb, comment.id
+        ));
+    }
+    None
+}
+
+fn action_name(action: ResolveAction) -> &'static str {
+    match action {
+        ResolveAction::Accept => "accept",
+        ResolveAction::Reject => "reject",
+        ResolveAction::Reply => "reply to",
+        ResolveAction::Address => "address",
+        ResolveAction::Resolve => "resolve",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(author: &str) -> CommentDetail {
+        CommentDetail {
+            id: "c-1".to_string(),
+            comment_type: "issue".to_string(),
+            status: "open".to_string(),
+            selection_start: 0,
+            selection_end: 4,
+            selection_text: None,
+            content: "Define the TTL".to_string(),
+            suggested_text: None,
+            author: author.to_string(),
+            resolved_by: None,
+            resolved_at: None,
+            resolution_note: None,
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            mentions: vec![],
+            parent_comment_id: None,
+        }
+    }
+
+    fn rules() -> Vec<CommentPolicy> {
+        serde_json::from_value(serde_json::json!([
+            {"role": "writer", "deny": ["reject"], "threads": "others", "authors": ["reviewer"]},
+            {"role": "writer", "deny": ["reject", "address", "resolve"], "threads": "own"}
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_config_rules() {
+        let rules = rules();
+        let refused = refusal(
+            &rules,
+            None,
+            "writer",
+            ResolveAction::Reject,
+            &comment("reviewer"),
+        )
+        .unwrap();
+        assert!(refused.contains("may not reject comment c-1 by 'reviewer'"));
+        assert!(refusal(
+            &rules,
+            None,
+            "writer",
+            ResolveAction::Address,
+            &comment("writer")
+        )
+        .is_some());
+
+        // Outside the rules: other authors, other actions, other roles
+        for (role, action, author) in [
+            ("writer", ResolveAction::Reject, "human"),
+            ("writer", ResolveAction::Reply, "writer"),
+            ("writer", ResolveAction::Address, "reviewer"),
+            ("reviewer", ResolveAction::Reject, "writer"),
+        ] {
+            assert_eq!(
+                refusal(&rules, None, role, action, &comment(author)),
+                None,
+                "{} {:?} {}",
+                role,
+                action,
+                author
+            );
+        }
+    }
+
+    #[test]
+    fn test_role_capabilities() {
+        let capabilities = RoleCapabilities {
+            can_reject_comments: false,
+            can_close_own_comments: false,
+            ..RoleCapabilities::default()
+        };
+        let check =
+            |action, author| refusal(&[], Some(&capabilities), "writer", action, &comment(author));
+        assert!(check(ResolveAction::Reject, "reviewer").is_some());
+        assert!(check(ResolveAction::Resolve, "writer").is_some());
+        assert!(check(ResolveAction::Address, "reviewer").is_none());
+        assert!(check(ResolveAction::Accept, "writer").is_none());
+        assert!(refusal(
+            &[],
+            Some(&RoleCapabilities::default()),
+            "writer",
+            ResolveAction::Reject,
+            &comment("reviewer")
+        )
+        .is_none());
+    }
+}
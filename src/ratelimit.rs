@@ -0,0 +1,137 @@
+//! Token-bucket rate limits on tool calls.
+//!
+//! An agent stuck in a loop can call the same tool hundreds of times a minute,
+//! each call a backend round trip. Every tool class has a bucket that refills
+//! at a steady rate; a call that finds its bucket empty is refused with how
+//! long to wait, and never reaches the backend.
+
+use crate::config::{RateLimit, RateLimitConfig};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Which bucket a tool call draws from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToolClass {
+    Read,
+    Mutating,
+}
+
+impl ToolClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ToolClass::Read => "read",
+            ToolClass::Mutating => "mutating",
+        }
+    }
+}
+
+/// Tokens left and when they were last counted.
+struct Bucket {
+    tokens: f64,
+    counted_at: Instant,
+}
+
+/// One token bucket per tool class.
+#[derive(Default)]
+pub struct RateLimiter {
+    limits: RateLimitConfig,
+    buckets: Mutex<HashMap<ToolClass, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimitConfig) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// The limit of `class`.
+    pub fn limit(&self, class: ToolClass) -> RateLimit {
+        match class {
+            ToolClass::Read => self.limits.read,
+            ToolClass::Mutating => self.limits.mutating,
+        }
+    }
+
+    /// Take a token for a `class` call made at `now`, or return how long until
+    /// one is available. Refused calls take nothing.
+    pub fn acquire(&self, class: ToolClass, now: Instant) -> Result<(), Duration> {
+        let limit = self.limit(class);
+        if limit.per_minute == 0 {
+            return Ok(());
+        }
+        let burst = f64::from(limit.burst());
+        let per_sec = f64::from(limit.per_minute) / 60.0;
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(class).or_insert(Bucket {
+            tokens: burst,
+            counted_at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.counted_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * per_sec).min(burst);
+        bucket.counted_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_sec))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(per_minute: u32, burst: u32) -> RateLimiter {
+        let limit = RateLimit {
+            per_minute,
+            burst: Some(burst),
+        };
+        RateLimiter::new(RateLimitConfig {
+            read: RateLimit::default(),
+            mutating: limit,
+        })
+    }
+
+    #[test]
+    fn test_burst_then_steady_rate() {
+        let limiter = limiter(60, 3);
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.acquire(ToolClass::Mutating, start), Ok(()));
+        }
+        let wait = limiter.acquire(ToolClass::Mutating, start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(1));
+
+        // Half a token later, still half a second to go
+        let later = start + Duration::from_millis(500);
+        let wait = limiter.acquire(ToolClass::Mutating, later).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+        assert_eq!(
+            limiter.acquire(ToolClass::Mutating, start + Duration::from_secs(1)),
+            Ok(())
+        );
+
+        // A long pause refills only up to the burst
+        let idle = start + Duration::from_secs(600);
+        for _ in 0..3 {
+            assert_eq!(limiter.acquire(ToolClass::Mutating, idle), Ok(()));
+        }
+        assert!(limiter.acquire(ToolClass::Mutating, idle).is_err());
+    }
+
+    #[test]
+    fn test_classes_are_limited_separately() {
+        let limiter = limiter(60, 1);
+        let now = Instant::now();
+        assert_eq!(limiter.acquire(ToolClass::Mutating, now), Ok(()));
+        assert!(limiter.acquire(ToolClass::Mutating, now).is_err());
+        // per_minute 0: never limited
+        for _ in 0..1000 {
+            assert_eq!(limiter.acquire(ToolClass::Read, now), Ok(()));
+        }
+    }
+}
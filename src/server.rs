@@ -1,1166 +1,7959 @@
+use crate::approval::{self, PendingApprovals};
+use crate::backup::{self, BackupStore};
+use crate::bridge::Bridge;
+use crate::config::{AgentToolNames, Config, PaginationConfig};
+use crate::dedup::RecentCalls;
+use crate::features;
+use crate::heartbeat;
+use crate::ipc::client::tcp_address;
 use crate::ipc::messages::{
+    AckMessageRequest,
+    AckNotificationRequest,
+    ArtifactBackup,
+    ArtifactBackupList,
+    ArtifactCommit,
+    AwaitInputRequest,
+    CommentDetail,
     DocArtifactAcceptSuggestionRequest,
     DocArtifactAddCommentRequest,
+    DocArtifactChangelogRequest,
+    DocArtifactCommitRequest,
     DocArtifactCreateRequest,
     DocArtifactEditRequest,
+    DocArtifactFeedbackSummaryRequest,
+    DocArtifactImportReviewRequest,
     DocArtifactListCommentsRequest,
     DocArtifactListRequest,
     DocArtifactListSuggestionsRequest,
+    DocArtifactReadAllRequest,
     DocArtifactReadRequest,
+    DocArtifactReadResponse,
     DocArtifactRejectSuggestionRequest,
+    DocArtifactRenderRequest,
     DocArtifactResolveCommentRequest,
+    DocArtifactReviewContextRequest,
+    DocArtifactSearchAllRequest,
     DocArtifactSearchRequest,
+    DocArtifactSetTagsRequest,
     // Edit suggestions (Mode 2)
     DocArtifactSuggestEditRequest,
+    DownstreamServer,
+    DumpEventsRequest,
+    EditCategory,
+    EditReason,
+    EditType,
+    ExportRunRequest,
+    GetAgentCapabilitiesRequest,
+    GetArtifactReadersRequest,
+    GetHandoffsRequest,
+    GetImpedimentResolutionRequest,
+    GetImpedimentsRequest,
+    GetLatestSummaryRequest,
+    GetMessageDeliveriesRequest,
+    GetMessagesRequest,
+    GetMyContextRequest,
+    GetNotificationsRequest,
+    GetPlaybookRequest,
     GetProtocolRequest,
+    GetRunConfigRequest,
+    GetRunContextRequest,
     GetRunStatusRequest,
+    GetRunUsageRequest,
     HandoffRequest,
     // Terminal workflow tools
     HotwireRequest,
+    HotwireResponse,
     ListActiveRunsRequest,
+    ListCompletedTasksRequest,
+    ListImpedimentTemplatesRequest,
     ListPlaybooksRequest,
+    Multiplexer,
+    OffsetUnit,
+    OutputFormat,
     PairRequest,
+    PairResponse,
+    PostSummaryRequest,
+    ProtocolStepCompleteRequest,
+    ProtocolStepsListRequest,
+    ReadRenderMode,
+    RenderedArtifact,
+    ReportFormat,
     ReportImpedimentRequest,
     ReportStatusRequest,
+    ReportUsageRequest,
     RequestEndRunRequest,
     RequestInputRequest,
+    ResolveAction,
     ResolveImpedimentRequest,
     RespondToEndRequestRequest,
+    RestoreBackupRequest,
+    ResumeRunRequest,
+    ResumeRunResponse,
+    RoleCapabilities,
+    SearchRunsRequest,
     SendMessageRequest,
+    SetRunContextRequest,
+    SweepStaleSuggestionsRequest,
+    SwitchRoleRequest,
     TaskCompleteRequest,
 };
 use crate::ipc::traits::IpcClient;
-use crate::tools::{artifacts, protocol, status, terminal};
+use crate::logging::LogLevelHandle;
+use crate::mirror::{self, PushGuard};
+use crate::policy::{self, CommentPolicy};
+use crate::ratelimit::{RateLimiter, ToolClass};
+use crate::state::StateStore;
+use crate::tools::artifacts::{CommentCache, CommentFilter, COMMENT_PREFETCH_TTL};
+use crate::tools::protocol::{ProtocolCache, RoleCapabilityCache, RunStatusCache};
+use crate::tools::status::StatusDebouncer;
+use crate::tools::terminal::{PlaybookCache, PLAYBOOK_CACHE_TTL};
+use crate::tools::usage::UsageAccumulator;
+use crate::tools::validate::{self, Validate};
+use crate::tools::{
+    artifacts, batch, changelog, chunk, compact, export, feedback, git, handoffs, history, html,
+    impediment_templates, notifications, offsets, protocol, read_receipts, report, review_import,
+    run_config, run_context, status, steps, summaries, sweep, tables, terminal, text, threads,
+    timestamps, usage,
+};
+use crate::types::errors::IpcError;
+use crate::watcher;
+use crate::webhook::{WebhookEvent, Webhooks};
 use rmcp::{
-    handler::server::{router::tool::ToolRouter, wrapper::Parameters},
+    handler::server::{router::tool::ToolRouter, tool::ToolCallContext, wrapper::Parameters},
     model::*,
-    tool, tool_handler, tool_router, ErrorData as McpError,
+    service::{NotificationContext, RequestContext},
+    tool, tool_router, ErrorData as McpError, Peer, RoleServer,
 };
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long startup waits for the backend's feature list.
+const FEATURE_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Tools that change run, session, or document state. Hidden in read-only mode.
+pub const MUTATING_TOOLS: &[&str] = &[
+    "report_status",
+    "send_message",
+    "task_complete",
+    "protocol_step_complete",
+    "report_usage",
+    "post_summary",
+    "set_run_context",
+    "report_impediment",
+    "resolve_impediment",
+    "request_input",
+    "ack_notification",
+    "ack_message",
+    "handoff",
+    "switch_role",
+    "request_end_run",
+    "respond_to_end_request",
+    "hotwire",
+    "pair",
+    "resume_run",
+    "doc_artifact_create",
+    "doc_artifact_edit",
+    "restore_backup",
+    "doc_artifact_tag",
+    "doc_artifact_add_comment",
+    "doc_artifact_resolve_comment",
+    "doc_artifact_suggest_edit",
+    "doc_artifact_accept_suggestion",
+    "doc_artifact_reject_suggestion",
+    "sweep_stale_suggestions",
+    "doc_artifact_commit",
+    "doc_artifact_render",
+    "export_run",
+    "dump_events",
+    "doc_artifact_import_review",
+];
+
+/// Tools that only read. Every tool is in exactly one of this list and
+/// [`MUTATING_TOOLS`].
+pub const READ_ONLY_TOOLS: &[&str] = &[
+    "ping",
+    "get_my_context",
+    "get_protocol",
+    "get_run_status",
+    "get_message_deliveries",
+    "get_messages",
+    "list_completed_tasks",
+    "list_impediment_templates",
+    "get_impediments",
+    "get_impediment_resolution",
+    "await_input",
+    "get_handoffs",
+    "get_agent_capabilities",
+    "get_notifications",
+    "protocol_steps_list",
+    "get_run_usage",
+    "get_latest_summary",
+    "get_run_context",
+    "get_run_config",
+    "list_active_runs",
+    "search_runs",
+    "list_playbooks",
+    "get_playbook",
+    "doc_artifact_list",
+    "doc_artifact_read",
+    "doc_artifact_review_context",
+    "get_artifact_readers",
+    "doc_artifact_changelog",
+    "doc_artifact_feedback_summary",
+    "doc_artifact_search",
+    "doc_artifact_search_all",
+    "doc_artifact_read_all",
+    "doc_artifact_list_comments",
+    "doc_artifact_list_suggestions",
+];
+
+/// What a mutating tool checks before it acts, for refusing a call held for
+/// approval before the human is asked (see [`preflight`]).
+struct Preflight {
+    /// Why the arguments are invalid, if they are
+    invalid: Option<String>,
+    /// Whether the tool refuses calls in ended runs
+    refuses_ended_runs: bool,
+}
+
+macro_rules! preflight {
+    (
+        refuses_ended_runs { $($guarded:literal => $guarded_request:ty,)* }
+        allows_ended_runs { $($open:literal => $open_request:ty,)* }
+        unchecked { $($unchecked:literal,)* }
+    ) => {
+        /// Check `arguments` the way mutating `tool` does before acting. `None`
+        /// for tools that aren't mutating.
+        fn preflight(
+            tool: &str,
+            arguments: Option<&JsonObject>,
+            pagination: &PaginationConfig,
+        ) -> Option<Preflight> {
+            fn invalid<T: DeserializeOwned + Validate>(
+                arguments: Option<&JsonObject>,
+                pagination: &PaginationConfig,
+            ) -> Option<String> {
+                let arguments = serde_json::Value::Object(arguments.cloned().unwrap_or_default());
+                match serde_json::from_value::<T>(arguments) {
+                    Ok(params) => params.validate(pagination).err(),
+                    Err(e) => Some(e.to_string()),
+                }
+            }
+            let (invalid, refuses_ended_runs) = match tool {
+                $($guarded => (invalid::<$guarded_request>(arguments, pagination), true),)*
+                $($open => (invalid::<$open_request>(arguments, pagination), false),)*
+                $($unchecked => (None, false),)*
+                _ => return None,
+            };
+            Some(Preflight {
+                invalid,
+                refuses_ended_runs,
+            })
+        }
+    };
+}
+
+preflight! {
+    refuses_ended_runs {
+        "report_status" => ReportStatusRequest,
+        "send_message" => SendMessageRequest,
+        "task_complete" => TaskCompleteRequest,
+        "protocol_step_complete" => ProtocolStepCompleteRequest,
+        "post_summary" => PostSummaryRequest,
+        "set_run_context" => SetRunContextRequest,
+        "report_impediment" => ReportImpedimentRequest,
+        "resolve_impediment" => ResolveImpedimentRequest,
+        "request_input" => RequestInputRequest,
+        "ack_notification" => AckNotificationRequest,
+        "ack_message" => AckMessageRequest,
+        "handoff" => HandoffRequest,
+        "switch_role" => SwitchRoleRequest,
+        "request_end_run" => RequestEndRunRequest,
+        "respond_to_end_request" => RespondToEndRequestRequest,
+        "doc_artifact_create" => DocArtifactCreateRequest,
+        "doc_artifact_edit" => DocArtifactEditRequest,
+        "restore_backup" => RestoreBackupRequest,
+        "doc_artifact_tag" => DocArtifactSetTagsRequest,
+        "doc_artifact_add_comment" => DocArtifactAddCommentRequest,
+        "doc_artifact_resolve_comment" => DocArtifactResolveCommentRequest,
+        "doc_artifact_suggest_edit" => DocArtifactSuggestEditRequest,
+        "doc_artifact_accept_suggestion" => DocArtifactAcceptSuggestionRequest,
+        "doc_artifact_reject_suggestion" => DocArtifactRejectSuggestionRequest,
+        "sweep_stale_suggestions" => SweepStaleSuggestionsRequest,
+        "doc_artifact_import_review" => DocArtifactImportReviewRequest,
+    }
+    allows_ended_runs {
+        "report_usage" => ReportUsageRequest,
+        "doc_artifact_commit" => DocArtifactCommitRequest,
+        "doc_artifact_render" => DocArtifactRenderRequest,
+        "export_run" => ExportRunRequest,
+        "dump_events" => DumpEventsRequest,
+    }
+    unchecked {
+        "hotwire",
+        "pair",
+        "resume_run",
+    }
+}
+
+/// The capability a role needs to use `tool`, for tools that check one.
+fn required_capability(tool: &str) -> Option<fn(&RoleCapabilities) -> bool> {
+    match tool {
+        "resolve_impediment" => Some(|c| c.can_resolve_impediments),
+        "request_end_run" => Some(|c| c.can_end_run),
+        "doc_artifact_create"
+        | "doc_artifact_edit"
+        | "restore_backup"
+        | "doc_artifact_accept_suggestion"
+        | "doc_artifact_reject_suggestion" => Some(|c| c.can_edit_artifacts),
+        _ => None,
+    }
+}
+
+/// The artifact mirror's sync task, with the run and role it syncs as.
+type MirrorTask = ((String, String), tokio::task::JoinHandle<()>);
 
 #[derive(Clone)]
 pub struct HotwiredMcp<C: IpcClient> {
     tool_router: ToolRouter<Self>,
     client: Arc<C>,
+    config: Arc<Config>,
+    /// Recently sent status reports, for skipping identical repeats
+    status_debouncer: Arc<StatusDebouncer>,
+    /// Recently fetched run statuses, for refusing mutations on ended runs
+    run_statuses: Arc<RunStatusCache>,
+    /// Fetched protocols per (run, role), for answering repeat `get_protocol` calls
+    protocols: Arc<ProtocolCache>,
+    /// Playbook list, prefetched at startup
+    playbooks: Arc<PlaybookCache>,
+    /// Comments prefetched alongside artifact reads
+    comments: Arc<CommentCache>,
+    /// Role capabilities per run, for refusing tools a role is not permitted to use
+    role_capabilities: Arc<RoleCapabilityCache>,
+    /// Current run, role, and last-read hashes; persisted per session when configured
+    state: Arc<StateStore>,
+    /// (Zellij session name, project dir) this server runs in, if known
+    session: Option<(String, String)>,
+    /// Backend socket path, for reporting in `get_my_context`
+    socket_path: Option<String>,
+    /// What names `session`, sent with `hotwire`/`pair` when the agent does not say
+    multiplexer: Option<Multiplexer>,
+    /// Handle for runtime log level changes (MCP logging/setLevel)
+    log_handle: Option<LogLevelHandle>,
+    /// Where selected run events are forwarded
+    webhooks: Arc<Webhooks>,
+    /// MCP servers declared by the attached run's playbook
+    bridge: Arc<Bridge>,
+    /// The MCP client, once initialized; told when bridged tools change
+    peer: Arc<std::sync::OnceLock<Peer<RoleServer>>>,
+    /// `report_usage` deltas not yet sent to the backend
+    usage: Arc<UsageAccumulator>,
+    /// Local artifact mirror of the attached run, if enabled
+    mirror: Arc<std::sync::Mutex<Option<MirrorTask>>>,
+    /// Token buckets per tool class, checked before a call is dispatched
+    rate_limiter: Arc<RateLimiter>,
+    /// Recent mutating calls, for answering identical repeats without running them
+    recent_calls: Arc<RecentCalls>,
+    /// Documents saved before destructive edits, for `restore_backup`
+    backups: Arc<BackupStore>,
+    /// Approval requests of held calls the human has not answered yet
+    approvals: Arc<PendingApprovals>,
 }
 
 #[tool_router]
 impl<C: IpcClient + 'static> HotwiredMcp<C> {
     pub fn new(client: C) -> Self {
         Self {
-            tool_router: Self::tool_router(),
+            tool_router: Self::agent_tool_router(&Config::default().agent_tools()),
             client: Arc::new(client),
+            config: Arc::new(Config::default()),
+            status_debouncer: Arc::new(StatusDebouncer::default()),
+            run_statuses: Arc::new(RunStatusCache::default()),
+            protocols: Arc::new(ProtocolCache::default()),
+            playbooks: Arc::new(PlaybookCache::default()),
+            comments: Arc::new(CommentCache::default()),
+            role_capabilities: Arc::new(RoleCapabilityCache::default()),
+            state: Arc::new(StateStore::default()),
+            session: None,
+            socket_path: None,
+            multiplexer: None,
+            log_handle: None,
+            webhooks: Arc::new(Webhooks::default()),
+            bridge: Arc::new(Bridge::default()),
+            peer: Arc::new(std::sync::OnceLock::new()),
+            usage: Arc::new(UsageAccumulator::default()),
+            mirror: Arc::new(std::sync::Mutex::new(None)),
+            rate_limiter: Arc::new(RateLimiter::default()),
+            recent_calls: Arc::new(RecentCalls::default()),
+            backups: Arc::new(BackupStore::default()),
+            approvals: Arc::new(PendingApprovals::default()),
         }
     }
 
-    #[tool(description = "Test connectivity to the Hotwired MCP server and backend API")]
-    async fn ping(&self) -> Result<CallToolResult, McpError> {
-        match self.client.health_check().await {
-            Ok(()) => Ok(CallToolResult::success(vec![Content::text(
-                "pong - Connected to Hotwired backend",
-            )])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "MCP running but backend unavailable: {}",
-                e
-            ))])),
+    /// The tool router with descriptions worded for an agent whose own file
+    /// tools are `tools`.
+    fn agent_tool_router(tools: &AgentToolNames) -> ToolRouter<Self> {
+        let mut router = Self::tool_router();
+        for route in router.map.values_mut() {
+            if let Some(description) = &route.attr.description {
+                if description.contains("{read_tool}") || description.contains("{edit_tools}") {
+                    route.attr.description = Some(
+                        description
+                            .replace("{read_tool}", tools.read())
+                            .replace("{edit_tools}", tools.edit())
+                            .into(),
+                    );
+                }
+            }
         }
+        router
     }
 
-    #[tool(
-        description = "Fetch your complete protocol, role instructions, and initialization condition. Call this when a run is initialized or if you need to refresh your understanding of the task. This is the PRIMARY way to receive your workflow instructions."
-    )]
-    async fn get_protocol(
-        &self,
-        Parameters(params): Parameters<GetProtocolRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match protocol::get_protocol(&*self.client, &params.run_id, &params.agent_role).await {
-            Ok(response) => {
-                let formatted = protocol::format_protocol_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+    /// Apply user configuration (see [`Config`]). Tool descriptions are worded
+    /// for the configured agent type. Tools disabled by the allow/deny lists, or
+    /// that change state when `read_only` is set, are removed from the router
+    /// and never advertised.
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.tool_router = Self::agent_tool_router(&config.agent_tools());
+        for tool in self.tool_router.list_all() {
+            if !config.tools.is_enabled(&tool.name) {
+                tracing::info!("Tool '{}' disabled by configuration", tool.name);
+                self.tool_router.remove_route(&tool.name);
+            } else if config.read_only && MUTATING_TOOLS.contains(&tool.name.as_ref()) {
+                tracing::info!("Tool '{}' disabled in read-only mode", tool.name);
+                self.tool_router.remove_route(&tool.name);
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to get protocol: {}",
-                e
-            ))])),
         }
+        self.webhooks = Arc::new(Webhooks::new(config.webhooks.clone()));
+        self.rate_limiter = Arc::new(RateLimiter::new(config.rate_limits.clone()));
+        self.config = Arc::new(config);
+        self
     }
 
-    #[tool(
-        description = "Get the current status of a run (phase, active agents, etc.). Use this to check if a run is still active before calling other tools. If you don't have the protocol yet, call get_protocol instead."
-    )]
-    async fn get_run_status(
+    /// Ask the backend which optional features it supports and drop the tools
+    /// of those it lacks (see [`features`]). Keeps every tool if the backend
+    /// can't be reached within [`FEATURE_DISCOVERY_TIMEOUT`].
+    pub async fn with_backend_features(mut self) -> Self {
+        let discovered = tokio::time::timeout(
+            FEATURE_DISCOVERY_TIMEOUT,
+            features::discover(self.client.as_ref()),
+        )
+        .await
+        .unwrap_or(Err(IpcError::Timeout));
+        let supported = match discovered {
+            Ok(supported) => supported,
+            Err(e) => {
+                tracing::debug!("Skipping feature discovery: {}", e);
+                return self;
+            }
+        };
+        for tool in features::unsupported_tools(supported.as_deref()) {
+            if self.tool_router.has_route(tool) {
+                tracing::info!("Tool '{}' not supported by the backend", tool);
+                self.tool_router.remove_route(tool);
+            }
+        }
+        self
+    }
+
+    /// Enable runtime log level changes via MCP `logging/setLevel`.
+    pub fn with_log_handle(mut self, handle: LogLevelHandle) -> Self {
+        self.log_handle = Some(handle);
+        self
+    }
+
+    /// Keep the run context in `state` (e.g. loaded from this session's state file).
+    pub fn with_state(mut self, state: StateStore) -> Self {
+        self.state = Arc::new(state);
+        self
+    }
+
+    /// Keep backups taken before destructive edits in `backups` (e.g. below
+    /// the state dir) instead of in memory.
+    pub fn with_backups(mut self, backups: BackupStore) -> Self {
+        self.backups = Arc::new(backups);
+        self
+    }
+
+    /// Record where this server runs, for `get_my_context`.
+    pub fn with_session(
+        mut self,
+        session: Option<(String, String)>,
+        socket_path: Option<String>,
+    ) -> Self {
+        self.session = session;
+        self.socket_path = socket_path;
+        self
+    }
+
+    /// Record what kind of terminal session this server runs in.
+    pub fn with_multiplexer(mut self, multiplexer: Option<Multiplexer>) -> Self {
+        self.multiplexer = multiplexer;
+        self
+    }
+
+    /// Start sending session heartbeats for `session_name` in the background,
+    /// unless disabled by `heartbeat_secs`.
+    pub fn spawn_heartbeat(
         &self,
-        Parameters(params): Parameters<GetRunStatusRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match protocol::get_run_status(&*self.client, &params.run_id).await {
-            Ok(response) => {
-                let formatted = protocol::format_run_status_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+        session_name: String,
+        project_dir: String,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        let interval = self.config.heartbeat_interval()?;
+        tracing::info!(
+            "Sending session heartbeats for {} every {:?}",
+            session_name,
+            interval
+        );
+        Some(heartbeat::spawn(
+            self.client.clone(),
+            session_name,
+            project_dir,
+            interval,
+        ))
+    }
+
+    /// Bridge the MCP servers `run_id`'s playbook declares, in the background,
+    /// and tell the client when that changes its tool list.
+    fn spawn_bridge(&self, run_id: &str, servers: Vec<DownstreamServer>) {
+        if !self.config.bridge_mcp_servers() {
+            return;
+        }
+        let bridge = self.bridge.clone();
+        let peer = self.peer.clone();
+        let run_id = run_id.to_string();
+        let project = self.project_dir();
+        tokio::spawn(async move {
+            if !bridge.attach(&run_id, &servers, &project).await {
+                return;
+            }
+            if let Some(peer) = peer.get() {
+                if let Err(e) = peer.notify_tool_list_changed().await {
+                    tracing::debug!("Failed to announce bridged tools: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Mirror `run_id`'s artifacts into the project, pushing local changes as
+    /// `role`, unless `mirror_artifacts_secs` is off or the server is
+    /// read-only. Replaces the mirror of a previously attached run or role.
+    /// Pushes are full-replace edits, so the mirror is not started either when
+    /// `doc_artifact_edit` is disabled or full replaces need approval.
+    fn spawn_mirror(&self, run_id: &str, role: &str) {
+        let Some(interval) = self.config.mirror_interval() else {
+            return;
+        };
+        if self.config.read_only {
+            return;
+        }
+        let edit = serde_json::json!({ "editType": approval::FULL_REPLACE });
+        let refusal = if !self.config.tools.is_enabled("doc_artifact_edit") {
+            Some("doc_artifact_edit is disabled".to_string())
+        } else {
+            self.config
+                .approvals
+                .gate("doc_artifact_edit", edit.as_object())
+                .map(|gate| format!("'{}' calls need approval", gate))
+        };
+        if let Some(refusal) = refusal {
+            tracing::info!("Not mirroring artifacts of run {}: {}", run_id, refusal);
+            return;
+        }
+        let key = (run_id.to_string(), role.to_string());
+        let mut current = self.mirror.lock().unwrap();
+        if current.as_ref().is_some_and(|(running, _)| *running == key) {
+            return;
+        }
+        if let Some((_, task)) = current.take() {
+            task.abort();
+        }
+        let dir = mirror::dir(&self.project_dir(), run_id);
+        tracing::info!(
+            "Mirroring artifacts of run {} to {} every {:?}",
+            run_id,
+            dir.display(),
+            interval
+        );
+        let task = mirror::spawn(
+            self.client.clone(),
+            run_id.to_string(),
+            role.to_string(),
+            dir,
+            self.config.pagination.read_max_limit.max(1),
+            interval,
+            PushGuard {
+                run_statuses: self.run_statuses.clone(),
+                run_guard_ttl: self.config.run_guard_ttl(),
+                role_capabilities: self.role_capabilities.clone(),
+                backups: self.backups.clone(),
+                backup_keep: self.config.backup_keep(),
+            },
+        );
+        *current = Some((key, task));
+    }
+
+    /// When awaited, sends all usage reported but not yet sent. Can be
+    /// created before the server is moved into its service, to run at shutdown.
+    pub fn flush_usage(&self) -> impl std::future::Future<Output = ()> + Send + 'static {
+        let client = self.client.clone();
+        let accumulator = self.usage.clone();
+        async move {
+            let pending = accumulator.take(None);
+            if pending.is_empty() {
+                return;
+            }
+            if let Err(e) = usage::flush(client.as_ref(), &accumulator, pending).await {
+                tracing::warn!("Failed to send usage reports: {}", e);
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to get run status: {}",
-                e
-            ))])),
         }
     }
 
-    #[tool(
-        description = "Report your current status to the Hotwired dashboard. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended. If unsure, call `get_run_status` first."
-    )]
-    async fn report_status(
+    /// Notify the agent of documents edited by someone else since it read
+    /// them, unless disabled by `watch_artifacts_secs`.
+    fn spawn_watcher(&self) {
+        let Some(interval) = self.config.watch_interval() else {
+            return;
+        };
+        watcher::spawn(
+            self.client.clone(),
+            self.state.clone(),
+            self.peer.clone(),
+            interval,
+        );
+    }
+
+    /// Whether a bridged tool is exposed: the allow/deny lists apply as to
+    /// built-in tools, and read-only mode keeps only tools marked read-only.
+    fn bridged_tool_enabled(&self, tool: &Tool) -> bool {
+        let read_only = tool.annotations.as_ref().and_then(|a| a.read_only_hint) == Some(true);
+        self.config.tools.is_enabled(&tool.name) && (!self.config.read_only || read_only)
+    }
+
+    /// In the background, prefetch the playbook list and the protocol of a
+    /// restored run, then leave a connection open for the agent's first call.
+    /// Disabled by `warm_up`.
+    pub fn spawn_warm_up(&self) -> Option<tokio::task::JoinHandle<()>> {
+        if !self.config.warm_up() {
+            return None;
+        }
+        let client = self.client.clone();
+        let playbooks = self.playbooks.clone();
+        let protocols = self.protocols.clone();
+        let role_capabilities = self.role_capabilities.clone();
+        let context = self.state.context();
+        let protocol_ttl = self.config.protocol_cache_ttl();
+        Some(tokio::spawn(async move {
+            if let Err(e) = playbooks.prefetch(client.as_ref()).await {
+                tracing::debug!("Skipping warm-up, backend unavailable: {}", e);
+                return;
+            }
+            if let (Some(run_id), Some(role), Some(ttl)) =
+                (context.run_id, context.role, protocol_ttl)
+            {
+                match protocols
+                    .protocol(client.as_ref(), &run_id, &role, ttl, false)
+                    .await
+                {
+                    Ok(protocol) => role_capabilities.record(&run_id, &role, protocol.capabilities),
+                    Err(e) => tracing::debug!("Protocol prefetch for run {} failed: {}", run_id, e),
+                }
+            }
+            if let Err(e) = client.warm_up().await {
+                tracing::debug!("Backend warm-up failed: {}", e);
+            }
+        }))
+    }
+
+    /// Render a backend response in the requested format, falling back to the
+    /// configured `output_format`. `markdown` and `compact` are the prose and
+    /// token-efficient renderings of the response; `table` renders as markdown.
+    ///
+    /// Output longer than `pagination.max_output_chars` is cut into chunks
+    /// addressed by `continuation`. JSON is pretty-printed first, so it is cut
+    /// between lines and the chunks join back into the whole document.
+    fn render<T: Serialize>(
         &self,
-        Parameters(params): Parameters<ReportStatusRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match status::report_status(
-            &*self.client,
-            &params.run_id,
-            &params.status,
-            &params.message,
-            &params.source,
-            params.agent_status.as_deref(),
-        )
-        .await
-        {
-            Ok(()) => Ok(CallToolResult::success(vec![Content::text(
-                "Status reported successfully",
-            )])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to report status: {}",
-                e
-            ))])),
+        format: Option<OutputFormat>,
+        continuation: Option<&str>,
+        response: &T,
+        markdown: impl FnOnce(&T) -> String,
+        compact: impl FnOnce(&T) -> String,
+    ) -> CallToolResult {
+        match format.unwrap_or(self.config.output_format) {
+            OutputFormat::Markdown | OutputFormat::Table => {
+                self.chunked(markdown(response), continuation)
+            }
+            OutputFormat::Compact => self.chunked(compact(response), continuation),
+            OutputFormat::Json => self.chunked(json(response), continuation),
         }
     }
 
-    #[tool(
-        description = "Send a message to the conversation log. Use this for general communication that should be visible in the Hotwired dashboard. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
-    )]
-    async fn send_message(
+    /// [`Self::render`] for mutating tools, whose output is never cut: the rest
+    /// could only be had by calling the tool, and so changing state, again.
+    fn render_whole<T: Serialize>(
         &self,
-        Parameters(params): Parameters<SendMessageRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match status::send_message(
-            &*self.client,
-            &params.run_id,
-            &params.content,
-            &params.source,
-            params.target.as_deref(),
-            params.agent_status.as_deref(),
-        )
-        .await
-        {
-            Ok(event_id) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Message sent (event ID: {})",
-                event_id
-            ))])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to send message: {}",
-                e
-            ))])),
+        format: Option<OutputFormat>,
+        response: &T,
+        markdown: impl FnOnce(&T) -> String,
+        compact: impl FnOnce(&T) -> String,
+    ) -> CallToolResult {
+        let text = match format.unwrap_or(self.config.output_format) {
+            OutputFormat::Markdown | OutputFormat::Table => markdown(response),
+            OutputFormat::Compact => compact(response),
+            OutputFormat::Json => json(response),
+        };
+        CallToolResult::success(vec![Content::text(text)])
+    }
+
+    /// [`Self::render`] for list tools, which also have a markdown `table` rendering.
+    fn render_list<T: Serialize>(
+        &self,
+        format: Option<OutputFormat>,
+        continuation: Option<&str>,
+        response: &T,
+        markdown: impl FnOnce(&T) -> String,
+        compact: impl FnOnce(&T) -> String,
+        table: impl FnOnce(&T) -> String,
+    ) -> CallToolResult {
+        match format.unwrap_or(self.config.output_format) {
+            OutputFormat::Table => self.chunked(table(response), continuation),
+            _ => self.render(format, continuation, response, markdown, compact),
         }
     }
 
-    #[tool(
-        description = "Mark a specific task as complete. Use this to signal that a piece of work is done (not the entire workflow). MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
-    )]
-    async fn task_complete(
+    /// The chunk of `text` addressed by `continuation` (the first chunk if `None`).
+    fn chunked(&self, text: String, continuation: Option<&str>) -> CallToolResult {
+        let start = match continuation.map(chunk::parse_token).transpose() {
+            Ok(start) => start.unwrap_or_default(),
+            Err(e) => return CallToolResult::error(vec![Content::text(e)]),
+        };
+        let text = chunk::chunk(&text, start, self.config.pagination.max_output_chars());
+        CallToolResult::success(vec![Content::text(text)])
+    }
+
+    /// A list tool's `report`: written to `path` under the project directory,
+    /// else returned inline in chunks.
+    fn report(
         &self,
-        Parameters(params): Parameters<TaskCompleteRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match status::task_complete(
-            &*self.client,
-            &params.run_id,
-            &params.task_description,
-            &params.source,
-            params.outcome.as_deref(),
-            params.agent_status.as_deref(),
-        )
-        .await
-        {
-            Ok(()) => Ok(CallToolResult::success(vec![Content::text(
-                "Task marked as complete",
-            )])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to mark task complete: {}",
+        report: serde_json::Result<String>,
+        count: usize,
+        noun: &str,
+        path: Option<&str>,
+        continuation: Option<&str>,
+    ) -> CallToolResult {
+        let report = match report {
+            Ok(report) => report,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Failed to build {} report: {}",
+                    noun, e
+                ))])
+            }
+        };
+        let Some(path) = path else {
+            return self.chunked(report, continuation);
+        };
+        if self.config.read_only {
+            return CallToolResult::error(vec![Content::text(
+                "report_path is not available in read-only mode; omit it to get the report inline.",
+            )]);
+        }
+        let file = self.project_dir().join(path);
+        let written = file
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&file, report));
+        match written {
+            Ok(()) => CallToolResult::success(vec![Content::text(report::format_written(
+                count,
+                noun,
+                &file.display().to_string(),
+            ))]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to write report to {}: {}",
+                file.display(),
                 e
-            ))])),
+            ))]),
         }
     }
 
-    #[tool(
-        description = "Report a blocker or impediment that prevents progress. Use this when you need human intervention, are missing information, or encounter an error you cannot resolve. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
-    )]
-    async fn report_impediment(
+    /// Convert `offsets` to characters and check them against the current
+    /// document, also returning its content. Skips the read when no offset is
+    /// given.
+    async fn char_offsets<const N: usize>(
         &self,
-        Parameters(params): Parameters<ReportImpedimentRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match status::report_impediment(
-            &*self.client,
-            &params.run_id,
-            &params.impediment_type,
-            &params.description,
-            &params.source,
-            params.context.as_deref(),
-            params.suggestion.as_deref(),
-            params.agent_status.as_deref(),
-            params.response_format,
-        )
-        .await
-        {
-            Ok(()) => Ok(CallToolResult::success(vec![Content::text(
-                "Impediment reported successfully",
-            )])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to report impediment: {}",
-                e
-            ))])),
+        run_id: &str,
+        artifact_id: &str,
+        unit: Option<OffsetUnit>,
+        offsets: [(&str, Option<i64>); N],
+    ) -> Result<([Option<i64>; N], Option<DocArtifactReadResponse>), CallToolResult> {
+        if offsets.iter().all(|(_, offset)| offset.is_none()) {
+            return Ok((offsets.map(|(_, offset)| offset), None));
+        }
+        let document = self
+            .current_document(run_id, artifact_id)
+            .await
+            .map_err(|e| {
+                CallToolResult::error(vec![Content::text(format!(
+                    "Failed to read artifact to check offsets: {}",
+                    e
+                ))])
+            })?;
+        let unit = unit.unwrap_or_default();
+        let mut converted = [None; N];
+        for (slot, (name, offset)) in converted.iter_mut().zip(offsets) {
+            if let Some(offset) = offset {
+                let chars = offsets::to_char_offset(&document.content, name, offset, unit)
+                    .map_err(|e| {
+                        CallToolResult::error(vec![Content::text(format!("Invalid offset: {}", e))])
+                    })?;
+                *slot = Some(chars);
+            }
         }
+        Ok((converted, Some(document)))
     }
 
-    #[tool(
-        description = "Resolve an impediment raised by another agent. Use this when your role's protocol authorizes you to resolve certain types of impediments. The response must match the impediment's responseFormat options if one was defined. Check your role's capabilities in the protocol to know which impediment types you can resolve."
-    )]
-    async fn resolve_impediment(
+    /// `document` if offset checking already read it, else the current document.
+    async fn document_or_current(
         &self,
-        Parameters(params): Parameters<ResolveImpedimentRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match status::resolve_impediment(
-            &*self.client,
-            &params.run_id,
-            params.impediment_id,
-            params.response,
-            &params.source,
-            params.rationale.as_deref(),
-        )
-        .await
-        {
-            Ok(()) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Impediment {} resolved successfully",
-                params.impediment_id
-            ))])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to resolve impediment: {}",
-                e
-            ))])),
+        run_id: &str,
+        artifact_id: &str,
+        document: Option<DocArtifactReadResponse>,
+    ) -> Result<DocArtifactReadResponse, IpcError> {
+        match document {
+            Some(document) => Ok(document),
+            None => self.current_document(run_id, artifact_id).await,
         }
     }
 
-    #[tool(
-        description = "Request input or clarification from the human user. Use this when you need a decision, preference, or additional information to proceed. This will block the workflow until the human responds. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
-    )]
-    async fn request_input(
+    /// The whole current document.
+    async fn current_document(
         &self,
-        Parameters(params): Parameters<RequestInputRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match status::request_input(
+        run_id: &str,
+        artifact_id: &str,
+    ) -> Result<DocArtifactReadResponse, IpcError> {
+        artifacts::read_full_content(
             &*self.client,
-            &params.run_id,
-            &params.question,
-            &params.source,
-            params.context.as_deref(),
-            params.options,
+            run_id,
+            artifact_id,
+            self.config.pagination.read_max_limit.max(1),
         )
         .await
-        {
-            Ok(()) => Ok(CallToolResult::success(vec![Content::text(
-                "Input requested from human. Wait for response before continuing.",
-            )])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to request input: {}",
-                e
-            ))])),
+    }
+
+    /// Save `document` (the current content when `None`) as a backup before an
+    /// edit that replaces much of it. A backup that can't be taken is logged;
+    /// the edit goes ahead.
+    async fn back_up(
+        &self,
+        run_id: &str,
+        artifact_id: &str,
+        document: Option<&DocArtifactReadResponse>,
+        reason: &str,
+        role: Option<&str>,
+    ) {
+        let keep = self.config.backup_keep();
+        if keep == 0 {
+            return;
+        }
+        let snapshot = match document {
+            Some(document) => backup::snapshot(run_id, document, reason, role),
+            None => match self.current_document(run_id, artifact_id).await {
+                Ok(document) => backup::snapshot(run_id, &document, reason, role),
+                Err(e) => {
+                    tracing::warn!("Failed to read {} for a backup: {}", artifact_id, e);
+                    return;
+                }
+            },
+        };
+        if let Err(e) = self.backups.save(snapshot, keep) {
+            tracing::warn!("Failed to back up {}: {}", artifact_id, e);
         }
     }
 
-    #[tool(
-        description = "Hand off work to another agent. Use this when transitioning responsibility, such as when the strategist hands tasks to the builder, or vice versa. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
-    )]
-    async fn handoff(
+    /// Tell the backend `source` (or the default role) has read `document`,
+    /// for `get_artifact_readers`. Skipped when the backend has no read
+    /// receipts; a failed report is only logged.
+    async fn report_read(
         &self,
-        Parameters(params): Parameters<HandoffRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match status::handoff(
+        run_id: &str,
+        source: Option<&str>,
+        document: &DocArtifactReadResponse,
+    ) {
+        if !self.tool_router.has_route("get_artifact_readers") {
+            return;
+        }
+        let Some(role) = self.source_or_default(source) else {
+            return;
+        };
+        if let Err(e) = read_receipts::report_read(
             &*self.client,
-            &params.run_id,
-            &params.to,
-            &params.summary,
-            &params.source,
-            params.details.as_deref(),
-            params.artifacts,
+            run_id,
+            &document.artifact_id,
+            &document.content_hash,
+            &role,
+            !document.has_more,
         )
         .await
         {
-            Ok(()) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Handoff to {} recorded",
-                params.to
-            ))])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to record handoff: {}",
-                e
-            ))])),
+            tracing::debug!("Failed to report read of {}: {}", document.artifact_id, e);
         }
     }
 
-    #[tool(
-        description = "Request to end, restart, or pause the workflow. Use this when the workflow task is complete or when the scope has changed significantly. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
-    )]
-    async fn request_end_run(
+    /// Ids of the artifact's comments, in any status, that have a reply.
+    async fn replied_comments(
         &self,
-        Parameters(params): Parameters<RequestEndRunRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match status::request_end_run(
-            &*self.client,
-            &params.run_id,
-            &params.reason,
-            &params.description,
-            &params.source,
-            params.suggested_follow_up,
-        ).await {
-            Ok(request_id) => Ok(CallToolResult::success(vec![
-                Content::text(format!("End run request submitted (ID: {}). Other agents will be asked to confirm. Wait for consensus before proceeding.", request_id))
-            ])),
-            Err(e) => Ok(CallToolResult::success(vec![
-                Content::text(format!("Failed to request end run: {}", e))
-            ])),
-        }
+        run_id: &str,
+        artifact_id: &str,
+    ) -> Result<HashSet<String>, IpcError> {
+        let all = match self.comments.lookup(
+            run_id,
+            artifact_id,
+            Some("all"),
+            None,
+            None,
+            COMMENT_PREFETCH_TTL,
+        ) {
+            Some(all) => all,
+            None => {
+                artifacts::list_comments(
+                    &*self.client,
+                    run_id,
+                    artifact_id,
+                    Some("all"),
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await?
+            }
+        };
+        Ok(artifacts::replied_to(&all.comments))
     }
 
-    #[tool(
-        description = "Respond to another agent's request to end the workflow. Called when you're notified that another agent wants to end the run. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
-    )]
-    async fn respond_to_end_request(
+    /// Cut a comment or suggestion listing to the page asked for, returning
+    /// its offset and whether more follow. Reports export every record unless
+    /// `limit` is given.
+    fn page<T>(
         &self,
-        Parameters(params): Parameters<RespondToEndRequestRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match status::respond_to_end_request(
+        items: &mut Vec<T>,
+        offset: Option<i64>,
+        limit: Option<i64>,
+        report: Option<ReportFormat>,
+    ) -> (i64, bool) {
+        let limit = match (report, limit) {
+            (Some(_), None) => None,
+            (_, limit) => self.config.pagination.list_limit(limit),
+        };
+        let offset = offset.unwrap_or(0).max(0);
+        (offset, artifacts::page(items, offset as usize, limit))
+    }
+
+    /// An error result if `params` fail client-side validation.
+    fn invalid(&self, params: &impl Validate) -> Option<CallToolResult> {
+        params.validate(&self.config.pagination).err().map(|e| {
+            CallToolResult::error(vec![Content::text(format!("Invalid arguments: {}", e))])
+        })
+    }
+
+    /// An error result if `tool`'s class has used up its rate limit, saying
+    /// when to retry.
+    fn rate_limited(&self, tool: &str) -> Option<CallToolResult> {
+        let class = if MUTATING_TOOLS.contains(&tool) {
+            ToolClass::Mutating
+        } else {
+            ToolClass::Read
+        };
+        let wait = self.rate_limiter.acquire(class, Instant::now()).err()?;
+        let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+        tracing::warn!(
+            "Rate limited {} ({} tools), retry after {}s",
+            tool,
+            class.as_str(),
+            retry_after
+        );
+        let mut result = CallToolResult::error(vec![Content::text(format!(
+            "Rate limited: more than {} {} tool calls per minute. Retry after {}s. \
+            If you are repeating the same call, check its result instead of retrying.",
+            self.rate_limiter.limit(class).per_minute,
+            class.as_str(),
+            retry_after
+        ))]);
+        result.structured_content = Some(serde_json::json!({
+            "error": "rate_limited",
+            "tool": tool,
+            "toolClass": class.as_str(),
+            "retryAfterSecs": retry_after,
+        }));
+        Some(result)
+    }
+
+    /// An error result if the run-state guard is enabled and `run_id` has ended.
+    /// A failed status lookup lets the call through to the backend.
+    async fn ended_run(&self, run_id: &str) -> Option<CallToolResult> {
+        let ttl = self.config.run_guard_ttl()?;
+        let state = self
+            .run_statuses
+            .status(self.client.as_ref(), run_id, ttl)
+            .await
+            .ok()?;
+        state
+            .refusal(run_id)
+            .map(|refusal| CallToolResult::error(vec![Content::text(refusal)]))
+    }
+
+    /// An error result if the caller's role in `run_id` is known not to permit
+    /// `tool` (see [`required_capability`]). The role is `role`, else the role
+    /// this server is attached with in the run, else `default_source`. Unknown
+    /// roles or capabilities are allowed.
+    async fn not_permitted(
+        &self,
+        run_id: &str,
+        role: Option<&str>,
+        tool: &str,
+    ) -> Option<CallToolResult> {
+        let permitted = required_capability(tool)?;
+        let context = self.state.context();
+        let attached = context
+            .role
+            .filter(|_| context.run_id.as_deref() == Some(run_id));
+        let role = role
+            .map(String::from)
+            .or(attached)
+            .or_else(|| self.source_or_default(None))?;
+        let capabilities = self
+            .role_capabilities
+            .capabilities(self.client.as_ref(), run_id, &role)
+            .await?;
+        (!permitted(&capabilities)).then(|| {
+            CallToolResult::error(vec![Content::text(format!(
+                "Your role '{}' is not permitted to use {} in this run.",
+                role, tool
+            ))])
+        })
+    }
+
+    /// An error result if `role` may not take `action` on the comment, under the
+    /// configured comment policies or the role's capabilities. The comment is
+    /// only fetched when a rule could apply; if it can't be read, the backend
+    /// decides.
+    async fn comment_policy_refusal(
+        &self,
+        params: &DocArtifactResolveCommentRequest,
+        role: &str,
+    ) -> Option<CallToolResult> {
+        let rules: Vec<CommentPolicy> = self
+            .config
+            .comment_policies
+            .iter()
+            .filter(|rule| rule.role == role && rule.deny.contains(&params.action))
+            .cloned()
+            .collect();
+        let capabilities = self
+            .role_capabilities
+            .capabilities(self.client.as_ref(), &params.run_id, role)
+            .await
+            .filter(|c| !c.can_reject_comments || !c.can_close_own_comments);
+        if rules.is_empty() && capabilities.is_none() {
+            return None;
+        }
+        let comments = artifacts::list_comments(
             &*self.client,
             &params.run_id,
-            &params.request_id,
-            &params.response,
-            &params.source,
-            params.reason.as_deref(),
+            &params.artifact_id,
+            Some("all"),
+            None,
+            None,
+            None,
+            None,
         )
         .await
-        {
-            Ok(()) => {
-                let message = match params.response.as_str() {
-                    "agree" => "You agreed to end the run.".to_string(),
-                    "verify" => "You requested to verify before deciding. Perform your verification and call this tool again with 'agree' or 'disagree'.".to_string(),
-                    "disagree" => format!("You disagreed: {}", params.reason.unwrap_or_default()),
-                    _ => "Response recorded.".to_string(),
-                };
-                Ok(CallToolResult::success(vec![Content::text(message)]))
-            }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to respond to end request: {}",
-                e
-            ))])),
+        .ok()?;
+        let comment = comments
+            .comments
+            .iter()
+            .find(|c| c.id == params.comment_id)?;
+        let refusal = policy::refusal(&rules, capabilities.as_ref(), role, params.action, comment)?;
+        tracing::info!("Refused comment action: {}", refusal);
+        let mut result = CallToolResult::error(vec![Content::text(refusal)]);
+        result.structured_content = Some(serde_json::json!({
+            "error": "comment_policy",
+            "role": role,
+            "action": params.action,
+            "commentId": params.comment_id,
+            "commentAuthor": comment.author,
+        }));
+        Some(result)
+    }
+
+    /// The project the agent was started in, else where the MCP client launched us.
+    fn project_dir(&self) -> std::path::PathBuf {
+        match &self.session {
+            Some((_, project)) => std::path::PathBuf::from(project),
+            None => std::env::current_dir().unwrap_or_default(),
         }
     }
 
-    // =========================================================================
-    // TERMINAL WORKFLOW TOOLS (/hotwire, /pair)
-    // =========================================================================
+    /// The refusal a mutating tool would give `arguments` before acting:
+    /// invalid arguments, an ended run, a role not permitted to use it, or a
+    /// comment policy.
+    async fn preflight_refusal(
+        &self,
+        tool: &str,
+        arguments: Option<&JsonObject>,
+    ) -> Option<CallToolResult> {
+        let checks = preflight(tool, arguments, &self.config.pagination)?;
+        if let Some(invalid) = checks.invalid {
+            return Some(CallToolResult::error(vec![Content::text(format!(
+                "Invalid arguments: {}",
+                invalid
+            ))]));
+        }
+        let arg = |name: &str| {
+            arguments
+                .and_then(|args| args.get(name))
+                .and_then(|value| value.as_str())
+        };
+        let run_id = arg("runId")?;
+        if checks.refuses_ended_runs {
+            if let Some(ended) = self.ended_run(run_id).await {
+                return Some(ended);
+            }
+        }
+        if tool == "doc_artifact_resolve_comment" {
+            let params: DocArtifactResolveCommentRequest = serde_json::from_value(
+                serde_json::Value::Object(arguments.cloned().unwrap_or_default()),
+            )
+            .ok()?;
+            let resolved_by = self.author_or_default(params.resolved_by.as_deref())?;
+            return self.comment_policy_refusal(&params, &resolved_by).await;
+        }
+        // doc_artifact_create names its caller's role `createdBy`
+        let role = match tool {
+            "doc_artifact_create" => arg("createdBy"),
+            _ => arg("source"),
+        };
+        self.not_permitted(run_id, role, tool).await
+    }
 
-    #[tool(
-        description = "Initiate a new Hotwired workflow run from the terminal. \
-        Called by /hotwire command. Provide project path, Zellij session, and optional intent. \
-        Returns either: 'started' with your protocol (begin working immediately), \
-        or 'needs_confirmation' (wait for user to confirm in app, then call get_protocol)."
-    )]
-    async fn hotwire(
+    /// Hold a call to a tool gated by `approvals` until the human approves it
+    /// (see [`approval`]). Calls the tool would refuse anyway are refused
+    /// first, without asking. `None` lets the call run; otherwise the result
+    /// says it was refused, denied, or is still waiting.
+    async fn approval_refusal(
         &self,
-        Parameters(params): Parameters<HotwireRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match terminal::hotwire(
+        tool: &str,
+        arguments: Option<&JsonObject>,
+    ) -> Option<CallToolResult> {
+        let gate = self.config.approvals.gate(tool, arguments)?.to_string();
+        if let Some(refused) = self.preflight_refusal(tool, arguments).await {
+            return Some(refused);
+        }
+        let arg = |name: &str| {
+            arguments
+                .and_then(|args| args.get(name))
+                .and_then(|value| value.as_str())
+        };
+        let Some(run_id) = arg("runId") else {
+            return Some(CallToolResult::error(vec![Content::text(format!(
+                "{} needs human approval, which is asked for in a run; pass runId.",
+                tool
+            ))]));
+        };
+        let role = self
+            .source_or_default(arg("source"))
+            .unwrap_or_else(|| "agent".to_string());
+        let call = RecentCalls::key(tool, arguments);
+        let request_id = match self.approvals.get(&call) {
+            Some(request_id) => request_id,
+            None => {
+                let question = approval::question(tool, &gate, &role);
+                let details = approval::describe(arguments);
+                match status::request_input(
+                    &*self.client,
+                    run_id,
+                    &question,
+                    &role,
+                    Some(&details),
+                    Some(approval::OPTIONS.map(String::from).to_vec()),
+                )
+                .await
+                {
+                    Ok(Some(request_id)) => {
+                        self.webhooks.notify(
+                            WebhookEvent::ApprovalRequest,
+                            run_id,
+                            &role,
+                            question,
+                            serde_json::json!({
+                                "tool": tool,
+                                "requestId": request_id,
+                                "arguments": details,
+                            }),
+                        );
+                        self.approvals.insert(call.clone(), request_id.clone());
+                        request_id
+                    }
+                    Ok(None) => {
+                        return Some(CallToolResult::error(vec![Content::text(format!(
+                        "{} needs human approval, but the backend gave no request ID to wait on. \
+                            Ask the human with request_input before doing this another way.",
+                        tool
+                    ))]))
+                    }
+                    Err(e) => {
+                        return Some(
+                            self.run_tool_failure(
+                                tool,
+                                run_id,
+                                Some(&role),
+                                "Failed to request approval",
+                                e,
+                            )
+                            .await,
+                        )
+                    }
+                }
+            }
+        };
+        let timeout = self.config.approvals.timeout();
+        match status::await_input(
             &*self.client,
-            &params.project_path,
-            &params.zellij_session,
-            params.intent.as_deref(),
-            params.suggested_playbook.as_deref(),
-            params.suggested_artifacts,
+            run_id,
+            &request_id,
+            timeout,
+            status::INPUT_POLL_INTERVAL,
         )
         .await
         {
-            Ok(response) => {
-                let formatted = terminal::format_hotwire_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+            Ok(Some(answer)) => {
+                self.approvals.remove(&call);
+                if approval::is_approved(answer.answer.as_deref()) {
+                    tracing::info!("{} approved (request {})", tool, request_id);
+                    return None;
+                }
+                Some(CallToolResult::error(vec![Content::text(format!(
+                    "✗ {} was not approved{}: {}. It was not run; don't retry it unless the human asks you to.",
+                    tool,
+                    answer
+                        .answered_by
+                        .as_deref()
+                        .map(|by| format!(" by {}", by))
+                        .unwrap_or_default(),
+                    answer.answer.as_deref().unwrap_or("(no answer text)")
+                ))]))
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to initiate workflow: {}",
-                e
+            Ok(None) => Some(CallToolResult::error(vec![Content::text(format!(
+                "⏳ {} is waiting for human approval (request {}); nothing has run after {}s. \
+                Continue with other work and call {} again with the same arguments to keep waiting.",
+                tool,
+                request_id,
+                timeout.as_secs(),
+                tool
             ))])),
+            Err(e) => Some(
+                self.run_tool_failure(tool, run_id, Some(&role), "Failed to check approval", e)
+                    .await,
+            ),
         }
     }
 
-    #[tool(description = "Join an existing workflow run as a second agent. \
-        Called by /pair command. Automatically finds runs that need a second agent. \
-        Returns: 'joined' with your protocol and context, \
-        'needs_selection' (multiple runs available, select in app), \
-        'none' (no runs need pairing), or 'project_mismatch' (wrong directory).")]
-    async fn pair(
+    /// The agent role for a call: the explicit param, else `default_source` from config.
+    fn source_or_default(&self, source: Option<&str>) -> Option<String> {
+        source
+            .or(self.config.default_source.as_deref())
+            .map(String::from)
+    }
+
+    /// The author for a call: the explicit param, else `default_author`, else `default_source`.
+    fn author_or_default(&self, author: Option<&str>) -> Option<String> {
+        author
+            .or(self.config.default_author.as_deref())
+            .or(self.config.default_source.as_deref())
+            .map(String::from)
+    }
+
+    /// The agent's own role: the one it is attached with, else `default_author`,
+    /// else `default_source`.
+    fn my_role(&self) -> Option<String> {
+        self.state
+            .context()
+            .role
+            .or_else(|| self.author_or_default(None))
+    }
+
+    /// Build the result for a failed run-scoped tool call. When `forward_tool_errors`
+    /// is enabled the failure is also recorded as a `tool_error` event on the run,
+    /// so the human sees it in the dashboard. Forwarding is best-effort.
+    async fn run_tool_failure(
         &self,
-        Parameters(params): Parameters<PairRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        match terminal::pair(&*self.client, &params.zellij_session, &params.project_path).await {
-            Ok(response) => {
-                let formatted = terminal::format_pair_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+        tool: &str,
+        run_id: &str,
+        source: Option<&str>,
+        action: &str,
+        error: IpcError,
+    ) -> CallToolResult {
+        let unreachable = matches!(
+            error,
+            IpcError::NotConnected | IpcError::ConnectionFailed(_)
+        );
+        if self.config.forward_tool_errors && !unreachable {
+            if let Err(e) =
+                status::report_tool_error(&*self.client, run_id, tool, &error.to_string(), source)
+                    .await
+            {
+                tracing::warn!("Failed to forward {} error to run {}: {}", tool, run_id, e);
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to join workflow: {}",
-                e
-            ))])),
         }
+
+        CallToolResult::error(vec![Content::text(format!("{}: {}", action, error))])
     }
 
-    #[tool(description = "List active or resumable workflow runs. \
-        Use this before /hotwire to check if there are existing runs to continue. \
-        If my_role is set for a run, you were previously attached to it. \
-        Optionally filter by project path or Zellij session.")]
-    async fn list_active_runs(
+    #[tool(
+        description = "Test connectivity to the Hotwired MCP server and backend API. \
+        Reports the transport and backend address, round-trip latency, when the backend \
+        last answered, and writes queued locally that have not reached it yet."
+    )]
+    async fn ping(&self) -> Result<CallToolResult, McpError> {
+        let last_contact = self.client.last_contact();
+        let started = Instant::now();
+        let health = self.client.health_check().await;
+        let round_trip = started.elapsed();
+
+        let mut output = match &health {
+            Ok(()) => "pong - Connected to Hotwired backend\n".to_string(),
+            Err(e) => format!("MCP running but backend unavailable: {}\n", e),
+        };
+        let (transport, backend) = match self.socket_path.as_deref() {
+            Some(path) => match tcp_address(path) {
+                Some(address) => ("TCP", address),
+                None => ("Unix socket", path),
+            },
+            None => ("unknown", "unknown"),
+        };
+        let _ = write!(
+            output,
+            "\n**Transport:** {}\n**Backend:** `{}`\n",
+            transport, backend
+        );
+        if health.is_ok() {
+            let _ = writeln!(
+                output,
+                "**Round trip:** {:.1} ms",
+                round_trip.as_secs_f64() * 1000.0
+            );
+        }
+        let _ = writeln!(
+            output,
+            "**Last contact before this ping:** {}",
+            last_contact
+                .map(|at| timestamps::format_timestamp(
+                    &at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+                ))
+                .unwrap_or_else(|| "never".to_string())
+        );
+        let _ = writeln!(
+            output,
+            "**Queued writes:** {} usage report(s), {} tokens, waiting for the next flush",
+            self.usage.pending_reports(),
+            self.usage.pending_tokens()
+        );
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Return everything this server knows about you: Zellij session, project directory, \
+        agent type, backend socket, the run you are attached to, your role, the run's status, and your role's capabilities. \
+        Call this after a context compaction to recover who you are and which run you are in."
+    )]
+    async fn get_my_context(
         &self,
-        Parameters(params): Parameters<ListActiveRunsRequest>,
+        Parameters(params): Parameters<GetMyContextRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match terminal::list_active_runs(
+        let mut context = protocol::my_context(
             &*self.client,
-            params.project_path.as_deref(),
-            params.zellij_session.as_deref(),
+            &self.state.context(),
+            self.session.as_ref(),
+            self.socket_path.as_deref(),
         )
-        .await
-        {
-            Ok(response) => {
-                let formatted = terminal::format_active_runs(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
-            }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to list active runs: {}",
-                e
-            ))])),
-        }
+        .await;
+        context.agent_type = Some(self.config.agent_type().to_string());
+        Ok(self.render(
+            params.format,
+            params.continuation.as_deref(),
+            &context,
+            protocol::format_my_context,
+            compact::format_my_context,
+        ))
     }
 
     #[tool(
-        description = "List available playbooks with metadata for intent matching. \
-        Returns playbook information including roles, keywords, and initialization hints. \
-        Use this to match user intent to the appropriate playbook before calling hotwire. \
-        The role with is_initiating=true is the one you'll take when using /hotwire."
+        description = "Fetch your complete protocol, role instructions, and initialization condition. Call this when a run is initialized or if you need to refresh your understanding of the task. This is the PRIMARY way to receive your workflow instructions."
     )]
-    async fn list_playbooks(
+    async fn get_protocol(
         &self,
-        Parameters(_params): Parameters<ListPlaybooksRequest>,
+        Parameters(params): Parameters<GetProtocolRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match terminal::list_playbooks(&*self.client).await {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let fetched = match self.config.protocol_cache_ttl() {
+            Some(ttl) => {
+                self.protocols
+                    .protocol(
+                        self.client.as_ref(),
+                        &params.run_id,
+                        &params.agent_role,
+                        ttl,
+                        params.refresh.unwrap_or(false),
+                    )
+                    .await
+            }
+            None => protocol::get_protocol(&*self.client, &params.run_id, &params.agent_role).await,
+        };
+        match fetched {
             Ok(response) => {
-                let formatted = terminal::format_playbooks(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+                self.state
+                    .update(|c| c.attach(&params.run_id, &params.agent_role));
+                self.role_capabilities.record(
+                    &params.run_id,
+                    &params.agent_role,
+                    response.capabilities.clone(),
+                );
+                self.spawn_bridge(&params.run_id, response.mcp_servers.clone());
+                self.spawn_mirror(&params.run_id, &params.agent_role);
+                Ok(self.render(
+                    params.format,
+                    params.continuation.as_deref(),
+                    &response,
+                    protocol::format_protocol_response,
+                    compact::format_protocol_response,
+                ))
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to list playbooks: {}",
-                e
-            ))])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_protocol",
+                    &params.run_id,
+                    Some(&params.agent_role),
+                    "Failed to get protocol",
+                    e,
+                )
+                .await),
         }
     }
 
-    // =========================================================================
-    // DOC-ARTIFACT TOOLS
-    // =========================================================================
-
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] List all document artifacts in the run. Returns artifact IDs, filenames, types, and metadata."
+        description = "Get the current status of a run (phase, active agents, etc.). Use this to check if a run is still active before calling other tools. If you don't have the protocol yet, call get_protocol instead."
     )]
-    async fn doc_artifact_list(
+    async fn get_run_status(
         &self,
-        Parameters(params): Parameters<DocArtifactListRequest>,
+        Parameters(params): Parameters<GetRunStatusRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match artifacts::list_artifacts(&*self.client, &params.run_id).await {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match protocol::get_run_status(&*self.client, &params.run_id).await {
             Ok(response) => {
-                let formatted = artifacts::format_list_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+                self.protocols
+                    .observe_version(&params.run_id, response.protocol_version.as_deref());
+                Ok(self.render(
+                    params.format,
+                    params.continuation.as_deref(),
+                    &response,
+                    protocol::format_run_status_response,
+                    compact::format_run_status_response,
+                ))
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to list artifacts: {}",
-                e
-            ))])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_run_status",
+                    &params.run_id,
+                    None,
+                    "Failed to get run status",
+                    e,
+                )
+                .await),
         }
     }
 
-    #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] Read content from a tracked document artifact. \
-        Returns document content with metadata. For large documents, use offset/limit for pagination. \
-        The response includes a contentHash for conflict detection on subsequent edits. \
-        WARNING: Only use this for documents in the doc-editor. Use Read tool for source files."
-    )]
-    async fn doc_artifact_read(
+    #[tool(description = "Report your current status to the Hotwired dashboard. \
+        Add progress (artifact_id, section, percent_complete, blocking_on) so the dashboard and other agents, through get_run_status, see where you are without parsing the message. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended. If unsure, call `get_run_status` first.")]
+    async fn report_status(
         &self,
-        Parameters(params): Parameters<DocArtifactReadRequest>,
+        Parameters(params): Parameters<ReportStatusRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match artifacts::read_artifact(
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        // Validate agent status
+        if let Some(agent_status) = &params.agent_status {
+            if let Err(e) = status::validate_agent_status(agent_status) {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Invalid agent status: {}",
+                    e
+                ))]));
+            }
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+
+        let fingerprint = format!(
+            "{:?}|{}|{:?}|{:?}",
+            params.status, params.message, params.agent_status, params.progress
+        );
+        let now = Instant::now();
+        let window = self.config.status_dedup_window();
+        if self
+            .status_debouncer
+            .is_duplicate(&params.run_id, &source, &fingerprint, window, now)
+        {
+            tracing::debug!("Skipping repeated status report for run {}", params.run_id);
+            return Ok(CallToolResult::success(vec![Content::text(
+                "Status reported successfully",
+            )]));
+        }
+
+        match status::report_status(
             &*self.client,
             &params.run_id,
-            &params.artifact_id,
-            params.offset,
-            params.limit,
-            params.include_comments,
+            params.status,
+            &params.message,
+            &source,
+            params.agent_status.as_deref(),
+            params.progress.as_ref(),
         )
         .await
         {
-            Ok(response) => {
-                let formatted = artifacts::format_read_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+            Ok(()) => {
+                self.status_debouncer
+                    .record(&params.run_id, &source, fingerprint, now);
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Status reported successfully",
+                )]))
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to read artifact: {}",
-                e
-            ))])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "report_status",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to report status",
+                    e,
+                )
+                .await),
         }
     }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] Create a new document artifact in the run. \
-        Returns the artifact ID and content hash for subsequent edits."
+        description = "Send a message to the conversation log. Use this for general communication that should be visible in the Hotwired dashboard. \
+        Address it to one agent with target, to several with targets, or to every other agent with broadcast; each recipient gets it in its notifications. \
+        To answer a message, pass its event ID as reply_to_event_id so get_messages shows the reply in its thread. \
+        For signals that must not be missed (\"stop editing section 3, I'm rewriting it\"), set priority to high or urgent so recipients see it first, \
+        and requires_ack so each recipient confirms with ack_message. \
+        The result includes the event ID; get_message_deliveries shows who has acknowledged it since. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
     )]
-    async fn doc_artifact_create(
+    async fn send_message(
         &self,
-        Parameters(params): Parameters<DocArtifactCreateRequest>,
+        Parameters(params): Parameters<SendMessageRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match artifacts::create_artifact(
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        // Validate agent status
+        if let Some(agent_status) = &params.agent_status {
+            if let Err(e) = status::validate_agent_status(agent_status) {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Invalid agent status: {}",
+                    e
+                ))]));
+            }
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+
+        let mut targets: Vec<String> = Vec::new();
+        for target in params.target.iter().chain(&params.targets) {
+            if !targets.contains(target) {
+                targets.push(target.clone());
+            }
+        }
+        let broadcast = params.broadcast == Some(true);
+
+        match status::send_message(
             &*self.client,
             &params.run_id,
-            &params.filename,
-            params.initial_content.as_deref(),
-            params.document_type.as_deref(),
-            params.created_by.as_deref(),
+            &params.content,
+            &source,
+            &targets,
+            broadcast,
+            params.reply_to_event_id.as_deref(),
+            params.priority,
+            params.requires_ack == Some(true),
+            params.agent_status.as_deref(),
         )
         .await
         {
-            Ok(response) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "✓ Artifact created successfully\n\n\
-                **ID:** `{}`\n\
-                **Filename:** {}\n\
-                **Content Hash:** `{}`",
-                response.artifact_id, response.filename, response.content_hash
-            ))])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to create artifact: {}",
-                e
-            ))])),
+            Ok(response) => {
+                let recipients = if response.recipients.is_empty() {
+                    &targets
+                } else {
+                    &response.recipients
+                };
+                let mut text = match (broadcast, recipients.is_empty()) {
+                    (false, true) => format!("Message sent (event ID: {})", response.event_id),
+                    (true, true) => format!("Message broadcast (event ID: {})", response.event_id),
+                    (_, false) => format!(
+                        "Message {} to {} (event ID: {}). Call get_message_deliveries to see who has acknowledged it.",
+                        if broadcast { "broadcast" } else { "sent" },
+                        recipients.join(", "),
+                        response.event_id
+                    ),
+                };
+                if params.requires_ack == Some(true) {
+                    text.push_str(" Each recipient has been asked to confirm it with ack_message.");
+                }
+                Ok(CallToolResult::success(vec![Content::text(text)]))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "send_message",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to send message",
+                    e,
+                )
+                .await),
         }
     }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] Edit content in a tracked document artifact. \
-        WARNING: Only for documents in the doc-editor. Use Edit/Write tools for source files. \
-        Supports edit types: replace_range, insert, append, full_replace. \
-        Requires contentHash from doc_artifact_read for conflict detection."
+        description = "See which recipients of a message sent with send_message have acknowledged it in their notifications, and which have not yet."
     )]
-    async fn doc_artifact_edit(
+    async fn get_message_deliveries(
         &self,
-        Parameters(params): Parameters<DocArtifactEditRequest>,
+        Parameters(params): Parameters<GetMessageDeliveriesRequest>,
     ) -> Result<CallToolResult, McpError> {
-        // Validate edit type
-        if let Err(e) = artifacts::validate_edit_type(&params.edit_type) {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "Invalid edit type: {}",
-                e
-            ))]));
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
         }
-
-        match artifacts::edit_artifact(
-            &*self.client,
-            &params.run_id,
-            &params.artifact_id,
-            &params.edit_type,
-            &params.content_hash,
-            &params.new_content,
-            params.start_offset,
-            params.end_offset,
-            params.insert_offset,
-            params.edit_reason.as_deref(),
-            params.source.as_deref(),
-        )
-        .await
+        match notifications::get_message_deliveries(&*self.client, &params.run_id, &params.event_id)
+            .await
         {
-            Ok(response) => {
-                let formatted = artifacts::format_edit_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
-            }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to edit artifact: {}",
-                e
-            ))])),
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                notifications::format_message_deliveries,
+                compact::format_message_deliveries,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_message_deliveries",
+                    &params.run_id,
+                    None,
+                    "Failed to get message deliveries",
+                    e,
+                )
+                .await),
         }
     }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] Search within a tracked document artifact. \
-        Unlike grep, this search returns character offsets for precise editing, understands markdown structure, \
-        and returns surrounding context. Use this to find exact positions before making edits."
+        description = "Read a run's messages as threads: each reply (sent with reply_to_event_id) is listed under the message it answers, \
+        so a discussion between several agents can be followed. Pass thread with the event ID of any message to get only its thread."
     )]
-    async fn doc_artifact_search(
+    async fn get_messages(
         &self,
-        Parameters(params): Parameters<DocArtifactSearchRequest>,
+        Parameters(params): Parameters<GetMessagesRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match artifacts::search_artifact(
-            &*self.client,
-            &params.run_id,
-            &params.artifact_id,
-            &params.query,
-            params.match_type.as_deref(),
-            params.scope.as_deref(),
-            params.context_lines,
-            params.max_results,
-        )
-        .await
-        {
-            Ok(response) => {
-                let formatted = artifacts::format_search_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let events = match export::conversation_events(&*self.client, &params.run_id).await {
+            Ok(response) => response.events,
+            Err(e) => {
+                return Ok(self
+                    .run_tool_failure(
+                        "get_messages",
+                        &params.run_id,
+                        None,
+                        "Failed to read messages",
+                        e,
+                    )
+                    .await)
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to search artifact: {}",
-                e
-            ))])),
+        };
+        match threads::threads(&params.run_id, events, params.thread.as_deref()) {
+            Ok(threads) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &threads,
+                threads::format_threads,
+                compact::format_message_threads,
+            )),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(e)])),
         }
     }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] Add a comment to a tracked document. \
-        Comment types: comment (general feedback), question (needs clarification), \
-        suggestion (proposed change with replacement text), issue (problem to address). \
-        Selection range is in character offsets. \
-        To reply to an existing comment thread, provide the parent_comment_id."
+        description = "Mark a specific task as complete. Use this to signal that a piece of work is done (not the entire workflow). \
+        Put what it produced in results (artifact IDs, test runs, links to PRs or commits) so the run's retrospective can be built from it. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
     )]
-    async fn doc_artifact_add_comment(
+    async fn task_complete(
         &self,
-        Parameters(params): Parameters<DocArtifactAddCommentRequest>,
+        Parameters(params): Parameters<TaskCompleteRequest>,
     ) -> Result<CallToolResult, McpError> {
-        // Validate comment type
-        if let Err(e) = artifacts::validate_comment_type(&params.comment_type) {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "Invalid comment type: {}",
-                e
-            ))]));
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        // Validate agent status
+        if let Some(agent_status) = &params.agent_status {
+            if let Err(e) = status::validate_agent_status(agent_status) {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Invalid agent status: {}",
+                    e
+                ))]));
+            }
         }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
 
-        match artifacts::add_comment(
+        match status::task_complete(
             &*self.client,
             &params.run_id,
-            &params.artifact_id,
-            &params.comment_type,
-            params.selection_start,
-            params.selection_end,
-            &params.content,
-            params.suggested_text.as_deref(),
-            &params.author,
-            params.parent_comment_id.as_deref(),
+            &params.task_description,
+            &source,
+            params.outcome.as_deref(),
+            params.agent_status.as_deref(),
+            params.results.as_ref(),
         )
         .await
         {
-            Ok(response) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "✓ Comment added successfully\n\n\
-                **Comment ID:** `{}`\n\
-                **Selected text:** \"{}\"",
-                response.comment_id,
-                if response.selection_text.len() > 50 {
-                    format!("{}...", &response.selection_text[..50])
-                } else {
-                    response.selection_text
-                }
-            ))])),
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to add comment: {}",
-                e
-            ))])),
+            Ok(()) => {
+                self.webhooks.notify(
+                    WebhookEvent::TaskComplete,
+                    &params.run_id,
+                    &source,
+                    format!("{} completed: {}", source, params.task_description),
+                    serde_json::json!({
+                        "taskDescription": params.task_description,
+                        "outcome": params.outcome,
+                        "results": params.results,
+                    }),
+                );
+                Ok(CallToolResult::success(vec![Content::text(
+                    "Task marked as complete",
+                )]))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "task_complete",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to mark task complete",
+                    e,
+                )
+                .await),
         }
     }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] Take action on a document comment. \
-        Actions: accept (user approves feedback, agent should address it), \
-        reject (user disagrees, closes the thread), \
-        reply (continue discussion, thread stays open), \
-        address (agent has addressed the feedback, closes the thread). \
-        Terminal actions (reject, address) resolve the entire comment thread."
+        description = "List the tasks completed in a run with task_complete, with their outcomes and results, from one role or from all. \
+        Ends with totals of artifacts touched and test runs, for writing up the run's retrospective."
     )]
-    async fn doc_artifact_resolve_comment(
+    async fn list_completed_tasks(
         &self,
-        Parameters(params): Parameters<DocArtifactResolveCommentRequest>,
+        Parameters(params): Parameters<ListCompletedTasksRequest>,
     ) -> Result<CallToolResult, McpError> {
-        // Validate action
-        if let Err(e) = artifacts::validate_resolve_action(&params.action) {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "Invalid action: {}",
-                e
-            ))]));
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match status::list_completed_tasks(&*self.client, &params.run_id, params.role.as_deref())
+            .await
+        {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                status::format_completed_tasks,
+                compact::format_completed_tasks,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "list_completed_tasks",
+                    &params.run_id,
+                    None,
+                    "Failed to list completed tasks",
+                    e,
+                )
+                .await),
         }
+    }
 
-        match artifacts::resolve_comment(
+    #[tool(
+        description = "Report a blocker or impediment that prevents progress. Use this when you need human intervention, are missing information, or encounter an error you cannot resolve. \
+        Set escalate_after_seconds to have it escalated to the human again if it is still unresolved after that long. \
+        For common blockers, pass a template_id from list_impediment_templates: it fills in the type, a description lead-in, and the form the human answers with. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn report_impediment(
+        &self,
+        Parameters(mut params): Parameters<ReportImpedimentRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        // Validate agent status
+        if let Some(agent_status) = &params.agent_status {
+            if let Err(e) = status::validate_agent_status(agent_status) {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Invalid agent status: {}",
+                    e
+                ))]));
+            }
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        if let Some(template_id) = params.template_id.clone() {
+            let templates =
+                match impediment_templates::list_templates(&*self.client, &params.run_id).await {
+                    Ok(response) => response.templates,
+                    Err(e) => {
+                        return Ok(self
+                            .run_tool_failure(
+                                "report_impediment",
+                                &params.run_id,
+                                Some(&source),
+                                "Failed to load impediment templates",
+                                e,
+                            )
+                            .await)
+                    }
+                };
+            let Some(template) = templates.iter().find(|t| t.id == template_id) else {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "No impediment template `{}` in this run's playbook. Call list_impediment_templates to see them.",
+                    template_id
+                ))]));
+            };
+            impediment_templates::apply(&mut params, template);
+        }
+        let Some(impediment_type) = params.impediment_type else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "impediment_type is required unless template_id is given",
+            )]));
+        };
+
+        match status::report_impediment(
             &*self.client,
             &params.run_id,
-            &params.artifact_id,
-            &params.comment_id,
-            &params.action,
-            params.response.as_deref(),
-            &params.resolved_by,
+            impediment_type,
+            &params.description,
+            &source,
+            params.context.as_deref(),
+            params.suggestion.as_deref(),
+            params.agent_status.as_deref(),
+            params.response_format,
+            params.escalate_after_seconds,
+            params.template_id.as_deref(),
         )
         .await
         {
-            Ok(response) => {
-                let action_verb = match params.action.as_str() {
-                    "accept" => "accepted",
-                    "reject" => "rejected",
-                    "reply" => "replied to",
-                    "address" => "addressed",
-                    "resolve" => "resolved",
-                    _ => "updated",
+            Ok(()) => {
+                self.webhooks.notify(
+                    WebhookEvent::Impediment,
+                    &params.run_id,
+                    &source,
+                    format!("{} is blocked: {}", source, params.description),
+                    serde_json::json!({
+                        "impedimentType": impediment_type,
+                        "templateId": params.template_id,
+                        "description": params.description,
+                        "context": params.context,
+                        "suggestion": params.suggestion,
+                        "escalateAfterSeconds": params.escalate_after_seconds,
+                    }),
+                );
+                let text = match params.escalate_after_seconds {
+                    Some(seconds) => format!(
+                        "Impediment reported successfully. It escalates if not resolved within {}s.",
+                        seconds
+                    ),
+                    None => "Impediment reported successfully".to_string(),
                 };
-                Ok(CallToolResult::success(vec![Content::text(format!(
-                    "✓ Comment {} successfully\n\n\
-                    **Comment ID:** `{}`\n\
-                    **New status:** {}",
-                    action_verb, response.comment_id, response.new_status
-                ))]))
+                Ok(CallToolResult::success(vec![Content::text(text)]))
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to resolve comment: {}",
-                e
-            ))])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "report_impediment",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to report impediment",
+                    e,
+                )
+                .await),
         }
     }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] List comments on a document artifact. \
-        Filter by status (open/resolved/rejected/all) and comment type."
+        description = "List the impediment templates of a run's playbook: common blockers such as missing credentials or a library choice, \
+        each with its impediment type, description lead-in, and the form the human answers with. Pass a template's ID to report_impediment as template_id."
     )]
-    async fn doc_artifact_list_comments(
+    async fn list_impediment_templates(
         &self,
-        Parameters(params): Parameters<DocArtifactListCommentsRequest>,
+        Parameters(params): Parameters<ListImpedimentTemplatesRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match impediment_templates::list_templates(&*self.client, &params.run_id).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                impediment_templates::format_templates,
+                compact::format_impediment_templates,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "list_impediment_templates",
+                    &params.run_id,
+                    None,
+                    "Failed to list impediment templates",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "List the impediments you raised in this run with their status (open, escalated, resolved), how long they have been in it, \
+        and when they escalate next. Returns open and escalated impediments unless include_resolved is true."
+    )]
+    async fn get_impediments(
+        &self,
+        Parameters(params): Parameters<GetImpedimentsRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match artifacts::list_comments(
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        match status::get_impediments(
             &*self.client,
             &params.run_id,
-            &params.artifact_id,
-            params.status.as_deref(),
-            params.comment_type.as_deref(),
-            params.line_start,
-            params.line_end,
+            &source,
+            params.include_resolved.unwrap_or(false),
         )
         .await
         {
-            Ok(response) => {
-                let formatted = artifacts::format_comments_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
-            }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to list comments: {}",
-                e
-            ))])),
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                status::format_impediments,
+                compact::format_impediments,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_impediments",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to get impediments",
+                    e,
+                )
+                .await),
         }
     }
 
-    // =========================================================================
-    // EDIT SUGGESTION TOOLS (Mode 2: Suggest with diff preview)
-    // =========================================================================
+    #[tool(
+        description = "Get the resolution of an impediment you raised: its status, and once resolved, \
+        the human's structured response with each answer under its response_format field. \
+        Use this to pick up the answer instead of waiting for a terminal message."
+    )]
+    async fn get_impediment_resolution(
+        &self,
+        Parameters(params): Parameters<GetImpedimentResolutionRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match status::get_impediment_resolution(&*self.client, &params.run_id, params.impediment_id)
+            .await
+        {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                status::format_impediment_resolution,
+                compact::format_impediment_resolution,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_impediment_resolution",
+                    &params.run_id,
+                    None,
+                    "Failed to get impediment resolution",
+                    e,
+                )
+                .await),
+        }
+    }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] Create an edit suggestion linked to a comment. \
-        Instead of directly editing, this proposes a change that the user can preview and accept/reject. \
-        Edit types: replace_range (replace text between offsets), insert (insert at offset), \
-        append (add to end), full_replace (replace entire content)."
+        description = "Resolve an impediment raised by another agent. Use this when your role's protocol authorizes you to resolve certain types of impediments. The response must match the impediment's responseFormat options if one was defined: \
+        an object keyed by field id, with radio and select answers one of the option values and checkbox answers a list of them. Check your role's capabilities in the protocol to know which impediment types you can resolve."
     )]
-    async fn doc_artifact_suggest_edit(
+    async fn resolve_impediment(
         &self,
-        Parameters(params): Parameters<DocArtifactSuggestEditRequest>,
+        Parameters(params): Parameters<ResolveImpedimentRequest>,
     ) -> Result<CallToolResult, McpError> {
-        // Validate edit type
-        if let Err(e) = artifacts::validate_edit_type(&params.edit_type) {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "Invalid edit type: {}",
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        if let Some(denied) = self
+            .not_permitted(
+                &params.run_id,
+                params.source.as_deref(),
+                "resolve_impediment",
+            )
+            .await
+        {
+            return Ok(denied);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        // Check the answer against the impediment's form here, where the error
+        // can name the field; the backend only rejects it as a whole
+        match status::get_impediment_resolution(&*self.client, &params.run_id, params.impediment_id)
+            .await
+        {
+            Ok(impediment) => {
+                if let Some(Err(e)) = impediment
+                    .response_format
+                    .as_ref()
+                    .map(|form| validate::form_answer(form, &params.response))
+                {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Invalid arguments: response doesn't fit the form of impediment #{}: {}",
+                        params.impediment_id, e
+                    ))]));
+                }
+            }
+            Err(e) => tracing::debug!(
+                "Not checking the response to impediment {} locally: {}",
+                params.impediment_id,
                 e
-            ))]));
+            ),
         }
 
-        match artifacts::suggest_edit(
+        match status::resolve_impediment(
             &*self.client,
             &params.run_id,
-            &params.artifact_id,
-            &params.comment_id,
-            &params.edit_type,
-            params.start_offset,
-            params.end_offset,
-            &params.suggested_text,
+            params.impediment_id,
+            params.response,
+            &source,
             params.rationale.as_deref(),
-            &params.source,
         )
         .await
         {
-            Ok(response) => {
-                let formatted = artifacts::format_suggest_edit_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
-            }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to create suggestion: {}",
-                e
+            Ok(()) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "Impediment {} resolved successfully",
+                params.impediment_id
             ))])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "resolve_impediment",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to resolve impediment",
+                    e,
+                )
+                .await),
         }
     }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] Accept an edit suggestion and apply it to the document. \
-        This applies the suggested change to the document content."
+        description = "Request input or clarification from the human user. Use this when you need a decision, preference, or additional information to proceed. \
+        Returns a request ID; call await_input with it to block until the human responds. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
     )]
-    async fn doc_artifact_accept_suggestion(
+    async fn request_input(
         &self,
-        Parameters(params): Parameters<DocArtifactAcceptSuggestionRequest>,
+        Parameters(params): Parameters<RequestInputRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match artifacts::accept_suggestion(
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+
+        match status::request_input(
             &*self.client,
             &params.run_id,
-            &params.artifact_id,
-            &params.suggestion_id,
-            &params.source,
+            &params.question,
+            &source,
+            params.context.as_deref(),
+            params.options,
         )
         .await
         {
-            Ok(response) => {
-                let formatted = artifacts::format_accept_suggestion_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+            Ok(request_id) => {
+                self.webhooks.notify(
+                    WebhookEvent::InputRequest,
+                    &params.run_id,
+                    &source,
+                    format!("{} needs input: {}", source, params.question),
+                    serde_json::json!({
+                        "question": params.question,
+                        "context": params.context,
+                    }),
+                );
+                Ok(CallToolResult::success(vec![Content::text(
+                    match request_id {
+                        Some(id) => format!(
+                            "Input requested from human (request ID {}). Call await_input with it to wait for the answer.",
+                            id
+                        ),
+                        None => "Input requested from human. Wait for response before continuing."
+                            .to_string(),
+                    },
+                )]))
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to accept suggestion: {}",
-                e
-            ))])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "request_input",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to request input",
+                    e,
+                )
+                .await),
         }
     }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] Reject an edit suggestion without applying it. \
-        Optionally provide a reason for rejection."
+        description = "Wait for the human's answer to a request_input question. Blocks until the question is answered \
+        or timeout_seconds (default 300) pass, then returns the answer, or guidance to continue with other work and wait again later."
     )]
-    async fn doc_artifact_reject_suggestion(
+    async fn await_input(
         &self,
-        Parameters(params): Parameters<DocArtifactRejectSuggestionRequest>,
+        Parameters(params): Parameters<AwaitInputRequest>,
     ) -> Result<CallToolResult, McpError> {
-        match artifacts::reject_suggestion(
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let timeout = Duration::from_secs(params.timeout_seconds.unwrap_or(300) as u64);
+        match status::await_input(
             &*self.client,
             &params.run_id,
-            &params.artifact_id,
-            &params.suggestion_id,
-            params.reason.as_deref(),
-            &params.source,
+            &params.request_id,
+            timeout,
+            status::INPUT_POLL_INTERVAL,
         )
         .await
         {
-            Ok(response) => {
-                let formatted = artifacts::format_reject_suggestion_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
-            }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to reject suggestion: {}",
-                e
-            ))])),
+            Ok(answer) => Ok(CallToolResult::success(vec![Content::text(
+                status::format_input_answer(&params.request_id, timeout, answer.as_ref()),
+            )])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "await_input",
+                    &params.run_id,
+                    None,
+                    "Failed to wait for input",
+                    e,
+                )
+                .await),
         }
     }
 
     #[tool(
-        description = "[[HOTWIRED DOC-EDITOR ONLY]] List edit suggestions for a document artifact. \
-        Filter by status (pending/accepted/rejected/all) to see relevant suggestions."
+        description = "Hand off work to another agent. Use this when transitioning responsibility, such as when the strategist hands tasks to the builder, or vice versa. \
+        Put the work in tasks (each with an id, title, acceptance_criteria, and artifacts) rather than only prose details; \
+        the receiver gets them as a checklist from get_handoffs. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
     )]
-    async fn doc_artifact_list_suggestions(
+    async fn handoff(
         &self,
-        Parameters(params): Parameters<DocArtifactListSuggestionsRequest>,
+        Parameters(params): Parameters<HandoffRequest>,
     ) -> Result<CallToolResult, McpError> {
-        // Validate status if provided
-        if let Some(ref status) = params.status {
-            if let Err(e) = artifacts::validate_suggestion_status(status) {
-                return Ok(CallToolResult::success(vec![Content::text(format!(
-                    "Invalid status filter: {}",
-                    e
-                ))]));
-            }
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
         }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
 
-        match artifacts::list_suggestions(
+        match status::handoff(
             &*self.client,
             &params.run_id,
-            &params.artifact_id,
-            params.status.as_deref(),
+            &params.to,
+            &params.summary,
+            &source,
+            params.details.as_deref(),
+            params.artifacts,
+            params.tasks.clone(),
         )
         .await
         {
-            Ok(response) => {
-                let formatted = artifacts::format_suggestions_response(&response);
-                Ok(CallToolResult::success(vec![Content::text(formatted)]))
+            Ok(()) => {
+                self.webhooks.notify(
+                    WebhookEvent::Handoff,
+                    &params.run_id,
+                    &source,
+                    format!("{} handed off to {}: {}", source, params.to, params.summary),
+                    serde_json::json!({
+                        "to": params.to,
+                        "summary": params.summary,
+                        "details": params.details,
+                        "tasks": params.tasks,
+                    }),
+                );
+                let tasks = params.tasks.as_ref().map_or(0, Vec::len);
+                Ok(CallToolResult::success(vec![Content::text(if tasks > 0 {
+                    format!("Handoff to {} recorded with {} task(s)", params.to, tasks)
+                } else {
+                    format!("Handoff to {} recorded", params.to)
+                })]))
             }
-            Err(e) => Ok(CallToolResult::success(vec![Content::text(format!(
-                "Failed to list suggestions: {}",
-                e
-            ))])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "handoff",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to record handoff",
+                    e,
+                )
+                .await),
         }
     }
-}
 
-// Implement the server handler
-#[tool_handler]
-impl<C: IpcClient + 'static> rmcp::ServerHandler for HotwiredMcp<C> {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            instructions: Some("Hotwired MCP server for multi-agent workflow coordination".into()),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            ..Default::default()
+    #[tool(
+        description = "List the handoffs you received in this run as a task board: every handed-off task with its acceptance criteria \
+        and artifacts, grouped by who handed it off, followed by handoffs that came without tasks."
+    )]
+    async fn get_handoffs(
+        &self,
+        Parameters(params): Parameters<GetHandoffsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        match handoffs::get_handoffs(&*self.client, &params.run_id, &source).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                handoffs::format_task_board,
+                compact::format_task_board,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_handoffs",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to get handoffs",
+                    e,
+                )
+                .await),
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ipc::mock::MockIpcClient;
+    #[tool(
+        description = "List the agents connected to the run and the capabilities they declared \
+        (languages, filesystem access, whether they can run tests, max context). \
+        Use this to decide who to hand work off to."
+    )]
+    async fn get_agent_capabilities(
+        &self,
+        Parameters(params): Parameters<GetAgentCapabilitiesRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match protocol::get_agent_capabilities(&*self.client, &params.run_id).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                protocol::format_agent_capabilities,
+                compact::format_agent_capabilities,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_agent_capabilities",
+                    &params.run_id,
+                    None,
+                    "Failed to get agent capabilities",
+                    e,
+                )
+                .await),
+        }
+    }
 
-    #[tokio::test]
-    async fn test_ping_returns_connected_when_backend_available() {
-        let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
+    #[tool(
+        description = "Check your inbox: comments addressed to you, handoffs, answered questions, and other events that need your attention in this run. \
+        Call it at the start of each turn instead of polling get_run_status, messages, and every artifact's comments. \
+        Returns only unacknowledged notifications unless include_acknowledged is true; call ack_notification with the IDs you have handled."
+    )]
+    async fn get_notifications(
+        &self,
+        Parameters(params): Parameters<GetNotificationsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        match notifications::get_notifications(
+            &*self.client,
+            &params.run_id,
+            &source,
+            params.include_acknowledged.unwrap_or(false),
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                notifications::format_notifications,
+                compact::format_notifications,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_notifications",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to get notifications",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Mark notifications from get_notifications as handled so they stop showing up in your inbox. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn ack_notification(
+        &self,
+        Parameters(params): Parameters<AckNotificationRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        match notifications::ack_notifications(
+            &*self.client,
+            &params.run_id,
+            &source,
+            &params.notification_ids,
+        )
+        .await
+        {
+            Ok(response) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "✓ Acknowledged {} notification(s)",
+                response.acknowledged
+            ))])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "ack_notification",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to acknowledge notifications",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Confirm that you have read and acted on messages sent to you, by their event IDs from get_notifications or get_messages. \
+        Messages sent with requires_ack wait for this; the sender sees it in get_message_deliveries. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn ack_message(
+        &self,
+        Parameters(params): Parameters<AckMessageRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        match notifications::ack_messages(&*self.client, &params.run_id, &source, &params.event_ids)
+            .await
+        {
+            Ok(response) => Ok(CallToolResult::success(vec![Content::text(format!(
+                "✓ Acknowledged {} message(s)",
+                response.acknowledged
+            ))])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "ack_message",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to acknowledge messages",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "List the steps of your role's playbook in this run, with which are done. \
+        Use it to see what to work on next, and call protocol_step_complete as you finish each step; the dashboard shows this as the run's progress."
+    )]
+    async fn protocol_steps_list(
+        &self,
+        Parameters(params): Parameters<ProtocolStepsListRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        match steps::list_steps(&*self.client, &params.run_id, &source).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                steps::format_steps,
+                compact::format_steps,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "protocol_steps_list",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to list protocol steps",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Mark a step from protocol_steps_list as done, optionally with a short note on what was done. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn protocol_step_complete(
+        &self,
+        Parameters(params): Parameters<ProtocolStepCompleteRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        match steps::complete_step(
+            &*self.client,
+            &params.run_id,
+            &source,
+            &params.step_id,
+            params.note.as_deref(),
+        )
+        .await
+        {
+            Ok(response) => Ok(CallToolResult::success(vec![Content::text(
+                steps::format_step_completed(&params.step_id, &response),
+            )])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "protocol_step_complete",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to complete protocol step",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Report the tokens and tool calls you used since your last report, e.g. once per turn, so the run can show per-agent cost. \
+        Reports are summed locally and sent in batches, so calling this every turn is cheap."
+    )]
+    async fn report_usage(
+        &self,
+        Parameters(params): Parameters<ReportUsageRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        let now = Instant::now();
+        self.usage.add(&params, &source, now);
+        let due = self.usage.take_due(self.config.usage_flush_interval(), now);
+        if !due.is_empty() {
+            // Kept for the next flush if sending fails; no need to bother the agent
+            if let Err(e) = usage::flush(self.client.as_ref(), &self.usage, due).await {
+                tracing::warn!("Failed to send usage reports: {}", e);
+            }
+        }
+        Ok(CallToolResult::success(vec![Content::text(
+            "✓ Usage recorded",
+        )]))
+    }
+
+    #[tool(
+        description = "Get the token, tool-call, and estimated cost totals of every agent in a run, per role and model."
+    )]
+    async fn get_run_usage(
+        &self,
+        Parameters(params): Parameters<GetRunUsageRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        // Include what this agent reported but has not sent yet
+        let pending = self.usage.take(Some(&params.run_id));
+        if let Err(e) = usage::flush(self.client.as_ref(), &self.usage, pending).await {
+            tracing::warn!("Failed to send usage reports: {}", e);
+        }
+        match usage::get_run_usage(&*self.client, &params.run_id).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                usage::format_run_usage,
+                compact::format_run_usage,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_run_usage",
+                    &params.run_id,
+                    None,
+                    "Failed to get run usage",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Save a short summary of your work so far: what is done, what you are doing now, and what is next. \
+        Each summary replaces your previous one. The latest is what an agent pairing into the run is told you are doing (its conversation_summary), \
+        so post one at natural checkpoints such as finishing a section or before a handoff. At most 4000 characters. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn post_summary(
+        &self,
+        Parameters(params): Parameters<PostSummaryRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        match summaries::post_summary(
+            &*self.client,
+            &params.run_id,
+            &source,
+            &params.summary,
+            params.current_artifact.as_deref(),
+        )
+        .await
+        {
+            Ok(response) => Ok(CallToolResult::success(vec![Content::text(
+                summaries::format_posted(&response),
+            )])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "post_summary",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to post summary",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Get the latest work summary posted with post_summary in a run, from one role or from any. \
+        Use it to catch up on what another agent is doing, or on your own work after losing context."
+    )]
+    async fn get_latest_summary(
+        &self,
+        Parameters(params): Parameters<GetLatestSummaryRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match summaries::latest_summary(&*self.client, &params.run_id, params.role.as_deref()).await
+        {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                summaries::format_latest,
+                compact::format_latest_summary,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_latest_summary",
+                    &params.run_id,
+                    None,
+                    "Failed to get latest summary",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Publish facts about the project this run works in: worktree path, branch, test command, package manager, and other named values in env. \
+        Agents pairing into the run are given them, so they don't have to ask the human. Fields you leave out keep their values. \
+        With detect set, the worktree, branch, and package manager are filled in from this server's project directory where not given. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn set_run_context(
+        &self,
+        Parameters(mut params): Parameters<SetRunContextRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        if params.detect == Some(true) {
+            run_context::fill_detected(&mut params, run_context::detect(&self.project_dir()));
+        }
+        match run_context::set_context(&*self.client, &params, &source).await {
+            Ok(response) => Ok(CallToolResult::success(vec![Content::text(
+                run_context::format_set_response(&response),
+            )])),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "set_run_context",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to set run context",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Get the project facts published for a run with set_run_context: worktree path, branch, test command, package manager, and anything else shared. \
+        Check it before asking the human how to build or test the project."
+    )]
+    async fn get_run_context(
+        &self,
+        Parameters(params): Parameters<GetRunContextRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match run_context::get_context(&*self.client, &params.run_id).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                run_context::format_get_response,
+                compact::format_run_context,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_run_context",
+                    &params.run_id,
+                    None,
+                    "Failed to get run context",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Get how a run was started: the human's original intent, the playbook, the artifacts selected, the initialization condition, and the setup wizard's answers. \
+        Call it after losing context (a compaction or a restarted session) to re-ground yourself instead of asking the human to repeat the brief."
+    )]
+    async fn get_run_config(
+        &self,
+        Parameters(params): Parameters<GetRunConfigRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match run_config::get_config(&*self.client, &params.run_id).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                run_config::format_run_config,
+                compact::format_run_config,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_run_config",
+                    &params.run_id,
+                    None,
+                    "Failed to get run config",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Take over a different role mid-run (e.g., the strategist takes over building when the builder disconnects). \
+        Updates the backend's agent mapping and returns the protocol for your new role. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn switch_role(
+        &self,
+        Parameters(params): Parameters<SwitchRoleRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        // The role this server is attached with in this run, if any
+        let context = self.state.context();
+        let attached_role = context
+            .role
+            .filter(|_| context.run_id.as_deref() == Some(params.run_id.as_str()));
+        let Some(source) = params
+            .source
+            .clone()
+            .or(attached_role)
+            .or_else(|| self.source_or_default(None))
+        else {
+            return Ok(missing_role("source", "default_source"));
+        };
+
+        match protocol::switch_role(
+            &*self.client,
+            &params.run_id,
+            &params.new_role,
+            &source,
+            params.reason.as_deref(),
+        )
+        .await
+        {
+            Ok(response) => {
+                self.state
+                    .update(|c| c.attach(&params.run_id, &params.new_role));
+                self.protocols.record(
+                    &params.run_id,
+                    &params.new_role,
+                    response.clone(),
+                    std::time::Instant::now(),
+                );
+                self.role_capabilities.record(
+                    &params.run_id,
+                    &params.new_role,
+                    response.capabilities.clone(),
+                );
+                let rendered = self.render_whole(
+                    params.format,
+                    &response,
+                    protocol::format_protocol_response,
+                    compact::format_protocol_response,
+                );
+                let mut content = vec![Content::text(format!(
+                    "Switched role from {} to {}.",
+                    source, params.new_role
+                ))];
+                content.extend(rendered.content);
+                Ok(CallToolResult::success(content))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "switch_role",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to switch role",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Request to end, restart, or pause the workflow. Use this when the workflow task is complete or when the scope has changed significantly. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn request_end_run(
+        &self,
+        Parameters(params): Parameters<RequestEndRunRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        if let Some(denied) = self
+            .not_permitted(&params.run_id, params.source.as_deref(), "request_end_run")
+            .await
+        {
+            return Ok(denied);
+        }
+        // Validate reason
+        if let Err(e) = status::validate_end_run_reason(&params.reason) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Invalid reason: {}",
+                e
+            ))]));
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+
+        match status::request_end_run(
+            &*self.client,
+            &params.run_id,
+            &params.reason,
+            &params.description,
+            &source,
+            params.suggested_follow_up,
+        )
+        .await
+        {
+            Ok(request_id) => {
+                self.webhooks.notify(
+                    WebhookEvent::EndRunRequest,
+                    &params.run_id,
+                    &source,
+                    format!(
+                        "{} asks to end the run ({}): {}",
+                        source, params.reason, params.description
+                    ),
+                    serde_json::json!({
+                        "requestId": request_id,
+                        "reason": params.reason,
+                        "description": params.description,
+                    }),
+                );
+                Ok(CallToolResult::success(vec![
+                    Content::text(format!("End run request submitted (ID: {}). Other agents will be asked to confirm. Wait for consensus before proceeding.", request_id))
+                ]))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "request_end_run",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to request end run",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Respond to another agent's request to end the workflow. Called when you're notified that another agent wants to end the run. MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn respond_to_end_request(
+        &self,
+        Parameters(params): Parameters<RespondToEndRequestRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        // Validate response
+        if let Err(e) = status::validate_end_response(&params.response) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Invalid response: {}",
+                e
+            ))]));
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+
+        match status::respond_to_end_request(
+            &*self.client,
+            &params.run_id,
+            &params.request_id,
+            &params.response,
+            &source,
+            params.reason.as_deref(),
+        )
+        .await
+        {
+            Ok(()) => {
+                let message = match params.response.as_str() {
+                    "agree" => "You agreed to end the run.".to_string(),
+                    "verify" => "You requested to verify before deciding. Perform your verification and call this tool again with 'agree' or 'disagree'.".to_string(),
+                    "disagree" => format!("You disagreed: {}", params.reason.unwrap_or_default()),
+                    _ => "Response recorded.".to_string(),
+                };
+                Ok(CallToolResult::success(vec![Content::text(message)]))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "respond_to_end_request",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to respond to end request",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    // =========================================================================
+    // TERMINAL WORKFLOW TOOLS (/hotwire, /pair)
+    // =========================================================================
+
+    #[tool(
+        description = "Initiate a new Hotwired workflow run from the terminal. \
+        Called by /hotwire command. Provide project path, terminal session (Zellij or tmux), and optional intent. \
+        Returns either: 'started' with your protocol (begin working immediately), \
+        or 'needs_confirmation' (wait for user to confirm in app, then call get_protocol)."
+    )]
+    async fn hotwire(
+        &self,
+        Parameters(params): Parameters<HotwireRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        match terminal::hotwire(
+            &*self.client,
+            &params.project_path,
+            &params.zellij_session,
+            params.multiplexer.or(self.multiplexer),
+            params.intent.as_deref(),
+            params.suggested_playbook.as_deref(),
+            params.suggested_artifacts,
+        )
+        .await
+        {
+            Ok(response) => {
+                if let HotwireResponse::Started(started) = &response {
+                    self.state
+                        .update(|c| c.attach(&started.run_id, &started.role));
+                }
+                Ok(self.render_whole(
+                    params.format,
+                    &response,
+                    terminal::format_hotwire_response,
+                    compact::format_hotwire_response,
+                ))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to initiate workflow: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(description = "Join an existing workflow run as a second agent. \
+        Called by /pair command. Automatically finds runs that need a second agent. \
+        Returns: 'joined' with your protocol and context, \
+        'needs_selection' (multiple runs available, select in app), \
+        'none' (no runs need pairing), or 'project_mismatch' (wrong directory).")]
+    async fn pair(
+        &self,
+        Parameters(params): Parameters<PairRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        match terminal::pair(
+            &*self.client,
+            &params.zellij_session,
+            params.multiplexer.or(self.multiplexer),
+            &params.project_path,
+        )
+        .await
+        {
+            Ok(response) => {
+                if let PairResponse::Joined(joined) = &response {
+                    self.state
+                        .update(|c| c.attach(&joined.run_id, &joined.role));
+                }
+                Ok(self.render_whole(
+                    params.format,
+                    &response,
+                    terminal::format_pair_response,
+                    compact::format_pair_response,
+                ))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to join workflow: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Re-attach to the run this session held a role in after the agent \
+        restarted (e.g. after a crash or a context reset). Provide project path and Zellij session. \
+        Returns: 'resumed' with your protocol and a digest of what happened while you were \
+        away (new messages, document edits, open comments), or 'none' if there is nothing to resume."
+    )]
+    async fn resume_run(
+        &self,
+        Parameters(params): Parameters<ResumeRunRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let last = self.state.context();
+        match terminal::resume_run(
+            &*self.client,
+            &params.zellij_session,
+            &params.project_path,
+            last.run_id.as_deref(),
+            last.role.as_deref(),
+        )
+        .await
+        {
+            Ok(response) => {
+                if let ResumeRunResponse::Resumed(resumed) = &response {
+                    self.state
+                        .update(|c| c.attach(&resumed.run_id, &resumed.role));
+                }
+                Ok(self.render_whole(
+                    params.format,
+                    &response,
+                    terminal::format_resume_response,
+                    compact::format_resume_response,
+                ))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to resume run: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(description = "List active or resumable workflow runs. \
+        Use this before /hotwire to check if there are existing runs to continue. \
+        If my_role is set for a run, you were previously attached to it. \
+        Optionally filter by project path or Zellij session.")]
+    async fn list_active_runs(
+        &self,
+        Parameters(params): Parameters<ListActiveRunsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        match terminal::list_active_runs(
+            &*self.client,
+            params.project_path.as_deref(),
+            params.zellij_session.as_deref(),
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                terminal::format_active_runs,
+                compact::format_active_runs,
+            )),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to list active runs: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Search previous runs, including ended ones, by keyword, project, playbook, or creation date, \
+        and list each match's artifacts. Use this to find decisions made in an earlier run (e.g. a past PRD session) \
+        and read them with doc_artifact_read using the returned run ID. Dates are YYYY-MM-DD or RFC 3339."
+    )]
+    async fn search_runs(
+        &self,
+        Parameters(params): Parameters<SearchRunsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match history::search_runs(&*self.client, &params).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                history::format_search_results,
+                compact::format_search_results,
+            )),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to search runs: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List available playbooks with metadata for intent matching. \
+        Returns playbook information including roles, keywords, and initialization hints. \
+        Use this to match user intent to the appropriate playbook before calling hotwire. \
+        The role with is_initiating=true is the one you'll take when using /hotwire."
+    )]
+    async fn list_playbooks(
+        &self,
+        Parameters(params): Parameters<ListPlaybooksRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        match self
+            .playbooks
+            .playbooks(self.client.as_ref(), PLAYBOOK_CACHE_TTL)
+            .await
+        {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                terminal::format_playbooks,
+                compact::format_playbooks,
+            )),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to list playbooks: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Get the full details of one playbook: roles, initialization hints, and an outline of its protocol. \
+        Use this after matching user intent with list_playbooks instead of re-reading the whole list."
+    )]
+    async fn get_playbook(
+        &self,
+        Parameters(params): Parameters<GetPlaybookRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match terminal::get_playbook(&*self.client, &params.playbook_id).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                terminal::format_playbook,
+                compact::format_playbook,
+            )),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to get playbook {}: {}. Call list_playbooks for the available IDs.",
+                params.playbook_id, e
+            ))])),
+        }
+    }
+
+    // =========================================================================
+    // DOC-ARTIFACT TOOLS
+    // =========================================================================
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] List all document artifacts in the run. Returns artifact IDs, filenames, types, tags, and metadata. \
+        Set tag or document_type to list only the matching artifacts."
+    )]
+    async fn doc_artifact_list(
+        &self,
+        Parameters(params): Parameters<DocArtifactListRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match artifacts::list_artifacts(&*self.client, &params.run_id).await {
+            Ok(mut response) => {
+                artifacts::filter_artifacts(
+                    &mut response,
+                    params.tag.as_deref(),
+                    params.document_type.as_deref(),
+                );
+                Ok(self.render_list(
+                    params.format,
+                    params.continuation.as_deref(),
+                    &response,
+                    artifacts::format_list_response,
+                    compact::format_list_response,
+                    tables::format_list_response,
+                ))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_list",
+                    &params.run_id,
+                    None,
+                    "Failed to list artifacts",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Add or remove tags on a document artifact, e.g. \
+        adr, prd, meeting-notes. Tags are lowercased and may not contain spaces. \
+        Use tags to keep runs with many documents navigable; doc_artifact_list filters by tag."
+    )]
+    async fn doc_artifact_tag(
+        &self,
+        Parameters(params): Parameters<DocArtifactSetTagsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+
+        match artifacts::set_tags(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            &params.add,
+            &params.remove,
+            &source,
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render_whole(
+                params.format,
+                &response,
+                artifacts::format_set_tags_response,
+                compact::format_set_tags_response,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_tag",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to tag artifact",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Read content from a tracked document artifact. \
+        Returns document content with metadata. Without a limit, pages are joined up to a size budget; \
+        if has_more is still true, continue from offset + returned lines. \
+        The response includes a contentHash for conflict detection on subsequent edits. \
+        Set render_mode to \"numbered\" for line numbers and inline [comment-id] anchors. \
+        WARNING: Only use this for documents in the doc-editor. Use {read_tool} for source files."
+    )]
+    async fn doc_artifact_read(
+        &self,
+        Parameters(params): Parameters<DocArtifactReadRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let pagination = &self.config.pagination;
+        let read = async {
+            match (params.limit, pagination.read_budget_bytes()) {
+                (None, budget) if budget > 0 => {
+                    artifacts::read_within_budget(
+                        &*self.client,
+                        &params.run_id,
+                        &params.artifact_id,
+                        params.offset,
+                        pagination.read_limit(None),
+                        params.include_comments,
+                        budget,
+                    )
+                    .await
+                }
+                (limit, _) => {
+                    artifacts::read_artifact(
+                        &*self.client,
+                        &params.run_id,
+                        &params.artifact_id,
+                        params.offset,
+                        Some(pagination.read_limit(limit)),
+                        params.include_comments,
+                    )
+                    .await
+                }
+            }
+        };
+        // The comment list is usually requested next; fetch it alongside
+        let prefetch = async {
+            if params.include_comments != Some(false) {
+                let prefetched = self
+                    .comments
+                    .prefetch(self.client.as_ref(), &params.run_id, &params.artifact_id)
+                    .await;
+                if let Err(e) = prefetched {
+                    tracing::debug!("Comment prefetch failed: {}", e);
+                }
+            }
+        };
+        let (read, ()) = tokio::join!(read, prefetch);
+        match read {
+            Ok(response) => {
+                self.state.update(|c| {
+                    c.record_hash(
+                        &params.run_id,
+                        &response.artifact_id,
+                        &response.content_hash,
+                    )
+                });
+                self.report_read(&params.run_id, params.source.as_deref(), &response)
+                    .await;
+                let rendered = match params.render_mode.unwrap_or_default() {
+                    ReadRenderMode::Plain => self.render(
+                        params.format,
+                        params.continuation.as_deref(),
+                        &response,
+                        artifacts::format_read_response,
+                        compact::format_read_response,
+                    ),
+                    ReadRenderMode::Numbered => self.render(
+                        params.format,
+                        params.continuation.as_deref(),
+                        &response,
+                        artifacts::format_read_response_numbered,
+                        compact::format_read_response_numbered,
+                    ),
+                };
+                Ok(rendered)
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_read",
+                    &params.run_id,
+                    None,
+                    "Failed to read artifact",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Everything needed to review a document in one call: \
+        the content with line numbers and [comment-id] anchors, its open comments, and its pending \
+        edit suggestions. Use this instead of doc_artifact_read + doc_artifact_list_comments + \
+        doc_artifact_list_suggestions when reviewing."
+    )]
+    async fn doc_artifact_review_context(
+        &self,
+        Parameters(params): Parameters<DocArtifactReviewContextRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match artifacts::review_context(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            params.offset,
+            Some(self.config.pagination.read_limit(params.limit)),
+        )
+        .await
+        {
+            Ok(context) => {
+                self.state.update(|c| {
+                    c.record_hash(
+                        &params.run_id,
+                        &context.document.artifact_id,
+                        &context.document.content_hash,
+                    )
+                });
+                self.report_read(&params.run_id, params.source.as_deref(), &context.document)
+                    .await;
+                Ok(self.render(
+                    params.format,
+                    params.continuation.as_deref(),
+                    &context,
+                    artifacts::format_review_context,
+                    compact::format_review_context,
+                ))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_review_context",
+                    &params.run_id,
+                    None,
+                    "Failed to load review context",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] See which version of a document each agent last read. \
+        Every doc_artifact_read and doc_artifact_review_context is recorded under the reader's role, \
+        so before asking for a review you can check whether the reviewer has seen the latest version."
+    )]
+    async fn get_artifact_readers(
+        &self,
+        Parameters(params): Parameters<GetArtifactReadersRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match read_receipts::get_readers(&*self.client, &params.run_id, &params.artifact_id).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                read_receipts::format_readers,
+                compact::format_artifact_readers,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "get_artifact_readers",
+                    &params.run_id,
+                    None,
+                    "Failed to get artifact readers",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] A document's revision history as a changelog: \
+        its edits grouped by the category of their edit_reason, each with its summary, author, age, \
+        and the comments it addressed."
+    )]
+    async fn doc_artifact_changelog(
+        &self,
+        Parameters(params): Parameters<DocArtifactChangelogRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match artifacts::list_edits(&*self.client, &params.run_id, &params.artifact_id).await {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                changelog::format_changelog,
+                compact::format_changelog,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_changelog",
+                    &params.run_id,
+                    None,
+                    "Failed to get changelog",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Where the feedback on a document clusters: \
+        comments per section (open, open questions, oldest open) as a table, the oldest open issue, \
+        and the unanswered questions. Use it to decide which revisions to make first."
+    )]
+    async fn doc_artifact_feedback_summary(
+        &self,
+        Parameters(params): Parameters<DocArtifactFeedbackSummaryRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match feedback::feedback_summary(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            self.config.pagination.read_max_limit.max(1),
+        )
+        .await
+        {
+            Ok(summary) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &summary,
+                feedback::format_feedback_summary,
+                compact::format_feedback_summary,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_feedback_summary",
+                    &params.run_id,
+                    None,
+                    "Failed to summarize feedback",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Create a new document artifact in the run. \
+        Returns the artifact ID and content hash for subsequent edits."
+    )]
+    async fn doc_artifact_create(
+        &self,
+        Parameters(params): Parameters<DocArtifactCreateRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        if let Some(denied) = self
+            .not_permitted(
+                &params.run_id,
+                params.created_by.as_deref(),
+                "doc_artifact_create",
+            )
+            .await
+        {
+            return Ok(denied);
+        }
+        match artifacts::create_artifact(
+            &*self.client,
+            &params.run_id,
+            &params.filename,
+            params.initial_content.as_deref(),
+            params.document_type.as_deref(),
+            self.author_or_default(params.created_by.as_deref())
+                .as_deref(),
+        )
+        .await
+        {
+            Ok(response) => {
+                self.state.update(|c| {
+                    c.record_hash(
+                        &params.run_id,
+                        &response.artifact_id,
+                        &response.content_hash,
+                    )
+                });
+                Ok(self.render_whole(
+                    params.format,
+                    &response,
+                    |response| {
+                        format!(
+                            "✓ Artifact created successfully\n\n\
+                    **ID:** `{}`\n\
+                    **Filename:** {}\n\
+                    **Content Hash:** `{}`",
+                            response.artifact_id, response.filename, response.content_hash
+                        )
+                    },
+                    compact::format_create_response,
+                ))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_create",
+                    &params.run_id,
+                    params.created_by.as_deref(),
+                    "Failed to create artifact",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Edit content in a tracked document artifact. \
+        WARNING: Only for documents in the doc-editor. Use {edit_tools} for source files. \
+        Supports edit types: replace_range, insert, append, full_replace. \
+        Requires contentHash from doc_artifact_read for conflict detection. \
+        Give an edit_reason (category, summary, related_comment_ids) so the change shows up in \
+        doc_artifact_changelog. Set dry_run to check the hash and offsets and see the resulting \
+        diff without changing the document."
+    )]
+    async fn doc_artifact_edit(
+        &self,
+        Parameters(params): Parameters<DocArtifactEditRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        self.comments
+            .invalidate(&params.run_id, &params.artifact_id);
+        if let Some(denied) = self
+            .not_permitted(
+                &params.run_id,
+                params.source.as_deref(),
+                "doc_artifact_edit",
+            )
+            .await
+        {
+            return Ok(denied);
+        }
+        let source = self.source_or_default(params.source.as_deref());
+        let ([start_offset, end_offset, mut insert_offset], document) = match self
+            .char_offsets(
+                &params.run_id,
+                &params.artifact_id,
+                params.offset_unit,
+                [
+                    ("start_offset", params.start_offset),
+                    ("end_offset", params.end_offset),
+                    ("insert_offset", params.insert_offset),
+                ],
+            )
+            .await
+        {
+            Ok(checked) => checked,
+            Err(invalid) => return Ok(invalid),
+        };
+        if params.dry_run.unwrap_or(false) {
+            let document = match self
+                .document_or_current(&params.run_id, &params.artifact_id, document)
+                .await
+            {
+                Ok(document) => document,
+                Err(e) => {
+                    return Ok(self
+                        .run_tool_failure(
+                            "doc_artifact_edit",
+                            &params.run_id,
+                            source.as_deref(),
+                            "Failed to read artifact for dry run",
+                            e,
+                        )
+                        .await)
+                }
+            };
+            return Ok(
+                match artifacts::preview_edit(
+                    &document,
+                    Some(&params.content_hash),
+                    params.edit_type,
+                    &params.new_content,
+                    start_offset,
+                    end_offset,
+                    insert_offset,
+                ) {
+                    Ok(preview) => self.render_whole(
+                        params.format,
+                        &preview,
+                        artifacts::format_edit_preview,
+                        compact::format_edit_preview,
+                    ),
+                    Err(e) => {
+                        CallToolResult::error(vec![Content::text(format!("Invalid edit: {}", e))])
+                    }
+                },
+            );
+        }
+        if let Some(reason) = backup::backup_reason(
+            params.edit_type,
+            start_offset,
+            end_offset,
+            self.config.backup_min_chars(),
+        ) {
+            self.back_up(
+                &params.run_id,
+                &params.artifact_id,
+                document.as_ref(),
+                reason,
+                source.as_deref(),
+            )
+            .await;
+        }
+        let document_chars = document.map(|document| document.content.chars().count());
+        let at_end = match params.edit_type {
+            EditType::Append => true,
+            EditType::Insert => {
+                document_chars.is_some() && insert_offset.map(|o| o as usize) == document_chars
+            }
+            _ => false,
+        };
+
+        let mut content_hash = params.content_hash.clone();
+        let mut attempt = 0;
+        let result = loop {
+            let result = artifacts::edit_artifact(
+                &*self.client,
+                &params.run_id,
+                &params.artifact_id,
+                params.edit_type,
+                &content_hash,
+                &params.new_content,
+                start_offset,
+                end_offset,
+                insert_offset,
+                params.edit_reason.as_ref(),
+                source.as_deref(),
+            )
+            .await;
+            let conflicted = matches!(&result, Ok(response) if response.conflict.is_some());
+            if !(conflicted && at_end && attempt < artifacts::END_EDIT_RETRIES) {
+                break result;
+            }
+            attempt += 1;
+            let Ok(latest) = self
+                .current_document(&params.run_id, &params.artifact_id)
+                .await
+            else {
+                break result;
+            };
+            tracing::info!(
+                "Retrying end-of-document edit on {} after hash conflict (attempt {})",
+                params.artifact_id,
+                attempt
+            );
+            content_hash = latest.content_hash;
+            if params.edit_type == EditType::Insert {
+                insert_offset = Some(latest.content.chars().count() as i64);
+            }
+        };
+
+        match result {
+            Ok(response) => {
+                if response.conflict.is_none() {
+                    self.state.update(|c| {
+                        c.record_hash(
+                            &params.run_id,
+                            &response.artifact_id,
+                            &response.new_content_hash,
+                        )
+                    });
+                }
+                let mut result = self.render_whole(
+                    params.format,
+                    &response,
+                    artifacts::format_edit_response,
+                    compact::format_edit_response,
+                );
+                // A conflict or refused edit
+                if !response.success {
+                    result.is_error = Some(true);
+                }
+                Ok(result)
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_edit",
+                    &params.run_id,
+                    source.as_deref(),
+                    "Failed to edit artifact",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Search within a tracked document artifact. \
+        Unlike grep, this search returns character offsets for precise editing, understands markdown structure, \
+        and returns surrounding context. Use this to find exact positions before making edits."
+    )]
+    async fn doc_artifact_search(
+        &self,
+        Parameters(params): Parameters<DocArtifactSearchRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match artifacts::search_artifact(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            &params.query,
+            params.match_type.as_deref(),
+            params.scope.as_deref(),
+            Some(
+                self.config
+                    .pagination
+                    .search_context_lines(params.context_lines),
+            ),
+            Some(
+                self.config
+                    .pagination
+                    .search_max_results(params.max_results),
+            ),
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                artifacts::format_search_response,
+                compact::format_search_response,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_search",
+                    &params.run_id,
+                    None,
+                    "Failed to search artifact",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Search every tracked document in a run at once. \
+        Returns matches grouped by artifact, with the same character offsets as doc_artifact_search. \
+        Use this instead of searching artifacts one by one."
+    )]
+    async fn doc_artifact_search_all(
+        &self,
+        Parameters(params): Parameters<DocArtifactSearchAllRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match batch::search_all(
+            &*self.client,
+            &params.run_id,
+            &params.query,
+            params.match_type.as_deref(),
+            params.scope.as_deref(),
+            Some(
+                self.config
+                    .pagination
+                    .search_context_lines(params.context_lines),
+            ),
+            Some(
+                self.config
+                    .pagination
+                    .search_max_results(params.max_results),
+            ),
+            self.config.batch_concurrency(),
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                batch::format_search_all_response,
+                compact::format_search_all_response,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_search_all",
+                    &params.run_id,
+                    None,
+                    "Failed to search artifacts",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Read the first page of every tracked document in a run. \
+        Use this to get an overview of all artifacts in one call; use doc_artifact_read to page through one."
+    )]
+    async fn doc_artifact_read_all(
+        &self,
+        Parameters(params): Parameters<DocArtifactReadAllRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        match batch::read_all(
+            &*self.client,
+            &params.run_id,
+            self.config.pagination.read_limit(params.limit),
+            self.config.batch_concurrency(),
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                batch::format_read_all_response,
+                compact::format_read_all_response,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_read_all",
+                    &params.run_id,
+                    None,
+                    "Failed to read artifacts",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "Export a run for archiving: its event timeline, the final content of every artifact, \
+        and all comments and suggestions are written to a directory as JSONL files, the documents, and a \
+        markdown transcript. Set zip to also pack it into a .zip. Use this when a run is complete and its \
+        record should be kept in the repository or attached to a ticket."
+    )]
+    async fn export_run(
+        &self,
+        Parameters(params): Parameters<ExportRunRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let bundle = match export::collect(
+            &*self.client,
+            &params.run_id,
+            self.config.pagination.read_max_limit.max(1),
+            self.config.batch_concurrency(),
+        )
+        .await
+        {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                return Ok(self
+                    .run_tool_failure(
+                        "export_run",
+                        &params.run_id,
+                        None,
+                        "Failed to export run",
+                        e,
+                    )
+                    .await)
+            }
+        };
+        let dir = self.project_dir().join(
+            params
+                .output_dir
+                .as_deref()
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| export::default_dir(&params.run_id)),
+        );
+        match export::write(&bundle, &dir, params.zip.unwrap_or(false)) {
+            Ok(response) => Ok(self.render_whole(
+                params.format,
+                &response,
+                export::format_run_export,
+                compact::format_run_export,
+            )),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to write export to {}: {}",
+                dir.display(),
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Write a run's full event stream to a JSONL file, one event per line, oldest first. \
+        Every line has the same fields (schema, runId, seq, id, eventType, source, content, metadata, \
+        createdAt), null when unknown, so the file can be loaded by analysis scripts without guessing. \
+        Use this to analyze a run's timeline offline; use export_run for a full archive."
+    )]
+    async fn dump_events(
+        &self,
+        Parameters(params): Parameters<DumpEventsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let records = match export::event_records(&*self.client, &params.run_id).await {
+            Ok(records) => records,
+            Err(e) => {
+                return Ok(self
+                    .run_tool_failure(
+                        "dump_events",
+                        &params.run_id,
+                        None,
+                        "Failed to read events",
+                        e,
+                    )
+                    .await)
+            }
+        };
+        let path = self.project_dir().join(
+            params
+                .output_file
+                .as_deref()
+                .map(std::path::PathBuf::from)
+                .unwrap_or_else(|| export::default_events_file(&params.run_id)),
+        );
+        match export::dump_events(&params.run_id, &records, &path) {
+            Ok(response) => Ok(self.render_whole(
+                params.format,
+                &response,
+                export::format_event_dump,
+                compact::format_event_dump,
+            )),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to write events to {}: {}",
+                path.display(),
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Undo a destructive edit from a local backup. \
+        Before every full_replace, and every replace_range over a large part of a document, this server saves the document's current content. \
+        Call without backup_id to list an artifact's backups; pass a backup_id to put that version back as a full_replace edit. \
+        The content replaced by the restore is backed up too, so a restore can itself be undone. \
+        MUST only be invoked when inside an active run that has a `RUN_ID` and has not been ended."
+    )]
+    async fn restore_backup(
+        &self,
+        Parameters(params): Parameters<RestoreBackupRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let Some(backup_id) = &params.backup_id else {
+            return Ok(
+                match self.backups.list(&params.run_id, &params.artifact_id) {
+                    Ok(backups) => {
+                        let list = ArtifactBackupList {
+                            run_id: params.run_id.clone(),
+                            artifact_id: params.artifact_id.clone(),
+                            backups: backups
+                                .into_iter()
+                                .map(|backup| ArtifactBackup {
+                                    content: String::new(),
+                                    ..backup
+                                })
+                                .collect(),
+                        };
+                        self.render_whole(
+                            params.format,
+                            &list,
+                            backup::format_backups,
+                            compact::format_backup_list,
+                        )
+                    }
+                    Err(e) => CallToolResult::error(vec![Content::text(format!(
+                        "Failed to list backups: {}",
+                        e
+                    ))]),
+                },
+            );
+        };
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        if let Some(denied) = self
+            .not_permitted(&params.run_id, params.source.as_deref(), "restore_backup")
+            .await
+        {
+            return Ok(denied);
+        }
+        let source = self.source_or_default(params.source.as_deref());
+        let backup = match self
+            .backups
+            .get(&params.run_id, &params.artifact_id, backup_id)
+        {
+            Ok(Some(backup)) => backup,
+            Ok(None) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "No backup `{}` of artifact {}. Call restore_backup without backup_id to list them.",
+                    backup_id, params.artifact_id
+                ))]))
+            }
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to read backup `{}`: {}",
+                    backup_id, e
+                ))]))
+            }
+        };
+        self.comments
+            .invalidate(&params.run_id, &params.artifact_id);
+        let current = match self
+            .current_document(&params.run_id, &params.artifact_id)
+            .await
+        {
+            Ok(current) => current,
+            Err(e) => {
+                return Ok(self
+                    .run_tool_failure(
+                        "restore_backup",
+                        &params.run_id,
+                        source.as_deref(),
+                        "Failed to read artifact",
+                        e,
+                    )
+                    .await)
+            }
+        };
+        if current.content == backup.content {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "{} already matches backup `{}`; nothing to restore.",
+                current.filename, backup.id
+            ))]));
+        }
+        self.back_up(
+            &params.run_id,
+            &params.artifact_id,
+            Some(&current),
+            &format!("Before restoring {}", backup.id),
+            source.as_deref(),
+        )
+        .await;
+        let reason = EditReason {
+            category: EditCategory::Correction,
+            summary: format!("Restore backup {} from {}", backup.id, backup.created_at),
+            related_comment_ids: vec![],
+        };
+        match artifacts::edit_artifact(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            EditType::FullReplace,
+            &current.content_hash,
+            &backup.content,
+            None,
+            None,
+            None,
+            Some(&reason),
+            source.as_deref(),
+        )
+        .await
+        {
+            Ok(response) => {
+                if response.conflict.is_none() {
+                    self.state.update(|c| {
+                        c.record_hash(
+                            &params.run_id,
+                            &response.artifact_id,
+                            &response.new_content_hash,
+                        )
+                    });
+                }
+                let mut result = self.render_whole(
+                    params.format,
+                    &response,
+                    artifacts::format_edit_response,
+                    compact::format_edit_response,
+                );
+                // A conflict or refused edit
+                if !response.success {
+                    result.is_error = Some(true);
+                }
+                Ok(result)
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "restore_backup",
+                    &params.run_id,
+                    source.as_deref(),
+                    "Failed to restore backup",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Write the current content of a tracked document into the \
+        project and commit that file with git. The commit message references the run, the document's edit IDs, \
+        and the agents that edited it. Only this file is committed; other staged changes are left alone. \
+        Use this when a document is final and should land in version control."
+    )]
+    async fn doc_artifact_commit(
+        &self,
+        Parameters(params): Parameters<DocArtifactCommitRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let fetched = tokio::try_join!(
+            self.current_document(&params.run_id, &params.artifact_id),
+            artifacts::list_edits(&*self.client, &params.run_id, &params.artifact_id),
+        );
+        let (document, edits) = match fetched {
+            Ok((document, edits)) => (document, edits.edits),
+            Err(e) => {
+                return Ok(self
+                    .run_tool_failure(
+                        "doc_artifact_commit",
+                        &params.run_id,
+                        None,
+                        "Failed to read artifact for commit",
+                        e,
+                    )
+                    .await)
+            }
+        };
+        let path = params.path.unwrap_or(document.filename);
+        if let Err(e) = git::project_relative(&path) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Cannot commit artifact: {}. Pass a path.",
+                e
+            ))]));
+        }
+        let project = self.project_dir();
+        let subject = params
+            .message
+            .unwrap_or_else(|| format!("Update {} from Hotwired run", path));
+        let message = git::commit_message(&subject, &params.run_id, &params.artifact_id, &edits);
+        match git::commit_file(&project, &path, &document.content, &message) {
+            Ok(commit) => Ok(self.render_whole(
+                params.format,
+                &ArtifactCommit {
+                    artifact_id: params.artifact_id,
+                    path,
+                    commit,
+                    edit_ids: edits.iter().map(|e| e.id.clone()).collect(),
+                    agents: git::contributors(&edits),
+                },
+                git::format_commit,
+                compact::format_artifact_commit,
+            )),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to commit {}: {}",
+                path, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Render a markdown document to a standalone HTML page and write it \
+        into the project, so people without the Hotwired app can read it. Set include_comments to show resolved \
+        comments as margin notes. Defaults to the artifact's filename with an .html extension."
+    )]
+    async fn doc_artifact_render(
+        &self,
+        Parameters(params): Parameters<DocArtifactRenderRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let notes = async {
+            if params.include_comments.unwrap_or(false) {
+                artifacts::list_comments(
+                    &*self.client,
+                    &params.run_id,
+                    &params.artifact_id,
+                    Some("resolved"),
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .map(|response| response.comments)
+            } else {
+                Ok(Vec::new())
+            }
+        };
+        let fetched = tokio::try_join!(
+            self.current_document(&params.run_id, &params.artifact_id),
+            notes,
+        );
+        let (document, notes) = match fetched {
+            Ok(fetched) => fetched,
+            Err(e) => {
+                return Ok(self
+                    .run_tool_failure(
+                        "doc_artifact_render",
+                        &params.run_id,
+                        None,
+                        "Failed to read artifact for rendering",
+                        e,
+                    )
+                    .await)
+            }
+        };
+        let path = params.output_path.unwrap_or_else(|| {
+            std::path::Path::new(&document.filename)
+                .with_extension("html")
+                .display()
+                .to_string()
+        });
+        if let Err(e) = git::project_relative(&path) {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Cannot render artifact: {}. Pass an output_path.",
+                e
+            ))]));
+        }
+        let page = html::render_page(&document.filename, &document.content, &notes);
+        let file = self.project_dir().join(&path);
+        let written = file
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&file, &page));
+        match written {
+            Ok(()) => Ok(self.render_whole(
+                params.format,
+                &RenderedArtifact {
+                    artifact_id: params.artifact_id,
+                    path,
+                    bytes: page.len(),
+                    notes: notes.len(),
+                },
+                html::format_rendered,
+                compact::format_rendered_artifact,
+            )),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to write {}: {}",
+                file.display(),
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Import the comments of a GitHub pull-request review into \
+        tracked documents. Pass the path of a review export (`gh api repos/OWNER/REPO/pulls/NUMBER/comments > review.json`). \
+        Each comment's commented text is searched for in the artifact matching its file path and becomes a comment there; \
+        ```suggestion blocks become suggestions and reply threads are kept. Comments whose text is no longer in the document are reported as skipped."
+    )]
+    async fn doc_artifact_import_review(
+        &self,
+        Parameters(params): Parameters<DocArtifactImportReviewRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        let Some(author) = self.author_or_default(params.author.as_deref()) else {
+            return Ok(missing_role("author", "default_author"));
+        };
+        let path = self.project_dir().join(&params.review_path);
+        let comments = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| review_import::parse_review(&json))
+        {
+            Ok(comments) => comments,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to read review {}: {}",
+                    path.display(),
+                    e
+                ))]))
+            }
+        };
+
+        match review_import::import_review(
+            &*self.client,
+            &params.run_id,
+            comments,
+            params.artifact_id.as_deref(),
+            &author,
+            self.config.pagination.read_max_limit.max(1),
+        )
+        .await
+        {
+            Ok(response) => {
+                for comment in &response.imported {
+                    self.comments
+                        .invalidate(&params.run_id, &comment.artifact_id);
+                }
+                Ok(self.render_whole(
+                    params.format,
+                    &response,
+                    review_import::format_review_import,
+                    compact::format_review_import,
+                ))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_import_review",
+                    &params.run_id,
+                    Some(&author),
+                    "Failed to import review",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Add a comment to a tracked document. \
+        Comment types: comment (general feedback), question (needs clarification), \
+        suggestion (proposed change with replacement text), issue (problem to address). \
+        Selection range is in character offsets. \
+        To reply to an existing comment thread, provide the parent_comment_id. \
+        List roles in mentions to route the comment to their notification inbox."
+    )]
+    async fn doc_artifact_add_comment(
+        &self,
+        Parameters(params): Parameters<DocArtifactAddCommentRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        self.comments
+            .invalidate(&params.run_id, &params.artifact_id);
+        let Some(author) = self.author_or_default(params.author.as_deref()) else {
+            return Ok(missing_role("author", "default_author"));
+        };
+        let (selection_start, selection_end) = match self
+            .char_offsets(
+                &params.run_id,
+                &params.artifact_id,
+                params.offset_unit,
+                [
+                    ("selection_start", Some(params.selection_start)),
+                    ("selection_end", Some(params.selection_end)),
+                ],
+            )
+            .await
+        {
+            Ok(([start, end], _)) => (
+                start.unwrap_or(params.selection_start),
+                end.unwrap_or(params.selection_end),
+            ),
+            Err(invalid) => return Ok(invalid),
+        };
+
+        match artifacts::add_comment(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            params.comment_type,
+            selection_start,
+            selection_end,
+            &params.content,
+            params.suggested_text.as_deref(),
+            &author,
+            params.parent_comment_id.as_deref(),
+            params.mentions,
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render_whole(
+                params.format,
+                &response,
+                |response| {
+                    format!(
+                        "✓ Comment added successfully\n\n\
+                    **Comment ID:** `{}`\n\
+                    **Selected text:** \"{}\"",
+                        response.comment_id,
+                        text::truncate(&response.selection_text, 50)
+                    )
+                },
+                compact::format_add_comment_response,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_add_comment",
+                    &params.run_id,
+                    Some(&author),
+                    "Failed to add comment",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Take action on a document comment. \
+        Actions: accept (user approves feedback, agent should address it), \
+        reject (user disagrees, closes the thread), \
+        reply (continue discussion, thread stays open), \
+        address (agent has addressed the feedback, closes the thread). \
+        Terminal actions (reject, address) resolve the entire comment thread."
+    )]
+    async fn doc_artifact_resolve_comment(
+        &self,
+        Parameters(params): Parameters<DocArtifactResolveCommentRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        self.comments
+            .invalidate(&params.run_id, &params.artifact_id);
+        let Some(resolved_by) = self.author_or_default(params.resolved_by.as_deref()) else {
+            return Ok(missing_role("resolved_by", "default_author"));
+        };
+        if let Some(refused) = self.comment_policy_refusal(&params, &resolved_by).await {
+            return Ok(refused);
+        }
+
+        match artifacts::resolve_comment(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            &params.comment_id,
+            params.action,
+            params.response.as_deref(),
+            &resolved_by,
+        )
+        .await
+        {
+            Ok(response) => {
+                let action_verb = match params.action {
+                    ResolveAction::Accept => "accepted",
+                    ResolveAction::Reject => "rejected",
+                    ResolveAction::Reply => "replied to",
+                    ResolveAction::Address => "addressed",
+                    ResolveAction::Resolve => "resolved",
+                };
+                Ok(self.render_whole(
+                    params.format,
+                    &response,
+                    |response| {
+                        format!(
+                            "✓ Comment {} successfully\n\n\
+                        **Comment ID:** `{}`\n\
+                        **New status:** {}",
+                            action_verb, response.comment_id, response.new_status
+                        )
+                    },
+                    compact::format_resolve_comment_response,
+                ))
+            }
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_resolve_comment",
+                    &params.run_id,
+                    Some(&resolved_by),
+                    "Failed to resolve comment",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] List comments on a document artifact. \
+        Filter by status (open/resolved/rejected/all) and comment type; set mentioning to \"me\" for the comments addressed to you. \
+        Narrow further by author, created_after, and has_reply (false for the comments nobody has answered yet). \
+        Results come in pages of limit comments; while has_more, list again with a higher offset. \
+        Set report to csv or jsonl to export the comments for analysis, written to report_path if given."
+    )]
+    async fn doc_artifact_list_comments(
+        &self,
+        Parameters(params): Parameters<DocArtifactListCommentsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let mentioning = match params.mentioning.as_deref() {
+            Some("me") => match self.my_role() {
+                Some(role) => Some(role),
+                None => return Ok(missing_role("mentioning", "default_author")),
+            },
+            other => other.map(String::from),
+        };
+        let author = match params.author.as_deref() {
+            Some("me") => match self.my_role() {
+                Some(role) => Some(role),
+                None => return Ok(missing_role("author", "default_author")),
+            },
+            other => other.map(String::from),
+        };
+        let filter = CommentFilter {
+            author: author.as_deref(),
+            created_after: validate::date("created_after", params.created_after.as_deref())
+                .ok()
+                .flatten(),
+            has_reply: params.has_reply,
+        };
+        let prefetched = match (params.line_start, params.line_end) {
+            (None, None) => self.comments.lookup(
+                &params.run_id,
+                &params.artifact_id,
+                params.status.as_deref(),
+                params.comment_type.as_deref(),
+                mentioning.as_deref(),
+                COMMENT_PREFETCH_TTL,
+            ),
+            _ => None,
+        };
+        let listed = match prefetched {
+            Some(response) => Ok(response),
+            None => {
+                artifacts::list_comments(
+                    &*self.client,
+                    &params.run_id,
+                    &params.artifact_id,
+                    params.status.as_deref(),
+                    params.comment_type.as_deref(),
+                    params.line_start,
+                    params.line_end,
+                    mentioning.as_deref(),
+                )
+                .await
+            }
+        };
+        let listed = match listed {
+            Ok(mut response) => {
+                let replied = if filter.needs_replies() {
+                    self.replied_comments(&params.run_id, &params.artifact_id)
+                        .await
+                } else {
+                    Ok(HashSet::new())
+                };
+                replied.map(|replied| {
+                    filter.apply(&mut response, &replied);
+                    (response.offset, response.has_more) = self.page(
+                        &mut response.comments,
+                        params.offset,
+                        params.limit,
+                        params.report,
+                    );
+                    response
+                })
+            }
+            Err(e) => Err(e),
+        };
+        match (listed, params.report) {
+            (Ok(response), Some(format)) => Ok(self.report(
+                report::comments_report(&response, format),
+                response.comments.len(),
+                "comment",
+                params.report_path.as_deref(),
+                params.continuation.as_deref(),
+            )),
+            (Ok(response), None) => Ok(self.render_list(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                artifacts::format_comments_response,
+                compact::format_comments_response,
+                tables::format_comments_response,
+            )),
+            (Err(e), _) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_list_comments",
+                    &params.run_id,
+                    None,
+                    "Failed to list comments",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    // =========================================================================
+    // EDIT SUGGESTION TOOLS (Mode 2: Suggest with diff preview)
+    // =========================================================================
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Create an edit suggestion linked to a comment. \
+        Instead of directly editing, this proposes a change that the user can preview and accept/reject. \
+        Edit types: replace_range (replace text between offsets), insert (insert at offset), \
+        append (add to end), full_replace (replace entire content). A replace_range whose offsets \
+        no longer cover the comment's text is refused with the offset to re-anchor at. \
+        Set dry_run to check the offsets and see the resulting diff without creating the suggestion."
+    )]
+    async fn doc_artifact_suggest_edit(
+        &self,
+        Parameters(params): Parameters<DocArtifactSuggestEditRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        self.comments
+            .invalidate(&params.run_id, &params.artifact_id);
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+        let ([start_offset, end_offset], document) = match self
+            .char_offsets(
+                &params.run_id,
+                &params.artifact_id,
+                params.offset_unit,
+                [
+                    ("start_offset", params.start_offset),
+                    ("end_offset", params.end_offset),
+                ],
+            )
+            .await
+        {
+            Ok(checked) => checked,
+            Err(invalid) => return Ok(invalid),
+        };
+        // A suggestion whose range no longer holds the commented text would
+        // fail on acceptance; catch it now. Best-effort: skipped if the
+        // comment can't be fetched.
+        if let (EditType::ReplaceRange, Some(start), Some(end), Some(document)) =
+            (params.edit_type, start_offset, end_offset, &document)
+        {
+            match artifacts::find_comment(
+                &*self.client,
+                &params.run_id,
+                &params.artifact_id,
+                &params.comment_id,
+            )
+            .await
+            {
+                Ok(Some(CommentDetail {
+                    selection_text: Some(anchored),
+                    ..
+                })) => {
+                    if let Err(e) = offsets::check_anchor(
+                        &document.content,
+                        start as usize,
+                        end as usize,
+                        &anchored,
+                    ) {
+                        return Ok(CallToolResult::error(vec![Content::text(format!(
+                            "Suggestion not created: {}",
+                            e
+                        ))]));
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Skipped anchor check for {}: {}", params.comment_id, e),
+            }
+        }
+        if params.dry_run.unwrap_or(false) {
+            let document = match self
+                .document_or_current(&params.run_id, &params.artifact_id, document)
+                .await
+            {
+                Ok(document) => document,
+                Err(e) => {
+                    return Ok(self
+                        .run_tool_failure(
+                            "doc_artifact_suggest_edit",
+                            &params.run_id,
+                            Some(&source),
+                            "Failed to read artifact for dry run",
+                            e,
+                        )
+                        .await)
+                }
+            };
+            return Ok(
+                match artifacts::preview_edit(
+                    &document,
+                    None,
+                    params.edit_type,
+                    &params.suggested_text,
+                    start_offset,
+                    end_offset,
+                    start_offset,
+                ) {
+                    Ok(preview) => self.render_whole(
+                        params.format,
+                        &preview,
+                        artifacts::format_edit_preview,
+                        compact::format_edit_preview,
+                    ),
+                    Err(e) => CallToolResult::error(vec![Content::text(format!(
+                        "Invalid suggestion: {}",
+                        e
+                    ))]),
+                },
+            );
+        }
+
+        match artifacts::suggest_edit(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            &params.comment_id,
+            params.edit_type,
+            start_offset,
+            end_offset,
+            &params.suggested_text,
+            params.rationale.as_deref(),
+            &source,
+            document.as_ref().map(|d| d.content_hash.as_str()),
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render_whole(
+                params.format,
+                &response,
+                artifacts::format_suggest_edit_response,
+                compact::format_suggest_edit_response,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_suggest_edit",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to create suggestion",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Accept an edit suggestion and apply it to the document. \
+        This applies the suggested change to the document content."
+    )]
+    async fn doc_artifact_accept_suggestion(
+        &self,
+        Parameters(params): Parameters<DocArtifactAcceptSuggestionRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        self.comments
+            .invalidate(&params.run_id, &params.artifact_id);
+        if let Some(denied) = self
+            .not_permitted(
+                &params.run_id,
+                params.source.as_deref(),
+                "doc_artifact_accept_suggestion",
+            )
+            .await
+        {
+            return Ok(denied);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+
+        match artifacts::accept_suggestion(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            &params.suggestion_id,
+            &source,
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render_whole(
+                params.format,
+                &response,
+                artifacts::format_accept_suggestion_response,
+                compact::format_accept_suggestion_response,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_accept_suggestion",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to accept suggestion",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Reject an edit suggestion without applying it. \
+        Optionally provide a reason for rejection."
+    )]
+    async fn doc_artifact_reject_suggestion(
+        &self,
+        Parameters(params): Parameters<DocArtifactRejectSuggestionRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        if let Some(ended) = self.ended_run(&params.run_id).await {
+            return Ok(ended);
+        }
+        self.comments
+            .invalidate(&params.run_id, &params.artifact_id);
+        if let Some(denied) = self
+            .not_permitted(
+                &params.run_id,
+                params.source.as_deref(),
+                "doc_artifact_reject_suggestion",
+            )
+            .await
+        {
+            return Ok(denied);
+        }
+        let Some(source) = self.source_or_default(params.source.as_deref()) else {
+            return Ok(missing_role("source", "default_source"));
+        };
+
+        match artifacts::reject_suggestion(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            &params.suggestion_id,
+            params.reason.as_deref(),
+            &source,
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render_whole(
+                params.format,
+                &response,
+                artifacts::format_reject_suggestion_response,
+                compact::format_reject_suggestion_response,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_reject_suggestion",
+                    &params.run_id,
+                    Some(&source),
+                    "Failed to reject suggestion",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] List edit suggestions for a document artifact. \
+        Filter by status (pending/accepted/rejected/all) to see relevant suggestions. \
+        Results come in pages of limit suggestions; while has_more, list again with a higher offset. \
+        Set report to csv or jsonl to export the suggestions for analysis, written to report_path if given."
+    )]
+    async fn doc_artifact_list_suggestions(
+        &self,
+        Parameters(params): Parameters<DocArtifactListSuggestionsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        // Validate status if provided
+        if let Some(ref status) = params.status {
+            if let Err(e) = artifacts::validate_suggestion_status(status) {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Invalid status filter: {}",
+                    e
+                ))]));
+            }
+        }
+
+        let listed = artifacts::list_suggestions(
+            &*self.client,
+            &params.run_id,
+            &params.artifact_id,
+            params.status.as_deref(),
+        )
+        .await
+        .map(|mut response| {
+            (response.offset, response.has_more) = self.page(
+                &mut response.suggestions,
+                params.offset,
+                params.limit,
+                params.report,
+            );
+            response
+        });
+        match (listed, params.report) {
+            (Ok(response), Some(format)) => Ok(self.report(
+                report::suggestions_report(&response, format),
+                response.suggestions.len(),
+                "suggestion",
+                params.report_path.as_deref(),
+                params.continuation.as_deref(),
+            )),
+            (Ok(response), None) => Ok(self.render_list(
+                params.format,
+                params.continuation.as_deref(),
+                &response,
+                artifacts::format_suggestions_response,
+                compact::format_suggestions_response,
+                tables::format_suggestions_response,
+            )),
+            (Err(e), _) => Ok(self
+                .run_tool_failure(
+                    "doc_artifact_list_suggestions",
+                    &params.run_id,
+                    None,
+                    "Failed to list suggestions",
+                    e,
+                )
+                .await),
+        }
+    }
+
+    #[tool(
+        description = "[[HOTWIRED DOC-EDITOR ONLY]] Find pending suggestions that no longer fit their document: \
+        the text they replace has moved to other offsets, or is gone. Checks one artifact, or every artifact \
+        in the run if artifact_id is omitted. Set withdraw to reject the suggestions whose text is gone, so the \
+        human is not shown changes that can't be applied; moved ones are only listed, for their author to recreate."
+    )]
+    async fn sweep_stale_suggestions(
+        &self,
+        Parameters(params): Parameters<SweepStaleSuggestionsRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        if let Some(invalid) = self.invalid(&params) {
+            return Ok(invalid);
+        }
+        let withdraw = if params.withdraw.unwrap_or(false) {
+            if let Some(ended) = self.ended_run(&params.run_id).await {
+                return Ok(ended);
+            }
+            let Some(source) = self.source_or_default(params.source.as_deref()) else {
+                return Ok(missing_role("source", "default_source"));
+            };
+            Some(source)
+        } else {
+            None
+        };
+
+        match sweep::sweep(
+            &*self.client,
+            &params.run_id,
+            params.artifact_id.as_deref(),
+            withdraw.as_deref(),
+            self.config.pagination.read_max_limit.max(1),
+            self.config.batch_concurrency(),
+        )
+        .await
+        {
+            Ok(response) => Ok(self.render_whole(
+                params.format,
+                &response,
+                sweep::format_sweep,
+                compact::format_suggestion_sweep,
+            )),
+            Err(e) => Ok(self
+                .run_tool_failure(
+                    "sweep_stale_suggestions",
+                    &params.run_id,
+                    withdraw.as_deref(),
+                    "Failed to sweep suggestions",
+                    e,
+                )
+                .await),
+        }
+    }
+}
+
+/// Result for a call that omitted a role field with no configured default.
+fn missing_role(field: &str, config_key: &str) -> CallToolResult {
+    CallToolResult::error(vec![Content::text(format!(
+        "Missing `{}`: pass your agent role or set `{}` in the Hotwired config",
+        field, config_key
+    ))])
+}
+
+/// The `json` output format: the response pretty-printed.
+fn json<T: Serialize>(response: &T) -> String {
+    serde_json::to_string_pretty(response)
+        .unwrap_or_else(|e| format!("Failed to serialize response: {}", e))
+}
+
+// Implement the server handler
+impl<C: IpcClient + 'static> rmcp::ServerHandler for HotwiredMcp<C> {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            server_info: Implementation {
+                name: env!("CARGO_PKG_NAME").into(),
+                title: Some(format!("Hotwired ({})", self.config.agent_type())),
+                version: env!("CARGO_PKG_VERSION").into(),
+                ..Implementation::default()
+            },
+            instructions: Some(format!(
+                "Hotwired MCP server for multi-agent workflow coordination (agent type: {})",
+                self.config.agent_type()
+            )),
+            capabilities: ServerCapabilities::builder()
+                .enable_logging()
+                .enable_tools()
+                .enable_tool_list_changed()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        let Some(handle) = &self.log_handle else {
+            return Err(McpError::internal_error(
+                "Log level cannot be changed: logging was not initialized by this server",
+                None,
+            ));
+        };
+        handle
+            .set_level(request.level)
+            .map_err(|e| McpError::internal_error(e, None))?;
+        tracing::info!("Log level changed to {:?}", request.level);
+        Ok(())
+    }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        if self.peer.set(context.peer).is_ok() {
+            self.spawn_watcher();
+        }
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let mut tools = self.tool_router.list_all();
+        tools.extend(
+            self.bridge
+                .tools()
+                .await
+                .into_iter()
+                .filter(|tool| self.bridged_tool_enabled(tool)),
+        );
+        Ok(ListToolsResult::with_all_items(tools))
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.tool_router.has_route(&request.name) {
+            let bridged = self.bridge.tools().await;
+            if let Some(tool) = bridged.iter().find(|tool| tool.name == request.name) {
+                if !self.bridged_tool_enabled(tool) {
+                    return Err(McpError::invalid_params("tool not found", None));
+                }
+                return match self.bridge.call(&request.name, request.arguments).await {
+                    Some(Ok(result)) => Ok(result),
+                    Some(Err(e)) => Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Playbook tool {} failed: {}",
+                        request.name, e
+                    ))])),
+                    // The run was switched in the meantime
+                    None => Err(McpError::invalid_params("tool not found", None)),
+                };
+            }
+        }
+        let duplicate = self
+            .config
+            .duplicate_call_window()
+            .filter(|_| MUTATING_TOOLS.contains(&request.name.as_ref()))
+            .map(|window| {
+                let key = RecentCalls::key(&request.name, request.arguments.as_ref());
+                (key, window)
+            });
+        if let Some((key, window)) = &duplicate {
+            if let Some(replayed) = self.recent_calls.replay(key, *window, Instant::now()) {
+                tracing::info!("Answered repeated {} call with its earlier result", key.0);
+                return Ok(replayed);
+            }
+        }
+        if self.tool_router.has_route(&request.name) {
+            if let Some(limited) = self.rate_limited(&request.name) {
+                return Ok(limited);
+            }
+        }
+        if let Some(held) = self
+            .approval_refusal(&request.name, request.arguments.as_ref())
+            .await
+        {
+            return Ok(held);
+        }
+        let context = ToolCallContext::new(self, request, context);
+        let result = self.tool_router.call(context).await;
+        if let (Some((key, window)), Ok(result)) = (duplicate, &result) {
+            self.recent_calls
+                .record(key, result, window, Instant::now());
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::{ImpedimentType, StatusProgress, WorkStatus};
+    use crate::ipc::mock::MockIpcClient;
+
+    #[tokio::test]
+    async fn test_ping_returns_connected_when_backend_available() {
+        let mock = MockIpcClient::new();
+        let server = HotwiredMcp::new(mock);
+
+        let result = server.ping().await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        // Check that content indicates connection
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ping_returns_helpful_error_when_backend_unavailable() {
+        let mock = MockIpcClient::new();
+        mock.set_disconnected(true);
+        let server = HotwiredMcp::new(mock);
+
+        let result = server.ping().await.unwrap();
+
+        assert_eq!(
+            result.is_error,
+            Some(false),
+            "Ping MCP call should succeed even if backend unavailable"
+        );
+        // The content should indicate backend is unavailable
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_ping_reports_transport_and_contact() {
+        let mock = MockIpcClient::new();
+        let server = HotwiredMcp::new(mock.clone())
+            .with_session(None, Some("tcp://backend.local:7777/".to_string()));
+        server.usage.add(
+            &ReportUsageRequest {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                model: "sonnet".to_string(),
+                input_tokens: 100,
+                output_tokens: 10,
+                tool_calls: 2,
+                source: None,
+            },
+            "builder",
+            Instant::now(),
+        );
+
+        let result = server.ping().await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("pong - Connected to Hotwired backend\n"));
+        assert!(text.contains("**Transport:** TCP\n**Backend:** `backend.local:7777`"));
+        assert!(text.contains("**Round trip:** "));
+        assert!(text.contains("**Last contact before this ping:** never"));
+        assert!(text.contains("**Queued writes:** 1 usage report(s), 110 tokens"));
+
+        mock.set_disconnected(true);
+        let result = server.ping().await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("MCP running but backend unavailable"));
+        assert!(!text.contains("**Round trip:**"));
+        assert!(
+            text.contains("**Last contact before this ping:** just now"),
+            "{}",
+            text
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tools_the_backend_lacks_are_dropped() {
+        use crate::ipc::messages::GetBackendCapabilitiesResponse;
+
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/capabilities",
+            GetBackendCapabilitiesResponse {
+                app_version: "0.14.0".to_string(),
+                features: vec!["suggestions".to_string()],
+            },
+        );
+        let server = HotwiredMcp::new(mock).with_backend_features().await;
+        assert!(server.tool_router.has_route("doc_artifact_suggest_edit"));
+        assert!(!server.tool_router.has_route("get_notifications"));
+        assert!(!server.tool_router.has_route("get_impediment_resolution"));
+        assert!(server.tool_router.has_route("doc_artifact_edit"));
+
+        // No capabilities endpoint: only tools newer than discovery are dropped
+        let legacy = HotwiredMcp::new(MockIpcClient::new())
+            .with_backend_features()
+            .await;
+        assert!(legacy.tool_router.has_route("get_notifications"));
+        assert!(!legacy.tool_router.has_route("doc_artifact_changelog"));
+
+        let offline = MockIpcClient::new();
+        offline.set_disconnected(true);
+        let offline = HotwiredMcp::new(offline).with_backend_features().await;
+        assert!(offline.tool_router.has_route("doc_artifact_changelog"));
+    }
+
+    #[tokio::test]
+    async fn test_get_protocol_returns_formatted_response() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/protocol",
+            backend_fixture("get_protocol"),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = GetProtocolRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            agent_role: "strategist".to_string(),
+            refresh: None,
+            format: None,
+            continuation: None,
+        };
+
+        let result = server.get_protocol(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_repeat_get_protocol_is_served_from_cache() {
+        use crate::ipc::messages::GetProtocolResponse;
+
+        let run_id = "123e4567-e89b-12d3-a456-426614174000";
+        let endpoint = format!("/api/runs/{}/protocol", run_id);
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &endpoint,
+            GetProtocolResponse {
+                run_id: run_id.into(),
+                template_name: "Plan → Build".into(),
+                playbook_protocol: None,
+                role_protocol: None,
+                initialization_condition: None,
+                project_name: None,
+                capabilities: None,
+                protocol_version: Some("v1".into()),
+                mcp_servers: vec![],
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone());
+        let request = |refresh| GetProtocolRequest {
+            run_id: run_id.to_string(),
+            agent_role: "builder".to_string(),
+            refresh,
+            format: None,
+            continuation: None,
+        };
+
+        for _ in 0..3 {
+            server
+                .get_protocol(Parameters(request(None)))
+                .await
+                .unwrap();
+        }
+        assert_eq!(mock.requests_to(&endpoint).len(), 1);
+
+        server
+            .get_protocol(Parameters(request(Some(true))))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to(&endpoint).len(), 2);
+
+        let uncached = HotwiredMcp::new(mock.clone()).with_config(Config {
+            protocol_cache_secs: Some(0),
+            ..Config::default()
+        });
+        uncached
+            .get_protocol(Parameters(request(None)))
+            .await
+            .unwrap();
+        uncached
+            .get_protocol(Parameters(request(None)))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to(&endpoint).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_prefetches_playbooks_and_restored_protocol() {
+        use crate::ipc::messages::{GetProtocolResponse, ListPlaybooksResponse};
+
+        let run_id = "123e4567-e89b-12d3-a456-426614174000";
+        let protocol_endpoint = format!("/api/runs/{}/protocol", run_id);
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/playbooks",
+            ListPlaybooksResponse { playbooks: vec![] },
+        );
+        mock.when_called(
+            &protocol_endpoint,
+            GetProtocolResponse {
+                run_id: run_id.into(),
+                template_name: "Plan → Build".into(),
+                playbook_protocol: None,
+                role_protocol: None,
+                initialization_condition: None,
+                project_name: None,
+                capabilities: None,
+                protocol_version: None,
+                mcp_servers: vec![],
+            },
+        );
+        let state = StateStore::default();
+        state.update(|c| c.attach(run_id, "builder"));
+        let server = HotwiredMcp::new(mock.clone()).with_state(state);
+
+        server.spawn_warm_up().unwrap().await.unwrap();
+        assert_eq!(mock.requests_to("/api/playbooks").len(), 1);
+        assert_eq!(mock.requests_to(&protocol_endpoint).len(), 1);
+
+        server
+            .list_playbooks(Parameters(ListPlaybooksRequest {
+                format: None,
+                continuation: None,
+            }))
+            .await
+            .unwrap();
+        server
+            .get_protocol(Parameters(GetProtocolRequest {
+                run_id: run_id.to_string(),
+                agent_role: "builder".to_string(),
+                refresh: None,
+                format: None,
+                continuation: None,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to("/api/playbooks").len(), 1);
+        assert_eq!(mock.requests_to(&protocol_endpoint).len(), 1);
+
+        let disabled = HotwiredMcp::new(mock).with_config(Config {
+            warm_up: Some(false),
+            ..Config::default()
+        });
+        assert!(disabled.spawn_warm_up().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_context_is_sent_when_resuming() {
+        use crate::ipc::messages::GetProtocolResponse;
+
+        let run_id = "123e4567-e89b-12d3-a456-426614174000";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/protocol", run_id),
+            GetProtocolResponse {
+                run_id: run_id.into(),
+                template_name: "Plan → Build".into(),
+                playbook_protocol: None,
+                role_protocol: None,
+                initialization_condition: None,
+                project_name: None,
+                capabilities: None,
+                protocol_version: None,
+                mcp_servers: vec![],
+            },
+        );
+        mock.when_called(
+            "/api/resume",
+            ResumeRunResponse::NoneAvailable {
+                message: "Nothing to resume".into(),
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone());
+
+        server
+            .get_protocol(Parameters(GetProtocolRequest {
+                run_id: run_id.to_string(),
+                agent_role: "builder".to_string(),
+                refresh: None,
+                format: None,
+                continuation: None,
+            }))
+            .await
+            .unwrap();
+        server
+            .resume_run(Parameters(ResumeRunRequest {
+                zellij_session: "hw-1".to_string(),
+                project_path: "/path".to_string(),
+                last_run_id: None,
+                last_role: None,
+                format: None,
+            }))
+            .await
+            .unwrap();
+
+        let requests = mock.requests_to("/api/resume");
+        assert!(requests[0].contains(&format!("\"lastRunId\":\"{}\"", run_id)));
+        assert!(requests[0].contains("\"lastRole\":\"builder\""));
+    }
+
+    #[tokio::test]
+    async fn test_get_run_status_returns_formatted_response() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/status",
+            backend_fixture("get_run_status"),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = GetRunStatusRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            format: None,
+            continuation: None,
+        };
+
+        let result = server.get_run_status(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_report_status_succeeds() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/report-status",
+            serde_json::json!({"success": true}),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = ReportStatusRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            status: WorkStatus::Working,
+            message: "Implementing feature".to_string(),
+            source: Some("strategist".to_string()),
+            agent_status: None,
+            progress: None,
+        };
+
+        let result = server.report_status(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_message_returns_event_id() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/message",
+            serde_json::json!({"success": true, "eventId": "evt-1"}),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = SendMessageRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            content: "Test message".to_string(),
+            source: Some("strategist".to_string()),
+            target: None,
+            targets: vec![],
+            broadcast: None,
+            reply_to_event_id: None,
+            priority: None,
+            requires_ack: None,
+            agent_status: None,
+        };
+
+        let result = server.send_message(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_message_to_several_targets() {
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let endpoint = format!("/api/runs/{}/message", run);
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &endpoint,
+            serde_json::json!({"success": true, "eventId": "evt-1"}),
+        );
+        let server = HotwiredMcp::new(mock.clone());
+        let params = |value: serde_json::Value| -> SendMessageRequest {
+            let mut params = serde_json::json!({
+                "runId": run,
+                "content": "Plan is ready",
+                "source": "strategist"
+            });
+            params
+                .as_object_mut()
+                .unwrap()
+                .extend(value.as_object().unwrap().clone());
+            serde_json::from_value(params).unwrap()
+        };
+        let text = |result: CallToolResult| result.content[0].as_text().unwrap().text.clone();
+
+        let result = server
+            .send_message(Parameters(params(serde_json::json!({
+                "target": "builder",
+                "targets": ["builder", "reviewer"]
+            }))))
+            .await
+            .unwrap();
+        assert!(text(result).starts_with("Message sent to builder, reviewer (event ID: evt-1)."));
+        assert!(mock.requests_to(&endpoint)[0].contains("\"targets\":[\"builder\",\"reviewer\"]"));
+
+        let result = server
+            .send_message(Parameters(params(serde_json::json!({
+                "target": "builder",
+                "broadcast": true
+            }))))
+            .await
+            .unwrap();
+        assert!(text(result).starts_with("Invalid arguments: broadcast sends to every agent"));
+
+        let result = server
+            .send_message(Parameters(params(serde_json::json!({"requiresAck": true}))))
+            .await
+            .unwrap();
+        assert!(text(result).starts_with("Invalid arguments: requires_ack needs target"));
+
+        let result = server
+            .send_message(Parameters(params(serde_json::json!({
+                "target": "builder",
+                "priority": "urgent",
+                "requiresAck": true
+            }))))
+            .await
+            .unwrap();
+        assert!(text(result).ends_with("asked to confirm it with ack_message."));
+        assert!(
+            mock.requests_to(&endpoint)[1].contains("\"priority\":\"urgent\",\"requiresAck\":true")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_task_complete_succeeds() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/task-complete",
+            serde_json::json!({"success": true}),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = TaskCompleteRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            task_description: "Implemented feature X".to_string(),
+            source: Some("builder".to_string()),
+            outcome: None,
+            agent_status: None,
+            results: None,
+        };
+
+        let result = server.task_complete(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_report_impediment_succeeds() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/impediment",
+            serde_json::json!({"success": true}),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = ReportImpedimentRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            impediment_type: Some(ImpedimentType::MissingInformation),
+            description: "Need clarification on requirements".to_string(),
+            template_id: None,
+            source: Some("strategist".to_string()),
+            context: None,
+            suggestion: None,
+            agent_status: None,
+            response_format: None,
+            escalate_after_seconds: None,
+        };
+
+        let result = server.report_impediment(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_report_impediment_from_template() {
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/impediment-templates", run),
+            serde_json::json!({
+                "runId": run,
+                "templates": [{
+                    "id": "choose-library",
+                    "title": "Choose a library",
+                    "impedimentType": "unclear_requirements",
+                    "description": "A library has to be chosen before work can go on.",
+                    "responseFormat": {"fields": [
+                        {"id": "library", "type": "text", "label": "Library"}
+                    ]}
+                }]
+            }),
+        );
+        mock.when_called(
+            &format!("/api/runs/{}/impediment", run),
+            serde_json::json!({"success": true}),
+        );
+        let server = HotwiredMcp::new(mock.clone());
+        let params = |template_id: &str| -> ReportImpedimentRequest {
+            serde_json::from_value(serde_json::json!({
+                "runId": run,
+                "description": "HTTP client: reqwest or ureq?",
+                "templateId": template_id,
+                "source": "builder"
+            }))
+            .unwrap()
+        };
+
+        let result = server
+            .report_impediment(Parameters(params("choose-library")))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(false));
+        let sent = mock.requests_to(&format!("/api/runs/{}/impediment", run));
+        assert!(sent[0].contains("\"impedimentType\":\"unclear_requirements\""));
+        assert!(sent[0].contains(
+            "\"description\":\"A library has to be chosen before work can go on.\\n\\nHTTP client: reqwest or ureq?\""
+        ));
+        assert!(sent[0].contains("\"label\":\"Library\""));
+        assert!(sent[0].contains("\"templateId\":\"choose-library\""));
+
+        let result = server
+            .report_impediment(Parameters(params("need-credentials")))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_request_input_succeeds() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/input",
+            serde_json::json!({"success": true, "requestId": "input-1"}),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = RequestInputRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            question: "Which approach should we use?".to_string(),
+            source: Some("strategist".to_string()),
+            context: None,
+            options: None,
+        };
+
+        let result = server.request_input(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_handoff_succeeds() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/handoff",
+            serde_json::json!({"success": true}),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = HandoffRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            to: "builder".to_string(),
+            summary: "Ready for implementation".to_string(),
+            source: Some("strategist".to_string()),
+            details: None,
+            artifacts: None,
+            tasks: None,
+        };
+
+        let result = server.handoff(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_end_run_returns_request_id() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/end",
+            serde_json::json!({"success": true, "requestId": "end-1"}),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = RequestEndRunRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            reason: "completed".to_string(),
+            description: "All tasks finished".to_string(),
+            source: Some("strategist".to_string()),
+            suggested_follow_up: None,
+        };
+
+        let result = server.request_end_run(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_role_without_permission_cannot_end_run() {
+        use crate::ipc::messages::GetProtocolResponse;
+
+        let run = "/api/runs/123e4567-e89b-12d3-a456-426614174000";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("{}/protocol", run),
+            GetProtocolResponse {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".into(),
+                template_name: "Plan → Build".into(),
+                playbook_protocol: None,
+                role_protocol: None,
+                initialization_condition: None,
+                project_name: None,
+                capabilities: Some(RoleCapabilities {
+                    can_end_run: false,
+                    ..RoleCapabilities::default()
+                }),
+                protocol_version: None,
+                mcp_servers: vec![],
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone());
+
+        let params = RequestEndRunRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            reason: "completed".to_string(),
+            description: "All tasks finished".to_string(),
+            source: Some("builder".to_string()),
+            suggested_follow_up: None,
+        };
+        let result = server.request_end_run(Parameters(params)).await.unwrap();
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("role 'builder' is not permitted to use request_end_run"));
+        assert_eq!(result.is_error, Some(true));
+        assert!(mock.requests_to(&format!("{}/end", run)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refused_call_is_run_again_on_retry() {
+        use crate::ipc::messages::GetProtocolResponse;
+
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let protocol = |can_end_run| GetProtocolResponse {
+            run_id: run.into(),
+            template_name: "Plan → Build".into(),
+            playbook_protocol: None,
+            role_protocol: None,
+            initialization_condition: None,
+            project_name: None,
+            capabilities: Some(RoleCapabilities {
+                can_end_run,
+                ..RoleCapabilities::default()
+            }),
+            protocol_version: None,
+            mcp_servers: vec![],
+        };
+        let mock = MockIpcClient::new();
+        mock.when_called(&format!("/api/runs/{}/protocol", run), protocol(false));
+        mock.when_called(
+            &format!("/api/runs/{}/end", run),
+            serde_json::json!({"success": true, "requestId": "end-1"}),
+        );
+        let server = HotwiredMcp::new(mock.clone());
+        let params = || RequestEndRunRequest {
+            run_id: run.to_string(),
+            reason: "completed".to_string(),
+            description: "All tasks finished".to_string(),
+            source: Some("builder".to_string()),
+            suggested_follow_up: None,
+        };
+        let arguments = serde_json::to_value(params()).unwrap();
+        let key = RecentCalls::key("request_end_run", arguments.as_object());
+        let window = Duration::from_secs(10);
+
+        // Recorded the way call_tool records every mutating call
+        let refused = server.request_end_run(Parameters(params())).await.unwrap();
+        server
+            .recent_calls
+            .record(key.clone(), &refused, window, Instant::now());
+        assert!(server
+            .recent_calls
+            .replay(&key, window, Instant::now())
+            .is_none());
+
+        // Permitted now, as after switch_role: the retry reaches the backend
+        server
+            .role_capabilities
+            .record(run, "builder", protocol(true).capabilities);
+        let retried = server.request_end_run(Parameters(params())).await.unwrap();
+        assert_eq!(retried.is_error, Some(false));
+        assert_eq!(mock.requests_to(&format!("/api/runs/{}/end", run)).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_comment_policy_refuses_before_the_backend() {
+        let run = "/api/runs/123e4567-e89b-12d3-a456-426614174000/artifacts/spec/comments";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("{}/list", run),
+            serde_json::json!({
+                "artifactId": "spec",
+                "comments": [{
+                    "id": "c-1",
+                    "commentType": "issue",
+                    "status": "open",
+                    "selectionStart": 0,
+                    "selectionEnd": 4,
+                    "selectionText": null,
+                    "content": "Define the TTL",
+                    "suggestedText": null,
+                    "author": "reviewer",
+                    "resolvedBy": null,
+                    "resolvedAt": null,
+                    "resolutionNote": null,
+                    "createdAt": "2024-01-15T10:00:00Z"
+                }]
+            }),
+        );
+        let config = Config {
+            comment_policies: serde_json::from_value(serde_json::json!([
+                {"role": "writer", "deny": ["reject"], "threads": "others"}
+            ]))
+            .unwrap(),
+            ..Config::default()
+        };
+        let server = HotwiredMcp::new(mock.clone()).with_config(config);
+        let params = |action, resolved_by: &str| DocArtifactResolveCommentRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            artifact_id: "spec".to_string(),
+            comment_id: "c-1".to_string(),
+            action,
+            response: None,
+            resolved_by: Some(resolved_by.to_string()),
+            format: None,
+        };
+
+        let result = server
+            .doc_artifact_resolve_comment(Parameters(params(ResolveAction::Reject, "writer")))
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(true));
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("role 'writer' may not reject comment c-1 by 'reviewer'"));
+        assert_eq!(
+            result.structured_content.unwrap()["error"],
+            serde_json::json!("comment_policy")
+        );
+        assert!(mock.requests_to(&format!("{}/c-1/resolve", run)).is_empty());
+
+        // Other roles are not covered by the rule
+        server
+            .doc_artifact_resolve_comment(Parameters(params(ResolveAction::Reject, "human")))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to(&format!("{}/c-1/resolve", run)).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_respond_to_end_request_succeeds() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/end/respond",
+            serde_json::json!({"success": true}),
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = RespondToEndRequestRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            request_id: "req-123".to_string(),
+            response: "agree".to_string(),
+            source: Some("builder".to_string()),
+            reason: None,
+        };
+
+        let result = server
+            .respond_to_end_request(Parameters(params))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_impediment_succeeds() {
+        use crate::ipc::messages::ResolveImpedimentResponse;
+
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/impediment/resolve",
+            ResolveImpedimentResponse {
+                success: true,
+                error: None,
+            },
+        );
+        let server = HotwiredMcp::new(mock);
+
+        let params = ResolveImpedimentRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            impediment_id: 42,
+            response: serde_json::json!({"database": "postgresql"}),
+            source: Some("strategist".to_string()),
+            rationale: Some("PostgreSQL better suits the production requirements".to_string()),
+        };
+
+        let result = server.resolve_impediment(Parameters(params)).await.unwrap();
+
+        assert_eq!(result.is_error, Some(false));
+        assert_eq!(result.content.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_impediment_checks_answer_against_form() {
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let resolve = format!("/api/runs/{}/impediment/resolve", run);
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/impediments/42", run),
+            serde_json::json!({
+                "impedimentId": 42,
+                "status": "open",
+                "responseFormat": {"fields": [{
+                    "id": "database",
+                    "type": "radio",
+                    "label": "Database",
+                    "options": [
+                        {"value": "postgresql", "label": "PostgreSQL"},
+                        {"value": "sqlite", "label": "SQLite"}
+                    ]
+                }]}
+            }),
+        );
+        mock.when_called(&resolve, serde_json::json!({"success": true}));
+        let server = HotwiredMcp::new(mock.clone());
+        let params = |database: &str| ResolveImpedimentRequest {
+            run_id: run.to_string(),
+            impediment_id: 42,
+            response: serde_json::json!({ "database": database }),
+            source: Some("strategist".to_string()),
+            rationale: None,
+        };
+
+        let result = server
+            .resolve_impediment(Parameters(params("postgres")))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "Invalid arguments: response doesn't fit the form of impediment #42: \
+            field \"database\" (Database) must be one of postgresql, sqlite, got \"postgres\""
+        );
+        assert!(mock.requests_to(&resolve).is_empty());
+
+        server
+            .resolve_impediment(Parameters(params("postgresql")))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to(&resolve).len(), 1);
+    }
+
+    /// The first response recorded in `tests/fixtures/backend/<method>.json`.
+    fn backend_fixture(method: &str) -> serde_json::Value {
+        let path = format!(
+            "{}/tests/fixtures/backend/{}.json",
+            env!("CARGO_MANIFEST_DIR"),
+            method
+        );
+        let fixture: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        fixture["responses"][0].clone()
+    }
+
+    fn report_status_params() -> ReportStatusRequest {
+        ReportStatusRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            status: WorkStatus::Working,
+            message: "Implementing feature".to_string(),
+            source: Some("builder".to_string()),
+            agent_status: None,
+            progress: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_error_forwarded_when_enabled() {
+        let mock = MockIpcClient::new();
+        let config = Config {
+            forward_tool_errors: true,
+            ..Config::default()
+        };
+        let server = HotwiredMcp::new(mock.clone()).with_config(config);
+
+        // No response configured for report-status, so the call fails
+        let result = server
+            .report_status(Parameters(report_status_params()))
+            .await
+            .unwrap();
+
+        assert_eq!(result.is_error, Some(true));
+        let requests = mock.requests_to("/api/runs/123e4567-e89b-12d3-a456-426614174000/events");
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].contains("tool_error"));
+        assert!(requests[0].contains("report_status"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_error_not_forwarded_by_default() {
+        let mock = MockIpcClient::new();
+        let server = HotwiredMcp::new(mock.clone());
+
+        server
+            .report_status(Parameters(report_status_params()))
+            .await
+            .unwrap();
+
+        assert!(mock
+            .requests_to("/api/runs/123e4567-e89b-12d3-a456-426614174000/events")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_omitted_source_uses_configured_default() {
+        use crate::ipc::messages::ReportStatusResponse;
+
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/report-status",
+            ReportStatusResponse { success: true },
+        );
+        let config = Config {
+            default_source: Some("builder".to_string()),
+            ..Config::default()
+        };
+        let server = HotwiredMcp::new(mock.clone()).with_config(config);
+
+        let params = ReportStatusRequest {
+            source: None,
+            ..report_status_params()
+        };
+        server.report_status(Parameters(params)).await.unwrap();
+
+        let requests =
+            mock.requests_to("/api/runs/123e4567-e89b-12d3-a456-426614174000/report-status");
+        assert!(requests[0].contains(r#""source":"builder""#));
+    }
+
+    #[tokio::test]
+    async fn test_notifications_are_fetched_and_acknowledged_for_default_source() {
+        use crate::ipc::messages::{AckNotificationResponse, GetNotificationsResponse};
+
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/notifications",
+            GetNotificationsResponse {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                role: "builder".to_string(),
+                notifications: vec![],
+            },
+        );
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/notifications/ack",
+            AckNotificationResponse {
+                success: true,
+                acknowledged: 1,
+            },
+        );
+        let config = Config {
+            default_source: Some("builder".to_string()),
+            ..Config::default()
+        };
+        let server = HotwiredMcp::new(mock.clone()).with_config(config);
+
+        let result = server
+            .get_notifications(Parameters(GetNotificationsRequest {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                source: None,
+                include_acknowledged: None,
+                format: None,
+                continuation: None,
+            }))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("No new notifications for builder"));
+
+        let result = server
+            .ack_notification(Parameters(AckNotificationRequest {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                notification_ids: vec!["n1".to_string()],
+                source: None,
+            }))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert_eq!(text, "✓ Acknowledged 1 notification(s)");
+        let requests =
+            mock.requests_to("/api/runs/123e4567-e89b-12d3-a456-426614174000/notifications/ack");
+        assert!(requests[0].contains(r#""source":"builder""#));
+
+        let result = server
+            .ack_notification(Parameters(AckNotificationRequest {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                notification_ids: vec![],
+                source: None,
+            }))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("notification_ids"));
+    }
+
+    #[tokio::test]
+    async fn test_report_usage_is_batched_until_run_usage_is_read() {
+        use crate::ipc::messages::{GetRunUsageResponse, ReportUsageResponse};
+
+        let mock = MockIpcClient::new();
+        let report = "/api/runs/123e4567-e89b-12d3-a456-426614174000/usage/report";
+        mock.when_called(report, ReportUsageResponse { success: true });
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/usage",
+            GetRunUsageResponse {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                usage: vec![],
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone());
+
+        for _ in 0..3 {
+            let params = ReportUsageRequest {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                model: "sonnet".to_string(),
+                input_tokens: 100,
+                output_tokens: 10,
+                tool_calls: 2,
+                source: Some("builder".to_string()),
+            };
+            let result = server.report_usage(Parameters(params)).await.unwrap();
+            assert_eq!(
+                result.content[0].as_text().unwrap().text,
+                "✓ Usage recorded"
+            );
+        }
+        assert!(mock.requests_to(report).is_empty());
+
+        server
+            .get_run_usage(Parameters(GetRunUsageRequest {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                format: None,
+                continuation: None,
+            }))
+            .await
+            .unwrap();
+        let sent = mock.requests_to(report);
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].contains(r#""inputTokens":300"#));
+        assert!(sent[0].contains(r#""toolCalls":6"#));
+    }
+
+    #[tokio::test]
+    async fn test_omitted_source_without_default_is_rejected() {
+        let mock = MockIpcClient::new();
+        let server = HotwiredMcp::new(mock.clone());
+
+        let params = ReportStatusRequest {
+            source: None,
+            ..report_status_params()
+        };
+        let result = server.report_status(Parameters(params)).await.unwrap();
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("default_source"));
+        mock.assert_no_calls();
+    }
+
+    #[test]
+    fn test_config_tool_filter_removes_routes() {
+        let config =
+            Config::from_json(r#"{"tools": {"deny": ["doc_artifact_*", "request_end_run"]}}"#)
+                .unwrap();
+        let server = HotwiredMcp::new(MockIpcClient::new()).with_config(config);
+
+        assert!(server.tool_router.has_route("report_status"));
+        assert!(!server.tool_router.has_route("request_end_run"));
+        assert!(!server
+            .tool_router
+            .list_all()
+            .iter()
+            .any(|tool| tool.name.starts_with("doc_artifact_")));
+    }
+
+    fn mock_with_artifacts() -> MockIpcClient {
+        use crate::ipc::messages::{ArtifactSummary, DocArtifactListResponse};
+
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/123e4567-e89b-12d3-a456-426614174000/artifacts",
+            DocArtifactListResponse {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                artifacts: vec![ArtifactSummary {
+                    id: "art-1".to_string(),
+                    filename: "spec.md".to_string(),
+                    document_type: "markdown".to_string(),
+                    total_lines: 42,
+                    content_hash: "abc123def456".to_string(),
+                    created_at: "2024-01-01T00:00:00Z".to_string(),
+                    updated_at: "2024-01-01T00:00:00Z".to_string(),
+                    tags: vec![],
+                }],
+            },
+        );
+        mock
+    }
+
+    #[tokio::test]
+    async fn test_per_call_json_format_returns_backend_json() {
+        let mock = mock_with_artifacts();
+        let server = HotwiredMcp::new(mock.clone());
+
+        let params = DocArtifactListRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            tag: None,
+            document_type: None,
+            format: Some(OutputFormat::Json),
+            continuation: None,
+        };
+        let result = server.doc_artifact_list(Parameters(params)).await.unwrap();
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["artifacts"][0]["filename"], "spec.md");
+        // The format param is never forwarded to the backend
+        let requests = mock.requests_to("/api/runs/123e4567-e89b-12d3-a456-426614174000/artifacts");
+        assert!(!requests[0].contains("format"));
+    }
+
+    #[tokio::test]
+    async fn test_config_output_format_applies_when_not_requested() {
+        let config = Config {
+            output_format: OutputFormat::Json,
+            ..Config::default()
+        };
+        let server = HotwiredMcp::new(mock_with_artifacts()).with_config(config);
+
+        let params = DocArtifactListRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            tag: None,
+            document_type: None,
+            format: None,
+            continuation: None,
+        };
+        let result = server.doc_artifact_list(Parameters(params)).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(serde_json::from_str::<serde_json::Value>(&text).is_ok());
+
+        let params = DocArtifactListRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            tag: None,
+            document_type: None,
+            format: Some(OutputFormat::Markdown),
+            continuation: None,
+        };
+        let result = server.doc_artifact_list(Parameters(params)).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("spec.md"));
+        assert!(serde_json::from_str::<serde_json::Value>(&text).is_err());
+    }
+
+    #[test]
+    fn test_format_param_is_advertised_in_tool_schema() {
+        let server = HotwiredMcp::new(MockIpcClient::new());
+        let tool = server
+            .tool_router
+            .list_all()
+            .into_iter()
+            .find(|tool| tool.name == "doc_artifact_read")
+            .unwrap();
+        assert!(tool.input_schema["properties"].get("format").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_compact_format_renders_table() {
+        let config = Config {
+            output_format: OutputFormat::Compact,
+            ..Config::default()
+        };
+        let server = HotwiredMcp::new(mock_with_artifacts()).with_config(config);
+
+        let params = DocArtifactListRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            tag: None,
+            document_type: None,
+            format: None,
+            continuation: None,
+        };
+        let result = server.doc_artifact_list(Parameters(params)).await.unwrap();
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("art-1 | spec.md | markdown | 42"));
+        assert!(!text.contains("**"));
+    }
+
+    #[tokio::test]
+    async fn test_long_output_is_chunked_with_continuation() {
+        let mut config = Config {
+            output_format: OutputFormat::Compact,
+            ..Config::default()
+        };
+        config.pagination.max_output_chars = 110;
+        let server = HotwiredMcp::new(mock_with_artifacts()).with_config(config);
+
+        let params = DocArtifactListRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            tag: None,
+            document_type: None,
+            format: None,
+            continuation: None,
+        };
+        let result = server.doc_artifact_list(Parameters(params)).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(!text.contains("art-1"));
+        let token = text
+            .split("continuation: \"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .unwrap()
+            .to_string();
+
+        let params = DocArtifactListRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            tag: None,
+            document_type: None,
+            format: None,
+            continuation: Some(token),
+        };
+        let result = server.doc_artifact_list(Parameters(params)).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("art-1 | spec.md"));
+        assert!(!text.contains("continuation"));
+    }
+
+    #[tokio::test]
+    async fn test_long_json_output_is_chunked_into_a_complete_document() {
+        let mut config = Config {
+            output_format: OutputFormat::Json,
+            ..Config::default()
+        };
+        config.pagination.max_output_chars = 80;
+        let server = HotwiredMcp::new(mock_with_artifacts()).with_config(config);
+
+        let mut document = String::new();
+        let mut continuation = None;
+        loop {
+            let params = DocArtifactListRequest {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+                tag: None,
+                document_type: None,
+                format: None,
+                continuation: continuation.take(),
+            };
+            let result = server.doc_artifact_list(Parameters(params)).await.unwrap();
+            let text = result.content[0].as_text().unwrap().text.clone();
+            let (body, footer) = text.split_once("\n---\n").unwrap_or((&text, ""));
+            document.push_str(body);
+            continuation = footer
+                .split("continuation: \"")
+                .nth(1)
+                .and_then(|rest| rest.split('"').next())
+                .map(str::to_string);
+            if continuation.is_none() {
+                break;
+            }
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&document).unwrap();
+        assert_eq!(json["artifacts"][0]["contentHash"], "abc123def456");
+    }
+
+    #[tokio::test]
+    async fn test_table_format_renders_markdown_table_for_lists() {
+        let server = HotwiredMcp::new(mock_with_artifacts());
+
+        let params = DocArtifactListRequest {
+            run_id: "123e4567-e89b-12d3-a456-426614174000".to_string(),
+            tag: None,
+            document_type: None,
+            format: Some(OutputFormat::Table),
+            continuation: None,
+        };
+        let result = server.doc_artifact_list(Parameters(params)).await.unwrap();
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("| id | filename | type | lines | updated |"));
+        assert!(text.contains("| `art-1` | spec.md | markdown | 42 |"));
+    }
+
+    #[tokio::test]
+    async fn test_list_suggestions_report_is_written_to_file() {
+        use crate::ipc::messages::{DocArtifactListSuggestionsResponse, ReportFormat};
+
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1/suggestions/list", run),
+            DocArtifactListSuggestionsResponse {
+                artifact_id: "art-1".to_string(),
+                offset: 0,
+                has_more: false,
+                suggestions: vec![],
+            },
+        );
+        let project = std::env::temp_dir().join(format!("hotwired-report-{}", std::process::id()));
+        let server = HotwiredMcp::new(mock).with_session(
+            Some(("session".to_string(), project.display().to_string())),
+            None,
+        );
+        let path = project.join("reports/suggestions.csv");
+
+        let result = server
+            .doc_artifact_list_suggestions(Parameters(DocArtifactListSuggestionsRequest {
+                run_id: run.to_string(),
+                artifact_id: "art-1".to_string(),
+                status: Some("all".to_string()),
+                format: None,
+                continuation: None,
+                report: Some(ReportFormat::Csv),
+                report_path: Some("reports/suggestions.csv".to_string()),
+                offset: None,
+                limit: None,
+            }))
+            .await
+            .unwrap();
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert_eq!(
+            text,
+            format!("✓ Wrote 0 suggestions to `{}`", path.display())
+        );
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert!(csv.starts_with("id,artifact_id,comment_id,suggested_by,"));
+        std::fs::remove_dir_all(&project).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_run_id_is_rejected_before_ipc() {
+        let mock = mock_with_artifacts();
+        let server = HotwiredMcp::new(mock.clone());
+
+        let params = DocArtifactListRequest {
+            run_id: "run-123".to_string(),
+            tag: None,
+            document_type: None,
+            format: None,
+            continuation: None,
+        };
+        let result = server.doc_artifact_list(Parameters(params)).await.unwrap();
+
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("Invalid arguments: run_id 'run-123' is not a UUID"));
+        assert!(mock.requests_to("/api/runs/run-123/artifacts").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_agent_status_is_rejected_before_ipc() {
+        let mock = MockIpcClient::new();
+        let server = HotwiredMcp::new(mock.clone());
+
+        let params = ReportStatusRequest {
+            agent_status: Some("busy".to_string()),
+            ..report_status_params()
+        };
+        let result = server.report_status(Parameters(params)).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("Invalid agent status 'busy'"));
+
+        assert!(mock
+            .requests_to("/api/runs/123e4567-e89b-12d3-a456-426614174000/report-status")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_status_progress_over_100_percent_is_rejected() {
+        let mock = MockIpcClient::new();
+        let server = HotwiredMcp::new(mock.clone());
+
+        let params = ReportStatusRequest {
+            progress: Some(StatusProgress {
+                percent_complete: Some(140),
+                ..StatusProgress::default()
+            }),
+            ..report_status_params()
+        };
+        let result = server.report_status(Parameters(params)).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert_eq!(
+            text,
+            "Invalid arguments: progress.percent_complete must be between 0 and 100"
+        );
+        mock.assert_no_calls();
+    }
+
+    #[test]
+    fn test_every_tool_is_classified_as_mutating_or_read_only() {
+        for tool in HotwiredMcp::new(MockIpcClient::new())
+            .tool_router
+            .list_all()
+        {
+            let name = tool.name.as_ref();
+            assert_ne!(
+                MUTATING_TOOLS.contains(&name),
+                READ_ONLY_TOOLS.contains(&name),
+                "{} must be in exactly one of MUTATING_TOOLS and READ_ONLY_TOOLS",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_mutating_tool_is_checked_before_approval() {
+        for tool in MUTATING_TOOLS {
+            assert!(
+                preflight(tool, None, &PaginationConfig::default()).is_some(),
+                "{} needs a preflight entry",
+                tool
+            );
+        }
+    }
+
+    #[test]
+    fn test_only_read_only_tools_offer_continuation() {
+        for tool in HotwiredMcp::new(MockIpcClient::new())
+            .tool_router
+            .list_all()
+        {
+            let continues = tool
+                .input_schema
+                .get("properties")
+                .and_then(|p| p.get("continuation"))
+                .is_some();
+            assert!(
+                !(continues && MUTATING_TOOLS.contains(&tool.name.as_ref())),
+                "{} changes state and must not take a continuation",
+                tool.name
+            );
+            if tool.name == "doc_artifact_read" {
+                assert!(continues);
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_only_mode_refuses_report_path() {
+        let server = HotwiredMcp::new(MockIpcClient::new()).with_config(Config {
+            read_only: true,
+            ..Config::default()
+        });
+        let result = server.report(
+            Ok("id\n".to_string()),
+            0,
+            "comment",
+            Some("reports/comments.csv"),
+            None,
+        );
+        assert_eq!(result.is_error, Some(true));
+        assert!(result.content[0]
+            .as_text()
+            .unwrap()
+            .text
+            .starts_with("report_path is not available in read-only mode"));
+    }
+
+    #[test]
+    fn test_read_only_mode_hides_mutating_tools() {
+        let all: Vec<_> = HotwiredMcp::new(MockIpcClient::new())
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        for name in MUTATING_TOOLS {
+            assert!(all.contains(&name.to_string()), "unknown tool {}", name);
+        }
+
+        let config = Config {
+            read_only: true,
+            ..Config::default()
+        };
+        let server = HotwiredMcp::new(MockIpcClient::new()).with_config(config);
+        let exposed: Vec<_> = server
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+
+        assert!(exposed.contains(&"doc_artifact_read".to_string()));
+        assert!(exposed.contains(&"get_run_status".to_string()));
+        assert_eq!(exposed.len(), all.len() - MUTATING_TOOLS.len());
+        for name in MUTATING_TOOLS {
+            assert!(!exposed.contains(&name.to_string()), "{} exposed", name);
+        }
+    }
+
+    #[test]
+    fn test_tool_descriptions_are_worded_for_agent_type() {
+        let description = |server: &HotwiredMcp<MockIpcClient>, name: &str| {
+            server
+                .tool_router
+                .list_all()
+                .into_iter()
+                .find(|tool| tool.name == name)
+                .and_then(|tool| tool.description)
+                .unwrap()
+                .to_string()
+        };
+
+        let claude = HotwiredMcp::new(MockIpcClient::new());
+        assert!(description(&claude, "doc_artifact_read").contains("Use the Read tool"));
+        assert!(description(&claude, "doc_artifact_edit").contains("Use the Edit/Write tools"));
+
+        let config = Config {
+            agent_type: Some("gemini".into()),
+            ..Config::default()
+        };
+        let gemini = HotwiredMcp::new(MockIpcClient::new()).with_config(config);
+        assert!(description(&gemini, "doc_artifact_read").contains("Use the read_file tool"));
+        for tool in gemini.tool_router.list_all() {
+            let text = tool.description.unwrap_or_default();
+            assert!(
+                !text.contains('{'),
+                "{} has a placeholder: {}",
+                tool.name,
+                text
+            );
+        }
+
+        let info = rmcp::ServerHandler::get_info(&gemini);
+        assert_eq!(info.server_info.name, "hotwired-mcp");
+        assert!(info.instructions.unwrap().contains("agent type: gemini"));
+    }
+
+    #[test]
+    fn test_enum_params_advertise_allowed_values() {
+        let server = HotwiredMcp::new(MockIpcClient::new());
+        let tools = server.tool_router.list_all();
+        let schema = |name: &str| {
+            let tool = tools.iter().find(|tool| tool.name == name).unwrap();
+            serde_json::to_string(&tool.input_schema).unwrap()
+        };
+
+        let edit = schema("doc_artifact_edit");
+        for value in ["replace_range", "insert", "append", "full_replace"] {
+            assert!(edit.contains(&format!("\"{}\"", value)), "{}", value);
+        }
+        assert!(schema("report_impediment").contains("\"dependency_blocked\""));
+        assert!(schema("doc_artifact_resolve_comment").contains("\"address\""));
+    }
+
+    #[tokio::test]
+    async fn test_review_context_combines_read_comments_and_suggestions() {
+        use crate::ipc::messages::{
+            CommentDetail, DocArtifactListCommentsResponse, DocArtifactListSuggestionsResponse,
+            DocArtifactReadResponse,
+        };
+
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1", run),
+            DocArtifactReadResponse {
+                artifact_id: "art-1".to_string(),
+                filename: "spec.md".to_string(),
+                content: "# Spec".to_string(),
+                content_hash: "abc".to_string(),
+                total_lines: 1,
+                returned_lines: 1,
+                offset: 0,
+                has_more: false,
+                comments: None,
+            },
+        );
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1/comments/list", run),
+            DocArtifactListCommentsResponse {
+                artifact_id: "art-1".to_string(),
+                offset: 0,
+                has_more: false,
+                comments: vec![CommentDetail {
+                    id: "c-1".to_string(),
+                    comment_type: "question".to_string(),
+                    status: "open".to_string(),
+                    selection_start: 0,
+                    selection_end: 6,
+                    selection_text: None,
+                    content: "Is this the final title?".to_string(),
+                    suggested_text: None,
+                    author: "reviewer".to_string(),
+                    resolved_by: None,
+                    resolved_at: None,
+                    resolution_note: None,
+                    created_at: "2024-01-15T10:00:00Z".to_string(),
+                    mentions: vec![],
+                    parent_comment_id: None,
+                }],
+            },
+        );
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1/suggestions/list", run),
+            DocArtifactListSuggestionsResponse {
+                artifact_id: "art-1".to_string(),
+                offset: 0,
+                has_more: false,
+                suggestions: vec![],
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone());
+
+        let result = server
+            .doc_artifact_review_context(Parameters(DocArtifactReviewContextRequest {
+                run_id: run.to_string(),
+                artifact_id: "art-1".to_string(),
+                offset: None,
+                limit: None,
+                source: None,
+                format: None,
+                continuation: None,
+            }))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("1 | # Spec"), "{}", text);
+        assert!(text.contains("Is this the final title?"));
+        assert!(text.contains("No suggestions found"));
+
+        let comments =
+            mock.requests_to(&format!("/api/runs/{}/artifacts/art-1/comments/list", run));
+        assert!(comments[0].contains("\"status\":\"open\""));
+        let suggestions = mock.requests_to(&format!(
+            "/api/runs/{}/artifacts/art-1/suggestions/list",
+            run
+        ));
+        assert!(suggestions[0].contains("\"status\":\"pending\""));
+
+        // One failed part fails the whole call
+        mock.set_disconnected(true);
+        let result = server
+            .doc_artifact_review_context(Parameters(DocArtifactReviewContextRequest {
+                run_id: run.to_string(),
+                artifact_id: "art-1".to_string(),
+                offset: None,
+                limit: None,
+                source: None,
+                format: None,
+                continuation: None,
+            }))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("Failed to load review context"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_edit_checks_offsets_against_the_anchored_text() {
+        use crate::ipc::messages::{
+            DocArtifactListCommentsResponse, DocArtifactReadResponse,
+            DocArtifactSuggestEditResponse,
+        };
+
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1", run),
+            DocArtifactReadResponse {
+                artifact_id: "art-1".to_string(),
+                filename: "spec.md".to_string(),
+                content: "# Spec\nThe cache TTL is 5m.".to_string(),
+                content_hash: "abc".to_string(),
+                total_lines: 2,
+                returned_lines: 2,
+                offset: 0,
+                has_more: false,
+                comments: None,
+            },
+        );
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1/comments/list", run),
+            DocArtifactListCommentsResponse {
+                artifact_id: "art-1".to_string(),
+                offset: 0,
+                has_more: false,
+                comments: vec![CommentDetail {
+                    id: "c-1".to_string(),
+                    comment_type: "suggestion".to_string(),
+                    status: "open".to_string(),
+                    selection_start: 17,
+                    selection_end: 20,
+                    selection_text: Some("TTL".to_string()),
+                    content: "Spell this out".to_string(),
+                    suggested_text: None,
+                    author: "reviewer".to_string(),
+                    resolved_by: None,
+                    resolved_at: None,
+                    resolution_note: None,
+                    created_at: "2024-01-15T10:00:00Z".to_string(),
+                    mentions: vec![],
+                    parent_comment_id: None,
+                }],
+            },
+        );
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1/suggestions", run),
+            DocArtifactSuggestEditResponse {
+                suggestion_id: "s-1".to_string(),
+                comment_id: "c-1".to_string(),
+                artifact_id: "art-1".to_string(),
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone()).with_config(Config {
+            default_source: Some("builder".to_string()),
+            ..Config::default()
+        });
+        let suggest = |start: i64, end: i64| DocArtifactSuggestEditRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            comment_id: "c-1".to_string(),
+            edit_type: EditType::ReplaceRange,
+            start_offset: Some(start),
+            end_offset: Some(end),
+            offset_unit: None,
+            suggested_text: "time-to-live".to_string(),
+            rationale: None,
+            source: None,
+            base_content_hash: None,
+            dry_run: None,
+            format: None,
+        };
+
+        // Offsets drifted by a line's worth of edits: refused before submitting
+        let result = server
+            .doc_artifact_suggest_edit(Parameters(suggest(10, 13)))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("Suggestion not created"), "{}", text);
+        assert!(
+            text.contains("Re-anchor the suggestion at offset 17"),
+            "{}",
+            text
+        );
+        assert!(mock
+            .requests_to(&format!("/api/runs/{}/artifacts/art-1/suggestions", run))
+            .is_empty());
+
+        let result = server
+            .doc_artifact_suggest_edit(Parameters(suggest(17, 20)))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("s-1"), "{}", text);
+        let sent = mock.requests_to(&format!("/api/runs/{}/artifacts/art-1/suggestions", run));
+        assert_eq!(sent.len(), 1);
+        // Recorded against the document the offsets were checked in
+        assert!(
+            sent[0].contains("\"baseContentHash\":\"abc\""),
+            "{}",
+            sent[0]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_comments_prefetched_on_read_answer_list_comments() {
+        use crate::ipc::messages::{
+            CommentDetail, DocArtifactListCommentsResponse, DocArtifactReadResponse,
+        };
+
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let list_endpoint = format!("/api/runs/{}/artifacts/art-1/comments/list", run);
+        let comment = |id: &str, status: &str| CommentDetail {
+            id: id.to_string(),
+            comment_type: "question".to_string(),
+            status: status.to_string(),
+            selection_start: 0,
+            selection_end: 6,
+            selection_text: None,
+            content: format!("Comment {}", id),
+            suggested_text: None,
+            author: "reviewer".to_string(),
+            resolved_by: None,
+            resolved_at: None,
+            resolution_note: None,
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            mentions: vec![],
+            parent_comment_id: None,
+        };
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1", run),
+            DocArtifactReadResponse {
+                artifact_id: "art-1".to_string(),
+                filename: "spec.md".to_string(),
+                content: "# Spec".to_string(),
+                content_hash: "abc".to_string(),
+                total_lines: 1,
+                returned_lines: 1,
+                offset: 0,
+                has_more: false,
+                comments: None,
+            },
+        );
+        mock.when_called(
+            &list_endpoint,
+            DocArtifactListCommentsResponse {
+                artifact_id: "art-1".to_string(),
+                offset: 0,
+                has_more: false,
+                comments: vec![
+                    CommentDetail {
+                        mentions: vec!["builder".to_string()],
+                        ..comment("c-open", "open")
+                    },
+                    comment("c-done", "resolved"),
+                ],
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone());
+        let read = |include_comments| DocArtifactReadRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            offset: None,
+            limit: None,
+            include_comments,
+            render_mode: None,
+            source: None,
+            format: None,
+            continuation: None,
+        };
+        let list = |status: Option<&str>, line_start| DocArtifactListCommentsRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            status: status.map(String::from),
+            comment_type: None,
+            line_start,
+            line_end: None,
+            mentioning: None,
+            author: None,
+            created_after: None,
+            has_reply: None,
+            format: None,
+            continuation: None,
+            report: None,
+            report_path: None,
+            offset: None,
+            limit: None,
+        };
+        let text = |result: CallToolResult| result.content[0].as_text().unwrap().text.clone();
+
+        server
+            .doc_artifact_read(Parameters(read(None)))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to(&list_endpoint).len(), 1);
+        assert!(mock.requests_to(&list_endpoint)[0].contains("\"status\":\"all\""));
+
+        // Served locally, filtered to open comments by default
+        let open = text(
+            server
+                .doc_artifact_list_comments(Parameters(list(None, None)))
+                .await
+                .unwrap(),
+        );
+        assert!(
+            open.contains("c-open") && !open.contains("c-done"),
+            "{}",
+            open
+        );
+        let all = text(
+            server
+                .doc_artifact_list_comments(Parameters(list(Some("all"), None)))
+                .await
+                .unwrap(),
+        );
+        assert!(all.contains("c-open") && all.contains("c-done"));
+        let mentioning = |role: &str| DocArtifactListCommentsRequest {
+            mentioning: Some(role.to_string()),
+            ..list(Some("all"), None)
+        };
+        let mine = text(
+            server
+                .doc_artifact_list_comments(Parameters(mentioning("builder")))
+                .await
+                .unwrap(),
+        );
+        assert!(mine.contains("**Mentions:** @builder") && !mine.contains("c-done"));
+        // "me" needs a known role
+        assert!(text(
+            server
+                .doc_artifact_list_comments(Parameters(mentioning("me")))
+                .await
+                .unwrap()
+        )
+        .starts_with("Missing `mentioning`"));
+        assert_eq!(mock.requests_to(&list_endpoint).len(), 1);
 
-        let result = server.ping().await.unwrap();
+        // Line ranges need the backend
+        server
+            .doc_artifact_list_comments(Parameters(list(None, Some(1))))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to(&list_endpoint).len(), 2);
 
-        assert_eq!(result.is_error, Some(false));
-        // Check that content indicates connection
-        assert_eq!(result.content.len(), 1);
+        // No prefetch when comments are explicitly excluded
+        server.comments.invalidate(run, "art-1");
+        server
+            .doc_artifact_read(Parameters(read(Some(false))))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to(&list_endpoint).len(), 2);
+        server
+            .doc_artifact_list_comments(Parameters(list(None, None)))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to(&list_endpoint).len(), 3);
     }
 
     #[tokio::test]
-    async fn test_ping_returns_helpful_error_when_backend_unavailable() {
+    async fn test_list_comments_filters_unanswered_questions_by_author() {
+        use crate::ipc::messages::{CommentDetail, DocArtifactListCommentsResponse};
+
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let comment = |id: &str, author: &str, created_at: &str| CommentDetail {
+            id: id.to_string(),
+            comment_type: "question".to_string(),
+            status: "open".to_string(),
+            selection_start: 0,
+            selection_end: 6,
+            selection_text: None,
+            content: format!("Comment {}", id),
+            suggested_text: None,
+            author: author.to_string(),
+            resolved_by: None,
+            resolved_at: None,
+            resolution_note: None,
+            created_at: created_at.to_string(),
+            mentions: vec![],
+            parent_comment_id: None,
+        };
         let mock = MockIpcClient::new();
-        mock.set_disconnected(true);
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1/comments/list", run),
+            DocArtifactListCommentsResponse {
+                artifact_id: "art-1".to_string(),
+                offset: 0,
+                has_more: false,
+                comments: vec![
+                    comment("q-answered", "reviewer", "2024-01-15T10:00:00Z"),
+                    comment("q-open", "reviewer", "2024-01-17T10:00:00Z"),
+                    comment("q-other", "strategist", "2024-01-15T10:00:00Z"),
+                    CommentDetail {
+                        parent_comment_id: Some("q-answered".to_string()),
+                        ..comment("reply", "builder", "2024-01-16T10:00:00Z")
+                    },
+                ],
+            },
+        );
         let server = HotwiredMcp::new(mock);
-
-        let result = server.ping().await.unwrap();
+        let list = |author: Option<&str>, created_after: Option<&str>, has_reply| {
+            DocArtifactListCommentsRequest {
+                run_id: run.to_string(),
+                artifact_id: "art-1".to_string(),
+                status: None,
+                comment_type: None,
+                line_start: None,
+                line_end: None,
+                mentioning: None,
+                author: author.map(String::from),
+                created_after: created_after.map(String::from),
+                has_reply,
+                format: None,
+                continuation: None,
+                report: None,
+                report_path: None,
+                offset: None,
+                limit: None,
+            }
+        };
+        let ids = |text: String| {
+            ["q-answered", "q-open", "q-other", "reply"]
+                .into_iter()
+                .filter(|id| text.contains(&format!("[{}]", id)))
+                .collect::<Vec<_>>()
+        };
+        let listed = |params| async {
+            let result = server
+                .doc_artifact_list_comments(Parameters(params))
+                .await
+                .unwrap();
+            ids(result.content[0].as_text().unwrap().text.clone())
+        };
 
         assert_eq!(
-            result.is_error,
-            Some(false),
-            "Ping MCP call should succeed even if backend unavailable"
+            listed(list(Some("reviewer"), None, Some(false))).await,
+            ["q-open"]
         );
-        // The content should indicate backend is unavailable
-        assert_eq!(result.content.len(), 1);
+        assert_eq!(
+            listed(list(Some("reviewer"), None, Some(true))).await,
+            ["q-answered"]
+        );
+        assert_eq!(
+            listed(list(None, Some("2024-01-16"), None)).await,
+            ["q-open", "reply"]
+        );
+        assert!(server
+            .doc_artifact_list_comments(Parameters(list(None, Some("last week"), None)))
+            .await
+            .unwrap()
+            .content[0]
+            .as_text()
+            .unwrap()
+            .text
+            .starts_with("Invalid arguments: created_after"));
     }
 
     #[tokio::test]
-    async fn test_get_protocol_returns_formatted_response() {
+    async fn test_list_comments_pages_with_offset_and_limit() {
+        use crate::ipc::messages::{CommentDetail, DocArtifactListCommentsResponse, ReportFormat};
+
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let comment = |id: &str| CommentDetail {
+            id: id.to_string(),
+            comment_type: "comment".to_string(),
+            status: "open".to_string(),
+            selection_start: 0,
+            selection_end: 6,
+            selection_text: None,
+            content: format!("Comment {}", id),
+            suggested_text: None,
+            author: "reviewer".to_string(),
+            resolved_by: None,
+            resolved_at: None,
+            resolution_note: None,
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            mentions: vec![],
+            parent_comment_id: None,
+        };
         let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1/comments/list", run),
+            DocArtifactListCommentsResponse {
+                artifact_id: "art-1".to_string(),
+                offset: 0,
+                has_more: false,
+                comments: vec![comment("c-1"), comment("c-2"), comment("c-3")],
+            },
+        );
         let server = HotwiredMcp::new(mock);
-
-        let params = GetProtocolRequest {
-            run_id: "test-run".to_string(),
-            agent_role: "strategist".to_string(),
+        let list = |offset, limit, report| DocArtifactListCommentsRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            status: None,
+            comment_type: None,
+            line_start: None,
+            line_end: None,
+            mentioning: None,
+            author: None,
+            created_after: None,
+            has_reply: None,
+            format: None,
+            continuation: None,
+            report,
+            report_path: None,
+            offset,
+            limit,
         };
+        let text = |result: CallToolResult| result.content[0].as_text().unwrap().text.clone();
 
-        let result = server.get_protocol(Parameters(params)).await.unwrap();
+        let first = text(
+            server
+                .doc_artifact_list_comments(Parameters(list(None, Some(2), None)))
+                .await
+                .unwrap(),
+        );
+        assert!(
+            first.contains("**Total:** 2 comments (offset: 0, has_more: true)"),
+            "{}",
+            first
+        );
+        assert!(first.contains("[c-2]") && !first.contains("[c-3]"));
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        let last = text(
+            server
+                .doc_artifact_list_comments(Parameters(list(Some(2), Some(2), None)))
+                .await
+                .unwrap(),
+        );
+        assert!(
+            last.contains("**Total:** 1 comments (offset: 2, has_more: false)"),
+            "{}",
+            last
+        );
+        assert!(last.contains("[c-3]") && !last.contains("[c-2]"));
+
+        // Reports export everything unless a limit is given
+        let report = text(
+            server
+                .doc_artifact_list_comments(Parameters(list(None, None, Some(ReportFormat::Jsonl))))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(report.lines().count(), 3, "{}", report);
     }
 
     #[tokio::test]
-    async fn test_get_run_status_returns_formatted_response() {
-        let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
+    async fn test_read_reports_a_receipt_for_the_reader() {
+        use crate::ipc::messages::{DocArtifactReadResponse, ReportArtifactReadResponse};
 
-        let params = GetRunStatusRequest {
-            run_id: "test-run".to_string(),
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let receipts = format!("/api/runs/{}/artifacts/art-1/readers/report", run);
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1", run),
+            DocArtifactReadResponse {
+                artifact_id: "art-1".to_string(),
+                filename: "spec.md".to_string(),
+                content: "# Spec".to_string(),
+                content_hash: "abc".to_string(),
+                total_lines: 1,
+                returned_lines: 1,
+                offset: 0,
+                has_more: false,
+                comments: None,
+            },
+        );
+        mock.when_called(&receipts, ReportArtifactReadResponse { success: true });
+        let read = |source: Option<&str>| DocArtifactReadRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            offset: None,
+            limit: None,
+            include_comments: Some(false),
+            render_mode: None,
+            source: source.map(String::from),
+            format: None,
+            continuation: None,
         };
 
-        let result = server.get_run_status(Parameters(params)).await.unwrap();
+        // No role to record it under
+        let server = HotwiredMcp::new(mock.clone());
+        server
+            .doc_artifact_read(Parameters(read(None)))
+            .await
+            .unwrap();
+        assert!(mock.requests_to(&receipts).is_empty());
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        server
+            .doc_artifact_read(Parameters(read(Some("reviewer"))))
+            .await
+            .unwrap();
+        assert!(mock.requests_to(&receipts)[0]
+            .contains(r#""contentHash":"abc","source":"reviewer","readToEnd":true"#));
     }
 
     #[tokio::test]
-    async fn test_report_status_succeeds() {
-        let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
+    async fn test_byte_offsets_are_converted_against_current_content() {
+        use crate::ipc::messages::{
+            CommentType, DocArtifactAddCommentResponse, DocArtifactReadResponse,
+        };
 
-        let params = ReportStatusRequest {
-            run_id: "test-run".to_string(),
-            status: "working".to_string(),
-            message: "Implementing feature".to_string(),
-            source: "strategist".to_string(),
-            agent_status: None,
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1", run),
+            DocArtifactReadResponse {
+                artifact_id: "art-1".to_string(),
+                filename: "spec.md".to_string(),
+                content: "Café 🌍 menu".to_string(),
+                content_hash: "abc".to_string(),
+                total_lines: 1,
+                returned_lines: 1,
+                offset: 0,
+                has_more: false,
+                comments: None,
+            },
+        );
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1/comments/add", run),
+            DocArtifactAddCommentResponse {
+                comment_id: "c-1".to_string(),
+                artifact_id: "art-1".to_string(),
+                selection_text: "🌍".to_string(),
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone());
+        let params = |start, end, unit| DocArtifactAddCommentRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            comment_type: CommentType::Comment,
+            selection_start: start,
+            selection_end: end,
+            offset_unit: unit,
+            content: "Nice".to_string(),
+            suggested_text: None,
+            author: Some("reviewer".to_string()),
+            parent_comment_id: None,
+            mentions: None,
+            format: None,
         };
 
-        let result = server.report_status(Parameters(params)).await.unwrap();
+        // "🌍" is bytes 6..10, characters 5..6
+        server
+            .doc_artifact_add_comment(Parameters(params(6, 10, Some(OffsetUnit::Byte))))
+            .await
+            .unwrap();
+        let sent = mock.requests_to(&format!("/api/runs/{}/artifacts/art-1/comments/add", run));
+        assert!(sent[0].contains("\"selectionStart\":5"));
+        assert!(sent[0].contains("\"selectionEnd\":6"));
+        assert!(!sent[0].contains("offsetUnit"));
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        // Byte 4 is inside "é"; character 40 is past the end
+        for (start, end, unit) in [(4, 6, Some(OffsetUnit::Byte)), (0, 40, None)] {
+            let result = server
+                .doc_artifact_add_comment(Parameters(params(start, end, unit)))
+                .await
+                .unwrap();
+            let text = result.content[0].as_text().unwrap().text.clone();
+            assert!(text.starts_with("Invalid offset: selection_"), "{}", text);
+        }
+        assert_eq!(
+            mock.requests_to(&format!("/api/runs/{}/artifacts/art-1/comments/add", run))
+                .len(),
+            1
+        );
     }
 
     #[tokio::test]
-    async fn test_send_message_returns_event_id() {
-        let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
+    async fn test_conflicting_append_is_retried_with_latest_hash() {
+        use crate::ipc::messages::{DocArtifactEditResponse, EditConflict};
 
-        let params = SendMessageRequest {
-            run_id: "test-run".to_string(),
-            content: "Test message".to_string(),
-            source: "strategist".to_string(),
-            target: None,
-            agent_status: None,
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1", run),
+            DocArtifactReadResponse {
+                artifact_id: "art-1".to_string(),
+                filename: "spec.md".to_string(),
+                content: "line".to_string(),
+                content_hash: "latest".to_string(),
+                total_lines: 1,
+                returned_lines: 1,
+                offset: 0,
+                has_more: false,
+                comments: None,
+            },
+        );
+        // The mock always conflicts, so every retry is used up
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1/edit", run),
+            DocArtifactEditResponse {
+                success: false,
+                artifact_id: "art-1".to_string(),
+                new_content_hash: String::new(),
+                edit_id: String::new(),
+                affected_comments: vec![],
+                conflict: Some(EditConflict {
+                    expected_hash: "stale".to_string(),
+                    actual_hash: "latest".to_string(),
+                    message: "Hash mismatch".to_string(),
+                }),
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone());
+        let params = |edit_type, start_offset, end_offset| DocArtifactEditRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            edit_type,
+            content_hash: "stale".to_string(),
+            new_content: "more".to_string(),
+            start_offset,
+            end_offset,
+            insert_offset: None,
+            offset_unit: None,
+            edit_reason: None,
+            source: Some("builder".to_string()),
+            dry_run: None,
+            format: None,
         };
+        let edits = || mock.requests_to(&format!("/api/runs/{}/artifacts/art-1/edit", run));
 
-        let result = server.send_message(Parameters(params)).await.unwrap();
+        let result = server
+            .doc_artifact_edit(Parameters(params(EditType::Append, None, None)))
+            .await
+            .unwrap();
+        let sent = edits();
+        assert_eq!(sent.len(), 1 + artifacts::END_EDIT_RETRIES);
+        assert!(sent[0].contains("\"contentHash\":\"stale\""));
+        assert!(sent[1].contains("\"contentHash\":\"latest\""));
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("CONFLICT DETECTED"));
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        // Range edits can overlap concurrent changes and are never retried
+        server
+            .doc_artifact_edit(Parameters(params(EditType::ReplaceRange, Some(0), Some(2))))
+            .await
+            .unwrap();
+        assert_eq!(edits().len(), 2 + artifacts::END_EDIT_RETRIES);
     }
 
     #[tokio::test]
-    async fn test_task_complete_succeeds() {
+    async fn test_dry_run_edit_previews_without_sending() {
+        let run = "123e4567-e89b-12d3-a456-426614174000";
         let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
-
-        let params = TaskCompleteRequest {
-            run_id: "test-run".to_string(),
-            task_description: "Implemented feature X".to_string(),
-            source: "builder".to_string(),
-            outcome: None,
-            agent_status: None,
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/art-1", run),
+            DocArtifactReadResponse {
+                artifact_id: "art-1".to_string(),
+                filename: "spec.md".to_string(),
+                content: "Cache TTL is 5m.".to_string(),
+                content_hash: "latest".to_string(),
+                total_lines: 1,
+                returned_lines: 1,
+                offset: 0,
+                has_more: false,
+                comments: None,
+            },
+        );
+        let server = HotwiredMcp::new(mock.clone());
+        let params = DocArtifactEditRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            edit_type: EditType::ReplaceRange,
+            content_hash: "stale".to_string(),
+            new_content: "10m".to_string(),
+            start_offset: Some(13),
+            end_offset: Some(15),
+            insert_offset: None,
+            offset_unit: None,
+            edit_reason: None,
+            source: Some("builder".to_string()),
+            dry_run: Some(true),
+            format: None,
         };
 
-        let result = server.task_complete(Parameters(params)).await.unwrap();
+        let result = server.doc_artifact_edit(Parameters(params)).await.unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("DRY RUN"), "{}", text);
+        assert!(text.contains("[-5m.-]{+10m.+}"), "{}", text);
+        assert!(text.contains("**Hash mismatch:** the document is now at `latest`"));
+        assert!(mock
+            .requests_to(&format!("/api/runs/{}/artifacts/art-1/edit", run))
+            .is_empty());
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        let suggest = DocArtifactSuggestEditRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            comment_id: "c-1".to_string(),
+            edit_type: EditType::Insert,
+            start_offset: Some(99),
+            end_offset: None,
+            offset_unit: None,
+            suggested_text: " Tunable.".to_string(),
+            rationale: None,
+            source: Some("builder".to_string()),
+            base_content_hash: None,
+            dry_run: Some(true),
+            format: None,
+        };
+        let result = server
+            .doc_artifact_suggest_edit(Parameters(suggest))
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        assert!(text.starts_with("Invalid offset: start_offset"), "{}", text);
+        assert!(mock
+            .requests_to(&format!("/api/runs/{}/artifacts/art-1/suggestions", run))
+            .is_empty());
     }
 
     #[tokio::test]
-    async fn test_report_impediment_succeeds() {
+    async fn test_full_replace_is_backed_up_and_restorable() {
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let read = format!("/api/runs/{}/artifacts/art-1", run);
+        let edit = format!("/api/runs/{}/artifacts/art-1/edit", run);
+        let document = |content: &str, hash: &str| DocArtifactReadResponse {
+            artifact_id: "art-1".to_string(),
+            filename: "spec.md".to_string(),
+            content: content.to_string(),
+            content_hash: hash.to_string(),
+            total_lines: 1,
+            returned_lines: 1,
+            offset: 0,
+            has_more: false,
+            comments: None,
+        };
         let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
-
-        let params = ReportImpedimentRequest {
-            run_id: "test-run".to_string(),
-            impediment_type: "missing_information".to_string(),
-            description: "Need clarification on requirements".to_string(),
-            source: "strategist".to_string(),
-            context: None,
-            suggestion: None,
-            agent_status: None,
-            response_format: None,
+        mock.when_called(&read, document("Original spec.", "h1"));
+        mock.when_called(
+            &edit,
+            serde_json::json!({
+                "success": true,
+                "artifactId": "art-1",
+                "newContentHash": "h2",
+                "editId": "edit-1"
+            }),
+        );
+        let server = HotwiredMcp::new(mock.clone());
+        let params = DocArtifactEditRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            edit_type: EditType::FullReplace,
+            content_hash: "h1".to_string(),
+            new_content: "Wiped".to_string(),
+            start_offset: None,
+            end_offset: None,
+            insert_offset: None,
+            offset_unit: None,
+            edit_reason: None,
+            source: Some("builder".to_string()),
+            dry_run: None,
+            format: None,
         };
+        server.doc_artifact_edit(Parameters(params)).await.unwrap();
+        mock.when_called(&read, document("Wiped", "h2"));
 
-        let result = server.report_impediment(Parameters(params)).await.unwrap();
+        let restore = |backup_id: Option<String>| RestoreBackupRequest {
+            run_id: run.to_string(),
+            artifact_id: "art-1".to_string(),
+            backup_id,
+            source: Some("builder".to_string()),
+            format: None,
+        };
+        let listed = server
+            .restore_backup(Parameters(restore(None)))
+            .await
+            .unwrap();
+        let text = listed.content[0].as_text().unwrap().text.clone();
+        assert!(text.contains("Before full_replace by builder"), "{}", text);
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        let backup = &server.backups.list(run, "art-1").unwrap()[0];
+        server
+            .restore_backup(Parameters(restore(Some(backup.id.clone()))))
+            .await
+            .unwrap();
+        let sent = mock.requests_to(&edit);
+        assert_eq!(sent.len(), 2);
+        assert!(sent[1].contains("\"newContent\":\"Original spec.\""));
+        assert!(sent[1].contains("\"contentHash\":\"h2\""));
+        // The restore itself can be undone
+        let backups = server.backups.list(run, "art-1").unwrap();
+        assert_eq!(backups.len(), 2);
+        assert_eq!(backups[0].content, "Wiped");
     }
 
     #[tokio::test]
-    async fn test_request_input_succeeds() {
+    async fn test_gated_calls_wait_for_approval() {
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let input = format!("/api/runs/{}/input", run);
+        let status = format!("/api/runs/{}/input/in-1", run);
         let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
+        mock.when_called(
+            &input,
+            serde_json::json!({"success": true, "requestId": "in-1"}),
+        );
+        mock.when_called(
+            &status,
+            serde_json::json!({"requestId": "in-1", "answered": false}),
+        );
+        let server = HotwiredMcp::new(mock.clone()).with_config(Config {
+            approvals: crate::approval::ApprovalConfig {
+                tools: vec!["request_end_run".to_string()],
+                timeout_secs: Some(0),
+            },
+            ..Config::default()
+        });
+        let arguments = serde_json::json!({
+            "runId": run,
+            "reason": "completed",
+            "description": "All tasks finished",
+            "source": "strategist"
+        });
+        let arguments = arguments.as_object();
+        let text = |result: CallToolResult| result.content[0].as_text().unwrap().text.clone();
 
-        let params = RequestInputRequest {
-            run_id: "test-run".to_string(),
-            question: "Which approach should we use?".to_string(),
-            source: "strategist".to_string(),
-            context: None,
-            options: None,
-        };
+        assert!(server
+            .approval_refusal("send_message", arguments)
+            .await
+            .is_none());
+        let waiting = text(
+            server
+                .approval_refusal("request_end_run", arguments)
+                .await
+                .unwrap(),
+        );
+        assert!(waiting.contains("waiting for human approval (request in-1)"));
+        // Asking again waits on the same request
+        server.approval_refusal("request_end_run", arguments).await;
+        let asked = mock.requests_to(&input);
+        assert_eq!(asked.len(), 1);
+        assert!(asked[0].contains("Approve request_end_run by strategist?"));
+        assert!(asked[0].contains("\"options\":[\"Approve\",\"Deny\"]"));
 
-        let result = server.request_input(Parameters(params)).await.unwrap();
+        mock.when_called(
+            &status,
+            serde_json::json!({"requestId": "in-1", "answered": true, "answer": "Deny", "answeredBy": "human"}),
+        );
+        let denied = text(
+            server
+                .approval_refusal("request_end_run", arguments)
+                .await
+                .unwrap(),
+        );
+        assert!(denied.starts_with("✗ request_end_run was not approved by human: Deny."));
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        mock.when_called(
+            &status,
+            serde_json::json!({"requestId": "in-1", "answered": true, "answer": "Approve"}),
+        );
+        assert!(server
+            .approval_refusal("request_end_run", arguments)
+            .await
+            .is_none());
+        assert_eq!(mock.requests_to(&input).len(), 2);
     }
 
     #[tokio::test]
-    async fn test_handoff_succeeds() {
-        let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
-
-        let params = HandoffRequest {
-            run_id: "test-run".to_string(),
-            to: "builder".to_string(),
-            summary: "Ready for implementation".to_string(),
-            source: "strategist".to_string(),
-            details: None,
-            artifacts: None,
+    async fn test_mirror_is_not_started_when_full_replace_edits_are_off_or_gated() {
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let mirrored = |config: Config| {
+            let server = HotwiredMcp::new(MockIpcClient::new()).with_config(Config {
+                mirror_artifacts_secs: Some(30),
+                ..config
+            });
+            server.spawn_mirror(run, "builder");
+            // The task has not run yet on this thread; stop it before it does
+            let task = server.mirror.lock().unwrap().take();
+            task.map(|(_, task)| task.abort()).is_some()
         };
 
-        let result = server.handoff(Parameters(params)).await.unwrap();
-
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        assert!(mirrored(Config::default()));
+        assert!(!mirrored(Config {
+            tools: crate::config::ToolFilterConfig {
+                allow: vec![],
+                deny: vec!["doc_artifact_*".to_string()],
+            },
+            ..Config::default()
+        }));
+        assert!(!mirrored(Config {
+            approvals: crate::approval::ApprovalConfig {
+                tools: vec![crate::approval::FULL_REPLACE.to_string()],
+                timeout_secs: None,
+            },
+            ..Config::default()
+        }));
     }
 
     #[tokio::test]
-    async fn test_request_end_run_returns_request_id() {
+    async fn test_gated_calls_that_would_be_refused_are_not_put_to_the_human() {
+        let run = "123e4567-e89b-12d3-a456-426614174000";
+        let input = format!("/api/runs/{}/input", run);
         let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
+        mock.when_called(
+            &input,
+            serde_json::json!({"success": true, "requestId": "in-1"}),
+        );
+        let server = HotwiredMcp::new(mock.clone()).with_config(Config {
+            approvals: crate::approval::ApprovalConfig {
+                tools: vec!["request_end_run".to_string()],
+                timeout_secs: Some(0),
+            },
+            ..Config::default()
+        });
+        let text = |result: CallToolResult| result.content[0].as_text().unwrap().text.clone();
 
-        let params = RequestEndRunRequest {
-            run_id: "test-run".to_string(),
-            reason: "completed".to_string(),
-            description: "All tasks finished".to_string(),
-            source: "strategist".to_string(),
-            suggested_follow_up: None,
-        };
+        let invalid = serde_json::json!({
+            "runId": "not-a-run",
+            "reason": "completed",
+            "description": "All tasks finished",
+            "source": "builder"
+        });
+        let refused = server
+            .approval_refusal("request_end_run", invalid.as_object())
+            .await
+            .unwrap();
+        assert!(text(refused).starts_with("Invalid arguments:"));
 
-        let result = server.request_end_run(Parameters(params)).await.unwrap();
+        server.role_capabilities.record(
+            run,
+            "builder",
+            Some(RoleCapabilities {
+                can_end_run: false,
+                ..RoleCapabilities::default()
+            }),
+        );
+        let unpermitted = serde_json::json!({
+            "runId": run,
+            "reason": "completed",
+            "description": "All tasks finished",
+            "source": "builder"
+        });
+        let refused = server
+            .approval_refusal("request_end_run", unpermitted.as_object())
+            .await
+            .unwrap();
+        assert_eq!(refused.is_error, Some(true));
+        assert!(text(refused).contains("role 'builder' is not permitted to use request_end_run"));
+        assert!(mock.requests_to(&input).is_empty());
+    }
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+    #[test]
+    fn test_rate_limited_calls_say_when_to_retry() {
+        use crate::config::{RateLimit, RateLimitConfig};
+
+        let server = HotwiredMcp::new(MockIpcClient::new()).with_config(Config {
+            rate_limits: RateLimitConfig {
+                mutating: RateLimit {
+                    per_minute: 6,
+                    burst: Some(2),
+                },
+                ..RateLimitConfig::default()
+            },
+            ..Config::default()
+        });
+
+        assert!(server.rate_limited("doc_artifact_edit").is_none());
+        assert!(server.rate_limited("report_status").is_none());
+        let limited = server.rate_limited("doc_artifact_edit").unwrap();
+        assert_eq!(limited.is_error, Some(true));
+        let text = limited.content[0].as_text().unwrap().text.clone();
+        assert!(
+            text.starts_with(
+                "Rate limited: more than 6 mutating tool calls per minute. Retry after 10s."
+            ),
+            "{}",
+            text
+        );
+        assert_eq!(
+            limited.structured_content.unwrap()["retryAfterSecs"],
+            serde_json::json!(10)
+        );
+        // Read tools draw from their own bucket
+        assert!(server.rate_limited("doc_artifact_read").is_none());
     }
 
     #[tokio::test]
-    async fn test_respond_to_end_request_succeeds() {
-        let mock = MockIpcClient::new();
-        let server = HotwiredMcp::new(mock);
+    async fn test_repeated_status_report_is_not_resent() {
+        use crate::ipc::messages::ReportStatusResponse;
 
-        let params = RespondToEndRequestRequest {
-            run_id: "test-run".to_string(),
-            request_id: "req-123".to_string(),
-            response: "agree".to_string(),
-            source: "builder".to_string(),
-            reason: None,
-        };
+        let endpoint = "/api/runs/123e4567-e89b-12d3-a456-426614174000/report-status";
+        let mock = MockIpcClient::new();
+        mock.when_called(endpoint, ReportStatusResponse { success: true });
+        let server = HotwiredMcp::new(mock.clone());
 
-        let result = server
-            .respond_to_end_request(Parameters(params))
-            .await
-            .unwrap();
+        for _ in 0..3 {
+            let result = server
+                .report_status(Parameters(report_status_params()))
+                .await
+                .unwrap();
+            let text = result.content[0].as_text().unwrap().text.clone();
+            assert_eq!(text, "Status reported successfully");
+        }
+        assert_eq!(mock.requests_to(endpoint).len(), 1);
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        let params = ReportStatusRequest {
+            message: "Writing tests".to_string(),
+            ..report_status_params()
+        };
+        server.report_status(Parameters(params)).await.unwrap();
+        assert_eq!(mock.requests_to(endpoint).len(), 2);
     }
 
     #[tokio::test]
-    async fn test_resolve_impediment_succeeds() {
-        use crate::ipc::messages::ResolveImpedimentResponse;
+    async fn test_ended_run_guard_refuses_mutating_tools() {
+        use crate::ipc::messages::{GetRunStatusResponse, ReportStatusResponse};
 
+        let run = "/api/runs/123e4567-e89b-12d3-a456-426614174000";
         let mock = MockIpcClient::new();
         mock.when_called(
-            "/api/resolve-impediment",
-            ResolveImpedimentResponse {
-                success: true,
-                error: None,
+            &format!("{}/status", run),
+            GetRunStatusResponse {
+                run_id: "123e4567-e89b-12d3-a456-426614174000".into(),
+                status: "completed".into(),
+                phase: "done".into(),
+                template_name: "Plan → Build".into(),
+                has_protocol: true,
+                connected_agents: vec![],
+                protocol_version: None,
             },
         );
-        let server = HotwiredMcp::new(mock);
+        mock.when_called(
+            &format!("{}/report-status", run),
+            ReportStatusResponse { success: true },
+        );
 
-        let params = ResolveImpedimentRequest {
-            run_id: "test-run".to_string(),
-            impediment_id: 42,
-            response: serde_json::json!({"database": "postgresql"}),
-            source: "strategist".to_string(),
-            rationale: Some("PostgreSQL better suits the production requirements".to_string()),
+        // Without the guard configured, calls go straight to the backend
+        let server = HotwiredMcp::new(mock.clone());
+        server
+            .report_status(Parameters(report_status_params()))
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to(&format!("{}/status", run)).len(), 0);
+        assert_eq!(mock.requests_to(&format!("{}/report-status", run)).len(), 1);
+
+        let config = Config {
+            run_guard_ttl_secs: Some(30),
+            status_dedup_secs: Some(0),
+            ..Config::default()
         };
+        let server = HotwiredMcp::new(mock.clone()).with_config(config);
+        for _ in 0..2 {
+            let result = server
+                .report_status(Parameters(report_status_params()))
+                .await
+                .unwrap();
+            let text = result.content[0].as_text().unwrap().text.clone();
+            assert!(text.contains("has ended (status: completed)"));
+        }
+        // The status is cached and the mutation never reaches the backend
+        assert_eq!(mock.requests_to(&format!("{}/status", run)).len(), 1);
+        assert_eq!(mock.requests_to(&format!("{}/report-status", run)).len(), 1);
+    }
 
-        let result = server.resolve_impediment(Parameters(params)).await.unwrap();
+    #[test]
+    fn test_tool_schemas_document_examples_defaults_and_ranges() {
+        let server = HotwiredMcp::new(MockIpcClient::new());
+        let tool = server
+            .tool_router
+            .list_all()
+            .into_iter()
+            .find(|tool| tool.name == "doc_artifact_read")
+            .unwrap();
+        let properties = &tool.input_schema["properties"];
 
-        assert_eq!(result.is_error, Some(false));
-        assert_eq!(result.content.len(), 1);
+        assert_eq!(
+            properties["runId"]["examples"][0],
+            "123e4567-e89b-12d3-a456-426614174000"
+        );
+        assert!(properties["runId"]["pattern"].is_string());
+        assert_eq!(properties["offset"]["minimum"], 0);
+        assert_eq!(properties["offset"]["default"], 0);
+        assert_eq!(properties["limit"]["minimum"], 1);
+        assert_eq!(properties["limit"]["default"], 500);
+        assert_eq!(properties["renderMode"]["default"], "plain");
     }
 }
@@ -0,0 +1,213 @@
+//! Identifies the terminal session an agent runs in.
+//!
+//! The backend finds an agent again by its session name, so each
+//! [`SessionProvider`] knows one way of naming it: Zellij and tmux expose the
+//! enclosing session to child processes, and outside a multiplexer the agent's
+//! TTY and process id stand in for a session.
+
+use crate::ipc::messages::Multiplexer;
+use std::path::Path;
+use std::process::Command;
+
+/// Environment variable Zellij sets to the name of the enclosing session.
+pub const ZELLIJ_SESSION_ENV: &str = "ZELLIJ_SESSION_NAME";
+
+/// Environment variable tmux sets (socket path, server pid, session index).
+pub const TMUX_ENV: &str = "TMUX";
+
+/// Environment variable tmux sets to the pane the process was started in.
+pub const TMUX_PANE_ENV: &str = "TMUX_PANE";
+
+/// Environment variable Claude Code sets to the project the agent was started in.
+pub const PROJECT_ENV: &str = "CLAUDE_PROJECT_DIR";
+
+/// The session this server runs in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalSession {
+    pub multiplexer: Multiplexer,
+    pub name: String,
+    pub project_dir: String,
+}
+
+/// One way of naming the enclosing terminal session.
+pub trait SessionProvider {
+    fn multiplexer(&self) -> Multiplexer;
+
+    /// The session name, or `None` when not running inside this kind of session.
+    fn session_name(&self, lookup: &dyn Fn(&str) -> Option<String>) -> Option<String>;
+}
+
+/// Reads the session name Zellij exports.
+pub struct Zellij;
+
+impl SessionProvider for Zellij {
+    fn multiplexer(&self) -> Multiplexer {
+        Multiplexer::Zellij
+    }
+
+    fn session_name(&self, lookup: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+        lookup(ZELLIJ_SESSION_ENV).filter(|s| !s.is_empty())
+    }
+}
+
+/// Asks the tmux server for the name of the session owning this pane.
+pub struct Tmux;
+
+impl SessionProvider for Tmux {
+    fn multiplexer(&self) -> Multiplexer {
+        Multiplexer::Tmux
+    }
+
+    fn session_name(&self, lookup: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+        lookup(TMUX_ENV).filter(|s| !s.is_empty())?;
+        let mut command = Command::new("tmux");
+        command.args(["display-message", "-p"]);
+        if let Some(pane) = lookup(TMUX_PANE_ENV).filter(|p| !p.is_empty()) {
+            command.args(["-t", &pane]);
+        }
+        command_output(command.arg("#S"))
+    }
+}
+
+/// Names the session after the agent process (this server's parent) and its TTY.
+/// Always available, so only used when asked for.
+pub struct Tty;
+
+impl SessionProvider for Tty {
+    fn multiplexer(&self) -> Multiplexer {
+        Multiplexer::Tty
+    }
+
+    fn session_name(&self, _lookup: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+        let pid = std::os::unix::process::parent_id();
+        let tty = command_output(Command::new("ps").args(["-o", "tty=", "-p", &pid.to_string()]));
+        Some(tty_session_name(tty.as_deref(), pid))
+    }
+}
+
+/// The provider for `multiplexer`.
+pub fn provider(multiplexer: Multiplexer) -> &'static dyn SessionProvider {
+    match multiplexer {
+        Multiplexer::Zellij => &Zellij,
+        Multiplexer::Tmux => &Tmux,
+        Multiplexer::Tty => &Tty,
+    }
+}
+
+/// The session this process runs in. With `preferred` unset, Zellij and then
+/// tmux are tried. The project comes from `CLAUDE_PROJECT_DIR`, falling back
+/// to `cwd`.
+pub fn detect(
+    preferred: Option<Multiplexer>,
+    lookup: impl Fn(&str) -> Option<String>,
+    cwd: Option<&Path>,
+) -> Option<TerminalSession> {
+    let candidates = match preferred {
+        Some(multiplexer) => vec![multiplexer],
+        None => vec![Multiplexer::Zellij, Multiplexer::Tmux],
+    };
+    let (multiplexer, name) = candidates
+        .into_iter()
+        .find_map(|m| provider(m).session_name(&lookup).map(|name| (m, name)))?;
+    let project_dir = lookup(PROJECT_ENV)
+        .filter(|p| !p.is_empty())
+        .or_else(|| cwd.map(|p| p.to_string_lossy().into_owned()))?;
+    Some(TerminalSession {
+        multiplexer,
+        name,
+        project_dir,
+    })
+}
+
+/// `tty-<tty>-<pid>`, or `pid-<pid>` when the process has no controlling terminal.
+fn tty_session_name(tty: Option<&str>, pid: u32) -> String {
+    match tty
+        .map(str::trim)
+        .filter(|t| !t.is_empty() && !t.starts_with('?'))
+    {
+        Some(tty) => format!("tty-{}-{}", tty.replace('/', "_"), pid),
+        None => format!("pid-{}", pid),
+    }
+}
+
+/// Trimmed stdout of a successful, non-empty run of `command`.
+fn command_output(command: &mut Command) -> Option<String> {
+    let output = command.output().ok().filter(|o| o.status.success())?;
+    let text = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |key: &str| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    fn zellij(name: &str, project: &str) -> Option<TerminalSession> {
+        Some(TerminalSession {
+            multiplexer: Multiplexer::Zellij,
+            name: name.to_string(),
+            project_dir: project.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_detect_zellij_session() {
+        let cwd = Path::new("/cwd");
+
+        assert_eq!(detect(None, env(&[]), Some(cwd)), None);
+        assert_eq!(
+            detect(None, env(&[(ZELLIJ_SESSION_ENV, "hw-1")]), Some(cwd)),
+            zellij("hw-1", "/cwd")
+        );
+        assert_eq!(
+            detect(
+                None,
+                env(&[(ZELLIJ_SESSION_ENV, "hw-1"), (PROJECT_ENV, "/project")]),
+                Some(cwd)
+            ),
+            zellij("hw-1", "/project")
+        );
+        assert_eq!(
+            detect(None, env(&[(ZELLIJ_SESSION_ENV, "hw-1")]), None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_preferred_provider_is_the_only_one_tried() {
+        let cwd = Path::new("/cwd");
+        let vars = env(&[(ZELLIJ_SESSION_ENV, "hw-1")]);
+
+        // Not inside tmux, so nothing to ask the tmux server
+        assert_eq!(detect(Some(Multiplexer::Tmux), &vars, Some(cwd)), None);
+        assert_eq!(
+            detect(Some(Multiplexer::Zellij), &vars, Some(cwd)),
+            zellij("hw-1", "/cwd")
+        );
+    }
+
+    #[test]
+    fn test_tty_session_name() {
+        assert_eq!(tty_session_name(Some("pts/3\n"), 42), "tty-pts_3-42");
+        assert_eq!(tty_session_name(Some("ttys001"), 42), "tty-ttys001-42");
+        assert_eq!(tty_session_name(Some("?"), 42), "pid-42");
+        assert_eq!(tty_session_name(Some("??"), 42), "pid-42");
+        assert_eq!(tty_session_name(None, 42), "pid-42");
+    }
+
+    #[test]
+    fn test_tty_provider_always_names_a_session() {
+        let session = detect(Some(Multiplexer::Tty), env(&[]), Some(Path::new("/cwd"))).unwrap();
+        assert_eq!(session.multiplexer, Multiplexer::Tty);
+        assert!(session
+            .name
+            .ends_with(&std::os::unix::process::parent_id().to_string()));
+    }
+}
@@ -0,0 +1,166 @@
+//! Run context that survives MCP server restarts.
+//!
+//! The current run, role, and the content hashes of documents last read are
+//! kept per Zellij session in `~/.hotwired/state/<session>.json` (below the
+//! state dir, see [`crate::paths`]), so a crash
+//! or a Claude Code restart mid-run does not lose track of where the agent was.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// What the agent was working on.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct RunContext {
+    pub run_id: Option<String>,
+    pub role: Option<String>,
+    /// artifact_id -> content hash of the version last read or written
+    pub last_read_hashes: HashMap<String, String>,
+}
+
+impl RunContext {
+    /// Switch to `run_id` as `role`. Hashes from a different run are dropped.
+    pub fn attach(&mut self, run_id: &str, role: &str) {
+        if self.run_id.as_deref() != Some(run_id) {
+            self.last_read_hashes.clear();
+        }
+        self.run_id = Some(run_id.to_string());
+        self.role = Some(role.to_string());
+    }
+
+    /// Remember the hash of a document in `run_id`; ignored for other runs.
+    pub fn record_hash(&mut self, run_id: &str, artifact_id: &str, hash: &str) {
+        if self.run_id.as_deref() == Some(run_id) {
+            self.last_read_hashes
+                .insert(artifact_id.to_string(), hash.to_string());
+        }
+    }
+}
+
+/// The run context, optionally backed by a file.
+#[derive(Default)]
+pub struct StateStore {
+    path: Option<PathBuf>,
+    context: Mutex<RunContext>,
+}
+
+impl StateStore {
+    /// State file for `session` under `state_dir`. Characters that are not safe
+    /// in file names are replaced.
+    pub fn path_for(state_dir: &Path, session: &str) -> PathBuf {
+        let name: String = session
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        state_dir.join(format!("{}.json", name.trim_start_matches('.')))
+    }
+
+    /// Load the context saved at `path`, starting empty if there is none or it
+    /// cannot be read. Later changes are written back to `path`.
+    pub fn load(path: PathBuf) -> Self {
+        let context = match std::fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable state file {}: {}", path.display(), e);
+                RunContext::default()
+            }),
+            Err(_) => RunContext::default(),
+        };
+        Self {
+            path: Some(path),
+            context: Mutex::new(context),
+        }
+    }
+
+    /// A copy of the current context.
+    pub fn context(&self) -> RunContext {
+        self.context.lock().unwrap().clone()
+    }
+
+    /// Apply `change` and persist the result if anything changed.
+    pub fn update(&self, change: impl FnOnce(&mut RunContext)) {
+        let mut context = self.context.lock().unwrap();
+        let before = context.clone();
+        change(&mut context);
+        if *context != before {
+            if let Err(e) = self.save(&context) {
+                tracing::warn!("Failed to save run context: {}", e);
+            }
+        }
+    }
+
+    /// Write via a temporary file so a crash never leaves a truncated state file.
+    fn save(&self, context: &RunContext) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(context)?)?;
+        std::fs::rename(&tmp, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attach_to_new_run_drops_old_hashes() {
+        let mut context = RunContext::default();
+        context.attach("run-1", "builder");
+        context.record_hash("run-1", "art-1", "abc");
+        context.record_hash("run-2", "art-2", "ignored");
+        assert_eq!(context.last_read_hashes.len(), 1);
+
+        context.attach("run-1", "reviewer");
+        assert_eq!(context.last_read_hashes.len(), 1);
+
+        context.attach("run-2", "builder");
+        assert!(context.last_read_hashes.is_empty());
+        assert_eq!(context.run_id.as_deref(), Some("run-2"));
+    }
+
+    #[test]
+    fn test_path_for_sanitizes_session_name() {
+        let dir = Path::new("/state");
+        assert_eq!(
+            StateStore::path_for(dir, "hw-1"),
+            PathBuf::from("/state/hw-1.json")
+        );
+        assert_eq!(
+            StateStore::path_for(dir, "../a b"),
+            PathBuf::from("/state/_a_b.json")
+        );
+    }
+
+    #[test]
+    fn test_context_survives_reload() {
+        let dir = std::env::temp_dir().join(format!("hotwired-state-{}", std::process::id()));
+        let path = StateStore::path_for(&dir, "hw-1");
+
+        let store = StateStore::load(path.clone());
+        assert_eq!(store.context(), RunContext::default());
+        store.update(|c| {
+            c.attach("run-1", "builder");
+            c.record_hash("run-1", "art-1", "abc");
+        });
+
+        let reloaded = StateStore::load(path.clone());
+        assert_eq!(reloaded.context().role.as_deref(), Some("builder"));
+        assert_eq!(reloaded.context().last_read_hashes["art-1"], "abc");
+
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(StateStore::load(path).context(), RunContext::default());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -4,16 +4,20 @@
 //! tracked document artifacts in the Hotwired doc-editor.
 
 use crate::ipc::messages::{
+    CommentDetail,
+    CommentType,
     DocArtifactAcceptSuggestionRequest,
     DocArtifactAcceptSuggestionResponse,
     DocArtifactAddCommentRequest,
     DocArtifactAddCommentResponse,
-    DocArtifactCreateRequest,
+    DocArtifactCreateBody,
     DocArtifactCreateResponse,
-    DocArtifactEditRequest,
+    DocArtifactEditBody,
     DocArtifactEditResponse,
     DocArtifactListCommentsRequest,
     DocArtifactListCommentsResponse,
+    DocArtifactListEditsRequest,
+    DocArtifactListEditsResponse,
     DocArtifactListRequest,
     DocArtifactListResponse,
     DocArtifactListSuggestionsRequest,
@@ -24,14 +28,26 @@ use crate::ipc::messages::{
     DocArtifactRejectSuggestionResponse,
     DocArtifactResolveCommentRequest,
     DocArtifactResolveCommentResponse,
+    DocArtifactReviewContext,
     DocArtifactSearchRequest,
     DocArtifactSearchResponse,
+    DocArtifactSetTagsRequest,
+    DocArtifactSetTagsResponse,
     // Edit suggestions (Mode 2)
     DocArtifactSuggestEditRequest,
     DocArtifactSuggestEditResponse,
+    EditPreview,
+    EditReason,
+    EditType,
+    ResolveAction,
 };
 use crate::ipc::traits::IpcClient;
+use crate::tools::{diff, offsets, text, timestamps};
 use crate::types::errors::IpcError;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 // =============================================================================
 // ARTIFACT LISTING AND READING
@@ -44,12 +60,68 @@ pub async fn list_artifacts<C: IpcClient>(
 ) -> Result<DocArtifactListResponse, IpcError> {
     let request = DocArtifactListRequest {
         run_id: run_id.to_string(),
+        tag: None,
+        document_type: None,
+        format: None,
+        continuation: None,
     };
 
     let endpoint = format!("/api/runs/{}/artifacts", run_id);
     client.request(&endpoint, &request).await
 }
 
+/// Keep only the artifacts with `tag` and of `document_type`, both compared
+/// case-insensitively.
+pub fn filter_artifacts(
+    response: &mut DocArtifactListResponse,
+    tag: Option<&str>,
+    document_type: Option<&str>,
+) {
+    response.artifacts.retain(|artifact| {
+        tag.is_none_or(|tag| artifact.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+            && document_type.is_none_or(|kind| artifact.document_type.eq_ignore_ascii_case(kind))
+    });
+}
+
+/// A tag as stored: trimmed and lowercase.
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Add and remove tags on an artifact. Returns its tags afterwards.
+pub async fn set_tags<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+    add: &[String],
+    remove: &[String],
+    source: &str,
+) -> Result<DocArtifactSetTagsResponse, IpcError> {
+    let request = DocArtifactSetTagsRequest {
+        run_id: run_id.to_string(),
+        artifact_id: artifact_id.to_string(),
+        add: add.iter().map(|t| normalize_tag(t)).collect(),
+        remove: remove.iter().map(|t| normalize_tag(t)).collect(),
+        source: Some(source.to_string()),
+        format: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/artifacts/{}/tags", run_id, artifact_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Format the tags left on an artifact.
+pub fn format_set_tags_response(response: &DocArtifactSetTagsResponse) -> String {
+    if response.tags.is_empty() {
+        return format!("✓ Artifact `{}` has no tags", response.artifact_id);
+    }
+    format!(
+        "✓ Artifact `{}` tagged: {}",
+        response.artifact_id,
+        response.tags.join(", ")
+    )
+}
+
 /// Read artifact content with pagination.
 pub async fn read_artifact<C: IpcClient>(
     client: &C,
@@ -65,43 +137,221 @@ pub async fn read_artifact<C: IpcClient>(
         offset,
         limit,
         include_comments,
+        render_mode: None,
+        source: None,
+        format: None,
+        continuation: None,
     };
 
     let endpoint = format!("/api/runs/{}/artifacts/{}", run_id, artifact_id);
     client.request(&endpoint, &request).await
 }
 
+/// Read the whole document, one page of `page_lines` lines at a time, as a
+/// single response.
+pub async fn read_full_content<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+    page_lines: i64,
+) -> Result<DocArtifactReadResponse, IpcError> {
+    read_within_budget(
+        client,
+        run_id,
+        artifact_id,
+        Some(0),
+        page_lines,
+        Some(false),
+        usize::MAX,
+    )
+    .await
+}
+
+/// Read from `offset` one page of `page_lines` lines at a time until the
+/// document ends or the next line would take the content past `budget_bytes`,
+/// and return the pages as a single response. The first page is always
+/// returned whole; `has_more` tells whether anything was left unread.
+pub async fn read_within_budget<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+    offset: Option<i64>,
+    page_lines: i64,
+    include_comments: Option<bool>,
+    budget_bytes: usize,
+) -> Result<DocArtifactReadResponse, IpcError> {
+    let mut document = read_artifact(
+        client,
+        run_id,
+        artifact_id,
+        offset,
+        Some(page_lines),
+        include_comments,
+    )
+    .await?;
+    while document.has_more && document.returned_lines > 0 && document.content.len() < budget_bytes
+    {
+        let page = read_artifact(
+            client,
+            run_id,
+            artifact_id,
+            Some(document.offset + document.returned_lines),
+            Some(page_lines),
+            include_comments,
+        )
+        .await?;
+        if !append_page(&mut document, page, budget_bytes) {
+            break;
+        }
+    }
+    Ok(document)
+}
+
+/// Append as many whole lines of `page` to `document` as fit in `budget_bytes`.
+/// Returns false if none did.
+fn append_page(
+    document: &mut DocArtifactReadResponse,
+    page: DocArtifactReadResponse,
+    budget_bytes: usize,
+) -> bool {
+    let separator = !document.content.is_empty() && !document.content.ends_with('\n');
+    let room = budget_bytes.saturating_sub(document.content.len() + usize::from(separator));
+
+    let mut taken_bytes = 0;
+    let mut taken_lines = 0;
+    for line in page.content.split_inclusive('\n') {
+        if taken_bytes + line.len() > room {
+            break;
+        }
+        taken_bytes += line.len();
+        taken_lines += 1;
+    }
+    if taken_lines == 0 {
+        return false;
+    }
+    let whole_page = taken_bytes == page.content.len();
+
+    if separator {
+        document.content.push('\n');
+    }
+    let taken = &page.content[..taken_bytes];
+    document.content.push_str(if whole_page {
+        taken
+    } else {
+        taken.trim_end_matches('\n')
+    });
+    if whole_page {
+        document.returned_lines += page.returned_lines;
+        document.has_more = page.has_more && page.returned_lines > 0;
+    } else {
+        document.returned_lines += taken_lines;
+        document.has_more = true;
+    }
+
+    let last_line = document.offset + document.returned_lines;
+    if let Some(comments) = page.comments {
+        document
+            .comments
+            .get_or_insert_with(Vec::new)
+            .extend(comments.into_iter().filter(|c| c.line_number <= last_line));
+    }
+    true
+}
+
+/// Appends (and inserts at the end of the document) can't overlap concurrent
+/// changes, so on a hash conflict they are retried against the latest hash up
+/// to this many times.
+pub const END_EDIT_RETRIES: usize = 3;
+
 /// Format the read response for agent consumption.
 pub fn format_read_response(response: &DocArtifactReadResponse) -> String {
-    let mut output = String::new();
+    format_read(response, false)
+}
 
-    output.push_str(&format!("## Document: {}\n\n", response.filename));
-    output.push_str(&format!("**Content Hash:** `{}`\n", response.content_hash));
-    output.push_str(&format!(
-        "**Lines:** {}/{} (offset: {}, has_more: {})\n\n",
-        response.returned_lines, response.total_lines, response.offset, response.has_more
-    ));
-
-    if let Some(comments) = &response.comments {
-        if !comments.is_empty() {
-            output.push_str("### Inline Comments\n");
-            for c in comments {
-                output.push_str(&format!(
-                    "- [{}] Line {}: {} ({}) - \"{}\"\n",
-                    c.id, c.line_number, c.comment_type, c.author, c.preview
-                ));
-            }
-            output.push('\n');
+/// Format the read response with numbered lines and inline comment anchors.
+pub fn format_read_response_numbered(response: &DocArtifactReadResponse) -> String {
+    format_read(response, true)
+}
+
+fn format_read(response: &DocArtifactReadResponse, numbered: bool) -> String {
+    let mut output = String::with_capacity(response.content.len() + 512);
+
+    let _ = write!(
+        output,
+        "## Document: {}\n\n\
+        **Content Hash:** `{}`\n\
+        **Lines:** {}/{} (offset: {}, has_more: {})\n\n",
+        response.filename,
+        response.content_hash,
+        response.returned_lines,
+        response.total_lines,
+        response.offset,
+        response.has_more
+    );
+
+    if let Some(comments) = response.comments.as_ref().filter(|c| !c.is_empty()) {
+        output.push_str("### Inline Comments\n");
+        for c in comments {
+            let _ = writeln!(
+                output,
+                "- [{}] Line {}: {} ({}) - \"{}\"",
+                c.id, c.line_number, c.comment_type, c.author, c.preview
+            );
         }
+        output.push('\n');
     }
 
     output.push_str("### Content\n\n```markdown\n");
-    output.push_str(&response.content);
+    if numbered {
+        write_numbered_lines(&mut output, response);
+    } else {
+        output.push_str(&response.content);
+    }
     output.push_str("\n```\n");
 
     output
 }
 
+/// Prefix each content line with its 1-based document line number and append
+/// `[comment-id]` anchors for comments on that line.
+pub fn number_lines(response: &DocArtifactReadResponse) -> String {
+    let mut output = String::new();
+    write_numbered_lines(&mut output, response);
+    output
+}
+
+/// [`number_lines`], appended to `output`.
+pub fn write_numbered_lines(output: &mut String, response: &DocArtifactReadResponse) {
+    let line_count = response.content.lines().count();
+    let last_line = response.offset + line_count as i64;
+    let width = last_line.max(1).to_string().len();
+    output.reserve(response.content.len() + line_count * (width + 3));
+
+    // Comments sorted by line (stable, so same-line anchors keep their order)
+    let mut anchors: Vec<(i64, &str)> = response
+        .comments
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .map(|c| (c.line_number, c.id.as_str()))
+        .collect();
+    anchors.sort_by_key(|(line, _)| *line);
+    let mut anchors = anchors.into_iter().peekable();
+
+    for (i, line) in response.content.lines().enumerate() {
+        let line_number = response.offset + i as i64 + 1;
+        if i > 0 {
+            output.push('\n');
+        }
+        let _ = write!(output, "{:>width$} | {}", line_number, line, width = width);
+        while let Some((anchor_line, id)) = anchors.next_if(|(l, _)| *l <= line_number) {
+            if anchor_line == line_number {
+                let _ = write!(output, " [{}]", id);
+            }
+        }
+    }
+}
+
 // =============================================================================
 // ARTIFACT CREATION
 // =============================================================================
@@ -115,12 +365,12 @@ pub async fn create_artifact<C: IpcClient>(
     document_type: Option<&str>,
     created_by: Option<&str>,
 ) -> Result<DocArtifactCreateResponse, IpcError> {
-    let request = DocArtifactCreateRequest {
-        run_id: run_id.to_string(),
-        filename: filename.to_string(),
-        initial_content: initial_content.map(String::from),
-        document_type: document_type.map(String::from),
-        created_by: created_by.map(String::from),
+    let request = DocArtifactCreateBody {
+        run_id,
+        filename,
+        initial_content,
+        document_type,
+        created_by,
     };
 
     let endpoint = "/api/artifacts".to_string();
@@ -137,46 +387,32 @@ pub async fn edit_artifact<C: IpcClient>(
     client: &C,
     run_id: &str,
     artifact_id: &str,
-    edit_type: &str,
+    edit_type: EditType,
     content_hash: &str,
     new_content: &str,
     start_offset: Option<i64>,
     end_offset: Option<i64>,
     insert_offset: Option<i64>,
-    edit_reason: Option<&str>,
+    edit_reason: Option<&EditReason>,
     source: Option<&str>,
 ) -> Result<DocArtifactEditResponse, IpcError> {
-    let request = DocArtifactEditRequest {
-        run_id: run_id.to_string(),
-        artifact_id: artifact_id.to_string(),
-        edit_type: edit_type.to_string(),
-        content_hash: content_hash.to_string(),
-        new_content: new_content.to_string(),
+    let request = DocArtifactEditBody {
+        run_id,
+        artifact_id,
+        edit_type,
+        content_hash,
+        new_content,
         start_offset,
         end_offset,
         insert_offset,
-        edit_reason: edit_reason.map(String::from),
-        source: source.map(String::from),
+        edit_reason,
+        source,
     };
 
     let endpoint = format!("/api/runs/{}/artifacts/{}/edit", run_id, artifact_id);
     client.request(&endpoint, &request).await
 }
 
-/// Validate edit type value.
-pub fn validate_edit_type(edit_type: &str) -> Result<(), String> {
-    const VALID: &[&str] = &["replace_range", "insert", "append", "full_replace"];
-    if VALID.contains(&edit_type) {
-        Ok(())
-    } else {
-        Err(format!(
-            "Invalid edit type '{}'. Must be one of: {}",
-            edit_type,
-            VALID.join(", ")
-        ))
-    }
-}
-
 /// Format edit response for agent consumption.
 pub fn format_edit_response(response: &DocArtifactEditResponse) -> String {
     if !response.success {
@@ -201,16 +437,86 @@ pub fn format_edit_response(response: &DocArtifactEditResponse) -> String {
     );
 
     if !response.affected_comments.is_empty() {
-        output.push_str(&format!(
+        let _ = write!(
+            output,
             "\n**Affected comments:** {}\n\
             These comments may need to be reviewed as their positions may have shifted.",
             response.affected_comments.join(", ")
-        ));
+        );
     }
 
     output
 }
 
+/// What an edit would do to `document`, without sending it. Offsets are in
+/// characters; `content_hash` is compared when given. Suggestions pass their
+/// `start_offset` as `insert_offset` for inserts.
+pub fn preview_edit(
+    document: &DocArtifactReadResponse,
+    content_hash: Option<&str>,
+    edit_type: EditType,
+    new_content: &str,
+    start_offset: Option<i64>,
+    end_offset: Option<i64>,
+    insert_offset: Option<i64>,
+) -> Result<EditPreview, String> {
+    let original = &document.content;
+    let byte = |name: &str, offset: Option<i64>| {
+        let offset = offset.ok_or_else(|| format!("{} is required for this edit type", name))?;
+        let chars =
+            usize::try_from(offset).map_err(|_| format!("{} must be 0 or greater", name))?;
+        offsets::char_to_byte(original, chars).map_err(|e| format!("{}: {}", name, e))
+    };
+    let resulting = match edit_type {
+        EditType::ReplaceRange => {
+            let start = byte("start_offset", start_offset)?;
+            let end = byte("end_offset", end_offset)?;
+            if start > end {
+                return Err("start_offset must not be after end_offset".to_string());
+            }
+            [&original[..start], new_content, &original[end..]].concat()
+        }
+        EditType::Insert => {
+            let at = byte("insert_offset", insert_offset)?;
+            [&original[..at], new_content, &original[at..]].concat()
+        }
+        EditType::Append => [original.as_str(), new_content].concat(),
+        EditType::FullReplace => new_content.to_string(),
+    };
+    Ok(EditPreview {
+        artifact_id: document.artifact_id.clone(),
+        content_hash: document.content_hash.clone(),
+        hash_matches: content_hash.map(|hash| hash == document.content_hash),
+        original_chars: original.chars().count(),
+        resulting_chars: resulting.chars().count(),
+        diff: diff::render_diff(original, &resulting),
+    })
+}
+
+/// Format a dry-run preview for agent consumption.
+pub fn format_edit_preview(preview: &EditPreview) -> String {
+    let mut output = format!(
+        "DRY RUN — nothing was changed\n\n\
+        **Artifact:** `{}`\n\
+        **Length:** {} → {} characters\n",
+        preview.artifact_id, preview.original_chars, preview.resulting_chars
+    );
+    if preview.hash_matches == Some(false) {
+        let _ = write!(
+            output,
+            "\n⚠️ **Hash mismatch:** the document is now at `{}`. The edit would be \
+            rejected as a conflict; re-read with `doc_artifact_read` first.\n",
+            preview.content_hash
+        );
+    }
+    if preview.diff.is_empty() {
+        output.push_str("\nNo change to the document.\n");
+    } else {
+        let _ = write!(output, "\n```diff\n{}\n```\n", preview.diff.trim_end());
+    }
+    output
+}
+
 // =============================================================================
 // ARTIFACT SEARCH
 // =============================================================================
@@ -235,6 +541,8 @@ pub async fn search_artifact<C: IpcClient>(
         scope: scope.map(String::from),
         context_lines,
         max_results,
+        format: None,
+        continuation: None,
     };
 
     let endpoint = format!("/api/runs/{}/artifacts/{}/search", run_id, artifact_id);
@@ -243,7 +551,15 @@ pub async fn search_artifact<C: IpcClient>(
 
 /// Format search response for agent consumption.
 pub fn format_search_response(response: &DocArtifactSearchResponse) -> String {
-    let mut output = format!(
+    let mut output = String::with_capacity(
+        256 + response
+            .results
+            .iter()
+            .map(|r| r.context.len() + r.match_text.len() + 96)
+            .sum::<usize>(),
+    );
+    let _ = write!(
+        output,
         "## Search Results for: \"{}\"\n\n\
         **Total matches:** {}\n\n",
         response.query, response.total_matches
@@ -255,23 +571,25 @@ pub fn format_search_response(response: &DocArtifactSearchResponse) -> String {
     }
 
     for (i, result) in response.results.iter().enumerate() {
-        output.push_str(&format!(
-            "### Match {} (Line {}, chars {}-{})\n",
+        let _ = writeln!(
+            output,
+            "### Match {} (Line {}, chars {}-{})",
             i + 1,
             result.line_number,
             result.char_start,
             result.char_end
-        ));
+        );
 
         if let Some(section) = &result.section {
-            output.push_str(&format!("**Section:** {}\n", section));
+            let _ = writeln!(output, "**Section:** {}", section);
         }
 
-        output.push_str(&format!(
+        let _ = write!(
+            output,
             "**Matched text:** `{}`\n\n\
             ```\n{}\n```\n\n",
             result.match_text, result.context
-        ));
+        );
     }
 
     output
@@ -288,24 +606,28 @@ pub async fn add_comment<C: IpcClient>(
     client: &C,
     run_id: &str,
     artifact_id: &str,
-    comment_type: &str,
+    comment_type: CommentType,
     selection_start: i64,
     selection_end: i64,
     content: &str,
     suggested_text: Option<&str>,
     author: &str,
     parent_comment_id: Option<&str>,
+    mentions: Option<Vec<String>>,
 ) -> Result<DocArtifactAddCommentResponse, IpcError> {
     let request = DocArtifactAddCommentRequest {
         run_id: run_id.to_string(),
         artifact_id: artifact_id.to_string(),
-        comment_type: comment_type.to_string(),
+        comment_type,
         selection_start,
         selection_end,
         content: content.to_string(),
         suggested_text: suggested_text.map(String::from),
-        author: author.to_string(),
+        author: Some(author.to_string()),
         parent_comment_id: parent_comment_id.map(String::from),
+        mentions,
+        format: None,
+        offset_unit: None,
     };
 
     let endpoint = format!(
@@ -315,27 +637,13 @@ pub async fn add_comment<C: IpcClient>(
     client.request(&endpoint, &request).await
 }
 
-/// Validate comment type value.
-pub fn validate_comment_type(comment_type: &str) -> Result<(), String> {
-    const VALID: &[&str] = &["comment", "question", "suggestion", "issue"];
-    if VALID.contains(&comment_type) {
-        Ok(())
-    } else {
-        Err(format!(
-            "Invalid comment type '{}'. Must be one of: {}",
-            comment_type,
-            VALID.join(", ")
-        ))
-    }
-}
-
 /// Resolve or respond to a comment.
 pub async fn resolve_comment<C: IpcClient>(
     client: &C,
     run_id: &str,
     artifact_id: &str,
     comment_id: &str,
-    action: &str,
+    action: ResolveAction,
     response_text: Option<&str>,
     resolved_by: &str,
 ) -> Result<DocArtifactResolveCommentResponse, IpcError> {
@@ -343,9 +651,10 @@ pub async fn resolve_comment<C: IpcClient>(
         run_id: run_id.to_string(),
         artifact_id: artifact_id.to_string(),
         comment_id: comment_id.to_string(),
-        action: action.to_string(),
+        action,
         response: response_text.map(String::from),
-        resolved_by: resolved_by.to_string(),
+        resolved_by: Some(resolved_by.to_string()),
+        format: None,
     };
 
     let endpoint = format!(
@@ -355,27 +664,8 @@ pub async fn resolve_comment<C: IpcClient>(
     client.request(&endpoint, &request).await
 }
 
-/// Validate resolve action value.
-/// Actions:
-/// - "accept": User approves the feedback, signals agent to address it (non-terminal)
-/// - "reject": User disagrees with the feedback, closes the thread (terminal)
-/// - "reply": Continue discussion, thread stays open (non-terminal)
-/// - "address": Agent has addressed the feedback, closes the thread (terminal)
-/// - "resolve": Legacy action, same as address (terminal)
-pub fn validate_resolve_action(action: &str) -> Result<(), String> {
-    const VALID: &[&str] = &["accept", "reject", "reply", "address", "resolve"];
-    if VALID.contains(&action) {
-        Ok(())
-    } else {
-        Err(format!(
-            "Invalid action '{}'. Must be one of: {}",
-            action,
-            VALID.join(", ")
-        ))
-    }
-}
-
 /// List comments on an artifact.
+#[allow(clippy::too_many_arguments)]
 pub async fn list_comments<C: IpcClient>(
     client: &C,
     run_id: &str,
@@ -384,6 +674,7 @@ pub async fn list_comments<C: IpcClient>(
     comment_type: Option<&str>,
     line_start: Option<i64>,
     line_end: Option<i64>,
+    mentioning: Option<&str>,
 ) -> Result<DocArtifactListCommentsResponse, IpcError> {
     let request = DocArtifactListCommentsRequest {
         run_id: run_id.to_string(),
@@ -392,6 +683,16 @@ pub async fn list_comments<C: IpcClient>(
         comment_type: comment_type.map(String::from),
         line_start,
         line_end,
+        mentioning: mentioning.map(String::from),
+        author: None,
+        created_after: None,
+        has_reply: None,
+        format: None,
+        continuation: None,
+        report: None,
+        report_path: None,
+        offset: None,
+        limit: None,
     };
 
     let endpoint = format!(
@@ -401,13 +702,197 @@ pub async fn list_comments<C: IpcClient>(
     client.request(&endpoint, &request).await
 }
 
+/// The comment `comment_id` on the artifact, in any status, if it exists.
+pub async fn find_comment<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+    comment_id: &str,
+) -> Result<Option<CommentDetail>, IpcError> {
+    let response = list_comments(
+        client,
+        run_id,
+        artifact_id,
+        Some("all"),
+        None,
+        None,
+        None,
+        None,
+    )
+    .await?;
+    Ok(response.comments.into_iter().find(|c| c.id == comment_id))
+}
+
+/// How long comments prefetched by `doc_artifact_read` answer a follow-up
+/// `doc_artifact_list_comments`.
+pub const COMMENT_PREFETCH_TTL: Duration = Duration::from_secs(30);
+
+/// All comments of recently read artifacts, so the `doc_artifact_list_comments`
+/// that usually follows a read is answered locally.
+#[derive(Default)]
+pub struct CommentCache {
+    /// (run_id, artifact_id) -> (every comment, when it was fetched)
+    entries: Mutex<HashMap<(String, String), (DocArtifactListCommentsResponse, Instant)>>,
+}
+
+impl CommentCache {
+    /// Fetch every comment on the artifact, in any status.
+    pub async fn prefetch<C: IpcClient>(
+        &self,
+        client: &C,
+        run_id: &str,
+        artifact_id: &str,
+    ) -> Result<(), IpcError> {
+        let response = list_comments(
+            client,
+            run_id,
+            artifact_id,
+            Some("all"),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await?;
+        self.entries.lock().unwrap().insert(
+            (run_id.to_string(), artifact_id.to_string()),
+            (response, Instant::now()),
+        );
+        Ok(())
+    }
+
+    /// Forget the artifact's comments, e.g. because they are about to change.
+    pub fn invalidate(&self, run_id: &str, artifact_id: &str) {
+        self.entries
+            .lock()
+            .unwrap()
+            .remove(&(run_id.to_string(), artifact_id.to_string()));
+    }
+
+    /// The artifact's comments matching the `doc_artifact_list_comments` filters
+    /// `status` (default open), `comment_type`, and `mentioning`, if fetched
+    /// less than `ttl` ago.
+    pub fn lookup(
+        &self,
+        run_id: &str,
+        artifact_id: &str,
+        status: Option<&str>,
+        comment_type: Option<&str>,
+        mentioning: Option<&str>,
+        ttl: Duration,
+    ) -> Option<DocArtifactListCommentsResponse> {
+        let entries = self.entries.lock().unwrap();
+        let (all, at) = entries.get(&(run_id.to_string(), artifact_id.to_string()))?;
+        if at.elapsed() >= ttl {
+            return None;
+        }
+        let matches = |filter: Option<&str>, value: &str| match filter {
+            None | Some("all") => true,
+            Some(filter) => filter == value,
+        };
+        let status = status.unwrap_or("open");
+        Some(DocArtifactListCommentsResponse {
+            artifact_id: artifact_id.to_string(),
+            offset: 0,
+            has_more: false,
+            comments: all
+                .comments
+                .iter()
+                .filter(|c| matches(Some(status), &c.status))
+                .filter(|c| matches(comment_type, &c.comment_type))
+                .filter(|c| mentioning.is_none_or(|role| c.mentions.iter().any(|m| m == role)))
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+/// The `doc_artifact_list_comments` filters applied here rather than by the
+/// backend.
+#[derive(Debug, Default)]
+pub struct CommentFilter<'a> {
+    pub author: Option<&'a str>,
+    /// Seconds since the epoch; only comments created later are kept.
+    pub created_after: Option<i64>,
+    /// Keep only comments with (true) or without (false) a reply.
+    pub has_reply: Option<bool>,
+}
+
+impl CommentFilter<'_> {
+    /// Whether keeping a comment depends on its replies, which may not be
+    /// among the listed comments.
+    pub fn needs_replies(&self) -> bool {
+        self.has_reply.is_some()
+    }
+
+    /// Drop the listed comments not matching the filter. `replied` holds the
+    /// ids of comments with at least one reply.
+    pub fn apply(&self, response: &mut DocArtifactListCommentsResponse, replied: &HashSet<String>) {
+        response.comments.retain(|c| {
+            self.author.is_none_or(|author| c.author == author)
+                && self.created_after.is_none_or(|after| {
+                    timestamps::epoch_seconds(&c.created_at).is_none_or(|at| at > after)
+                })
+                && self
+                    .has_reply
+                    .is_none_or(|has_reply| replied.contains(&c.id) == has_reply)
+        });
+    }
+}
+
+/// Ids of the comments some other comment replies to.
+pub fn replied_to(comments: &[CommentDetail]) -> HashSet<String> {
+    comments
+        .iter()
+        .filter_map(|c| c.parent_comment_id.clone())
+        .collect()
+}
+
+/// Keep the `limit` items after the first `offset`; whether any follow them.
+pub fn page<T>(items: &mut Vec<T>, offset: usize, limit: Option<usize>) -> bool {
+    items.drain(..offset.min(items.len()));
+    match limit {
+        Some(limit) if items.len() > limit => {
+            items.truncate(limit);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// " (offset: 100, has_more: true)" after a listing's total when it is one
+/// page of a longer list; empty otherwise.
+pub fn page_note(offset: i64, has_more: bool) -> String {
+    if offset == 0 && !has_more {
+        return String::new();
+    }
+    format!(" (offset: {}, has_more: {})", offset, has_more)
+}
+
+/// How many of `comments` reply to the comment `id`.
+pub fn reply_count(comments: &[CommentDetail], id: &str) -> usize {
+    comments
+        .iter()
+        .filter(|c| c.parent_comment_id.as_deref() == Some(id))
+        .count()
+}
+
 /// Format comments list response for agent consumption.
 pub fn format_comments_response(response: &DocArtifactListCommentsResponse) -> String {
-    let mut output = format!(
+    let mut output = String::with_capacity(
+        128 + response
+            .comments
+            .iter()
+            .map(|c| c.content.len() + 256)
+            .sum::<usize>(),
+    );
+    let _ = write!(
+        output,
         "## Comments on Artifact: {}\n\n\
-        **Total:** {} comments\n\n",
+        **Total:** {} comments{}\n\n",
         response.artifact_id,
-        response.comments.len()
+        response.comments.len(),
+        page_note(response.offset, response.has_more)
     );
 
     if response.comments.is_empty() {
@@ -431,41 +916,59 @@ pub fn format_comments_response(response: &DocArtifactListCommentsResponse) -> S
             _ => "📝",
         };
 
-        output.push_str(&format!(
-            "### {} {} [{}] - {} (by {})\n",
-            status_icon, type_icon, comment.id, comment.comment_type, comment.author
-        ));
-
-        output.push_str(&format!(
-            "**Selection:** chars {}-{}\n",
-            comment.selection_start, comment.selection_end
-        ));
-
-        if let Some(text) = &comment.selection_text {
-            let preview = if text.len() > 50 {
-                format!("{}...", &text[..50])
-            } else {
-                text.clone()
-            };
-            output.push_str(&format!("**Selected text:** \"{}\"\n", preview));
+        let _ = write!(
+            output,
+            "### {} {} [{}] - {} (by {})\n\
+            **Selection:** chars {}-{}\n",
+            status_icon,
+            type_icon,
+            comment.id,
+            comment.comment_type,
+            comment.author,
+            comment.selection_start,
+            comment.selection_end
+        );
+
+        if let Some(selection) = &comment.selection_text {
+            let _ = writeln!(
+                output,
+                "**Selected text:** \"{}\"",
+                text::truncate(selection, 50)
+            );
         }
 
-        output.push_str(&format!("**Content:** {}\n", comment.content));
+        let _ = writeln!(output, "**Content:** {}", comment.content);
+
+        if let Some(parent) = &comment.parent_comment_id {
+            let _ = writeln!(output, "**In reply to:** `{}`", parent);
+        }
+        let replies = reply_count(&response.comments, &comment.id);
+        if replies > 0 {
+            let _ = writeln!(output, "**Replies:** {}", replies);
+        }
+
+        if !comment.mentions.is_empty() {
+            let _ = writeln!(output, "**Mentions:** @{}", comment.mentions.join(", @"));
+        }
 
         if let Some(suggested) = &comment.suggested_text {
-            output.push_str(&format!("**Suggested replacement:** \"{}\"\n", suggested));
+            let _ = writeln!(output, "**Suggested replacement:** \"{}\"", suggested);
         }
 
         if comment.status != "open" {
             if let Some(by) = &comment.resolved_by {
-                output.push_str(&format!("**Resolved by:** {}\n", by));
+                let _ = writeln!(output, "**Resolved by:** {}", by);
             }
             if let Some(note) = &comment.resolution_note {
-                output.push_str(&format!("**Resolution note:** {}\n", note));
+                let _ = writeln!(output, "**Resolution note:** {}", note);
             }
         }
 
-        output.push_str(&format!("**Created:** {}\n\n", comment.created_at));
+        let _ = write!(
+            output,
+            "**Created:** {}\n\n",
+            timestamps::format_timestamp(&comment.created_at)
+        );
     }
 
     output
@@ -473,7 +976,9 @@ pub fn format_comments_response(response: &DocArtifactListCommentsResponse) -> S
 
 /// Format artifact list response for agent consumption.
 pub fn format_list_response(response: &DocArtifactListResponse) -> String {
-    let mut output = format!(
+    let mut output = String::with_capacity(128 + response.artifacts.len() * 160);
+    let _ = write!(
+        output,
         "## Artifacts in Run: {}\n\n\
         **Total:** {} artifacts\n\n",
         response.run_id,
@@ -486,18 +991,23 @@ pub fn format_list_response(response: &DocArtifactListResponse) -> String {
     }
 
     for artifact in &response.artifacts {
-        output.push_str(&format!(
+        let _ = write!(
+            output,
             "- **{}** ({})\n  \
             ID: `{}`\n  \
             Lines: {} | Hash: `{}`\n  \
-            Updated: {}\n\n",
+            Updated: {}\n",
             artifact.filename,
             artifact.document_type,
             artifact.id,
             artifact.total_lines,
-            &artifact.content_hash[..8], // Show just first 8 chars of hash
-            artifact.updated_at
-        ));
+            text::prefix(&artifact.content_hash, 8), // Show just first 8 chars of hash
+            timestamps::format_timestamp(&artifact.updated_at)
+        );
+        if !artifact.tags.is_empty() {
+            let _ = writeln!(output, "  Tags: {}", artifact.tags.join(", "));
+        }
+        output.push('\n');
     }
 
     output
@@ -516,23 +1026,28 @@ pub async fn suggest_edit<C: IpcClient>(
     run_id: &str,
     artifact_id: &str,
     comment_id: &str,
-    edit_type: &str,
+    edit_type: EditType,
     start_offset: Option<i64>,
     end_offset: Option<i64>,
     suggested_text: &str,
     rationale: Option<&str>,
     source: &str,
+    base_content_hash: Option<&str>,
 ) -> Result<DocArtifactSuggestEditResponse, IpcError> {
     let request = DocArtifactSuggestEditRequest {
         run_id: run_id.to_string(),
         artifact_id: artifact_id.to_string(),
         comment_id: comment_id.to_string(),
-        edit_type: edit_type.to_string(),
+        edit_type,
         start_offset,
         end_offset,
         suggested_text: suggested_text.to_string(),
         rationale: rationale.map(String::from),
-        source: source.to_string(),
+        source: Some(source.to_string()),
+        base_content_hash: base_content_hash.map(String::from),
+        format: None,
+        offset_unit: None,
+        dry_run: None,
     };
 
     let endpoint = format!("/api/runs/{}/artifacts/{}/suggestions", run_id, artifact_id);
@@ -551,7 +1066,8 @@ pub async fn accept_suggestion<C: IpcClient>(
         run_id: run_id.to_string(),
         artifact_id: artifact_id.to_string(),
         suggestion_id: suggestion_id.to_string(),
-        source: source.to_string(),
+        source: Some(source.to_string()),
+        format: None,
     };
 
     let endpoint = format!(
@@ -575,7 +1091,8 @@ pub async fn reject_suggestion<C: IpcClient>(
         artifact_id: artifact_id.to_string(),
         suggestion_id: suggestion_id.to_string(),
         reason: reason.map(String::from),
-        source: source.to_string(),
+        source: Some(source.to_string()),
+        format: None,
     };
 
     let endpoint = format!(
@@ -596,6 +1113,12 @@ pub async fn list_suggestions<C: IpcClient>(
         run_id: run_id.to_string(),
         artifact_id: artifact_id.to_string(),
         status: status.map(String::from),
+        format: None,
+        continuation: None,
+        report: None,
+        report_path: None,
+        offset: None,
+        limit: None,
     };
 
     // Use /list suffix to distinguish from create endpoint
@@ -606,6 +1129,64 @@ pub async fn list_suggestions<C: IpcClient>(
     client.request(&endpoint, &request).await
 }
 
+/// List the edits applied to an artifact, oldest first.
+pub async fn list_edits<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+) -> Result<DocArtifactListEditsResponse, IpcError> {
+    let request = DocArtifactListEditsRequest {
+        run_id: run_id.to_string(),
+        artifact_id: artifact_id.to_string(),
+    };
+
+    let endpoint = format!("/api/runs/{}/artifacts/{}/edits/list", run_id, artifact_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Read an artifact with its open comments and pending suggestions. The three
+/// requests are sent concurrently; any failure fails the whole call.
+pub async fn review_context<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+    offset: Option<i64>,
+    limit: Option<i64>,
+) -> Result<DocArtifactReviewContext, IpcError> {
+    let (document, comments, suggestions) = tokio::try_join!(
+        read_artifact(client, run_id, artifact_id, offset, limit, Some(true)),
+        list_comments(
+            client,
+            run_id,
+            artifact_id,
+            Some("open"),
+            None,
+            None,
+            None,
+            None
+        ),
+        list_suggestions(client, run_id, artifact_id, Some("pending")),
+    )?;
+    Ok(DocArtifactReviewContext {
+        document,
+        comments,
+        suggestions,
+    })
+}
+
+/// Format the review context as the numbered document followed by its open
+/// comments and pending suggestions.
+pub fn format_review_context(context: &DocArtifactReviewContext) -> String {
+    format!(
+        "{}
+{}
+{}",
+        format_read_response_numbered(&context.document),
+        format_comments_response(&context.comments),
+        format_suggestions_response(&context.suggestions)
+    )
+}
+
 /// Validate suggestion status filter value.
 pub fn validate_suggestion_status(status: &str) -> Result<(), String> {
     const VALID: &[&str] = &["pending", "accepted", "rejected", "all"];
@@ -622,11 +1203,20 @@ pub fn validate_suggestion_status(status: &str) -> Result<(), String> {
 
 /// Format suggestions list response for agent consumption.
 pub fn format_suggestions_response(response: &DocArtifactListSuggestionsResponse) -> String {
-    let mut output = format!(
+    let mut output = String::with_capacity(
+        128 + response
+            .suggestions
+            .iter()
+            .map(|s| s.suggested_text.len() + s.original_text.as_ref().map_or(0, String::len) + 256)
+            .sum::<usize>(),
+    );
+    let _ = write!(
+        output,
         "## Edit Suggestions for Artifact: {}\n\n\
-        **Total:** {} suggestions\n\n",
+        **Total:** {} suggestions{}\n\n",
         response.artifact_id,
-        response.suggestions.len()
+        response.suggestions.len(),
+        page_note(response.offset, response.has_more)
     );
 
     if response.suggestions.is_empty() {
@@ -642,54 +1232,65 @@ pub fn format_suggestions_response(response: &DocArtifactListSuggestionsResponse
             _ => "⚪",
         };
 
-        output.push_str(&format!(
-            "### {} [{}] - {} (by {})\n",
-            status_icon, suggestion.id, suggestion.edit_type, suggestion.suggested_by
-        ));
-
-        output.push_str(&format!(
-            "**Linked to comment:** `{}`\n",
+        let _ = write!(
+            output,
+            "### {} [{}] - {} (by {})\n\
+            **Linked to comment:** `{}`\n",
+            status_icon,
+            suggestion.id,
+            suggestion.edit_type,
+            suggestion.suggested_by,
             suggestion.comment_id
-        ));
+        );
 
         if let (Some(start), Some(end)) = (suggestion.start_offset, suggestion.end_offset) {
-            output.push_str(&format!("**Selection:** chars {}-{}\n", start, end));
+            let _ = writeln!(output, "**Selection:** chars {}-{}", start, end);
         }
 
-        if let Some(original) = &suggestion.original_text {
-            let preview = if original.len() > 100 {
-                format!("{}...", &original[..100])
-            } else {
-                original.clone()
-            };
-            output.push_str(&format!("**Original text:** \"{}\"\n", preview));
+        match &suggestion.original_text {
+            Some(original) => {
+                output.push_str("**Change:**\n```diff\n");
+                let diff_start = output.len();
+                diff::write_diff(&mut output, original, &suggestion.suggested_text);
+                let trimmed = output[diff_start..].trim_end().len();
+                output.truncate(diff_start + trimmed);
+                output.push_str("\n```\n");
+            }
+            None => {
+                let _ = writeln!(
+                    output,
+                    "**Suggested text:** \"{}\"",
+                    text::truncate(&suggestion.suggested_text, 100)
+                );
+            }
         }
 
-        let suggested_preview = if suggestion.suggested_text.len() > 100 {
-            format!("{}...", &suggestion.suggested_text[..100])
-        } else {
-            suggestion.suggested_text.clone()
-        };
-        output.push_str(&format!("**Suggested text:** \"{}\"\n", suggested_preview));
-
         if let Some(rationale) = &suggestion.rationale {
-            output.push_str(&format!("**Rationale:** {}\n", rationale));
+            let _ = writeln!(output, "**Rationale:** {}", rationale);
         }
 
         if suggestion.status == "accepted" {
             if let Some(by) = &suggestion.accepted_by {
-                output.push_str(&format!("**Accepted by:** {}\n", by));
+                let _ = writeln!(output, "**Accepted by:** {}", by);
             }
             if let Some(at) = &suggestion.accepted_at {
-                output.push_str(&format!("**Accepted at:** {}\n", at));
+                let _ = writeln!(
+                    output,
+                    "**Accepted at:** {}",
+                    timestamps::format_timestamp(at)
+                );
             }
         } else if suggestion.status == "rejected" {
             if let Some(reason) = &suggestion.rejection_reason {
-                output.push_str(&format!("**Rejection reason:** {}\n", reason));
+                let _ = writeln!(output, "**Rejection reason:** {}", reason);
             }
         }
 
-        output.push_str(&format!("**Created:** {}\n\n", suggestion.created_at));
+        let _ = write!(
+            output,
+            "**Created:** {}\n\n",
+            timestamps::format_timestamp(&suggestion.created_at)
+        );
     }
 
     output
@@ -719,10 +1320,11 @@ pub fn format_accept_suggestion_response(response: &DocArtifactAcceptSuggestionR
     );
 
     if !response.resolved_comments.is_empty() {
-        output.push_str(&format!(
-            "\n**Resolved comments:** {}\n",
+        let _ = writeln!(
+            output,
+            "\n**Resolved comments:** {}",
             response.resolved_comments.join(", ")
-        ));
+        );
     }
 
     output
@@ -748,74 +1350,80 @@ mod tests {
     use crate::ipc::messages::{
         ArtifactSummary, CommentDetail, EditConflict, SearchMatch, SuggestionDetail,
     };
+    use crate::ipc::messages::{CommentType, EditType, ResolveAction};
 
     // -------------------------------------------------------------------------
     // Validation Tests
     // -------------------------------------------------------------------------
 
+    /// Deserialize a tool argument the way the MCP layer does.
+    fn parse<T: serde::de::DeserializeOwned>(value: &str) -> Result<T, serde_json::Error> {
+        serde_json::from_value(serde_json::Value::String(value.to_string()))
+    }
+
     #[test]
-    fn test_validate_edit_type_valid() {
-        assert!(validate_edit_type("replace_range").is_ok());
-        assert!(validate_edit_type("insert").is_ok());
-        assert!(validate_edit_type("append").is_ok());
-        assert!(validate_edit_type("full_replace").is_ok());
+    fn test_edit_type_valid() {
+        assert!(parse::<EditType>("replace_range").is_ok());
+        assert!(parse::<EditType>("insert").is_ok());
+        assert!(parse::<EditType>("append").is_ok());
+        assert!(parse::<EditType>("full_replace").is_ok());
     }
 
     #[test]
-    fn test_validate_edit_type_invalid() {
-        let result = validate_edit_type("invalid_type");
+    fn test_edit_type_invalid() {
+        let result = parse::<EditType>("invalid_type");
         assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.contains("Invalid edit type"));
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("unknown variant"));
         assert!(err.contains("invalid_type"));
         assert!(err.contains("replace_range"));
     }
 
     #[test]
-    fn test_validate_edit_type_empty() {
-        let result = validate_edit_type("");
+    fn test_edit_type_empty() {
+        let result = parse::<EditType>("");
         assert!(result.is_err());
     }
 
     #[test]
-    fn test_validate_edit_type_case_sensitive() {
+    fn test_edit_type_case_sensitive() {
         // Edit types are case-sensitive
-        assert!(validate_edit_type("REPLACE_RANGE").is_err());
-        assert!(validate_edit_type("Insert").is_err());
+        assert!(parse::<EditType>("REPLACE_RANGE").is_err());
+        assert!(parse::<EditType>("Insert").is_err());
     }
 
     #[test]
-    fn test_validate_comment_type_valid() {
-        assert!(validate_comment_type("comment").is_ok());
-        assert!(validate_comment_type("question").is_ok());
-        assert!(validate_comment_type("suggestion").is_ok());
-        assert!(validate_comment_type("issue").is_ok());
+    fn test_comment_type_valid() {
+        assert!(parse::<CommentType>("comment").is_ok());
+        assert!(parse::<CommentType>("question").is_ok());
+        assert!(parse::<CommentType>("suggestion").is_ok());
+        assert!(parse::<CommentType>("issue").is_ok());
     }
 
     #[test]
-    fn test_validate_comment_type_invalid() {
-        let result = validate_comment_type("feedback");
+    fn test_comment_type_invalid() {
+        let result = parse::<CommentType>("feedback");
         assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.contains("Invalid comment type"));
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("unknown variant"));
         assert!(err.contains("feedback"));
     }
 
     #[test]
-    fn test_validate_resolve_action_valid() {
-        assert!(validate_resolve_action("accept").is_ok());
-        assert!(validate_resolve_action("reject").is_ok());
-        assert!(validate_resolve_action("reply").is_ok());
-        assert!(validate_resolve_action("address").is_ok());
-        assert!(validate_resolve_action("resolve").is_ok());
+    fn test_resolve_action_valid() {
+        assert!(parse::<ResolveAction>("accept").is_ok());
+        assert!(parse::<ResolveAction>("reject").is_ok());
+        assert!(parse::<ResolveAction>("reply").is_ok());
+        assert!(parse::<ResolveAction>("address").is_ok());
+        assert!(parse::<ResolveAction>("resolve").is_ok());
     }
 
     #[test]
-    fn test_validate_resolve_action_invalid() {
-        let result = validate_resolve_action("dismiss");
+    fn test_resolve_action_invalid() {
+        let result = parse::<ResolveAction>("dismiss");
         assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.contains("Invalid action"));
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("unknown variant"));
         assert!(err.contains("dismiss"));
     }
 
@@ -1046,6 +1654,8 @@ mod tests {
     fn test_format_comments_response_empty() {
         let response = DocArtifactListCommentsResponse {
             artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
             comments: vec![],
         };
 
@@ -1058,6 +1668,8 @@ mod tests {
     fn test_format_comments_response_with_comments() {
         let response = DocArtifactListCommentsResponse {
             artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
             comments: vec![CommentDetail {
                 id: "comment-123".to_string(),
                 comment_type: "suggestion".to_string(),
@@ -1069,6 +1681,8 @@ mod tests {
                 content: "This needs improvement".to_string(),
                 suggested_text: Some("Better text".to_string()),
                 created_at: "2024-01-15T10:30:00Z".to_string(),
+                mentions: vec![],
+                parent_comment_id: None,
                 resolved_by: None,
                 resolved_at: None,
                 resolution_note: None,
@@ -1089,6 +1703,8 @@ mod tests {
     fn test_format_comments_response_resolved() {
         let response = DocArtifactListCommentsResponse {
             artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
             comments: vec![CommentDetail {
                 id: "comment-456".to_string(),
                 comment_type: "question".to_string(),
@@ -1100,6 +1716,8 @@ mod tests {
                 content: "What does this mean?".to_string(),
                 suggested_text: None,
                 created_at: "2024-01-15T10:30:00Z".to_string(),
+                mentions: vec![],
+                parent_comment_id: None,
                 resolved_by: Some("human".to_string()),
                 resolved_at: Some("2024-01-15T11:00:00Z".to_string()),
                 resolution_note: Some("Clarified the meaning".to_string()),
@@ -1113,6 +1731,228 @@ mod tests {
         assert!(output.contains("Resolution note:** Clarified"));
     }
 
+    #[test]
+    fn test_format_comments_response_truncates_multibyte_selection() {
+        // 60 CJK characters (180 bytes): byte index 50 is not a char boundary
+        let selection = "文档".repeat(30);
+        let response = DocArtifactListCommentsResponse {
+            artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
+            comments: vec![CommentDetail {
+                id: "comment-789".to_string(),
+                comment_type: "comment".to_string(),
+                status: "open".to_string(),
+                author: "critiquer".to_string(),
+                selection_start: 0,
+                selection_end: 60,
+                selection_text: Some(selection),
+                content: "🚀 Looks good".to_string(),
+                suggested_text: None,
+                created_at: "2024-01-15T10:30:00Z".to_string(),
+                mentions: vec![],
+                parent_comment_id: None,
+                resolved_by: None,
+                resolved_at: None,
+                resolution_note: None,
+            }],
+        };
+
+        let output = format_comments_response(&response);
+        assert!(output.contains(&format!("\"{}...\"", "文档".repeat(25))));
+    }
+
+    #[test]
+    fn test_format_comments_response_shows_threads() {
+        let comment = |id: &str, parent: Option<&str>| CommentDetail {
+            id: id.to_string(),
+            comment_type: "question".to_string(),
+            status: "open".to_string(),
+            author: "reviewer".to_string(),
+            selection_start: 0,
+            selection_end: 10,
+            selection_text: None,
+            content: "Why?".to_string(),
+            suggested_text: None,
+            created_at: "2024-01-15T10:30:00Z".to_string(),
+            mentions: vec![],
+            parent_comment_id: parent.map(String::from),
+            resolved_by: None,
+            resolved_at: None,
+            resolution_note: None,
+        };
+        let response = DocArtifactListCommentsResponse {
+            artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
+            comments: vec![
+                comment("c-1", None),
+                comment("c-2", Some("c-1")),
+                comment("c-3", Some("c-1")),
+            ],
+        };
+
+        let output = format_comments_response(&response);
+        assert!(output.contains("**Replies:** 2"));
+        assert_eq!(output.matches("**In reply to:** `c-1`").count(), 2);
+    }
+
+    #[test]
+    fn test_format_read_response_numbered_with_anchors() {
+        let response = DocArtifactReadResponse {
+            artifact_id: "artifact-1".to_string(),
+            filename: "spec.md".to_string(),
+            content: "# Spec\nFirst point\nSecond point".to_string(),
+            content_hash: "hash".to_string(),
+            total_lines: 20,
+            returned_lines: 3,
+            offset: 8,
+            has_more: true,
+            comments: Some(vec![crate::ipc::messages::InlineComment {
+                id: "comment-3".to_string(),
+                comment_type: "question".to_string(),
+                status: "open".to_string(),
+                line_number: 10,
+                author: "critiquer".to_string(),
+                preview: "Why?".to_string(),
+            }]),
+        };
+
+        let output = format_read_response_numbered(&response);
+        assert!(output.contains(" 9 | # Spec\n"));
+        assert!(output.contains("10 | First point [comment-3]\n"));
+        assert!(output.contains("11 | Second point\n"));
+        assert!(output.contains("[comment-3] Line 10"));
+
+        let plain = format_read_response(&response);
+        assert!(plain.contains("```markdown\n# Spec\n"));
+    }
+
+    #[test]
+    fn test_number_lines_skips_comments_outside_page_and_keeps_order() {
+        let comment = |id: &str, line_number| crate::ipc::messages::InlineComment {
+            id: id.to_string(),
+            comment_type: "comment".to_string(),
+            status: "open".to_string(),
+            line_number,
+            author: "reviewer".to_string(),
+            preview: String::new(),
+        };
+        let response = DocArtifactReadResponse {
+            artifact_id: "artifact-1".to_string(),
+            filename: "spec.md".to_string(),
+            content: "a\nb".to_string(),
+            content_hash: "hash".to_string(),
+            total_lines: 10,
+            returned_lines: 2,
+            offset: 4,
+            has_more: true,
+            comments: Some(vec![
+                comment("c-late", 6),
+                comment("c-before", 2),
+                comment("c-first", 5),
+                comment("c-second", 5),
+            ]),
+        };
+
+        assert_eq!(
+            number_lines(&response),
+            "5 | a [c-first] [c-second]\n6 | b [c-late]"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_within_budget_joins_pages_and_stops_at_budget() {
+        use crate::ipc::messages::InlineComment;
+        use crate::ipc::mock::MockIpcClient;
+
+        // Every page is the same two lines; the mock ignores the offset
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/artifacts/art-1",
+            DocArtifactReadResponse {
+                artifact_id: "art-1".to_string(),
+                filename: "spec.md".to_string(),
+                content: "x\ny".to_string(),
+                content_hash: "hash".to_string(),
+                total_lines: 100,
+                returned_lines: 2,
+                offset: 0,
+                has_more: true,
+                comments: Some(vec![InlineComment {
+                    id: "c-1".to_string(),
+                    comment_type: "comment".to_string(),
+                    status: "open".to_string(),
+                    line_number: 6,
+                    author: "reviewer".to_string(),
+                    preview: String::new(),
+                }]),
+            },
+        );
+
+        let document = read_within_budget(&mock, "run-1", "art-1", None, 2, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(document.content, "x\ny\nx\ny\nx");
+        assert_eq!(document.returned_lines, 5);
+        assert!(document.has_more);
+        // Only the first page's copy is kept: line 6 is past every later page's end
+        assert_eq!(document.comments.unwrap().len(), 1);
+        let requests = mock.requests_to("/api/runs/run-1/artifacts/art-1");
+        assert_eq!(requests.len(), 4);
+        assert!(requests[1].contains("\"offset\":2"));
+        assert!(requests[2].contains("\"offset\":4"));
+
+        // A tiny budget still returns the first page
+        let document = read_within_budget(&mock, "run-1", "art-1", None, 2, None, 1)
+            .await
+            .unwrap();
+        assert_eq!(document.content, "x\ny");
+    }
+
+    #[tokio::test]
+    async fn test_edit_body_matches_tool_request_on_the_wire() {
+        let mock = crate::ipc::mock::MockIpcClient::new();
+        let tool_request: crate::ipc::messages::DocArtifactEditRequest =
+            serde_json::from_value(serde_json::json!({
+                "runId": "run-1",
+                "artifactId": "art-1",
+                "editType": "replace_range",
+                "contentHash": "hash",
+                "newContent": "A \"quoted\" line\n🚀",
+                "startOffset": 3,
+                "endOffset": 9,
+                "offsetUnit": "char",
+                "editReason": {
+                    "category": "address_feedback",
+                    "summary": "Quote the error",
+                    "relatedCommentIds": ["c-1"]
+                },
+                "source": "builder",
+                "format": "compact"
+            }))
+            .unwrap();
+
+        let _ = edit_artifact(
+            &mock,
+            &tool_request.run_id,
+            &tool_request.artifact_id,
+            tool_request.edit_type,
+            &tool_request.content_hash,
+            &tool_request.new_content,
+            tool_request.start_offset,
+            tool_request.end_offset,
+            tool_request.insert_offset,
+            tool_request.edit_reason.as_ref(),
+            tool_request.source.as_deref(),
+        )
+        .await;
+
+        let sent = mock.requests_to("/api/runs/run-1/artifacts/art-1/edit");
+        let sent: serde_json::Value = serde_json::from_str(&sent[0]).unwrap();
+        assert_eq!(sent, serde_json::to_value(&tool_request).unwrap());
+    }
+
     // -------------------------------------------------------------------------
     // Format List Response Tests
     // -------------------------------------------------------------------------
@@ -1143,6 +1983,7 @@ mod tests {
                     content_hash: "abcdef123456789012345678".to_string(),
                     created_at: "2024-01-15T10:00:00Z".to_string(),
                     updated_at: "2024-01-15T10:30:00Z".to_string(),
+                    tags: vec!["prd".to_string(), "q3".to_string()],
                 },
                 ArtifactSummary {
                     id: "artifact-2".to_string(),
@@ -1152,6 +1993,7 @@ mod tests {
                     content_hash: "xyz789abcdef0123456789ab".to_string(),
                     created_at: "2024-01-15T10:30:00Z".to_string(),
                     updated_at: "2024-01-15T11:00:00Z".to_string(),
+                    tags: vec![],
                 },
             ],
         };
@@ -1164,6 +2006,36 @@ mod tests {
         assert!(output.contains("Lines: 75"));
         // Hash should be truncated to 8 chars
         assert!(output.contains("abcdef12"));
+        assert!(output.contains("  Tags: prd, q3\n\n"));
+        assert_eq!(output.matches("Tags:").count(), 1);
+
+        let mut tagged = response;
+        filter_artifacts(&mut tagged, Some("Q3"), None);
+        assert_eq!(tagged.artifacts.len(), 1);
+        assert_eq!(tagged.artifacts[0].id, "artifact-1");
+        filter_artifacts(&mut tagged, Some("q3"), Some("design"));
+        assert!(tagged.artifacts.is_empty());
+    }
+
+    #[test]
+    fn test_format_list_response_short_hash_does_not_panic() {
+        let response = DocArtifactListResponse {
+            run_id: "run-123".to_string(),
+            artifacts: vec![ArtifactSummary {
+                id: "artifact-1".to_string(),
+                filename: "メモ.md".to_string(),
+                document_type: "notes".to_string(),
+                total_lines: 3,
+                content_hash: "abc".to_string(),
+                created_at: "2024-01-15T10:00:00Z".to_string(),
+                updated_at: "2024-01-15T10:30:00Z".to_string(),
+                tags: vec![],
+            }],
+        };
+
+        let output = format_list_response(&response);
+        assert!(output.contains("Hash: `abc`"));
+        assert!(output.contains("メモ.md"));
     }
 
     // -------------------------------------------------------------------------
@@ -1174,6 +2046,8 @@ mod tests {
     fn test_format_suggestions_response_empty() {
         let response = DocArtifactListSuggestionsResponse {
             artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
             suggestions: vec![],
         };
 
@@ -1186,6 +2060,8 @@ mod tests {
     fn test_format_suggestions_response_pending() {
         let response = DocArtifactListSuggestionsResponse {
             artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
             suggestions: vec![SuggestionDetail {
                 id: "suggestion-1".to_string(),
                 comment_id: "comment-1".to_string(),
@@ -1203,6 +2079,7 @@ mod tests {
                 accepted_by: None,
                 accepted_at: None,
                 rejection_reason: None,
+                base_content_hash: None,
             }],
         };
 
@@ -1214,12 +2091,48 @@ mod tests {
         assert!(output.contains("writer"));
         assert!(output.contains("chars 0-50"));
         assert!(output.contains("Better clarity"));
+        assert!(output.contains("[-Original-]{+Improved+} content here"));
+    }
+
+    #[test]
+    fn test_format_suggestions_response_diffs_emoji_and_cjk_text() {
+        let response = DocArtifactListSuggestionsResponse {
+            artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
+            suggestions: vec![SuggestionDetail {
+                id: "suggestion-2".to_string(),
+                comment_id: "comment-2".to_string(),
+                artifact_id: "artifact-1".to_string(),
+                edit_type: "full_replace".to_string(),
+                status: "pending".to_string(),
+                suggested_by: "writer".to_string(),
+                start_offset: None,
+                end_offset: None,
+                original_text: Some("✨".repeat(120)),
+                suggested_text: "日本語".repeat(50),
+                rationale: None,
+                created_at: "2024-01-15T10:30:00Z".to_string(),
+                updated_at: "2024-01-15T10:30:00Z".to_string(),
+                accepted_by: None,
+                accepted_at: None,
+                rejection_reason: None,
+                base_content_hash: None,
+            }],
+        };
+
+        let output = format_suggestions_response(&response);
+        assert!(output.contains("```diff"));
+        assert!(output.contains(&format!("[-{}-]", "✨".repeat(120))));
+        assert!(output.contains(&format!("{{+{}+}}", "日本語".repeat(50))));
     }
 
     #[test]
     fn test_format_suggestions_response_accepted() {
         let response = DocArtifactListSuggestionsResponse {
             artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
             suggestions: vec![SuggestionDetail {
                 id: "suggestion-2".to_string(),
                 comment_id: "comment-2".to_string(),
@@ -1237,19 +2150,22 @@ mod tests {
                 accepted_by: Some("human".to_string()),
                 accepted_at: Some("2024-01-15T11:00:00Z".to_string()),
                 rejection_reason: None,
+                base_content_hash: None,
             }],
         };
 
         let output = format_suggestions_response(&response);
         assert!(output.contains("✅")); // accepted icon
         assert!(output.contains("Accepted by:** human"));
-        assert!(output.contains("Accepted at:** 2024-01-15T11:00:00Z"));
+        assert!(output.contains("ago (2024-01-15T11:00:00Z)"));
     }
 
     #[test]
     fn test_format_suggestions_response_rejected() {
         let response = DocArtifactListSuggestionsResponse {
             artifact_id: "artifact-1".to_string(),
+            offset: 0,
+            has_more: false,
             suggestions: vec![SuggestionDetail {
                 id: "suggestion-3".to_string(),
                 comment_id: "comment-3".to_string(),
@@ -1267,6 +2183,7 @@ mod tests {
                 accepted_by: None,
                 accepted_at: None,
                 rejection_reason: Some("Not relevant to scope".to_string()),
+                base_content_hash: None,
             }],
         };
 
@@ -1338,4 +2255,62 @@ mod tests {
         assert!(output.contains("suggestion-rejected"));
         assert!(output.contains("NOT applied"));
     }
+
+    #[test]
+    fn test_preview_edit_applies_at_char_offsets() {
+        let document = DocArtifactReadResponse {
+            artifact_id: "art-1".to_string(),
+            filename: "spec.md".to_string(),
+            content: "Café menu\nSoup\n".to_string(),
+            content_hash: "h1".to_string(),
+            total_lines: 2,
+            returned_lines: 2,
+            offset: 0,
+            has_more: false,
+            comments: None,
+        };
+        let preview = |edit_type, text, start, end, insert| {
+            preview_edit(&document, Some("h1"), edit_type, text, start, end, insert)
+        };
+
+        let replaced = preview(EditType::ReplaceRange, "Bistro", Some(0), Some(4), None).unwrap();
+        assert_eq!(replaced.hash_matches, Some(true));
+        assert_eq!(
+            (replaced.original_chars, replaced.resulting_chars),
+            (15, 17)
+        );
+        assert!(
+            replaced.diff.contains("-Café menu\n+Bistro menu\n"),
+            "{}",
+            replaced.diff
+        );
+
+        let inserted = preview(EditType::Insert, "Bread\n", None, None, Some(10)).unwrap();
+        assert!(inserted.diff.contains("+Bread\n"), "{}", inserted.diff);
+        let appended = preview(EditType::Append, "Salad\n", None, None, None).unwrap();
+        assert_eq!(appended.resulting_chars, 21);
+        let unchanged =
+            preview(EditType::FullReplace, &document.content, None, None, None).unwrap();
+        assert!(unchanged.diff.is_empty());
+        assert!(format_edit_preview(&unchanged).contains("No change to the document."));
+
+        let missing = preview(EditType::Insert, "x", None, None, None).unwrap_err();
+        assert_eq!(missing, "insert_offset is required for this edit type");
+        let reversed = preview(EditType::ReplaceRange, "x", Some(4), Some(2), None).unwrap_err();
+        assert_eq!(reversed, "start_offset must not be after end_offset");
+
+        let stale = preview_edit(
+            &document,
+            Some("h0"),
+            EditType::Append,
+            "x",
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(
+            format_edit_preview(&stale).contains("**Hash mismatch:** the document is now at `h1`")
+        );
+    }
 }
@@ -0,0 +1,301 @@
+//! Run-wide artifact operations.
+//!
+//! Reading or searching every artifact in a run means one IPC call per
+//! artifact. These are independent, so they are issued concurrently with a cap
+//! on how many are in flight, instead of awaiting them one after another.
+
+use crate::ipc::messages::{
+    ArtifactOutcome, ArtifactSummary, DocArtifactReadAllResponse, DocArtifactSearchAllResponse,
+};
+use crate::ipc::traits::IpcClient;
+use crate::tools::artifacts;
+use crate::types::errors::IpcError;
+use futures::stream::{self, StreamExt};
+use std::fmt::Write;
+use std::future::Future;
+
+/// Calls in flight at once when `batch_concurrency` is not configured.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Apply `call` to every item with at most `concurrency` calls in flight.
+/// Results are returned in input order.
+pub async fn run<I, F, Fut>(items: I, concurrency: usize, call: F) -> Vec<Fut::Output>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future,
+{
+    stream::iter(items)
+        .map(call)
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// `call` applied to every artifact in the run. Only listing the artifacts can
+/// fail the whole operation.
+pub(crate) async fn for_each_artifact<C, T, F, Fut>(
+    client: &C,
+    run_id: &str,
+    concurrency: usize,
+    call: F,
+) -> Result<Vec<ArtifactOutcome<T>>, IpcError>
+where
+    C: IpcClient,
+    F: FnMut(ArtifactSummary) -> Fut,
+    Fut: Future<Output = (ArtifactSummary, Result<T, IpcError>)>,
+{
+    let listing = artifacts::list_artifacts(client, run_id).await?;
+    let outcomes = run(listing.artifacts, concurrency, call).await;
+    Ok(outcomes
+        .into_iter()
+        .map(|(artifact, result)| {
+            let (result, error) = match result {
+                Ok(value) => (Some(value), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            ArtifactOutcome {
+                artifact_id: artifact.id,
+                filename: artifact.filename,
+                result,
+                error,
+            }
+        })
+        .collect())
+}
+
+/// Search every artifact in the run for `query`.
+#[allow(clippy::too_many_arguments)]
+pub async fn search_all<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    query: &str,
+    match_type: Option<&str>,
+    scope: Option<&str>,
+    context_lines: Option<i64>,
+    max_results: Option<i64>,
+    concurrency: usize,
+) -> Result<DocArtifactSearchAllResponse, IpcError> {
+    let artifacts = for_each_artifact(client, run_id, concurrency, |artifact| async move {
+        let result = artifacts::search_artifact(
+            client,
+            run_id,
+            &artifact.id,
+            query,
+            match_type,
+            scope,
+            context_lines,
+            max_results,
+        )
+        .await;
+        (artifact, result)
+    })
+    .await?;
+    Ok(DocArtifactSearchAllResponse {
+        run_id: run_id.to_string(),
+        query: query.to_string(),
+        artifacts,
+    })
+}
+
+/// Read the first `limit` lines of every artifact in the run.
+pub async fn read_all<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    limit: i64,
+    concurrency: usize,
+) -> Result<DocArtifactReadAllResponse, IpcError> {
+    let artifacts = for_each_artifact(client, run_id, concurrency, |artifact| async move {
+        let result =
+            artifacts::read_artifact(client, run_id, &artifact.id, Some(0), Some(limit), None)
+                .await;
+        (artifact, result)
+    })
+    .await?;
+    Ok(DocArtifactReadAllResponse {
+        run_id: run_id.to_string(),
+        artifacts,
+    })
+}
+
+/// Format run-wide search results, grouped by artifact. Artifacts without
+/// matches are listed on one line at the end.
+pub fn format_search_all_response(response: &DocArtifactSearchAllResponse) -> String {
+    let total: i64 = response
+        .artifacts
+        .iter()
+        .filter_map(|a| a.result.as_ref())
+        .map(|r| r.total_matches)
+        .sum();
+    let mut output = format!(
+        "## Search Results for: \"{}\" across {} artifacts\n\n**Total matches:** {}\n\n",
+        response.query,
+        response.artifacts.len(),
+        total
+    );
+
+    let mut without_matches = Vec::new();
+    for artifact in &response.artifacts {
+        match (&artifact.result, &artifact.error) {
+            (Some(search), _) if !search.results.is_empty() => {
+                let _ = writeln!(
+                    output,
+                    "### {} (`{}`): {} matches\n",
+                    artifact.filename, artifact.artifact_id, search.total_matches
+                );
+                for result in &search.results {
+                    let _ = write!(
+                        output,
+                        "- Line {} (chars {}-{}): `{}`\n  ```\n  {}\n  ```\n",
+                        result.line_number,
+                        result.char_start,
+                        result.char_end,
+                        result.match_text,
+                        result.context.replace('\n', "\n  ")
+                    );
+                }
+                output.push('\n');
+            }
+            (_, Some(error)) => {
+                let _ = writeln!(
+                    output,
+                    "### {} (`{}`): search failed\n\n{}\n",
+                    artifact.filename, artifact.artifact_id, error
+                );
+            }
+            _ => without_matches.push(artifact.filename.as_str()),
+        }
+    }
+    if !without_matches.is_empty() {
+        let _ = writeln!(output, "**No matches in:** {}", without_matches.join(", "));
+    }
+    output
+}
+
+/// Format every artifact's first page, one section per artifact.
+pub fn format_read_all_response(response: &DocArtifactReadAllResponse) -> String {
+    let mut output = format!(
+        "# Artifacts in Run: {}\n\n**Total:** {} artifacts\n\n",
+        response.run_id,
+        response.artifacts.len()
+    );
+    for artifact in &response.artifacts {
+        match (&artifact.result, &artifact.error) {
+            (Some(document), _) => {
+                output.push_str(&artifacts::format_read_response(document));
+                output.push('\n');
+            }
+            (None, error) => {
+                let _ = writeln!(
+                    output,
+                    "## Document: {}\n\nRead failed: {}\n",
+                    artifact.filename,
+                    error.as_deref().unwrap_or("unknown error")
+                );
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::{DocArtifactListResponse, DocArtifactSearchResponse, SearchMatch};
+    use crate::ipc::mock::MockIpcClient;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn summary(id: &str) -> ArtifactSummary {
+        ArtifactSummary {
+            id: id.to_string(),
+            filename: format!("{}.md", id),
+            document_type: "markdown".to_string(),
+            total_lines: 10,
+            content_hash: "hash".to_string(),
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            updated_at: "2024-01-15T10:00:00Z".to_string(),
+            tags: vec![],
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_bounds_concurrency_and_keeps_order() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        let results = run(0..10, 3, |i| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                // Later items finish first
+                tokio::time::sleep(Duration::from_millis(100 - i * 10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                i * 2
+            }
+        })
+        .await;
+
+        assert_eq!(results, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_search_all_reports_each_artifact() {
+        let run_id = "run-1";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/artifacts",
+            DocArtifactListResponse {
+                run_id: run_id.to_string(),
+                artifacts: vec![summary("spec"), summary("notes"), summary("plan")],
+            },
+        );
+        mock.when_called(
+            "/api/runs/run-1/artifacts/spec/search",
+            DocArtifactSearchResponse {
+                artifact_id: "spec".to_string(),
+                query: "TODO".to_string(),
+                total_matches: 1,
+                results: vec![SearchMatch {
+                    line_number: 3,
+                    char_start: 40,
+                    char_end: 44,
+                    match_text: "TODO".to_string(),
+                    context: "TODO: pick a name".to_string(),
+                    section: None,
+                }],
+            },
+        );
+        mock.when_called(
+            "/api/runs/run-1/artifacts/notes/search",
+            DocArtifactSearchResponse {
+                artifact_id: "notes".to_string(),
+                query: "TODO".to_string(),
+                total_matches: 0,
+                results: vec![],
+            },
+        );
+        // No response configured for "plan": that search fails on its own
+
+        let response = search_all(&mock, run_id, "TODO", None, None, None, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(response.artifacts.len(), 3);
+        assert!(response.artifacts[2].error.is_some());
+
+        let output = format_search_all_response(&response);
+        assert!(output.contains("**Total matches:** 1"));
+        assert!(output.contains("### spec.md (`spec`): 1 matches"));
+        assert!(output.contains("Line 3 (chars 40-44): `TODO`"));
+        assert!(output.contains("### plan.md (`plan`): search failed"));
+        assert!(output.contains("**No matches in:** notes.md"));
+    }
+
+    #[tokio::test]
+    async fn test_listing_failure_fails_the_operation() {
+        let mock = MockIpcClient::new();
+        assert!(read_all(&mock, "run-1", 100, 4).await.is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! An artifact's revision history as a narrative.
+//!
+//! Edits carry a structured reason (category, one-line summary, the comments
+//! they address). The changelog groups an artifact's edits by category so a
+//! human can read what changed and why without diffing versions. Edits made
+//! without a reason are counted at the end.
+
+use crate::ipc::messages::{DocArtifactListEditsResponse, EditCategory, EditRecord};
+use crate::tools::timestamps;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Edits grouped by category in category order, each group oldest first,
+/// and the edits without a reason.
+pub fn group_by_reason(
+    edits: &[EditRecord],
+) -> (BTreeMap<EditCategory, Vec<&EditRecord>>, Vec<&EditRecord>) {
+    let mut groups: BTreeMap<EditCategory, Vec<&EditRecord>> = BTreeMap::new();
+    let mut unexplained = Vec::new();
+    for edit in edits {
+        match &edit.edit_reason {
+            Some(reason) => groups.entry(reason.category).or_default().push(edit),
+            None => unexplained.push(edit),
+        }
+    }
+    (groups, unexplained)
+}
+
+/// Formats the edit history as a changelog: one section per reason category
+/// listing each change with its author, age, and the comments it addresses.
+pub fn format_changelog(response: &DocArtifactListEditsResponse) -> String {
+    if response.edits.is_empty() {
+        return format!("No edits to {} yet.", response.artifact_id);
+    }
+
+    let mut output = format!(
+        "## Changelog of {}\n\n{} edit(s)\n",
+        response.artifact_id,
+        response.edits.len()
+    );
+    let (groups, unexplained) = group_by_reason(&response.edits);
+    for (category, edits) in &groups {
+        let _ = write!(output, "\n### {}\n\n", category.title());
+        for edit in edits {
+            let Some(reason) = &edit.edit_reason else {
+                continue;
+            };
+            let _ = write!(
+                output,
+                "- {} ({}, {})",
+                reason.summary,
+                edit.author,
+                timestamps::age(&edit.created_at)
+            );
+            if !reason.related_comment_ids.is_empty() {
+                let _ = write!(
+                    output,
+                    ", addresses {}",
+                    reason.related_comment_ids.join(", ")
+                );
+            }
+            output.push('\n');
+        }
+    }
+    if !unexplained.is_empty() {
+        let mut authors: Vec<&str> = unexplained.iter().map(|e| e.author.as_str()).collect();
+        authors.sort_unstable();
+        authors.dedup();
+        let _ = write!(
+            output,
+            "\n{} edit(s) without a reason, by {}\n",
+            unexplained.len(),
+            authors.join(", ")
+        );
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::EditReason;
+
+    fn edit(id: &str, author: &str, reason: Option<(EditCategory, &str, &[&str])>) -> EditRecord {
+        EditRecord {
+            id: id.to_string(),
+            author: author.to_string(),
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            edit_reason: reason.map(|(category, summary, comments)| EditReason {
+                category,
+                summary: summary.to_string(),
+                related_comment_ids: comments.iter().map(|c| c.to_string()).collect(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_changelog_groups_edits_by_category() {
+        let response = DocArtifactListEditsResponse {
+            artifact_id: "spec".to_string(),
+            edits: vec![
+                edit(
+                    "e1",
+                    "writer",
+                    Some((EditCategory::Addition, "Add rollout plan", &[])),
+                ),
+                edit(
+                    "e2",
+                    "writer",
+                    Some((EditCategory::AddressFeedback, "Define TTL", &["c-1", "c-2"])),
+                ),
+                edit("e3", "human", None),
+                edit("e6", "writer", None),
+                edit(
+                    "e4",
+                    "editor",
+                    Some((EditCategory::Addition, "Add FAQ", &[])),
+                ),
+                edit("e5", "human", None),
+            ],
+        };
+        let output = format_changelog(&response);
+
+        let feedback = output.find("### Review feedback").unwrap();
+        let additions = output.find("### Additions").unwrap();
+        assert!(feedback < additions, "{}", output);
+        assert!(output.contains("- Define TTL (writer, "));
+        assert!(output.contains(", addresses c-1, c-2\n"));
+        let rollout = output.find("- Add rollout plan").unwrap();
+        assert!(rollout > additions && rollout < output.find("- Add FAQ").unwrap());
+        assert!(output.ends_with("\n3 edit(s) without a reason, by human, writer\n"));
+
+        let empty = DocArtifactListEditsResponse {
+            artifact_id: "spec".to_string(),
+            edits: vec![],
+        };
+        assert_eq!(format_changelog(&empty), "No edits to spec yet.");
+    }
+
+    #[test]
+    fn test_plain_text_reasons_are_other_changes() {
+        let record: EditRecord = serde_json::from_value(serde_json::json!({
+            "id": "e1",
+            "author": "builder",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "editReason": "Clarify wording"
+        }))
+        .unwrap();
+        let reason = record.edit_reason.unwrap();
+        assert_eq!(reason.category, EditCategory::Other);
+        assert_eq!(reason.summary, "Clarify wording");
+        assert!(reason.related_comment_ids.is_empty());
+    }
+}
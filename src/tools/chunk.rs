@@ -0,0 +1,194 @@
+//! Size caps on formatted tool output.
+//!
+//! Output longer than the cap is cut at a line boundary and ends with a
+//! continuation token. Passing the token back with the same arguments returns
+//! the next chunk. Only read-only tools are cut: calling a mutating tool again
+//! would repeat what it did. Tokens count lines rather than characters so they
+//! stay valid when the output changes slightly between calls (e.g., relative
+//! timestamps). A line longer than the cap is split, and its token also
+//! counts the characters already returned from that line.
+
+use crate::tools::text;
+use std::fmt::Write;
+
+const TOKEN_PREFIX: &str = "line:";
+const CHAR_SEPARATOR: &str = ":char:";
+
+/// Where a chunk starts: a line, and a character offset into that line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub char: usize,
+}
+
+impl Position {
+    fn token(self) -> String {
+        if self.char == 0 {
+            format!("{}{}", TOKEN_PREFIX, self.line)
+        } else {
+            format!(
+                "{}{}{}{}",
+                TOKEN_PREFIX, self.line, CHAR_SEPARATOR, self.char
+            )
+        }
+    }
+}
+
+/// Parse a continuation token (`line:N` or `line:N:char:M`) into the position
+/// to resume from.
+pub fn parse_token(token: &str) -> Result<Position, String> {
+    let invalid = || format!("Invalid continuation token '{}'", token);
+    let rest = token.strip_prefix(TOKEN_PREFIX).ok_or_else(invalid)?;
+    let (line, char) = match rest.split_once(CHAR_SEPARATOR) {
+        Some((line, char)) => (line, char.parse().map_err(|_| invalid())?),
+        None => (rest, 0),
+    };
+    let line = line.parse().map_err(|_| invalid())?;
+    Ok(Position { line, char })
+}
+
+/// The part of `output` starting at `start`, cut to at most `max_chars`
+/// characters (plus a footer when more remains). A `max_chars` of 0 disables the cap.
+pub fn chunk(output: &str, start: Position, max_chars: usize) -> String {
+    if start == Position::default() && (max_chars == 0 || output.chars().count() <= max_chars) {
+        return output.to_string();
+    }
+
+    let lines: Vec<&str> = output.lines().collect();
+    let first = lines
+        .get(start.line)
+        .map(|line| skip_chars(line, start.char));
+    let Some(first) = first.filter(|rest| !rest.is_empty() || start.char == 0) else {
+        return format!("No more output: the response has {} lines.", lines.len());
+    };
+
+    let mut body = String::new();
+    let mut used = 0;
+    let mut next = start;
+    for (i, line) in std::iter::once(first)
+        .chain(lines[start.line + 1..].iter().copied())
+        .enumerate()
+    {
+        let len = line.chars().count();
+        if max_chars > 0 && used + len + 1 > max_chars {
+            // Split a line that alone exceeds the cap rather than drop its tail.
+            if i == 0 {
+                let taken = text::prefix(line, max_chars.saturating_sub(1).max(1));
+                body.push_str(taken);
+                body.push('\n');
+                next.char += taken.chars().count();
+            }
+            break;
+        }
+        body.push_str(line);
+        body.push('\n');
+        used += len + 1;
+        next = Position {
+            line: start.line + i + 1,
+            char: 0,
+        };
+    }
+
+    if next.line < lines.len() {
+        let _ = write!(
+            body,
+            "\n---\n_Output truncated: lines {}-{} of {}. Call again with the same arguments and `continuation: \"{}\"` for the rest._\n",
+            start.line + 1,
+            if next.char > 0 { next.line + 1 } else { next.line },
+            lines.len(),
+            next.token()
+        );
+    }
+    body
+}
+
+/// `line` without its first `n` characters.
+fn skip_chars(line: &str, n: usize) -> &str {
+    line.char_indices().nth(n).map_or("", |(i, _)| &line[i..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_output_is_unchanged() {
+        assert_eq!(chunk("a\nb\n", Position::default(), 100), "a\nb\n");
+        assert_eq!(chunk("a\nb\n", Position::default(), 0), "a\nb\n");
+    }
+
+    #[test]
+    fn test_truncates_at_line_boundary_with_token() {
+        let output = "aaaa\nbbbb\ncccc\ndddd\n";
+        let first = chunk(output, Position::default(), 12);
+        assert!(first.starts_with("aaaa\nbbbb\n"));
+        assert!(!first.contains("cccc"));
+        assert!(first.contains("lines 1-2 of 4"));
+        assert!(first.contains("continuation: \"line:2\""));
+
+        let next = parse_token("line:2").unwrap();
+        assert_eq!(next, Position { line: 2, char: 0 });
+        let second = chunk(output, next, 12);
+        assert!(second.starts_with("cccc\ndddd\n"));
+        assert!(!second.contains("continuation"));
+    }
+
+    /// Follow continuation tokens from the start and return the chunks.
+    fn all_chunks(output: &str, max_chars: usize) -> Vec<String> {
+        let token = regex::Regex::new(r#"continuation: "([^"]+)""#).unwrap();
+        let mut chunks = vec![chunk(output, Position::default(), max_chars)];
+        while let Some(next) = token.captures(chunks.last().unwrap()) {
+            let start = parse_token(&next[1]).unwrap();
+            chunks.push(chunk(output, start, max_chars));
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_oversized_line_is_split_without_losing_text() {
+        let long = "x".repeat(25) + "y";
+        let output = format!(
+            "{}
+next
+",
+            long
+        );
+        let chunks = all_chunks(&output, 10);
+        assert!(chunks[0].starts_with(&format!("{}\n", "x".repeat(9))));
+        assert!(chunks[0].contains("continuation: \"line:0:char:9\""));
+
+        let body: String = chunks
+            .iter()
+            .map(|c| c.split("\n---\n").next().unwrap().replace('\n', ""))
+            .collect();
+        assert_eq!(body, format!("{}next", long));
+    }
+
+    #[test]
+    fn test_oversized_last_line_still_has_a_token() {
+        // e.g. a large JSON response serialized on one line
+        let output = "a\n".to_string() + &"z".repeat(30);
+        let chunks = all_chunks(&output, 10);
+        assert!(chunks.len() > 2);
+        let tail: String = chunks[1..]
+            .iter()
+            .map(|c| c.split("\n---\n").next().unwrap().trim_end())
+            .collect();
+        assert_eq!(tail, "z".repeat(30));
+    }
+
+    #[test]
+    fn test_past_end_and_invalid_tokens() {
+        let past_end = Position { line: 5, char: 0 };
+        assert!(chunk("a\n", past_end, 100).starts_with("No more output"));
+        let past_line_end = Position { line: 0, char: 5 };
+        assert!(chunk("abc\n", past_line_end, 2).starts_with("No more output"));
+        assert_eq!(
+            parse_token("line:3:char:40").unwrap(),
+            Position { line: 3, char: 40 }
+        );
+        assert!(parse_token("line:abc").is_err());
+        assert!(parse_token("line:3:char:").is_err());
+        assert!(parse_token("42").is_err());
+    }
+}
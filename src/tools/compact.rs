@@ -0,0 +1,1635 @@
+//! Token-efficient renderings of tool responses (`OutputFormat::Compact`).
+//!
+//! Same information as the markdown formatters, minus emoji, banners, and
+//! prose: key=value headers, `|`-separated tables for lists, and short
+//! previews of quoted text. Protocol and document content are never truncated.
+
+use crate::ipc::messages::{
+    ArtifactBackupList, ArtifactCommit, DocArtifactAcceptSuggestionResponse,
+    DocArtifactAddCommentResponse, DocArtifactCreateResponse, DocArtifactEditResponse,
+    DocArtifactFeedbackSummary, DocArtifactListCommentsResponse, DocArtifactListEditsResponse,
+    DocArtifactListResponse, DocArtifactListSuggestionsResponse, DocArtifactReadAllResponse,
+    DocArtifactReadResponse, DocArtifactRejectSuggestionResponse,
+    DocArtifactResolveCommentResponse, DocArtifactReviewContext, DocArtifactSearchAllResponse,
+    DocArtifactSearchResponse, DocArtifactSetTagsResponse, DocArtifactSuggestEditResponse,
+    EditPreview, EventDump, GetAgentCapabilitiesResponse, GetArtifactReadersResponse,
+    GetHandoffsResponse, GetImpedimentResolutionResponse, GetImpedimentsResponse,
+    GetLatestSummaryResponse, GetMessageDeliveriesResponse, GetNotificationsResponse,
+    GetPlaybookResponse, GetProtocolResponse, GetRunConfigResponse, GetRunContextResponse,
+    GetRunStatusResponse, GetRunUsageResponse, HotwireResponse, ListActiveRunsResponse,
+    ListCompletedTasksResponse, ListImpedimentTemplatesResponse, ListPlaybooksResponse,
+    MessageThreads, MyContext, PairResponse, ProtocolStepsListResponse, RenderedArtifact,
+    ResumeRunResponse, ReviewImport, RunContext, RunExport, SearchRunsResponse, Staleness,
+    SuggestionSweep,
+};
+use crate::tools::tables::{table, TableStyle};
+use crate::tools::{artifacts, protocol, text, timestamps, usage};
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// Max characters of quoted text (selections, suggestions, context) in list rows.
+const PREVIEW_CHARS: usize = 60;
+
+/// Single-line preview of `text`, truncated to `PREVIEW_CHARS` characters.
+fn preview(text: &str) -> String {
+    flat_preview(text).into_owned()
+}
+
+/// [`preview`], borrowing `text` when it is already a short single line.
+fn flat_preview(text: &str) -> Cow<'_, str> {
+    let flat = !text.starts_with(' ')
+        && !text.ends_with(' ')
+        && !text.contains("  ")
+        && !text.contains(|c: char| c.is_whitespace() && c != ' ');
+    if flat {
+        return text::truncate(text, PREVIEW_CHARS);
+    }
+    let joined = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    Cow::Owned(text::truncate(&joined, PREVIEW_CHARS).into_owned())
+}
+
+/// " offset=100 has_more=true" for one page of a longer listing; empty otherwise.
+fn page(offset: i64, has_more: bool) -> String {
+    if offset == 0 && !has_more {
+        return String::new();
+    }
+    format!(" offset={} has_more={}", offset, has_more)
+}
+
+fn or_dash(value: Option<&str>) -> String {
+    value.unwrap_or("-").to_string()
+}
+
+// =============================================================================
+// PROTOCOL / RUN STATUS
+// =============================================================================
+
+pub fn format_protocol_response(response: &GetProtocolResponse) -> String {
+    let mut output = format!(
+        "run={} playbook={}\n",
+        response.run_id, response.template_name
+    );
+    if let Some(caps) = &response.capabilities {
+        if caps.can_resolve_impediments {
+            output.push_str("capabilities: resolve_impediment\n");
+        }
+        let denied: Vec<&str> = [
+            (!caps.can_edit_artifacts).then_some("doc_edits"),
+            (!caps.can_end_run).then_some("request_end_run"),
+            (!caps.can_reject_comments).then_some("reject_comments"),
+            (!caps.can_close_own_comments).then_some("close_own_comments"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !denied.is_empty() {
+            let _ = writeln!(output, "not_permitted: {}", denied.join(","));
+        }
+    }
+    if !response.mcp_servers.is_empty() {
+        let names: Vec<&str> = response
+            .mcp_servers
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        let _ = writeln!(output, "mcp_servers: {}", names.join(","));
+    }
+    output.push_str("\n[protocol]\n");
+    output.push_str(response.playbook_protocol.as_deref().unwrap_or("(none)"));
+    if let Some(role) = response.role_protocol.as_deref().filter(|r| !r.is_empty()) {
+        output.push_str("\n\n[role]\n");
+        output.push_str(role);
+    }
+    output.push_str("\n\n[initialization]\n");
+    output.push_str(
+        response
+            .initialization_condition
+            .as_deref()
+            .unwrap_or("(none)"),
+    );
+    output.push('\n');
+    output
+}
+
+/// Keeps the plain "Status: x" line the mock agent's status parser looks for.
+pub fn format_run_status_response(response: &GetRunStatusResponse) -> String {
+    let agents = if response.connected_agents.is_empty() {
+        "-".to_string()
+    } else {
+        response
+            .connected_agents
+            .iter()
+            .map(|a| match a.progress.as_ref().filter(|p| !p.is_empty()) {
+                Some(progress) => format!(
+                    "{}={}({})[{}]",
+                    a.role_id,
+                    a.session_name,
+                    a.agent_type,
+                    progress.summary()
+                ),
+                None => format!("{}={}({})", a.role_id, a.session_name, a.agent_type),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!(
+        "Run ID: {}\nStatus: {}\nPhase: {}\nNext: {}\nTemplate: {}\nHas Protocol: {}\nAgents: {}\n",
+        response.run_id,
+        response.status,
+        response.phase,
+        protocol::next_statuses(&response.status),
+        response.template_name,
+        response.has_protocol,
+        agents
+    )
+}
+
+pub fn format_agent_capabilities(response: &GetAgentCapabilitiesResponse) -> String {
+    let rows = response
+        .agents
+        .iter()
+        .map(|agent| {
+            let caps = agent.capabilities.as_ref();
+            vec![
+                agent.role_id.clone(),
+                agent.session_name.clone(),
+                caps.map_or("-".to_string(), |c| c.languages.join(",")),
+                caps.map_or("-".to_string(), |c| c.filesystem_access.to_string()),
+                caps.map_or("-".to_string(), |c| c.can_run_tests.to_string()),
+                or_dash(
+                    caps.and_then(|c| c.max_context_tokens)
+                        .map(|t| t.to_string())
+                        .as_deref(),
+                ),
+            ]
+        })
+        .collect();
+    format!(
+        "agents: {}\n{}\n",
+        response.agents.len(),
+        table(
+            TableStyle::Compact,
+            &["role", "session", "languages", "fs", "tests", "max_context"],
+            rows
+        )
+    )
+}
+
+pub fn format_task_board(response: &GetHandoffsResponse) -> String {
+    let rows = response
+        .handoffs
+        .iter()
+        .flat_map(|h| {
+            h.tasks.iter().map(move |t| {
+                vec![
+                    t.id.clone(),
+                    h.from.clone(),
+                    h.id.clone(),
+                    preview(&t.title),
+                    preview(&t.acceptance_criteria.join("; ")),
+                    t.artifacts.join(","),
+                ]
+            })
+        })
+        .collect();
+    let untasked = response
+        .handoffs
+        .iter()
+        .filter(|h| h.tasks.is_empty())
+        .map(|h| {
+            vec![
+                h.id.clone(),
+                h.from.clone(),
+                timestamps::age(&h.created_at),
+                preview(&h.summary),
+            ]
+        })
+        .collect();
+    format!(
+        "role={} handoffs: {}\n{}\n{}\n",
+        response.role,
+        response.handoffs.len(),
+        table(
+            TableStyle::Compact,
+            &["task", "from", "handoff", "title", "criteria", "artifacts"],
+            rows
+        ),
+        table(
+            TableStyle::Compact,
+            &["handoff", "from", "age", "summary"],
+            untasked
+        )
+    )
+}
+
+pub fn format_impediments(response: &GetImpedimentsResponse) -> String {
+    let rows = response
+        .impediments
+        .iter()
+        .map(|i| {
+            vec![
+                i.id.to_string(),
+                i.impediment_type.clone(),
+                i.status.clone(),
+                timestamps::age(&i.status_since),
+                i.escalation_level.to_string(),
+                i.escalate_at
+                    .as_deref()
+                    .map_or("-".to_string(), timestamps::age),
+                i.raised_by.clone(),
+                preview(&i.description),
+            ]
+        })
+        .collect();
+    format!(
+        "run={} impediments: {}\n{}\n",
+        response.run_id,
+        response.impediments.len(),
+        table(
+            TableStyle::Compact,
+            &[
+                "id",
+                "type",
+                "status",
+                "since",
+                "level",
+                "escalates",
+                "by",
+                "description"
+            ],
+            rows
+        )
+    )
+}
+
+pub fn format_impediment_resolution(response: &GetImpedimentResolutionResponse) -> String {
+    let response_text = match &response.response {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+        None => "-".to_string(),
+    };
+    format!(
+        "impediment={} status={} by={} at={}\nresponse={}\n",
+        response.impediment_id,
+        response.status,
+        or_dash(response.resolved_by.as_deref()),
+        or_dash(response.resolved_at.as_deref()),
+        response_text
+    )
+}
+
+pub fn format_notifications(response: &GetNotificationsResponse) -> String {
+    let rows = response
+        .notifications
+        .iter()
+        .map(|n| {
+            vec![
+                n.id.clone(),
+                n.kind.clone(),
+                or_dash(n.from.as_deref()),
+                or_dash(n.artifact_id.as_deref()),
+                or_dash(n.comment_id.as_deref()),
+                n.priority.as_str().to_string(),
+                n.acknowledged.to_string(),
+                timestamps::age(&n.created_at),
+                preview(&n.summary),
+            ]
+        })
+        .collect();
+    format!(
+        "role={} notifications: {}\n{}\n",
+        response.role,
+        response.notifications.len(),
+        table(
+            TableStyle::Compact,
+            &["id", "kind", "from", "artifact", "comment", "priority", "acked", "age", "summary"],
+            rows
+        )
+    )
+}
+
+pub fn format_steps(response: &ProtocolStepsListResponse) -> String {
+    let done = response.steps.iter().filter(|step| step.completed).count();
+    let rows = response
+        .steps
+        .iter()
+        .map(|step| {
+            vec![
+                step.id.clone(),
+                step.completed.to_string(),
+                step.completed_at
+                    .as_deref()
+                    .map_or("-".to_string(), timestamps::age),
+                preview(&step.title),
+                or_dash(step.note.as_deref()),
+            ]
+        })
+        .collect();
+    format!(
+        "role={} steps: {}/{} done\n{}\n",
+        response.role,
+        done,
+        response.steps.len(),
+        table(
+            TableStyle::Compact,
+            &["id", "done", "completed", "title", "note"],
+            rows
+        )
+    )
+}
+
+pub fn format_run_usage(response: &GetRunUsageResponse) -> String {
+    let rows = response
+        .usage
+        .iter()
+        .map(|row| {
+            vec![
+                row.role.clone(),
+                row.model.clone(),
+                row.input_tokens.to_string(),
+                row.output_tokens.to_string(),
+                row.tool_calls.to_string(),
+                usage::format_cost(row.cost_usd),
+            ]
+        })
+        .collect();
+    format!(
+        "run={} rows: {}\n{}\n",
+        response.run_id,
+        response.usage.len(),
+        table(
+            TableStyle::Compact,
+            &["role", "model", "input", "output", "tools", "cost"],
+            rows
+        )
+    )
+}
+
+pub fn format_latest_summary(response: &GetLatestSummaryResponse) -> String {
+    match &response.summary {
+        Some(summary) => format!(
+            "run={} role={} artifact={} posted={}\n{}\n",
+            response.run_id,
+            summary.role,
+            or_dash(summary.current_artifact.as_deref()),
+            timestamps::age(&summary.created_at),
+            summary.summary
+        ),
+        None => format!("run={} summary=-\n", response.run_id),
+    }
+}
+
+pub fn format_message_threads(threads: &MessageThreads) -> String {
+    let rows = threads
+        .messages
+        .iter()
+        .map(|m| {
+            vec![
+                m.thread_id.clone(),
+                m.event.id.clone(),
+                or_dash(m.event.reply_to_event_id.as_deref()),
+                or_dash(m.event.source.as_deref()),
+                timestamps::age(&m.event.created_at),
+                preview(&m.event.content),
+            ]
+        })
+        .collect();
+    format!(
+        "run={} threads={} messages: {}\n{}\n",
+        threads.run_id,
+        threads.threads,
+        threads.messages.len(),
+        table(
+            TableStyle::Compact,
+            &["thread", "id", "reply_to", "from", "age", "message"],
+            rows
+        )
+    )
+}
+
+pub fn format_message_deliveries(response: &GetMessageDeliveriesResponse) -> String {
+    let rows = response
+        .deliveries
+        .iter()
+        .map(|d| {
+            vec![
+                d.recipient.clone(),
+                timestamps::age(&d.delivered_at),
+                d.acknowledged_at
+                    .as_deref()
+                    .map_or("-".to_string(), timestamps::age),
+            ]
+        })
+        .collect();
+    format!(
+        "run={} event={} from={} broadcast={} priority={} requires_ack={} deliveries: {}\n{}\n",
+        response.run_id,
+        response.event_id,
+        response.from,
+        response.broadcast,
+        response.priority.as_str(),
+        response.requires_ack,
+        response.deliveries.len(),
+        table(
+            TableStyle::Compact,
+            &["recipient", "delivered", "acknowledged"],
+            rows
+        )
+    )
+}
+
+pub fn format_impediment_templates(response: &ListImpedimentTemplatesResponse) -> String {
+    let rows = response
+        .templates
+        .iter()
+        .map(|t| {
+            vec![
+                t.id.clone(),
+                t.impediment_type.as_str().to_string(),
+                t.response_format.as_ref().map_or("-".to_string(), |form| {
+                    form.fields
+                        .iter()
+                        .map(|f| f.id.as_str())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                }),
+                preview(&t.title),
+                preview(&t.description),
+            ]
+        })
+        .collect();
+    format!(
+        "run={} playbook={} templates: {}\n{}\n",
+        response.run_id,
+        or_dash(response.playbook_id.as_deref()),
+        response.templates.len(),
+        table(
+            TableStyle::Compact,
+            &["id", "type", "fields", "title", "lead_in"],
+            rows
+        )
+    )
+}
+
+pub fn format_completed_tasks(response: &ListCompletedTasksResponse) -> String {
+    let rows = response
+        .tasks
+        .iter()
+        .map(|t| {
+            let results = t.results.clone().unwrap_or_default();
+            let passed = results.tests.iter().filter(|run| run.passed).count();
+            vec![
+                t.id.clone(),
+                t.role.clone(),
+                timestamps::age(&t.completed_at),
+                if results.artifact_ids.is_empty() {
+                    "-".to_string()
+                } else {
+                    results.artifact_ids.join(",")
+                },
+                if results.tests.is_empty() {
+                    "-".to_string()
+                } else {
+                    format!("{}/{}", passed, results.tests.len())
+                },
+                results.links.len().to_string(),
+                preview(&t.task_description),
+            ]
+        })
+        .collect();
+    format!(
+        "run={} completed: {}\n{}\n",
+        response.run_id,
+        response.tasks.len(),
+        table(
+            TableStyle::Compact,
+            &["id", "role", "age", "artifacts", "tests", "links", "task"],
+            rows
+        )
+    )
+}
+
+/// `key=value` pairs for the set fields of a run context.
+fn format_run_context_fields(context: &RunContext) -> String {
+    let fields = [
+        ("worktree", &context.worktree_path),
+        ("branch", &context.branch),
+        ("test", &context.test_command),
+        ("pm", &context.package_manager),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(key, value)| value.as_deref().map(|v| format!("{}={}", key, v)))
+        .chain(
+            context
+                .env
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value)),
+        )
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn format_run_context(response: &GetRunContextResponse) -> String {
+    let fields = format_run_context_fields(&response.context);
+    if fields.is_empty() {
+        return format!("run={} context=-\n", response.run_id);
+    }
+    format!(
+        "run={} {} updated_by={}\n",
+        response.run_id,
+        fields,
+        or_dash(response.context.updated_by.as_deref())
+    )
+}
+
+pub fn format_artifact_readers(response: &GetArtifactReadersResponse) -> String {
+    let rows = response
+        .readers
+        .iter()
+        .map(|r| {
+            vec![
+                r.role.clone(),
+                r.content_hash.clone(),
+                r.is_current(&response.content_hash).to_string(),
+                r.read_to_end.to_string(),
+                timestamps::age(&r.read_at),
+            ]
+        })
+        .collect();
+    format!(
+        "run={} artifact={} hash={} readers: {}\n{}\n",
+        response.run_id,
+        response.artifact_id,
+        response.content_hash,
+        response.readers.len(),
+        table(
+            TableStyle::Compact,
+            &["role", "hash", "current", "to_end", "read"],
+            rows
+        )
+    )
+}
+
+pub fn format_run_config(config: &GetRunConfigResponse) -> String {
+    let mut output = format!(
+        "run={} playbook={} project={} started_by={} artifacts={} answers={}\nintent: {}\n",
+        config.run_id,
+        config.playbook_id,
+        or_dash(config.project_path.as_deref()),
+        or_dash(config.started_by.as_deref()),
+        config.selected_artifacts.len(),
+        config.wizard_answers.len(),
+        preview(&config.intent)
+    );
+    if let Some(condition) = &config.initialization_condition {
+        output.push_str(&format!("init: {}\n", preview(condition)));
+    }
+    if !config.selected_artifacts.is_empty() {
+        let rows = config
+            .selected_artifacts
+            .iter()
+            .map(|a| vec![a.path.clone(), a.action.clone()])
+            .collect();
+        output.push_str(&table(TableStyle::Compact, &["artifact", "action"], rows));
+        output.push('\n');
+    }
+    if !config.wizard_answers.is_empty() {
+        let rows = config
+            .wizard_answers
+            .iter()
+            .map(|a| vec![preview(&a.question), preview(&a.answer)])
+            .collect();
+        output.push_str(&table(TableStyle::Compact, &["question", "answer"], rows));
+        output.push('\n');
+    }
+    output
+}
+
+pub fn format_my_context(context: &MyContext) -> String {
+    format!(
+        "session={} project={} agent={} socket={}\nrun={} role={} status={} can_resolve_impediments={} tracked_documents={}\n",
+        or_dash(context.session_name.as_deref()),
+        or_dash(context.project_dir.as_deref()),
+        or_dash(context.agent_type.as_deref()),
+        or_dash(context.socket_path.as_deref()),
+        or_dash(context.run_id.as_deref()),
+        or_dash(context.role.as_deref()),
+        or_dash(context.run_status.as_deref()),
+        context
+            .capabilities
+            .as_ref()
+            .map_or("-".to_string(), |c| c.can_resolve_impediments.to_string()),
+        context.tracked_documents
+    )
+}
+
+// =============================================================================
+// TERMINAL WORKFLOW
+// =============================================================================
+
+pub fn format_hotwire_response(response: &HotwireResponse) -> String {
+    match response {
+        HotwireResponse::Started(started) => format!(
+            "started run={} playbook={} role={} (primary agent)\n\n{}\n",
+            started.run_id, started.playbook, started.role, started.protocol
+        ),
+        HotwireResponse::NeedsConfirmation(needs_conf) => format!(
+            "needs_confirmation pending_run={} suggested_playbook={}\n{}\n\
+            Wait for the user to confirm in the app, then call get_protocol(run_id, role).\n",
+            needs_conf.pending_run_id,
+            or_dash(needs_conf.suggested_playbook.as_deref()),
+            needs_conf.message
+        ),
+        HotwireResponse::Error { error } => format!("error: failed to start workflow: {}\n", error),
+    }
+}
+
+pub fn format_pair_response(response: &PairResponse) -> String {
+    match response {
+        PairResponse::Joined(joined) => format!(
+            "joined run={} playbook={} role={} ({}) (secondary agent)\n\
+            primary_status={} artifact={}\n\
+            summary: {}\n{}\n{}\n",
+            joined.run_id,
+            joined.playbook,
+            joined.role,
+            joined.role_name,
+            joined.context.primary_status,
+            or_dash(joined.context.current_artifact.as_deref()),
+            joined.context.conversation_summary,
+            joined
+                .context
+                .project
+                .as_ref()
+                .map(|project| format!("project: {}\n", format_run_context_fields(project)))
+                .unwrap_or_default(),
+            joined.protocol
+        ),
+        PairResponse::NeedsSelection(needs_sel) => {
+            let rows = needs_sel
+                .pending_runs
+                .iter()
+                .map(|run| {
+                    vec![
+                        run.run_id.clone(),
+                        run.playbook.clone(),
+                        run.role_needed.clone(),
+                        preview(&run.intent),
+                    ]
+                })
+                .collect();
+            format!(
+                "needs_selection: {}\n{}\n\
+                Select a run in the app, then call get_protocol(run_id, role).\n",
+                needs_sel.message,
+                table(
+                    TableStyle::Compact,
+                    &["run", "playbook", "role_needed", "intent"],
+                    rows
+                )
+            )
+        }
+        PairResponse::NoneAvailable { message } => format!("none: {}\n", message),
+        PairResponse::ProjectMismatch(mismatch) => format!(
+            "project_mismatch: {}\nrequired={} current={}\n",
+            mismatch.message, mismatch.required_path, mismatch.current_path
+        ),
+        PairResponse::Error { error } => format!("error: failed to join workflow: {}\n", error),
+    }
+}
+
+pub fn format_resume_response(response: &ResumeRunResponse) -> String {
+    match response {
+        ResumeRunResponse::Resumed(resumed) => {
+            let digest = &resumed.digest;
+            let messages = digest
+                .messages
+                .iter()
+                .map(|m| {
+                    vec![
+                        timestamps::age(&m.created_at),
+                        m.source.clone(),
+                        preview(&m.content),
+                    ]
+                })
+                .collect();
+            let edits = digest
+                .edits
+                .iter()
+                .map(|e| {
+                    vec![
+                        e.artifact_id.clone(),
+                        e.filename.clone(),
+                        e.edit_count.to_string(),
+                        e.edited_by.join(","),
+                        timestamps::age(&e.updated_at),
+                    ]
+                })
+                .collect();
+            let comments = digest
+                .open_comments
+                .iter()
+                .map(|c| {
+                    vec![
+                        c.comment_id.clone(),
+                        c.comment_type.clone(),
+                        c.artifact_id.clone(),
+                        c.author.clone(),
+                        preview(&c.content),
+                    ]
+                })
+                .collect();
+            format!(
+                "resumed run={} playbook={} role={} ({}) away_since={}\n\
+                messages: {}\n{}\n\
+                edits: {}\n{}\n\
+                open_comments: {}\n{}\n\n{}\n",
+                resumed.run_id,
+                resumed.playbook,
+                resumed.role,
+                resumed.role_name,
+                resumed
+                    .away_since
+                    .as_deref()
+                    .map(timestamps::age)
+                    .unwrap_or_else(|| "-".to_string()),
+                digest.messages.len(),
+                table(TableStyle::Compact, &["age", "source", "content"], messages),
+                digest.edits.len(),
+                table(
+                    TableStyle::Compact,
+                    &["artifact", "filename", "edits", "by", "age"],
+                    edits
+                ),
+                digest.open_comments.len(),
+                table(
+                    TableStyle::Compact,
+                    &["id", "type", "artifact", "author", "content"],
+                    comments
+                ),
+                resumed.protocol
+            )
+        }
+        ResumeRunResponse::NoneAvailable { message } => format!("none: {}\n", message),
+        ResumeRunResponse::Error { error } => format!("error: failed to resume run: {}\n", error),
+    }
+}
+
+pub fn format_active_runs(response: &ListActiveRunsResponse) -> String {
+    if response.runs.is_empty() {
+        return "runs: 0\n".to_string();
+    }
+    let rows = response
+        .runs
+        .iter()
+        .map(|run| {
+            vec![
+                run.run_id.clone(),
+                run.playbook.clone(),
+                run.status.clone(),
+                or_dash(run.my_role.as_deref()),
+                timestamps::age(&run.created_at),
+                preview(&run.intent),
+            ]
+        })
+        .collect();
+    format!(
+        "runs: {}\n{}\n",
+        response.runs.len(),
+        table(
+            TableStyle::Compact,
+            &["run", "playbook", "status", "my_role", "created", "intent"],
+            rows
+        )
+    )
+}
+
+pub fn format_search_results(response: &SearchRunsResponse) -> String {
+    let rows = response
+        .runs
+        .iter()
+        .map(|run| {
+            vec![
+                run.run_id.clone(),
+                run.playbook.clone(),
+                run.status.clone(),
+                timestamps::age(&run.created_at),
+                run.artifacts
+                    .iter()
+                    .map(|a| a.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                preview(&run.intent),
+                preview(run.snippet.as_deref().unwrap_or("")),
+            ]
+        })
+        .collect();
+    format!(
+        "runs: {} more: {}\n{}\n",
+        response.runs.len(),
+        response.more,
+        table(
+            TableStyle::Compact,
+            &[
+                "run",
+                "playbook",
+                "status",
+                "created",
+                "artifacts",
+                "intent",
+                "match"
+            ],
+            rows
+        )
+    )
+}
+
+pub fn format_playbook(response: &GetPlaybookResponse) -> String {
+    let pb = &response.playbook;
+    let mut output = format!(
+        "playbook={} name={} artifact_mode={} expects_document={} expects_goal={} paths={}\n{}\n",
+        pb.id,
+        pb.name,
+        pb.artifact_mode,
+        pb.initialization.expects_document,
+        pb.initialization.expects_goal,
+        pb.initialization.suggested_paths.join(","),
+        preview(&pb.tagline)
+    );
+    let roles = pb
+        .roles
+        .iter()
+        .map(|r| {
+            vec![
+                r.id.clone(),
+                r.name.clone(),
+                r.is_initiating.to_string(),
+                preview(&r.description),
+            ]
+        })
+        .collect();
+    let _ = writeln!(
+        output,
+        "{}",
+        table(
+            TableStyle::Compact,
+            &["role", "name", "initiating", "description"],
+            roles
+        )
+    );
+    let outline = response
+        .protocol_outline
+        .iter()
+        .map(|s| {
+            vec![
+                s.title.clone(),
+                or_dash(s.role.as_deref()),
+                or_dash(s.summary.as_deref()),
+            ]
+        })
+        .collect();
+    let _ = writeln!(
+        output,
+        "{}",
+        table(
+            TableStyle::Compact,
+            &["section", "role", "summary"],
+            outline
+        )
+    );
+    output
+}
+
+pub fn format_playbooks(response: &ListPlaybooksResponse) -> String {
+    if response.playbooks.is_empty() {
+        return "playbooks: 0\n".to_string();
+    }
+    let rows = response
+        .playbooks
+        .iter()
+        .map(|pb| {
+            let roles = pb
+                .roles
+                .iter()
+                .map(|r| {
+                    if r.is_initiating {
+                        format!("{}*", r.id)
+                    } else {
+                        r.id.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            vec![
+                pb.id.clone(),
+                roles,
+                pb.keywords.join(","),
+                preview(&pb.tagline),
+            ]
+        })
+        .collect();
+    format!(
+        "playbooks: {} (* = initiating role)\n{}\n",
+        response.playbooks.len(),
+        table(
+            TableStyle::Compact,
+            &["id", "roles", "keywords", "tagline"],
+            rows
+        )
+    )
+}
+
+// =============================================================================
+// DOC ARTIFACTS
+// =============================================================================
+
+pub fn format_list_response(response: &DocArtifactListResponse) -> String {
+    if response.artifacts.is_empty() {
+        return format!("artifacts: 0 run={}\n", response.run_id);
+    }
+    let rows = response
+        .artifacts
+        .iter()
+        .map(|a| {
+            vec![
+                a.id.clone(),
+                a.filename.clone(),
+                a.document_type.clone(),
+                a.total_lines.to_string(),
+                a.content_hash.clone(),
+                timestamps::age(&a.updated_at),
+                a.tags.join(","),
+            ]
+        })
+        .collect();
+    format!(
+        "artifacts: {} run={}\n{}\n",
+        response.artifacts.len(),
+        response.run_id,
+        table(
+            TableStyle::Compact,
+            &["id", "filename", "type", "lines", "hash", "updated", "tags"],
+            rows
+        )
+    )
+}
+
+pub fn format_set_tags_response(response: &DocArtifactSetTagsResponse) -> String {
+    format!(
+        "tagged artifact={} tags={}\n",
+        response.artifact_id,
+        if response.tags.is_empty() {
+            "-".to_string()
+        } else {
+            response.tags.join(",")
+        }
+    )
+}
+
+pub fn format_read_response(response: &DocArtifactReadResponse) -> String {
+    format_read(response, false)
+}
+
+pub fn format_read_response_numbered(response: &DocArtifactReadResponse) -> String {
+    format_read(response, true)
+}
+
+fn format_read(response: &DocArtifactReadResponse, numbered: bool) -> String {
+    let mut output = String::with_capacity(response.content.len() + 256);
+    let _ = writeln!(
+        output,
+        "{} hash={} lines={}/{} offset={} has_more={}",
+        response.filename,
+        response.content_hash,
+        response.returned_lines,
+        response.total_lines,
+        response.offset,
+        response.has_more
+    );
+    if let Some(comments) = response.comments.as_ref().filter(|c| !c.is_empty()) {
+        let rows = comments
+            .iter()
+            .map(|c| {
+                vec![
+                    Cow::Borrowed(c.id.as_str()),
+                    Cow::Owned(c.line_number.to_string()),
+                    Cow::Borrowed(c.comment_type.as_str()),
+                    Cow::Borrowed(c.author.as_str()),
+                    flat_preview(&c.preview),
+                ]
+            })
+            .collect();
+        output.push_str(&table(
+            TableStyle::Compact,
+            &["comment", "line", "type", "author", "text"],
+            rows,
+        ));
+        output.push('\n');
+    }
+    output.push_str("---\n");
+    if numbered {
+        artifacts::write_numbered_lines(&mut output, response);
+    } else {
+        output.push_str(&response.content);
+    }
+    output.push('\n');
+    output
+}
+
+pub fn format_create_response(response: &DocArtifactCreateResponse) -> String {
+    format!(
+        "created id={} filename={} hash={}\n",
+        response.artifact_id, response.filename, response.content_hash
+    )
+}
+
+pub fn format_edit_response(response: &DocArtifactEditResponse) -> String {
+    if !response.success {
+        return match &response.conflict {
+            Some(conflict) => format!(
+                "conflict expected={} actual={}: re-read with doc_artifact_read and retry\n",
+                conflict.expected_hash, conflict.actual_hash
+            ),
+            None => "edit failed\n".to_string(),
+        };
+    }
+    let mut output = format!(
+        "edited id={} hash={}",
+        response.edit_id, response.new_content_hash
+    );
+    if !response.affected_comments.is_empty() {
+        let _ = write!(
+            output,
+            " affected_comments={}",
+            response.affected_comments.join(",")
+        );
+    }
+    output.push('\n');
+    output
+}
+
+pub fn format_edit_preview(preview: &EditPreview) -> String {
+    let mut output = format!(
+        "dry_run id={} chars={}->{}",
+        preview.artifact_id, preview.original_chars, preview.resulting_chars
+    );
+    if preview.hash_matches == Some(false) {
+        let _ = write!(
+            output,
+            " conflict actual={}: re-read with doc_artifact_read",
+            preview.content_hash
+        );
+    }
+    output.push('\n');
+    output.push_str(&preview.diff);
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+    output
+}
+
+pub fn format_search_response(response: &DocArtifactSearchResponse) -> String {
+    if response.results.is_empty() {
+        return format!("matches: 0 query=\"{}\"\n", response.query);
+    }
+    let rows = response
+        .results
+        .iter()
+        .map(|m| {
+            vec![
+                m.line_number.to_string(),
+                format!("{}-{}", m.char_start, m.char_end),
+                or_dash(m.section.as_deref()),
+                preview(&m.match_text),
+                preview(&m.context),
+            ]
+        })
+        .collect();
+    format!(
+        "matches: {} query=\"{}\"\n{}\n",
+        response.total_matches,
+        response.query,
+        table(
+            TableStyle::Compact,
+            &["line", "chars", "section", "match", "context"],
+            rows
+        )
+    )
+}
+
+pub fn format_add_comment_response(response: &DocArtifactAddCommentResponse) -> String {
+    format!(
+        "comment added id={} selected=\"{}\"\n",
+        response.comment_id,
+        preview(&response.selection_text)
+    )
+}
+
+pub fn format_resolve_comment_response(response: &DocArtifactResolveCommentResponse) -> String {
+    format!(
+        "comment {} status={}\n",
+        response.comment_id, response.new_status
+    )
+}
+
+pub fn format_comments_response(response: &DocArtifactListCommentsResponse) -> String {
+    let page = page(response.offset, response.has_more);
+    if response.comments.is_empty() {
+        return format!("comments: 0 artifact={}{}\n", response.artifact_id, page);
+    }
+    let rows = response
+        .comments
+        .iter()
+        .map(|c| {
+            vec![
+                c.id.clone(),
+                c.status.clone(),
+                c.comment_type.clone(),
+                c.author.clone(),
+                timestamps::age(&c.created_at),
+                artifacts::reply_count(&response.comments, &c.id).to_string(),
+                format!("{}-{}", c.selection_start, c.selection_end),
+                preview(&c.content),
+            ]
+        })
+        .collect();
+    format!(
+        "comments: {} artifact={}{}\n{}\n",
+        response.comments.len(),
+        response.artifact_id,
+        page,
+        table(
+            TableStyle::Compact,
+            &["id", "status", "type", "author", "age", "replies", "chars", "content"],
+            rows
+        )
+    )
+}
+
+pub fn format_suggest_edit_response(response: &DocArtifactSuggestEditResponse) -> String {
+    format!(
+        "suggestion created id={} comment={} (awaiting human accept/reject)\n",
+        response.suggestion_id, response.comment_id
+    )
+}
+
+pub fn format_accept_suggestion_response(response: &DocArtifactAcceptSuggestionResponse) -> String {
+    let mut output = format!(
+        "suggestion accepted id={} hash={}",
+        response.suggestion_id, response.new_content_hash
+    );
+    if !response.resolved_comments.is_empty() {
+        let _ = write!(
+            output,
+            " resolved_comments={}",
+            response.resolved_comments.join(",")
+        );
+    }
+    output.push('\n');
+    output
+}
+
+pub fn format_reject_suggestion_response(response: &DocArtifactRejectSuggestionResponse) -> String {
+    format!(
+        "suggestion rejected id={} (not applied)\n",
+        response.suggestion_id
+    )
+}
+
+pub fn format_suggestions_response(response: &DocArtifactListSuggestionsResponse) -> String {
+    let page = page(response.offset, response.has_more);
+    if response.suggestions.is_empty() {
+        return format!("suggestions: 0 artifact={}{}\n", response.artifact_id, page);
+    }
+    let rows = response
+        .suggestions
+        .iter()
+        .map(|s| {
+            vec![
+                s.id.clone(),
+                s.status.clone(),
+                s.edit_type.clone(),
+                s.suggested_by.clone(),
+                s.comment_id.clone(),
+                preview(&s.suggested_text),
+            ]
+        })
+        .collect();
+    format!(
+        "suggestions: {} artifact={}{}\n{}\n",
+        response.suggestions.len(),
+        response.artifact_id,
+        page,
+        table(
+            TableStyle::Compact,
+            &["id", "status", "type", "by", "comment", "suggested"],
+            rows
+        )
+    )
+}
+
+pub fn format_suggestion_sweep(sweep: &SuggestionSweep) -> String {
+    let mut output = format!(
+        "swept run={} checked={} stale={}\n",
+        sweep.run_id,
+        sweep.checked,
+        sweep.stale.len()
+    );
+    if !sweep.stale.is_empty() {
+        let rows = sweep
+            .stale
+            .iter()
+            .map(|s| {
+                let state = if s.withdrawn {
+                    "withdrawn"
+                } else if s.error.is_some() {
+                    "withdraw_failed"
+                } else {
+                    "flagged"
+                };
+                vec![
+                    s.suggestion_id.clone(),
+                    s.artifact_id.clone(),
+                    s.suggested_by.clone(),
+                    match s.staleness {
+                        Staleness::Moved => "moved".to_string(),
+                        Staleness::Missing => "missing".to_string(),
+                    },
+                    state.to_string(),
+                ]
+            })
+            .collect();
+        output.push_str(&table(
+            TableStyle::Compact,
+            &["id", "artifact", "by", "anchor", "state"],
+            rows,
+        ));
+        output.push('\n');
+    }
+    for (artifact_id, error) in &sweep.failed {
+        let _ = writeln!(output, "failed artifact={}: {}", artifact_id, error);
+    }
+    output
+}
+
+pub fn format_review_context(context: &DocArtifactReviewContext) -> String {
+    format!(
+        "{}{}{}",
+        format_read_response_numbered(&context.document),
+        format_comments_response(&context.comments),
+        format_suggestions_response(&context.suggestions)
+    )
+}
+
+pub fn format_changelog(response: &DocArtifactListEditsResponse) -> String {
+    let rows = response
+        .edits
+        .iter()
+        .map(|e| {
+            let reason = e.edit_reason.as_ref();
+            vec![
+                e.id.clone(),
+                e.author.clone(),
+                timestamps::age(&e.created_at),
+                reason
+                    .map(|r| r.category.title().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                reason
+                    .map(|r| preview(&r.summary))
+                    .unwrap_or_else(|| "-".to_string()),
+                reason
+                    .map(|r| r.related_comment_ids.join(","))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    format!(
+        "artifact={} edits={}\n{}\n",
+        response.artifact_id,
+        response.edits.len(),
+        table(
+            TableStyle::Compact,
+            &["edit", "author", "age", "category", "summary", "comments"],
+            rows
+        )
+    )
+}
+
+pub fn format_feedback_summary(summary: &DocArtifactFeedbackSummary) -> String {
+    let rows = summary
+        .sections
+        .iter()
+        .map(|s| {
+            vec![
+                or_dash(s.heading.as_deref()),
+                s.line.to_string(),
+                s.comments.to_string(),
+                s.open.to_string(),
+                s.open_questions.to_string(),
+                s.oldest_open
+                    .as_deref()
+                    .map(timestamps::age)
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+    let questions = summary
+        .unanswered_questions
+        .iter()
+        .map(|c| {
+            vec![
+                c.id.clone(),
+                timestamps::age(&c.created_at),
+                preview(&c.content),
+            ]
+        })
+        .collect();
+    format!(
+        "artifact={} comments={} open={} oldest_open={}\n{}\n{}\n",
+        summary.artifact_id,
+        summary.total_comments,
+        summary.open_comments,
+        or_dash(summary.oldest_open.as_ref().map(|c| c.id.as_str())),
+        table(
+            TableStyle::Compact,
+            &[
+                "section",
+                "line",
+                "comments",
+                "open",
+                "questions",
+                "oldest_open"
+            ],
+            rows
+        ),
+        table(
+            TableStyle::Compact,
+            &["question", "age", "content"],
+            questions
+        )
+    )
+}
+
+/// One table row per match, prefixed with the artifact it came from.
+pub fn format_search_all_response(response: &DocArtifactSearchAllResponse) -> String {
+    let mut total = 0;
+    let mut rows = Vec::new();
+    let mut failed = Vec::new();
+    for artifact in &response.artifacts {
+        match (&artifact.result, &artifact.error) {
+            (Some(search), _) => {
+                total += search.total_matches;
+                rows.extend(search.results.iter().map(|m| {
+                    vec![
+                        artifact.filename.clone(),
+                        m.line_number.to_string(),
+                        format!("{}-{}", m.char_start, m.char_end),
+                        preview(&m.match_text),
+                        preview(&m.context),
+                    ]
+                }));
+            }
+            (None, error) => failed.push(format!(
+                "failed: {} {}\n",
+                artifact.filename,
+                or_dash(error.as_deref())
+            )),
+        }
+    }
+    let mut output = format!(
+        "matches: {} artifacts={} query=\"{}\"\n",
+        total,
+        response.artifacts.len(),
+        response.query
+    );
+    if !rows.is_empty() {
+        output.push_str(&table(
+            TableStyle::Compact,
+            &["artifact", "line", "chars", "match", "context"],
+            rows,
+        ));
+        output.push('\n');
+    }
+    output.extend(failed);
+    output
+}
+
+pub fn format_read_all_response(response: &DocArtifactReadAllResponse) -> String {
+    let mut output = format!(
+        "artifacts: {} run={}\n",
+        response.artifacts.len(),
+        response.run_id
+    );
+    for artifact in &response.artifacts {
+        output.push('\n');
+        match (&artifact.result, &artifact.error) {
+            (Some(document), _) => output.push_str(&format_read_response(document)),
+            (None, error) => {
+                let _ = writeln!(
+                    output,
+                    "{} failed: {}",
+                    artifact.filename,
+                    or_dash(error.as_deref())
+                );
+            }
+        }
+    }
+    output
+}
+
+pub fn format_artifact_commit(commit: &ArtifactCommit) -> String {
+    format!(
+        "committed path={} commit={} edits={} agents={}",
+        commit.path,
+        or_dash(commit.commit.as_deref()),
+        commit.edit_ids.len(),
+        commit.agents.join(",")
+    )
+}
+
+pub fn format_backup_list(list: &ArtifactBackupList) -> String {
+    let rows = list
+        .backups
+        .iter()
+        .map(|backup| {
+            vec![
+                backup.id.clone(),
+                timestamps::age(&backup.created_at),
+                backup.chars.to_string(),
+                backup.content_hash.clone(),
+                or_dash(backup.role.as_deref()),
+                backup.reason.clone(),
+            ]
+        })
+        .collect();
+    format!(
+        "run={} artifact={} backups: {}\n{}\n",
+        list.run_id,
+        list.artifact_id,
+        list.backups.len(),
+        table(
+            TableStyle::Compact,
+            &["id", "age", "chars", "hash", "role", "reason"],
+            rows
+        )
+    )
+}
+
+pub fn format_rendered_artifact(rendered: &RenderedArtifact) -> String {
+    format!(
+        "rendered path={} bytes={} notes={}",
+        rendered.path, rendered.bytes, rendered.notes
+    )
+}
+
+pub fn format_review_import(import: &ReviewImport) -> String {
+    let mut output = format!(
+        "imported={} skipped={} run={}\n",
+        import.imported.len(),
+        import.skipped.len(),
+        import.run_id
+    );
+    let rows = import
+        .imported
+        .iter()
+        .map(|c| {
+            vec![
+                c.github_id.to_string(),
+                c.comment_id.clone(),
+                c.artifact_id.clone(),
+                c.line.to_string(),
+            ]
+        })
+        .chain(import.skipped.iter().map(|c| {
+            vec![
+                c.github_id.to_string(),
+                "-".to_string(),
+                c.path.clone(),
+                format!("skipped: {}", c.reason),
+            ]
+        }))
+        .collect();
+    output.push_str(&table(
+        TableStyle::Compact,
+        &["github_id", "comment", "artifact", "line"],
+        rows,
+    ));
+    output
+}
+
+pub fn format_event_dump(dump: &EventDump) -> String {
+    let mut output = format!(
+        "dumped run={} file={} schema={} events={} span={}..{}\n",
+        dump.run_id,
+        dump.path,
+        dump.schema,
+        dump.events,
+        or_dash(dump.first_at.as_deref()),
+        or_dash(dump.last_at.as_deref())
+    );
+    let rows = dump
+        .event_types
+        .iter()
+        .map(|(kind, count)| vec![kind.clone(), count.to_string()])
+        .collect();
+    output.push_str(&table(TableStyle::Compact, &["type", "count"], rows));
+    output
+}
+
+pub fn format_run_export(export: &RunExport) -> String {
+    let mut output = format!(
+        "exported run={} dir={} archive={} events={}\n",
+        export.run_id,
+        export.directory,
+        or_dash(export.archive.as_deref()),
+        export.events
+    );
+    let rows = export
+        .artifacts
+        .iter()
+        .map(|artifact| match &artifact.result {
+            Some(exported) => vec![
+                exported.path.clone(),
+                exported.total_lines.to_string(),
+                exported.comments.to_string(),
+                exported.suggestions.to_string(),
+            ],
+            None => vec![
+                artifact.filename.clone(),
+                format!("failed: {}", or_dash(artifact.error.as_deref())),
+                "-".to_string(),
+                "-".to_string(),
+            ],
+        })
+        .collect();
+    output.push_str(&table(
+        TableStyle::Compact,
+        &["path", "lines", "comments", "suggestions"],
+        rows,
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::{ArtifactSummary, ConnectedAgent};
+
+    #[test]
+    fn test_list_is_tabular_without_emoji_or_banners() {
+        let response = DocArtifactListResponse {
+            run_id: "run-1".to_string(),
+            artifacts: vec![ArtifactSummary {
+                id: "art-1".to_string(),
+                filename: "spec.md".to_string(),
+                document_type: "markdown".to_string(),
+                total_lines: 42,
+                content_hash: "abc123".to_string(),
+                created_at: "2024-01-01".to_string(),
+                updated_at: "2024-01-02".to_string(),
+                tags: vec![],
+            }],
+        };
+
+        let output = format_list_response(&response);
+
+        assert!(output.starts_with("artifacts: 1 run=run-1\n"));
+        assert!(output.contains("id | filename | type | lines | hash | updated"));
+        assert!(output.contains("art-1 | spec.md | markdown | 42 | abc123 | 2024-01-02"));
+        assert!(output.is_ascii());
+    }
+
+    #[test]
+    fn test_run_status_keeps_plain_status_line() {
+        let response = GetRunStatusResponse {
+            run_id: "run-1".to_string(),
+            status: "blocked".into(),
+            phase: "build".to_string(),
+            template_name: "doc-review".to_string(),
+            has_protocol: true,
+            connected_agents: vec![ConnectedAgent {
+                role_id: "builder".to_string(),
+                session_name: "hw-1".to_string(),
+                agent_type: "claude".to_string(),
+                progress: None,
+            }],
+            protocol_version: None,
+        };
+
+        let output = format_run_status_response(&response);
+
+        assert!(output.contains("Status: blocked"));
+        assert!(output.contains("Agents: builder=hw-1(claude)"));
+        assert!(!output.contains('═'));
+    }
+
+    #[test]
+    fn test_preview_flattens_and_truncates() {
+        let long = "word ".repeat(40);
+        let output = preview(&long);
+        assert!(output.ends_with("..."));
+        assert_eq!(output.chars().count(), PREVIEW_CHARS + 3);
+        assert_eq!(preview("two\nlines"), "two lines");
+    }
+}
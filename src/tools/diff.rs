@@ -0,0 +1,125 @@
+//! Diff rendering for edit suggestions.
+//!
+//! Multi-line changes render as a unified diff; single-line changes (typically
+//! a sentence or paragraph) render as a word diff, where a line diff would just
+//! show the whole paragraph removed and re-added.
+
+use similar::{ChangeTag, TextDiff};
+use std::fmt::Write;
+
+/// Lines of unchanged context around each hunk in unified diffs.
+const CONTEXT_LINES: usize = 2;
+
+/// Render the change from `original` to `suggested`, picking unified or word
+/// diff based on whether either side spans multiple lines.
+pub fn render_diff(original: &str, suggested: &str) -> String {
+    let mut output = String::new();
+    write_diff(&mut output, original, suggested);
+    output
+}
+
+/// [`render_diff`], appended to `output`.
+pub fn write_diff(output: &mut String, original: &str, suggested: &str) {
+    if original.contains('\n') || suggested.contains('\n') {
+        write_unified_diff(output, original, suggested)
+    } else {
+        write_word_diff(output, original, suggested)
+    }
+}
+
+/// Unified line diff (`-`/`+` lines with context), without file headers.
+pub fn unified_diff(original: &str, suggested: &str) -> String {
+    let mut output = String::new();
+    write_unified_diff(&mut output, original, suggested);
+    output
+}
+
+fn write_unified_diff(output: &mut String, original: &str, suggested: &str) {
+    let diff = TextDiff::from_lines(original, suggested);
+    for hunk in diff
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .missing_newline_hint(false)
+        .iter_hunks()
+    {
+        let _ = write!(output, "{}", hunk);
+    }
+}
+
+/// Inline word diff in `git diff --word-diff` style: `[-removed-]{+added+}`.
+pub fn word_diff(original: &str, suggested: &str) -> String {
+    let mut output = String::new();
+    write_word_diff(&mut output, original, suggested);
+    output
+}
+
+fn write_word_diff(output: &mut String, original: &str, suggested: &str) {
+    let diff = TextDiff::from_words(original, suggested);
+    output.reserve(original.len().max(suggested.len()));
+    let mut run_tag = ChangeTag::Equal;
+    let mut run_start = output.len();
+
+    // Each run of same-tag changes is written in place, then wrapped in its markers
+    for change in diff.iter_all_changes() {
+        if change.tag() != run_tag {
+            close_run(output, run_tag, run_start);
+            run_tag = change.tag();
+            run_start = output.len();
+        }
+        output.push_str(change.value());
+    }
+    close_run(output, run_tag, run_start);
+}
+
+/// Wrap `output[run_start..]` in the markers for `tag`.
+fn close_run(output: &mut String, tag: ChangeTag, run_start: usize) {
+    if output.len() == run_start {
+        return;
+    }
+    let (open, close) = match tag {
+        ChangeTag::Equal => return,
+        ChangeTag::Delete => ("[-", "-]"),
+        ChangeTag::Insert => ("{+", "+}"),
+    };
+    output.insert_str(run_start, open);
+    output.push_str(close);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_diff_marks_changed_words_only() {
+        let output = word_diff("The quick brown fox jumps", "The quick red fox leaps high");
+        assert_eq!(
+            output,
+            "The quick [-brown-]{+red+} fox [-jumps-]{+leaps high+}"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_shows_changed_lines_with_context() {
+        let original = "# Title\nintro\nold line\noutro\n";
+        let suggested = "# Title\nintro\nnew line\noutro\n";
+
+        let output = unified_diff(original, suggested);
+
+        assert!(output.starts_with("@@"));
+        assert!(output.contains("-old line\n"));
+        assert!(output.contains("+new line\n"));
+        assert!(output.contains(" intro\n"));
+    }
+
+    #[test]
+    fn test_render_diff_picks_mode_by_line_count() {
+        assert!(render_diff("a b", "a c").contains("[-b-]{+c+}"));
+        assert!(render_diff("a\nb\n", "a\nc\n").contains("@@"));
+    }
+
+    #[test]
+    fn test_word_diff_handles_multibyte_text() {
+        let output = word_diff("日本語 🚀 launch", "日本語 ✨ launch");
+        assert_eq!(output, "日本語 [-🚀-]{+✨+} launch");
+    }
+}
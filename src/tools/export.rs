@@ -0,0 +1,598 @@
+//! Export a run for archiving.
+//!
+//! `export_run` pulls a run's event timeline and the final state of every
+//! artifact, with all of its comments and suggestions, into a directory:
+//!
+//! ```text
+//! <output_dir>/
+//!   transcript.md       timeline, then each artifact's comments and suggestions
+//!   events.jsonl        one event per line, oldest first
+//!   comments.jsonl      one comment per line, with its artifactId
+//!   suggestions.jsonl   one suggestion per line
+//!   artifacts/<file>    final content of each artifact
+//! ```
+//!
+//! The directory can also be packed into `<output_dir>.zip` with the system
+//! `zip` command.
+//!
+//! `dump_events` writes only the timeline, as [`EventRecord`]s with a
+//! versioned schema, for analysis outside Hotwired.
+
+use crate::ipc::messages::{
+    ArtifactOutcome, CommentDetail, DocArtifactListCommentsResponse,
+    DocArtifactListSuggestionsResponse, DocArtifactReadResponse, EventDump, EventRecord,
+    ExportedArtifact, GetConversationEventsRequest, GetConversationEventsResponse, RunEvent,
+    RunExport, SuggestionDetail,
+};
+use crate::ipc::traits::IpcClient;
+use crate::tools::{artifacts, batch};
+use crate::types::errors::IpcError;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// One artifact's final content with every comment and suggestion on it.
+#[derive(Debug)]
+pub struct ArtifactBundle {
+    pub document: DocArtifactReadResponse,
+    pub comments: Vec<CommentDetail>,
+    pub suggestions: Vec<SuggestionDetail>,
+}
+
+/// Everything fetched for an export, before anything is written.
+#[derive(Debug)]
+pub struct RunBundle {
+    pub run_id: String,
+    pub events: Vec<RunEvent>,
+    pub artifacts: Vec<ArtifactOutcome<ArtifactBundle>>,
+}
+
+/// Default export directory for `run_id`, relative to the project directory.
+pub fn default_dir(run_id: &str) -> PathBuf {
+    PathBuf::from(format!("hotwired-export-{}", run_id))
+}
+
+/// The run's event timeline, oldest first.
+pub async fn conversation_events<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+) -> Result<GetConversationEventsResponse, IpcError> {
+    let request = GetConversationEventsRequest {
+        run_id: run_id.to_string(),
+    };
+    client.request("/api/events/conversation", &request).await
+}
+
+/// Fetch the timeline and, for every artifact, its whole content (read
+/// `page_lines` lines at a time) with comments and suggestions of any status.
+/// Only fetching the timeline or the artifact list fails the whole export.
+pub async fn collect<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    page_lines: i64,
+    concurrency: usize,
+) -> Result<RunBundle, IpcError> {
+    let events = conversation_events(client, run_id).await?.events;
+    let artifacts = batch::for_each_artifact(client, run_id, concurrency, |artifact| async move {
+        let result = tokio::try_join!(
+            artifacts::read_full_content(client, run_id, &artifact.id, page_lines),
+            artifacts::list_comments(
+                client,
+                run_id,
+                &artifact.id,
+                Some("all"),
+                None,
+                None,
+                None,
+                None
+            ),
+            artifacts::list_suggestions(client, run_id, &artifact.id, Some("all")),
+        )
+        .map(|(document, comments, suggestions)| ArtifactBundle {
+            document,
+            comments: comments.comments,
+            suggestions: suggestions.suggestions,
+        });
+        (artifact, result)
+    })
+    .await?;
+    Ok(RunBundle {
+        run_id: run_id.to_string(),
+        events,
+        artifacts,
+    })
+}
+
+/// Write `bundle` to `dir`, creating it if needed, and pack it into
+/// `<dir>.zip` if `zip` is set. Existing files of the same name are replaced.
+pub fn write(bundle: &RunBundle, dir: &Path, zip: bool) -> io::Result<RunExport> {
+    let artifact_dir = dir.join("artifacts");
+    std::fs::create_dir_all(&artifact_dir)?;
+
+    write_jsonl(&dir.join("events.jsonl"), &bundle.events)?;
+
+    let mut used = HashSet::new();
+    let mut comments = Vec::new();
+    let mut suggestions = Vec::new();
+    let mut exported = Vec::with_capacity(bundle.artifacts.len());
+    for outcome in &bundle.artifacts {
+        let result = match &outcome.result {
+            Some(artifact) => {
+                let name = file_name(&outcome.filename, &outcome.artifact_id, &mut used);
+                std::fs::write(artifact_dir.join(&name), &artifact.document.content)?;
+                for comment in &artifact.comments {
+                    let mut record = serde_json::to_value(comment)?;
+                    record["artifactId"] = outcome.artifact_id.clone().into();
+                    comments.push(record);
+                }
+                suggestions.extend(&artifact.suggestions);
+                Some(ExportedArtifact {
+                    path: format!("artifacts/{}", name),
+                    total_lines: artifact.document.total_lines,
+                    comments: artifact.comments.len(),
+                    suggestions: artifact.suggestions.len(),
+                })
+            }
+            None => None,
+        };
+        exported.push(ArtifactOutcome {
+            artifact_id: outcome.artifact_id.clone(),
+            filename: outcome.filename.clone(),
+            result,
+            error: outcome.error.clone(),
+        });
+    }
+    write_jsonl(&dir.join("comments.jsonl"), &comments)?;
+    write_jsonl(&dir.join("suggestions.jsonl"), &suggestions)?;
+
+    let mut export = RunExport {
+        run_id: bundle.run_id.clone(),
+        directory: dir.display().to_string(),
+        archive: None,
+        events: bundle.events.len(),
+        artifacts: exported,
+    };
+    std::fs::write(
+        dir.join("transcript.md"),
+        format_transcript(bundle, &export),
+    )?;
+
+    if zip {
+        export.archive = Some(zip_dir(dir)?.display().to_string());
+    }
+    Ok(export)
+}
+
+/// One JSON document per line.
+fn write_jsonl<T: Serialize>(path: &Path, records: &[T]) -> io::Result<()> {
+    write_jsonl_to(std::fs::File::create(path)?, records)
+}
+
+/// One JSON document per line, to `writer`.
+pub fn write_jsonl_to<T: Serialize>(writer: impl io::Write, records: &[T]) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(writer);
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Version of the [`EventRecord`] schema written by `dump_events`.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// Default `dump_events` file for `run_id`, relative to the project directory.
+pub fn default_events_file(run_id: &str) -> PathBuf {
+    PathBuf::from(format!("hotwired-events-{}.jsonl", run_id))
+}
+
+/// The run's whole event stream as records, oldest first.
+pub async fn event_records<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+) -> Result<Vec<EventRecord>, IpcError> {
+    let events = conversation_events(client, run_id).await?.events;
+    Ok(events
+        .into_iter()
+        .enumerate()
+        .map(|(seq, event)| EventRecord {
+            schema: EVENT_SCHEMA_VERSION,
+            run_id: run_id.to_string(),
+            seq,
+            id: event.id,
+            event_type: event.event_type,
+            source: event.source,
+            content: event.content,
+            metadata: event.metadata,
+            created_at: event.created_at,
+        })
+        .collect())
+}
+
+/// Write `records` to `path` and summarize them.
+pub fn dump_events(run_id: &str, records: &[EventRecord], path: &Path) -> io::Result<EventDump> {
+    write_jsonl(path, records)?;
+    Ok(summarize_events(
+        run_id,
+        records,
+        &path.display().to_string(),
+    ))
+}
+
+/// What was dumped to `path`: counts per event type and the time span.
+pub fn summarize_events(run_id: &str, records: &[EventRecord], path: &str) -> EventDump {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for record in records {
+        match counts
+            .iter_mut()
+            .find(|(kind, _)| *kind == record.event_type)
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((record.event_type.clone(), 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    EventDump {
+        run_id: run_id.to_string(),
+        path: path.to_string(),
+        schema: EVENT_SCHEMA_VERSION,
+        events: records.len(),
+        event_types: counts,
+        first_at: records.first().map(|r| r.created_at.clone()),
+        last_at: records.last().map(|r| r.created_at.clone()),
+    }
+}
+
+/// A file name for an artifact inside `artifacts/`: the last component of
+/// `filename`, so a backend-supplied path cannot escape the directory, made
+/// unique by prefixing the artifact id.
+pub fn file_name(filename: &str, artifact_id: &str, used: &mut HashSet<String>) -> String {
+    let name = Path::new(filename)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| artifact_id.to_string());
+    let name = if used.contains(&name) {
+        format!("{}-{}", artifact_id, name)
+    } else {
+        name
+    };
+    used.insert(name.clone());
+    name
+}
+
+/// Pack `dir` into `<dir>.zip` next to it, with the directory as the archive's
+/// top-level folder.
+fn zip_dir(dir: &Path) -> io::Result<PathBuf> {
+    let name = dir
+        .file_name()
+        .ok_or_else(|| io::Error::other(format!("cannot zip {}", dir.display())))?;
+    let parent = dir
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let archive = format!("{}.zip", name.to_string_lossy());
+    // zip adds to an existing archive; start over so removed files do not linger
+    match std::fs::remove_file(parent.join(&archive)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let output = Command::new("zip")
+        .args(["-qr", &archive])
+        .arg(name)
+        .current_dir(parent)
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to run zip: {}", e)))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "zip failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(parent.join(archive))
+}
+
+/// The human-readable part of the export: the timeline, then every artifact's
+/// comments and suggestions.
+pub fn format_transcript(bundle: &RunBundle, export: &RunExport) -> String {
+    let mut output = format!(
+        "# Run {}\n\n**Events:** {}\n**Artifacts:** {}\n\n",
+        bundle.run_id,
+        bundle.events.len(),
+        bundle.artifacts.len()
+    );
+    for (outcome, exported) in bundle.artifacts.iter().zip(&export.artifacts) {
+        match &exported.result {
+            Some(artifact) => {
+                let _ = writeln!(
+                    output,
+                    "- [{}]({}) ({} lines)",
+                    outcome.filename, artifact.path, artifact.total_lines
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    output,
+                    "- {}: export failed: {}",
+                    outcome.filename,
+                    outcome.error.as_deref().unwrap_or("unknown error")
+                );
+            }
+        }
+    }
+
+    output.push_str("\n## Timeline\n\n");
+    if bundle.events.is_empty() {
+        output.push_str("No events recorded.\n");
+    }
+    for event in &bundle.events {
+        let _ = writeln!(
+            output,
+            "- `{}` **{}** ({}): {}",
+            event.created_at,
+            event.source.as_deref().unwrap_or("system"),
+            event.event_type,
+            // Continuation lines stay inside the list item
+            event.content.trim_end().replace('\n', "\n  ")
+        );
+    }
+
+    for outcome in &bundle.artifacts {
+        let Some(artifact) = &outcome.result else {
+            continue;
+        };
+        let _ = write!(output, "\n# {}\n\n", outcome.filename);
+        output.push_str(&artifacts::format_comments_response(
+            &DocArtifactListCommentsResponse {
+                artifact_id: outcome.artifact_id.clone(),
+                offset: 0,
+                has_more: false,
+                comments: artifact.comments.clone(),
+            },
+        ));
+        output.push('\n');
+        output.push_str(&artifacts::format_suggestions_response(
+            &DocArtifactListSuggestionsResponse {
+                artifact_id: outcome.artifact_id.clone(),
+                offset: 0,
+                has_more: false,
+                suggestions: artifact.suggestions.clone(),
+            },
+        ));
+    }
+    output
+}
+
+/// Summarize what `dump_events` wrote.
+pub fn format_event_dump(dump: &EventDump) -> String {
+    let mut output = format!(
+        "✓ Dumped {} events of run {}\n\n**File:** `{}` (schema v{})\n",
+        dump.events, dump.run_id, dump.path, dump.schema
+    );
+    if let (Some(first), Some(last)) = (&dump.first_at, &dump.last_at) {
+        let _ = writeln!(output, "**Span:** {} to {}", first, last);
+    }
+    if !dump.event_types.is_empty() {
+        output.push('\n');
+        for (kind, count) in &dump.event_types {
+            let _ = writeln!(output, "- {}: {}", kind, count);
+        }
+    }
+    output
+}
+
+/// Summarize what an export wrote.
+pub fn format_run_export(export: &RunExport) -> String {
+    let mut output = format!(
+        "✓ Exported run {}\n\n**Directory:** `{}`\n",
+        export.run_id, export.directory
+    );
+    if let Some(archive) = &export.archive {
+        let _ = writeln!(output, "**Archive:** `{}`", archive);
+    }
+    let _ = write!(output, "**Events:** {}\n\n", export.events);
+    for artifact in &export.artifacts {
+        match (&artifact.result, &artifact.error) {
+            (Some(exported), _) => {
+                let _ = writeln!(
+                    output,
+                    "- `{}`: {} lines, {} comments, {} suggestions",
+                    exported.path, exported.total_lines, exported.comments, exported.suggestions
+                );
+            }
+            (None, error) => {
+                let _ = writeln!(
+                    output,
+                    "- {}: export failed: {}",
+                    artifact.filename,
+                    error.as_deref().unwrap_or("unknown error")
+                );
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::mock::MockIpcClient;
+    use serde_json::json;
+
+    fn artifact(id: &str, filename: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "filename": filename,
+            "documentType": "markdown",
+            "totalLines": 2,
+            "contentHash": "hash",
+            "createdAt": "2024-01-15T10:00:00Z",
+            "updatedAt": "2024-01-15T10:00:00Z"
+        })
+    }
+
+    fn mock_run() -> MockIpcClient {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/events/conversation",
+            json!({
+                "runId": "run-1",
+                "events": [{
+                    "id": "evt_1",
+                    "eventType": "message",
+                    "source": "strategist",
+                    "content": "Spec is ready.\nPlease review.",
+                    "createdAt": "2024-01-15T10:05:00Z"
+                }]
+            }),
+        );
+        mock.when_called(
+            "/api/runs/run-1/artifacts",
+            json!({
+                "runId": "run-1",
+                "artifacts": [
+                    artifact("spec", "docs/spec.md"),
+                    artifact("copy", "spec.md"),
+                    artifact("plan", "plan.md")
+                ]
+            }),
+        );
+        for id in ["spec", "copy"] {
+            mock.when_called(
+                &format!("/api/runs/run-1/artifacts/{}", id),
+                json!({
+                    "artifactId": id,
+                    "filename": "spec.md",
+                    "content": "# Spec\nCache documents.",
+                    "contentHash": "hash",
+                    "totalLines": 2,
+                    "returnedLines": 2,
+                    "offset": 0,
+                    "hasMore": false
+                }),
+            );
+            mock.when_called(
+                &format!("/api/runs/run-1/artifacts/{}/comments/list", id),
+                json!({
+                    "artifactId": id,
+                    "comments": [{
+                        "id": "cmt_1",
+                        "commentType": "question",
+                        "status": "resolved",
+                        "selectionStart": 0,
+                        "selectionEnd": 6,
+                        "selectionText": "# Spec",
+                        "content": "Which cache?",
+                        "suggestedText": null,
+                        "author": "builder",
+                        "resolvedBy": "strategist",
+                        "resolvedAt": "2024-01-15T10:09:00Z",
+                        "resolutionNote": null,
+                        "createdAt": "2024-01-15T10:08:00Z"
+                    }]
+                }),
+            );
+            mock.when_called(
+                &format!("/api/runs/run-1/artifacts/{}/suggestions/list", id),
+                json!({ "artifactId": id, "suggestions": [] }),
+            );
+        }
+        // No responses for "plan": exporting it fails on its own
+        mock
+    }
+
+    #[tokio::test]
+    async fn test_export_writes_bundle() {
+        let mock = mock_run();
+        let bundle = collect(&mock, "run-1", 500, 2).await.unwrap();
+        // Comments and suggestions of every status, not just the open ones
+        assert!(
+            mock.requests_to("/api/runs/run-1/artifacts/spec/comments/list")[0]
+                .contains(r#""status":"all""#)
+        );
+
+        let dir = std::env::temp_dir()
+            .join(format!("hotwired-export-{}", std::process::id()))
+            .join("run-1");
+        let export = write(&bundle, &dir, false).unwrap();
+        assert_eq!(export.events, 1);
+        assert_eq!(export.archive, None);
+
+        let read = |path: &str| std::fs::read_to_string(dir.join(path)).unwrap();
+        assert_eq!(read("artifacts/spec.md"), "# Spec\nCache documents.");
+        // Same file name twice: the second is prefixed with its artifact id
+        assert_eq!(read("artifacts/copy-spec.md"), "# Spec\nCache documents.");
+        assert_eq!(read("events.jsonl").lines().count(), 1);
+        let comments: Vec<serde_json::Value> = read("comments.jsonl")
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[1]["artifactId"], "copy");
+        assert_eq!(read("suggestions.jsonl"), "");
+
+        let transcript = read("transcript.md");
+        assert!(transcript.contains("- [docs/spec.md](artifacts/spec.md) (2 lines)"));
+        assert!(transcript.contains("- plan.md: export failed"));
+        assert!(transcript.contains("**strategist** (message): Spec is ready.\n  Please review."));
+        assert!(transcript.contains("Which cache?"));
+
+        let output = format_run_export(&export);
+        assert!(output.contains("`artifacts/copy-spec.md`: 2 lines, 1 comments, 0 suggestions"));
+        assert!(output.contains("- plan.md: export failed"));
+
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_timeline_failure_fails_the_export() {
+        let mock = MockIpcClient::new();
+        assert!(collect(&mock, "run-1", 500, 2).await.is_err());
+    }
+
+    #[test]
+    fn test_file_name_stays_inside_artifacts_dir() {
+        let mut used = HashSet::new();
+        assert_eq!(file_name("../../etc/passwd", "a1", &mut used), "passwd");
+        assert_eq!(file_name("/tmp/passwd", "a2", &mut used), "a2-passwd");
+        assert_eq!(file_name("..", "a3", &mut used), "a3");
+        assert_eq!(file_name("", "a4", &mut used), "a4");
+    }
+
+    #[tokio::test]
+    async fn test_dumped_events_have_a_stable_shape() {
+        let mock = mock_run();
+        let records = event_records(&mock, "run-1").await.unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "hotwired-events-{}-run-1.jsonl",
+            std::process::id()
+        ));
+        let dump = dump_events("run-1", &records, &path).unwrap();
+        assert_eq!(dump.events, 1);
+        assert_eq!(dump.event_types, vec![("message".to_string(), 1)]);
+        assert_eq!(dump.first_at.as_deref(), Some("2024-01-15T10:05:00Z"));
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let line: serde_json::Value = serde_json::from_str(written.trim_end()).unwrap();
+        // Unknown fields are written as null, never left out
+        assert_eq!(
+            line,
+            json!({
+                "schema": EVENT_SCHEMA_VERSION,
+                "runId": "run-1",
+                "seq": 0,
+                "id": "evt_1",
+                "eventType": "message",
+                "source": "strategist",
+                "content": "Spec is ready.\nPlease review.",
+                "metadata": null,
+                "createdAt": "2024-01-15T10:05:00Z"
+            })
+        );
+        assert!(format_event_dump(&dump).contains("- message: 1"));
+        let _ = std::fs::remove_file(path);
+    }
+}
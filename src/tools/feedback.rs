@@ -0,0 +1,296 @@
+//! Where the feedback on an artifact clusters.
+//!
+//! A writer with dozens of comments to work through wants to know which
+//! sections drew the most, which issue has waited longest and which questions
+//! are still unanswered. The summary buckets every comment into the markdown
+//! section its selection starts in, so that can be read off one table.
+
+use crate::ipc::messages::{CommentDetail, DocArtifactFeedbackSummary, SectionFeedback};
+use crate::ipc::traits::IpcClient;
+use crate::tools::{artifacts, html, text, timestamps};
+use crate::types::errors::IpcError;
+use std::fmt::Write;
+
+/// Width of the heat bar of the most-commented section.
+const HEAT_WIDTH: usize = 10;
+
+/// Reads the whole artifact and all its comments and summarizes them per section.
+pub async fn feedback_summary<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+    page_lines: i64,
+) -> Result<DocArtifactFeedbackSummary, IpcError> {
+    let (document, comments) = tokio::try_join!(
+        artifacts::read_full_content(client, run_id, artifact_id, page_lines),
+        artifacts::list_comments(
+            client,
+            run_id,
+            artifact_id,
+            Some("all"),
+            None,
+            None,
+            None,
+            None
+        ),
+    )?;
+    Ok(summarize(
+        &document.artifact_id,
+        &document.filename,
+        &document.content,
+        &comments.comments,
+    ))
+}
+
+/// Buckets `comments` into the sections of `content` they start in.
+pub fn summarize(
+    artifact_id: &str,
+    filename: &str,
+    content: &str,
+    comments: &[CommentDetail],
+) -> DocArtifactFeedbackSummary {
+    let mut sections = sections(content);
+    for comment in comments {
+        let start = comment.selection_start.max(0) as usize;
+        let index = sections
+            .iter()
+            .rposition(|(offset, _)| *offset <= start)
+            .unwrap_or(0);
+        let section = &mut sections[index].1;
+        section.comments += 1;
+        if comment.status == "open" {
+            section.open += 1;
+            if comment.comment_type == "question" {
+                section.open_questions += 1;
+            }
+            if section
+                .oldest_open
+                .as_ref()
+                .is_none_or(|oldest| comment.created_at < *oldest)
+            {
+                section.oldest_open = Some(comment.created_at.clone());
+            }
+        }
+    }
+
+    let mut open: Vec<&CommentDetail> = comments.iter().filter(|c| c.status == "open").collect();
+    open.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    DocArtifactFeedbackSummary {
+        artifact_id: artifact_id.to_string(),
+        filename: filename.to_string(),
+        total_comments: comments.len(),
+        open_comments: open.len(),
+        sections: sections
+            .into_iter()
+            .map(|(_, section)| section)
+            .filter(|section| section.comments > 0)
+            .collect(),
+        oldest_open: open.first().map(|c| (*c).clone()),
+        unanswered_questions: open
+            .into_iter()
+            .filter(|c| c.comment_type == "question")
+            .cloned()
+            .collect(),
+    }
+}
+
+/// The sections of `content` with the character offset each starts at. Text
+/// before the first heading is a section of its own; headings inside fenced
+/// code blocks don't start one.
+fn sections(content: &str) -> Vec<(usize, SectionFeedback)> {
+    let empty = |heading: Option<&str>, line| SectionFeedback {
+        heading: heading.map(str::to_string),
+        line,
+        comments: 0,
+        open: 0,
+        open_questions: 0,
+        oldest_open: None,
+    };
+    let mut sections = vec![(0, empty(None, 1))];
+    let mut offset = 0;
+    let mut in_fence: Option<&str> = None;
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        match (in_fence, html::fence(trimmed)) {
+            (Some(open), _) if trimmed.starts_with(open) => in_fence = None,
+            (Some(_), _) => {}
+            (None, Some(marker)) => in_fence = Some(marker),
+            (None, None) => {
+                if let Some((_, heading)) = html::heading(trimmed) {
+                    sections.push((offset, empty(Some(heading), index + 1)));
+                }
+            }
+        }
+        offset += line.chars().count();
+    }
+    sections
+}
+
+/// Formats the summary as a table of sections with a heat bar, then the
+/// oldest open issue and the unanswered questions.
+pub fn format_feedback_summary(summary: &DocArtifactFeedbackSummary) -> String {
+    if summary.total_comments == 0 {
+        return format!("No comments on {} yet.", summary.filename);
+    }
+
+    let mut output = format!(
+        "## Feedback on {}\n\n**Comments:** {} ({} open, {} unanswered questions)\n\n",
+        summary.filename,
+        summary.total_comments,
+        summary.open_comments,
+        summary.unanswered_questions.len()
+    );
+    let max = summary
+        .sections
+        .iter()
+        .map(|s| s.comments)
+        .max()
+        .unwrap_or(1);
+    output.push_str("| Section | Line | Heat | Comments | Open | Questions | Oldest open |\n");
+    output.push_str("|---|---|---|---|---|---|---|\n");
+    for section in &summary.sections {
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            section
+                .heading
+                .as_deref()
+                .unwrap_or("(before first heading)"),
+            section.line,
+            "█".repeat((section.comments * HEAT_WIDTH).div_ceil(max)),
+            section.comments,
+            section.open,
+            section.open_questions,
+            section
+                .oldest_open
+                .as_deref()
+                .map(timestamps::age)
+                .unwrap_or_else(|| "-".to_string())
+        );
+    }
+
+    if let Some(oldest) = &summary.oldest_open {
+        let _ = write!(
+            output,
+            "\n**Oldest open issue:** [{}] {} by {}, {}: {}\n",
+            oldest.id,
+            oldest.comment_type,
+            oldest.author,
+            timestamps::age(&oldest.created_at),
+            text::truncate(&oldest.content, 120)
+        );
+    }
+    if !summary.unanswered_questions.is_empty() {
+        output.push_str("\n### Unanswered questions\n\n");
+        for question in &summary.unanswered_questions {
+            let _ = writeln!(
+                output,
+                "- [{}] {} ({})",
+                question.id,
+                text::truncate(&question.content, 120),
+                timestamps::age(&question.created_at)
+            );
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: &str, start: i64, kind: &str, status: &str, created: &str) -> CommentDetail {
+        CommentDetail {
+            id: id.to_string(),
+            comment_type: kind.to_string(),
+            status: status.to_string(),
+            selection_start: start,
+            selection_end: start + 3,
+            selection_text: None,
+            content: format!("Comment {}", id),
+            suggested_text: None,
+            author: "reviewer".to_string(),
+            resolved_by: None,
+            resolved_at: None,
+            resolution_note: None,
+            created_at: created.to_string(),
+            mentions: vec![],
+            parent_comment_id: None,
+        }
+    }
+
+    // Chars: "Intro\n" 0..6, "# Setup\n" 6..14, "```\n# not a heading\n```\n" 14..38,
+    // "## Usage\n" 38..47, "Run it.\n" 47..55
+    const DOC: &str = "Intro\n# Setup\n```\n# not a heading\n```\n## Usage\nRun it.\n";
+
+    #[test]
+    fn test_comments_are_bucketed_by_section() {
+        let comments = vec![
+            comment("c-1", 2, "question", "open", "2024-01-15T10:00:00Z"),
+            comment("c-2", 20, "issue", "open", "2024-01-14T10:00:00Z"),
+            comment("c-3", 30, "suggestion", "resolved", "2024-01-13T10:00:00Z"),
+            comment("c-4", 50, "question", "open", "2024-01-16T10:00:00Z"),
+            comment("c-5", 40, "question", "resolved", "2024-01-12T10:00:00Z"),
+        ];
+        let summary = summarize("art-1", "spec.md", DOC, &comments);
+
+        assert_eq!(summary.total_comments, 5);
+        assert_eq!(summary.open_comments, 3);
+        let headings: Vec<_> = summary
+            .sections
+            .iter()
+            .map(|s| (s.heading.as_deref(), s.line, s.comments, s.open))
+            .collect();
+        assert_eq!(
+            headings,
+            vec![
+                (None, 1, 1, 1),
+                (Some("Setup"), 2, 2, 1),
+                (Some("Usage"), 6, 2, 1)
+            ]
+        );
+        assert_eq!(
+            summary.sections[1].oldest_open.as_deref(),
+            Some("2024-01-14T10:00:00Z")
+        );
+        assert_eq!(summary.sections[2].open_questions, 1);
+        assert_eq!(summary.oldest_open.as_ref().unwrap().id, "c-2");
+        let questions: Vec<_> = summary
+            .unanswered_questions
+            .iter()
+            .map(|c| c.id.as_str())
+            .collect();
+        assert_eq!(questions, vec!["c-1", "c-4"]);
+    }
+
+    #[test]
+    fn test_format_feedback_summary() {
+        let comments = vec![
+            comment("c-1", 8, "question", "open", "2024-01-15T10:00:00Z"),
+            comment("c-2", 9, "issue", "resolved", "2024-01-14T10:00:00Z"),
+            comment("c-3", 50, "issue", "open", "2024-01-16T10:00:00Z"),
+        ];
+        let output = format_feedback_summary(&summarize("art-1", "spec.md", DOC, &comments));
+
+        assert!(output.contains("**Comments:** 3 (2 open, 1 unanswered questions)"));
+        assert!(
+            output.contains("| Setup | 2 | ██████████ | 2 | 1 | 1 |"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("| Usage | 6 | █████ | 1 | 1 | 0 |"),
+            "{}",
+            output
+        );
+        assert!(!output.contains("before first heading"));
+        assert!(output.contains("**Oldest open issue:** [c-1] question by reviewer"));
+        assert!(output.contains("- [c-1] Comment c-1"));
+
+        let empty = summarize("art-1", "spec.md", DOC, &[]);
+        assert_eq!(
+            format_feedback_summary(&empty),
+            "No comments on spec.md yet."
+        );
+    }
+}
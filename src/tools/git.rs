@@ -0,0 +1,236 @@
+//! Committing artifacts to the project's git repository.
+//!
+//! `doc_artifact_commit` writes the final content of an artifact into the
+//! project and commits that one file. The commit message ends in trailers that
+//! tie it back to the run:
+//!
+//! ```text
+//! Hotwired-Run: <run id>
+//! Hotwired-Artifact: <artifact id>
+//! Hotwired-Edits: <edit id>, <edit id>, ...
+//! Hotwired-Agents: <role>, <role>, ...
+//! ```
+//!
+//! so `git log --grep "Hotwired-Run: <run id>"` finds everything a run produced.
+
+use crate::ipc::messages::{ArtifactCommit, EditRecord};
+use std::io;
+use std::path::{Component, Path};
+use std::process::Command;
+
+/// Check that `path` is relative and stays inside the project directory.
+pub fn project_relative(path: &str) -> Result<(), String> {
+    let path = Path::new(path);
+    let inside = path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if path.as_os_str().is_empty() || !inside {
+        Err(format!(
+            "path '{}' must be relative to the project directory, without '..'",
+            path.display()
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Roles that made `edits`, in order of their first edit.
+pub fn contributors(edits: &[EditRecord]) -> Vec<String> {
+    let mut agents: Vec<String> = Vec::new();
+    for edit in edits {
+        if !agents.contains(&edit.author) {
+            agents.push(edit.author.clone());
+        }
+    }
+    agents
+}
+
+/// `subject`, then the provenance trailers. Trailers without a value are left out.
+pub fn commit_message(
+    subject: &str,
+    run_id: &str,
+    artifact_id: &str,
+    edits: &[EditRecord],
+) -> String {
+    let edit_ids: Vec<&str> = edits.iter().map(|e| e.id.as_str()).collect();
+    let mut message = format!(
+        "{}\n\nHotwired-Run: {}\nHotwired-Artifact: {}\n",
+        subject.trim(),
+        run_id,
+        artifact_id
+    );
+    if !edit_ids.is_empty() {
+        message.push_str(&format!("Hotwired-Edits: {}\n", edit_ids.join(", ")));
+    }
+    let agents = contributors(edits);
+    if !agents.is_empty() {
+        message.push_str(&format!("Hotwired-Agents: {}\n", agents.join(", ")));
+    }
+    message
+}
+
+/// Write `content` to `path` inside the repository at `project`, then stage
+/// and commit only that file; anything else already staged is left alone.
+/// Returns the new commit hash, or `None` if the file was already up to date.
+pub fn commit_file(
+    project: &Path,
+    path: &str,
+    content: &str,
+    message: &str,
+) -> io::Result<Option<String>> {
+    let file = project.join(path);
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&file, content)?;
+
+    git(project, &["add", "--", path])?;
+    let unchanged = Command::new("git")
+        .current_dir(project)
+        .args(["diff", "--cached", "--quiet", "--", path])
+        .status()?
+        .success();
+    if unchanged {
+        return Ok(None);
+    }
+    git(
+        project,
+        &["commit", "--quiet", "--message", message, "--", path],
+    )?;
+    git(project, &["rev-parse", "HEAD"]).map(Some)
+}
+
+/// Top-level directory of the repository containing `project`.
+pub fn toplevel(project: &Path) -> io::Result<String> {
+    git(project, &["rev-parse", "--show-toplevel"])
+}
+
+/// Branch checked out in `project`, or `None` when HEAD is detached.
+pub fn current_branch(project: &Path) -> io::Result<Option<String>> {
+    git(project, &["branch", "--show-current"]).map(|b| Some(b).filter(|b| !b.is_empty()))
+}
+
+/// Summarize a commit made by `doc_artifact_commit`.
+pub fn format_commit(commit: &ArtifactCommit) -> String {
+    let Some(hash) = &commit.commit else {
+        return format!(
+            "`{}` is already up to date; nothing to commit.",
+            commit.path
+        );
+    };
+    format!(
+        "✓ Committed `{}`\n\n**Commit:** `{}`\n**Edits:** {}\n**Agents:** {}",
+        commit.path,
+        hash,
+        commit.edit_ids.len(),
+        if commit.agents.is_empty() {
+            "none".to_string()
+        } else {
+            commit.agents.join(", ")
+        }
+    )
+}
+
+/// Trimmed stdout of `git <args>` run in `project`; stderr becomes the error.
+fn git(project: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .current_dir(project)
+        .args(args)
+        .output()
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(id: &str, author: &str) -> EditRecord {
+        EditRecord {
+            id: id.to_string(),
+            author: author.to_string(),
+            edit_reason: None,
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_commit_message_carries_provenance() {
+        let edits = [
+            edit("edit_1", "strategist"),
+            edit("edit_2", "builder"),
+            edit("edit_3", "strategist"),
+        ];
+        assert_eq!(
+            commit_message("Update docs/spec.md\n", "run-1", "spec", &edits),
+            "Update docs/spec.md\n\n\
+            Hotwired-Run: run-1\n\
+            Hotwired-Artifact: spec\n\
+            Hotwired-Edits: edit_1, edit_2, edit_3\n\
+            Hotwired-Agents: strategist, builder\n"
+        );
+        assert_eq!(
+            commit_message("Add spec", "run-1", "spec", &[]),
+            "Add spec\n\nHotwired-Run: run-1\nHotwired-Artifact: spec\n"
+        );
+    }
+
+    #[test]
+    fn test_project_relative() {
+        assert!(project_relative("docs/spec.md").is_ok());
+        assert!(project_relative("./spec.md").is_ok());
+        assert!(project_relative("../spec.md").is_err());
+        assert!(project_relative("docs/../../spec.md").is_err());
+        assert!(project_relative("/etc/passwd").is_err());
+        assert!(project_relative("").is_err());
+    }
+
+    #[test]
+    fn test_commit_file_commits_only_that_file() {
+        let project = std::env::temp_dir().join(format!("hotwired-git-{}", std::process::id()));
+        std::fs::create_dir_all(&project).unwrap();
+        for args in [
+            &["init", "--quiet"][..],
+            &["config", "user.name", "Test"],
+            &["config", "user.email", "test@example.com"],
+            &["config", "commit.gpgsign", "false"],
+        ] {
+            git(&project, args).unwrap();
+        }
+        // Staged work of the user's own is not swept into the commit
+        std::fs::write(project.join("notes.txt"), "wip").unwrap();
+        git(&project, &["add", "notes.txt"]).unwrap();
+
+        let message = commit_message("Add spec", "run-1", "spec", &[edit("edit_1", "builder")]);
+        let hash = commit_file(&project, "docs/spec.md", "# Spec\n", &message)
+            .unwrap()
+            .unwrap();
+        assert_eq!(git(&project, &["rev-parse", "HEAD"]).unwrap(), hash);
+        assert_eq!(
+            git(&project, &["show", "--name-only", "--format=", "HEAD"]).unwrap(),
+            "docs/spec.md"
+        );
+        assert!(git(&project, &["log", "-1", "--format=%B"])
+            .unwrap()
+            .contains("Hotwired-Agents: builder"));
+        assert_eq!(
+            git(&project, &["diff", "--cached", "--name-only"]).unwrap(),
+            "notes.txt"
+        );
+
+        // Same content again: nothing to commit
+        assert_eq!(
+            commit_file(&project, "docs/spec.md", "# Spec\n", &message).unwrap(),
+            None
+        );
+
+        std::fs::remove_dir_all(&project).unwrap();
+    }
+}
@@ -0,0 +1,166 @@
+//! The receiving side of handoffs.
+//!
+//! A handoff can carry structured tasks besides its prose summary. The
+//! receiver lists the handoffs addressed to it and gets their tasks as one
+//! task board, each task with its acceptance criteria and the artifacts it
+//! touches, instead of re-reading every handoff's details.
+
+use crate::ipc::messages::{GetHandoffsRequest, GetHandoffsResponse};
+use crate::ipc::traits::IpcClient;
+use crate::tools::timestamps;
+use crate::types::errors::IpcError;
+use std::fmt::Write;
+
+/// Fetches the handoffs `role` received in `run_id`, oldest first.
+pub async fn get_handoffs<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: &str,
+) -> Result<GetHandoffsResponse, IpcError> {
+    let request = GetHandoffsRequest {
+        run_id: run_id.to_string(),
+        source: Some(role.to_string()),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/handoffs", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Formats received handoffs as a task board: every handed-off task, then
+/// the handoffs that came without tasks.
+pub fn format_task_board(response: &GetHandoffsResponse) -> String {
+    if response.handoffs.is_empty() {
+        return format!("No handoffs to {} yet.", response.role);
+    }
+
+    let tasks = response
+        .handoffs
+        .iter()
+        .map(|handoff| handoff.tasks.len())
+        .sum::<usize>();
+    let mut output = format!(
+        "## Task board for {} ({} task(s) from {} handoff(s))\n\n",
+        response.role,
+        tasks,
+        response.handoffs.len()
+    );
+    for handoff in response.handoffs.iter().filter(|h| !h.tasks.is_empty()) {
+        let _ = writeln!(
+            output,
+            "### From {} — {} ({})\n",
+            handoff.from,
+            handoff.summary,
+            timestamps::age(&handoff.created_at)
+        );
+        for task in &handoff.tasks {
+            let _ = writeln!(output, "- [ ] `{}` {}", task.id, task.title);
+            for criterion in &task.acceptance_criteria {
+                let _ = writeln!(output, "  - {}", criterion);
+            }
+            if !task.artifacts.is_empty() {
+                let _ = writeln!(output, "  Artifacts: {}", task.artifacts.join(", "));
+            }
+        }
+        output.push('\n');
+    }
+
+    let untasked: Vec<_> = response
+        .handoffs
+        .iter()
+        .filter(|h| h.tasks.is_empty())
+        .collect();
+    if !untasked.is_empty() {
+        output.push_str("### Handoffs without tasks\n\n");
+        for handoff in untasked {
+            let _ = write!(
+                output,
+                "- [{}] From {}: {} ({})",
+                handoff.id,
+                handoff.from,
+                handoff.summary,
+                timestamps::age(&handoff.created_at)
+            );
+            if let Some(details) = &handoff.details {
+                let _ = write!(output, "\n  {}", details);
+            }
+            if !handoff.artifacts.is_empty() {
+                let _ = write!(output, "\n  Artifacts: {}", handoff.artifacts.join(", "));
+            }
+            output.push('\n');
+        }
+    }
+    output.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::{HandoffRecord, HandoffTask};
+    use crate::ipc::mock::MockIpcClient;
+
+    fn handoffs() -> GetHandoffsResponse {
+        GetHandoffsResponse {
+            run_id: "run-1".to_string(),
+            role: "builder".to_string(),
+            handoffs: vec![
+                HandoffRecord {
+                    id: "h1".to_string(),
+                    from: "strategist".to_string(),
+                    summary: "Spec is ready".to_string(),
+                    details: None,
+                    artifacts: vec![],
+                    tasks: vec![
+                        HandoffTask {
+                            id: "T1".to_string(),
+                            title: "Add the cache layer".to_string(),
+                            acceptance_criteria: vec![
+                                "Hit rate is logged".to_string(),
+                                "TTL is configurable".to_string(),
+                            ],
+                            artifacts: vec!["spec".to_string()],
+                        },
+                        HandoffTask {
+                            id: "T2".to_string(),
+                            title: "Update the docs".to_string(),
+                            acceptance_criteria: vec![],
+                            artifacts: vec![],
+                        },
+                    ],
+                    created_at: "2024-01-15T10:00:00Z".to_string(),
+                },
+                HandoffRecord {
+                    id: "h2".to_string(),
+                    from: "reviewer".to_string(),
+                    summary: "Looks good".to_string(),
+                    details: Some("Ship it".to_string()),
+                    artifacts: vec![],
+                    tasks: vec![],
+                    created_at: "2024-01-15T11:00:00Z".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_handoffs_sends_role() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/runs/run-1/handoffs", handoffs());
+
+        let response = get_handoffs(&mock, "run-1", "builder").await.unwrap();
+        assert_eq!(response.handoffs.len(), 2);
+        assert!(mock.requests_to("/api/runs/run-1/handoffs")[0].contains(r#""source":"builder""#));
+    }
+
+    #[test]
+    fn test_format_task_board() {
+        let output = format_task_board(&handoffs());
+        assert!(output.starts_with("## Task board for builder (2 task(s) from 2 handoff(s))"));
+        assert!(output.contains(
+            "- [ ] `T1` Add the cache layer\n  - Hit rate is logged\n  - TTL is configurable\n  Artifacts: spec\n- [ ] `T2` Update the docs\n"
+        ));
+        assert!(output.contains("### Handoffs without tasks\n\n- [h2] From reviewer: Looks good ("));
+        assert!(output.ends_with("\n  Ship it"));
+    }
+}
@@ -0,0 +1,133 @@
+//! Search across past runs.
+//!
+//! A run's decisions live in its artifacts, which are out of reach once the
+//! run has ended. `search_runs` finds earlier runs by keyword, project,
+//! playbook, or date and lists their artifacts, whose IDs work with the
+//! `doc_artifact_*` tools under the returned run ID.
+
+use crate::ipc::messages::{SearchRunsRequest, SearchRunsResponse};
+use crate::ipc::traits::IpcClient;
+use crate::tools::timestamps;
+use crate::types::errors::IpcError;
+use std::fmt::Write;
+
+/// Searches runs matching every given filter.
+pub async fn search_runs<C: IpcClient>(
+    client: &C,
+    filters: &SearchRunsRequest,
+) -> Result<SearchRunsResponse, IpcError> {
+    client.request("/api/runs/search", filters).await
+}
+
+/// Formats search results with each run's artifacts.
+pub fn format_search_results(response: &SearchRunsResponse) -> String {
+    if response.runs.is_empty() {
+        return "No runs match. Try fewer keywords or a wider date range.".to_string();
+    }
+
+    let mut output = format!("## {} matching run(s)\n", response.runs.len());
+    for run in &response.runs {
+        let _ = write!(
+            output,
+            "\n### {} — {}\n\nRun `{}` ({}), created {}",
+            run.playbook,
+            run.intent,
+            run.run_id,
+            run.status,
+            timestamps::format_timestamp(&run.created_at)
+        );
+        if let Some(ended_at) = &run.ended_at {
+            let _ = write!(output, ", ended {}", timestamps::age(ended_at));
+        }
+        output.push('\n');
+        if let Some(project_path) = &run.project_path {
+            let _ = writeln!(output, "Project: {}", project_path);
+        }
+        if let Some(snippet) = &run.snippet {
+            let _ = writeln!(output, "> {}", snippet);
+        }
+        if run.artifacts.is_empty() {
+            output.push_str("No artifacts.\n");
+        } else {
+            output.push_str("Artifacts:\n");
+            for artifact in &run.artifacts {
+                let _ = writeln!(
+                    output,
+                    "- `{}` {} ({})",
+                    artifact.id, artifact.filename, artifact.document_type
+                );
+            }
+        }
+    }
+    if response.more > 0 {
+        let _ = write!(
+            output,
+            "\n{} more run(s) match; narrow the search or raise limit.\n",
+            response.more
+        );
+    }
+    output.push_str("\nRead an artifact with doc_artifact_read using the run's ID.");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::{RunArtifactRef, RunSearchHit};
+    use crate::ipc::mock::MockIpcClient;
+
+    fn results() -> SearchRunsResponse {
+        SearchRunsResponse {
+            runs: vec![RunSearchHit {
+                run_id: "run-1".to_string(),
+                playbook: "spec-and-build".to_string(),
+                intent: "Add a cache layer".to_string(),
+                status: "completed".to_string(),
+                project_path: Some("/home/dev/app".to_string()),
+                created_at: "2024-01-15T10:00:00Z".to_string(),
+                ended_at: Some("2024-01-16T10:00:00Z".to_string()),
+                snippet: Some("decided on SQLite for the cache".to_string()),
+                artifacts: vec![RunArtifactRef {
+                    id: "spec".to_string(),
+                    filename: "spec.md".to_string(),
+                    document_type: "prd".to_string(),
+                }],
+            }],
+            more: 3,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_runs_sends_only_given_filters() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/runs/search", results());
+
+        let filters = SearchRunsRequest {
+            query: Some("cache".to_string()),
+            project_path: None,
+            playbook: None,
+            since: Some("2024-01-01".to_string()),
+            until: None,
+            limit: None,
+            format: None,
+            continuation: None,
+        };
+        search_runs(&mock, &filters).await.unwrap();
+        assert_eq!(
+            mock.requests_to("/api/runs/search")[0],
+            r#"{"query":"cache","since":"2024-01-01"}"#
+        );
+    }
+
+    #[test]
+    fn test_format_search_results() {
+        let output = format_search_results(&results());
+        assert!(output.starts_with("## 1 matching run(s)"));
+        assert!(
+            output.contains("### spec-and-build — Add a cache layer\n\nRun `run-1` (completed)")
+        );
+        assert!(output.contains("> decided on SQLite for the cache\n"));
+        assert!(output.contains("- `spec` spec.md (prd)\n"));
+        assert!(output.contains("3 more run(s) match"));
+    }
+}
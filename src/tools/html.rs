@@ -0,0 +1,528 @@
+//! Standalone HTML rendering of markdown artifacts.
+//!
+//! `doc_artifact_render` turns a document into one self-contained HTML page
+//! (inline styles, no scripts) that stakeholders can open without the Hotwired
+//! app. The converter covers the markdown agents write: headings, paragraphs,
+//! nested lists, task lists, block quotes, fenced code, tables, rules, and
+//! inline code, emphasis, links and images. Raw HTML in the document is shown
+//! as text, never interpreted.
+//!
+//! Resolved comments can be shown as margin notes next to the block that
+//! contains the start of their selection.
+
+use crate::ipc::messages::{CommentDetail, RenderedArtifact};
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+const STYLE: &str = "\
+body { font: 16px/1.6 -apple-system, BlinkMacSystemFont, \"Segoe UI\", Helvetica, Arial, sans-serif; color: #1f2328; margin: 0; }
+main { max-width: 46rem; margin: 2rem auto; padding: 0 1rem; }
+h1, h2 { border-bottom: 1px solid #d1d9e0; padding-bottom: .3em; }
+pre { background: #f6f8fa; padding: 1rem; overflow: auto; border-radius: 6px; }
+code { font: 85% ui-monospace, SFMono-Regular, Menlo, monospace; background: #f6f8fa; padding: .1em .3em; border-radius: 4px; }
+pre code { background: none; padding: 0; }
+blockquote { margin: 0; padding: 0 1em; color: #59636e; border-left: .25em solid #d1d9e0; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d1d9e0; padding: .3em .8em; }
+img { max-width: 100%; }
+.note { font-size: 13px; line-height: 1.4; background: #fff8c5; border-left: 3px solid #d4a72c; padding: .5em .8em; margin: .5em 0; }
+.note p { margin: .3em 0; }
+.note .meta { color: #59636e; }
+@media (min-width: 82rem) {
+  main { margin-left: calc(50% - 32rem); }
+  .note { float: right; clear: right; width: 14rem; margin: 0 -17rem 1rem 0; }
+}
+";
+
+/// A complete HTML page for the markdown document `content`, with `notes`
+/// shown in the margin.
+pub fn render_page(title: &str, content: &str, notes: &[CommentDetail]) -> String {
+    let lines = lines(content);
+    let blocks = blocks(&lines);
+
+    let mut body = String::new();
+    for (i, block) in blocks.iter().enumerate() {
+        let next = blocks.get(i + 1).map(|b| b.start);
+        for note in notes.iter().filter(|note| {
+            let offset = note.selection_start.max(0) as usize;
+            // Notes past the last block start belong to the last block
+            (i == 0 || offset >= block.start) && next.is_none_or(|next| offset < next)
+        }) {
+            body.push_str(&margin_note(note));
+        }
+        body.push_str(&block.html);
+        body.push('\n');
+    }
+    if blocks.is_empty() {
+        for note in notes {
+            body.push_str(&margin_note(note));
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+        <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+        <meta name=\"generator\" content=\"hotwired-mcp\">\n\
+        <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+/// Summarize a page written by `doc_artifact_render`.
+pub fn format_rendered(rendered: &RenderedArtifact) -> String {
+    format!(
+        "✓ Rendered `{}` to `{}`\n\n**Size:** {} bytes\n**Margin notes:** {}",
+        rendered.artifact_id, rendered.path, rendered.bytes, rendered.notes
+    )
+}
+
+fn margin_note(note: &CommentDetail) -> String {
+    let mut html = format!(
+        "<aside class=\"note\"><p class=\"meta\">{} by {}",
+        escape(&note.comment_type),
+        escape(&note.author)
+    );
+    if let Some(resolved_by) = &note.resolved_by {
+        html.push_str(&format!(" · resolved by {}", escape(resolved_by)));
+    }
+    html.push_str("</p>");
+    html.push_str(&format!("<p>{}</p>", inline(&note.content)));
+    if let Some(resolution) = &note.resolution_note {
+        html.push_str(&format!("<p class=\"meta\">{}</p>", inline(resolution)));
+    }
+    html.push_str("</aside>\n");
+    html
+}
+
+/// A source line and the character offset where it starts.
+struct Line<'a> {
+    text: &'a str,
+    start: usize,
+}
+
+fn lines(content: &str) -> Vec<Line<'_>> {
+    let mut start = 0;
+    content
+        .split_inclusive('\n')
+        .map(|raw| {
+            let line = Line {
+                text: raw.trim_end_matches(['\n', '\r']),
+                start,
+            };
+            start += raw.chars().count();
+            line
+        })
+        .collect()
+}
+
+/// A rendered block and the character offset of its first line.
+struct Block {
+    start: usize,
+    html: String,
+}
+
+fn blocks(lines: &[Line]) -> Vec<Block> {
+    let mut rendered = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].text;
+        let trimmed = line.trim_start();
+        let start = lines[i].start;
+        let html = if trimmed.is_empty() {
+            i += 1;
+            continue;
+        } else if let Some(marker) = fence(trimmed) {
+            let language = trimmed[marker.len()..].trim();
+            let mut code = String::new();
+            i += 1;
+            while i < lines.len() && !lines[i].text.trim_start().starts_with(marker) {
+                code.push_str(lines[i].text);
+                code.push('\n');
+                i += 1;
+            }
+            i += 1;
+            let class = match language.split_whitespace().next() {
+                Some(language) => format!(" class=\"language-{}\"", escape(language)),
+                None => String::new(),
+            };
+            format!("<pre><code{}>{}</code></pre>", class, escape(&code))
+        } else if let Some((level, text)) = heading(trimmed) {
+            i += 1;
+            format!("<h{0}>{1}</h{0}>", level, inline(text))
+        } else if is_rule(trimmed) {
+            i += 1;
+            "<hr>".to_string()
+        } else if trimmed.starts_with('>') {
+            let mut quoted = Vec::new();
+            while i < lines.len() && lines[i].text.trim_start().starts_with('>') {
+                let text = &lines[i].text.trim_start()[1..];
+                quoted.push(Line {
+                    text: text.strip_prefix(' ').unwrap_or(text),
+                    start: lines[i].start,
+                });
+                i += 1;
+            }
+            let inner: Vec<String> = blocks(&quoted).into_iter().map(|b| b.html).collect();
+            format!("<blockquote>\n{}\n</blockquote>", inner.join("\n"))
+        } else if trimmed.starts_with('|')
+            && lines.get(i + 1).is_some_and(|l| is_delimiter_row(l.text))
+        {
+            let header = cells(trimmed);
+            i += 2;
+            let mut rows = Vec::new();
+            while i < lines.len() && lines[i].text.trim_start().starts_with('|') {
+                rows.push(cells(lines[i].text.trim_start()));
+                i += 1;
+            }
+            table(&header, &rows)
+        } else if list_item(line).is_some() {
+            let mut items: Vec<ListItem> = Vec::new();
+            while i < lines.len() {
+                let text = lines[i].text;
+                if let Some(item) = list_item(text) {
+                    items.push(item);
+                } else if text.trim().is_empty() {
+                    // A blank line only continues the list if more of it follows
+                    let continues = lines.get(i + 1).is_some_and(|next| {
+                        list_item(next.text).is_some() || next.text.starts_with([' ', '\t'])
+                    });
+                    if !continues {
+                        break;
+                    }
+                } else if text.starts_with([' ', '\t']) {
+                    if let Some(last) = items.last_mut() {
+                        last.text.push(' ');
+                        last.text.push_str(text.trim());
+                    }
+                } else {
+                    break;
+                }
+                i += 1;
+            }
+            let mut html = String::new();
+            list(&items, &mut 0, &mut html);
+            html
+        } else {
+            let mut paragraph = vec![line.trim()];
+            i += 1;
+            while i < lines.len() && !starts_block(lines[i].text) {
+                paragraph.push(lines[i].text.trim());
+                i += 1;
+            }
+            format!("<p>{}</p>", inline(&paragraph.join("\n")))
+        };
+        rendered.push(Block { start, html });
+    }
+    rendered
+}
+
+/// Whether `line` ends a paragraph.
+fn starts_block(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.is_empty()
+        || fence(trimmed).is_some()
+        || heading(trimmed).is_some()
+        || is_rule(trimmed)
+        || trimmed.starts_with('>')
+        || list_item(line).is_some()
+}
+
+/// The fence marker a line opens or closes a code block with.
+pub fn fence(trimmed: &str) -> Option<&'static str> {
+    ["```", "~~~"]
+        .into_iter()
+        .find(|marker| trimmed.starts_with(marker))
+}
+
+/// The level and text of an ATX heading line.
+pub fn heading(trimmed: &str) -> Option<(usize, &str)> {
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    let rest = &trimmed[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+        Some((level, rest.trim().trim_end_matches('#').trim_end()))
+    } else {
+        None
+    }
+}
+
+fn is_rule(trimmed: &str) -> bool {
+    let compact: String = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&mark| compact.chars().all(|c| c == mark))
+}
+
+fn is_delimiter_row(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.starts_with('|')
+        && trimmed.contains('-')
+        && trimmed
+            .chars()
+            .all(|c| matches!(c, '|' | '-' | ':' | ' ' | '\t'))
+}
+
+fn cells(row: &str) -> Vec<String> {
+    let row = row.trim();
+    let row = row.strip_prefix('|').unwrap_or(row);
+    let row = row.strip_suffix('|').unwrap_or(row);
+    row.split('|').map(|cell| inline(cell.trim())).collect()
+}
+
+fn table(header: &[String], rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table>\n<thead><tr>");
+    for cell in header {
+        html.push_str(&format!("<th>{}</th>", cell));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", cell));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>");
+    html
+}
+
+struct ListItem {
+    indent: usize,
+    ordered: bool,
+    text: String,
+}
+
+fn list_item(line: &str) -> Option<ListItem> {
+    static ITEM: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^(\s*)([-*+]|\d{1,9}[.)])\s+(.*)$").unwrap());
+    let captures = ITEM.captures(line)?;
+    Some(ListItem {
+        indent: captures[1].replace('\t', "    ").len(),
+        ordered: captures[2].starts_with(|c: char| c.is_ascii_digit()),
+        text: captures[3].to_string(),
+    })
+}
+
+/// Render `items` from `*i` on as one list, recursing into more deeply
+/// indented items; stops at the first item indented less than the first.
+fn list(items: &[ListItem], i: &mut usize, html: &mut String) {
+    let indent = items[*i].indent;
+    let tag = if items[*i].ordered { "ol" } else { "ul" };
+    html.push_str(&format!("<{}>\n", tag));
+    while *i < items.len() && items[*i].indent >= indent {
+        let text = &items[*i].text;
+        let content = if let Some(rest) = text.strip_prefix("[ ] ") {
+            format!("☐ {}", inline(rest))
+        } else if let Some(rest) = text
+            .strip_prefix("[x] ")
+            .or_else(|| text.strip_prefix("[X] "))
+        {
+            format!("☑ {}", inline(rest))
+        } else {
+            inline(text)
+        };
+        html.push_str(&format!("<li>{}", content));
+        *i += 1;
+        if *i < items.len() && items[*i].indent > indent {
+            html.push('\n');
+            list(items, i, html);
+        }
+        html.push_str("</li>\n");
+    }
+    html.push_str(&format!("</{}>", tag));
+}
+
+/// Inline markdown of `text` as HTML: code spans first, since nothing inside
+/// them is formatted.
+fn inline(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('`') {
+        let ticks = rest[open..].chars().take_while(|&c| c == '`').count();
+        let fence = &rest[open..open + ticks];
+        let after = &rest[open + ticks..];
+        match after.find(fence) {
+            Some(close) => {
+                html.push_str(&spans(&rest[..open]));
+                html.push_str(&format!("<code>{}</code>", escape(after[..close].trim())));
+                rest = &after[close + ticks..];
+            }
+            None => {
+                html.push_str(&spans(&rest[..open + ticks]));
+                rest = after;
+            }
+        }
+    }
+    html.push_str(&spans(rest));
+    html
+}
+
+/// Links, images and emphasis in text without code spans.
+fn spans(text: &str) -> String {
+    static LINK: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(!?)\[([^\]]*)\]\(([^)\s]+)\)").unwrap());
+    static STRONG: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\*\*([^*]+)\*\*|__([^_]+)__").unwrap());
+    static EMPHASIS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\*([^*\s](?:[^*]*[^*\s])?)\*").unwrap());
+    static STRIKE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"~~([^~]+)~~").unwrap());
+    static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new("\u{0}(\\d+)\u{0}").unwrap());
+
+    // Links become placeholders so emphasis markers inside URLs are left alone
+    let escaped = escape(&text.replace('\0', ""));
+    let mut links = Vec::new();
+    let html = LINK.replace_all(&escaped, |c: &Captures| {
+        let (image, label, url) = (&c[1] == "!", &c[2], &c[3]);
+        let html = if !safe_url(url) {
+            label.to_string()
+        } else if image {
+            format!("<img src=\"{}\" alt=\"{}\">", url, label)
+        } else {
+            format!("<a href=\"{}\">{}</a>", url, label)
+        };
+        links.push(html);
+        format!("\u{0}{}\u{0}", links.len() - 1)
+    });
+    let html = STRONG.replace_all(&html, |c: &Captures| {
+        format!(
+            "<strong>{}</strong>",
+            c.get(1).or(c.get(2)).unwrap().as_str()
+        )
+    });
+    let html = EMPHASIS.replace_all(&html, "<em>$1</em>");
+    let html = STRIKE.replace_all(&html, "<del>$1</del>");
+    let html = PLACEHOLDER.replace_all(&html, |c: &Captures| {
+        let link = &links[c[1].parse::<usize>().unwrap()];
+        // Emphasis inside the link text
+        let link = STRONG.replace_all(link, |c: &Captures| {
+            format!(
+                "<strong>{}</strong>",
+                c.get(1).or(c.get(2)).unwrap().as_str()
+            )
+        });
+        EMPHASIS.replace_all(&link, "<em>$1</em>").into_owned()
+    });
+    html.into_owned()
+}
+
+/// Only web and mail links, and relative or fragment URLs, are kept, so a
+/// shared page cannot run code. Control characters and whitespace are ignored
+/// when reading the scheme, as browsers do.
+fn safe_url(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_control() && !c.is_ascii_whitespace())
+        .collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(end) if url[end..].starts_with(':') => {
+            let scheme = url[..end].to_ascii_lowercase();
+            ["http", "https", "mailto"].contains(&scheme.as_str())
+        }
+        _ => true,
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(markdown: &str) -> String {
+        let lines = lines(markdown);
+        blocks(&lines)
+            .into_iter()
+            .map(|b| b.html)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_blocks() {
+        let markdown = "# Cache *layer*\n\nReads go through `get<K>`\nfirst.\n\n\
+            - one\n- two\n  - nested\n1. first\n\n> quoted **text**\n\n\
+            ```rust\nlet x = a < b;\n```\n\n| a | b |\n|---|:-:|\n| 1 | 2 |\n\n---\n";
+        assert_eq!(
+            body(markdown),
+            "<h1>Cache <em>layer</em></h1>\n\
+            <p>Reads go through <code>get&lt;K&gt;</code>\nfirst.</p>\n\
+            <ul>\n<li>one</li>\n<li>two\n<ul>\n<li>nested</li>\n</ul></li>\n<li>first</li>\n</ul>\n\
+            <blockquote>\n<p>quoted <strong>text</strong></p>\n</blockquote>\n\
+            <pre><code class=\"language-rust\">let x = a &lt; b;\n</code></pre>\n\
+            <table>\n<thead><tr><th>a</th><th>b</th></tr></thead>\n<tbody>\n\
+            <tr><td>1</td><td>2</td></tr>\n</tbody>\n</table>\n\
+            <hr>"
+        );
+    }
+
+    #[test]
+    fn test_inline_escapes_html_and_unsafe_links() {
+        assert_eq!(
+            inline("<script>x</script> [docs](https://e.com/a_b_c) ~~old~~"),
+            "&lt;script&gt;x&lt;/script&gt; <a href=\"https://e.com/a_b_c\">docs</a> <del>old</del>"
+        );
+        assert_eq!(inline("[click](JavaScript:void)"), "click");
+        // The link ends at the first `)`, so the call's own `)` stays as text
+        assert_eq!(inline("[x](\u{1}javascript:alert(1))"), "x)");
+        assert_eq!(inline("[x](java\u{8}script:alert(1))"), "x)");
+        assert_eq!(inline("[x](file:///etc/passwd)"), "x");
+        assert_eq!(
+            inline("[mail](mailto:a@b.c) [top](#intro)"),
+            "<a href=\"mailto:a@b.c\">mail</a> <a href=\"#intro\">top</a>"
+        );
+        assert_eq!(inline("a * b * c"), "a * b * c");
+        assert_eq!(
+            inline("[**bold** link](/x)"),
+            "<a href=\"/x\"><strong>bold</strong> link</a>"
+        );
+    }
+
+    #[test]
+    fn test_notes_go_next_to_their_block() {
+        let content = "# Title\n\nFirst paragraph.\n\nSecond paragraph.\n";
+        let note = |start: i64, text: &str| CommentDetail {
+            id: "c1".to_string(),
+            comment_type: "issue".to_string(),
+            status: "resolved".to_string(),
+            selection_start: start,
+            selection_end: start + 5,
+            selection_text: None,
+            content: text.to_string(),
+            suggested_text: None,
+            author: "reviewer".to_string(),
+            resolved_by: Some("builder".to_string()),
+            resolved_at: None,
+            resolution_note: Some("Fixed".to_string()),
+            created_at: "2024-01-15T10:00:00Z".to_string(),
+            mentions: vec![],
+            parent_comment_id: None,
+        };
+        // "Second paragraph." starts at character 27
+        let page = render_page(
+            "spec.md",
+            content,
+            &[note(30, "Reword this"), note(999, "Late")],
+        );
+        assert!(page.contains("<title>spec.md</title>"));
+        let second = page.find("<p>Second paragraph.</p>").unwrap();
+        let reword = page.find("Reword this").unwrap();
+        assert!(page.find("<p>First paragraph.</p>").unwrap() < reword && reword < second);
+        assert!(page.find("Late").unwrap() < second);
+        assert!(page.contains("issue by reviewer · resolved by builder"));
+    }
+}
@@ -0,0 +1,167 @@
+//! Impediment templates from the run's playbook.
+//!
+//! Some blockers come up in every run: credentials are missing, a library
+//! has to be chosen. A playbook can define a template for each, with the
+//! impediment type, a lead-in for the description, and the form the human
+//! answers with. `report_impediment` with a `template_id` fills those in, so
+//! the human gets the same well-formed prompt whichever agent asks.
+
+use crate::ipc::messages::{
+    ImpedimentTemplate, ListImpedimentTemplatesRequest, ListImpedimentTemplatesResponse,
+    ReportImpedimentRequest,
+};
+use crate::ipc::traits::IpcClient;
+use crate::types::errors::IpcError;
+use std::fmt::Write;
+
+/// Fetches the impediment templates of `run_id`'s playbook.
+pub async fn list_templates<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+) -> Result<ListImpedimentTemplatesResponse, IpcError> {
+    let request = ListImpedimentTemplatesRequest {
+        run_id: run_id.to_string(),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/impediment-templates", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Fill what `request` leaves out from `template`. The description becomes
+/// the template's lead-in followed by the agent's specifics.
+pub fn apply(request: &mut ReportImpedimentRequest, template: &ImpedimentTemplate) {
+    request.impediment_type = request.impediment_type.or(Some(template.impediment_type));
+    request.response_format = request
+        .response_format
+        .take()
+        .or_else(|| template.response_format.clone());
+    let specifics = request.description.trim();
+    request.description = if specifics.is_empty() {
+        template.description.clone()
+    } else if specifics.starts_with(template.description.trim()) {
+        specifics.to_string()
+    } else {
+        format!("{}\n\n{}", template.description, specifics)
+    };
+}
+
+/// Lists the templates with what each fills in.
+pub fn format_templates(response: &ListImpedimentTemplatesResponse) -> String {
+    if response.templates.is_empty() {
+        return format!(
+            "The playbook of run {} has no impediment templates. Report impediments with an impediment_type instead.",
+            response.run_id
+        );
+    }
+    let mut output = match &response.playbook_id {
+        Some(playbook) => format!("## Impediment templates of playbook {}\n", playbook),
+        None => "## Impediment templates\n".to_string(),
+    };
+    for template in &response.templates {
+        let _ = write!(
+            output,
+            "\n### {} (`{}`)\n\n**Type:** {}\n\n{}\n",
+            template.title,
+            template.id,
+            template.impediment_type.as_str(),
+            template.description
+        );
+        if let Some(form) = &template.response_format {
+            let fields: Vec<String> = form
+                .fields
+                .iter()
+                .map(|f| format!("{} ({})", f.label, f.field_type))
+                .collect();
+            let _ = writeln!(output, "\n**Human answers with:** {}", fields.join(", "));
+        }
+    }
+    output.push_str(
+        "\nPass a template's ID as template_id to report_impediment, with the specifics as description.\n",
+    );
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::{ImpedimentType, ResponseFormat};
+    use crate::ipc::mock::MockIpcClient;
+    use serde_json::json;
+
+    fn templates() -> serde_json::Value {
+        json!({
+            "runId": "run-1",
+            "playbookId": "spec-and-build",
+            "templates": [{
+                "id": "need-credentials",
+                "title": "Need credentials",
+                "impedimentType": "permission_needed",
+                "description": "Credentials are needed to continue.",
+                "responseFormat": {"fields": [
+                    {"id": "where", "type": "text", "label": "Where to find them"}
+                ]}
+            }]
+        })
+    }
+
+    #[test]
+    fn test_apply_fills_only_what_is_left_out() {
+        let response: ListImpedimentTemplatesResponse =
+            serde_json::from_value(templates()).unwrap();
+        let template = &response.templates[0];
+        let mut request: ReportImpedimentRequest = serde_json::from_value(json!({
+            "runId": "run-1",
+            "description": "The staging database password.",
+            "templateId": "need-credentials"
+        }))
+        .unwrap();
+        apply(&mut request, template);
+        assert_eq!(
+            request.impediment_type,
+            Some(ImpedimentType::PermissionNeeded)
+        );
+        assert_eq!(
+            request.description,
+            "Credentials are needed to continue.\n\nThe staging database password."
+        );
+        assert_eq!(request.response_format.unwrap().fields[0].id, "where");
+
+        let mut request: ReportImpedimentRequest = serde_json::from_value(json!({
+            "runId": "run-1",
+            "impedimentType": "missing_information",
+            "description": "Credentials are needed to continue. The staging database password.",
+            "responseFormat": {"fields": []}
+        }))
+        .unwrap();
+        apply(&mut request, template);
+        assert_eq!(
+            request.impediment_type,
+            Some(ImpedimentType::MissingInformation)
+        );
+        assert!(request
+            .description
+            .starts_with("Credentials are needed to continue. The"));
+        assert!(matches!(
+            request.response_format,
+            Some(ResponseFormat { fields }) if fields.is_empty()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_and_format() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/runs/run-1/impediment-templates", templates());
+
+        let response = list_templates(&mock, "run-1").await.unwrap();
+        let output = format_templates(&response);
+        assert!(output.starts_with(
+            "## Impediment templates of playbook spec-and-build\n\n\
+            ### Need credentials (`need-credentials`)\n\n\
+            **Type:** permission_needed\n\n\
+            Credentials are needed to continue.\n\n\
+            **Human answers with:** Where to find them (text)\n"
+        ));
+    }
+}
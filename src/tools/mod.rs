@@ -1,4 +1,32 @@
 pub mod artifacts;
+pub mod batch;
+pub mod changelog;
+pub mod chunk;
+pub mod compact;
+pub mod diff;
+pub mod export;
+pub mod feedback;
+pub mod git;
+pub mod handoffs;
+pub mod history;
+pub mod html;
+pub mod impediment_templates;
+pub mod notifications;
+pub mod offsets;
 pub mod protocol;
+pub mod read_receipts;
+pub mod report;
+pub mod review_import;
+pub mod run_config;
+pub mod run_context;
 pub mod status;
+pub mod steps;
+pub mod summaries;
+pub mod sweep;
+pub mod tables;
 pub mod terminal;
+pub mod text;
+pub mod threads;
+pub mod timestamps;
+pub mod usage;
+pub mod validate;
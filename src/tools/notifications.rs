@@ -0,0 +1,378 @@
+//! Per-agent notification inbox.
+//!
+//! The backend collects what needs an agent's attention (comments addressed to
+//! it, handoffs, answered questions, ...) in an inbox per run and role, so one
+//! cheap `get_notifications` call at the top of a turn replaces polling the
+//! run status, messages, and every artifact's comments.
+//!
+//! A message sent to other agents lands in each recipient's inbox; its
+//! deliveries show which recipients have acknowledged it since. High and
+//! urgent messages are listed first, and a sender can ask every recipient to
+//! confirm with `ack_message` that they have acted on it.
+
+use crate::ipc::messages::{
+    AckMessageRequest, AckMessageResponse, AckNotificationRequest, AckNotificationResponse,
+    GetMessageDeliveriesRequest, GetMessageDeliveriesResponse, GetNotificationsRequest,
+    GetNotificationsResponse, MessagePriority,
+};
+use crate::ipc::traits::IpcClient;
+use crate::tools::timestamps;
+use crate::types::errors::IpcError;
+use std::cmp::Reverse;
+use std::fmt::Write;
+
+/// Fetches `role`'s notifications in `run_id`; only unacknowledged ones unless
+/// `include_acknowledged`.
+pub async fn get_notifications<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: &str,
+    include_acknowledged: bool,
+) -> Result<GetNotificationsResponse, IpcError> {
+    let request = GetNotificationsRequest {
+        run_id: run_id.to_string(),
+        source: Some(role.to_string()),
+        include_acknowledged: include_acknowledged.then_some(true),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/notifications", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Marks `notification_ids` in `role`'s inbox as handled.
+pub async fn ack_notifications<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: &str,
+    notification_ids: &[String],
+) -> Result<AckNotificationResponse, IpcError> {
+    let request = AckNotificationRequest {
+        run_id: run_id.to_string(),
+        notification_ids: notification_ids.to_vec(),
+        source: Some(role.to_string()),
+    };
+
+    let endpoint = format!("/api/runs/{}/notifications/ack", run_id);
+    let response: AckNotificationResponse = client.request(&endpoint, &request).await?;
+    if response.success {
+        Ok(response)
+    } else {
+        Err(IpcError::RequestFailed(
+            "Backend reported failure acknowledging notifications".to_string(),
+        ))
+    }
+}
+
+/// Marks messages `event_ids` as acted on by `role`, which their senders see
+/// in get_message_deliveries.
+pub async fn ack_messages<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: &str,
+    event_ids: &[String],
+) -> Result<AckMessageResponse, IpcError> {
+    let request = AckMessageRequest {
+        run_id: run_id.to_string(),
+        event_ids: event_ids.to_vec(),
+        source: Some(role.to_string()),
+    };
+
+    let endpoint = format!("/api/runs/{}/messages/ack", run_id);
+    let response: AckMessageResponse = client.request(&endpoint, &request).await?;
+    if response.success {
+        Ok(response)
+    } else {
+        Err(IpcError::RequestFailed(
+            "Backend reported failure acknowledging messages".to_string(),
+        ))
+    }
+}
+
+/// Fetches where the copies of message `event_id` are.
+pub async fn get_message_deliveries<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    event_id: &str,
+) -> Result<GetMessageDeliveriesResponse, IpcError> {
+    let request = GetMessageDeliveriesRequest {
+        run_id: run_id.to_string(),
+        event_id: event_id.to_string(),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/messages/{}", run_id, event_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Formats a message's deliveries, acknowledged ones first.
+pub fn format_message_deliveries(response: &GetMessageDeliveriesResponse) -> String {
+    if response.deliveries.is_empty() {
+        return format!(
+            "Message {} from {} was not delivered to any agent; it is only in the conversation log.",
+            response.event_id, response.from
+        );
+    }
+    let acknowledged = response
+        .deliveries
+        .iter()
+        .filter(|d| d.acknowledged_at.is_some())
+        .count();
+    let mut output = format!(
+        "## Deliveries of {}message {} from {}{}\n\n{} of {} recipient(s) acknowledged it{}.\n\n",
+        response.priority.marker(),
+        response.event_id,
+        response.from,
+        if response.broadcast {
+            " (broadcast)"
+        } else {
+            ""
+        },
+        acknowledged,
+        response.deliveries.len(),
+        if response.requires_ack {
+            "; the sender asked every recipient to"
+        } else {
+            ""
+        }
+    );
+    let mut deliveries: Vec<_> = response.deliveries.iter().collect();
+    deliveries.sort_by_key(|d| d.acknowledged_at.is_none());
+    for delivery in deliveries {
+        let _ = match &delivery.acknowledged_at {
+            Some(at) => writeln!(
+                output,
+                "- ✓ **{}** acknowledged {}",
+                delivery.recipient,
+                timestamps::age(at)
+            ),
+            None => writeln!(
+                output,
+                "- **{}** not acknowledged yet (delivered {})",
+                delivery.recipient,
+                timestamps::age(&delivery.delivered_at)
+            ),
+        };
+    }
+    output
+}
+
+/// Formats an inbox for display to the agent.
+pub fn format_notifications(response: &GetNotificationsResponse) -> String {
+    if response.notifications.is_empty() {
+        return format!(
+            "📭 No new notifications for {} — nothing needs your attention.",
+            response.role
+        );
+    }
+
+    let mut output = format!(
+        "📬 {} notification(s) for {}\n\n",
+        response.notifications.len(),
+        response.role
+    );
+    // Urgent first; otherwise in the order the backend gave
+    let mut notifications: Vec<_> = response.notifications.iter().collect();
+    notifications.sort_by_key(|n| Reverse(n.priority.max(MessagePriority::Normal)));
+    let mut awaiting_ack = false;
+    for notification in notifications {
+        let _ = write!(
+            output,
+            "- [{}] {}**{}**{}: {}",
+            notification.id,
+            notification.priority.marker(),
+            notification.kind,
+            notification
+                .from
+                .as_deref()
+                .map(|from| format!(" from {}", from))
+                .unwrap_or_default(),
+            notification.summary
+        );
+        if let Some(artifact_id) = &notification.artifact_id {
+            let _ = write!(output, " (artifact `{}`", artifact_id);
+            if let Some(comment_id) = &notification.comment_id {
+                let _ = write!(output, ", comment `{}`", comment_id);
+            }
+            output.push(')');
+        }
+        if notification.acknowledged {
+            output.push_str(" ✓");
+        } else if let (true, Some(event_id)) = (notification.requires_ack, &notification.event_id) {
+            let _ = write!(output, " [ack required: message `{}`]", event_id);
+            awaiting_ack = true;
+        }
+        let _ = writeln!(output, " — {}", timestamps::age(&notification.created_at));
+    }
+    output.push_str("\nCall ack_notification with the IDs you have handled.");
+    if awaiting_ack {
+        output.push_str(
+            " Messages marked ack required also need ack_message with their message ID once you have acted on them.",
+        );
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::Notification;
+    use crate::ipc::mock::MockIpcClient;
+
+    fn inbox() -> GetNotificationsResponse {
+        GetNotificationsResponse {
+            run_id: "run-1".to_string(),
+            role: "builder".to_string(),
+            notifications: vec![
+                Notification {
+                    id: "n1".to_string(),
+                    kind: "comment".to_string(),
+                    from: Some("strategist".to_string()),
+                    summary: "Is the cache TTL configurable?".to_string(),
+                    artifact_id: Some("spec".to_string()),
+                    comment_id: Some("c7".to_string()),
+                    created_at: "2024-01-15T10:00:00Z".to_string(),
+                    acknowledged: false,
+                    event_id: None,
+                    priority: MessagePriority::Normal,
+                    requires_ack: false,
+                },
+                Notification {
+                    id: "n2".to_string(),
+                    kind: "handoff".to_string(),
+                    from: None,
+                    summary: "Implement the cache layer".to_string(),
+                    artifact_id: None,
+                    comment_id: None,
+                    created_at: "2024-01-15T10:05:00Z".to_string(),
+                    acknowledged: false,
+                    event_id: None,
+                    priority: MessagePriority::Normal,
+                    requires_ack: false,
+                },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_and_ack_notifications() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/runs/run-1/notifications", inbox());
+        mock.when_called(
+            "/api/runs/run-1/notifications/ack",
+            AckNotificationResponse {
+                success: true,
+                acknowledged: 2,
+            },
+        );
+
+        let response = get_notifications(&mock, "run-1", "builder", false)
+            .await
+            .unwrap();
+        assert_eq!(response.notifications.len(), 2);
+        let sent = &mock.requests_to("/api/runs/run-1/notifications")[0];
+        assert!(sent.contains("\"source\":\"builder\""));
+        assert!(!sent.contains("includeAcknowledged"));
+
+        let ids = vec!["n1".to_string(), "n2".to_string()];
+        let acked = ack_notifications(&mock, "run-1", "builder", &ids)
+            .await
+            .unwrap();
+        assert_eq!(acked.acknowledged, 2);
+        assert!(mock.requests_to("/api/runs/run-1/notifications/ack")[0]
+            .contains("\"notificationIds\":[\"n1\",\"n2\"]"));
+    }
+
+    #[test]
+    fn test_format_notifications() {
+        let output = format_notifications(&inbox());
+        assert!(output.starts_with("📬 2 notification(s) for builder"));
+        assert!(output.contains(
+            "- [n1] **comment** from strategist: Is the cache TTL configurable? (artifact `spec`, comment `c7`)"
+        ));
+        assert!(output.contains("- [n2] **handoff**: Implement the cache layer —"));
+
+        let empty = GetNotificationsResponse {
+            notifications: vec![],
+            ..inbox()
+        };
+        assert!(format_notifications(&empty).starts_with("📭 No new notifications for builder"));
+    }
+
+    #[tokio::test]
+    async fn test_urgent_messages_come_first_and_ask_for_ack() {
+        let mut inbox = inbox();
+        inbox.notifications.push(Notification {
+            id: "n3".to_string(),
+            kind: "message".to_string(),
+            from: Some("reviewer".to_string()),
+            summary: "Stop editing section 3, I'm rewriting it".to_string(),
+            artifact_id: None,
+            comment_id: None,
+            created_at: "2024-01-15T10:06:00Z".to_string(),
+            acknowledged: false,
+            event_id: Some("evt-9".to_string()),
+            priority: MessagePriority::Urgent,
+            requires_ack: true,
+        });
+        let output = format_notifications(&inbox);
+        assert!(output.contains(
+            "\n\n- [n3] 🚨 **message** from reviewer: Stop editing section 3, I'm rewriting it \
+            [ack required: message `evt-9`] — "
+        ));
+        assert!(output.find("[n3]") < output.find("[n1]"));
+        assert!(
+            output.ends_with("need ack_message with their message ID once you have acted on them.")
+        );
+
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/messages/ack",
+            AckMessageResponse {
+                success: true,
+                acknowledged: 1,
+            },
+        );
+        let acked = ack_messages(&mock, "run-1", "builder", &["evt-9".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(acked.acknowledged, 1);
+        assert!(mock.requests_to("/api/runs/run-1/messages/ack")[0]
+            .contains("\"eventIds\":[\"evt-9\"],\"source\":\"builder\""));
+    }
+
+    #[tokio::test]
+    async fn test_message_deliveries() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/messages/evt-1",
+            serde_json::json!({
+                "runId": "run-1",
+                "eventId": "evt-1",
+                "from": "strategist",
+                "broadcast": true,
+                "deliveries": [
+                    {"recipient": "builder", "deliveredAt": "2024-01-15T10:00:00Z"},
+                    {
+                        "recipient": "reviewer",
+                        "deliveredAt": "2024-01-15T10:00:00Z",
+                        "acknowledgedAt": "2024-01-15T10:02:00Z"
+                    }
+                ]
+            }),
+        );
+
+        let response = get_message_deliveries(&mock, "run-1", "evt-1")
+            .await
+            .unwrap();
+        let output = format_message_deliveries(&response);
+        assert!(output.starts_with(
+            "## Deliveries of message evt-1 from strategist (broadcast)\n\n\
+            1 of 2 recipient(s) acknowledged it.\n\n\
+            - ✓ **reviewer** acknowledged "
+        ));
+        assert!(output.contains("\n- **builder** not acknowledged yet (delivered "));
+    }
+}
@@ -0,0 +1,150 @@
+//! Conversion between byte and character offsets.
+//!
+//! The doc-editor addresses text by character (Unicode scalar value), while
+//! agents that slice UTF-8 buffers often produce byte offsets. Offsets are
+//! converted to characters and checked against the document before sending.
+
+use crate::ipc::messages::OffsetUnit;
+
+/// Character index of byte offset `byte`. Fails inside a multi-byte character
+/// or past the end of `text`.
+pub fn byte_to_char(text: &str, byte: usize) -> Result<usize, String> {
+    if byte > text.len() {
+        return Err(format!(
+            "byte offset {} is past the end of the document ({} bytes)",
+            byte,
+            text.len()
+        ));
+    }
+    if !text.is_char_boundary(byte) {
+        return Err(format!(
+            "byte offset {} falls inside a multi-byte character",
+            byte
+        ));
+    }
+    Ok(text[..byte].chars().count())
+}
+
+/// Byte offset of character index `char_index`. Fails past the end of `text`.
+pub fn char_to_byte(text: &str, char_index: usize) -> Result<usize, String> {
+    text.char_indices()
+        .map(|(byte, _)| byte)
+        .chain(std::iter::once(text.len()))
+        .nth(char_index)
+        .ok_or_else(|| {
+            format!(
+                "character offset {} is past the end of the document ({} characters)",
+                char_index,
+                text.chars().count()
+            )
+        })
+}
+
+/// `offset` (named `name`, in `unit`) as a character offset into `text`.
+pub fn to_char_offset(
+    text: &str,
+    name: &str,
+    offset: i64,
+    unit: OffsetUnit,
+) -> Result<i64, String> {
+    let value = usize::try_from(offset).map_err(|_| format!("{} must be 0 or greater", name))?;
+    let chars = match unit {
+        OffsetUnit::Byte => byte_to_char(text, value),
+        OffsetUnit::Char => char_to_byte(text, value).map(|_| value),
+    };
+    chars
+        .map(|c| c as i64)
+        .map_err(|e| format!("{}: {}", name, e))
+}
+
+/// Check that characters `start..end` of `text` still contain `anchored`, the
+/// text a comment was made on. If not, the error names where that text is now,
+/// at the occurrence nearest `start`, or says it is gone.
+pub fn check_anchor(text: &str, start: usize, end: usize, anchored: &str) -> Result<(), String> {
+    let (Ok(start_byte), Ok(end_byte)) = (char_to_byte(text, start), char_to_byte(text, end))
+    else {
+        return Err(format!(
+            "chars {}-{} are past the end of the document",
+            start, end
+        ));
+    };
+    if start_byte <= end_byte && text[start_byte..end_byte].contains(anchored) {
+        return Ok(());
+    }
+    let nearest = text
+        .match_indices(anchored)
+        .map(|(byte, _)| text[..byte].chars().count())
+        .min_by_key(|found| found.abs_diff(start));
+    match nearest {
+        Some(found) => Err(format!(
+            "the document changed since the comment was anchored: its text is now at chars {}-{}, not {}-{}. \
+            Re-anchor the suggestion at offset {}",
+            found,
+            found + anchored.chars().count(),
+            start,
+            end,
+            found
+        )),
+        None => Err(format!(
+            "the document changed since the comment was anchored and its text \"{}\" is gone. \
+            Read the document again and suggest against the current text, or resolve the comment",
+            anchored
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "héllo 🌍 wörld";
+
+    #[test]
+    fn test_byte_and_char_round_trip() {
+        for (char_index, (byte, _)) in TEXT.char_indices().enumerate() {
+            assert_eq!(byte_to_char(TEXT, byte).unwrap(), char_index);
+            assert_eq!(char_to_byte(TEXT, char_index).unwrap(), byte);
+        }
+        let chars = TEXT.chars().count();
+        assert_eq!(byte_to_char(TEXT, TEXT.len()).unwrap(), chars);
+        assert_eq!(char_to_byte(TEXT, chars).unwrap(), TEXT.len());
+    }
+
+    #[test]
+    fn test_byte_inside_character_is_rejected() {
+        // "é" occupies bytes 1..3
+        let err = byte_to_char(TEXT, 2).unwrap_err();
+        assert!(err.contains("inside a multi-byte character"));
+    }
+
+    #[test]
+    fn test_check_anchor() {
+        let text = "Cache 🌍 TTL is 5m. The TTL is fixed.";
+        // "TTL" is chars 8..11 and 23..26
+        assert!(check_anchor(text, 8, 11, "TTL").is_ok());
+        assert!(check_anchor(text, 0, 18, "TTL").is_ok());
+
+        let moved = check_anchor(text, 20, 23, "TTL").unwrap_err();
+        assert!(moved.contains("now at chars 23-26, not 20-23"), "{}", moved);
+        assert!(moved.ends_with("Re-anchor the suggestion at offset 23"));
+
+        assert!(check_anchor(text, 8, 11, "expiry")
+            .unwrap_err()
+            .contains("\"expiry\" is gone"));
+        assert!(check_anchor(text, 8, 100, "TTL").is_err());
+    }
+
+    #[test]
+    fn test_offsets_past_end_are_rejected() {
+        assert!(byte_to_char(TEXT, TEXT.len() + 1).is_err());
+        let err = to_char_offset(TEXT, "end_offset", 100, OffsetUnit::Char).unwrap_err();
+        assert!(err.starts_with("end_offset: character offset 100 is past the end"));
+    }
+
+    #[test]
+    fn test_to_char_offset_converts_bytes() {
+        // "🌍" starts at byte 7, character 6
+        assert_eq!(to_char_offset(TEXT, "start", 7, OffsetUnit::Byte), Ok(6));
+        assert_eq!(to_char_offset(TEXT, "start", 6, OffsetUnit::Char), Ok(6));
+    }
+}
@@ -1,8 +1,15 @@
 use crate::ipc::messages::{
-    GetProtocolResponse, GetProtocolSocketRequest, GetRunStatusRequest, GetRunStatusResponse,
+    GetAgentCapabilitiesRequest, GetAgentCapabilitiesResponse, GetProtocolResponse,
+    GetProtocolSocketRequest, GetRunStatusRequest, GetRunStatusResponse, MyContext,
+    RoleCapabilities, SwitchRoleRequest, SwitchRoleResponse,
 };
 use crate::ipc::traits::IpcClient;
+use crate::state::RunContext;
 use crate::types::errors::IpcError;
+use crate::types::run_state::{RunState, RunStatus};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 /// Fetches the protocol for a specific run and role.
 /// This is the PRIMARY way agents receive their instructions.
@@ -21,6 +28,36 @@ pub async fn get_protocol<C: IpcClient>(
     client.request(&endpoint, &request).await
 }
 
+/// Moves the caller from `source` to `new_role` in the backend's connected-agent
+/// mapping, then fetches the protocol for the new role.
+pub async fn switch_role<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    new_role: &str,
+    source: &str,
+    reason: Option<&str>,
+) -> Result<GetProtocolResponse, IpcError> {
+    let request = SwitchRoleRequest {
+        run_id: run_id.to_string(),
+        new_role: new_role.to_string(),
+        source: Some(source.to_string()),
+        reason: reason.map(String::from),
+        format: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/switch-role", run_id);
+    let response: SwitchRoleResponse = client.request(&endpoint, &request).await?;
+    if !response.success {
+        return Err(IpcError::RequestFailed(
+            response
+                .error
+                .unwrap_or_else(|| "Role switch failed".into()),
+        ));
+    }
+
+    get_protocol(client, run_id, new_role).await
+}
+
 /// Formats the protocol response for display to the agent.
 pub fn format_protocol_response(response: &GetProtocolResponse) -> String {
     let role_section = match &response.role_protocol {
@@ -45,6 +82,20 @@ pub fn format_protocol_response(response: &GetProtocolResponse) -> String {
             if caps.can_resolve_impediments {
                 caps_lines.push("- **Can resolve impediments**: You can use `resolve_impediment` to resolve blockers raised by other agents");
             }
+            if !caps.can_edit_artifacts {
+                caps_lines.push("- **Cannot edit documents**: Comment or suggest edits instead; creating, editing, and accepting or rejecting suggestions is refused");
+            }
+            if !caps.can_end_run {
+                caps_lines.push("- **Cannot end the run**: Ask an agent with that permission to call `request_end_run`");
+            }
+            if !caps.can_reject_comments {
+                caps_lines.push(
+                    "- **Cannot reject others' comments**: Reply with your reasoning instead",
+                );
+            }
+            if !caps.can_close_own_comments {
+                caps_lines.push("- **Cannot close your own comments**: Another role has to address or reject them");
+            }
             if caps_lines.is_empty() {
                 String::new()
             } else {
@@ -54,6 +105,21 @@ pub fn format_protocol_response(response: &GetProtocolResponse) -> String {
         None => String::new(),
     };
 
+    let servers_section = if response.mcp_servers.is_empty() {
+        String::new()
+    } else {
+        let servers: Vec<String> = response
+            .mcp_servers
+            .iter()
+            .map(|server| format!("- **{0}**: tools named `{0}__<tool>`", server.name))
+            .collect();
+        format!(
+            "\n## Playbook Tools\n\nThis playbook's MCP servers are bridged through Hotwired; \
+            their tools appear in your tool list once they have started.\n\n{}\n",
+            servers.join("\n")
+        )
+    };
+
     format!(
         r#"# Hotwired Workflow Protocol
 
@@ -63,7 +129,7 @@ pub fn format_protocol_response(response: &GetProtocolResponse) -> String {
 ## Protocol Instructions
 
 {}
-{}{}
+{}{}{}
 ## Initialization Condition
 
 {}
@@ -73,6 +139,7 @@ pub fn format_protocol_response(response: &GetProtocolResponse) -> String {
         protocol_instructions,
         role_section,
         capabilities_section,
+        servers_section,
         init_condition,
     )
 }
@@ -84,12 +151,348 @@ pub async fn get_run_status<C: IpcClient>(
 ) -> Result<GetRunStatusResponse, IpcError> {
     let request = GetRunStatusRequest {
         run_id: run_id.to_string(),
+        format: None,
+        continuation: None,
     };
 
     let endpoint = format!("/api/runs/{}/status", run_id);
     client.request(&endpoint, &request).await
 }
 
+/// Fetches the capabilities the agents connected to a run declared at registration.
+pub async fn get_agent_capabilities<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+) -> Result<GetAgentCapabilitiesResponse, IpcError> {
+    let request = GetAgentCapabilitiesRequest {
+        run_id: run_id.to_string(),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/agents/capabilities", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Formats peer capabilities for display to the agent.
+pub fn format_agent_capabilities(response: &GetAgentCapabilitiesResponse) -> String {
+    let agents = if response.agents.is_empty() {
+        "(no agents connected)".to_string()
+    } else {
+        response
+            .agents
+            .iter()
+            .map(|agent| {
+                let details = match &agent.capabilities {
+                    Some(caps) => format!(
+                        "  - Languages: {}\n  - Filesystem access: {}\n  - Can run tests: {}\n  - Max context: {}",
+                        if caps.languages.is_empty() {
+                            "(not declared)".to_string()
+                        } else {
+                            caps.languages.join(", ")
+                        },
+                        caps.filesystem_access,
+                        caps.can_run_tests,
+                        caps.max_context_tokens
+                            .map(|t| format!("{} tokens", t))
+                            .unwrap_or_else(|| "(not declared)".to_string())
+                    ),
+                    None => "  (no capabilities declared)".to_string(),
+                };
+                format!(
+                    "- {}: {} ({})\n{}",
+                    agent.role_id, agent.session_name, agent.agent_type, details
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+
+    format!(
+        r#"═══════════════════════════════════════════════════════════════
+AGENT CAPABILITIES
+═══════════════════════════════════════════════════════════════
+
+{}
+
+═══════════════════════════════════════════════════════════════
+"#,
+        agents
+    )
+}
+
+/// Gathers what the server knows about the calling agent. The run's status and
+/// the role's capabilities are fetched live; backend failures leave them unset.
+pub async fn my_context<C: IpcClient>(
+    client: &C,
+    run_context: &RunContext,
+    session: Option<&(String, String)>,
+    socket_path: Option<&str>,
+) -> MyContext {
+    let mut context = MyContext {
+        session_name: session.map(|(name, _)| name.clone()),
+        project_dir: session.map(|(_, dir)| dir.clone()),
+        socket_path: socket_path.map(String::from),
+        run_id: run_context.run_id.clone(),
+        role: run_context.role.clone(),
+        tracked_documents: run_context.last_read_hashes.len(),
+        ..MyContext::default()
+    };
+    if let Some(run_id) = &run_context.run_id {
+        context.run_status = get_run_status(client, run_id)
+            .await
+            .ok()
+            .map(|r| r.status.to_string());
+        if let Some(role) = &run_context.role {
+            context.capabilities = get_protocol(client, run_id, role)
+                .await
+                .ok()
+                .and_then(|p| p.capabilities);
+        }
+    }
+    context
+}
+
+/// Formats the caller's context for display to the agent.
+pub fn format_my_context(context: &MyContext) -> String {
+    let unknown = |value: &Option<String>| value.clone().unwrap_or_else(|| "(unknown)".into());
+    let capabilities = match &context.capabilities {
+        Some(caps) => format!("can_resolve_impediments={}", caps.can_resolve_impediments),
+        None => "(unknown)".to_string(),
+    };
+    let hint = if context.run_id.is_none() {
+        "\nYou are not attached to a run. Use /hotwire to start one, /pair to join one,\n\
+        or resume_run to re-attach after a restart.\n"
+    } else {
+        ""
+    };
+
+    format!(
+        r#"═══════════════════════════════════════════════════════════════
+MY CONTEXT
+═══════════════════════════════════════════════════════════════
+
+Session: {}
+Project: {}
+Agent type: {}
+Backend socket: {}
+
+Run ID: {}
+Role: {}
+Run status: {}
+Capabilities: {}
+Tracked documents: {}
+{}
+═══════════════════════════════════════════════════════════════
+"#,
+        unknown(&context.session_name),
+        unknown(&context.project_dir),
+        unknown(&context.agent_type),
+        unknown(&context.socket_path),
+        context.run_id.as_deref().unwrap_or("(none)"),
+        context.role.as_deref().unwrap_or("(none)"),
+        unknown(&context.run_status),
+        capabilities,
+        context.tracked_documents,
+        hint,
+    )
+}
+
+/// Capabilities of each (run, role), learned from protocol fetches.
+#[derive(Default)]
+pub struct RoleCapabilityCache {
+    /// (run_id, role) -> capabilities; `None` when the protocol declared none
+    entries: Mutex<HashMap<(String, String), Option<RoleCapabilities>>>,
+}
+
+impl RoleCapabilityCache {
+    /// Remember the capabilities from a protocol fetched for `role` in `run_id`.
+    pub fn record(&self, run_id: &str, role: &str, capabilities: Option<RoleCapabilities>) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert((run_id.to_string(), role.to_string()), capabilities);
+    }
+
+    /// The capabilities of `role` in `run_id`, fetching its protocol on a miss.
+    /// `None` if none are declared or the backend could not be reached.
+    pub async fn capabilities<C: IpcClient>(
+        &self,
+        client: &C,
+        run_id: &str,
+        role: &str,
+    ) -> Option<RoleCapabilities> {
+        let key = (run_id.to_string(), role.to_string());
+        if let Some(capabilities) = self.entries.lock().unwrap().get(&key) {
+            return capabilities.clone();
+        }
+        let capabilities = get_protocol(client, run_id, role).await.ok()?.capabilities;
+        self.record(run_id, role, capabilities.clone());
+        capabilities
+    }
+}
+
+/// Protocols fetched per (run, role), so repeated `get_protocol` calls are
+/// answered locally until the run's protocol version changes.
+#[derive(Default)]
+pub struct ProtocolCache {
+    /// (run_id, role) -> (protocol, when it was fetched or last revalidated)
+    entries: Mutex<HashMap<(String, String), (GetProtocolResponse, Instant)>>,
+}
+
+impl ProtocolCache {
+    /// The cached protocol for `role` in `run_id` if it was validated less than
+    /// `ttl` before `now`.
+    pub fn cached(
+        &self,
+        run_id: &str,
+        role: &str,
+        ttl: Duration,
+        now: Instant,
+    ) -> Option<GetProtocolResponse> {
+        let entries = self.entries.lock().unwrap();
+        match entries.get(&(run_id.to_string(), role.to_string())) {
+            Some((protocol, at)) if now.duration_since(*at) < ttl => Some(protocol.clone()),
+            _ => None,
+        }
+    }
+
+    /// Remember a freshly fetched protocol.
+    pub fn record(&self, run_id: &str, role: &str, protocol: GetProtocolResponse, now: Instant) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert((run_id.to_string(), role.to_string()), (protocol, now));
+    }
+
+    /// Drop protocols cached for `run_id` whose version differs from `version`,
+    /// the version the backend currently reports. `None` tells us nothing.
+    pub fn observe_version(&self, run_id: &str, version: Option<&str>) {
+        let Some(version) = version else {
+            return;
+        };
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|(run, _), (protocol, _)| {
+                run != run_id || protocol.protocol_version.as_deref() == Some(version)
+            });
+    }
+
+    /// Restart the cached protocol's `ttl` and return it if its version is still `version`.
+    fn revalidate(
+        &self,
+        run_id: &str,
+        role: &str,
+        version: Option<&str>,
+        now: Instant,
+    ) -> Option<GetProtocolResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get_mut(&(run_id.to_string(), role.to_string())) {
+            Some((protocol, at))
+                if version.is_some() && protocol.protocol_version.as_deref() == version =>
+            {
+                *at = now;
+                Some(protocol.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// The protocol for `role` in `run_id`. A cached copy is served for `ttl`;
+    /// after that a run-status check confirms the protocol version is unchanged
+    /// before it is served again. Otherwise (or with `refresh`) it is refetched.
+    pub async fn protocol<C: IpcClient>(
+        &self,
+        client: &C,
+        run_id: &str,
+        role: &str,
+        ttl: Duration,
+        refresh: bool,
+    ) -> Result<GetProtocolResponse, IpcError> {
+        if !refresh {
+            if let Some(protocol) = self.cached(run_id, role, ttl, Instant::now()) {
+                return Ok(protocol);
+            }
+            let versioned = self
+                .entries
+                .lock()
+                .unwrap()
+                .get(&(run_id.to_string(), role.to_string()))
+                .is_some_and(|(protocol, _)| protocol.protocol_version.is_some());
+            if versioned {
+                if let Ok(status) = get_run_status(client, run_id).await {
+                    let version = status.protocol_version.as_deref();
+                    if let Some(protocol) = self.revalidate(run_id, role, version, Instant::now()) {
+                        return Ok(protocol);
+                    }
+                }
+            }
+        }
+        let protocol = get_protocol(client, run_id, role).await?;
+        self.record(run_id, role, protocol.clone(), Instant::now());
+        Ok(protocol)
+    }
+}
+
+/// Recently fetched run states, for the preflight guard on mutating tools.
+#[derive(Default)]
+pub struct RunStatusCache {
+    /// run_id -> (state, when it was fetched)
+    entries: Mutex<HashMap<String, (RunState, Instant)>>,
+}
+
+impl RunStatusCache {
+    /// The cached state of `run_id` if it was fetched less than `ttl` before `now`.
+    pub fn cached(&self, run_id: &str, ttl: Duration, now: Instant) -> Option<RunState> {
+        let entries = self.entries.lock().unwrap();
+        match entries.get(run_id) {
+            Some((status, at)) if now.duration_since(*at) < ttl => Some(status.clone()),
+            _ => None,
+        }
+    }
+
+    /// Remember a freshly fetched state.
+    pub fn record(&self, run_id: &str, status: RunState, now: Instant) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(run_id.to_string(), (status, now));
+    }
+
+    /// The state of `run_id`, from the cache or refreshed from the backend when stale.
+    pub async fn status<C: IpcClient>(
+        &self,
+        client: &C,
+        run_id: &str,
+        ttl: Duration,
+    ) -> Result<RunState, IpcError> {
+        if let Some(status) = self.cached(run_id, ttl, Instant::now()) {
+            return Ok(status);
+        }
+        let response = get_run_status(client, run_id).await?;
+        let status = RunState {
+            status: response.status,
+            phase: response.phase,
+        };
+        self.record(run_id, status.clone(), Instant::now());
+        Ok(status)
+    }
+}
+
+/// The statuses a run can move to next, or why there are none.
+pub fn next_statuses(status: &RunStatus) -> String {
+    match status.next() {
+        [] if status.is_ended() => "(none, the run has ended)".to_string(),
+        [] => "(unknown)".to_string(),
+        next => next
+            .iter()
+            .map(RunStatus::as_str)
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
 /// Formats the run status for display.
 ///
 /// IMPORTANT: Uses plain text format (not markdown bold) so that the mock agent's
@@ -104,10 +507,15 @@ pub fn format_run_status_response(response: &GetRunStatusResponse) -> String {
             .connected_agents
             .iter()
             .map(|agent| {
-                format!(
+                let mut line = format!(
                     "  - {}: {} ({})",
                     agent.role_id, agent.session_name, agent.agent_type
-                )
+                );
+                if let Some(progress) = agent.progress.as_ref().filter(|p| !p.is_empty()) {
+                    line.push_str(" — ");
+                    line.push_str(&progress.summary());
+                }
+                line
             })
             .collect::<Vec<_>>()
             .join("\n")
@@ -121,6 +529,7 @@ RUN STATUS
 Run ID: {}
 Status: {}
 Phase: {}
+Can Move To: {}
 Template: {}
 Has Protocol: {}
 
@@ -132,6 +541,7 @@ Connected Agents:
         response.run_id,
         response.status,
         response.phase,
+        next_statuses(&response.status),
         response.template_name,
         response.has_protocol,
         agents_section,
@@ -144,7 +554,6 @@ mod tests {
     use crate::ipc::mock::MockIpcClient;
 
     fn sample_protocol_response() -> GetProtocolResponse {
-        use crate::ipc::messages::RoleCapabilities;
         GetProtocolResponse {
             run_id: "test-run-123".into(),
             template_name: "Plan → Build".into(),
@@ -154,10 +563,67 @@ mod tests {
             project_name: Some("test-project".into()),
             capabilities: Some(RoleCapabilities {
                 can_resolve_impediments: true,
+                ..RoleCapabilities::default()
             }),
+            protocol_version: None,
+            mcp_servers: vec![],
         }
     }
 
+    #[tokio::test]
+    async fn test_switch_role_fetches_new_protocol() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/test-run-123/switch-role",
+            SwitchRoleResponse {
+                success: true,
+                error: None,
+            },
+        );
+        mock.when_called(
+            "/api/runs/test-run-123/protocol",
+            sample_protocol_response(),
+        );
+
+        let result = switch_role(
+            &mock,
+            "test-run-123",
+            "builder",
+            "strategist",
+            Some("builder disconnected"),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.template_name, "Plan → Build");
+        let switches = mock.requests_to("/api/runs/test-run-123/switch-role");
+        assert!(switches[0].contains("\"newRole\":\"builder\""));
+        assert!(switches[0].contains("\"source\":\"strategist\""));
+        let protocols = mock.requests_to("/api/runs/test-run-123/protocol");
+        assert!(protocols[0].contains("\"role\":\"builder\""));
+    }
+
+    #[tokio::test]
+    async fn test_switch_role_refused_by_backend() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/test-run-123/switch-role",
+            SwitchRoleResponse {
+                success: false,
+                error: Some("builder is still connected".into()),
+            },
+        );
+
+        let err = switch_role(&mock, "test-run-123", "builder", "strategist", None)
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().contains("builder is still connected"));
+        assert!(mock
+            .requests_to("/api/runs/test-run-123/protocol")
+            .is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_protocol_sends_correct_request() {
         let mock = MockIpcClient::new();
@@ -247,7 +713,6 @@ mod tests {
 
     #[test]
     fn test_format_protocol_no_capabilities_section_when_none() {
-        use crate::ipc::messages::RoleCapabilities;
         let response = GetProtocolResponse {
             run_id: "test-run".into(),
             template_name: "Test".into(),
@@ -257,7 +722,10 @@ mod tests {
             project_name: None,
             capabilities: Some(RoleCapabilities {
                 can_resolve_impediments: false,
+                ..RoleCapabilities::default()
             }),
+            protocol_version: None,
+            mcp_servers: vec![],
         };
         let formatted = format_protocol_response(&response);
 
@@ -266,12 +734,51 @@ mod tests {
             "Should NOT include capabilities section when no capabilities"
         );
     }
+
+    #[tokio::test]
+    async fn test_role_capability_cache_fetches_protocol_once() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/test-run-123/protocol",
+            sample_protocol_response(),
+        );
+        let cache = RoleCapabilityCache::default();
+
+        for _ in 0..2 {
+            let caps = cache
+                .capabilities(&mock, "test-run-123", "strategist")
+                .await
+                .unwrap();
+            assert!(caps.can_resolve_impediments);
+            assert!(caps.can_edit_artifacts);
+        }
+        assert_eq!(mock.requests_to("/api/runs/test-run-123/protocol").len(), 1);
+
+        // Unreachable backends are not cached
+        assert!(cache
+            .capabilities(&mock, "other-run", "strategist")
+            .await
+            .is_none());
+    }
+
+    #[test]
+    fn test_missing_permission_flags_default_to_allowed() {
+        let caps: RoleCapabilities =
+            serde_json::from_str(r#"{"canResolveImpediments": true}"#).unwrap();
+        assert!(caps.can_edit_artifacts);
+        assert!(caps.can_end_run);
+
+        let caps: RoleCapabilities =
+            serde_json::from_str(r#"{"canEditArtifacts": false}"#).unwrap();
+        assert!(!caps.can_edit_artifacts);
+        assert!(!caps.can_resolve_impediments);
+    }
 }
 
 #[cfg(test)]
 mod status_tests {
     use super::*;
-    use crate::ipc::messages::ConnectedAgent;
+    use crate::ipc::messages::{ConnectedAgent, StatusProgress};
     use crate::ipc::mock::MockIpcClient;
 
     fn sample_status_response() -> GetRunStatusResponse {
@@ -286,13 +793,21 @@ mod status_tests {
                     role_id: "strategist".into(),
                     session_name: "claude-1".into(),
                     agent_type: "claude".into(),
+                    progress: None,
                 },
                 ConnectedAgent {
                     role_id: "builder".into(),
                     session_name: "claude-2".into(),
                     agent_type: "claude".into(),
+                    progress: Some(StatusProgress {
+                        artifact_id: Some("spec".into()),
+                        section: Some("Caching".into()),
+                        percent_complete: Some(40),
+                        blocking_on: Some("strategist".into()),
+                    }),
                 },
             ],
+            protocol_version: None,
         }
     }
 
@@ -330,6 +845,10 @@ mod status_tests {
         assert!(formatted.contains("strategist"));
         assert!(formatted.contains("builder"));
         assert!(formatted.contains("Connected Agents"));
+        assert!(formatted.contains(
+            "  - strategist: claude-1 (claude)\n  - builder: claude-2 (claude) — 40% · `spec` § Caching · blocked on: strategist\n"
+        ));
+        assert!(formatted.contains("Can Move To: paused, completed, ended, cancelled, failed"));
     }
 
     #[test]
@@ -341,10 +860,206 @@ mod status_tests {
             template_name: "Plan → Build".into(),
             has_protocol: false,
             connected_agents: vec![],
+            protocol_version: None,
         };
 
         let formatted = format_run_status_response(&response);
 
         assert!(formatted.contains("(no agents connected)"));
     }
+
+    fn versioned_protocol(version: &str) -> GetProtocolResponse {
+        GetProtocolResponse {
+            run_id: "run-456".into(),
+            template_name: "Plan → Build".into(),
+            playbook_protocol: Some(format!("Protocol {}", version)),
+            role_protocol: None,
+            initialization_condition: None,
+            project_name: None,
+            capabilities: None,
+            protocol_version: Some(version.into()),
+            mcp_servers: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_protocol_cache_serves_repeats_and_revalidates_by_version() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/runs/run-456/protocol", versioned_protocol("v1"));
+        mock.when_called(
+            "/api/runs/run-456/status",
+            GetRunStatusResponse {
+                protocol_version: Some("v1".into()),
+                ..sample_status_response()
+            },
+        );
+        let cache = ProtocolCache::default();
+        let ttl = Duration::from_secs(300);
+
+        for _ in 0..3 {
+            cache
+                .protocol(&mock, "run-456", "builder", ttl, false)
+                .await
+                .unwrap();
+        }
+        assert_eq!(mock.requests_to("/api/runs/run-456/protocol").len(), 1);
+        assert_eq!(mock.requests_to("/api/runs/run-456/status").len(), 0);
+
+        // Stale but unchanged: one status check, no refetch
+        cache
+            .protocol(&mock, "run-456", "builder", Duration::ZERO, false)
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to("/api/runs/run-456/status").len(), 1);
+        assert_eq!(mock.requests_to("/api/runs/run-456/protocol").len(), 1);
+
+        // Other roles are cached separately
+        cache
+            .protocol(&mock, "run-456", "reviewer", ttl, false)
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to("/api/runs/run-456/protocol").len(), 2);
+
+        cache
+            .protocol(&mock, "run-456", "builder", ttl, true)
+            .await
+            .unwrap();
+        assert_eq!(mock.requests_to("/api/runs/run-456/protocol").len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_cache_refetches_after_version_change() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/runs/run-456/protocol", versioned_protocol("v1"));
+        mock.when_called(
+            "/api/runs/run-456/status",
+            GetRunStatusResponse {
+                protocol_version: Some("v2".into()),
+                ..sample_status_response()
+            },
+        );
+        let cache = ProtocolCache::default();
+        let ttl = Duration::from_secs(300);
+        cache
+            .protocol(&mock, "run-456", "builder", ttl, false)
+            .await
+            .unwrap();
+
+        mock.when_called("/api/runs/run-456/protocol", versioned_protocol("v2"));
+        let protocol = cache
+            .protocol(&mock, "run-456", "builder", Duration::ZERO, false)
+            .await
+            .unwrap();
+        assert_eq!(protocol.protocol_version.as_deref(), Some("v2"));
+        assert_eq!(mock.requests_to("/api/runs/run-456/protocol").len(), 2);
+
+        // A status reporting a newer version drops the entry; an unknown version keeps it
+        cache.observe_version("run-456", None);
+        assert!(cache
+            .cached("run-456", "builder", ttl, Instant::now())
+            .is_some());
+        cache.observe_version("run-456", Some("v3"));
+        assert!(cache
+            .cached("run-456", "builder", ttl, Instant::now())
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_run_status_cache_refreshes_when_stale() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/runs/run-456/status", sample_status_response());
+        let cache = RunStatusCache::default();
+        let ttl = Duration::from_secs(30);
+
+        assert_eq!(
+            cache.status(&mock, "run-456", ttl).await.unwrap().status,
+            RunStatus::Active
+        );
+        assert_eq!(
+            cache.status(&mock, "run-456", ttl).await.unwrap().status,
+            RunStatus::Active
+        );
+        assert_eq!(mock.requests_to("/api/runs/run-456/status").len(), 1);
+
+        let later = Instant::now() + ttl;
+        assert_eq!(cache.cached("run-456", ttl, later), None);
+        assert_eq!(
+            cache
+                .status(&mock, "run-456", Duration::ZERO)
+                .await
+                .unwrap()
+                .status,
+            RunStatus::Active
+        );
+        assert_eq!(mock.requests_to("/api/runs/run-456/status").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_my_context_combines_local_and_backend_state() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/runs/run-456/status", sample_status_response());
+        let mut run_context = RunContext::default();
+        run_context.attach("run-456", "builder");
+        run_context.record_hash("run-456", "art-1", "abc");
+        let session = ("hw-1".to_string(), "/path".to_string());
+
+        let context = my_context(&mock, &run_context, Some(&session), Some("/tmp/h.sock")).await;
+
+        assert_eq!(context.session_name.as_deref(), Some("hw-1"));
+        assert_eq!(context.run_status.as_deref(), Some("active"));
+        // No protocol response configured, so capabilities stay unknown
+        assert!(context.capabilities.is_none());
+        assert_eq!(context.tracked_documents, 1);
+
+        let formatted = format_my_context(&context);
+        assert!(formatted.contains("Role: builder"));
+        assert!(formatted.contains("Backend socket: /tmp/h.sock"));
+        assert!(formatted.contains("Capabilities: (unknown)"));
+    }
+
+    #[test]
+    fn test_format_my_context_without_run_suggests_next_step() {
+        let formatted = format_my_context(&MyContext::default());
+        assert!(formatted.contains("Run ID: (none)"));
+        assert!(formatted.contains("resume_run"));
+    }
+
+    #[tokio::test]
+    async fn test_get_agent_capabilities_formats_declared_and_missing() {
+        use crate::ipc::messages::{AgentCapabilities, AgentCapabilityInfo};
+
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-456/agents/capabilities",
+            GetAgentCapabilitiesResponse {
+                agents: vec![
+                    AgentCapabilityInfo {
+                        role_id: "builder".into(),
+                        session_name: "claude-2".into(),
+                        agent_type: "claude".into(),
+                        capabilities: Some(AgentCapabilities {
+                            languages: vec!["rust".into(), "typescript".into()],
+                            filesystem_access: true,
+                            can_run_tests: true,
+                            max_context_tokens: Some(200_000),
+                        }),
+                    },
+                    AgentCapabilityInfo {
+                        role_id: "strategist".into(),
+                        session_name: "claude-1".into(),
+                        agent_type: "claude".into(),
+                        capabilities: None,
+                    },
+                ],
+            },
+        );
+
+        let response = get_agent_capabilities(&mock, "run-456").await.unwrap();
+        let formatted = format_agent_capabilities(&response);
+
+        assert!(formatted.contains("- builder: claude-2 (claude)"));
+        assert!(formatted.contains("Languages: rust, typescript"));
+        assert!(formatted.contains("Max context: 200000 tokens"));
+        assert!(formatted.contains("(no capabilities declared)"));
+    }
 }
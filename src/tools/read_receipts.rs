@@ -0,0 +1,157 @@
+//! Which version of a document each agent has read.
+//!
+//! Every `doc_artifact_read` and `doc_artifact_review_context` reports the
+//! content hash it returned, under the reader's role. `get_artifact_readers`
+//! compares those receipts with the document as it is now, so a writer can
+//! tell whether the reviewer has seen the latest version before asking for
+//! a review.
+
+use crate::ipc::messages::{
+    GetArtifactReadersRequest, GetArtifactReadersResponse, ReportArtifactReadRequest,
+    ReportArtifactReadResponse,
+};
+use crate::ipc::traits::IpcClient;
+use crate::tools::timestamps;
+use crate::types::errors::IpcError;
+use std::fmt::Write;
+
+/// Records that `role` read `artifact_id` at `content_hash`.
+pub async fn report_read<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+    content_hash: &str,
+    role: &str,
+    read_to_end: bool,
+) -> Result<(), IpcError> {
+    let request = ReportArtifactReadRequest {
+        run_id: run_id.to_string(),
+        artifact_id: artifact_id.to_string(),
+        content_hash: content_hash.to_string(),
+        source: role.to_string(),
+        read_to_end,
+    };
+
+    let endpoint = format!(
+        "/api/runs/{}/artifacts/{}/readers/report",
+        run_id, artifact_id
+    );
+    let response: ReportArtifactReadResponse = client.request(&endpoint, &request).await?;
+    if response.success {
+        Ok(())
+    } else {
+        Err(IpcError::RequestFailed(
+            "Backend reported failure recording the read".to_string(),
+        ))
+    }
+}
+
+/// Fetches the last read of `artifact_id` by each role.
+pub async fn get_readers<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+) -> Result<GetArtifactReadersResponse, IpcError> {
+    let request = GetArtifactReadersRequest {
+        run_id: run_id.to_string(),
+        artifact_id: artifact_id.to_string(),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/artifacts/{}/readers", run_id, artifact_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Formats who has seen the current version, up to date readers first.
+pub fn format_readers(response: &GetArtifactReadersResponse) -> String {
+    if response.readers.is_empty() {
+        return format!(
+            "No agent has read {} ({}) yet.",
+            response.filename, response.artifact_id
+        );
+    }
+    let current = response
+        .readers
+        .iter()
+        .filter(|r| r.is_current(&response.content_hash))
+        .count();
+    let mut output = format!(
+        "## Readers of {} ({})\n\n{} of {} reader(s) have seen the current version (hash `{}`).\n\n",
+        response.filename,
+        response.artifact_id,
+        current,
+        response.readers.len(),
+        response.content_hash
+    );
+    let mut readers: Vec<_> = response.readers.iter().collect();
+    readers.sort_by_key(|r| !r.is_current(&response.content_hash));
+    for reader in readers {
+        let age = timestamps::age(&reader.read_at);
+        let _ = if reader.is_current(&response.content_hash) {
+            writeln!(
+                output,
+                "- ✓ **{}** read the current version {}",
+                reader.role, age
+            )
+        } else if reader.content_hash == response.content_hash {
+            writeln!(
+                output,
+                "- **{}** read only part of the current version {}",
+                reader.role, age
+            )
+        } else {
+            writeln!(
+                output,
+                "- **{}** last read an older version (hash `{}`) {}",
+                reader.role, reader.content_hash, age
+            )
+        };
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::mock::MockIpcClient;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_report_and_list_readers() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/artifacts/spec/readers/report",
+            ReportArtifactReadResponse { success: true },
+        );
+        mock.when_called(
+            "/api/runs/run-1/artifacts/spec/readers",
+            json!({
+                "runId": "run-1",
+                "artifactId": "spec",
+                "filename": "spec.md",
+                "contentHash": "h2",
+                "readers": [
+                    {"role": "strategist", "contentHash": "h1", "readAt": "2024-01-15T10:00:00Z", "readToEnd": true},
+                    {"role": "builder", "contentHash": "h2", "readAt": "2024-01-15T10:05:00Z", "readToEnd": false},
+                    {"role": "reviewer", "contentHash": "h2", "readAt": "2024-01-15T10:06:00Z", "readToEnd": true}
+                ]
+            }),
+        );
+
+        report_read(&mock, "run-1", "spec", "h2", "reviewer", true)
+            .await
+            .unwrap();
+        let sent = &mock.requests_to("/api/runs/run-1/artifacts/spec/readers/report")[0];
+        assert!(sent.contains("\"contentHash\":\"h2\",\"source\":\"reviewer\",\"readToEnd\":true"));
+
+        let output = format_readers(&get_readers(&mock, "run-1", "spec").await.unwrap());
+        assert!(output.starts_with(
+            "## Readers of spec.md (spec)\n\n\
+            1 of 3 reader(s) have seen the current version (hash `h2`).\n\n\
+            - ✓ **reviewer** read the current version "
+        ));
+        assert!(output.contains("\n- **strategist** last read an older version (hash `h1`) "));
+        assert!(output.contains("\n- **builder** read only part of the current version "));
+    }
+}
@@ -0,0 +1,227 @@
+//! CSV and JSONL reports of comments and suggestions.
+//!
+//! `doc_artifact_list_comments` and `doc_artifact_list_suggestions` can export
+//! the listed records instead of rendering them, so review metrics from a large
+//! documentation run can be analyzed in a spreadsheet. Every record carries its
+//! artifact id, so reports of several artifacts can be concatenated.
+
+use crate::ipc::messages::{
+    DocArtifactListCommentsResponse, DocArtifactListSuggestionsResponse, ReportFormat,
+};
+use serde::Serialize;
+
+const COMMENT_COLUMNS: &[&str] = &[
+    "id",
+    "artifact_id",
+    "comment_type",
+    "status",
+    "author",
+    "selection_start",
+    "selection_end",
+    "selection_text",
+    "content",
+    "suggested_text",
+    "resolved_by",
+    "resolved_at",
+    "resolution_note",
+    "created_at",
+];
+
+const SUGGESTION_COLUMNS: &[&str] = &[
+    "id",
+    "artifact_id",
+    "comment_id",
+    "suggested_by",
+    "edit_type",
+    "status",
+    "start_offset",
+    "end_offset",
+    "original_text",
+    "suggested_text",
+    "rationale",
+    "accepted_by",
+    "accepted_at",
+    "rejection_reason",
+    "created_at",
+    "updated_at",
+];
+
+/// Report of the listed comments.
+pub fn comments_report(
+    response: &DocArtifactListCommentsResponse,
+    format: ReportFormat,
+) -> serde_json::Result<String> {
+    match format {
+        ReportFormat::Csv => Ok(csv(
+            COMMENT_COLUMNS,
+            response.comments.iter().map(|c| {
+                vec![
+                    c.id.clone(),
+                    response.artifact_id.clone(),
+                    c.comment_type.clone(),
+                    c.status.clone(),
+                    c.author.clone(),
+                    c.selection_start.to_string(),
+                    c.selection_end.to_string(),
+                    c.selection_text.clone().unwrap_or_default(),
+                    c.content.clone(),
+                    c.suggested_text.clone().unwrap_or_default(),
+                    c.resolved_by.clone().unwrap_or_default(),
+                    c.resolved_at.clone().unwrap_or_default(),
+                    c.resolution_note.clone().unwrap_or_default(),
+                    c.created_at.clone(),
+                ]
+            }),
+        )),
+        ReportFormat::Jsonl => {
+            let records = response
+                .comments
+                .iter()
+                .map(|comment| {
+                    let mut record = serde_json::to_value(comment)?;
+                    record["artifactId"] = response.artifact_id.clone().into();
+                    Ok(record)
+                })
+                .collect::<serde_json::Result<Vec<_>>>()?;
+            jsonl(&records)
+        }
+    }
+}
+
+/// Report of the listed suggestions.
+pub fn suggestions_report(
+    response: &DocArtifactListSuggestionsResponse,
+    format: ReportFormat,
+) -> serde_json::Result<String> {
+    match format {
+        ReportFormat::Csv => Ok(csv(
+            SUGGESTION_COLUMNS,
+            response.suggestions.iter().map(|s| {
+                vec![
+                    s.id.clone(),
+                    s.artifact_id.clone(),
+                    s.comment_id.clone(),
+                    s.suggested_by.clone(),
+                    s.edit_type.clone(),
+                    s.status.clone(),
+                    s.start_offset.map(|o| o.to_string()).unwrap_or_default(),
+                    s.end_offset.map(|o| o.to_string()).unwrap_or_default(),
+                    s.original_text.clone().unwrap_or_default(),
+                    s.suggested_text.clone(),
+                    s.rationale.clone().unwrap_or_default(),
+                    s.accepted_by.clone().unwrap_or_default(),
+                    s.accepted_at.clone().unwrap_or_default(),
+                    s.rejection_reason.clone().unwrap_or_default(),
+                    s.created_at.clone(),
+                    s.updated_at.clone(),
+                ]
+            }),
+        )),
+        ReportFormat::Jsonl => jsonl(&response.suggestions),
+    }
+}
+
+/// Confirmation for a report written to `path`.
+pub fn format_written(count: usize, noun: &str, path: &str) -> String {
+    format!(
+        "✓ Wrote {} {}{} to `{}`",
+        count,
+        noun,
+        if count == 1 { "" } else { "s" },
+        path
+    )
+}
+
+/// RFC 4180 CSV: a header row, then one row per record, CRLF line endings.
+fn csv(headers: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut out = headers.join(",");
+    out.push_str("\r\n");
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&fields.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// `field`, quoted if it contains a delimiter, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn jsonl<T: Serialize>(records: &[T]) -> serde_json::Result<String> {
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&serde_json::to_string(record)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::CommentDetail;
+
+    fn comments() -> DocArtifactListCommentsResponse {
+        DocArtifactListCommentsResponse {
+            artifact_id: "spec".to_string(),
+            offset: 0,
+            has_more: false,
+            comments: vec![CommentDetail {
+                id: "c1".to_string(),
+                comment_type: "issue".to_string(),
+                status: "open".to_string(),
+                selection_start: 10,
+                selection_end: 20,
+                selection_text: Some("Step one".to_string()),
+                content: "Say \"why\", not how,\nplease".to_string(),
+                suggested_text: None,
+                author: "reviewer".to_string(),
+                resolved_by: None,
+                resolved_at: None,
+                resolution_note: None,
+                created_at: "2024-01-15T10:00:00Z".to_string(),
+                mentions: vec![],
+                parent_comment_id: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_comments_csv_quotes_fields() {
+        let report = comments_report(&comments(), ReportFormat::Csv).unwrap();
+        let (header, rows) = report.split_once("\r\n").unwrap();
+        assert_eq!(header, COMMENT_COLUMNS.join(","));
+        assert_eq!(
+            rows,
+            "c1,spec,issue,open,reviewer,10,20,Step one,\
+            \"Say \"\"why\"\", not how,\nplease\",,,,,2024-01-15T10:00:00Z\r\n"
+        );
+    }
+
+    #[test]
+    fn test_comments_jsonl_carries_artifact_id() {
+        let report = comments_report(&comments(), ReportFormat::Jsonl).unwrap();
+        assert_eq!(report.lines().count(), 1);
+        let record: serde_json::Value = serde_json::from_str(report.trim_end()).unwrap();
+        assert_eq!(record["artifactId"], "spec");
+        assert_eq!(record["selectionStart"], 10);
+
+        let empty = DocArtifactListSuggestionsResponse {
+            artifact_id: "spec".to_string(),
+            offset: 0,
+            has_more: false,
+            suggestions: vec![],
+        };
+        assert_eq!(suggestions_report(&empty, ReportFormat::Jsonl).unwrap(), "");
+        assert_eq!(
+            suggestions_report(&empty, ReportFormat::Csv).unwrap(),
+            format!("{}\r\n", SUGGESTION_COLUMNS.join(","))
+        );
+    }
+}
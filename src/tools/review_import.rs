@@ -0,0 +1,492 @@
+//! Import of GitHub pull-request review comments.
+//!
+//! A review exported with `gh api repos/{owner}/{repo}/pulls/{number}/comments`
+//! anchors each comment to a line of a file in the PR. The document in the
+//! run may have changed since, so the commented text is taken from the end of
+//! the comment's diff hunk and searched for in the current artifact; the match
+//! closest to the original line number becomes the comment's selection.
+//! Comments without a diff hunk fall back to their line numbers.
+
+use crate::ipc::messages::{
+    ArtifactSummary, CommentType, DocArtifactReadResponse, ImportedComment, ReviewImport,
+    SkippedComment,
+};
+use crate::ipc::traits::IpcClient;
+use crate::tools::artifacts;
+use crate::types::errors::IpcError;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// One comment from a GitHub review export. Only the fields used here.
+#[derive(Deserialize, Debug, Clone)]
+pub struct GithubReviewComment {
+    pub id: u64,
+    pub path: String,
+    pub body: String,
+    /// Last line of the commented range in the PR's version of the file
+    pub line: Option<i64>,
+    pub original_line: Option<i64>,
+    /// First line of a multi-line comment
+    pub start_line: Option<i64>,
+    /// "RIGHT" (the PR's version, default) or "LEFT" (the base version)
+    pub side: Option<String>,
+    pub diff_hunk: Option<String>,
+    pub user: Option<GithubUser>,
+    pub in_reply_to_id: Option<u64>,
+    pub html_url: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GithubUser {
+    pub login: String,
+}
+
+/// Where a comment lands in a document, in characters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub start: i64,
+    pub end: i64,
+    /// 1-based line the selection starts on
+    pub line: i64,
+}
+
+/// Parse a review export: the array of review comments GitHub's REST API
+/// returns, or an object holding it under `comments`.
+pub fn parse_review(json: &str) -> Result<Vec<GithubReviewComment>, String> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Export {
+        Comments(Vec<GithubReviewComment>),
+        Review { comments: Vec<GithubReviewComment> },
+    }
+    let export: Export =
+        serde_json::from_str(json).map_err(|e| format!("not a GitHub review export: {}", e))?;
+    let (Export::Comments(comments) | Export::Review { comments }) = export;
+    Ok(comments)
+}
+
+/// Create a doc-artifact comment for every review comment. Comments go to
+/// `artifact_id` if given, else to the artifact whose filename matches the
+/// comment's path. Replies stay in their thread. Only listing the artifacts
+/// fails the whole import; other failures skip the comment concerned.
+pub async fn import_review<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    mut comments: Vec<GithubReviewComment>,
+    artifact_id: Option<&str>,
+    author: &str,
+    page_lines: i64,
+) -> Result<ReviewImport, IpcError> {
+    let listing = artifacts::list_artifacts(client, run_id).await?;
+    // Replies have higher ids than the comments they answer
+    comments.sort_by_key(|c| c.id);
+
+    let mut documents: HashMap<String, Result<DocArtifactReadResponse, String>> = HashMap::new();
+    // GitHub comment id -> (artifact id, comment id, location) of imported comments
+    let mut threads: HashMap<u64, (String, String, Location)> = HashMap::new();
+    let mut import = ReviewImport {
+        run_id: run_id.to_string(),
+        imported: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for comment in comments {
+        let skip = |reason: String| SkippedComment {
+            github_id: comment.id,
+            path: comment.path.clone(),
+            reason,
+        };
+        let parent = comment
+            .in_reply_to_id
+            .and_then(|id| threads.get(&id))
+            .cloned();
+        let (target, location, parent_id) = match parent {
+            Some((target, parent_id, location)) => (target, location, Some(parent_id)),
+            None => {
+                let target = match artifact_id {
+                    Some(id) => id.to_string(),
+                    None => match matching_artifact(&listing.artifacts, &comment.path) {
+                        Ok(artifact) => artifact.id.clone(),
+                        Err(reason) => {
+                            import.skipped.push(skip(reason));
+                            continue;
+                        }
+                    },
+                };
+                if !documents.contains_key(&target) {
+                    let document =
+                        artifacts::read_full_content(client, run_id, &target, page_lines)
+                            .await
+                            .map_err(|e| format!("failed to read artifact {}: {}", target, e));
+                    documents.insert(target.clone(), document);
+                }
+                let location = match &documents[&target] {
+                    Ok(document) => locate(&document.content, &comment),
+                    Err(reason) => Err(reason.clone()),
+                };
+                match location {
+                    Ok(location) => (target, location, None),
+                    Err(reason) => {
+                        import.skipped.push(skip(reason));
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let (comment_type, content, suggested_text) = comment_content(&comment);
+        match artifacts::add_comment(
+            client,
+            run_id,
+            &target,
+            comment_type,
+            location.start,
+            location.end,
+            &content,
+            suggested_text.as_deref(),
+            author,
+            parent_id.as_deref(),
+            None,
+        )
+        .await
+        {
+            Ok(response) => {
+                threads.insert(
+                    comment.id,
+                    (target.clone(), response.comment_id.clone(), location),
+                );
+                import.imported.push(ImportedComment {
+                    github_id: comment.id,
+                    artifact_id: target,
+                    comment_id: response.comment_id,
+                    line: location.line,
+                });
+            }
+            Err(e) => import
+                .skipped
+                .push(skip(format!("failed to add comment: {}", e))),
+        }
+    }
+    Ok(import)
+}
+
+/// The artifact a review comment on `path` belongs to: an exact filename
+/// match, else the only artifact whose filename and `path` end the same way.
+fn matching_artifact<'a>(
+    artifacts: &'a [ArtifactSummary],
+    path: &str,
+) -> Result<&'a ArtifactSummary, String> {
+    if let Some(exact) = artifacts.iter().find(|a| a.filename == path) {
+        return Ok(exact);
+    }
+    let ends_with = |longer: &str, shorter: &str| {
+        longer
+            .strip_suffix(shorter)
+            .is_some_and(|prefix| prefix.ends_with('/'))
+    };
+    let candidates: Vec<&ArtifactSummary> = artifacts
+        .iter()
+        .filter(|a| ends_with(path, &a.filename) || ends_with(&a.filename, path))
+        .collect();
+    match candidates.as_slice() {
+        [artifact] => Ok(artifact),
+        [] => Err(format!("no artifact matches {}", path)),
+        _ => Err(format!(
+            "{} matches several artifacts; pass artifact_id",
+            path
+        )),
+    }
+}
+
+/// The commented lines, taken from the end of the diff hunk (which stops at
+/// the commented line), without their diff markers.
+fn anchored_lines(comment: &GithubReviewComment) -> Vec<&str> {
+    let Some(hunk) = &comment.diff_hunk else {
+        return Vec::new();
+    };
+    // Lines removed on the other side of the diff are not in this version
+    let other_side = match comment.side.as_deref() {
+        Some("LEFT") => '+',
+        _ => '-',
+    };
+    let lines: Vec<&str> = hunk
+        .lines()
+        .filter(|l| !l.starts_with("@@") && !l.starts_with(other_side))
+        .map(|l| l.get(1..).unwrap_or(""))
+        .collect();
+    let count = match (comment.start_line, comment.line.or(comment.original_line)) {
+        (Some(start), Some(end)) if start <= end => (end - start + 1) as usize,
+        _ => 1,
+    };
+    lines[lines.len().saturating_sub(count)..].to_vec()
+}
+
+/// Character range of `comment` in `content`.
+pub fn locate(content: &str, comment: &GithubReviewComment) -> Result<Location, String> {
+    let line = comment.line.or(comment.original_line);
+    let needle = anchored_lines(comment).join("\n");
+    let (start, end) = if !needle.trim().is_empty() {
+        let target = line.unwrap_or(1);
+        content
+            .match_indices(needle.as_str())
+            .map(|(start, text)| (start, start + text.len()))
+            .min_by_key(|(start, _)| (line_at(content, *start) - target).abs())
+            .ok_or_else(|| "commented text is no longer in the document".to_string())?
+    } else {
+        let end_line = line.ok_or("comment has neither a diff hunk nor a line number")?;
+        let start_line = comment.start_line.unwrap_or(end_line).min(end_line);
+        line_range(content, start_line, end_line).ok_or_else(|| {
+            format!(
+                "line {} is past the end of the document ({} lines)",
+                end_line,
+                content.lines().count()
+            )
+        })?
+    };
+    Ok(Location {
+        start: content[..start].chars().count() as i64,
+        end: content[..end].chars().count() as i64,
+        line: line_at(content, start),
+    })
+}
+
+/// 1-based line of byte offset `byte`.
+fn line_at(content: &str, byte: usize) -> i64 {
+    content[..byte].matches('\n').count() as i64 + 1
+}
+
+/// Byte range from the start of line `first` to the end of line `last` (1-based).
+fn line_range(content: &str, first: i64, last: i64) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    let mut start = None;
+    for (number, line) in (1..).zip(content.split('\n')) {
+        if number == first {
+            start = Some(offset);
+        }
+        if number == last {
+            return Some((start?, offset + line.len()));
+        }
+        offset += line.len() + 1;
+    }
+    None
+}
+
+/// Comment type, content, and suggested text for a review comment. A
+/// ```` ```suggestion ```` block becomes the suggested text.
+fn comment_content(comment: &GithubReviewComment) -> (CommentType, String, Option<String>) {
+    let (text, suggestion) = match comment.body.split_once("```suggestion") {
+        Some((before, rest)) => {
+            let rest = rest.split_once('\n').map_or("", |(_, r)| r);
+            let (suggested, after) = rest.split_once("```").unwrap_or((rest, ""));
+            (
+                format!("{}{}", before.trim_end(), after).trim().to_string(),
+                Some(
+                    suggested
+                        .strip_suffix('\n')
+                        .unwrap_or(suggested)
+                        .to_string(),
+                ),
+            )
+        }
+        None => (comment.body.trim().to_string(), None),
+    };
+    let comment_type = if suggestion.is_some() {
+        CommentType::Suggestion
+    } else {
+        CommentType::Comment
+    };
+    let text = if text.is_empty() {
+        "Suggested change".to_string()
+    } else {
+        text
+    };
+    let reviewer = comment
+        .user
+        .as_ref()
+        .map_or("a reviewer".to_string(), |u| format!("@{}", u.login));
+    let mut content = format!("{}\n\n(GitHub review by {}", text, reviewer);
+    if let Some(url) = &comment.html_url {
+        let _ = write!(content, ": {}", url);
+    }
+    content.push(')');
+    (comment_type, content, suggestion)
+}
+
+/// Summarize an import.
+pub fn format_review_import(import: &ReviewImport) -> String {
+    let mut output = format!(
+        "✓ Imported {} review comments into run {}\n",
+        import.imported.len(),
+        import.run_id
+    );
+    for comment in &import.imported {
+        let _ = writeln!(
+            output,
+            "- #{} → `{}` on `{}` (line {})",
+            comment.github_id, comment.comment_id, comment.artifact_id, comment.line
+        );
+    }
+    if !import.skipped.is_empty() {
+        let _ = write!(output, "\n**Skipped:** {}\n", import.skipped.len());
+        for comment in &import.skipped {
+            let _ = writeln!(
+                output,
+                "- #{} on {}: {}",
+                comment.github_id, comment.path, comment.reason
+            );
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::mock::MockIpcClient;
+    use serde_json::json;
+
+    const SPEC: &str =
+        "# Spec\n\nThe cache stores “parsed” documents.\nEntries expire after an hour.\n";
+
+    fn review_comment(id: u64, line: Option<i64>, hunk: Option<&str>) -> GithubReviewComment {
+        GithubReviewComment {
+            id,
+            path: "docs/spec.md".to_string(),
+            body: "Why an hour?".to_string(),
+            line,
+            original_line: None,
+            start_line: None,
+            side: None,
+            diff_hunk: hunk.map(String::from),
+            user: Some(GithubUser {
+                login: "octocat".to_string(),
+            }),
+            in_reply_to_id: None,
+            html_url: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_review_accepts_array_or_object() {
+        let comment = r#"{"id": 1, "path": "spec.md", "body": "Nit", "line": 3, "user": {"login": "octocat"}, "extra": true}"#;
+        assert_eq!(parse_review(&format!("[{}]", comment)).unwrap().len(), 1);
+        assert_eq!(
+            parse_review(&format!(r#"{{"id": 9, "comments": [{}]}}"#, comment))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(parse_review(r#"{"message": "Not Found"}"#).is_err());
+    }
+
+    #[test]
+    fn test_locate_searches_for_the_commented_text() {
+        // The PR had the line at 9; the document has moved it to line 4
+        let hunk = "@@ -7,3 +7,3 @@\n The cache stores “parsed” documents.\n-Entries expire.\n+Entries expire after an hour.";
+        let location = locate(SPEC, &review_comment(1, Some(9), Some(hunk))).unwrap();
+        let start = SPEC.find("Entries").unwrap();
+        assert_eq!(location.line, 4);
+        // Character offsets: the curly quotes are multi-byte
+        assert_eq!(location.start, SPEC[..start].chars().count() as i64);
+        assert_eq!(
+            location.end - location.start,
+            "Entries expire after an hour.".len() as i64
+        );
+
+        // Multi-line comment covers both lines
+        let mut multi = review_comment(2, Some(4), Some(hunk));
+        multi.start_line = Some(3);
+        let location = locate(SPEC, &multi).unwrap();
+        assert_eq!(location.line, 3);
+
+        // Text that is gone is not guessed at
+        let gone = "@@ -1,1 +1,1 @@\n+Entries never expire.";
+        assert!(locate(SPEC, &review_comment(3, Some(4), Some(gone))).is_err());
+    }
+
+    #[test]
+    fn test_locate_falls_back_to_line_numbers() {
+        let location = locate(SPEC, &review_comment(1, Some(1), None)).unwrap();
+        assert_eq!((location.start, location.end, location.line), (0, 6, 1));
+        assert!(locate(SPEC, &review_comment(1, Some(40), None)).is_err());
+    }
+
+    #[test]
+    fn test_suggestion_blocks_become_suggested_text() {
+        let mut comment = review_comment(1, Some(4), None);
+        comment.body =
+            "Shorter:\n```suggestion\nEntries expire hourly.\n```\nThoughts?".to_string();
+        comment.html_url = Some("https://github.com/o/r/pull/1#r1".to_string());
+        let (comment_type, content, suggested) = comment_content(&comment);
+        assert_eq!(comment_type, CommentType::Suggestion);
+        assert_eq!(suggested.as_deref(), Some("Entries expire hourly."));
+        assert_eq!(
+            content,
+            "Shorter:\nThoughts?\n\n(GitHub review by @octocat: https://github.com/o/r/pull/1#r1)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_review_keeps_threads_and_reports_skips() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/artifacts",
+            json!({
+                "runId": "run-1",
+                "artifacts": [{
+                    "id": "spec",
+                    "filename": "spec.md",
+                    "documentType": "markdown",
+                    "totalLines": 4,
+                    "contentHash": "hash",
+                    "createdAt": "2024-01-15T10:00:00Z",
+                    "updatedAt": "2024-01-15T10:00:00Z"
+                }]
+            }),
+        );
+        mock.when_called(
+            "/api/runs/run-1/artifacts/spec",
+            json!({
+                "artifactId": "spec",
+                "filename": "spec.md",
+                "content": SPEC,
+                "contentHash": "hash",
+                "totalLines": 4,
+                "returnedLines": 4,
+                "offset": 0,
+                "hasMore": false
+            }),
+        );
+        mock.when_called(
+            "/api/runs/run-1/artifacts/spec/comments/add",
+            json!({
+                "commentId": "cmt_1",
+                "artifactId": "spec",
+                "selectionText": "Entries expire after an hour."
+            }),
+        );
+
+        let mut reply = review_comment(2, Some(4), None);
+        reply.in_reply_to_id = Some(1);
+        let mut elsewhere = review_comment(3, Some(1), None);
+        elsewhere.path = "src/main.rs".to_string();
+        // Out of order on purpose: the reply must still follow its parent
+        let comments = vec![reply, review_comment(1, Some(4), None), elsewhere];
+
+        let import = import_review(&mock, "run-1", comments, None, "human", 500)
+            .await
+            .unwrap();
+        assert_eq!(import.imported.len(), 2);
+        assert_eq!(import.skipped.len(), 1);
+        assert_eq!(import.skipped[0].reason, "no artifact matches src/main.rs");
+
+        let added = mock.requests_to("/api/runs/run-1/artifacts/spec/comments/add");
+        assert!(!added[0].contains("parentCommentId"));
+        assert!(added[1].contains(r#""parentCommentId":"cmt_1""#));
+        assert!(added[1].contains(r#""author":"human""#));
+
+        let output = format_review_import(&import);
+        assert!(output.contains("Imported 2 review comments"));
+        assert!(output.contains("- #3 on src/main.rs: no artifact matches src/main.rs"));
+    }
+}
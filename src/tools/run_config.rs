@@ -0,0 +1,128 @@
+//! How a run was started.
+//!
+//! An agent that lost its context (a compaction, a restarted session) only
+//! has the run ID left. `get_run_config` gives back the brief: the intent the
+//! human typed, the artifacts and answers they picked when the run was set
+//! up, and the initialization condition, so the agent can pick up again
+//! without asking the human to repeat themselves.
+
+use crate::ipc::messages::{GetRunConfigRequest, GetRunConfigResponse};
+use crate::ipc::traits::IpcClient;
+use crate::tools::timestamps;
+use crate::types::errors::IpcError;
+use std::fmt::Write;
+
+/// Fetches how `run_id` was started.
+pub async fn get_config<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+) -> Result<GetRunConfigResponse, IpcError> {
+    let request = GetRunConfigRequest {
+        run_id: run_id.to_string(),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/config", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Formats the brief, leaving out what the run wasn't started with.
+pub fn format_run_config(config: &GetRunConfigResponse) -> String {
+    let mut output = format!("## How run {} was started\n\n", config.run_id);
+    let _ = match &config.playbook_name {
+        Some(name) => writeln!(
+            output,
+            "- **Playbook:** {} (`{}`)",
+            name, config.playbook_id
+        ),
+        None => writeln!(output, "- **Playbook:** `{}`", config.playbook_id),
+    };
+    if let Some(path) = &config.project_path {
+        let _ = writeln!(output, "- **Project:** `{}`", path);
+    }
+    match (&config.started_by, &config.created_at) {
+        (Some(by), Some(at)) => {
+            let _ = writeln!(output, "- **Started:** by {}, {}", by, timestamps::age(at));
+        }
+        (Some(by), None) => {
+            let _ = writeln!(output, "- **Started:** by {}", by);
+        }
+        (None, Some(at)) => {
+            let _ = writeln!(output, "- **Started:** {}", timestamps::age(at));
+        }
+        (None, None) => {}
+    }
+
+    let _ = write!(output, "\n### Intent\n\n{}\n", config.intent.trim());
+    if let Some(condition) = &config.initialization_condition {
+        let _ = write!(
+            output,
+            "\n### Initialization condition\n\n{}\n",
+            condition.trim()
+        );
+    }
+    if !config.selected_artifacts.is_empty() {
+        output.push_str("\n### Selected artifacts\n\n");
+        for artifact in &config.selected_artifacts {
+            let _ = writeln!(output, "- `{}` ({})", artifact.path, artifact.action);
+        }
+    }
+    if !config.wizard_answers.is_empty() {
+        output.push_str("\n### Setup answers\n\n");
+        for answer in &config.wizard_answers {
+            let _ = writeln!(output, "- **{}** {}", answer.question, answer.answer);
+        }
+    }
+    output.push_str(
+        "\nThis is the brief as given when the run started; get_run_context and get_latest_summary have what agents shared since.\n",
+    );
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::mock::MockIpcClient;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_get_and_format_config() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/config",
+            json!({
+                "runId": "run-1",
+                "playbookId": "spec-and-build",
+                "playbookName": "Spec → Build",
+                "intent": "Add a Redis cache in front of the pricing API",
+                "projectPath": "/src/shop",
+                "selectedArtifacts": [{"path": "docs/cache-spec.md", "action": "create"}],
+                "initializationCondition": "Spec approved by the human",
+                "wizardAnswers": [
+                    {"question": "Which environments?", "answer": "Staging first"}
+                ],
+                "startedBy": "human"
+            }),
+        );
+
+        let config = get_config(&mock, "run-1").await.unwrap();
+        mock.assert_called("/api/runs/run-1/config");
+
+        let output = format_run_config(&config);
+        assert!(output.starts_with(
+            "## How run run-1 was started\n\n\
+            - **Playbook:** Spec → Build (`spec-and-build`)\n\
+            - **Project:** `/src/shop`\n\
+            - **Started:** by human\n\n\
+            ### Intent\n\n\
+            Add a Redis cache in front of the pricing API\n\n\
+            ### Initialization condition\n\n\
+            Spec approved by the human\n\n\
+            ### Selected artifacts\n\n\
+            - `docs/cache-spec.md` (create)\n\n\
+            ### Setup answers\n\n\
+            - **Which environments?** Staging first\n"
+        ));
+    }
+}
@@ -0,0 +1,222 @@
+//! Project facts shared within a run.
+//!
+//! An agent pairing into a run otherwise has to ask the human where the
+//! worktree is, which branch to use, and how to run the tests. Whoever knows
+//! publishes that once with `set_run_context`; the pair response and
+//! `get_run_context` hand it to everyone else.
+
+use crate::ipc::messages::{
+    GetRunContextRequest, GetRunContextResponse, RunContext, SetRunContextRequest,
+    SetRunContextResponse,
+};
+use crate::ipc::traits::IpcClient;
+use crate::tools::{git, timestamps};
+use crate::types::errors::IpcError;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Lockfiles and the package manager each one implies, most specific first.
+const LOCKFILES: &[(&str, &str)] = &[
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "yarn"),
+    ("bun.lockb", "bun"),
+    ("bun.lock", "bun"),
+    ("package-lock.json", "npm"),
+    ("uv.lock", "uv"),
+    ("poetry.lock", "poetry"),
+    ("Pipfile.lock", "pipenv"),
+    ("Cargo.lock", "cargo"),
+    ("go.sum", "go"),
+    ("Gemfile.lock", "bundler"),
+];
+
+/// What can be worked out about `project` locally: its git worktree and
+/// branch, and its package manager from the lockfile. Best effort; anything
+/// that can't be found is left unset.
+pub fn detect(project: &Path) -> RunContext {
+    RunContext {
+        worktree_path: git::toplevel(project).ok(),
+        branch: git::current_branch(project).ok().flatten(),
+        package_manager: LOCKFILES
+            .iter()
+            .find(|(lockfile, _)| project.join(lockfile).is_file())
+            .map(|(_, manager)| manager.to_string()),
+        ..RunContext::default()
+    }
+}
+
+/// Fill the fields of `request` the caller left out from `detected`.
+pub fn fill_detected(request: &mut SetRunContextRequest, detected: RunContext) {
+    request.worktree_path = request.worktree_path.take().or(detected.worktree_path);
+    request.branch = request.branch.take().or(detected.branch);
+    request.package_manager = request.package_manager.take().or(detected.package_manager);
+}
+
+/// Publish the fields set in `request` as `role`; fields left out keep
+/// their current values.
+pub async fn set_context<C: IpcClient>(
+    client: &C,
+    request: &SetRunContextRequest,
+    role: &str,
+) -> Result<SetRunContextResponse, IpcError> {
+    let request = SetRunContextRequest {
+        source: Some(role.to_string()),
+        ..request.clone()
+    };
+
+    let endpoint = format!("/api/runs/{}/context/set", request.run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Fetches what has been published for `run_id`.
+pub async fn get_context<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+) -> Result<GetRunContextResponse, IpcError> {
+    let request = GetRunContextRequest {
+        run_id: run_id.to_string(),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/context", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// `- **Name:** value` lines for the fields that are set.
+pub fn context_lines(context: &RunContext) -> Vec<String> {
+    let fields = [
+        ("Worktree", &context.worktree_path),
+        ("Branch", &context.branch),
+        ("Test command", &context.test_command),
+        ("Package manager", &context.package_manager),
+    ];
+    let mut lines: Vec<String> = fields
+        .into_iter()
+        .filter_map(|(name, value)| value.as_deref().map(|v| format!("- **{}:** `{}`", name, v)))
+        .collect();
+    lines.extend(
+        context
+            .env
+            .iter()
+            .map(|(name, value)| format!("- **{}:** `{}`", name, value)),
+    );
+    lines
+}
+
+/// Formats a run's context with who last updated it.
+pub fn format_context(run_id: &str, context: &RunContext) -> String {
+    let lines = context_lines(context);
+    if lines.is_empty() {
+        return format!(
+            "No project context has been published for run {} yet.",
+            run_id
+        );
+    }
+    let mut output = format!("## Project context for run {}\n\n", run_id);
+    for line in lines {
+        let _ = writeln!(output, "{}", line);
+    }
+    if let (Some(by), Some(at)) = (&context.updated_by, &context.updated_at) {
+        let _ = write!(
+            output,
+            "\n*Last updated by {} ({})*\n",
+            by,
+            timestamps::age(at)
+        );
+    }
+    output
+}
+
+pub fn format_get_response(response: &GetRunContextResponse) -> String {
+    format_context(&response.run_id, &response.context)
+}
+
+pub fn format_set_response(response: &SetRunContextResponse) -> String {
+    format!(
+        "✓ Project context updated\n\n{}",
+        format_context(&response.run_id, &response.context)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::mock::MockIpcClient;
+    use serde_json::json;
+
+    fn request() -> SetRunContextRequest {
+        serde_json::from_value(json!({
+            "runId": "run-1",
+            "testCommand": "cargo test --workspace",
+            "branch": "feature/cache"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_detected_values_only_fill_gaps() {
+        let mut request = request();
+        fill_detected(
+            &mut request,
+            RunContext {
+                worktree_path: Some("/src/app".to_string()),
+                branch: Some("main".to_string()),
+                package_manager: Some("cargo".to_string()),
+                ..RunContext::default()
+            },
+        );
+        assert_eq!(request.worktree_path.as_deref(), Some("/src/app"));
+        assert_eq!(request.branch.as_deref(), Some("feature/cache"));
+        assert_eq!(request.package_manager.as_deref(), Some("cargo"));
+    }
+
+    #[test]
+    fn test_detect_package_manager() {
+        let project =
+            std::env::temp_dir().join(format!("hotwired-run-context-{}", std::process::id()));
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("package-lock.json"), "{}").unwrap();
+        std::fs::write(project.join("pnpm-lock.yaml"), "").unwrap();
+        let detected = detect(&project);
+        std::fs::remove_dir_all(&project).unwrap();
+        assert_eq!(detected.package_manager.as_deref(), Some("pnpm"));
+    }
+
+    #[tokio::test]
+    async fn test_set_sends_role_and_formats_result() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/context/set",
+            json!({
+                "runId": "run-1",
+                "context": {
+                    "branch": "feature/cache",
+                    "testCommand": "cargo test --workspace",
+                    "env": {"build_command": "cargo build"},
+                    "updatedBy": "strategist",
+                    "updatedAt": "2024-01-15T10:00:00Z"
+                }
+            }),
+        );
+
+        let mut request = request();
+        request.detect = Some(true);
+        let response = set_context(&mock, &request, "strategist").await.unwrap();
+        let sent = mock.requests_to("/api/runs/run-1/context/set");
+        assert!(sent[0].contains("\"source\":\"strategist\""));
+        assert!(!sent[0].contains("detect"));
+
+        let output = format_set_response(&response);
+        assert!(output.contains(
+            "- **Branch:** `feature/cache`\n\
+            - **Test command:** `cargo test --workspace`\n\
+            - **build_command:** `cargo build`\n"
+        ));
+        assert!(output.contains("*Last updated by strategist ("));
+        assert_eq!(
+            format_context("run-2", &RunContext::default()),
+            "No project context has been published for run run-2 yet."
+        );
+    }
+}
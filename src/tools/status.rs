@@ -1,28 +1,40 @@
 use crate::ipc::messages::{
-    HandoffRequest, HandoffResponse, ReportImpedimentRequest, ReportImpedimentResponse,
-    ReportStatusRequest, ReportStatusResponse, RequestEndRunRequest, RequestEndRunResponse,
-    RequestInputRequest, RequestInputResponse, ResolveImpedimentRequest, ResolveImpedimentResponse,
-    RespondToEndRequestRequest, RespondToEndRequestResponse, ResponseFormat, SendMessageRequest,
-    SendMessageResponse, SuggestedFollowUp, TaskCompleteRequest, TaskCompleteResponse,
+    CreateEventRequest, CreateEventResponse, GetImpedimentResolutionRequest,
+    GetImpedimentResolutionResponse, GetImpedimentsRequest, GetImpedimentsResponse, HandoffRequest,
+    HandoffResponse, HandoffTask, ImpedimentType, InputStatusRequest, InputStatusResponse,
+    ListCompletedTasksRequest, ListCompletedTasksResponse, MessagePriority,
+    ReportImpedimentRequest, ReportImpedimentResponse, ReportStatusRequest, ReportStatusResponse,
+    RequestEndRunRequest, RequestEndRunResponse, RequestInputRequest, RequestInputResponse,
+    ResolveImpedimentRequest, ResolveImpedimentResponse, RespondToEndRequestRequest,
+    RespondToEndRequestResponse, ResponseFormat, ResponseFormatField, SendMessageRequest,
+    SendMessageResponse, StatusProgress, SuggestedFollowUp, TaskCompleteRequest,
+    TaskCompleteResponse, TaskResults, WorkStatus, TOOL_ERROR_EVENT,
 };
 use crate::ipc::traits::IpcClient;
+use crate::tools::timestamps;
 use crate::types::errors::IpcError;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 /// Report the agent's current working status.
 pub async fn report_status<C: IpcClient>(
     client: &C,
     run_id: &str,
-    status: &str,
+    status: WorkStatus,
     message: &str,
     source: &str,
     agent_status: Option<&str>,
+    progress: Option<&StatusProgress>,
 ) -> Result<(), IpcError> {
     let request = ReportStatusRequest {
         run_id: run_id.to_string(),
-        status: status.to_string(),
+        status,
         message: message.to_string(),
-        source: source.to_string(),
+        source: Some(source.to_string()),
         agent_status: agent_status.map(String::from),
+        progress: progress.filter(|p| !p.is_empty()).cloned(),
     };
 
     let endpoint = format!("/api/runs/{}/report-status", run_id);
@@ -35,17 +47,38 @@ pub async fn report_status<C: IpcClient>(
     }
 }
 
-/// Validate status value.
-pub fn validate_status(status: &str) -> Result<(), String> {
-    const VALID_STATUSES: &[&str] = &["working", "thinking", "waiting", "idle", "complete"];
-    if VALID_STATUSES.contains(&status) {
-        Ok(())
-    } else {
-        Err(format!(
-            "Invalid status '{}'. Must be one of: {}",
-            status,
-            VALID_STATUSES.join(", ")
-        ))
+/// The last status each agent sent per run, so identical reports repeated
+/// within a short window can be skipped.
+#[derive(Default)]
+pub struct StatusDebouncer {
+    /// (run_id, source) -> (status fingerprint, when it was sent)
+    last_sent: Mutex<HashMap<(String, String), (String, Instant)>>,
+}
+
+impl StatusDebouncer {
+    /// Whether `fingerprint` matches what `source` last sent for `run_id`
+    /// less than `window` before `now`.
+    pub fn is_duplicate(
+        &self,
+        run_id: &str,
+        source: &str,
+        fingerprint: &str,
+        window: Duration,
+        now: Instant,
+    ) -> bool {
+        let last_sent = self.last_sent.lock().unwrap();
+        matches!(
+            last_sent.get(&(run_id.to_string(), source.to_string())),
+            Some((last, at)) if last == fingerprint && now.duration_since(*at) < window
+        )
+    }
+
+    /// Remember a status that was sent successfully.
+    pub fn record(&self, run_id: &str, source: &str, fingerprint: String, now: Instant) {
+        self.last_sent
+            .lock()
+            .unwrap()
+            .insert((run_id.to_string(), source.to_string()), (fingerprint, now));
     }
 }
 
@@ -63,20 +96,36 @@ pub fn validate_agent_status(status: &str) -> Result<(), String> {
     }
 }
 
-/// Send a message to other agents or the user.
+/// Send a message to other agents or the user: to `targets`, to everyone
+/// with `broadcast`, or to the conversation log if neither. A single target
+/// goes in `target`, which backends without multi-target support read.
+#[allow(clippy::too_many_arguments)]
 pub async fn send_message<C: IpcClient>(
     client: &C,
     run_id: &str,
     content: &str,
     source: &str,
-    target: Option<&str>,
+    targets: &[String],
+    broadcast: bool,
+    reply_to_event_id: Option<&str>,
+    priority: Option<MessagePriority>,
+    requires_ack: bool,
     agent_status: Option<&str>,
-) -> Result<String, IpcError> {
+) -> Result<SendMessageResponse, IpcError> {
+    let (target, targets) = match targets {
+        [target] => (Some(target.clone()), vec![]),
+        targets => (None, targets.to_vec()),
+    };
     let request = SendMessageRequest {
         run_id: run_id.to_string(),
         content: content.to_string(),
-        source: source.to_string(),
-        target: target.map(String::from),
+        source: Some(source.to_string()),
+        target,
+        targets,
+        broadcast: broadcast.then_some(true),
+        reply_to_event_id: reply_to_event_id.map(String::from),
+        priority,
+        requires_ack: requires_ack.then_some(true),
         agent_status: agent_status.map(String::from),
     };
 
@@ -84,7 +133,7 @@ pub async fn send_message<C: IpcClient>(
     let response: SendMessageResponse = client.request(&endpoint, &request).await?;
 
     if response.success {
-        Ok(response.event_id)
+        Ok(response)
     } else {
         Err(IpcError::RequestFailed("Message send failed".into()))
     }
@@ -98,13 +147,15 @@ pub async fn task_complete<C: IpcClient>(
     source: &str,
     outcome: Option<&str>,
     agent_status: Option<&str>,
+    results: Option<&TaskResults>,
 ) -> Result<(), IpcError> {
     let request = TaskCompleteRequest {
         run_id: run_id.to_string(),
         task_description: task_description.to_string(),
-        source: source.to_string(),
+        source: Some(source.to_string()),
         outcome: outcome.map(String::from),
         agent_status: agent_status.map(String::from),
+        results: results.filter(|r| !r.is_empty()).cloned(),
     };
 
     let endpoint = format!("/api/runs/{}/task-complete", run_id);
@@ -117,25 +168,97 @@ pub async fn task_complete<C: IpcClient>(
     }
 }
 
-/// Validate impediment type value.
-pub fn validate_impediment_type(impediment_type: &str) -> Result<(), String> {
-    const VALID: &[&str] = &[
-        "missing_information",
-        "permission_needed",
-        "technical_error",
-        "unclear_requirements",
-        "dependency_blocked",
-        "other",
-    ];
-    if VALID.contains(&impediment_type) {
-        Ok(())
-    } else {
-        Err(format!(
-            "Invalid impediment type '{}'. Must be one of: {}",
-            impediment_type,
-            VALID.join(", ")
-        ))
+/// List the tasks completed in `run_id`, by `role` if given, oldest first.
+pub async fn list_completed_tasks<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: Option<&str>,
+) -> Result<ListCompletedTasksResponse, IpcError> {
+    let request = ListCompletedTasksRequest {
+        run_id: run_id.to_string(),
+        role: role.map(String::from),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/tasks/completed", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Formats completed tasks with their results, then totals for the run's
+/// retrospective.
+pub fn format_completed_tasks(response: &ListCompletedTasksResponse) -> String {
+    if response.tasks.is_empty() {
+        return format!(
+            "No tasks have been completed in run {} yet.",
+            response.run_id
+        );
+    }
+
+    let mut output = format!(
+        "## Completed tasks in run {} ({})\n",
+        response.run_id,
+        response.tasks.len()
+    );
+    let (mut artifacts, mut tests, mut failing) = (Vec::new(), 0, 0);
+    for task in &response.tasks {
+        let _ = write!(
+            output,
+            "\n### {}\n\n*{}, {}*\n",
+            task.task_description,
+            task.role,
+            timestamps::age(&task.completed_at)
+        );
+        if let Some(outcome) = &task.outcome {
+            let _ = write!(output, "\n{}\n", outcome);
+        }
+        let Some(results) = task.results.as_ref().filter(|r| !r.is_empty()) else {
+            continue;
+        };
+        output.push('\n');
+        if !results.artifact_ids.is_empty() {
+            let ids: Vec<String> = results
+                .artifact_ids
+                .iter()
+                .map(|id| format!("`{}`", id))
+                .collect();
+            let _ = writeln!(output, "- **Artifacts:** {}", ids.join(", "));
+        }
+        for run in &results.tests {
+            let _ = writeln!(
+                output,
+                "- **Tests:** {} `{}`{}",
+                if run.passed { "✓" } else { "✗" },
+                run.command,
+                run.summary
+                    .as_deref()
+                    .map(|summary| format!(" ({})", summary))
+                    .unwrap_or_default()
+            );
+        }
+        for link in &results.links {
+            let _ = match &link.title {
+                Some(title) => writeln!(output, "- **Link:** [{}]({})", title, link.url),
+                None => writeln!(output, "- **Link:** {}", link.url),
+            };
+        }
+        for id in &results.artifact_ids {
+            if !artifacts.contains(id) {
+                artifacts.push(id.clone());
+            }
+        }
+        tests += results.tests.len();
+        failing += results.tests.iter().filter(|run| !run.passed).count();
     }
+    let _ = writeln!(
+        output,
+        "\n**Totals:** {} task(s), {} artifact(s), {} test run(s), {} failing",
+        response.tasks.len(),
+        artifacts.len(),
+        tests,
+        failing
+    );
+    output
 }
 
 /// Report a blocker or impediment that prevents progress.
@@ -143,23 +266,27 @@ pub fn validate_impediment_type(impediment_type: &str) -> Result<(), String> {
 pub async fn report_impediment<C: IpcClient>(
     client: &C,
     run_id: &str,
-    impediment_type: &str,
+    impediment_type: ImpedimentType,
     description: &str,
     source: &str,
     context: Option<&str>,
     suggestion: Option<&str>,
     agent_status: Option<&str>,
     response_format: Option<ResponseFormat>,
+    escalate_after_seconds: Option<i64>,
+    template_id: Option<&str>,
 ) -> Result<(), IpcError> {
     let request = ReportImpedimentRequest {
         run_id: run_id.to_string(),
-        impediment_type: impediment_type.to_string(),
+        impediment_type: Some(impediment_type),
         description: description.to_string(),
-        source: source.to_string(),
+        template_id: template_id.map(String::from),
+        source: Some(source.to_string()),
         context: context.map(String::from),
         suggestion: suggestion.map(String::from),
         agent_status: agent_status.map(String::from),
         response_format,
+        escalate_after_seconds,
     };
 
     let endpoint = format!("/api/runs/{}/impediment", run_id);
@@ -180,11 +307,11 @@ pub async fn request_input<C: IpcClient>(
     source: &str,
     context: Option<&str>,
     options: Option<Vec<String>>,
-) -> Result<(), IpcError> {
+) -> Result<Option<String>, IpcError> {
     let request = RequestInputRequest {
         run_id: run_id.to_string(),
         question: question.to_string(),
-        source: source.to_string(),
+        source: Some(source.to_string()),
         context: context.map(String::from),
         options,
     };
@@ -193,13 +320,82 @@ pub async fn request_input<C: IpcClient>(
     let response: RequestInputResponse = client.request(&endpoint, &request).await?;
 
     if response.success {
-        Ok(())
+        Ok(response.request_id)
     } else {
         Err(IpcError::RequestFailed("Request input failed".into()))
     }
 }
 
+/// How often `await_input` checks for an answer.
+pub const INPUT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Wait up to `timeout` for the human to answer `request_id`, checking every
+/// `poll_interval`. `None` if it is still unanswered.
+pub async fn await_input<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    request_id: &str,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<Option<InputStatusResponse>, IpcError> {
+    let request = InputStatusRequest {
+        run_id: run_id.to_string(),
+        request_id: request_id.to_string(),
+    };
+    let endpoint = format!("/api/runs/{}/input/{}", run_id, request_id);
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        let status: InputStatusResponse = client.request(&endpoint, &request).await?;
+        if status.answered {
+            return Ok(Some(status));
+        }
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            return Ok(None);
+        }
+        tokio::time::sleep(remaining.min(poll_interval)).await;
+    }
+}
+
+/// The answer to a `request_input` question, or guidance if it timed out.
+pub fn format_input_answer(
+    request_id: &str,
+    waited: Duration,
+    status: Option<&InputStatusResponse>,
+) -> String {
+    match status {
+        Some(status) => {
+            let mut output = format!(
+                "✓ Input request {} answered{}",
+                request_id,
+                status
+                    .answered_by
+                    .as_deref()
+                    .map(|by| format!(" by {}", by))
+                    .unwrap_or_default()
+            );
+            if let Some(answered_at) = &status.answered_at {
+                let _ = write!(output, " {}", timestamps::age(answered_at));
+            }
+            let _ = write!(
+                output,
+                ":\n\n{}",
+                status.answer.as_deref().unwrap_or("(no answer text)")
+            );
+            output
+        }
+        None => format!(
+            "⏳ No answer to input request {} after {}s. The question is still open. \
+            Continue with work that does not depend on it and call await_input again later; \
+            the answer will also show up in get_notifications.",
+            request_id,
+            waited.as_secs()
+        ),
+    }
+}
+
 /// Hand off work to another agent.
+#[allow(clippy::too_many_arguments)]
 pub async fn handoff<C: IpcClient>(
     client: &C,
     run_id: &str,
@@ -208,14 +404,16 @@ pub async fn handoff<C: IpcClient>(
     source: &str,
     details: Option<&str>,
     artifacts: Option<Vec<String>>,
+    tasks: Option<Vec<HandoffTask>>,
 ) -> Result<(), IpcError> {
     let request = HandoffRequest {
         run_id: run_id.to_string(),
         to: to.to_string(),
         summary: summary.to_string(),
-        source: source.to_string(),
+        source: Some(source.to_string()),
         details: details.map(String::from),
         artifacts,
+        tasks,
     };
 
     let endpoint = format!("/api/runs/{}/handoff", run_id);
@@ -255,7 +453,7 @@ pub async fn request_end_run<C: IpcClient>(
         run_id: run_id.to_string(),
         reason: reason.to_string(),
         description: description.to_string(),
-        source: source.to_string(),
+        source: Some(source.to_string()),
         suggested_follow_up,
     };
 
@@ -296,7 +494,7 @@ pub async fn respond_to_end_request<C: IpcClient>(
         run_id: run_id.to_string(),
         request_id: request_id.to_string(),
         response: response.to_string(),
-        source: source.to_string(),
+        source: Some(source.to_string()),
         reason: reason.map(String::from),
     };
 
@@ -332,7 +530,7 @@ pub async fn resolve_impediment<C: IpcClient>(
         run_id: run_id.to_string(),
         impediment_id,
         response,
-        source: source.to_string(),
+        source: Some(source.to_string()),
         rationale: rationale.map(String::from),
     };
 
@@ -349,11 +547,228 @@ pub async fn resolve_impediment<C: IpcClient>(
     }
 }
 
+/// List the impediments `role` raised in `run_id`; open and escalated ones
+/// only unless `include_resolved`.
+pub async fn get_impediments<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: &str,
+    include_resolved: bool,
+) -> Result<GetImpedimentsResponse, IpcError> {
+    let request = GetImpedimentsRequest {
+        run_id: run_id.to_string(),
+        source: Some(role.to_string()),
+        include_resolved: include_resolved.then_some(true),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/impediments", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Formats impediments with how long each has been in its state.
+pub fn format_impediments(response: &GetImpedimentsResponse) -> String {
+    if response.impediments.is_empty() {
+        return "No impediments are open.".to_string();
+    }
+
+    let mut output = format!("{} impediment(s)\n\n", response.impediments.len());
+    for impediment in &response.impediments {
+        let _ = write!(
+            output,
+            "- #{} [{}] **{}** since {}",
+            impediment.id,
+            impediment.impediment_type,
+            impediment.status,
+            timestamps::age(&impediment.status_since)
+        );
+        if impediment.escalation_level > 0 {
+            let _ = write!(output, ", escalated {}x", impediment.escalation_level);
+        }
+        if let Some(escalate_at) = &impediment.escalate_at {
+            let _ = write!(output, ", escalates {}", timestamps::age(escalate_at));
+        }
+        let _ = writeln!(
+            output,
+            " (raised by {} {}): {}",
+            impediment.raised_by,
+            timestamps::age(&impediment.created_at),
+            impediment.description
+        );
+    }
+    output
+}
+
+/// Fetches the state of impediment `impediment_id` and its resolution, if resolved.
+pub async fn get_impediment_resolution<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    impediment_id: i64,
+) -> Result<GetImpedimentResolutionResponse, IpcError> {
+    let request = GetImpedimentResolutionRequest {
+        run_id: run_id.to_string(),
+        impediment_id,
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/impediments/{}", run_id, impediment_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Formats a resolution with each answer under its response_format label, or
+/// says that the impediment is still waiting.
+pub fn format_impediment_resolution(response: &GetImpedimentResolutionResponse) -> String {
+    let Some(answer) = response
+        .response
+        .as_ref()
+        .filter(|_| response.status == "resolved")
+    else {
+        return format!(
+            "Impediment #{} is still {}. Check again later; get_impediments shows when it escalates.",
+            response.impediment_id, response.status
+        );
+    };
+
+    let mut output = format!(
+        "Impediment #{} resolved by {}",
+        response.impediment_id,
+        response.resolved_by.as_deref().unwrap_or("unknown")
+    );
+    if let Some(at) = &response.resolved_at {
+        let _ = write!(output, " {}", timestamps::age(at));
+    }
+    output.push_str("\n\n");
+    let fields = response
+        .response_format
+        .as_ref()
+        .map(|f| f.fields.as_slice())
+        .unwrap_or_default();
+    match answer {
+        serde_json::Value::Object(values) => {
+            for field in fields {
+                if let Some(value) = values.get(&field.id) {
+                    let _ = writeln!(
+                        output,
+                        "- **{}** ({}): {}",
+                        field.label,
+                        field.id,
+                        answer_text(value, field)
+                    );
+                }
+            }
+            for (id, value) in values
+                .iter()
+                .filter(|(id, _)| !fields.iter().any(|f| &f.id == *id))
+            {
+                let _ = writeln!(output, "- **{}**: {}", id, plain(value));
+            }
+        }
+        other => {
+            let _ = writeln!(output, "{}", plain(other));
+        }
+    }
+    if let Some(rationale) = &response.rationale {
+        let _ = write!(output, "\n**Rationale:** {}\n", rationale);
+    }
+    output
+}
+
+/// A field's answer, with option values shown by their labels.
+fn answer_text(value: &serde_json::Value, field: &ResponseFormatField) -> String {
+    let label = |value: &serde_json::Value| {
+        let text = plain(value);
+        field
+            .options
+            .iter()
+            .flatten()
+            .find(|o| o.value == text)
+            .map(|o| format!("{} ({})", o.label, o.value))
+            .unwrap_or(text)
+    };
+    match value {
+        serde_json::Value::Array(values) => values.iter().map(label).collect::<Vec<_>>().join(", "),
+        value => label(value),
+    }
+}
+
+/// Strings without their quotes, anything else as JSON.
+fn plain(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Record a failed tool call as a `tool_error` event so it shows in the dashboard.
+pub async fn report_tool_error<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    tool: &str,
+    error: &str,
+    source: Option<&str>,
+) -> Result<(), IpcError> {
+    let request = CreateEventRequest {
+        run_id: run_id.to_string(),
+        event_type: TOOL_ERROR_EVENT.to_string(),
+        source: source.map(String::from),
+        content: format!("{} failed: {}", tool, error),
+        metadata: Some(serde_json::json!({
+            "tool": tool,
+            "error": error,
+        })),
+    };
+
+    let endpoint = format!("/api/runs/{}/events", run_id);
+    let response: CreateEventResponse = client.request(&endpoint, &request).await?;
+
+    if response.success {
+        Ok(())
+    } else {
+        Err(IpcError::RequestFailed("Tool error event failed".into()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::ipc::mock::MockIpcClient;
 
+    /// Deserialize a tool argument the way the MCP layer does.
+    pub(super) fn parse<T: serde::de::DeserializeOwned>(
+        value: &str,
+    ) -> Result<T, serde_json::Error> {
+        serde_json::from_value(serde_json::Value::String(value.to_string()))
+    }
+
+    #[test]
+    fn test_status_debouncer_skips_identical_reports_within_window() {
+        let debouncer = StatusDebouncer::default();
+        let window = Duration::from_secs(60);
+        let start = Instant::now();
+
+        assert!(!debouncer.is_duplicate("run-1", "builder", "working|Coding", window, start));
+        debouncer.record("run-1", "builder", "working|Coding".to_string(), start);
+
+        let soon = start + Duration::from_secs(10);
+        assert!(debouncer.is_duplicate("run-1", "builder", "working|Coding", window, soon));
+        // A different message, agent, or run is always sent
+        assert!(!debouncer.is_duplicate("run-1", "builder", "working|Testing", window, soon));
+        assert!(!debouncer.is_duplicate("run-1", "reviewer", "working|Coding", window, soon));
+        assert!(!debouncer.is_duplicate("run-2", "builder", "working|Coding", window, soon));
+
+        let later = start + Duration::from_secs(61);
+        assert!(!debouncer.is_duplicate("run-1", "builder", "working|Coding", window, later));
+        assert!(!debouncer.is_duplicate(
+            "run-1",
+            "builder",
+            "working|Coding",
+            Duration::ZERO,
+            soon
+        ));
+    }
+
     #[tokio::test]
     async fn test_report_status_sends_all_fields() {
         let mock = MockIpcClient::new();
@@ -365,10 +780,16 @@ mod tests {
         let _ = report_status(
             &mock,
             "run-1",
-            "working",
+            WorkStatus::Working,
             "Implementing feature",
             "builder",
             Some("active"),
+            Some(&StatusProgress {
+                artifact_id: Some("spec".to_string()),
+                section: Some("Caching".to_string()),
+                percent_complete: Some(40),
+                blocking_on: None,
+            }),
         )
         .await;
 
@@ -379,6 +800,9 @@ mod tests {
         assert!(req.contains("Implementing feature"));
         assert!(req.contains("builder"));
         assert!(req.contains("active"));
+        assert!(req.contains(
+            "\"progress\":{\"artifactId\":\"spec\",\"section\":\"Caching\",\"percentComplete\":40}"
+        ));
     }
 
     #[tokio::test]
@@ -392,28 +816,30 @@ mod tests {
         let result = report_status(
             &mock,
             "run-1",
-            "thinking",
+            WorkStatus::Thinking,
             "Planning next step",
             "strategist",
             None,
+            Some(&StatusProgress::default()),
         )
         .await;
 
         assert!(result.is_ok());
+        assert!(!mock.requests_to("/api/runs/run-1/report-status")[0].contains("progress"));
     }
 
     #[test]
-    fn test_validate_status_accepts_valid() {
-        assert!(validate_status("working").is_ok());
-        assert!(validate_status("thinking").is_ok());
-        assert!(validate_status("complete").is_ok());
+    fn test_work_status_accepts_valid() {
+        assert!(parse::<WorkStatus>("working").is_ok());
+        assert!(parse::<WorkStatus>("thinking").is_ok());
+        assert!(parse::<WorkStatus>("complete").is_ok());
     }
 
     #[test]
-    fn test_validate_status_rejects_invalid() {
-        let result = validate_status("running");
+    fn test_work_status_rejects_invalid() {
+        let result = parse::<WorkStatus>("running");
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("running"));
+        assert!(result.unwrap_err().to_string().contains("running"));
     }
 
     #[test]
@@ -426,6 +852,35 @@ mod tests {
     fn test_validate_agent_status_rejects_invalid() {
         assert!(validate_agent_status("busy").is_err());
     }
+
+    #[tokio::test]
+    async fn test_report_tool_error_sends_event() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/events",
+            CreateEventResponse {
+                success: true,
+                event_id: Some("evt-1".into()),
+            },
+        );
+
+        let result = report_tool_error(
+            &mock,
+            "run-1",
+            "doc_artifact_edit",
+            "Artifact not found",
+            Some("builder"),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        let requests = mock.requests_to("/api/runs/run-1/events");
+        let req = &requests[0];
+        assert!(req.contains("tool_error"));
+        assert!(req.contains("doc_artifact_edit"));
+        assert!(req.contains("Artifact not found"));
+        assert!(req.contains("builder"));
+    }
 }
 
 #[cfg(test)]
@@ -441,6 +896,7 @@ mod message_tests {
             SendMessageResponse {
                 success: true,
                 event_id: "msg-123".into(),
+                recipients: vec![],
             },
         );
 
@@ -449,7 +905,11 @@ mod message_tests {
             "run-1",
             "Here's the plan",
             "strategist",
-            Some("builder"),
+            &["builder".to_string()],
+            false,
+            None,
+            None,
+            false,
             None,
         )
         .await;
@@ -457,7 +917,46 @@ mod message_tests {
         assert!(result.is_ok());
         let requests = mock.requests_to("/api/runs/run-1/message");
         let req = &requests[0];
-        assert!(req.contains("builder"));
+        assert!(req.contains("\"target\":\"builder\""));
+        assert!(!req.contains("targets"));
+        assert!(!req.contains("priority"));
+        assert!(!req.contains("requiresAck"));
+    }
+
+    #[tokio::test]
+    async fn test_send_message_to_several_targets() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/message",
+            serde_json::json!({
+                "success": true,
+                "eventId": "msg-124",
+                "recipients": ["builder", "reviewer"]
+            }),
+        );
+
+        let targets = ["builder".to_string(), "reviewer".to_string()];
+        let response = send_message(
+            &mock,
+            "run-1",
+            "Plan is ready",
+            "strategist",
+            &targets,
+            false,
+            Some("msg-100"),
+            Some(MessagePriority::Urgent),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.recipients, targets);
+        let requests = mock.requests_to("/api/runs/run-1/message");
+        assert!(requests[0].contains("\"targets\":[\"builder\",\"reviewer\"]"));
+        assert!(requests[0].contains("\"replyToEventId\":\"msg-100\""));
+        assert!(requests[0].contains("\"priority\":\"urgent\",\"requiresAck\":true"));
+        assert!(!requests[0].contains("\"target\":"));
     }
 
     #[tokio::test]
@@ -468,6 +967,7 @@ mod message_tests {
             SendMessageResponse {
                 success: true,
                 event_id: "msg-456".into(),
+                recipients: vec![],
             },
         );
 
@@ -476,12 +976,18 @@ mod message_tests {
             "run-1",
             "Broadcasting update",
             "strategist",
+            &[],
+            true,
             None,
             None,
+            false,
+            None,
         )
         .await;
 
         assert!(result.is_ok());
+        let requests = mock.requests_to("/api/runs/run-1/message");
+        assert!(requests[0].contains("\"broadcast\":true"));
     }
 
     #[tokio::test]
@@ -492,12 +998,25 @@ mod message_tests {
             SendMessageResponse {
                 success: true,
                 event_id: "event-789".into(),
+                recipients: vec![],
             },
         );
 
-        let result = send_message(&mock, "run-1", "Test message", "builder", None, None).await;
+        let result = send_message(
+            &mock,
+            "run-1",
+            "Test message",
+            "builder",
+            &[],
+            false,
+            None,
+            None,
+            false,
+            None,
+        )
+        .await;
 
-        assert_eq!(result.unwrap(), "event-789");
+        assert_eq!(result.unwrap().event_id, "event-789");
     }
 }
 
@@ -521,6 +1040,7 @@ mod task_tests {
             "builder",
             Some("All tests passing"),
             Some("active"),
+            None,
         )
         .await;
 
@@ -539,32 +1059,188 @@ mod task_tests {
             TaskCompleteResponse { success: true },
         );
 
-        let result = task_complete(&mock, "run-1", "Fixed bug", "builder", None, None).await;
+        let result = task_complete(&mock, "run-1", "Fixed bug", "builder", None, None, None).await;
 
         assert!(result.is_ok());
+        let requests = mock.requests_to("/api/runs/run-1/task-complete");
+        assert!(!requests[0].contains("results"));
+    }
+
+    #[tokio::test]
+    async fn test_task_results_are_listed_with_totals() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/task-complete",
+            TaskCompleteResponse { success: true },
+        );
+        mock.when_called(
+            "/api/runs/run-1/tasks/completed",
+            serde_json::json!({
+                "runId": "run-1",
+                "tasks": [
+                    {
+                        "id": "task-1",
+                        "role": "builder",
+                        "taskDescription": "Implemented parser",
+                        "outcome": "Parses every fixture",
+                        "results": {
+                            "artifactIds": ["spec", "plan"],
+                            "tests": [
+                                {"command": "cargo test", "passed": true, "summary": "412 passed"},
+                                {"command": "npm test", "passed": false}
+                            ],
+                            "links": [{"url": "https://github.com/o/r/pull/12", "title": "PR #12"}]
+                        },
+                        "completedAt": "2024-01-15T10:00:00Z"
+                    },
+                    {
+                        "id": "task-2",
+                        "role": "writer",
+                        "taskDescription": "Updated spec",
+                        "results": {"artifactIds": ["spec"]},
+                        "completedAt": "2024-01-15T11:00:00Z"
+                    }
+                ]
+            }),
+        );
+
+        let results: TaskResults = serde_json::from_value(serde_json::json!({
+            "artifactIds": ["spec"],
+            "tests": [{"command": "cargo test", "passed": true}]
+        }))
+        .unwrap();
+        task_complete(
+            &mock,
+            "run-1",
+            "Implemented parser",
+            "builder",
+            None,
+            None,
+            Some(&results),
+        )
+        .await
+        .unwrap();
+        let requests = mock.requests_to("/api/runs/run-1/task-complete");
+        assert!(requests[0].contains(
+            "\"results\":{\"artifactIds\":[\"spec\"],\"tests\":[{\"command\":\"cargo test\",\"passed\":true}]}"
+        ));
+
+        let listed = list_completed_tasks(&mock, "run-1", None).await.unwrap();
+        let output = format_completed_tasks(&listed);
+        assert!(output.starts_with(
+            "## Completed tasks in run run-1 (2)\n\n### Implemented parser\n\n*builder, "
+        ));
+        assert!(output.contains(
+            "\nParses every fixture\n\n\
+            - **Artifacts:** `spec`, `plan`\n\
+            - **Tests:** ✓ `cargo test` (412 passed)\n\
+            - **Tests:** ✗ `npm test`\n\
+            - **Link:** [PR #12](https://github.com/o/r/pull/12)\n"
+        ));
+        assert!(
+            output.ends_with("**Totals:** 2 task(s), 2 artifact(s), 2 test run(s), 1 failing\n")
+        );
     }
 }
 
 #[cfg(test)]
 mod impediment_tests {
+    use super::tests::parse;
     use super::*;
+    use crate::ipc::messages::ImpedimentState;
     use crate::ipc::mock::MockIpcClient;
 
     #[test]
-    fn test_validate_impediment_type_accepts_valid() {
-        assert!(validate_impediment_type("missing_information").is_ok());
-        assert!(validate_impediment_type("permission_needed").is_ok());
-        assert!(validate_impediment_type("technical_error").is_ok());
-        assert!(validate_impediment_type("unclear_requirements").is_ok());
-        assert!(validate_impediment_type("dependency_blocked").is_ok());
-        assert!(validate_impediment_type("other").is_ok());
+    fn test_impediment_type_accepts_valid() {
+        assert!(parse::<ImpedimentType>("missing_information").is_ok());
+        assert!(parse::<ImpedimentType>("permission_needed").is_ok());
+        assert!(parse::<ImpedimentType>("technical_error").is_ok());
+        assert!(parse::<ImpedimentType>("unclear_requirements").is_ok());
+        assert!(parse::<ImpedimentType>("dependency_blocked").is_ok());
+        assert!(parse::<ImpedimentType>("other").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_impediments_formats_escalation() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/impediments",
+            GetImpedimentsResponse {
+                run_id: "run-1".to_string(),
+                impediments: vec![ImpedimentState {
+                    id: 42,
+                    impediment_type: "missing_information".to_string(),
+                    description: "Which database?".to_string(),
+                    raised_by: "builder".to_string(),
+                    status: "escalated".to_string(),
+                    escalation_level: 2,
+                    created_at: "2024-01-15T10:00:00Z".to_string(),
+                    status_since: "2024-01-15T10:30:00Z".to_string(),
+                    escalate_at: None,
+                }],
+            },
+        );
+
+        let response = get_impediments(&mock, "run-1", "builder", false)
+            .await
+            .unwrap();
+        let sent = &mock.requests_to("/api/runs/run-1/impediments")[0];
+        assert!(sent.contains(r#""source":"builder""#));
+        assert!(!sent.contains("includeResolved"));
+
+        let output = format_impediments(&response);
+        assert!(output.contains("- #42 [missing_information] **escalated** since "));
+        assert!(output.contains(", escalated 2x (raised by builder "));
+        assert!(output.ends_with("): Which database?\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_impediment_resolution_labels_answers() {
+        let mock = MockIpcClient::new();
+        let endpoint = "/api/runs/run-1/impediments/42";
+        mock.when_called(
+            endpoint,
+            serde_json::json!({"impedimentId": 42, "status": "escalated"}),
+        );
+        let waiting = get_impediment_resolution(&mock, "run-1", 42).await.unwrap();
+        assert!(format_impediment_resolution(&waiting)
+            .starts_with("Impediment #42 is still escalated."));
+        assert!(mock.requests_to(endpoint)[0].contains(r#""impedimentId":42"#));
+
+        mock.when_called(
+            endpoint,
+            serde_json::json!({
+                "impedimentId": 42,
+                "status": "resolved",
+                "responseFormat": {"fields": [
+                    {"id": "database", "type": "radio", "label": "Which database?",
+                     "options": [{"value": "pg", "label": "PostgreSQL"}]},
+                    {"id": "extras", "type": "checkbox", "label": "Extras",
+                     "options": [{"value": "ha", "label": "High availability"}]}
+                ]},
+                "response": {"database": "pg", "extras": ["ha", "backups"], "notes": "shared cluster"},
+                "resolvedBy": "human",
+                "rationale": "Matches prod"
+            }),
+        );
+        let resolved = get_impediment_resolution(&mock, "run-1", 42).await.unwrap();
+        let output = format_impediment_resolution(&resolved);
+        assert!(
+            output.starts_with("Impediment #42 resolved by human\n"),
+            "{}",
+            output
+        );
+        assert!(output.contains("- **Which database?** (database): PostgreSQL (pg)\n"));
+        assert!(output.contains("- **Extras** (extras): High availability (ha), backups\n"));
+        assert!(output.contains("- **notes**: shared cluster\n"));
+        assert!(output.ends_with("**Rationale:** Matches prod\n"));
     }
 
     #[test]
-    fn test_validate_impediment_type_rejects_invalid() {
-        let result = validate_impediment_type("invalid_type");
+    fn test_impediment_type_rejects_invalid() {
+        let result = parse::<ImpedimentType>("invalid_type");
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("invalid_type"));
+        assert!(result.unwrap_err().to_string().contains("invalid_type"));
     }
 
     #[tokio::test]
@@ -578,13 +1254,15 @@ mod impediment_tests {
         let _ = report_impediment(
             &mock,
             "run-1",
-            "technical_error",
+            ImpedimentType::TechnicalError,
             "Database connection failed",
             "builder",
             Some("Tried 3 times"),
             Some("Check DB credentials"),
             Some("blocked"),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -610,13 +1288,15 @@ mod impediment_tests {
         let result = report_impediment(
             &mock,
             "run-1",
-            "missing_information",
+            ImpedimentType::MissingInformation,
             "Need API key",
             "strategist",
             None,
             None,
             None,
             None,
+            None,
+            None,
         )
         .await;
 
@@ -634,13 +1314,15 @@ mod impediment_tests {
         let result = report_impediment(
             &mock,
             "run-1",
-            "other",
+            ImpedimentType::Other,
             "Something blocked",
             "builder",
             None,
             None,
             None,
             None,
+            None,
+            None,
         )
         .await;
 
@@ -658,13 +1340,15 @@ mod impediment_tests {
         let _ = report_impediment(
             &mock,
             "run-1",
-            "permission_needed",
+            ImpedimentType::PermissionNeeded,
             "Cannot write to directory",
             "builder",
             None,
             Some("Run with sudo or change permissions"),
             None,
             None,
+            None,
+            None,
         )
         .await;
 
@@ -706,13 +1390,15 @@ mod impediment_tests {
         let result = report_impediment(
             &mock,
             "run-1",
-            "missing_information",
+            ImpedimentType::MissingInformation,
             "Need database selection",
             "strategist",
             None,
             None,
             None,
             Some(response_format),
+            None,
+            None,
         )
         .await;
 
@@ -736,7 +1422,10 @@ mod input_tests {
         let mock = MockIpcClient::new();
         mock.when_called(
             "/api/runs/run-1/input",
-            RequestInputResponse { success: true },
+            RequestInputResponse {
+                success: true,
+                request_id: None,
+            },
         );
 
         let result = request_input(
@@ -763,7 +1452,10 @@ mod input_tests {
         let mock = MockIpcClient::new();
         mock.when_called(
             "/api/runs/run-1/input",
-            RequestInputResponse { success: true },
+            RequestInputResponse {
+                success: true,
+                request_id: None,
+            },
         );
 
         let result = request_input(
@@ -784,7 +1476,10 @@ mod input_tests {
         let mock = MockIpcClient::new();
         mock.when_called(
             "/api/runs/run-1/input",
-            RequestInputResponse { success: true },
+            RequestInputResponse {
+                success: true,
+                request_id: None,
+            },
         );
 
         let _ = request_input(
@@ -810,13 +1505,83 @@ mod input_tests {
         let mock = MockIpcClient::new();
         mock.when_called(
             "/api/runs/run-1/input",
-            RequestInputResponse { success: false },
+            RequestInputResponse {
+                success: false,
+                request_id: None,
+            },
         );
 
         let result = request_input(&mock, "run-1", "Question?", "builder", None, None).await;
 
         assert!(result.is_err());
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_await_input_polls_until_answered() {
+        let mock = MockIpcClient::new();
+        let endpoint = "/api/runs/run-1/input/in-1";
+        let unanswered = || InputStatusResponse {
+            request_id: "in-1".to_string(),
+            answered: false,
+            answer: None,
+            answered_by: None,
+            answered_at: None,
+        };
+        mock.when_called(endpoint, unanswered());
+        let backend = mock.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            backend.when_called(
+                endpoint,
+                InputStatusResponse {
+                    answered: true,
+                    answer: Some("Use JWT".to_string()),
+                    answered_by: Some("human".to_string()),
+                    ..unanswered()
+                },
+            );
+        });
+
+        let status = await_input(
+            &mock,
+            "run-1",
+            "in-1",
+            Duration::from_secs(60),
+            INPUT_POLL_INTERVAL,
+        )
+        .await
+        .unwrap();
+        assert_eq!(mock.requests_to(endpoint).len(), 3);
+        assert_eq!(
+            format_input_answer("in-1", Duration::ZERO, status.as_ref()),
+            "✓ Input request in-1 answered by human:\n\nUse JWT"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_await_input_times_out_with_guidance() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/input/in-1",
+            InputStatusResponse {
+                request_id: "in-1".to_string(),
+                answered: false,
+                answer: None,
+                answered_by: None,
+                answered_at: None,
+            },
+        );
+
+        let timeout = Duration::from_secs(5);
+        let status = await_input(&mock, "run-1", "in-1", timeout, INPUT_POLL_INTERVAL)
+            .await
+            .unwrap();
+        assert!(status.is_none());
+        // Checked at 0s, 2s, 4s, and once more at the deadline
+        assert_eq!(mock.requests_to("/api/runs/run-1/input/in-1").len(), 4);
+        assert!(format_input_answer("in-1", timeout, None)
+            .starts_with("⏳ No answer to input request in-1 after 5s."));
+    }
 }
 
 #[cfg(test)]
@@ -840,6 +1605,7 @@ mod handoff_tests {
                 "src/auth.rs".to_string(),
                 "tests/auth_test.rs".to_string(),
             ]),
+            None,
         )
         .await;
 
@@ -865,6 +1631,7 @@ mod handoff_tests {
             "builder",
             None,
             None,
+            None,
         )
         .await;
 
@@ -884,6 +1651,7 @@ mod handoff_tests {
             "strategist",
             None,
             None,
+            None,
         )
         .await;
 
@@ -895,6 +1663,36 @@ mod handoff_tests {
         assert!(req.contains("strategist"));
     }
 
+    #[tokio::test]
+    async fn test_handoff_sends_tasks() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/runs/run-1/handoff", HandoffResponse { success: true });
+
+        let tasks = vec![HandoffTask {
+            id: "T1".to_string(),
+            title: "Add the cache layer".to_string(),
+            acceptance_criteria: vec!["Hit rate is logged".to_string()],
+            artifacts: vec![],
+        }];
+        handoff(
+            &mock,
+            "run-1",
+            "builder",
+            "Spec is ready",
+            "strategist",
+            None,
+            None,
+            Some(tasks),
+        )
+        .await
+        .unwrap();
+
+        let req = &mock.requests_to("/api/runs/run-1/handoff")[0];
+        assert!(req.contains(
+            r#""tasks":[{"id":"T1","title":"Add the cache layer","acceptanceCriteria":["Hit rate is logged"]}]"#
+        ));
+    }
+
     #[tokio::test]
     async fn test_handoff_handles_backend_error() {
         let mock = MockIpcClient::new();
@@ -911,6 +1709,7 @@ mod handoff_tests {
             "strategist",
             None,
             None,
+            None,
         )
         .await;
 
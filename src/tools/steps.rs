@@ -0,0 +1,181 @@
+//! Progress against a role's playbook steps.
+//!
+//! The backend splits each role's protocol into steps. Agents tick them off as
+//! they go, which gives the dashboard a progress bar instead of having to
+//! guess progress from free-text status reports.
+
+use crate::ipc::messages::{
+    ProtocolStepCompleteRequest, ProtocolStepCompleteResponse, ProtocolStepsListRequest,
+    ProtocolStepsListResponse,
+};
+use crate::ipc::traits::IpcClient;
+use crate::tools::timestamps;
+use crate::types::errors::IpcError;
+use std::fmt::Write;
+
+/// Fetches the steps of `role` in `run_id`, with their completion state.
+pub async fn list_steps<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: &str,
+) -> Result<ProtocolStepsListResponse, IpcError> {
+    let request = ProtocolStepsListRequest {
+        run_id: run_id.to_string(),
+        source: Some(role.to_string()),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/steps", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Marks `step_id` of `role` as done.
+pub async fn complete_step<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: &str,
+    step_id: &str,
+    note: Option<&str>,
+) -> Result<ProtocolStepCompleteResponse, IpcError> {
+    let request = ProtocolStepCompleteRequest {
+        run_id: run_id.to_string(),
+        step_id: step_id.to_string(),
+        note: note.map(String::from),
+        source: Some(role.to_string()),
+    };
+
+    let endpoint = format!("/api/runs/{}/steps/complete", run_id);
+    let response: ProtocolStepCompleteResponse = client.request(&endpoint, &request).await?;
+    if response.success {
+        Ok(response)
+    } else {
+        Err(IpcError::RequestFailed(format!(
+            "Backend reported failure completing step {}",
+            step_id
+        )))
+    }
+}
+
+/// Formats a role's checklist for display to the agent.
+pub fn format_steps(response: &ProtocolStepsListResponse) -> String {
+    if response.steps.is_empty() {
+        return format!(
+            "No steps are defined for {} in this run's playbook. Use report_status to report progress.",
+            response.role
+        );
+    }
+
+    let done = response.steps.iter().filter(|step| step.completed).count();
+    let mut output = format!(
+        "## Steps for {} ({}/{} done)\n\n",
+        response.role,
+        done,
+        response.steps.len()
+    );
+    for step in &response.steps {
+        let _ = write!(
+            output,
+            "- [{}] `{}` {}",
+            if step.completed { "x" } else { " " },
+            step.id,
+            step.title
+        );
+        if let Some(completed_at) = &step.completed_at {
+            let _ = write!(output, " — {}", timestamps::age(completed_at));
+        }
+        output.push('\n');
+        if let Some(description) = &step.description {
+            let _ = writeln!(output, "  {}", description);
+        }
+        if let Some(note) = &step.note {
+            let _ = writeln!(output, "  Note: {}", note);
+        }
+    }
+    if let Some(next) = response.steps.iter().find(|step| !step.completed) {
+        let _ = write!(
+            output,
+            "\nNext: `{}`. Call protocol_step_complete when it is done.",
+            next.id
+        );
+    } else {
+        output.push_str("\nAll steps are done.");
+    }
+    output
+}
+
+/// Confirmation for a completed step.
+pub fn format_step_completed(step_id: &str, response: &ProtocolStepCompleteResponse) -> String {
+    format!(
+        "✓ Step `{}` completed ({}/{} done)",
+        step_id, response.completed, response.total
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::ProtocolStep;
+    use crate::ipc::mock::MockIpcClient;
+
+    fn steps() -> ProtocolStepsListResponse {
+        ProtocolStepsListResponse {
+            run_id: "run-1".to_string(),
+            role: "builder".to_string(),
+            steps: vec![
+                ProtocolStep {
+                    id: "design".to_string(),
+                    title: "Review the design".to_string(),
+                    description: None,
+                    completed: true,
+                    completed_at: Some("2024-01-15T10:00:00Z".to_string()),
+                    note: Some("Asked about cache TTL".to_string()),
+                },
+                ProtocolStep {
+                    id: "implement".to_string(),
+                    title: "Implement the cache layer".to_string(),
+                    description: Some("Behind a feature flag".to_string()),
+                    completed: false,
+                    completed_at: None,
+                    note: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_format_steps_marks_progress_and_next_step() {
+        let output = format_steps(&steps());
+        assert!(output.starts_with("## Steps for builder (1/2 done)"));
+        assert!(output.contains("- [x] `design` Review the design — "));
+        assert!(output.contains("  Note: Asked about cache TTL\n"));
+        assert!(output
+            .contains("- [ ] `implement` Implement the cache layer\n  Behind a feature flag\n"));
+        assert!(output.ends_with("Next: `implement`. Call protocol_step_complete when it is done."));
+    }
+
+    #[tokio::test]
+    async fn test_complete_step_sends_role_and_note() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/steps/complete",
+            ProtocolStepCompleteResponse {
+                success: true,
+                completed: 2,
+                total: 2,
+            },
+        );
+
+        let response = complete_step(&mock, "run-1", "builder", "implement", Some("Merged"))
+            .await
+            .unwrap();
+        assert_eq!(
+            format_step_completed("implement", &response),
+            "✓ Step `implement` completed (2/2 done)"
+        );
+        let sent = &mock.requests_to("/api/runs/run-1/steps/complete")[0];
+        assert!(sent.contains(r#""stepId":"implement""#));
+        assert!(sent.contains(r#""note":"Merged""#));
+        assert!(sent.contains(r#""source":"builder""#));
+    }
+}
@@ -0,0 +1,137 @@
+//! Rolling summaries of an agent's work.
+//!
+//! When a second agent pairs into a run it is told what the primary agent
+//! has been doing. Without anything better the backend guesses that from the
+//! event stream. Agents post their own summary at checkpoints instead; the
+//! latest one becomes the pairing context and can be read back by anyone in
+//! the run, including the agent itself after a context compaction.
+
+use crate::ipc::messages::{
+    GetLatestSummaryRequest, GetLatestSummaryResponse, PostSummaryRequest, PostSummaryResponse,
+};
+use crate::ipc::traits::IpcClient;
+use crate::tools::timestamps;
+use crate::types::errors::IpcError;
+use std::fmt::Write;
+
+/// Longest summary accepted, in characters.
+pub const MAX_SUMMARY_CHARS: usize = 4000;
+
+/// Stores `summary` as `role`'s latest account of its work in `run_id`.
+pub async fn post_summary<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: &str,
+    summary: &str,
+    current_artifact: Option<&str>,
+) -> Result<PostSummaryResponse, IpcError> {
+    let request = PostSummaryRequest {
+        run_id: run_id.to_string(),
+        summary: summary.trim().to_string(),
+        current_artifact: current_artifact.map(String::from),
+        source: Some(role.to_string()),
+    };
+
+    let endpoint = format!("/api/runs/{}/summary", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Fetches the latest summary posted in `run_id`, by `role` if given.
+pub async fn latest_summary<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    role: Option<&str>,
+) -> Result<GetLatestSummaryResponse, IpcError> {
+    let request = GetLatestSummaryRequest {
+        run_id: run_id.to_string(),
+        role: role.map(String::from),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/summary/latest", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Confirms a posted summary.
+pub fn format_posted(response: &PostSummaryResponse) -> String {
+    format!(
+        "✓ Summary saved (ID: {}). Agents joining the run will see it as your current work.",
+        response.summary_id
+    )
+}
+
+/// Formats the latest summary with who wrote it and when.
+pub fn format_latest(response: &GetLatestSummaryResponse) -> String {
+    let Some(summary) = &response.summary else {
+        return format!("No summary has been posted in run {} yet.", response.run_id);
+    };
+    let mut output = format!(
+        "## Latest summary by {} ({})\n\n",
+        summary.role,
+        timestamps::age(&summary.created_at)
+    );
+    if let Some(artifact) = &summary.current_artifact {
+        let _ = write!(output, "**Working on:** `{}`\n\n", artifact);
+    }
+    output.push_str(&summary.summary);
+    output.push('\n');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::mock::MockIpcClient;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_post_then_read_latest() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/runs/run-1/summary",
+            json!({"summaryId": "sum-1", "createdAt": "2024-01-15T10:00:00Z"}),
+        );
+        mock.when_called(
+            "/api/runs/run-1/summary/latest",
+            json!({
+                "runId": "run-1",
+                "summary": {
+                    "id": "sum-1",
+                    "role": "writer",
+                    "summary": "Drafted sections 1-2; next is the rollout plan.",
+                    "currentArtifact": "spec",
+                    "createdAt": "2024-01-15T10:00:00Z"
+                }
+            }),
+        );
+
+        let posted = post_summary(
+            &mock,
+            "run-1",
+            "writer",
+            "  Drafted sections 1-2; next is the rollout plan.\n",
+            Some("spec"),
+        )
+        .await
+        .unwrap();
+        assert!(format_posted(&posted).contains("sum-1"));
+        let sent = mock.requests_to("/api/runs/run-1/summary");
+        assert!(sent[0].contains("\"summary\":\"Drafted sections 1-2; next is the rollout plan.\""));
+        assert!(sent[0].contains("\"source\":\"writer\""));
+
+        let latest = latest_summary(&mock, "run-1", None).await.unwrap();
+        let output = format_latest(&latest);
+        assert!(output.starts_with("## Latest summary by writer ("));
+        assert!(output.contains("**Working on:** `spec`\n\nDrafted sections 1-2"));
+
+        let none = GetLatestSummaryResponse {
+            run_id: "run-1".to_string(),
+            summary: None,
+        };
+        assert_eq!(
+            format_latest(&none),
+            "No summary has been posted in run run-1 yet."
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Stale suggestion sweeping.
+//!
+//! A suggestion replaces the text at its offsets, and the document keeps
+//! changing after it is made. Once that text has moved or been rewritten the
+//! suggestion can't be applied, but the human is still shown it. A sweep
+//! compares every pending suggestion with the current document: suggestions
+//! whose text moved are listed so their author can recreate them, and those
+//! whose text is gone can be withdrawn.
+
+use crate::ipc::messages::{
+    DocArtifactReadResponse, StaleSuggestion, Staleness, SuggestionDetail, SuggestionSweep,
+};
+use crate::ipc::traits::IpcClient;
+use crate::tools::{artifacts, batch, offsets, text};
+use crate::types::errors::IpcError;
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// Rejection reason given for withdrawn suggestions.
+const WITHDRAW_REASON: &str =
+    "Withdrawn as stale: the text it changes is no longer in the document";
+
+/// Max characters of suggested-over text quoted in a stale suggestion's detail.
+const EXCERPT_CHARS: usize = 60;
+
+/// Whether `suggestion` still fits `document`, and why not. Suggestions made
+/// against the current content are never stale.
+pub fn staleness(
+    document: &DocArtifactReadResponse,
+    suggestion: &SuggestionDetail,
+) -> Option<(Staleness, String)> {
+    if suggestion.base_content_hash.as_deref() == Some(document.content_hash.as_str()) {
+        return None;
+    }
+    let text = &document.content;
+    let at = |offset: Option<i64>| {
+        offset.and_then(|o| {
+            usize::try_from(o)
+                .ok()
+                .and_then(|o| offsets::char_to_byte(text, o).ok())
+        })
+    };
+    match suggestion
+        .original_text
+        .as_deref()
+        .filter(|t| !t.is_empty())
+    {
+        Some(original) => {
+            let current = match (at(suggestion.start_offset), at(suggestion.end_offset)) {
+                (Some(start), Some(end)) if start <= end => Some(&text[start..end]),
+                _ => None,
+            };
+            if current == Some(original) {
+                None
+            } else if text.contains(original) {
+                Some((
+                    Staleness::Moved,
+                    format!(
+                        "\"{}\" is no longer at chars {}-{}",
+                        excerpt(original),
+                        suggestion.start_offset.unwrap_or_default(),
+                        suggestion.end_offset.unwrap_or_default()
+                    ),
+                ))
+            } else {
+                Some((
+                    Staleness::Missing,
+                    format!("\"{}\" is no longer in the document", excerpt(original)),
+                ))
+            }
+        }
+        None => {
+            let offset = suggestion.end_offset.or(suggestion.start_offset)?;
+            at(Some(offset)).is_none().then(|| {
+                (
+                    Staleness::Missing,
+                    format!(
+                        "char {} is past the end of the document ({} characters)",
+                        offset,
+                        text.chars().count()
+                    ),
+                )
+            })
+        }
+    }
+}
+
+/// The first line of `text`, shortened for messages.
+fn excerpt(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    match text::truncate(line, EXCERPT_CHARS) {
+        Cow::Borrowed(line) if line.len() < text.len() => format!("{}{}", line, text::ELLIPSIS),
+        excerpt => excerpt.into_owned(),
+    }
+}
+
+/// Check the pending suggestions of one artifact, withdrawing those whose
+/// text is gone when `withdraw` is set. Returns how many were checked.
+async fn sweep_artifact<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: &str,
+    withdraw: Option<&str>,
+    page_lines: i64,
+) -> Result<(usize, Vec<StaleSuggestion>), IpcError> {
+    let pending = artifacts::list_suggestions(client, run_id, artifact_id, Some("pending")).await?;
+    if pending.suggestions.is_empty() {
+        return Ok((0, vec![]));
+    }
+    let document = artifacts::read_full_content(client, run_id, artifact_id, page_lines).await?;
+    let mut stale = Vec::new();
+    for suggestion in &pending.suggestions {
+        let Some((staleness, detail)) = staleness(&document, suggestion) else {
+            continue;
+        };
+        let mut found = StaleSuggestion {
+            artifact_id: artifact_id.to_string(),
+            suggestion_id: suggestion.id.clone(),
+            comment_id: suggestion.comment_id.clone(),
+            suggested_by: suggestion.suggested_by.clone(),
+            staleness,
+            detail,
+            withdrawn: false,
+            error: None,
+        };
+        if let (Some(source), Staleness::Missing) = (withdraw, staleness) {
+            match artifacts::reject_suggestion(
+                client,
+                run_id,
+                artifact_id,
+                &suggestion.id,
+                Some(WITHDRAW_REASON),
+                source,
+            )
+            .await
+            {
+                Ok(_) => found.withdrawn = true,
+                Err(e) => found.error = Some(e.to_string()),
+            }
+        }
+        stale.push(found);
+    }
+    Ok((pending.suggestions.len(), stale))
+}
+
+/// Sweep one artifact, or every artifact in the run. `withdraw` is the role
+/// rejecting suggestions whose text is gone; `None` only lists them.
+pub async fn sweep<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+    artifact_id: Option<&str>,
+    withdraw: Option<&str>,
+    page_lines: i64,
+    concurrency: usize,
+) -> Result<SuggestionSweep, IpcError> {
+    let mut sweep = SuggestionSweep {
+        run_id: run_id.to_string(),
+        checked: 0,
+        stale: vec![],
+        failed: vec![],
+    };
+    let outcomes = match artifact_id {
+        Some(artifact_id) => {
+            vec![sweep_artifact(client, run_id, artifact_id, withdraw, page_lines).await?]
+        }
+        None => {
+            let outcomes =
+                batch::for_each_artifact(client, run_id, concurrency, |artifact| async move {
+                    let result =
+                        sweep_artifact(client, run_id, &artifact.id, withdraw, page_lines).await;
+                    (artifact, result)
+                })
+                .await?;
+            let mut swept = Vec::new();
+            for outcome in outcomes {
+                match outcome.result {
+                    Some(result) => swept.push(result),
+                    None => sweep
+                        .failed
+                        .push((outcome.artifact_id, outcome.error.unwrap_or_default())),
+                }
+            }
+            swept
+        }
+    };
+    for (checked, stale) in outcomes {
+        sweep.checked += checked;
+        sweep.stale.extend(stale);
+    }
+    Ok(sweep)
+}
+
+/// Headings of `format_sweep`, in order.
+const SECTIONS: [&str; 4] = [
+    "Withdrawn",
+    "Withdrawal failed",
+    "Text gone (not withdrawn)",
+    "Text moved (recreate at the new offsets)",
+];
+
+/// Which heading a stale suggestion is listed under.
+fn section(stale: &StaleSuggestion) -> &'static str {
+    match (stale.withdrawn, &stale.error, stale.staleness) {
+        (true, _, _) => SECTIONS[0],
+        (false, Some(_), _) => SECTIONS[1],
+        (false, None, Staleness::Missing) => SECTIONS[2],
+        (false, None, Staleness::Moved) => SECTIONS[3],
+    }
+}
+
+/// Format a sweep: the stale suggestions grouped by what happened to them.
+pub fn format_sweep(sweep: &SuggestionSweep) -> String {
+    if sweep.stale.is_empty() && sweep.failed.is_empty() {
+        return format!(
+            "✓ All {} pending suggestion(s) in run {} still fit their documents.",
+            sweep.checked, sweep.run_id
+        );
+    }
+    let mut output = format!(
+        "## Stale suggestions in run {}\n\n{} of {} pending suggestion(s) no longer fit their document.\n",
+        sweep.run_id,
+        sweep.stale.len(),
+        sweep.checked
+    );
+    for title in SECTIONS {
+        let entries: Vec<&StaleSuggestion> =
+            sweep.stale.iter().filter(|s| section(s) == title).collect();
+        if entries.is_empty() {
+            continue;
+        }
+        let _ = write!(output, "\n### {}\n\n", title);
+        for s in entries {
+            let _ = writeln!(
+                output,
+                "- `{}` on {} by {} (comment `{}`): {}{}",
+                s.suggestion_id,
+                s.artifact_id,
+                s.suggested_by,
+                s.comment_id,
+                s.detail,
+                s.error
+                    .as_deref()
+                    .map(|e| format!(" — {}", e))
+                    .unwrap_or_default()
+            );
+        }
+    }
+    if !sweep.failed.is_empty() {
+        output.push_str("\n### Not checked\n\n");
+        for (artifact_id, error) in &sweep.failed {
+            let _ = writeln!(output, "- {}: {}", artifact_id, error);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::mock::MockIpcClient;
+    use serde_json::json;
+
+    fn suggestion(id: &str, start: i64, end: i64, original: Option<&str>) -> serde_json::Value {
+        json!({
+            "id": id,
+            "commentId": "c-1",
+            "artifactId": "spec",
+            "suggestedBy": "editor",
+            "editType": "replace_range",
+            "startOffset": start,
+            "endOffset": end,
+            "originalText": original,
+            "suggestedText": "five minutes",
+            "rationale": null,
+            "status": "pending",
+            "acceptedBy": null,
+            "acceptedAt": null,
+            "rejectionReason": null,
+            "createdAt": "2024-01-15T10:00:00Z",
+            "updatedAt": "2024-01-15T10:00:00Z"
+        })
+    }
+
+    fn document(content: &str) -> DocArtifactReadResponse {
+        DocArtifactReadResponse {
+            artifact_id: "spec".to_string(),
+            filename: "spec.md".to_string(),
+            content: content.to_string(),
+            content_hash: "h2".to_string(),
+            total_lines: 1,
+            returned_lines: 1,
+            offset: 0,
+            has_more: false,
+            comments: None,
+        }
+    }
+
+    #[test]
+    fn test_staleness() {
+        let doc = document("TTL: sixty seconds. Retries: three.");
+        let check = |value: serde_json::Value| {
+            staleness(&doc, &serde_json::from_value(value).unwrap()).map(|(s, _)| s)
+        };
+        assert_eq!(check(suggestion("s1", 5, 18, Some("sixty seconds"))), None);
+        assert_eq!(
+            check(suggestion("s2", 0, 5, Some("three"))),
+            Some(Staleness::Moved)
+        );
+        assert_eq!(
+            check(suggestion("s3", 5, 18, Some("ten seconds"))),
+            Some(Staleness::Missing)
+        );
+        // Inserts only go stale past the end
+        assert_eq!(check(suggestion("s4", 35, 35, None)), None);
+        assert_eq!(
+            check(suggestion("s5", 90, 90, None)),
+            Some(Staleness::Missing)
+        );
+
+        // Made against the current content: trusted as is
+        let mut current = suggestion("s6", 5, 18, Some("ten seconds"));
+        current["baseContentHash"] = json!("h2");
+        assert_eq!(check(current), None);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_withdraws_only_missing_text() {
+        let base = "/api/runs/run-1/artifacts/spec";
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            &format!("{}/suggestions/list", base),
+            json!({
+                "artifactId": "spec",
+                "suggestions": [
+                    suggestion("s1", 0, 5, Some("three")),
+                    suggestion("s2", 5, 18, Some("ten seconds"))
+                ]
+            }),
+        );
+        mock.when_called(
+            base,
+            json!({
+                "artifactId": "spec",
+                "filename": "spec.md",
+                "content": "TTL: sixty seconds. Retries: three.",
+                "contentHash": "h2",
+                "totalLines": 1,
+                "returnedLines": 1,
+                "offset": 0,
+                "hasMore": false
+            }),
+        );
+        mock.when_called(
+            &format!("{}/suggestions/s2/reject", base),
+            json!({"success": true, "suggestionId": "s2"}),
+        );
+
+        let sweep = sweep(&mock, "run-1", Some("spec"), Some("editor"), 500, 4)
+            .await
+            .unwrap();
+        assert_eq!(sweep.checked, 2);
+        let states: Vec<(&str, Staleness, bool)> = sweep
+            .stale
+            .iter()
+            .map(|s| (s.suggestion_id.as_str(), s.staleness, s.withdrawn))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("s1", Staleness::Moved, false),
+                ("s2", Staleness::Missing, true)
+            ]
+        );
+        assert!(mock
+            .requests_to(&format!("{}/suggestions/s1/reject", base))
+            .is_empty());
+        let output = format_sweep(&sweep);
+        assert!(output.contains("### Withdrawn\n\n- `s2` on spec by editor"));
+        assert!(output.contains("\"three\" is no longer at chars 0-5"));
+    }
+}
@@ -0,0 +1,233 @@
+//! Markdown table renderings of list-style tool responses (`OutputFormat::Table`).
+//!
+//! One row per item, so long lists of artifacts, comments, and suggestions can be
+//! scanned at a glance. Details that don't fit a row (comment bodies, diffs) are
+//! left to the default markdown output.
+
+use crate::ipc::messages::{
+    DocArtifactListCommentsResponse, DocArtifactListResponse, DocArtifactListSuggestionsResponse,
+};
+use crate::tools::{artifacts, text, timestamps};
+use std::fmt::Write;
+
+/// Max characters of comment content shown in a row.
+const PREVIEW_CHARS: usize = 40;
+
+/// How [`table`] lays out its rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStyle {
+    /// `| a | b |` rows under a `|---|` separator, ending in a newline.
+    Markdown,
+    /// Bare `a | b` rows under a header line, for `OutputFormat::Compact`.
+    Compact,
+}
+
+/// Render rows as a `|`-separated table. Pipes and newlines in cells are
+/// escaped so a cell can't break its row.
+pub fn table<S: AsRef<str>>(style: TableStyle, headers: &[&str], rows: Vec<Vec<S>>) -> String {
+    let markdown = style == TableStyle::Markdown;
+    let mut output = String::with_capacity(
+        (headers.len() + 1) * 8
+            + rows
+                .iter()
+                .flatten()
+                .map(|c| c.as_ref().len() + 3)
+                .sum::<usize>(),
+    );
+    if markdown {
+        let _ = writeln!(output, "| {} |", headers.join(" | "));
+        output.push('|');
+        for _ in headers {
+            output.push_str("---|");
+        }
+    } else {
+        output.push_str(&headers.join(" | "));
+    }
+    for row in rows {
+        output.push('\n');
+        if markdown {
+            output.push_str("| ");
+        }
+        for (i, cell) in row.iter().enumerate() {
+            if i > 0 {
+                output.push_str(" | ");
+            }
+            for c in cell.as_ref().chars() {
+                match c {
+                    '|' => output.push_str("\\|"),
+                    '\n' => output.push(' '),
+                    c => output.push(c),
+                }
+            }
+        }
+        if markdown {
+            output.push_str(" |");
+        }
+    }
+    if markdown {
+        output.push('\n');
+    }
+    output
+}
+
+pub fn format_list_response(response: &DocArtifactListResponse) -> String {
+    let mut output = format!(
+        "## Artifacts in Run: {}\n\n**Total:** {} artifacts\n\n",
+        response.run_id,
+        response.artifacts.len()
+    );
+    if response.artifacts.is_empty() {
+        output.push_str("No artifacts found in this run.\n");
+        return output;
+    }
+    let rows = response
+        .artifacts
+        .iter()
+        .map(|a| {
+            vec![
+                format!("`{}`", a.id),
+                a.filename.clone(),
+                a.document_type.clone(),
+                a.total_lines.to_string(),
+                timestamps::age(&a.updated_at),
+                a.tags.join(", "),
+            ]
+        })
+        .collect();
+    output.push_str(&table(
+        TableStyle::Markdown,
+        &["id", "filename", "type", "lines", "updated", "tags"],
+        rows,
+    ));
+    output
+}
+
+pub fn format_comments_response(response: &DocArtifactListCommentsResponse) -> String {
+    let mut output = format!(
+        "## Comments on Artifact: {}\n\n**Total:** {} comments{}\n\n",
+        response.artifact_id,
+        response.comments.len(),
+        artifacts::page_note(response.offset, response.has_more)
+    );
+    if response.comments.is_empty() {
+        output.push_str("No comments found matching the filter.\n");
+        return output;
+    }
+    let rows = response
+        .comments
+        .iter()
+        .map(|c| {
+            vec![
+                format!("`{}`", c.id),
+                c.comment_type.clone(),
+                c.status.clone(),
+                c.author.clone(),
+                timestamps::age(&c.created_at),
+                artifacts::reply_count(&response.comments, &c.id).to_string(),
+                text::truncate(&c.content, PREVIEW_CHARS).into_owned(),
+            ]
+        })
+        .collect();
+    output.push_str(&table(
+        TableStyle::Markdown,
+        &[
+            "id", "type", "status", "author", "age", "replies", "comment",
+        ],
+        rows,
+    ));
+    output
+}
+
+pub fn format_suggestions_response(response: &DocArtifactListSuggestionsResponse) -> String {
+    let mut output = format!(
+        "## Edit Suggestions for Artifact: {}\n\n**Total:** {} suggestions{}\n\n",
+        response.artifact_id,
+        response.suggestions.len(),
+        artifacts::page_note(response.offset, response.has_more)
+    );
+    if response.suggestions.is_empty() {
+        output.push_str("No suggestions found matching the filter.\n");
+        return output;
+    }
+    let rows = response
+        .suggestions
+        .iter()
+        .map(|s| {
+            vec![
+                format!("`{}`", s.id),
+                s.edit_type.clone(),
+                s.status.clone(),
+                s.suggested_by.clone(),
+                timestamps::age(&s.created_at),
+                format!("`{}`", s.comment_id),
+            ]
+        })
+        .collect();
+    output.push_str(&table(
+        TableStyle::Markdown,
+        &["id", "type", "status", "author", "age", "comment"],
+        rows,
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::CommentDetail;
+
+    #[test]
+    fn test_comments_table_has_one_row_per_comment() {
+        let comment = |id: &str, content: &str| CommentDetail {
+            id: id.to_string(),
+            comment_type: "issue".to_string(),
+            status: "open".to_string(),
+            selection_start: 0,
+            selection_end: 5,
+            selection_text: None,
+            content: content.to_string(),
+            suggested_text: None,
+            author: "strategist".to_string(),
+            resolved_by: None,
+            resolved_at: None,
+            resolution_note: None,
+            created_at: "not-a-timestamp".to_string(),
+            mentions: vec![],
+            parent_comment_id: None,
+        };
+        let response = DocArtifactListCommentsResponse {
+            artifact_id: "art-1".to_string(),
+            offset: 0,
+            has_more: false,
+            comments: vec![
+                comment("c-1", "Fix a | b"),
+                CommentDetail {
+                    parent_comment_id: Some("c-1".to_string()),
+                    ..comment("c-2", "Second\nline")
+                },
+            ],
+        };
+
+        let output = format_comments_response(&response);
+        assert!(output.contains(
+            "| id | type | status | author | age | replies | comment |\n|---|---|---|---|---|---|---|\n"
+        ));
+        assert!(output
+            .contains("| `c-1` | issue | open | strategist | not-a-timestamp | 1 | Fix a \\| b |"));
+        assert!(output
+            .contains("| `c-2` | issue | open | strategist | not-a-timestamp | 0 | Second line |"));
+    }
+
+    #[test]
+    fn test_empty_lists_keep_markdown_message() {
+        let response = DocArtifactListSuggestionsResponse {
+            artifact_id: "art-1".to_string(),
+            offset: 0,
+            has_more: false,
+            suggestions: vec![],
+        };
+        let output = format_suggestions_response(&response);
+        assert!(output.contains("No suggestions found"));
+        assert!(!output.contains("|---|"));
+    }
+}
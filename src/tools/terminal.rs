@@ -4,12 +4,16 @@
 //! runs directly from the terminal rather than through the app wizard.
 
 use crate::ipc::messages::{
-    HotwireArtifact, HotwireRequest, HotwireResponse, ListActiveRunsRequest,
-    ListActiveRunsResponse, ListPlaybooksRequest, ListPlaybooksResponse, PairRequest, PairResponse,
-    PlaybookInfo,
+    GetPlaybookRequest, GetPlaybookResponse, HotwireArtifact, HotwireRequest, HotwireResponse,
+    ListActiveRunsRequest, ListActiveRunsResponse, ListPlaybooksRequest, ListPlaybooksResponse,
+    Multiplexer, PairRequest, PairResponse, PlaybookInfo, ResumeDigest, ResumeRunRequest,
+    ResumeRunResponse,
 };
 use crate::ipc::traits::IpcClient;
+use crate::tools::{run_context, timestamps};
 use crate::types::errors::IpcError;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 // =============================================================================
 // HOTWIRE - Initiate run from terminal
@@ -25,6 +29,7 @@ pub async fn hotwire<C: IpcClient>(
     client: &C,
     project_path: &str,
     zellij_session: &str,
+    multiplexer: Option<Multiplexer>,
     intent: Option<&str>,
     suggested_playbook: Option<&str>,
     suggested_artifacts: Option<Vec<HotwireArtifact>>,
@@ -32,9 +37,11 @@ pub async fn hotwire<C: IpcClient>(
     let request = HotwireRequest {
         project_path: project_path.to_string(),
         zellij_session: zellij_session.to_string(),
+        multiplexer,
         intent: intent.map(|s| s.to_string()),
         suggested_playbook: suggested_playbook.map(|s| s.to_string()),
         suggested_artifacts,
+        format: None,
     };
 
     client.request("/api/hotwire", &request).await
@@ -118,11 +125,14 @@ Failed to start workflow: {}
 pub async fn pair<C: IpcClient>(
     client: &C,
     zellij_session: &str,
+    multiplexer: Option<Multiplexer>,
     project_path: &str,
 ) -> Result<PairResponse, IpcError> {
     let request = PairRequest {
         zellij_session: zellij_session.to_string(),
         project_path: project_path.to_string(),
+        multiplexer,
+        format: None,
     };
 
     client.request("/api/pair", &request).await
@@ -147,7 +157,7 @@ Context from primary agent:
 - Primary status: {}
 - Current artifact: {}
 - Summary: {}
-
+{}
 Your protocol is below.
 
 ═══════════════════════════════════════════════════════════════
@@ -165,6 +175,15 @@ Your protocol is below.
                     .as_deref()
                     .unwrap_or("(none)"),
                 joined.context.conversation_summary,
+                joined
+                    .context
+                    .project
+                    .as_ref()
+                    .map(|project| format!(
+                        "\nProject context:\n{}\n",
+                        run_context::context_lines(project).join("\n")
+                    ))
+                    .unwrap_or_default(),
                 joined.protocol
             )
         }
@@ -254,6 +273,167 @@ Failed to join workflow: {}
     }
 }
 
+// =============================================================================
+// RESUME RUN - Re-attach after agent restart
+// =============================================================================
+
+/// Re-attaches this session to the run it previously held a role in.
+/// `last_run_id`/`last_role` come from the saved run context and help the
+/// backend when it no longer knows the session.
+///
+/// Returns one of:
+/// - `Resumed`: Re-attached, protocol and a digest of activity since the session was last seen
+/// - `NoneAvailable`: This session holds no role in an active run
+/// - `Error`: Something went wrong
+pub async fn resume_run<C: IpcClient>(
+    client: &C,
+    zellij_session: &str,
+    project_path: &str,
+    last_run_id: Option<&str>,
+    last_role: Option<&str>,
+) -> Result<ResumeRunResponse, IpcError> {
+    let request = ResumeRunRequest {
+        zellij_session: zellij_session.to_string(),
+        project_path: project_path.to_string(),
+        last_run_id: last_run_id.map(String::from),
+        last_role: last_role.map(String::from),
+        format: None,
+    };
+
+    client.request("/api/resume", &request).await
+}
+
+/// Formats the "while you were away" digest as markdown sections.
+fn format_digest(digest: &ResumeDigest) -> String {
+    let messages = if digest.messages.is_empty() {
+        "(no new messages)".to_string()
+    } else {
+        digest
+            .messages
+            .iter()
+            .map(|m| {
+                format!(
+                    "- [{}] {}: {}",
+                    timestamps::age(&m.created_at),
+                    m.source,
+                    m.content
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let edits = if digest.edits.is_empty() {
+        "(no document changes)".to_string()
+    } else {
+        digest
+            .edits
+            .iter()
+            .map(|e| {
+                format!(
+                    "- {} [{}] - {} edit(s) by {}, last {}",
+                    e.filename,
+                    e.artifact_id,
+                    e.edit_count,
+                    e.edited_by.join(", "),
+                    timestamps::age(&e.updated_at)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let comments = if digest.open_comments.is_empty() {
+        "(no open comments)".to_string()
+    } else {
+        digest
+            .open_comments
+            .iter()
+            .map(|c| {
+                format!(
+                    "- [{}] {} on {} by {}: {}",
+                    c.comment_id, c.comment_type, c.artifact_id, c.author, c.content
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    format!(
+        "New messages:\n{}\n\nDocument changes:\n{}\n\nOpen comments:\n{}",
+        messages, edits, comments
+    )
+}
+
+/// Formats the resume response for display to the agent.
+pub fn format_resume_response(response: &ResumeRunResponse) -> String {
+    match response {
+        ResumeRunResponse::Resumed(resumed) => {
+            format!(
+                r#"═══════════════════════════════════════════════════════════════
+RUN RESUMED
+═══════════════════════════════════════════════════════════════
+
+Run ID: {}
+Playbook: {}
+Your Role: {} ({})
+Away since: {}
+
+While you were away:
+
+{}
+
+Review the activity above, then continue from where you left off.
+Your protocol is below.
+
+═══════════════════════════════════════════════════════════════
+
+{}
+"#,
+                resumed.run_id,
+                resumed.playbook,
+                resumed.role_name,
+                resumed.role,
+                resumed
+                    .away_since
+                    .as_deref()
+                    .map(timestamps::format_timestamp)
+                    .unwrap_or_else(|| "(unknown)".to_string()),
+                format_digest(&resumed.digest),
+                resumed.protocol
+            )
+        }
+        ResumeRunResponse::NoneAvailable { message } => {
+            format!(
+                r#"═══════════════════════════════════════════════════════════════
+NO RUN TO RESUME
+═══════════════════════════════════════════════════════════════
+
+{}
+
+Use /hotwire to start a new run or /pair to join one.
+
+═══════════════════════════════════════════════════════════════
+"#,
+                message
+            )
+        }
+        ResumeRunResponse::Error { error } => {
+            format!(
+                r#"═══════════════════════════════════════════════════════════════
+ERROR
+═══════════════════════════════════════════════════════════════
+
+Failed to resume run: {}
+
+═══════════════════════════════════════════════════════════════
+"#,
+                error
+            )
+        }
+    }
+}
+
 // =============================================================================
 // LIST ACTIVE RUNS
 // =============================================================================
@@ -267,6 +447,8 @@ pub async fn list_active_runs<C: IpcClient>(
     let request = ListActiveRunsRequest {
         project_path: project_path.map(|s| s.to_string()),
         zellij_session: zellij_session.map(|s| s.to_string()),
+        format: None,
+        continuation: None,
     };
 
     client.request("/api/active-runs", &request).await
@@ -298,7 +480,12 @@ No active runs found.
 
             format!(
                 "- [{}] {} - {} ({}){}\n  Created: {}",
-                run.run_id, run.playbook, run.intent, run.status, role_info, run.created_at
+                run.run_id,
+                run.playbook,
+                run.intent,
+                run.status,
+                role_info,
+                timestamps::format_timestamp(&run.created_at)
             )
         })
         .collect::<Vec<_>>()
@@ -323,10 +510,62 @@ ACTIVE RUNS
 
 /// Lists available playbooks with metadata for intent matching.
 pub async fn list_playbooks<C: IpcClient>(client: &C) -> Result<ListPlaybooksResponse, IpcError> {
-    let request = ListPlaybooksRequest {};
+    let request = ListPlaybooksRequest {
+        format: None,
+        continuation: None,
+    };
     client.request("/api/playbooks", &request).await
 }
 
+/// Fetches the full details of one playbook.
+pub async fn get_playbook<C: IpcClient>(
+    client: &C,
+    playbook_id: &str,
+) -> Result<GetPlaybookResponse, IpcError> {
+    let request = GetPlaybookRequest {
+        playbook_id: playbook_id.to_string(),
+        format: None,
+        continuation: None,
+    };
+    let endpoint = format!("/api/playbooks/{}", playbook_id);
+    client.request(&endpoint, &request).await
+}
+
+/// How long a fetched playbook list is reused.
+pub const PLAYBOOK_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// The playbook list, prefetched at startup so the first `list_playbooks` or
+/// `/hotwire` doesn't wait on the backend.
+#[derive(Default)]
+pub struct PlaybookCache {
+    entry: Mutex<Option<(ListPlaybooksResponse, Instant)>>,
+}
+
+impl PlaybookCache {
+    /// Fetch the playbook list now and keep it for [`PLAYBOOK_CACHE_TTL`].
+    pub async fn prefetch<C: IpcClient>(&self, client: &C) -> Result<(), IpcError> {
+        let playbooks = list_playbooks(client).await?;
+        *self.entry.lock().unwrap() = Some((playbooks, Instant::now()));
+        Ok(())
+    }
+
+    /// The playbook list, from the cache if fetched less than `ttl` ago.
+    pub async fn playbooks<C: IpcClient>(
+        &self,
+        client: &C,
+        ttl: Duration,
+    ) -> Result<ListPlaybooksResponse, IpcError> {
+        if let Some((playbooks, at)) = self.entry.lock().unwrap().as_ref() {
+            if at.elapsed() < ttl {
+                return Ok(playbooks.clone());
+            }
+        }
+        let playbooks = list_playbooks(client).await?;
+        *self.entry.lock().unwrap() = Some((playbooks.clone(), Instant::now()));
+        Ok(playbooks)
+    }
+}
+
 /// Formats the playbooks response for display.
 pub fn format_playbooks(response: &ListPlaybooksResponse) -> String {
     if response.playbooks.is_empty() {
@@ -344,7 +583,7 @@ No playbooks available.
     let playbooks_list: String = response
         .playbooks
         .iter()
-        .map(|pb| format_single_playbook(pb))
+        .map(format_single_playbook)
         .collect::<Vec<_>>()
         .join("\n---\n\n");
 
@@ -361,6 +600,56 @@ AVAILABLE PLAYBOOKS
     )
 }
 
+/// Formats one playbook with its initialization hints and protocol outline.
+pub fn format_playbook(response: &GetPlaybookResponse) -> String {
+    let pb = &response.playbook;
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    let suggested_paths = if pb.initialization.suggested_paths.is_empty() {
+        "(none)".to_string()
+    } else {
+        pb.initialization.suggested_paths.join(", ")
+    };
+
+    let outline = if response.protocol_outline.is_empty() {
+        "(not available)".to_string()
+    } else {
+        response
+            .protocol_outline
+            .iter()
+            .map(|section| {
+                let role = section
+                    .role
+                    .as_deref()
+                    .map(|role| format!(" [{}]", role))
+                    .unwrap_or_default();
+                match &section.summary {
+                    Some(summary) => format!("  - {}{}: {}", section.title, role, summary),
+                    None => format!("  - {}{}", section.title, role),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    format!(
+        r#"{}
+
+**Initialization:**
+  - Expects document: {}
+  - Expects goal: {}
+  - Suggested paths: {}
+
+**Protocol outline:**
+{}
+"#,
+        format_single_playbook(pb),
+        yes_no(pb.initialization.expects_document),
+        yes_no(pb.initialization.expects_goal),
+        suggested_paths,
+        outline
+    )
+}
+
 fn format_single_playbook(pb: &PlaybookInfo) -> String {
     let roles_list: String = pb
         .roles
@@ -416,8 +705,9 @@ fn format_single_playbook(pb: &PlaybookInfo) -> String {
 mod tests {
     use super::*;
     use crate::ipc::messages::{
-        ActiveRun, HotwireNeedsConfirmation, HotwireStarted, PairJoined, PairNeedsSelection,
-        PairProjectMismatch, PairingContext, PendingPairRun, PlaybookInitHints, PlaybookRoleInfo,
+        ActiveRun, DigestEdit, DigestMessage, HotwireNeedsConfirmation, HotwireStarted, PairJoined,
+        PairNeedsSelection, PairProjectMismatch, PairingContext, PendingPairRun, PlaybookInitHints,
+        PlaybookRoleInfo, ProtocolOutlineSection, RunContext, RunResumed,
     };
     use crate::ipc::mock::MockIpcClient;
 
@@ -449,6 +739,7 @@ mod tests {
             &mock,
             "/path/to/project",
             "my-session",
+            None,
             Some("write a PRD"),
             Some("doc-editor"),
             None,
@@ -460,6 +751,8 @@ mod tests {
         assert!(requests[0].contains("/path/to/project"));
         assert!(requests[0].contains("my-session"));
         assert!(requests[0].contains("write a PRD"));
+        // Zellij is assumed when the multiplexer is not known
+        assert!(!requests[0].contains("multiplexer"));
     }
 
     #[tokio::test]
@@ -467,7 +760,7 @@ mod tests {
         let mock = MockIpcClient::new();
         mock.when_called("/api/hotwire", sample_hotwire_started());
 
-        let result = hotwire(&mock, "/path", "session", None, None, None).await;
+        let result = hotwire(&mock, "/path", "session", None, None, None, None).await;
 
         assert!(result.is_ok());
         match result.unwrap() {
@@ -515,6 +808,7 @@ mod tests {
                 primary_status: "Writing section 3".to_string(),
                 current_artifact: Some("docs/prd.md".to_string()),
                 conversation_summary: "Writer has completed intro and section 1-2.".to_string(),
+                project: None,
             },
         })
     }
@@ -544,12 +838,19 @@ mod tests {
         let mock = MockIpcClient::new();
         mock.when_called("/api/pair", sample_pair_joined());
 
-        let _ = pair(&mock, "my-session", "/path/to/project").await;
+        let _ = pair(
+            &mock,
+            "my-session",
+            Some(Multiplexer::Tmux),
+            "/path/to/project",
+        )
+        .await;
 
         mock.assert_called("/api/pair");
         let requests = mock.requests_to("/api/pair");
         assert!(requests[0].contains("my-session"));
         assert!(requests[0].contains("/path/to/project"));
+        assert!(requests[0].contains(r#""multiplexer":"tmux""#));
     }
 
     #[test]
@@ -565,6 +866,29 @@ mod tests {
         assert!(formatted.contains("docs/prd.md"));
     }
 
+    #[test]
+    fn test_format_pair_joined_with_project_context() {
+        let PairResponse::Joined(mut joined) = sample_pair_joined() else {
+            unreachable!()
+        };
+        let formatted = format_pair_response(&PairResponse::Joined(joined.clone()));
+        assert!(!formatted.contains("Project context"));
+
+        joined.context.project = Some(Box::new(RunContext {
+            branch: Some("feature/auth".to_string()),
+            test_command: Some("npm test".to_string()),
+            ..RunContext::default()
+        }));
+        let formatted = format_pair_response(&PairResponse::Joined(joined));
+        assert!(formatted.contains(
+            "- Summary: Writer has completed intro and section 1-2.\n\n\
+            Project context:\n\
+            - **Branch:** `feature/auth`\n\
+            - **Test command:** `npm test`\n\n\
+            Your protocol is below."
+        ));
+    }
+
     #[test]
     fn test_format_pair_needs_selection() {
         let response = sample_pair_needs_selection();
@@ -591,6 +915,78 @@ mod tests {
         assert!(formatted.contains("/wrong/path"));
     }
 
+    // ===== RESUME RUN TESTS =====
+
+    fn sample_resumed() -> ResumeRunResponse {
+        ResumeRunResponse::Resumed(RunResumed {
+            run_id: "run-789".to_string(),
+            role: "reviewer".to_string(),
+            role_name: "Reviewer".to_string(),
+            playbook: "Write → Review".to_string(),
+            protocol: "Review each section.".to_string(),
+            away_since: Some("2024-01-15T10:00:00Z".to_string()),
+            digest: ResumeDigest {
+                messages: vec![DigestMessage {
+                    source: "writer".to_string(),
+                    content: "Section 3 is ready".to_string(),
+                    created_at: "2024-01-15T10:05:00Z".to_string(),
+                }],
+                edits: vec![DigestEdit {
+                    artifact_id: "art-1".to_string(),
+                    filename: "docs/prd.md".to_string(),
+                    edited_by: vec!["writer".to_string()],
+                    edit_count: 4,
+                    updated_at: "2024-01-15T10:04:00Z".to_string(),
+                }],
+                open_comments: vec![],
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn test_resume_run_sends_correct_request() {
+        let mock = MockIpcClient::new();
+        mock.when_called("/api/resume", sample_resumed());
+
+        let response = resume_run(
+            &mock,
+            "my-session",
+            "/path/to/project",
+            Some("run-789"),
+            Some("reviewer"),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(response, ResumeRunResponse::Resumed(_)));
+        let requests = mock.requests_to("/api/resume");
+        assert!(requests[0].contains("\"zellijSession\":\"my-session\""));
+        assert!(requests[0].contains("\"projectPath\":\"/path/to/project\""));
+        assert!(requests[0].contains("\"lastRunId\":\"run-789\""));
+    }
+
+    #[test]
+    fn test_format_resume_includes_digest_and_protocol() {
+        let formatted = format_resume_response(&sample_resumed());
+
+        assert!(formatted.contains("RUN RESUMED"));
+        assert!(formatted.contains("Reviewer (reviewer)"));
+        assert!(formatted.contains("writer: Section 3 is ready"));
+        assert!(formatted.contains("docs/prd.md [art-1] - 4 edit(s) by writer"));
+        assert!(formatted.contains("(no open comments)"));
+        assert!(formatted.contains("Review each section."));
+    }
+
+    #[test]
+    fn test_format_resume_none_available() {
+        let formatted = format_resume_response(&ResumeRunResponse::NoneAvailable {
+            message: "This session holds no role in an active run.".to_string(),
+        });
+
+        assert!(formatted.contains("NO RUN TO RESUME"));
+        assert!(formatted.contains("no role in an active run"));
+    }
+
     // ===== LIST ACTIVE RUNS TESTS =====
 
     fn sample_active_runs() -> ListActiveRunsResponse {
@@ -695,6 +1091,37 @@ mod tests {
         mock.assert_called("/api/playbooks");
     }
 
+    #[tokio::test]
+    async fn test_get_playbook_formats_hints_and_outline() {
+        let mock = MockIpcClient::new();
+        mock.when_called(
+            "/api/playbooks/doc-editor",
+            GetPlaybookResponse {
+                playbook: sample_playbooks().playbooks.remove(0),
+                protocol_outline: vec![
+                    ProtocolOutlineSection {
+                        title: "Drafting".to_string(),
+                        role: Some("writer".to_string()),
+                        summary: Some("Write the first draft".to_string()),
+                    },
+                    ProtocolOutlineSection {
+                        title: "Handoffs".to_string(),
+                        role: None,
+                        summary: None,
+                    },
+                ],
+            },
+        );
+
+        let response = get_playbook(&mock, "doc-editor").await.unwrap();
+        let formatted = format_playbook(&response);
+
+        assert!(formatted.starts_with("## Document Editor (doc-editor)"));
+        assert!(formatted.contains("  - Expects document: yes\n  - Expects goal: no"));
+        assert!(formatted.contains("  - Suggested paths: docs/"));
+        assert!(formatted.contains("  - Drafting [writer]: Write the first draft\n  - Handoffs\n"));
+    }
+
     #[test]
     fn test_format_playbooks() {
         let response = sample_playbooks();
@@ -0,0 +1,76 @@
+//! UTF-8–safe string shortening for formatters.
+//!
+//! Byte slicing (`&text[..50]`) panics when the cut lands inside a multi-byte
+//! character, which is routine for emoji and CJK documents. These helpers cut on
+//! grapheme cluster boundaries instead, so combined emoji and accented
+//! characters are never split either.
+
+use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Appended to text that was shortened by [`truncate`].
+pub const ELLIPSIS: &str = "...";
+
+/// The first `max` graphemes of `text`.
+pub fn prefix(text: &str, max: usize) -> &str {
+    match text.grapheme_indices(true).nth(max) {
+        Some((end, _)) => &text[..end],
+        None => text,
+    }
+}
+
+/// `text` limited to `max` graphemes, with [`ELLIPSIS`] appended if anything was cut.
+pub fn truncate(text: &str, max: usize) -> Cow<'_, str> {
+    let cut = prefix(text, max);
+    if cut.len() == text.len() {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(format!("{}{}", cut, ELLIPSIS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_ascii_is_borrowed_unchanged() {
+        let output = truncate("hello", 50);
+        assert_eq!(output, "hello");
+        assert!(matches!(output, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_truncates_ascii_with_ellipsis() {
+        assert_eq!(truncate("abcdefghij", 4), "abcd...");
+        assert_eq!(truncate("abcd", 4), "abcd");
+    }
+
+    #[test]
+    fn test_does_not_split_emoji() {
+        // Each emoji is 4 bytes; a byte slice at 5 would panic
+        let text = "🚀🚀🚀🚀🚀";
+        assert_eq!(truncate(text, 2), "🚀🚀...");
+        assert_eq!(prefix(text, 5), text);
+    }
+
+    #[test]
+    fn test_keeps_combined_emoji_whole() {
+        // Family emoji: several code points joined by ZWJ, one grapheme
+        let text = "👨‍👩‍👧 family";
+        assert_eq!(truncate(text, 1), "👨‍👩‍👧...");
+    }
+
+    #[test]
+    fn test_handles_cjk() {
+        let text = "日本語のドキュメントです";
+        assert_eq!(truncate(text, 3), "日本語...");
+        assert_eq!(prefix(text, 100), text);
+    }
+
+    #[test]
+    fn test_prefix_of_hash() {
+        assert_eq!(prefix("abc123def456", 8), "abc123de");
+        assert_eq!(prefix("abc", 8), "abc");
+    }
+}
@@ -0,0 +1,204 @@
+//! A run's messages as threads.
+//!
+//! A message can reply to an earlier one (`reply_to_event_id`). `get_messages`
+//! puts every reply under the message it answers, so a discussion between
+//! several agents reads as threads instead of one interleaved log.
+
+use crate::ipc::messages::{MessageThreads, RunEvent, ThreadedMessage};
+use crate::tools::timestamps;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Event type of `send_message` events.
+pub const MESSAGE_EVENT: &str = "message";
+
+/// The messages among `events` in thread order, or only the thread holding
+/// message `thread`. A reply to a message that isn't there starts a thread.
+pub fn threads(
+    run_id: &str,
+    events: Vec<RunEvent>,
+    thread: Option<&str>,
+) -> Result<MessageThreads, String> {
+    let messages: Vec<RunEvent> = events
+        .into_iter()
+        .filter(|e| e.event_type == MESSAGE_EVENT)
+        .collect();
+    let index: HashMap<&str, usize> = messages
+        .iter()
+        .enumerate()
+        .map(|(i, m)| (m.id.as_str(), i))
+        .collect();
+    let parent = |i: usize| {
+        messages[i]
+            .reply_to_event_id
+            .as_deref()
+            .and_then(|id| index.get(id).copied())
+            .filter(|&p| p != i)
+    };
+    let mut replies: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..messages.len() {
+        if let Some(p) = parent(i) {
+            replies.entry(p).or_default().push(i);
+        }
+    }
+
+    // Roots first, then anything only reachable through a reply cycle
+    let mut order = Vec::with_capacity(messages.len());
+    let mut seen = HashSet::new();
+    let roots = (0..messages.len()).filter(|&i| parent(i).is_none());
+    for root in roots.chain(0..messages.len()) {
+        let mut stack = vec![(root, 0)];
+        while let Some((i, depth)) = stack.pop() {
+            if !seen.insert(i) {
+                continue;
+            }
+            order.push((root, i, depth));
+            for &reply in replies.get(&i).into_iter().flatten().rev() {
+                stack.push((reply, depth + 1));
+            }
+        }
+    }
+
+    let only = match thread {
+        Some(id) => {
+            let Some(&i) = index.get(id) else {
+                return Err(format!("No message {} in run {}", id, run_id));
+            };
+            order
+                .iter()
+                .find(|(_, m, _)| *m == i)
+                .map(|(root, _, _)| *root)
+        }
+        None => None,
+    };
+    let order: Vec<(usize, usize, usize)> = order
+        .into_iter()
+        .filter(|(root, _, _)| only.is_none_or(|only| *root == only))
+        .collect();
+    Ok(MessageThreads {
+        run_id: run_id.to_string(),
+        threads: order.iter().filter(|(_, _, depth)| *depth == 0).count(),
+        messages: order
+            .into_iter()
+            .map(|(root, i, depth)| ThreadedMessage {
+                thread_id: messages[root].id.clone(),
+                depth,
+                event: messages[i].clone(),
+            })
+            .collect(),
+    })
+}
+
+/// Formats threads as nested lists, replies under what they answer.
+pub fn format_threads(threads: &MessageThreads) -> String {
+    if threads.messages.is_empty() {
+        return format!("No messages in run {} yet.", threads.run_id);
+    }
+    let mut output = format!(
+        "## Messages in run {}\n\n{} message(s) in {} thread(s). To answer one, pass its event ID to send_message as reply_to_event_id.\n",
+        threads.run_id,
+        threads.messages.len(),
+        threads.threads
+    );
+    for message in &threads.messages {
+        if message.depth == 0 {
+            output.push('\n');
+        }
+        let indent = "  ".repeat(message.depth);
+        let event = &message.event;
+        let mut lines = event.content.lines();
+        let _ = writeln!(
+            output,
+            "{}- {}**{}** (`{}`, {}): {}",
+            indent,
+            event.priority.unwrap_or_default().marker(),
+            event.source.as_deref().unwrap_or("unknown"),
+            event.id,
+            timestamps::age(&event.created_at),
+            lines.next().unwrap_or_default()
+        );
+        for line in lines {
+            let _ = writeln!(output, "{}  {}", indent, line);
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn events() -> Vec<RunEvent> {
+        serde_json::from_value(json!([
+            {"id": "m1", "eventType": "message", "source": "strategist",
+             "content": "Which cache?", "createdAt": "2024-01-15T10:00:00Z"},
+            {"id": "m2", "eventType": "message", "source": "builder",
+             "content": "Status update", "createdAt": "2024-01-15T10:01:00Z"},
+            {"id": "s1", "eventType": "status", "source": "builder",
+             "content": "Working", "createdAt": "2024-01-15T10:01:30Z"},
+            {"id": "m3", "eventType": "message", "source": "builder",
+             "content": "Redis.\nIt is already deployed.", "replyToEventId": "m1",
+             "createdAt": "2024-01-15T10:02:00Z"},
+            {"id": "m4", "eventType": "message", "source": "reviewer",
+             "content": "Agreed", "replyToEventId": "m3", "createdAt": "2024-01-15T10:03:00Z"},
+            {"id": "m5", "eventType": "message", "source": "reviewer",
+             "content": "And TTLs?", "replyToEventId": "m1", "createdAt": "2024-01-15T10:04:00Z"},
+            {"id": "m6", "eventType": "message", "source": "human",
+             "content": "Re: lost", "replyToEventId": "gone", "createdAt": "2024-01-15T10:05:00Z"}
+        ]))
+        .unwrap()
+    }
+
+    fn shape(threads: &MessageThreads) -> Vec<(&str, &str, usize)> {
+        threads
+            .messages
+            .iter()
+            .map(|m| (m.thread_id.as_str(), m.event.id.as_str(), m.depth))
+            .collect()
+    }
+
+    #[test]
+    fn test_replies_follow_what_they_answer() {
+        let all = threads("run-1", events(), None).unwrap();
+        assert_eq!(all.threads, 3);
+        assert_eq!(
+            shape(&all),
+            vec![
+                ("m1", "m1", 0),
+                ("m1", "m3", 1),
+                ("m1", "m4", 2),
+                ("m1", "m5", 1),
+                ("m2", "m2", 0),
+                ("m6", "m6", 0)
+            ]
+        );
+
+        let one = threads("run-1", events(), Some("m4")).unwrap();
+        assert_eq!(one.threads, 1);
+        assert_eq!(one.messages.len(), 4);
+        assert_eq!(
+            threads("run-1", events(), Some("s1")).unwrap_err(),
+            "No message s1 in run run-1"
+        );
+    }
+
+    #[test]
+    fn test_reply_cycles_are_still_listed() {
+        let mut events = events();
+        events[0].reply_to_event_id = Some("m4".to_string());
+        let all = threads("run-1", events, None).unwrap();
+        assert_eq!(all.messages.len(), 6);
+    }
+
+    #[test]
+    fn test_format_nests_replies() {
+        let output = format_threads(&threads("run-1", events(), Some("m1")).unwrap());
+        assert!(output.starts_with("## Messages in run run-1\n\n4 message(s) in 1 thread(s)."));
+        assert!(output.contains("\n- **strategist** (`m1`, "));
+        assert!(output.contains("\n  - **builder** (`m3`, "));
+        assert!(
+            output.contains("): Redis.\n    It is already deployed.\n    - **reviewer** (`m4`, ")
+        );
+    }
+}
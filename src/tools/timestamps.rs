@@ -0,0 +1,127 @@
+//! Human-friendly rendering of backend timestamps.
+//!
+//! The backend sends RFC 3339 strings. Formatters show them relative to now
+//! with the original kept alongside, e.g. "12 minutes ago (2024-01-15T10:30:00Z)".
+//! Strings that don't parse are shown unchanged.
+
+use chrono::{DateTime, Utc};
+
+/// "12 minutes ago (2024-01-15T10:30:00Z)".
+pub fn format_timestamp(raw: &str) -> String {
+    format_timestamp_at(raw, Utc::now())
+}
+
+/// Short relative age for tables, e.g. "12m ago".
+pub fn age(raw: &str) -> String {
+    age_at(raw, Utc::now())
+}
+
+/// Seconds since the epoch, if `raw` is RFC 3339.
+pub fn epoch_seconds(raw: &str) -> Option<i64> {
+    parse(raw).map(|t| t.timestamp())
+}
+
+fn parse(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn format_timestamp_at(raw: &str, now: DateTime<Utc>) -> String {
+    let Some(time) = parse(raw) else {
+        return raw.to_string();
+    };
+    let seconds = (now - time).num_seconds();
+    let (amount, unit) = largest_unit(seconds.abs());
+    let relative = match (amount, seconds < 0) {
+        (0, _) => "just now".to_string(),
+        (1, false) => format!("1 {} ago", unit),
+        (n, false) => format!("{} {}s ago", n, unit),
+        (1, true) => format!("in 1 {}", unit),
+        (n, true) => format!("in {} {}s", n, unit),
+    };
+    format!("{} ({})", relative, raw)
+}
+
+fn age_at(raw: &str, now: DateTime<Utc>) -> String {
+    let Some(time) = parse(raw) else {
+        return raw.to_string();
+    };
+    let seconds = (now - time).num_seconds();
+    let (amount, unit) = largest_unit(seconds.abs());
+    if amount == 0 {
+        return "now".to_string();
+    }
+    let short = &unit[..1];
+    if seconds < 0 {
+        format!("in {}{}", amount, short)
+    } else {
+        format!("{}{} ago", amount, short)
+    }
+}
+
+/// Largest whole unit for a duration; 0 means "under a minute".
+fn largest_unit(seconds: i64) -> (i64, &'static str) {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    if seconds < MINUTE {
+        (0, "minute")
+    } else if seconds < HOUR {
+        (seconds / MINUTE, "minute")
+    } else if seconds < DAY {
+        (seconds / HOUR, "hour")
+    } else {
+        (seconds / DAY, "day")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        parse("2024-01-15T12:00:00Z").unwrap()
+    }
+
+    #[test]
+    fn test_relative_minutes_hours_days() {
+        assert_eq!(
+            format_timestamp_at("2024-01-15T11:48:00Z", now()),
+            "12 minutes ago (2024-01-15T11:48:00Z)"
+        );
+        assert_eq!(
+            format_timestamp_at("2024-01-15T11:00:00Z", now()),
+            "1 hour ago (2024-01-15T11:00:00Z)"
+        );
+        assert_eq!(
+            format_timestamp_at("2024-01-12T12:00:00+00:00", now()),
+            "3 days ago (2024-01-12T12:00:00+00:00)"
+        );
+    }
+
+    #[test]
+    fn test_recent_and_future() {
+        assert_eq!(
+            format_timestamp_at("2024-01-15T11:59:30Z", now()),
+            "just now (2024-01-15T11:59:30Z)"
+        );
+        assert_eq!(
+            format_timestamp_at("2024-01-15T14:00:00Z", now()),
+            "in 2 hours (2024-01-15T14:00:00Z)"
+        );
+    }
+
+    #[test]
+    fn test_unparsable_is_unchanged() {
+        assert_eq!(format_timestamp_at("yesterday", now()), "yesterday");
+        assert_eq!(age_at("", now()), "");
+    }
+
+    #[test]
+    fn test_short_age() {
+        assert_eq!(age_at("2024-01-15T11:48:00Z", now()), "12m ago");
+        assert_eq!(age_at("2024-01-13T12:00:00Z", now()), "2d ago");
+        assert_eq!(age_at("2024-01-15T12:00:10Z", now()), "now");
+    }
+}
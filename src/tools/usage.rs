@@ -0,0 +1,296 @@
+//! Token and tool-call usage of a run's agents.
+//!
+//! Agents report each turn's usage with `report_usage`. The deltas are summed
+//! locally per run, role, and model and sent to the backend at most once per
+//! `usage_flush_secs`, so reporting every turn costs no round trip. The
+//! backend keeps the run totals, priced per model, that `get_run_usage` shows.
+
+use crate::ipc::messages::{
+    GetRunUsageRequest, GetRunUsageResponse, ReportUsageRequest, ReportUsageResponse,
+};
+use crate::ipc::traits::IpcClient;
+use crate::types::errors::IpcError;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Usage reported but not yet sent.
+#[derive(Default)]
+pub struct UsageAccumulator {
+    pending: Mutex<Pending>,
+}
+
+#[derive(Default)]
+struct Pending {
+    /// When the oldest unsent delta was added
+    since: Option<Instant>,
+    /// (run_id, role, model) -> summed deltas
+    totals: HashMap<(String, String, String), ReportUsageRequest>,
+}
+
+impl UsageAccumulator {
+    /// Add a turn's usage reported by `role`.
+    pub fn add(&self, delta: &ReportUsageRequest, role: &str, now: Instant) {
+        let mut pending = self.pending.lock().unwrap();
+        pending.since.get_or_insert(now);
+        let key = (delta.run_id.clone(), role.to_string(), delta.model.clone());
+        let total = pending
+            .totals
+            .entry(key)
+            .or_insert_with(|| ReportUsageRequest {
+                input_tokens: 0,
+                output_tokens: 0,
+                tool_calls: 0,
+                source: Some(role.to_string()),
+                ..delta.clone()
+            });
+        total.input_tokens += delta.input_tokens;
+        total.output_tokens += delta.output_tokens;
+        total.tool_calls += delta.tool_calls;
+    }
+
+    /// Everything pending, if the oldest delta is at least `interval` old.
+    pub fn take_due(&self, interval: Duration, now: Instant) -> Vec<ReportUsageRequest> {
+        let mut pending = self.pending.lock().unwrap();
+        match pending.since {
+            Some(since) if now.duration_since(since) >= interval => {
+                pending.since = None;
+                pending.totals.drain().map(|(_, total)| total).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Everything pending for `run_id`, or for every run if `None`, however old.
+    pub fn take(&self, run_id: Option<&str>) -> Vec<ReportUsageRequest> {
+        let mut pending = self.pending.lock().unwrap();
+        let keys: Vec<_> = pending
+            .totals
+            .keys()
+            .filter(|(run, _, _)| run_id.is_none_or(|id| id == run))
+            .cloned()
+            .collect();
+        let taken = keys
+            .iter()
+            .filter_map(|key| pending.totals.remove(key))
+            .collect();
+        if pending.totals.is_empty() {
+            pending.since = None;
+        }
+        taken
+    }
+
+    /// Put back totals that could not be sent, to go with the next flush.
+    pub fn restore(&self, totals: Vec<ReportUsageRequest>, now: Instant) {
+        for total in totals {
+            let role = total.source.clone().unwrap_or_default();
+            self.add(&total, &role, now);
+        }
+    }
+
+    /// Totals waiting to be sent, one request each.
+    pub fn pending_reports(&self) -> usize {
+        self.pending.lock().unwrap().totals.len()
+    }
+
+    /// Tokens reported but not yet sent.
+    pub fn pending_tokens(&self) -> i64 {
+        self.pending
+            .lock()
+            .unwrap()
+            .totals
+            .values()
+            .map(|total| total.input_tokens + total.output_tokens)
+            .sum()
+    }
+}
+
+/// Send `totals`, one request each. Totals that were not sent are put back
+/// into `accumulator` and the first failure is returned.
+pub async fn flush<C: IpcClient>(
+    client: &C,
+    accumulator: &UsageAccumulator,
+    totals: Vec<ReportUsageRequest>,
+) -> Result<(), IpcError> {
+    let mut unsent = Vec::new();
+    let mut failure = None;
+    for total in totals {
+        if failure.is_some() {
+            unsent.push(total);
+            continue;
+        }
+        let endpoint = format!("/api/runs/{}/usage/report", total.run_id);
+        match client
+            .request::<_, ReportUsageResponse>(&endpoint, &total)
+            .await
+        {
+            Ok(response) if response.success => {}
+            Ok(_) => {
+                failure = Some(IpcError::RequestFailed(
+                    "Backend reported failure recording usage".to_string(),
+                ));
+                unsent.push(total);
+            }
+            Err(e) => {
+                failure = Some(e);
+                unsent.push(total);
+            }
+        }
+    }
+    accumulator.restore(unsent, Instant::now());
+    failure.map_or(Ok(()), Err)
+}
+
+/// Fetches the usage totals of every role in `run_id`.
+pub async fn get_run_usage<C: IpcClient>(
+    client: &C,
+    run_id: &str,
+) -> Result<GetRunUsageResponse, IpcError> {
+    let request = GetRunUsageRequest {
+        run_id: run_id.to_string(),
+        format: None,
+        continuation: None,
+    };
+
+    let endpoint = format!("/api/runs/{}/usage", run_id);
+    client.request(&endpoint, &request).await
+}
+
+/// Formats run usage as a table per role and model, with a total.
+pub fn format_run_usage(response: &GetRunUsageResponse) -> String {
+    if response.usage.is_empty() {
+        return format!("No usage has been reported in run {} yet.", response.run_id);
+    }
+
+    let mut output = format!(
+        "## Usage for run {}\n\n| Role | Model | Input tokens | Output tokens | Tool calls | Cost |\n|------|-------|-------------:|--------------:|-----------:|-----:|\n",
+        response.run_id
+    );
+    for row in &response.usage {
+        let _ = writeln!(
+            output,
+            "| {} | {} | {} | {} | {} | {} |",
+            row.role,
+            row.model,
+            row.input_tokens,
+            row.output_tokens,
+            row.tool_calls,
+            format_cost(row.cost_usd)
+        );
+    }
+    let sum = |field: fn(&_) -> i64| response.usage.iter().map(field).sum::<i64>();
+    // Unknown if any row is unpriced, rather than an understated total
+    let cost = response
+        .usage
+        .iter()
+        .map(|row| row.cost_usd)
+        .sum::<Option<f64>>();
+    let _ = write!(
+        output,
+        "| **Total** | | {} | {} | {} | {} |",
+        sum(|row| row.input_tokens),
+        sum(|row| row.output_tokens),
+        sum(|row| row.tool_calls),
+        format_cost(cost)
+    );
+    output
+}
+
+/// Cost in dollars, `-` if unknown.
+pub fn format_cost(cost_usd: Option<f64>) -> String {
+    cost_usd.map_or("-".to_string(), |cost| format!("${:.2}", cost))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::UsageTotals;
+    use crate::ipc::mock::MockIpcClient;
+
+    fn delta(model: &str, input_tokens: i64) -> ReportUsageRequest {
+        ReportUsageRequest {
+            run_id: "run-1".to_string(),
+            model: model.to_string(),
+            input_tokens,
+            output_tokens: 10,
+            tool_calls: 1,
+            source: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deltas_are_summed_and_flushed_when_due() {
+        let accumulator = UsageAccumulator::default();
+        let start = Instant::now();
+        let interval = Duration::from_secs(60);
+        accumulator.add(&delta("sonnet", 100), "builder", start);
+        accumulator.add(
+            &delta("sonnet", 50),
+            "builder",
+            start + Duration::from_secs(5),
+        );
+        accumulator.add(
+            &delta("haiku", 7),
+            "builder",
+            start + Duration::from_secs(5),
+        );
+        assert_eq!(accumulator.pending_tokens(), 187);
+        assert!(accumulator
+            .take_due(interval, start + Duration::from_secs(30))
+            .is_empty());
+
+        let mut due = accumulator.take_due(interval, start + interval);
+        due.sort_by(|a, b| a.model.cmp(&b.model));
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[1].input_tokens, 150);
+        assert_eq!(due[1].output_tokens, 20);
+        assert_eq!(due[1].tool_calls, 2);
+        assert_eq!(due[1].source.as_deref(), Some("builder"));
+
+        // A failed flush keeps the totals for the next one
+        let mock = MockIpcClient::new();
+        mock.set_disconnected(true);
+        assert!(flush(&mock, &accumulator, due).await.is_err());
+        assert_eq!(accumulator.pending_tokens(), 187);
+
+        mock.set_disconnected(false);
+        mock.when_called(
+            "/api/runs/run-1/usage/report",
+            ReportUsageResponse { success: true },
+        );
+        flush(&mock, &accumulator, accumulator.take(Some("run-1")))
+            .await
+            .unwrap();
+        assert_eq!(accumulator.pending_tokens(), 0);
+        assert_eq!(mock.requests_to("/api/runs/run-1/usage/report").len(), 2);
+    }
+
+    #[test]
+    fn test_format_run_usage_totals_rows() {
+        let response = GetRunUsageResponse {
+            run_id: "run-1".to_string(),
+            usage: vec![
+                UsageTotals {
+                    role: "builder".to_string(),
+                    model: "sonnet".to_string(),
+                    input_tokens: 1000,
+                    output_tokens: 200,
+                    tool_calls: 12,
+                    cost_usd: Some(0.25),
+                },
+                UsageTotals {
+                    role: "strategist".to_string(),
+                    model: "local".to_string(),
+                    input_tokens: 500,
+                    output_tokens: 100,
+                    tool_calls: 3,
+                    cost_usd: None,
+                },
+            ],
+        };
+        let output = format_run_usage(&response);
+        assert!(output.contains("| builder | sonnet | 1000 | 200 | 12 | $0.25 |"));
+        assert!(output.ends_with("| **Total** | | 1500 | 300 | 15 | - |"));
+    }
+}
@@ -0,0 +1,1090 @@
+//! Client-side checks on tool arguments.
+//!
+//! Malformed IDs and ranges are rejected before the IPC round trip with an
+//! error that says how to fix the call, instead of an opaque backend failure.
+
+use crate::config::PaginationConfig;
+use crate::ipc::messages::{
+    AckMessageRequest, AckNotificationRequest, AwaitInputRequest,
+    DocArtifactAcceptSuggestionRequest, DocArtifactAddCommentRequest, DocArtifactChangelogRequest,
+    DocArtifactCommitRequest, DocArtifactCreateRequest, DocArtifactEditRequest,
+    DocArtifactFeedbackSummaryRequest, DocArtifactImportReviewRequest,
+    DocArtifactListCommentsRequest, DocArtifactListRequest, DocArtifactListSuggestionsRequest,
+    DocArtifactReadAllRequest, DocArtifactReadRequest, DocArtifactRejectSuggestionRequest,
+    DocArtifactRenderRequest, DocArtifactResolveCommentRequest, DocArtifactReviewContextRequest,
+    DocArtifactSearchAllRequest, DocArtifactSearchRequest, DocArtifactSetTagsRequest,
+    DocArtifactSuggestEditRequest, DumpEventsRequest, ExportRunRequest,
+    GetAgentCapabilitiesRequest, GetArtifactReadersRequest, GetHandoffsRequest,
+    GetImpedimentResolutionRequest, GetImpedimentsRequest, GetLatestSummaryRequest,
+    GetMessageDeliveriesRequest, GetMessagesRequest, GetNotificationsRequest, GetPlaybookRequest,
+    GetProtocolRequest, GetRunConfigRequest, GetRunContextRequest, GetRunStatusRequest,
+    GetRunUsageRequest, HandoffRequest, ListCompletedTasksRequest, ListImpedimentTemplatesRequest,
+    PostSummaryRequest, ProtocolStepCompleteRequest, ProtocolStepsListRequest, ReportFormat,
+    ReportImpedimentRequest, ReportStatusRequest, ReportUsageRequest, RequestEndRunRequest,
+    RequestInputRequest, ResolveImpedimentRequest, RespondToEndRequestRequest, ResponseFormat,
+    RestoreBackupRequest, SearchRunsRequest, SendMessageRequest, SetRunContextRequest,
+    SweepStaleSuggestionsRequest, SwitchRoleRequest, TaskCompleteRequest, RESPONSE_FIELD_TYPES,
+};
+use crate::tools::{git, summaries};
+use serde_json::Value;
+
+/// Forms the UI can render: at least one field, unique ids, known types,
+/// and options for every choice field.
+pub fn response_format(form: &ResponseFormat) -> Result<(), String> {
+    if form.fields.is_empty() {
+        return Err("response_format needs at least one field".to_string());
+    }
+    let mut ids = std::collections::HashSet::new();
+    for field in &form.fields {
+        not_blank("response_format field id", &field.id)?;
+        if !ids.insert(field.id.as_str()) {
+            return Err(format!(
+                "response_format field id \"{}\" is used more than once",
+                field.id
+            ));
+        }
+        not_blank(&format!("label of field \"{}\"", field.id), &field.label)?;
+        if !RESPONSE_FIELD_TYPES.contains(&field.field_type.as_str()) {
+            return Err(format!(
+                "field \"{}\" has type \"{}\"; use one of {}",
+                field.id,
+                field.field_type,
+                RESPONSE_FIELD_TYPES.join(", ")
+            ));
+        }
+        let needs_options = matches!(field.field_type.as_str(), "radio" | "select");
+        if needs_options && field.options.as_ref().is_none_or(Vec::is_empty) {
+            return Err(format!(
+                "{} field \"{}\" needs options to choose from",
+                field.field_type, field.id
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Problems with `answer` as a response to `form`, one per field. The UI
+/// sends an object keyed by field id; radio and select answers are one of
+/// the option values, checkbox answers a list of them (or a bool without
+/// options), and text answers strings.
+pub fn form_answer(form: &ResponseFormat, answer: &Value) -> Result<(), String> {
+    if form.fields.is_empty() {
+        return Ok(());
+    }
+    let Value::Object(values) = answer else {
+        return Err(format!(
+            "response must be an object keyed by field id ({})",
+            form.fields
+                .iter()
+                .map(|f| f.id.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    };
+    let mut problems = Vec::new();
+    for id in values.keys() {
+        if !form.fields.iter().any(|f| &f.id == id) {
+            problems.push(format!("\"{}\" is not a field of the form", id));
+        }
+    }
+    for field in &form.fields {
+        let options: Vec<&str> = field
+            .options
+            .iter()
+            .flatten()
+            .map(|o| o.value.as_str())
+            .collect();
+        let is_option = |value: &Value| value.as_str().is_some_and(|v| options.contains(&v));
+        let expected = match field.field_type.as_str() {
+            "radio" | "select" => format!("one of {}", options.join(", ")),
+            "checkbox" if options.is_empty() => "true or false".to_string(),
+            "checkbox" => format!("a list of {}", options.join(", ")),
+            _ => "a string".to_string(),
+        };
+        let fits = match (values.get(&field.id), field.field_type.as_str()) {
+            (None | Some(Value::Null), _) => field.required != Some(true),
+            (Some(value), "radio" | "select") => is_option(value),
+            (Some(value), "checkbox") if options.is_empty() => value.is_boolean(),
+            (Some(Value::Array(chosen)), "checkbox") => chosen.iter().all(is_option),
+            (Some(_), "checkbox") => false,
+            (Some(value), _) => value.is_string(),
+        };
+        if !fits {
+            let got = values
+                .get(&field.id)
+                .filter(|v| !v.is_null())
+                .map_or("nothing".to_string(), Value::to_string);
+            problems.push(format!(
+                "field \"{}\" ({}) must be {}, got {}",
+                field.id, field.label, expected, got
+            ));
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+/// Tool arguments that can be checked before sending.
+pub trait Validate {
+    fn validate(&self, pagination: &PaginationConfig) -> Result<(), String>;
+}
+
+/// Run IDs are UUIDs (8-4-4-4-12 hex digits).
+pub fn run_id(id: &str) -> Result<(), String> {
+    let is_uuid = id.len() == 36
+        && id.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    if is_uuid {
+        Ok(())
+    } else {
+        Err(format!(
+            "run_id '{}' is not a UUID (e.g., 123e4567-e89b-12d3-a456-426614174000). \
+            Use the run ID from your protocol or list_active_runs.",
+            id
+        ))
+    }
+}
+
+/// IDs other than run IDs are opaque but must not be blank.
+pub fn not_blank(name: &str, value: &str) -> Result<(), String> {
+    if value.trim().is_empty() {
+        Err(format!("{} must not be empty", name))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn non_negative(name: &str, value: Option<i64>) -> Result<(), String> {
+    match value {
+        Some(v) if v < 0 => Err(format!("{} must be 0 or greater, got {}", name, v)),
+        _ => Ok(()),
+    }
+}
+
+pub fn in_range(name: &str, value: Option<i64>, min: i64, max: i64) -> Result<(), String> {
+    match value {
+        Some(v) if v < min || v > max => Err(format!(
+            "{} must be between {} and {}, got {}",
+            name, min, max, v
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// A list tool's `report_path` is only used with `report`, and stays inside
+/// the project directory.
+fn report_path(report: Option<ReportFormat>, path: Option<&str>) -> Result<(), String> {
+    match (report, path) {
+        (None, Some(_)) => Err("report_path requires report (csv or jsonl)".to_string()),
+        (_, Some(path)) => {
+            not_blank("report_path", path)?;
+            git::project_relative(path)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A tag: non-blank, at most 50 characters, and without spaces.
+pub fn tag(tag: &str) -> Result<(), String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("tags must not be blank".to_string());
+    }
+    if tag.chars().any(char::is_whitespace) {
+        return Err(format!(
+            "tag \"{}\" contains spaces; use dashes, e.g. \"{}\"",
+            tag,
+            tag.split_whitespace().collect::<Vec<_>>().join("-")
+        ));
+    }
+    if tag.chars().count() > 50 {
+        return Err(format!("tag \"{}\" is longer than 50 characters", tag));
+    }
+    Ok(())
+}
+
+/// `start <= end` when both are given.
+pub fn ordered(
+    start_name: &str,
+    start: Option<i64>,
+    end_name: &str,
+    end: Option<i64>,
+) -> Result<(), String> {
+    match (start, end) {
+        (Some(s), Some(e)) if s > e => Err(format!(
+            "{} ({}) must not be greater than {} ({})",
+            start_name, s, end_name, e
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// A date bound, either `YYYY-MM-DD` or RFC 3339, as seconds since the epoch.
+pub fn date(name: &str, value: Option<&str>) -> Result<Option<i64>, String> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.timestamp())
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|d| d.and_time(chrono::NaiveTime::MIN).and_utc().timestamp())
+        })
+        .map(Some)
+        .map_err(|_| {
+            format!(
+                "{} must be a date like 2024-01-15 or 2024-01-15T10:30:00Z, got '{}'",
+                name, value
+            )
+        })
+}
+
+/// Requests whose only ID is the run.
+macro_rules! validate_run_id {
+    ($($request:ty),* $(,)?) => {
+        $(impl Validate for $request {
+            fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+                run_id(&self.run_id)
+            }
+        })*
+    };
+}
+
+validate_run_id!(
+    GetProtocolRequest,
+    GetAgentCapabilitiesRequest,
+    GetRunStatusRequest,
+    GetNotificationsRequest,
+    ProtocolStepsListRequest,
+    GetRunUsageRequest,
+    GetLatestSummaryRequest,
+    GetRunContextRequest,
+    GetRunConfigRequest,
+    GetMessagesRequest,
+    ListCompletedTasksRequest,
+    ListImpedimentTemplatesRequest,
+    GetImpedimentsRequest,
+    RequestInputRequest,
+    GetHandoffsRequest,
+    RequestEndRunRequest,
+    DocArtifactListRequest,
+    DocArtifactCreateRequest,
+);
+
+impl Validate for ReportStatusRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        let Some(progress) = &self.progress else {
+            return Ok(());
+        };
+        if progress.percent_complete.is_some_and(|p| p > 100) {
+            return Err("progress.percent_complete must be between 0 and 100".to_string());
+        }
+        for (field, value) in [
+            ("progress.artifact_id", &progress.artifact_id),
+            ("progress.section", &progress.section),
+            ("progress.blocking_on", &progress.blocking_on),
+        ] {
+            if let Some(value) = value {
+                not_blank(field, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for SendMessageRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        for target in self.target.iter().chain(&self.targets) {
+            not_blank("targets", target)?;
+        }
+        if let Some(reply_to) = &self.reply_to_event_id {
+            not_blank("reply_to_event_id", reply_to)?;
+        }
+        if self.broadcast == Some(true) && (self.target.is_some() || !self.targets.is_empty()) {
+            return Err(
+                "broadcast sends to every agent in the run; leave target and targets out"
+                    .to_string(),
+            );
+        }
+        if self.requires_ack == Some(true)
+            && self.broadcast != Some(true)
+            && self.target.is_none()
+            && self.targets.is_empty()
+        {
+            return Err(
+                "requires_ack needs target, targets, or broadcast; a message only in the conversation log has no one to acknowledge it"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Validate for AckMessageRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        if self.event_ids.is_empty() {
+            return Err("event_ids must list at least one message".to_string());
+        }
+        self.event_ids
+            .iter()
+            .try_for_each(|id| not_blank("event_ids entries", id))
+    }
+}
+
+impl Validate for GetMessageDeliveriesRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("event_id", &self.event_id)
+    }
+}
+
+impl Validate for TaskCompleteRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        let Some(results) = &self.results else {
+            return Ok(());
+        };
+        for id in &results.artifact_ids {
+            not_blank("results.artifact_ids", id)?;
+        }
+        for test in &results.tests {
+            not_blank("results.tests.command", &test.command)?;
+        }
+        for link in &results.links {
+            if !(link.url.starts_with("https://") || link.url.starts_with("http://")) {
+                return Err(format!(
+                    "results.links url \"{}\" must start with http:// or https://",
+                    link.url
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for DocArtifactSetTagsRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        if self.add.is_empty() && self.remove.is_empty() {
+            return Err("pass tags to add or remove".to_string());
+        }
+        self.add.iter().chain(&self.remove).try_for_each(|t| tag(t))
+    }
+}
+
+impl Validate for ReportImpedimentRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        if let Some(form) = &self.response_format {
+            response_format(form)?;
+        }
+        match (&self.impediment_type, &self.template_id) {
+            (None, None) => {
+                return Err("impediment_type is required unless template_id is given".to_string())
+            }
+            (_, Some(template_id)) => not_blank("template_id", template_id)?,
+            _ => {}
+        }
+        in_range(
+            "escalate_after_seconds",
+            self.escalate_after_seconds,
+            60,
+            604800,
+        )
+    }
+}
+
+impl Validate for ResolveImpedimentRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        non_negative("impediment_id", Some(self.impediment_id))
+    }
+}
+
+impl Validate for GetImpedimentResolutionRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        non_negative("impediment_id", Some(self.impediment_id))
+    }
+}
+
+impl Validate for AckNotificationRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        if self.notification_ids.is_empty() {
+            return Err("notification_ids must list at least one notification".to_string());
+        }
+        self.notification_ids
+            .iter()
+            .try_for_each(|id| not_blank("notification_ids entries", id))
+    }
+}
+
+impl Validate for ProtocolStepCompleteRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("step_id", &self.step_id)
+    }
+}
+
+impl Validate for ReportUsageRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("model", &self.model)?;
+        non_negative("input_tokens", Some(self.input_tokens))?;
+        non_negative("output_tokens", Some(self.output_tokens))?;
+        non_negative("tool_calls", Some(self.tool_calls))
+    }
+}
+
+impl Validate for PostSummaryRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("summary", &self.summary)?;
+        let length = self.summary.trim().chars().count();
+        if length > summaries::MAX_SUMMARY_CHARS {
+            return Err(format!(
+                "summary is {} characters; keep it under {} by dropping finished detail",
+                length,
+                summaries::MAX_SUMMARY_CHARS
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for SetRunContextRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        let fields = [
+            &self.worktree_path,
+            &self.branch,
+            &self.test_command,
+            &self.package_manager,
+        ];
+        if fields.iter().all(|f| f.is_none()) && self.env.is_empty() && self.detect != Some(true) {
+            return Err("pass the context to publish, or detect: true".to_string());
+        }
+        self.env
+            .keys()
+            .try_for_each(|key| not_blank("env names", key))
+    }
+}
+
+impl Validate for GetPlaybookRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        not_blank("playbook_id", &self.playbook_id)
+    }
+}
+
+impl Validate for AwaitInputRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("request_id", &self.request_id)?;
+        in_range("timeout_seconds", self.timeout_seconds, 1, 900)
+    }
+}
+
+impl Validate for HandoffRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        let mut ids = std::collections::HashSet::new();
+        for task in self.tasks.iter().flatten() {
+            not_blank("tasks[].id", &task.id)?;
+            not_blank("tasks[].title", &task.title)?;
+            if !ids.insert(task.id.as_str()) {
+                return Err(format!(
+                    "tasks contains id '{}' more than once; task ids must be unique within a handoff",
+                    task.id
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for SearchRunsRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        in_range("limit", self.limit, 1, 100)?;
+        let since = date("since", self.since.as_deref())?;
+        let until = date("until", self.until.as_deref())?;
+        ordered("since", since, "until", until)
+    }
+}
+
+impl Validate for SwitchRoleRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("new_role", &self.new_role)
+    }
+}
+
+impl Validate for RespondToEndRequestRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("request_id", &self.request_id)
+    }
+}
+
+impl Validate for DocArtifactReadRequest {
+    fn validate(&self, pagination: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        non_negative("offset", self.offset)?;
+        in_range("limit", self.limit, 1, pagination.read_max_limit.max(1))
+    }
+}
+
+impl Validate for GetArtifactReadersRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)
+    }
+}
+
+impl Validate for DocArtifactReviewContextRequest {
+    fn validate(&self, pagination: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        non_negative("offset", self.offset)?;
+        in_range("limit", self.limit, 1, pagination.read_max_limit.max(1))
+    }
+}
+
+impl Validate for DocArtifactFeedbackSummaryRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)
+    }
+}
+
+impl Validate for DocArtifactEditRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        non_negative("start_offset", self.start_offset)?;
+        non_negative("end_offset", self.end_offset)?;
+        non_negative("insert_offset", self.insert_offset)?;
+        if let Some(reason) = &self.edit_reason {
+            not_blank("edit_reason.summary", &reason.summary)?;
+            reason
+                .related_comment_ids
+                .iter()
+                .try_for_each(|id| not_blank("edit_reason.related_comment_ids entries", id))?;
+        }
+        ordered(
+            "start_offset",
+            self.start_offset,
+            "end_offset",
+            self.end_offset,
+        )
+    }
+}
+
+impl Validate for DocArtifactChangelogRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)
+    }
+}
+
+impl Validate for DocArtifactSearchRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        non_negative("context_lines", self.context_lines)?;
+        in_range("max_results", self.max_results, 1, i64::MAX)
+    }
+}
+
+impl Validate for DocArtifactSearchAllRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("query", &self.query)?;
+        non_negative("context_lines", self.context_lines)?;
+        in_range("max_results", self.max_results, 1, i64::MAX)
+    }
+}
+
+impl Validate for DocArtifactReadAllRequest {
+    fn validate(&self, pagination: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        in_range("limit", self.limit, 1, pagination.read_max_limit.max(1))
+    }
+}
+
+impl Validate for RestoreBackupRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)
+    }
+}
+
+impl Validate for DocArtifactCommitRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        match &self.path {
+            Some(path) => git::project_relative(path),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Validate for DocArtifactRenderRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        match &self.output_path {
+            Some(path) => git::project_relative(path),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Validate for DocArtifactImportReviewRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("review_path", &self.review_path)?;
+        git::project_relative(&self.review_path)?;
+        match &self.artifact_id {
+            Some(id) => not_blank("artifact_id", id),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Validate for DumpEventsRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        match &self.output_file {
+            Some(file) => {
+                not_blank("output_file", file)?;
+                git::project_relative(file)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Validate for ExportRunRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        match &self.output_dir {
+            Some(dir) => {
+                not_blank("output_dir", dir)?;
+                git::project_relative(dir)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Validate for DocArtifactAddCommentRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        non_negative("selection_start", Some(self.selection_start))?;
+        self.mentions
+            .iter()
+            .flatten()
+            .try_for_each(|role| not_blank("mentions entries", role))?;
+        ordered(
+            "selection_start",
+            Some(self.selection_start),
+            "selection_end",
+            Some(self.selection_end),
+        )
+    }
+}
+
+impl Validate for DocArtifactResolveCommentRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        not_blank("comment_id", &self.comment_id)
+    }
+}
+
+impl Validate for DocArtifactListCommentsRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        in_range("line_start", self.line_start, 1, i64::MAX)?;
+        in_range("line_end", self.line_end, 1, i64::MAX)?;
+        ordered("line_start", self.line_start, "line_end", self.line_end)?;
+        if let Some(role) = &self.mentioning {
+            not_blank("mentioning", role)?;
+        }
+        if let Some(role) = &self.author {
+            not_blank("author", role)?;
+        }
+        date("created_after", self.created_after.as_deref())?;
+        non_negative("offset", self.offset)?;
+        in_range("limit", self.limit, 1, i64::MAX)?;
+        report_path(self.report, self.report_path.as_deref())
+    }
+}
+
+impl Validate for DocArtifactSuggestEditRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        not_blank("comment_id", &self.comment_id)?;
+        non_negative("start_offset", self.start_offset)?;
+        non_negative("end_offset", self.end_offset)?;
+        ordered(
+            "start_offset",
+            self.start_offset,
+            "end_offset",
+            self.end_offset,
+        )
+    }
+}
+
+impl Validate for DocArtifactAcceptSuggestionRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        not_blank("suggestion_id", &self.suggestion_id)
+    }
+}
+
+impl Validate for DocArtifactRejectSuggestionRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        not_blank("suggestion_id", &self.suggestion_id)
+    }
+}
+
+impl Validate for SweepStaleSuggestionsRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        match &self.artifact_id {
+            Some(artifact_id) => not_blank("artifact_id", artifact_id),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Validate for DocArtifactListSuggestionsRequest {
+    fn validate(&self, _: &PaginationConfig) -> Result<(), String> {
+        run_id(&self.run_id)?;
+        not_blank("artifact_id", &self.artifact_id)?;
+        non_negative("offset", self.offset)?;
+        in_range("limit", self.limit, 1, i64::MAX)?;
+        report_path(self.report, self.report_path.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::{CommentType, HandoffTask};
+    use serde_json::json;
+
+    const RUN_ID: &str = "123e4567-e89b-12d3-a456-426614174000";
+
+    #[test]
+    fn test_run_id_must_be_uuid() {
+        assert!(run_id(RUN_ID).is_ok());
+        assert!(run_id(&RUN_ID.to_uppercase()).is_ok());
+        assert!(run_id("run-123").unwrap_err().contains("not a UUID"));
+        assert!(run_id("123e4567e89b12d3a456426614174000").is_err());
+        assert!(run_id("123e4567-e89b-12d3-a456-42661417400g").is_err());
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert!(non_negative("offset", Some(0)).is_ok());
+        assert!(non_negative("offset", None).is_ok());
+        assert_eq!(
+            non_negative("offset", Some(-1)).unwrap_err(),
+            "offset must be 0 or greater, got -1"
+        );
+        assert!(in_range("limit", Some(2001), 1, 2000).is_err());
+        assert!(ordered("a", Some(5), "b", Some(5)).is_ok());
+        assert!(ordered("a", Some(6), "b", None).is_ok());
+        assert!(ordered("a", Some(6), "b", Some(5)).is_err());
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(date("since", None), Ok(None));
+        assert_eq!(date("since", Some("1970-01-02")), Ok(Some(86400)));
+        assert_eq!(date("since", Some("1970-01-01T00:01:00Z")), Ok(Some(60)));
+        assert!(date("since", Some("last week"))
+            .unwrap_err()
+            .contains("got 'last week'"));
+    }
+
+    #[test]
+    fn test_read_request_limit_uses_configured_max() {
+        let request = |limit| DocArtifactReadRequest {
+            run_id: RUN_ID.to_string(),
+            artifact_id: "art-1".to_string(),
+            offset: Some(0),
+            limit: Some(limit),
+            include_comments: None,
+            render_mode: None,
+            source: None,
+            format: None,
+            continuation: None,
+        };
+        let pagination = PaginationConfig::default();
+        assert!(request(2000).validate(&pagination).is_ok());
+        assert!(request(2001).validate(&pagination).is_err());
+        assert!(request(0).validate(&pagination).is_err());
+    }
+
+    #[test]
+    fn test_tags() {
+        assert!(tag("meeting-notes").is_ok());
+        assert!(tag("  ").is_err());
+        assert_eq!(
+            tag("meeting notes").unwrap_err(),
+            "tag \"meeting notes\" contains spaces; use dashes, e.g. \"meeting-notes\""
+        );
+        assert!(tag(&"x".repeat(51)).is_err());
+    }
+
+    #[test]
+    fn test_response_format() {
+        let form = ResponseFormat::builder()
+            .radio(
+                "db",
+                "Database",
+                &[("redis", "Redis"), ("sqlite", "SQLite")],
+            )
+            .required()
+            .textarea("notes", "Notes")
+            .description("Anything else")
+            .build();
+        assert!(response_format(&form).is_ok());
+        assert_eq!(form.fields[0].required, Some(true));
+        assert_eq!(form.fields[1].description.as_deref(), Some("Anything else"));
+
+        let invalid = |form: ResponseFormat| response_format(&form).unwrap_err();
+        assert_eq!(
+            invalid(ResponseFormat::builder().build()),
+            "response_format needs at least one field"
+        );
+        assert_eq!(
+            invalid(
+                ResponseFormat::builder()
+                    .text("db", "Database")
+                    .text("db", "Again")
+                    .build()
+            ),
+            "response_format field id \"db\" is used more than once"
+        );
+        assert_eq!(
+            invalid(
+                ResponseFormat::builder()
+                    .select("db", "Database", &[])
+                    .build()
+            ),
+            "select field \"db\" needs options to choose from"
+        );
+        let mut form = ResponseFormat::builder().text("db", "Database").build();
+        form.fields[0].field_type = "dropdown".to_string();
+        assert!(invalid(form).starts_with("field \"db\" has type \"dropdown\""));
+    }
+
+    #[test]
+    fn test_form_answer() {
+        let form = ResponseFormat::builder()
+            .radio(
+                "db",
+                "Database",
+                &[("redis", "Redis"), ("sqlite", "SQLite")],
+            )
+            .required()
+            .checkbox("envs", "Environments", &[("dev", "Dev"), ("prod", "Prod")])
+            .checkbox("migrate", "Migrate now", &[])
+            .textarea("notes", "Notes")
+            .build();
+        let answer = |value: Value| form_answer(&form, &value);
+        assert!(answer(json!({"db": "sqlite", "envs": ["dev"], "migrate": true})).is_ok());
+        assert!(answer(json!({"db": "redis", "notes": null})).is_ok());
+        assert_eq!(
+            answer(json!("sqlite")).unwrap_err(),
+            "response must be an object keyed by field id (db, envs, migrate, notes)"
+        );
+        assert_eq!(
+            answer(json!({"envs": ["dev", "staging"], "notes": 3, "extra": 1})).unwrap_err(),
+            "\"extra\" is not a field of the form; \
+            field \"db\" (Database) must be one of redis, sqlite, got nothing; \
+            field \"envs\" (Environments) must be a list of dev, prod, got [\"dev\",\"staging\"]; \
+            field \"notes\" (Notes) must be a string, got 3"
+        );
+        assert!(answer(json!({"db": "mysql"}))
+            .unwrap_err()
+            .ends_with("got \"mysql\""));
+    }
+
+    #[test]
+    fn test_summary_length() {
+        let request = |summary: String| PostSummaryRequest {
+            run_id: RUN_ID.to_string(),
+            summary,
+            current_artifact: None,
+            source: None,
+        };
+        let pagination = PaginationConfig::default();
+        assert!(request("x".repeat(4000)).validate(&pagination).is_ok());
+        assert!(request(" \n".to_string()).validate(&pagination).is_err());
+        assert!(request("x".repeat(4001))
+            .validate(&pagination)
+            .unwrap_err()
+            .starts_with("summary is 4001 characters"));
+    }
+
+    #[test]
+    fn test_task_results() {
+        let request = |results: serde_json::Value| -> TaskCompleteRequest {
+            serde_json::from_value(serde_json::json!({
+                "runId": RUN_ID,
+                "taskDescription": "Implemented parser",
+                "results": results
+            }))
+            .unwrap()
+        };
+        let pagination = PaginationConfig::default();
+        assert!(request(serde_json::json!({
+            "artifactIds": ["spec"],
+            "tests": [{"command": "cargo test", "passed": true}],
+            "links": [{"url": "https://github.com/o/r/pull/12"}]
+        }))
+        .validate(&pagination)
+        .is_ok());
+        assert!(request(serde_json::json!({"artifactIds": [" "]}))
+            .validate(&pagination)
+            .is_err());
+        assert_eq!(
+            request(serde_json::json!({"links": [{"url": "PR 12"}]}))
+                .validate(&pagination)
+                .unwrap_err(),
+            "results.links url \"PR 12\" must start with http:// or https://"
+        );
+    }
+
+    #[test]
+    fn test_comment_selection_must_be_ordered() {
+        let request = DocArtifactAddCommentRequest {
+            run_id: RUN_ID.to_string(),
+            artifact_id: "art-1".to_string(),
+            comment_type: CommentType::Issue,
+            selection_start: 20,
+            selection_end: 10,
+            content: "typo".to_string(),
+            suggested_text: None,
+            author: None,
+            parent_comment_id: None,
+            mentions: None,
+            format: None,
+            offset_unit: None,
+        };
+        let err = request.validate(&PaginationConfig::default()).unwrap_err();
+        assert!(err.contains("selection_start (20)"));
+    }
+
+    #[test]
+    fn test_report_path_stays_inside_the_project() {
+        let report = Some(ReportFormat::Csv);
+        assert!(report_path(report, Some("reports/comments.csv")).is_ok());
+        assert!(report_path(report, None).is_ok());
+        assert!(report_path(None, Some("comments.csv")).is_err());
+        for path in [
+            "/etc/passwd",
+            "../comments.csv",
+            "reports/../../x.csv",
+            "  ",
+        ] {
+            assert!(report_path(report, Some(path)).is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_review_path_stays_inside_the_project() {
+        let import = |path: &str| DocArtifactImportReviewRequest {
+            run_id: RUN_ID.to_string(),
+            review_path: path.to_string(),
+            artifact_id: None,
+            author: None,
+            format: None,
+        };
+        let pagination = PaginationConfig::default();
+        assert!(import("reviews/pr-12.json").validate(&pagination).is_ok());
+        for path in ["/etc/passwd", "../x", "reviews/../../x.json"] {
+            assert!(import(path).validate(&pagination).is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_export_dir_stays_inside_the_project() {
+        let export = |dir: &str| ExportRunRequest {
+            run_id: RUN_ID.to_string(),
+            output_dir: Some(dir.to_string()),
+            zip: None,
+            format: None,
+        };
+        let pagination = PaginationConfig::default();
+        assert!(export("exports/run").validate(&pagination).is_ok());
+        for dir in ["/tmp/export", "../export", "exports/../../x"] {
+            assert!(export(dir).validate(&pagination).is_err(), "{}", dir);
+        }
+    }
+
+    #[test]
+    fn test_events_file_stays_inside_the_project() {
+        let dump = |file: &str| DumpEventsRequest {
+            run_id: RUN_ID.to_string(),
+            output_file: Some(file.to_string()),
+            format: None,
+        };
+        let pagination = PaginationConfig::default();
+        assert!(dump("logs/events.jsonl").validate(&pagination).is_ok());
+        for file in ["/tmp/events.jsonl", "../events.jsonl", "logs/../../x.jsonl"] {
+            assert!(dump(file).validate(&pagination).is_err(), "{}", file);
+        }
+    }
+
+    #[test]
+    fn test_handoff_task_ids_must_be_unique() {
+        let task = |id: &str| HandoffTask {
+            id: id.to_string(),
+            title: "Add the cache layer".to_string(),
+            acceptance_criteria: vec![],
+            artifacts: vec![],
+        };
+        let request = |tasks| HandoffRequest {
+            run_id: RUN_ID.to_string(),
+            to: "builder".to_string(),
+            summary: "Spec is ready".to_string(),
+            source: None,
+            details: None,
+            artifacts: None,
+            tasks: Some(tasks),
+        };
+        let pagination = PaginationConfig::default();
+        assert!(request(vec![task("T1"), task("T2")])
+            .validate(&pagination)
+            .is_ok());
+        assert!(request(vec![task("T1"), task("T1")])
+            .validate(&pagination)
+            .unwrap_err()
+            .contains("'T1' more than once"));
+        assert!(request(vec![task(" ")]).validate(&pagination).is_err());
+    }
+}
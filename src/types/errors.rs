@@ -16,4 +16,31 @@ pub enum IpcError {
 
     #[error("Timeout waiting for response")]
     Timeout,
+
+    #[error("Refusing to use backend socket: {0}")]
+    InsecureSocket(String),
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {0}")]
+    Read(String),
+
+    #[error("Invalid config file: {0}")]
+    Parse(String),
+}
+
+#[derive(Error, Debug)]
+pub enum MirrorError {
+    #[error(transparent)]
+    Ipc(#[from] IpcError),
+
+    #[error("Mirror directory: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid mirror manifest: {0}")]
+    Manifest(#[from] serde_json::Error),
+
+    #[error("Local changes to {files} not pushed: {reason}")]
+    NotPushed { files: String, reason: String },
 }
@@ -1 +1,2 @@
 pub mod errors;
+pub mod run_state;
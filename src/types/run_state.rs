@@ -0,0 +1,161 @@
+//! The run lifecycle as a state machine.
+//!
+//! A run moves from `pending` through `active` (and possibly `paused`) to one
+//! of the terminal statuses. Phases are named by the playbook, so they stay
+//! free-form and travel alongside the status in [`RunState`]. Statuses this
+//! crate doesn't know yet are kept as [`RunStatus::Other`] and never refused.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Lifecycle status of a run, as reported by `get_run_status`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum RunStatus {
+    Pending,
+    Active,
+    Paused,
+    Completed,
+    Ended,
+    Cancelled,
+    Failed,
+    /// A status newer than this crate, kept as sent
+    Other(String),
+}
+
+impl RunStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RunStatus::Pending => "pending",
+            RunStatus::Active => "active",
+            RunStatus::Paused => "paused",
+            RunStatus::Completed => "completed",
+            RunStatus::Ended => "ended",
+            RunStatus::Cancelled => "cancelled",
+            RunStatus::Failed => "failed",
+            RunStatus::Other(status) => status,
+        }
+    }
+
+    /// Whether the run is over; mutating tools are refused from here on.
+    pub fn is_ended(&self) -> bool {
+        matches!(
+            self,
+            RunStatus::Completed | RunStatus::Ended | RunStatus::Cancelled | RunStatus::Failed
+        )
+    }
+
+    /// The statuses a run can move to from this one. Empty for ended runs and
+    /// for unknown statuses.
+    pub fn next(&self) -> &'static [RunStatus] {
+        use RunStatus::*;
+        match self {
+            Pending => &[Active, Cancelled, Failed],
+            Active => &[Paused, Completed, Ended, Cancelled, Failed],
+            Paused => &[Active, Ended, Cancelled, Failed],
+            Completed | Ended | Cancelled | Failed | Other(_) => &[],
+        }
+    }
+}
+
+impl From<&str> for RunStatus {
+    fn from(status: &str) -> Self {
+        match status.to_ascii_lowercase().as_str() {
+            "pending" => RunStatus::Pending,
+            "active" => RunStatus::Active,
+            "paused" => RunStatus::Paused,
+            "completed" => RunStatus::Completed,
+            "ended" => RunStatus::Ended,
+            "cancelled" => RunStatus::Cancelled,
+            "failed" => RunStatus::Failed,
+            _ => RunStatus::Other(status.to_string()),
+        }
+    }
+}
+
+impl From<String> for RunStatus {
+    fn from(status: String) -> Self {
+        RunStatus::from(status.as_str())
+    }
+}
+
+impl From<RunStatus> for String {
+    fn from(status: RunStatus) -> Self {
+        status.as_str().to_string()
+    }
+}
+
+impl fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where a run is: its lifecycle status and the playbook phase it is in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunState {
+    pub status: RunStatus,
+    pub phase: String,
+}
+
+impl RunState {
+    /// Why a mutating tool can't be used in this state, if it can't.
+    pub fn refusal(&self, run_id: &str) -> Option<String> {
+        self.status.is_ended().then(|| {
+            format!(
+                "Run {} has ended (status: {}) in phase {}. This tool can only be used in an active run.",
+                run_id, self.status, self.phase
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ended_statuses() {
+        assert!(RunStatus::from("completed").is_ended());
+        assert!(RunStatus::from("Cancelled").is_ended());
+        assert!(!RunStatus::from("active").is_ended());
+        assert!(!RunStatus::from("paused").is_ended());
+        assert!(!RunStatus::from("archiving").is_ended());
+    }
+
+    #[test]
+    fn test_transitions() {
+        assert!(RunStatus::Pending.next().contains(&RunStatus::Active));
+        assert!(RunStatus::Active.next().contains(&RunStatus::Paused));
+        assert!(RunStatus::Paused.next().contains(&RunStatus::Active));
+        assert!(!RunStatus::Pending.next().contains(&RunStatus::Completed));
+        assert!(RunStatus::from("archiving").next().is_empty());
+        for ended in [RunStatus::Completed, RunStatus::Cancelled] {
+            assert!(ended.next().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_status_round_trips_as_a_string() {
+        let status: RunStatus = serde_json::from_str("\"Paused\"").unwrap();
+        assert_eq!(status, RunStatus::Paused);
+        assert_eq!(serde_json::to_string(&status).unwrap(), "\"paused\"");
+        let other: RunStatus = serde_json::from_str("\"archiving\"").unwrap();
+        assert_eq!(serde_json::to_string(&other).unwrap(), "\"archiving\"");
+    }
+
+    #[test]
+    fn test_refusal_names_status_and_phase() {
+        let state = RunState {
+            status: RunStatus::Completed,
+            phase: "review".to_string(),
+        };
+        let refusal = state.refusal("run-1").unwrap();
+        assert!(refusal.contains("has ended (status: completed) in phase review"));
+        let active = RunState {
+            status: RunStatus::Active,
+            ..state
+        };
+        assert_eq!(active.refusal("run-1"), None);
+    }
+}
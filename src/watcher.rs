@@ -0,0 +1,230 @@
+//! Warnings about documents edited by someone else after the agent read them.
+//!
+//! Edits carry the content hash of the version they were based on, so an
+//! agent working from a stale read only learns of it when its edit is
+//! rejected. The watcher polls the attached run's artifact list and compares
+//! it with the hashes of the documents the agent last read or wrote; when one
+//! changed through another party's edit, the agent is sent an MCP log
+//! notification telling it to re-read before editing.
+
+use crate::ipc::traits::IpcClient;
+use crate::state::StateStore;
+use crate::tools::artifacts;
+use crate::types::errors::IpcError;
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use rmcp::service::Peer;
+use rmcp::RoleServer;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// `logger` of the notifications sent.
+const LOGGER: &str = "hotwired.artifacts";
+
+/// A document that changed since the agent read it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactChange {
+    pub artifact_id: String,
+    pub filename: String,
+    /// Hash of the version the agent read or wrote
+    pub read_hash: String,
+    pub current_hash: String,
+    /// Who made the latest edit, if the edit history could be fetched
+    pub edited_by: Option<String>,
+}
+
+/// Tracks which changes were already reported.
+#[derive(Default)]
+pub struct Watcher {
+    /// artifact_id -> current hash last reported
+    reported: HashMap<String, String>,
+}
+
+impl Watcher {
+    /// Documents in `state`'s run whose content hash differs from the one last
+    /// read, changed by someone other than the agent's own role and not
+    /// reported before.
+    pub async fn check<C: IpcClient>(
+        &mut self,
+        client: &C,
+        state: &StateStore,
+    ) -> Result<Vec<ArtifactChange>, IpcError> {
+        let context = state.context();
+        let Some(run_id) = context.run_id.as_deref() else {
+            return Ok(Vec::new());
+        };
+        if context.last_read_hashes.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.reported
+            .retain(|id, _| context.last_read_hashes.contains_key(id));
+
+        let listed = artifacts::list_artifacts(client, run_id).await?;
+        let mut changes = Vec::new();
+        for artifact in listed.artifacts {
+            let Some(read_hash) = context.last_read_hashes.get(&artifact.id) else {
+                continue;
+            };
+            if *read_hash == artifact.content_hash
+                || self.reported.get(&artifact.id) == Some(&artifact.content_hash)
+            {
+                continue;
+            }
+            self.reported
+                .insert(artifact.id.clone(), artifact.content_hash.clone());
+            let edited_by = match artifacts::list_edits(client, run_id, &artifact.id).await {
+                Ok(history) => history.edits.last().map(|edit| edit.author.clone()),
+                Err(e) => {
+                    tracing::debug!("Failed to get edits of {}: {}", artifact.id, e);
+                    None
+                }
+            };
+            // Written by this agent some other way, e.g. through the mirror
+            if edited_by.is_some() && edited_by == context.role {
+                continue;
+            }
+            changes.push(ArtifactChange {
+                artifact_id: artifact.id,
+                filename: artifact.filename,
+                read_hash: read_hash.clone(),
+                current_hash: artifact.content_hash,
+                edited_by,
+            });
+        }
+        Ok(changes)
+    }
+}
+
+/// Message sent to the agent about `change`.
+pub fn format_change(change: &ArtifactChange) -> String {
+    format!(
+        "Document `{}` ({}) was edited{} since you last read it (hash {} -> {}). \
+        Re-read it with doc_artifact_read before editing; edits based on the old hash will be rejected.",
+        change.filename,
+        change.artifact_id,
+        change
+            .edited_by
+            .as_deref()
+            .map(|author| format!(" by {}", author))
+            .unwrap_or_default(),
+        change.read_hash,
+        change.current_hash
+    )
+}
+
+/// Check every `interval` until the task is aborted, notifying the MCP client
+/// of each change once it has initialized.
+pub fn spawn<C: IpcClient + 'static>(
+    client: Arc<C>,
+    state: Arc<StateStore>,
+    peer: Arc<OnceLock<Peer<RoleServer>>>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut watcher = Watcher::default();
+        loop {
+            ticker.tick().await;
+            let Some(peer) = peer.get() else {
+                continue;
+            };
+            let changes = match watcher.check(client.as_ref(), &state).await {
+                Ok(changes) => changes,
+                Err(e) => {
+                    tracing::debug!("Artifact watcher skipped a check: {}", e);
+                    continue;
+                }
+            };
+            for change in changes {
+                tracing::info!(
+                    "Artifact {} changed since it was read: {} -> {}",
+                    change.artifact_id,
+                    change.read_hash,
+                    change.current_hash
+                );
+                let notification = LoggingMessageNotificationParam {
+                    level: LoggingLevel::Warning,
+                    logger: Some(LOGGER.to_string()),
+                    data: format_change(&change).into(),
+                };
+                if let Err(e) = peer.notify_logging_message(notification).await {
+                    tracing::debug!("Failed to send artifact change notification: {}", e);
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::messages::{
+        ArtifactSummary, DocArtifactListEditsResponse, DocArtifactListResponse, EditRecord,
+    };
+    use crate::ipc::mock::MockIpcClient;
+
+    const RUN: &str = "123e4567-e89b-12d3-a456-426614174000";
+
+    fn listing(mock: &MockIpcClient, hash: &str, author: &str) {
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts", RUN),
+            DocArtifactListResponse {
+                run_id: RUN.to_string(),
+                artifacts: vec![ArtifactSummary {
+                    id: "spec".to_string(),
+                    filename: "spec.md".to_string(),
+                    document_type: "spec".to_string(),
+                    total_lines: 10,
+                    content_hash: hash.to_string(),
+                    created_at: "2024-01-15T10:00:00Z".to_string(),
+                    updated_at: "2024-01-15T10:05:00Z".to_string(),
+                    tags: vec![],
+                }],
+            },
+        );
+        mock.when_called(
+            &format!("/api/runs/{}/artifacts/spec/edits/list", RUN),
+            DocArtifactListEditsResponse {
+                artifact_id: "spec".to_string(),
+                edits: vec![EditRecord {
+                    id: "e1".to_string(),
+                    author: author.to_string(),
+                    created_at: "2024-01-15T10:05:00Z".to_string(),
+                    edit_reason: None,
+                }],
+            },
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_reports_each_change_once() {
+        let mock = MockIpcClient::new();
+        let state = StateStore::default();
+        let mut watcher = Watcher::default();
+
+        // Nothing read yet: no requests
+        assert!(watcher.check(&mock, &state).await.unwrap().is_empty());
+        mock.assert_no_calls();
+
+        state.update(|c| {
+            c.attach(RUN, "builder");
+            c.record_hash(RUN, "spec", "h1");
+        });
+        listing(&mock, "h1", "strategist");
+        assert!(watcher.check(&mock, &state).await.unwrap().is_empty());
+
+        listing(&mock, "h2", "strategist");
+        let changes = watcher.check(&mock, &state).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].edited_by.as_deref(), Some("strategist"));
+        assert!(format_change(&changes[0])
+            .starts_with("Document `spec.md` (spec) was edited by strategist since you last read it (hash h1 -> h2)."));
+        assert!(watcher.check(&mock, &state).await.unwrap().is_empty());
+
+        // The agent's own edits are not reported
+        listing(&mock, "h3", "builder");
+        assert!(watcher.check(&mock, &state).await.unwrap().is_empty());
+    }
+}
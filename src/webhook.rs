@@ -0,0 +1,253 @@
+//! Forwarding of selected run events to user-configured webhooks.
+//!
+//! When an agent reports an impediment, asks to end the run, hands off, etc.
+//! through this server, the event is also POSTed as JSON to every webhook
+//! subscribed to its type, so teams get Slack or CI notifications without
+//! backend changes. Posting goes through the system `curl` in the background
+//! and never fails or delays the tool call.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+/// Seconds allowed for one webhook POST.
+const TIMEOUT_SECS: u32 = 10;
+
+/// Run events that can be forwarded.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Impediment,
+    EndRunRequest,
+    Handoff,
+    InputRequest,
+    ApprovalRequest,
+    TaskComplete,
+}
+
+/// One webhook from the config file's `webhooks` list.
+#[derive(Deserialize, Debug, Clone)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Event types sent to this URL; empty sends all of them.
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    /// Extra request headers, e.g. `Authorization`.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+impl WebhookConfig {
+    fn wants(&self, event: WebhookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// Body POSTed to a webhook.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub run_id: String,
+    /// Agent role that produced the event
+    pub source: String,
+    /// One-line summary; Slack incoming webhooks display this field as the message
+    pub text: String,
+    /// The event's fields as sent to the backend
+    pub details: serde_json::Value,
+    pub timestamp: String,
+}
+
+/// The configured webhooks.
+#[derive(Debug, Default)]
+pub struct Webhooks {
+    hooks: Vec<WebhookConfig>,
+}
+
+impl Webhooks {
+    pub fn new(hooks: Vec<WebhookConfig>) -> Self {
+        Self { hooks }
+    }
+
+    /// POST the event to every webhook subscribed to `event`, in the background.
+    /// Failures are logged.
+    pub fn notify(
+        &self,
+        event: WebhookEvent,
+        run_id: &str,
+        source: &str,
+        text: String,
+        details: serde_json::Value,
+    ) {
+        let hooks: Vec<WebhookConfig> = self
+            .hooks
+            .iter()
+            .filter(|hook| hook.wants(event))
+            .cloned()
+            .collect();
+        if hooks.is_empty() {
+            return;
+        }
+        let payload = WebhookPayload {
+            event,
+            run_id: run_id.to_string(),
+            source: source.to_string(),
+            text,
+            details,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::warn!("Failed to serialize webhook payload: {}", e);
+                return;
+            }
+        };
+        for hook in hooks {
+            let body = body.clone();
+            tokio::spawn(async move {
+                if let Err(e) = post(&hook, &body).await {
+                    tracing::warn!("Webhook {} failed: {}", hook.url, e);
+                }
+            });
+        }
+    }
+}
+
+/// POST `body` to `hook` with curl. The request is passed as a curl config on
+/// stdin, so header values such as tokens do not show up in the process list.
+pub async fn post(hook: &WebhookConfig, body: &str) -> Result<(), String> {
+    let mut child = tokio::process::Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to run curl: {}", e))?;
+    let mut stdin = child.stdin.take().ok_or("curl has no stdin")?;
+    stdin
+        .write_all(curl_config(hook, body).as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+    drop(stdin);
+    let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// curl config file for POSTing `body` to `hook`.
+fn curl_config(hook: &WebhookConfig, body: &str) -> String {
+    let mut config = format!(
+        "url = {}\nrequest = \"POST\"\nmax-time = {}\nheader = \"Content-Type: application/json\"\n",
+        quote(&hook.url),
+        TIMEOUT_SECS
+    );
+    for (name, value) in &hook.headers {
+        config.push_str(&format!(
+            "header = {}\n",
+            quote(&format!("{}: {}", name, value))
+        ));
+    }
+    config.push_str(&format!("data-binary = {}\n", quote(body)));
+    config
+}
+
+/// A double-quoted curl config value.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    fn hook(url: &str, events: Vec<WebhookEvent>) -> WebhookConfig {
+        WebhookConfig {
+            url: url.to_string(),
+            events,
+            headers: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let all = hook("https://example.com/all", vec![]);
+        let handoffs = hook("https://example.com/h", vec![WebhookEvent::Handoff]);
+        assert!(all.wants(WebhookEvent::Impediment));
+        assert!(handoffs.wants(WebhookEvent::Handoff));
+        assert!(!handoffs.wants(WebhookEvent::EndRunRequest));
+
+        let parsed: WebhookConfig = serde_json::from_str(
+            r#"{"url": "https://example.com", "events": ["impediment", "end_run_request"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.events,
+            vec![WebhookEvent::Impediment, WebhookEvent::EndRunRequest]
+        );
+    }
+
+    #[test]
+    fn test_curl_config_quotes_values() {
+        let mut hook = hook("https://example.com/hook", vec![]);
+        hook.headers
+            .insert("Authorization".to_string(), "Bearer t\"k".to_string());
+        let config = curl_config(&hook, r#"{"text":"a \"b\"\nc"}"#);
+        assert!(config.contains("url = \"https://example.com/hook\"\n"));
+        assert!(config.contains("header = \"Authorization: Bearer t\\\"k\"\n"));
+        assert!(config.contains(r#"data-binary = "{\"text\":\"a \\\"b\\\"\\nc\"}""#));
+    }
+
+    #[tokio::test]
+    async fn test_post_sends_json_body() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // Read until the body (the last thing sent) has arrived
+            while !String::from_utf8_lossy(&request).ends_with('}') {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let mut hook = hook(&url, vec![]);
+        hook.headers
+            .insert("X-Token".to_string(), "secret".to_string());
+        post(&hook, r#"{"text":"Handoff to builder"}"#)
+            .await
+            .unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST /hook HTTP/1.1"));
+        assert!(request.contains("Content-Type: application/json"));
+        assert!(request.contains("X-Token: secret"));
+        assert!(request.ends_with(r#"{"text":"Handoff to builder"}"#));
+    }
+}
@@ -0,0 +1,324 @@
+use crate::Contract;
+use hotwired_mcp::ipc::messages::*;
+use hotwired_mcp::tools::{artifacts, compact};
+use serde_json::json;
+
+const RUN_ID: &str = "7c9e6679-7425-40de-944b-e07fc1f90ae7";
+const ARTIFACT_ID: &str = "4f1c2b9e-8d3a-4e57-9b1f-6a0c2d7e5f31";
+
+#[tokio::test]
+async fn doc_artifact_list() {
+    let contract = Contract::load("doc_artifact_list", 0);
+    let args: DocArtifactListRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::list_artifacts(&client, &args.run_id)
+        .await
+        .unwrap();
+    client.assert_all_replayed();
+    assert!(artifacts::format_list_response(&response).contains("spec.md"));
+    assert!(compact::format_list_response(&response).contains("spec.md"));
+}
+
+#[tokio::test]
+async fn doc_artifact_read() {
+    let contract = Contract::load("doc_artifact_read", 0);
+    let args: DocArtifactReadRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::read_artifact(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        args.offset,
+        args.limit,
+        args.include_comments,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    let output = artifacts::format_read_response_numbered(&response);
+    assert!(output.contains("“parsed” documents 🚀"));
+    assert!(output.contains("Which cache?"));
+}
+
+#[tokio::test]
+async fn doc_artifact_read_budgeted() {
+    let page = |offset: i64| json!({"runId": RUN_ID, "artifactId": ARTIFACT_ID, "offset": offset, "limit": 2});
+    let contract = Contract::load("doc_artifact_read", 2)
+        .sending(page(0))
+        .then("doc_artifact_read", 3)
+        .sending(page(2));
+    let args: DocArtifactReadRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::read_within_budget(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        args.offset,
+        args.limit.unwrap(),
+        args.include_comments,
+        usize::MAX,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert_eq!(response.returned_lines, 3);
+    assert!(!response.has_more);
+    assert!(response.content.ends_with("documents 🚀."));
+}
+
+#[tokio::test]
+async fn doc_artifact_create() {
+    let contract = Contract::load("doc_artifact_create", 0);
+    let args: DocArtifactCreateRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::create_artifact(
+        &client,
+        &args.run_id,
+        &args.filename,
+        args.initial_content.as_deref(),
+        args.document_type.as_deref(),
+        args.created_by.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert_eq!(response.filename, "notes.md");
+}
+
+async fn replay_edit(response: usize) -> DocArtifactEditResponse {
+    let contract = Contract::load("doc_artifact_edit", response);
+    let args: DocArtifactEditRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::edit_artifact(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        args.edit_type,
+        &args.content_hash,
+        &args.new_content,
+        args.start_offset,
+        args.end_offset,
+        args.insert_offset,
+        args.edit_reason.as_ref(),
+        args.source.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    response
+}
+
+#[tokio::test]
+async fn doc_artifact_edit() {
+    let response = replay_edit(0).await;
+    let output = artifacts::format_edit_response(&response);
+    assert!(output.contains("edit_77"));
+    assert!(output.contains("cmt_1"));
+}
+
+#[tokio::test]
+async fn doc_artifact_edit_conflict() {
+    let response = replay_edit(1).await;
+    assert!(artifacts::format_edit_response(&response).contains("CONFLICT DETECTED"));
+}
+
+#[tokio::test]
+async fn doc_artifact_search() {
+    let contract = Contract::load("doc_artifact_search", 0);
+    let args: DocArtifactSearchRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::search_artifact(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        &args.query,
+        args.match_type.as_deref(),
+        args.scope.as_deref(),
+        args.context_lines,
+        args.max_results,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert!(artifacts::format_search_response(&response).contains("chars 12-17"));
+}
+
+#[tokio::test]
+async fn doc_artifact_add_comment() {
+    let contract = Contract::load("doc_artifact_add_comment", 0);
+    let args: DocArtifactAddCommentRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::add_comment(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        args.comment_type,
+        args.selection_start,
+        args.selection_end,
+        &args.content,
+        args.suggested_text.as_deref(),
+        args.author.as_deref().unwrap(),
+        args.parent_comment_id.as_deref(),
+        args.mentions,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert_eq!(response.comment_id, "cmt_2");
+}
+
+#[tokio::test]
+async fn doc_artifact_resolve_comment() {
+    let contract = Contract::load("doc_artifact_resolve_comment", 0);
+    let args: DocArtifactResolveCommentRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::resolve_comment(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        &args.comment_id,
+        args.action,
+        args.response.as_deref(),
+        args.resolved_by.as_deref().unwrap(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert_eq!(response.new_status, "accepted");
+}
+
+#[tokio::test]
+async fn doc_artifact_list_comments() {
+    let contract = Contract::load("doc_artifact_list_comments", 0);
+    let args: DocArtifactListCommentsRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::list_comments(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        args.status.as_deref(),
+        args.comment_type.as_deref(),
+        args.line_start,
+        args.line_end,
+        args.mentioning.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert!(artifacts::format_comments_response(&response).contains("Which cache?"));
+}
+
+#[tokio::test]
+async fn doc_artifact_suggest_edit() {
+    let contract = Contract::load("doc_artifact_suggest_edit", 0);
+    let args: DocArtifactSuggestEditRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::suggest_edit(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        &args.comment_id,
+        args.edit_type,
+        args.start_offset,
+        args.end_offset,
+        &args.suggested_text,
+        args.rationale.as_deref(),
+        args.source.as_deref().unwrap(),
+        args.base_content_hash.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert!(artifacts::format_suggest_edit_response(&response).contains("sug_3"));
+}
+
+#[tokio::test]
+async fn doc_artifact_accept_suggestion() {
+    let contract = Contract::load("doc_artifact_accept_suggestion", 0);
+    let args: DocArtifactAcceptSuggestionRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::accept_suggestion(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        &args.suggestion_id,
+        args.source.as_deref().unwrap(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert!(artifacts::format_accept_suggestion_response(&response).contains("cmt_1"));
+}
+
+#[tokio::test]
+async fn doc_artifact_reject_suggestion() {
+    let contract = Contract::load("doc_artifact_reject_suggestion", 0);
+    let args: DocArtifactRejectSuggestionRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::reject_suggestion(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        &args.suggestion_id,
+        args.reason.as_deref(),
+        args.source.as_deref().unwrap(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert!(response.success);
+}
+
+#[tokio::test]
+async fn doc_artifact_list_suggestions() {
+    let contract = Contract::load("doc_artifact_list_suggestions", 0);
+    let args: DocArtifactListSuggestionsRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = artifacts::list_suggestions(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        args.status.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert!(artifacts::format_suggestions_response(&response).contains("sug_3"));
+}
+
+#[tokio::test]
+async fn doc_artifact_review_context() {
+    let contract = Contract::load("doc_artifact_read", 0)
+        .then("doc_artifact_list_comments", 0)
+        .sending(json!({"runId": RUN_ID, "artifactId": ARTIFACT_ID, "status": "open"}))
+        .then("doc_artifact_list_suggestions", 0);
+    let args: DocArtifactReviewContextRequest = contract.arguments();
+    let client = contract.client();
+
+    let context = artifacts::review_context(
+        &client,
+        &args.run_id,
+        &args.artifact_id,
+        args.offset,
+        args.limit,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    let output = artifacts::format_review_context(&context);
+    assert!(output.contains("Which cache?"));
+    assert!(output.contains("sug_3"));
+}
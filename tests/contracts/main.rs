@@ -0,0 +1,188 @@
+//! Contract tests: the backend payloads in `tests/fixtures/backend` replayed
+//! through the tools layer.
+//!
+//! A contract names the socket methods a tool call reaches, in order, and which
+//! of each method's recorded `responses` the backend answers with. Every
+//! request the tools layer sends must equal the recorded `params` of its
+//! method, so replaying a contract checks that the tools still send exactly
+//! those requests and can still read and format what the backend returns. The
+//! tool's arguments are the first method's `params`: for tool methods they are
+//! passed to the backend as-is (`ipc::compat` checks them against the schema).
+//! Requests a tool derives itself, like a follow-up page, name their `params`
+//! in the contract instead.
+//!
+//! To refresh a fixture, run the server with `RUST_LOG=hotwired_mcp=debug`
+//! against a real backend, call the tool, and copy `params` and the response
+//! `data` from the logged `IPC request`/`IPC response` lines.
+
+mod artifacts;
+mod protocol;
+mod status;
+mod terminal;
+
+use async_trait::async_trait;
+use hotwired_mcp::ipc::client::endpoint_to_method;
+use hotwired_mcp::ipc::IpcClient;
+use hotwired_mcp::types::errors::IpcError;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Mutex;
+
+#[derive(Deserialize)]
+struct Fixture {
+    params: Value,
+    responses: Vec<Value>,
+}
+
+struct Exchange {
+    method: String,
+    params: Value,
+    data: Value,
+}
+
+/// Socket requests a tool call is expected to make, with the backend's answers.
+pub struct Contract {
+    exchanges: Vec<Exchange>,
+}
+
+impl Contract {
+    /// A tool call sending `method`'s recorded `params`, answered with its
+    /// `response`-th recorded response.
+    pub fn load(method: &str, response: usize) -> Self {
+        Self { exchanges: vec![] }.then(method, response)
+    }
+
+    /// The tool call also sends `method`'s recorded `params`, answered with
+    /// its `response`-th recorded response.
+    pub fn then(mut self, method: &str, response: usize) -> Self {
+        let path = format!(
+            "{}/tests/fixtures/backend/{}.json",
+            env!("CARGO_MANIFEST_DIR"),
+            method
+        );
+        let json = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path, e));
+        let mut fixture: Fixture = serde_json::from_str(&json)
+            .unwrap_or_else(|e| panic!("{} is not a valid fixture: {}", path, e));
+        assert!(
+            response < fixture.responses.len(),
+            "{} records no response {}",
+            path,
+            response
+        );
+        self.exchanges.push(Exchange {
+            method: method.to_string(),
+            params: fixture.params,
+            data: fixture.responses.swap_remove(response),
+        });
+        self
+    }
+
+    /// The last request is sent with `params` rather than the recorded ones.
+    pub fn sending(mut self, params: Value) -> Self {
+        self.exchanges
+            .last_mut()
+            .expect("a contract starts with load")
+            .params = params;
+        self
+    }
+
+    /// The tool arguments: the first method's `params`, parsed as the tool's
+    /// request type.
+    pub fn arguments<T: DeserializeOwned>(&self) -> T {
+        let first = &self.exchanges[0];
+        serde_json::from_value(first.params.clone()).unwrap_or_else(|e| {
+            panic!(
+                "{}: params no longer parse as {}: {}",
+                first.method,
+                std::any::type_name::<T>(),
+                e
+            )
+        })
+    }
+
+    /// A client that answers with the recorded responses.
+    pub fn client(&self) -> ReplayClient<'_> {
+        ReplayClient {
+            contract: self,
+            replayed: Mutex::new(vec![false; self.exchanges.len()]),
+        }
+    }
+}
+
+/// Serves a contract's recorded responses, failing any request the backend
+/// did not see.
+pub struct ReplayClient<'a> {
+    contract: &'a Contract,
+    replayed: Mutex<Vec<bool>>,
+}
+
+impl ReplayClient<'_> {
+    /// Every expected exchange must have been requested.
+    pub fn assert_all_replayed(&self) {
+        let replayed = self.replayed.lock().unwrap();
+        let missing: Vec<&str> = self
+            .contract
+            .exchanges
+            .iter()
+            .zip(replayed.iter())
+            .filter(|(_, done)| !**done)
+            .map(|(exchange, _)| exchange.method.as_str())
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "recorded requests were never sent: {:?}",
+            missing
+        );
+    }
+}
+
+#[async_trait]
+impl IpcClient for ReplayClient<'_> {
+    async fn request<Req, Res>(&self, endpoint: &str, request: &Req) -> Result<Res, IpcError>
+    where
+        Req: serde::Serialize + Send + Sync,
+        Res: serde::de::DeserializeOwned,
+    {
+        let method = endpoint_to_method(endpoint);
+        let params = serde_json::to_value(request).unwrap();
+        let exchanges = &self.contract.exchanges;
+
+        // Concurrent requests may arrive in any order, so match on content
+        let exchange = {
+            let mut replayed = self.replayed.lock().unwrap();
+            let index = exchanges
+                .iter()
+                .enumerate()
+                .position(|(i, e)| !replayed[i] && e.method == method && e.params == params)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "unrecorded request to {} ({}):\n{}\nrecorded:\n{}",
+                        method,
+                        endpoint,
+                        serde_json::to_string_pretty(&params).unwrap(),
+                        exchanges
+                            .iter()
+                            .map(|e| format!(
+                                "{} {}",
+                                e.method,
+                                serde_json::to_string_pretty(&e.params).unwrap()
+                            ))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    )
+                });
+            replayed[index] = true;
+            &exchanges[index]
+        };
+
+        serde_json::from_value(exchange.data.clone()).map_err(|e| {
+            IpcError::InvalidResponse(format!("Failed to deserialize response data: {}", e))
+        })
+    }
+
+    async fn health_check(&self) -> Result<(), IpcError> {
+        Ok(())
+    }
+}
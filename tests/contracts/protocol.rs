@@ -0,0 +1,88 @@
+use crate::Contract;
+use hotwired_mcp::ipc::messages::*;
+use hotwired_mcp::tools::protocol;
+
+#[tokio::test]
+async fn get_protocol() {
+    let contract = Contract::load("get_protocol", 0);
+    let client = contract.client();
+
+    // The tool's `agentRole` argument is sent as `role`
+    let response =
+        protocol::get_protocol(&client, "7c9e6679-7425-40de-944b-e07fc1f90ae7", "builder")
+            .await
+            .unwrap();
+    client.assert_all_replayed();
+    let output = protocol::format_protocol_response(&response);
+    assert!(output.contains("spec-and-build"));
+    assert!(output.contains("Implement the spec."));
+}
+
+#[tokio::test]
+async fn switch_role() {
+    let contract = Contract::load("switch_role", 0).then("get_protocol", 0);
+    let args: SwitchRoleRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = protocol::switch_role(
+        &client,
+        &args.run_id,
+        &args.new_role,
+        args.source.as_deref().unwrap(),
+        args.reason.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert!(protocol::format_protocol_response(&response).contains("## Builder"));
+}
+
+#[tokio::test]
+async fn switch_role_refused() {
+    let contract = Contract::load("switch_role", 1);
+    let args: SwitchRoleRequest = contract.arguments();
+    let client = contract.client();
+
+    let err = protocol::switch_role(
+        &client,
+        &args.run_id,
+        &args.new_role,
+        args.source.as_deref().unwrap(),
+        args.reason.as_deref(),
+    )
+    .await
+    .unwrap_err();
+    // The protocol is never fetched for a refused switch
+    client.assert_all_replayed();
+    assert!(err.to_string().contains("held by a connected agent"));
+}
+
+#[tokio::test]
+async fn get_run_status() {
+    let contract = Contract::load("get_run_status", 0);
+    let args: GetRunStatusRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = protocol::get_run_status(&client, &args.run_id)
+        .await
+        .unwrap();
+    client.assert_all_replayed();
+    let output = protocol::format_run_status_response(&response);
+    assert!(output.contains("building"));
+    assert!(output.contains("hotwired-builder"));
+}
+
+#[tokio::test]
+async fn get_agent_capabilities() {
+    let contract = Contract::load("get_agent_capabilities", 0);
+    let args: GetAgentCapabilitiesRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = protocol::get_agent_capabilities(&client, &args.run_id)
+        .await
+        .unwrap();
+    client.assert_all_replayed();
+    let output = protocol::format_agent_capabilities(&response);
+    assert!(output.contains("rust"));
+    assert!(output.contains("gemini"));
+}
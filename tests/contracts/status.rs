@@ -0,0 +1,190 @@
+use crate::Contract;
+use hotwired_mcp::ipc::messages::*;
+use hotwired_mcp::tools::status;
+
+#[tokio::test]
+async fn report_status() {
+    let contract = Contract::load("report_status", 0);
+    let args: ReportStatusRequest = contract.arguments();
+    let client = contract.client();
+
+    status::report_status(
+        &client,
+        &args.run_id,
+        args.status,
+        &args.message,
+        args.source.as_deref().unwrap(),
+        args.agent_status.as_deref(),
+        args.progress.as_ref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+}
+
+#[tokio::test]
+async fn send_message() {
+    let contract = Contract::load("send_message", 0);
+    let args: SendMessageRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = status::send_message(
+        &client,
+        &args.run_id,
+        &args.content,
+        args.source.as_deref().unwrap(),
+        &[args.target.unwrap()],
+        false,
+        args.reply_to_event_id.as_deref(),
+        args.priority,
+        args.requires_ack == Some(true),
+        args.agent_status.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert_eq!(response.event_id, "evt_01HQ3K8Z");
+}
+
+#[tokio::test]
+async fn task_complete() {
+    let contract = Contract::load("task_complete", 0);
+    let args: TaskCompleteRequest = contract.arguments();
+    let client = contract.client();
+
+    status::task_complete(
+        &client,
+        &args.run_id,
+        &args.task_description,
+        args.source.as_deref().unwrap(),
+        args.outcome.as_deref(),
+        args.agent_status.as_deref(),
+        args.results.as_ref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+}
+
+#[tokio::test]
+async fn report_impediment() {
+    let contract = Contract::load("report_impediment", 0);
+    let args: ReportImpedimentRequest = contract.arguments();
+    let client = contract.client();
+
+    status::report_impediment(
+        &client,
+        &args.run_id,
+        args.impediment_type.unwrap(),
+        &args.description,
+        args.source.as_deref().unwrap(),
+        args.context.as_deref(),
+        args.suggestion.as_deref(),
+        args.agent_status.as_deref(),
+        args.response_format,
+        args.escalate_after_seconds,
+        args.template_id.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+}
+
+#[tokio::test]
+async fn resolve_impediment_already_resolved() {
+    let contract = Contract::load("resolve_impediment", 1);
+    let args: ResolveImpedimentRequest = contract.arguments();
+    let client = contract.client();
+
+    let err = status::resolve_impediment(
+        &client,
+        &args.run_id,
+        args.impediment_id,
+        args.response,
+        args.source.as_deref().unwrap(),
+        args.rationale.as_deref(),
+    )
+    .await
+    .unwrap_err();
+    client.assert_all_replayed();
+    assert!(err.to_string().contains("already resolved"));
+}
+
+#[tokio::test]
+async fn request_input() {
+    let contract = Contract::load("request_input", 0);
+    let args: RequestInputRequest = contract.arguments();
+    let client = contract.client();
+
+    status::request_input(
+        &client,
+        &args.run_id,
+        &args.question,
+        args.source.as_deref().unwrap(),
+        args.context.as_deref(),
+        args.options,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+}
+
+#[tokio::test]
+async fn handoff() {
+    let contract = Contract::load("handoff", 0);
+    let args: HandoffRequest = contract.arguments();
+    let client = contract.client();
+
+    status::handoff(
+        &client,
+        &args.run_id,
+        &args.to,
+        &args.summary,
+        args.source.as_deref().unwrap(),
+        args.details.as_deref(),
+        args.artifacts,
+        args.tasks,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+}
+
+#[tokio::test]
+async fn request_end_run() {
+    let contract = Contract::load("request_end_run", 0);
+    let args: RequestEndRunRequest = contract.arguments();
+    let client = contract.client();
+
+    let request_id = status::request_end_run(
+        &client,
+        &args.run_id,
+        &args.reason,
+        &args.description,
+        args.source.as_deref().unwrap(),
+        args.suggested_follow_up,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert_eq!(request_id, "end_5d2a");
+}
+
+#[tokio::test]
+async fn respond_to_end_request() {
+    let contract = Contract::load("respond_input", 0);
+    let args: RespondToEndRequestRequest = contract.arguments();
+    let client = contract.client();
+
+    status::respond_to_end_request(
+        &client,
+        &args.run_id,
+        &args.request_id,
+        &args.response,
+        args.source.as_deref().unwrap(),
+        args.reason.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+}
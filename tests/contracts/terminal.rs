@@ -0,0 +1,113 @@
+use crate::Contract;
+use hotwired_mcp::ipc::messages::*;
+use hotwired_mcp::tools::terminal;
+
+#[tokio::test]
+async fn hotwire() {
+    let contract = Contract::load("hotwire", 0);
+    let args: HotwireRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = terminal::hotwire(
+        &client,
+        &args.project_path,
+        &args.zellij_session,
+        args.multiplexer,
+        args.intent.as_deref(),
+        args.suggested_playbook.as_deref(),
+        args.suggested_artifacts,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    let output = terminal::format_hotwire_response(&response);
+    assert!(output.contains("7c9e6679-7425-40de-944b-e07fc1f90ae7"));
+    assert!(output.contains("# Spec and Build"));
+}
+
+#[tokio::test]
+async fn pair() {
+    let contract = Contract::load("pair", 0);
+    let args: PairRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = terminal::pair(
+        &client,
+        &args.zellij_session,
+        args.multiplexer,
+        &args.project_path,
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    let output = terminal::format_pair_response(&response);
+    assert!(output.contains("## Builder"));
+    assert!(output.contains("Drafting the spec."));
+}
+
+#[tokio::test]
+async fn resume_run() {
+    let contract = Contract::load("resume_run", 0);
+    let args: ResumeRunRequest = contract.arguments();
+    let client = contract.client();
+
+    // The last run and role come from the session, not the agent's arguments
+    let response = terminal::resume_run(
+        &client,
+        &args.zellij_session,
+        &args.project_path,
+        Some("7c9e6679-7425-40de-944b-e07fc1f90ae7"),
+        Some("builder"),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    let output = terminal::format_resume_response(&response);
+    assert!(output.contains("Spec updated."));
+    assert!(output.contains("spec.md"));
+}
+
+#[tokio::test]
+async fn list_active_runs() {
+    let contract = Contract::load("list_active_runs", 0);
+    let args: ListActiveRunsRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = terminal::list_active_runs(
+        &client,
+        args.project_path.as_deref(),
+        args.zellij_session.as_deref(),
+    )
+    .await
+    .unwrap();
+    client.assert_all_replayed();
+    assert!(terminal::format_active_runs(&response).contains("Add a cache layer"));
+}
+
+#[tokio::test]
+async fn list_playbooks() {
+    let contract = Contract::load("list_playbooks", 0);
+    let _: ListPlaybooksRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = terminal::list_playbooks(&client).await.unwrap();
+    client.assert_all_replayed();
+    let output = terminal::format_playbooks(&response);
+    assert!(output.contains("Spec and Build"));
+    assert!(output.contains("Strategist"));
+}
+
+#[tokio::test]
+async fn get_playbook() {
+    let contract = Contract::load("get_playbook", 0);
+    let args: GetPlaybookRequest = contract.arguments();
+    let client = contract.client();
+
+    let response = terminal::get_playbook(&client, &args.playbook_id)
+        .await
+        .unwrap();
+    client.assert_all_replayed();
+    let output = terminal::format_playbook(&response);
+    assert!(output.contains("  - Writing the spec [strategist]: Draft the spec"));
+    assert!(output.contains("  - Suggested paths: docs/specs/"));
+}